@@ -0,0 +1,101 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Compares the cleaning time of a large document with and without parallel tasks.
+//
+// Run with `cargo bench --bench jobs`.
+
+#[macro_use]
+extern crate criterion;
+extern crate svgcleaner;
+
+use std::fmt::Write;
+use std::thread;
+
+use criterion::Criterion;
+
+use svgcleaner::{
+    Cleaner,
+    CleaningOptions,
+};
+
+const GROUPS: usize = 100;
+const PATHS_PER_GROUP: usize = 20;
+const SEGMENTS_PER_PATH: usize = 200;
+
+// Generates a map-like document: top-level groups with long transformed paths.
+fn gen_document() -> String {
+    // A simple LCG, so the document is the same on each run.
+    let mut seed: u64 = 1;
+    let mut rand = move || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) as f64 / (1u64 << 31) as f64 * 1000.0
+    };
+
+    let mut s = String::from("<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1000 1000'>\n");
+    for g in 0..GROUPS {
+        writeln!(s, "<g fill='#{:06x}'>", g * 1234).unwrap();
+        for _ in 0..PATHS_PER_GROUP {
+            write!(s, "<path transform='translate(10 20) scale(0.5)' d='M {:.4} {:.4}",
+                   rand(), rand()).unwrap();
+            for i in 0..SEGMENTS_PER_PATH {
+                if i % 2 == 0 {
+                    write!(s, " C {:.4} {:.4} {:.4} {:.4} {:.4} {:.4}",
+                           rand(), rand(), rand(), rand(), rand(), rand()).unwrap();
+                } else {
+                    write!(s, " L {:.4} {:.4}", rand(), rand()).unwrap();
+                }
+            }
+            s.push_str(" Z'/>\n");
+        }
+        s.push_str("</g>\n");
+    }
+    s.push_str("</svg>\n");
+    s
+}
+
+fn cleaner(jobs: usize) -> Cleaner {
+    let mut options = CleaningOptions::recommended();
+    options.apply_transform_to_paths = true;
+    options.jobs = jobs;
+    Cleaner::new().cleaning_options(options)
+}
+
+fn jobs(c: &mut Criterion) {
+    let text = gen_document();
+    let jobs = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+    // The output must not depend on the number of jobs.
+    assert_eq!(cleaner(1).clean_str(&text).unwrap(), cleaner(jobs).clean_str(&text).unwrap());
+
+    let mut group = c.benchmark_group("jobs");
+    group.sample_size(10);
+    for &n in &[1, jobs] {
+        let cleaner = cleaner(n);
+        group.bench_function(format!("{}", n), |b| b.iter(|| cleaner.clean_str(&text).unwrap()));
+
+        // Only one CPU is available.
+        if jobs == 1 {
+            break;
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, jobs);
+criterion_main!(benches);
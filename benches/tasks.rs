@@ -0,0 +1,138 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Measures the cleaning time of generated documents,
+// which are slow to process by specific tasks.
+//
+// Run with `cargo bench --bench tasks`. A single document can be selected
+// by its name, like `cargo bench --bench tasks -- paths`.
+//
+// The throughput of each task on real files can be printed
+// with `svgcleaner --bench <corpus-dir>`.
+
+#[macro_use]
+extern crate criterion;
+extern crate svgcleaner;
+
+use std::fmt::Write;
+
+use criterion::{
+    Criterion,
+    Throughput,
+};
+
+use svgcleaner::{
+    Cleaner,
+    CleaningOptions,
+};
+
+// A simple LCG, so documents are the same on each run.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) as f64 / (1u64 << 31) as f64 * 1000.0
+    }
+}
+
+// A map-like document with a few giant paths.
+fn gen_paths() -> String {
+    let mut rand = Random(1);
+    let mut s = String::from("<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1000 1000'>\n");
+    for _ in 0..4 {
+        write!(s, "<path fill='none' stroke='#000' d='M {:.4} {:.4}", rand.next(), rand.next())
+            .unwrap();
+        for i in 0..20_000 {
+            match i % 20 {
+                0 => write!(s, " Z M {:.4} {:.4}", rand.next(), rand.next()).unwrap(),
+                1 => write!(s, " C {:.4} {:.4} {:.4} {:.4} {:.4} {:.4}", rand.next(), rand.next(),
+                            rand.next(), rand.next(), rand.next(), rand.next()).unwrap(),
+                2 => write!(s, " H {:.4}", rand.next()).unwrap(),
+                _ => write!(s, " L {:.4} {:.4}", rand.next(), rand.next()).unwrap(),
+            }
+        }
+        s.push_str("'/>\n");
+    }
+    s.push_str("</svg>\n");
+    s
+}
+
+// Nested groups with transforms, which should be applied to shapes and paths.
+fn gen_transforms() -> String {
+    let mut rand = Random(2);
+    let mut s = String::from("<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 1000 1000'>\n");
+    for i in 0..500 {
+        write!(s, "<g transform='translate({:.2} {:.2}) rotate({})'>\
+                   <g transform='scale(0.5)'>",
+               rand.next(), rand.next(), i % 360).unwrap();
+        write!(s, "<rect x='{:.2}' y='{:.2}' width='10' height='20' fill='#{:06x}'/>",
+               rand.next(), rand.next(), i * 997).unwrap();
+        write!(s, "<path d='M {:.2} {:.2} L {:.2} {:.2} L {:.2} {:.2} Z' fill='red'/>",
+               rand.next(), rand.next(), rand.next(), rand.next(), rand.next(), rand.next())
+            .unwrap();
+        s.push_str("</g></g>\n");
+    }
+    s.push_str("</svg>\n");
+    s
+}
+
+// Duplicated gradients, which are referenced by many elements.
+fn gen_defs() -> String {
+    let mut s = String::from("<svg xmlns='http://www.w3.org/2000/svg' \
+                              xmlns:xlink='http://www.w3.org/1999/xlink' viewBox='0 0 1000 1000'>\n\
+                              <defs>\n");
+    for i in 0..300 {
+        writeln!(s, "<linearGradient id='lg{}'><stop offset='0' stop-color='#{:06x}'/>\
+                     <stop offset='1' stop-color='#fff'/></linearGradient>",
+                 i, (i % 10) * 4099).unwrap();
+    }
+    s.push_str("</defs>\n");
+    for i in 0..1000 {
+        writeln!(s, "<rect x='{}' y='{}' width='10' height='10' fill='url(#lg{})'/>",
+                 i % 100 * 10, i / 100 * 10, i % 300).unwrap();
+    }
+    s.push_str("</svg>\n");
+    s
+}
+
+type Generator = fn() -> String;
+
+fn tasks(c: &mut Criterion) {
+    let documents: &[(&str, Generator)] = &[
+        ("paths", gen_paths),
+        ("transforms", gen_transforms),
+        ("defs", gen_defs),
+    ];
+
+    let mut options = CleaningOptions::recommended();
+    options.apply_transform_to_paths = true;
+    let cleaner = Cleaner::new().cleaning_options(options);
+
+    let mut group = c.benchmark_group("tasks");
+    group.sample_size(10);
+    for &(name, gen) in documents {
+        let text = gen();
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_function(name, |b| b.iter(|| cleaner.clean_str(&text).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tasks);
+criterion_main!(benches);
@@ -0,0 +1,96 @@
+/*
+ * svgcleaner could help you to clean up your SVG files
+ * from unnecessary data.
+ * Copyright (C) 2012-2018 Evgeniy Reizner
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+#ifndef SVGCLEANER_H
+#define SVGCLEANER_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * @brief Cleaning options.
+ */
+typedef struct svgcleaner_options svgcleaner_options;
+
+/**
+ * @brief svgcleaner_clean() result codes.
+ */
+typedef enum svgcleaner_error {
+    SVGCLEANER_OK = 0,
+    /** The input is NULL or not a valid UTF-8. */
+    SVGCLEANER_INVALID_INPUT,
+    /** The input is not a valid SVG. */
+    SVGCLEANER_PARSING_FAILED,
+    /** The input contains an SVG feature, which can't be cleaned safely. */
+    SVGCLEANER_UNSUPPORTED_FEATURE,
+    /** Any other error, including an internal one. */
+    SVGCLEANER_CLEANING_FAILED,
+} svgcleaner_error;
+
+/**
+ * @brief Creates options with the recommended values.
+ *
+ * Must be destroyed via svgcleaner_options_destroy().
+ */
+svgcleaner_options *svgcleaner_options_new(void);
+
+/**
+ * @brief Creates options from the JSON form.
+ *
+ * Options have the same names and values as in the CLI, like {"remove-title": false}.
+ * Not specified options are set to the recommended values.
+ *
+ * Must be destroyed via svgcleaner_options_destroy().
+ *
+ * @param json A NUL-terminated UTF-8 string.
+ * @return NULL on invalid options.
+ */
+svgcleaner_options *svgcleaner_options_from_json(const char *json);
+
+/**
+ * @brief Destroys options.
+ */
+void svgcleaner_options_destroy(svgcleaner_options *options);
+
+/**
+ * @brief Cleans an SVG string.
+ *
+ * @param input A NUL-terminated UTF-8 string.
+ * @param options Cleaning options. The recommended ones are used when NULL.
+ * @param out On success, the cleaned SVG, otherwise an error message.
+ *            Must be destroyed via svgcleaner_string_destroy().
+ * @return SVGCLEANER_OK on success. Internal errors are reported
+ *         as SVGCLEANER_CLEANING_FAILED.
+ */
+svgcleaner_error svgcleaner_clean(const char *input,
+                                  const svgcleaner_options *options,
+                                  char **out);
+
+/**
+ * @brief Destroys a string returned by svgcleaner_clean().
+ */
+void svgcleaner_string_destroy(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SVGCLEANER_H */
@@ -0,0 +1,74 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Helpers for binary formats, like PNG and JPEG.
+
+/// Reads a 16-bit integer at the specified position.
+pub fn read_u16(data: &[u8], pos: usize, le: bool) -> Option<u16> {
+    let b = data.get(pos..pos.checked_add(2)?)?;
+    let b = [b[0], b[1]];
+    Some(if le { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+}
+
+/// Reads a 32-bit integer at the specified position.
+pub fn read_u32(data: &[u8], pos: usize, le: bool) -> Option<u32> {
+    let b = data.get(pos..pos.checked_add(4)?)?;
+    let b = [b[0], b[1], b[2], b[3]];
+    Some(if le { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+}
+
+/// Calculates the CRC-32 checksum, which is used by PNG.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, v) in table.iter_mut().enumerate() {
+        let mut c = i as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *v = c;
+    }
+
+    let mut crc = !0u32;
+    for &b in data {
+        crc = table[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_1() {
+        let data = [1, 2, 3, 4];
+        assert_eq!(read_u16(&data, 1, false), Some(0x0203));
+        assert_eq!(read_u16(&data, 1, true), Some(0x0302));
+        assert_eq!(read_u32(&data, 0, false), Some(0x0102_0304));
+        assert_eq!(read_u32(&data, 0, true), Some(0x0403_0201));
+        assert_eq!(read_u32(&data, 1, false), None);
+        assert_eq!(read_u16(&data, usize::MAX, false), None);
+    }
+
+    #[test]
+    fn crc32_1() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(crc32(b""), 0);
+    }
+}
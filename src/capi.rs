@@ -0,0 +1,232 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! C bindings.
+//!
+//! See `capi/svgcleaner.h` for the documentation.
+
+#![allow(unsafe_code)]
+
+use std::ffi::{
+    CStr,
+    CString,
+};
+use std::os::raw::c_char;
+use std::panic::{
+    self,
+    AssertUnwindSafe,
+};
+use std::ptr;
+
+use cleaner::Cleaner;
+use error::ErrorKind;
+
+/// Cleaning options.
+#[allow(non_camel_case_types)]
+pub struct svgcleaner_options(Cleaner);
+
+/// `svgcleaner_clean` result codes.
+#[repr(C)]
+#[allow(non_camel_case_types)]
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum svgcleaner_error {
+    SVGCLEANER_OK = 0,
+    /// The input is `NULL` or not a valid UTF-8.
+    SVGCLEANER_INVALID_INPUT,
+    /// The input is not a valid SVG.
+    SVGCLEANER_PARSING_FAILED,
+    /// The input contains an SVG feature, which can't be cleaned safely.
+    SVGCLEANER_UNSUPPORTED_FEATURE,
+    /// Any other error, including an internal one.
+    SVGCLEANER_CLEANING_FAILED,
+}
+
+/// Creates options with the recommended values.
+#[no_mangle]
+pub extern "C" fn svgcleaner_options_new() -> *mut svgcleaner_options {
+    Box::into_raw(Box::new(svgcleaner_options(Cleaner::new())))
+}
+
+/// Creates options from the JSON form.
+///
+/// Returns `NULL` on invalid options.
+///
+/// # Safety
+///
+/// `json` must be `NULL` or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn svgcleaner_options_from_json(json: *const c_char) -> *mut svgcleaner_options {
+    let json = match to_str(json) {
+        Some(json) => json,
+        None => return ptr::null_mut(),
+    };
+
+    match Cleaner::from_json(json) {
+        Ok(cleaner) => Box::into_raw(Box::new(svgcleaner_options(cleaner))),
+        Err(e) => {
+            warn!("{}.", e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Destroys options.
+///
+/// # Safety
+///
+/// `options` must be `NULL` or created by `svgcleaner_options_new`
+/// or `svgcleaner_options_from_json`.
+#[no_mangle]
+pub unsafe extern "C" fn svgcleaner_options_destroy(options: *mut svgcleaner_options) {
+    if !options.is_null() {
+        drop(Box::from_raw(options));
+    }
+}
+
+/// Cleans an SVG string.
+///
+/// `options` can be `NULL`, so the recommended options are used.
+/// On success, `out` is set to the cleaned SVG, otherwise to an error message,
+/// which must be freed via `svgcleaner_string_destroy`.
+///
+/// Panics are caught, so they never unwind into the caller.
+///
+/// # Safety
+///
+/// `input` must be `NULL` or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn svgcleaner_clean(
+    input: *const c_char,
+    options: *const svgcleaner_options,
+    out: *mut *mut c_char,
+) -> svgcleaner_error {
+    let (code, text) = match to_str(input) {
+        Some(input) => {
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                match options.as_ref() {
+                    Some(options) => options.0.clean_str(input),
+                    None => Cleaner::new().clean_str(input),
+                }
+            }));
+
+            match res {
+                Ok(Ok(text)) => (svgcleaner_error::SVGCLEANER_OK, text),
+                Ok(Err(e)) => {
+                    let code = match *e.kind() {
+                        ErrorKind::ParseError(..) => svgcleaner_error::SVGCLEANER_PARSING_FAILED,
+                        ErrorKind::UnsupportedFeature(_) => svgcleaner_error::SVGCLEANER_UNSUPPORTED_FEATURE,
+                        _ => svgcleaner_error::SVGCLEANER_CLEANING_FAILED,
+                    };
+                    (code, e.to_string())
+                }
+                Err(payload) => {
+                    let msg = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    (svgcleaner_error::SVGCLEANER_CLEANING_FAILED, format!("internal error: {}", msg))
+                }
+            }
+        }
+        None => (svgcleaner_error::SVGCLEANER_INVALID_INPUT, "invalid input".to_string()),
+    };
+
+    if !out.is_null() {
+        // The writer never produces a NUL character, but error messages can contain it.
+        let text = CString::new(text.replace('\0', "")).unwrap();
+        *out = text.into_raw();
+    }
+
+    code
+}
+
+/// Destroys a string returned by `svgcleaner_clean`.
+///
+/// # Safety
+///
+/// `s` must be `NULL` or returned by `svgcleaner_clean`.
+#[no_mangle]
+pub unsafe extern "C" fn svgcleaner_string_destroy(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(s).to_str().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use registry::{
+        Position,
+        TaskRegistry,
+    };
+
+    fn clean(input: &str, options: *const svgcleaner_options) -> (svgcleaner_error, String) {
+        let input = CString::new(input).unwrap();
+        let mut out = ptr::null_mut();
+        unsafe {
+            let code = svgcleaner_clean(input.as_ptr(), options, &mut out);
+            let text = CStr::from_ptr(out).to_str().unwrap().to_string();
+            svgcleaner_string_destroy(out);
+            (code, text)
+        }
+    }
+
+    #[test]
+    fn clean_1() {
+        let svg = "<svg xmlns='http://www.w3.org/2000/svg'><title>t</title></svg>";
+        assert_eq!(clean(svg, ptr::null()),
+                   (svgcleaner_error::SVGCLEANER_OK, "<svg xmlns=\"http://www.w3.org/2000/svg\"/>".to_string()));
+
+        let json = CString::new("{\"remove-title\": false}").unwrap();
+        unsafe {
+            let options = svgcleaner_options_from_json(json.as_ptr());
+            assert!(!options.is_null());
+            assert_eq!(clean(svg, options).1,
+                       "<svg xmlns=\"http://www.w3.org/2000/svg\"><title>t</title></svg>");
+            svgcleaner_options_destroy(options);
+        }
+    }
+
+    #[test]
+    fn clean_panic_1() {
+        let mut registry = TaskRegistry::new();
+        registry.register("panic", Position::First, |_, _| panic!("oops")).unwrap();
+        let options = svgcleaner_options(Cleaner::new().task_registry(registry));
+
+        assert_eq!(clean("<svg xmlns='http://www.w3.org/2000/svg'/>", &options),
+                   (svgcleaner_error::SVGCLEANER_CLEANING_FAILED, "internal error: oops".to_string()));
+    }
+
+    #[test]
+    fn clean_err_1() {
+        assert_eq!(clean("<svg>\n<rect x='1' =/></svg>", ptr::null()).0,
+                   svgcleaner_error::SVGCLEANER_PARSING_FAILED);
+
+        let json = CString::new("{\"indent\": 5}").unwrap();
+        unsafe {
+            assert!(svgcleaner_options_from_json(json.as_ptr()).is_null());
+        }
+    }
+}
@@ -16,48 +16,164 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
+use std::borrow::Cow;
+#[cfg(feature = "fs")]
+use std::ffi::OsString;
+#[cfg(feature = "fs")]
 use std::fs;
+use std::io;
+#[cfg(feature = "fs")]
 use std::io::{
-    self,
     Read,
     Write,
 };
+#[cfg(feature = "fs")]
+use std::path::Path;
+#[cfg(feature = "fs")]
+use std::process;
 
 use svgdom::{
-    self,
     Document,
-    ElementId,
     ParseOptions,
     WriteBuffer,
     WriteOptions,
 };
 
 use options::CleaningOptions;
-use task::*;
+pub use dynamic_css::DynamicCss;
+pub use foreign::ForeignContent;
+pub use href::{
+    resolve_style as resolve_href_style,
+    write as write_href,
+};
+pub use prolog::Prolog;
+pub use recover::fix_markup;
+pub use stream::strip as strip_stream;
+pub use task::resolve_css_variables;
+use recover::{
+    self,
+    Recovery,
+    RecoveryKind,
+};
+use registry::TaskRegistry;
+use warning::{
+    self,
+    Warning,
+};
+use report::Report;
+use task::resolve_css;
+use task::utils;
 use error;
+use json_options;
+use gzip;
+use href;
 
-pub fn load_stdin() -> Result<String, io::Error> {
-    let mut s = String::new();
+#[cfg(feature = "fs")]
+pub fn load_stdin() -> Result<Vec<u8>, io::Error> {
+    let mut data = Vec::new();
     let stdin = io::stdin();
     let mut handle = stdin.lock();
 
-    handle.read_to_string(&mut s)?;
+    handle.read_to_end(&mut data)?;
 
-    Ok(s)
+    Ok(data)
 }
 
-pub fn load_file(path: &str) -> Result<String, io::Error> {
+#[cfg(feature = "fs")]
+pub fn load_file(path: &str) -> Result<Vec<u8>, io::Error> {
     let mut file = fs::File::open(path)?;
     let length = file.metadata()?.len() as usize;
 
-    let mut s = String::with_capacity(length + 1);
-    file.read_to_string(&mut s)?;
+    let mut data = Vec::with_capacity(length + 1);
+    file.read_to_end(&mut data)?;
+
+    Ok(data)
+}
+
+pub fn is_compressed(data: &[u8]) -> bool {
+    gzip::is_gzip(data)
+}
+
+/// Converts loaded data into a string, decompressing it if needed.
+pub fn decode_data(data: &[u8]) -> Result<String, io::Error> {
+    let data = if is_compressed(data) {
+        gzip::decompress(data)?
+    } else {
+        data.to_vec()
+    };
 
-    Ok(s)
+    String::from_utf8(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
 
-pub fn parse_data(data: &str, opt: &ParseOptions) -> Result<Document, svgdom::Error> {
-    Document::from_str_with_opt(data, opt)
+pub fn compress_data(data: &[u8]) -> Vec<u8> {
+    gzip::compress(data)
+}
+
+pub fn parse_data(data: &str, opt: &ParseOptions) -> Result<Document, error::Error> {
+    parse_data_impl(data, opt, None)
+}
+
+/// Parses a document like `parse_data`, but removes attributes with invalid values
+/// instead of failing, like browsers do.
+///
+/// Removed attributes are added to `recoveries`.
+pub fn parse_data_with_recovery(
+    data: &str,
+    opt: &ParseOptions,
+    recoveries: &mut Vec<Recovery>
+) -> Result<Document, error::Error> {
+    parse_data_impl(data, opt, Some(recoveries))
+}
+
+// Each removed attribute requires a new parsing, so their number is limited.
+const MAX_INVALID_ATTRIBUTES: usize = 64;
+
+fn parse_data_impl(
+    data: &str,
+    opt: &ParseOptions,
+    recoveries: Option<&mut Vec<Recovery>>
+) -> Result<Document, error::Error> {
+    // The parser supports only 'xlink:href'.
+    let data = href::normalize(data);
+    let data = resolve_css_variables(&data, &[]);
+    let mut data = resolve_css(&data);
+    let mut res = Document::from_str_with_opt(&data, opt).map_err(error::Error::from_parse_error);
+
+    if let Some(recoveries) = recoveries {
+        for _ in 0..MAX_INVALID_ATTRIBUTES {
+            let (line, column) = match res {
+                Err(ref e) => match *e.kind() {
+                    error::ErrorKind::ParseError(line, column, _) => (line, column),
+                    _ => break,
+                },
+                Ok(_) => break,
+            };
+
+            let (new_data, name) = match recover::remove_attribute(&data, line, column) {
+                Some(v) => v,
+                None => break,
+            };
+
+            recoveries.push(Recovery { pos: None, kind: RecoveryKind::InvalidAttribute(name) });
+            data = Cow::Owned(new_data);
+            res = Document::from_str_with_opt(&data, opt).map_err(error::Error::from_parse_error);
+        }
+    }
+
+    let doc = res?;
+
+    // Scripts can select elements by classes that are not defined in CSS,
+    // so we have to preserve them.
+    if opt.skip_unresolved_classes && utils::script::has_scripts(&doc) {
+        let opt = ParseOptions {
+            skip_unresolved_classes: false,
+            .. *opt
+        };
+
+        return Document::from_str_with_opt(&data, &opt).map_err(error::Error::from_parse_error);
+    }
+
+    Ok(doc)
 }
 
 pub fn clean_doc(
@@ -65,200 +181,601 @@ pub fn clean_doc(
     options: &CleaningOptions,
     opt: &WriteOptions
 ) -> Result<(), error::Error> {
-    preclean_checks(doc)?;
+    clean_doc_impl(doc, options, opt, None)
+}
 
-    // NOTE: Order is important.
-    //       Methods should not depend on each other, but for performance reasons
-    //       they should be executed in this order.
+/// Cleans a document and collects statistics of each executed task.
+///
+/// Much slower than `clean_doc`, because the document is serialized after each task
+/// to calculate its size.
+pub fn clean_doc_with_report(
+    doc: &mut Document,
+    options: &CleaningOptions,
+    opt: &WriteOptions,
+    report: &mut Report
+) -> Result<(), error::Error> {
+    clean_doc_impl(doc, options, opt, Some(report))
+}
 
-    // Prepare our document.
-    // This methods is not optional.
-    resolve_linear_gradient_attributes(doc);
-    resolve_radial_gradient_attributes(doc);
-    resolve_stop_attributes(doc)?;
+fn clean_doc_impl(
+    doc: &mut Document,
+    options: &CleaningOptions,
+    opt: &WriteOptions,
+    report: Option<&mut Report>
+) -> Result<(), error::Error> {
+    TaskRegistry::new().run(doc, options, opt, report)
+}
 
-    resolve_inherit(doc)?;
-    fix_invalid_attributes(doc);
-    group_defs(doc);
+pub fn write_buffer(doc: &Document, opt: &WriteOptions, buf: &mut Vec<u8>) {
+    doc.write_buf_opt(opt, buf);
+}
 
-    // Round numbers before everything else.
-    round_numbers(doc, options);
+/// Formats an SVG string with an indent, so it can be compared line by line.
+///
+/// Unlike `parse_data`, keeps comments, declarations and unknown elements and attributes.
+pub fn pretty_print(text: &str, opt: &WriteOptions) -> Result<String, error::Error> {
+    use svgdom::Indent;
+
+    let doc = Document::from_str(text).map_err(error::Error::from_parse_error)?;
+
+    // Each attribute is written on a separate line for a more precise diff.
+    let opt = WriteOptions {
+        indent: Indent::Spaces(2),
+        attributes_indent: Indent::Spaces(4),
+        use_single_quote: opt.use_single_quote,
+        trim_hex_colors: opt.trim_hex_colors,
+        write_hidden_attributes: opt.write_hidden_attributes,
+        remove_leading_zero: opt.remove_leading_zero,
+        use_compact_path_notation: opt.use_compact_path_notation,
+        join_arc_to_flags: opt.join_arc_to_flags,
+        remove_duplicated_path_commands: opt.remove_duplicated_path_commands,
+        use_implicit_lineto_commands: opt.use_implicit_lineto_commands,
+        simplify_transform_matrices: opt.simplify_transform_matrices,
+        list_separator: opt.list_separator,
+        attributes_order: opt.attributes_order,
+    };
+
+    let mut buf = Vec::new();
+    write_buffer(&doc, &opt, &mut buf);
+
+    // The writer always produces a valid UTF-8.
+    Ok(String::from_utf8(buf).unwrap())
+}
 
-    // Manipulate with tree structure.
-    // Do not remove any attributes before this methods
-    // since they uses them.
+#[cfg(feature = "fs")]
+pub fn write_stdout(data: &[u8]) -> Result<(), io::Error> {
+    io::stdout().write_all(data)?;
+    Ok(())
+}
 
-    // Independent task, doesn't impact any other tasks..
-    if options.remove_title {
-        remove_element(doc, ElementId::Title);
-    }
+#[cfg(feature = "fs")]
+pub fn save_file(data: &[u8], path: &str) -> Result<(), io::Error> {
+    let mut f = fs::File::create(path)?;
+    f.write_all(data)?;
 
-    // Independent task, doesn't impact any other tasks.
-    if options.remove_desc {
-        remove_element(doc, ElementId::Desc);
-    }
+    Ok(())
+}
 
-    // Independent task, doesn't impact any other tasks.
-    if options.remove_metadata {
-        remove_element(doc, ElementId::Metadata);
+/// Replaces a file atomically.
+///
+/// The data is written to a temporary file in the same directory, which is then renamed
+/// over the original one, so the file is never left partially written.
+/// The original file is kept with the specified suffix, when set.
+#[cfg(feature = "fs")]
+pub fn replace_file(data: &[u8], path: &str, backup_suffix: Option<&str>) -> Result<(), io::Error> {
+    let path = Path::new(path);
+    let file_name = path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+
+    // Hidden and unique, so it doesn't match batch patterns and parallel jobs don't clash.
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(".{}.tmp", process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let res = write_temp_file(data, path, &tmp_path).and_then(|_| {
+        if let Some(suffix) = backup_suffix {
+            let mut backup_name = file_name.to_os_string();
+            backup_name.push(suffix);
+            fs::copy(path, path.with_file_name(backup_name))?;
+        }
+
+        fs::rename(&tmp_path, path)
+    });
+
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp_path);
     }
 
-    if options.remove_unused_defs {
-        remove_unused_defs(doc);
+    res
+}
+
+#[cfg(feature = "fs")]
+fn write_temp_file(data: &[u8], path: &Path, tmp_path: &Path) -> Result<(), io::Error> {
+    let mut f = fs::OpenOptions::new().write(true).create_new(true).open(tmp_path)?;
+    f.write_all(data)?;
+    f.sync_all()?;
+
+    // Keep permissions of the original file.
+    if let Ok(meta) = fs::metadata(path) {
+        f.set_permissions(meta.permissions())?;
     }
 
-    // Impact only 'linearGradient' and 'radialGradient'.
-    if options.remove_invalid_stops {
-        remove_invalid_stops(doc);
+    Ok(())
+}
+
+/// Cleans an SVG string using the recommended parsing and writing options.
+pub fn clean_str(text: &str, options: &CleaningOptions) -> Result<String, error::Error> {
+    Cleaner::new().cleaning_options(options.clone()).clean_str(text)
+}
+
+/// An SVG cleaner builder.
+///
+/// All options are set to the same values as in the CLI by default.
+///
+/// # Examples
+///
+/// ```ignore
+/// let cleaner = svgcleaner::Cleaner::new().multipass(true);
+/// let svg = cleaner.clean_str("<svg xmlns='http://www.w3.org/2000/svg'/>")?;
+/// ```
+pub struct Cleaner {
+    parse_options: ParseOptions,
+    write_options: WriteOptions,
+    cleaning_options: CleaningOptions,
+    task_registry: TaskRegistry,
+    multipass: bool,
+    strict: bool,
+}
+
+impl Cleaner {
+    /// Creates a new `Cleaner` with the recommended options.
+    pub fn new() -> Cleaner {
+        Cleaner {
+            parse_options: Cleaner::recommended_parse_options(),
+            write_options: Cleaner::recommended_write_options(),
+            cleaning_options: CleaningOptions::recommended(),
+            task_registry: TaskRegistry::new(),
+            multipass: false,
+            strict: false,
+        }
     }
 
-    if options.apply_transform_to_gradients {
-        // Apply transform to gradients before processing to simplify duplicates
-        // detecting and merging.
-        apply_transforms::apply_transform_to_gradients(doc);
+    /// Creates a new `Cleaner` from options in the JSON form.
+    ///
+    /// Options have the same names and values as in the CLI, like `{"remove-title": false}`.
+    /// Not specified options are set to the recommended values.
+    pub fn from_json(text: &str) -> Result<Cleaner, error::Error> {
+        let opt = json_options::parse(text).map_err(error::ErrorKind::InvalidOptions)?;
+        Ok(Cleaner::new()
+            .parse_options(opt.parse)
+            .write_options(opt.write)
+            .cleaning_options(opt.cleaning)
+            .multipass(opt.multipass)
+            .strict(opt.strict))
     }
 
-    if options.remove_dupl_linear_gradients {
-        remove_dupl_linear_gradients(doc);
+    /// Returns parsing options that are used by the CLI by default.
+    pub fn recommended_parse_options() -> ParseOptions {
+        ParseOptions {
+            parse_comments: false,
+            parse_declarations: false,
+            parse_unknown_elements: false,
+            parse_unknown_attributes: false,
+            parse_px_unit: false,
+            skip_unresolved_classes: true,
+            skip_invalid_attributes: false,
+            skip_invalid_css: false,
+            skip_paint_fallback: false,
+        }
     }
 
-    if options.remove_dupl_radial_gradients {
-        remove_dupl_radial_gradients(doc);
+    /// Returns writing options that are used by the CLI by default.
+    pub fn recommended_write_options() -> WriteOptions {
+        use svgdom::{
+            AttributesOrder,
+            Indent,
+            ListSeparator,
+        };
+
+        WriteOptions {
+            indent: Indent::None,
+            attributes_indent: Indent::None,
+            use_single_quote: false,
+            trim_hex_colors: true,
+            write_hidden_attributes: false,
+            remove_leading_zero: true,
+            use_compact_path_notation: true,
+            join_arc_to_flags: false,
+            remove_duplicated_path_commands: true,
+            use_implicit_lineto_commands: true,
+            simplify_transform_matrices: true,
+            list_separator: ListSeparator::Space,
+            attributes_order: AttributesOrder::Alphabetical,
+        }
     }
 
-    if options.remove_dupl_fe_gaussian_blur {
-        remove_dupl_fe_gaussian_blur(doc);
+    /// Sets parsing options.
+    pub fn parse_options(mut self, options: ParseOptions) -> Cleaner {
+        self.parse_options = options;
+        self
     }
 
-    if options.merge_gradients {
-        merge_gradients(doc);
+    /// Sets writing options.
+    pub fn write_options(mut self, options: WriteOptions) -> Cleaner {
+        self.write_options = options;
+        self
     }
 
-    if options.apply_transform_to_gradients {
-        // Do it again, because something may changed after gradients processing.
-        apply_transforms::apply_transform_to_gradients(doc);
+    /// Sets cleaning options.
+    pub fn cleaning_options(mut self, options: CleaningOptions) -> Cleaner {
+        self.cleaning_options = options;
+        self
     }
 
-    // Run before 'apply_transform_to_shapes' and 'process_paths',
-    // because it impact transform processing.
-    if options.ungroup_groups {
-        ungroup_groups(doc, options);
+    /// Sets a list of tasks to execute.
+    pub fn task_registry(mut self, registry: TaskRegistry) -> Cleaner {
+        self.task_registry = registry;
+        self
     }
 
-    // Run before 'convert_shapes_to_paths'.
-    if options.apply_transform_to_shapes {
-        apply_transforms::apply_transform_to_shapes(doc);
+    /// Cleans a document multiple times, until its size stops changing.
+    pub fn multipass(mut self, flag: bool) -> Cleaner {
+        self.multipass = flag;
+        self
     }
 
-    // Impact only shapes.
-    if options.convert_shapes {
-        convert_shapes_to_paths(doc);
+    /// Fails on malformed documents instead of fixing them.
+    ///
+    /// By default, malformations that browsers accept, like unclosed elements or a stray `&`,
+    /// are fixed and reported as warnings.
+    pub fn strict(mut self, flag: bool) -> Cleaner {
+        self.strict = flag;
+        self
     }
 
-    // NOTE: Run before 'remove_invisible_elements', because this method can remove all
-    //       segments from the path which makes it invisible.
-    if options.paths_to_relative {
-        // We only process path's segments if 'PathsToRelative' is enabled.
-        paths::process_paths(doc, options);
+    /// Cleans an SVG string and returns warnings, which were reported during cleaning.
+    ///
+    /// Unlike `clean_str`, warnings are not logged.
+    pub fn clean_str_with_warnings(&self, text: &str) -> Result<(String, Vec<Warning>), error::Error> {
+        let (res, warnings) = warning::collect(|| self.clean_str(text));
+        res.map(|data| (data, warnings))
     }
 
-    if options.remove_invisible_elements {
-        remove_invisible_elements(doc);
+    /// Cleans an already parsed document.
+    ///
+    /// Note that a document can't be cleaned again after this method,
+    /// because some tasks break the linking.
+    pub fn clean_doc(&self, doc: &mut Document) -> Result<(), error::Error> {
+        self.task_registry.run(doc, &self.cleaning_options, &self.write_options, None)
     }
 
-    // Impact only 'linearGradient' and 'radialGradient'.
-    if options.regroup_gradient_stops {
-        regroup_gradient_stops(doc);
+    /// Cleans an SVG string.
+    pub fn clean_str(&self, text: &str) -> Result<String, error::Error> {
+        let mut data = self.clean_once(text)?;
+
+        if self.multipass {
+            loop {
+                let new_data = self.clean_once(&data)?;
+
+                // If size is not reduced - exit from the loop.
+                //
+                // Tasks like 'ungroup_groups' and 'group_by_style' can undo each other,
+                // so we should not rely on an unchanged size only.
+                if new_data.len() >= data.len() {
+                    break;
+                }
+
+                data = new_data;
+            }
+        }
+
+        if self.cleaning_options.append_newline {
+            data.push('\n');
+        }
+
+        Ok(data)
     }
 
-    // Ungroup again.
-    if options.ungroup_groups {
-        ungroup_groups(doc, options);
+    fn clean_once(&self, text: &str) -> Result<String, error::Error> {
+        let (text, mut recoveries) = fix_markup(text);
+        if self.strict {
+            if let Some(r) = recoveries.first() {
+                return Err(r.clone().into());
+            }
+        }
+
+        let (foreign_text, foreign) = ForeignContent::extract(&text);
+        let foreign_text = resolve_css_variables(&foreign_text, &self.cleaning_options.css_variables);
+        let (css_text, dynamic_css) = DynamicCss::extract(&foreign_text);
+        let parse_options = dynamic_css.parse_options(&self.parse_options);
+        let mut doc = if self.strict {
+            parse_data(&css_text, &parse_options)?
+        } else {
+            parse_data_with_recovery(&css_text, &parse_options, &mut recoveries)?
+        };
+
+        for r in recoveries {
+            warning::add(r.into());
+        }
+
+        dynamic_css.restore_classes(&doc);
+        dynamic_css.insert(&mut doc);
+
+        // Elements referenced from the 'foreignObject' content are not visible to tasks.
+        let mut options = self.cleaning_options.clone();
+        if !options.remove_foreign_objects {
+            options.keep_ids.extend(foreign.referenced_ids());
+        }
+        options.keep_ids.extend(dynamic_css.referenced_ids());
+        options.href_style = resolve_href_style(options.href_style, &foreign_text);
+        self.task_registry.run(&mut doc, &options, &self.write_options, None)?;
+
+        let mut buf = Vec::new();
+        write_buffer(&doc, &self.write_options, &mut buf);
+        write_href(options.href_style, &mut buf);
+        foreign.write(&self.write_options, &mut buf);
+        Prolog::parse(&text).write(&self.cleaning_options, &self.write_options, &mut buf);
+
+        // The writer always produces a valid UTF-8.
+        Ok(String::from_utf8(buf).unwrap())
     }
+}
 
-    if options.resolve_use {
-        resolve_use(doc);
+impl Default for Cleaner {
+    fn default() -> Cleaner {
+        Cleaner::new()
     }
+}
 
-    // Now we can remove any unneeded attributes.
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if options.remove_default_attributes {
-        remove_default_attributes(doc);
+    #[test]
+    fn clean_str_1() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'>\n\
+                    <!-- Comment -->\n\
+                    <title>Title</title>\n\
+                    <rect width='10' height='10' fill='#ff0000'/>\n\
+                    </svg>";
+
+        assert_eq!(clean_str(text, &CleaningOptions::recommended()).unwrap(),
+                   "<svg xmlns=\"http://www.w3.org/2000/svg\"><path d=\"m0 0h10v10H0z\" fill=\"red\"/></svg>");
     }
 
-    if options.remove_text_attributes {
-        remove_text_attributes(doc);
+    #[test]
+    fn clean_str_2() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'><title>Title</title></svg>";
+
+        let mut opt = CleaningOptions::recommended();
+        opt.remove_title = false;
+        opt.append_newline = true;
+
+        let res = Cleaner::new().cleaning_options(opt).multipass(true).clean_str(text).unwrap();
+        assert_eq!(res, "<svg xmlns=\"http://www.w3.org/2000/svg\"><title>Title</title></svg>\n");
     }
 
-    if options.remove_needless_attributes {
-        remove_needless_attributes(doc);
+    #[test]
+    fn clean_str_3() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'><switch systemLanguage='en'/></svg>";
+        assert!(clean_str(text, &CleaningOptions::recommended()).is_err());
     }
 
-    if options.remove_gradient_attributes {
-        remove_gradient_attributes(doc);
+    // Cleaning changes only the notation.
+    #[test]
+    fn clean_str_4() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'>\n\
+                    <!-- Comment -->\n\
+                    <rect x='10.000' width='10px' height='10' fill='#ff0000'/>\n\
+                    </svg>";
+
+        let mut opt = CleaningOptions::recommended();
+        opt.convert_shapes = false;
+        let res = clean_str(text, &opt).unwrap();
+        assert_eq!(res, "<svg xmlns=\"http://www.w3.org/2000/svg\">\
+                         <rect fill=\"red\" height=\"10\" width=\"10\" x=\"10\"/></svg>");
+
+        let doc1 = Document::from_str(text).unwrap();
+        let doc2 = Document::from_str(&res).unwrap();
+        assert!(::compare_documents(&doc1, &doc2).is_empty());
     }
 
-    if options.remove_unused_coordinates {
-        remove_unused_coordinates(doc);
+    // The 'foreignObject' content is preserved as is.
+    #[test]
+    fn clean_str_5() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'>\
+                    <defs><linearGradient id='lg1'><stop offset='0'/><stop offset='1' stop-color='red'/></linearGradient></defs>\
+                    <foreignObject width='10' height='10'>\
+                    <div xmlns='http://www.w3.org/1999/xhtml' style='fill:url(#lg1)'>  Text</div>\
+                    </foreignObject></svg>";
+
+        assert_eq!(clean_str(text, &CleaningOptions::recommended()).unwrap(),
+                   "<svg xmlns=\"http://www.w3.org/2000/svg\"><linearGradient id=\"lg1\">\
+                    <stop offset=\"0\"/><stop offset=\"1\" stop-color=\"red\"/></linearGradient>\
+                    <foreignObject height=\"10\" width=\"10\">\
+                    <div xmlns='http://www.w3.org/1999/xhtml' style='fill:url(#lg1)'>  Text</div>\
+                    </foreignObject></svg>");
+
+        let mut opt = CleaningOptions::recommended();
+        opt.remove_foreign_objects = true;
+        assert_eq!(clean_str(text, &opt).unwrap(), "<svg xmlns=\"http://www.w3.org/2000/svg\"/>");
     }
 
-    // Ungroup again.
-    if options.ungroup_groups {
-        ungroup_groups(doc, options);
+    // Dynamic CSS rules are preserved, while static ones are resolved.
+    #[test]
+    fn clean_str_7() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'>\
+                    <style>.a{fill:red} .a:hover{fill:blue} @media print{#r1{fill:green}}</style>\
+                    <rect id='r1' class='a' width='10' height='10'/></svg>";
+
+        let mut opt = CleaningOptions::recommended();
+        opt.convert_shapes = false;
+        assert_eq!(clean_str(text, &opt).unwrap(),
+                   "<svg xmlns=\"http://www.w3.org/2000/svg\">\
+                    <style>.a:hover{fill:blue}@media print{#r1{fill:green}}</style>\
+                    <rect id=\"r1\" class=\"a\" fill=\"red\" height=\"10\" width=\"10\"/></svg>");
     }
 
-    // Run only after attributes processed, because
-    // there is no point in grouping default/unneeded attributes.
-    if options.group_by_style {
-        group_by_style(doc, opt);
+    // SVG 2 'href' is preserved in the auto mode.
+    #[test]
+    fn clean_str_6() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'>\
+                    <linearGradient id='lg1'><stop offset='0'/><stop offset='1' stop-color='red'/></linearGradient>\
+                    <linearGradient id='lg2' href='#lg1' x2='0.5'/>\
+                    <rect width='10' height='10' fill='url(#lg1)'/>\
+                    <rect width='10' height='10' fill='url(#lg2)'/></svg>";
+
+        let mut opt = CleaningOptions::recommended();
+        opt.convert_shapes = false;
+        assert_eq!(clean_str(text, &opt).unwrap(),
+                   "<svg xmlns=\"http://www.w3.org/2000/svg\"><linearGradient id=\"a\">\
+                    <stop offset=\"0\"/><stop offset=\"1\" stop-color=\"red\"/></linearGradient>\
+                    <linearGradient id=\"b\" x2=\".5\" href=\"#a\"/>\
+                    <rect fill=\"url(#a)\" height=\"10\" width=\"10\"/>\
+                    <rect fill=\"url(#b)\" height=\"10\" width=\"10\"/></svg>");
+
+        opt.href_style = ::HrefStyle::Xlink;
+        assert_eq!(clean_str(text, &opt).unwrap(),
+                   "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">\
+                    <linearGradient id=\"a\">\
+                    <stop offset=\"0\"/><stop offset=\"1\" stop-color=\"red\"/></linearGradient>\
+                    <linearGradient id=\"b\" x2=\".5\" xlink:href=\"#a\"/>\
+                    <rect fill=\"url(#a)\" height=\"10\" width=\"10\"/>\
+                    <rect fill=\"url(#b)\" height=\"10\" width=\"10\"/></svg>");
     }
 
-    // Final fixes.
-    // List of things that can't break anything.
+    static DETERMINISTIC_TEXT: &str = "\
+<svg xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <linearGradient id='lg1'><stop offset='0' stop-color='red'/><stop offset='1'/></linearGradient>
+        <linearGradient id='lg2' xlink:href='#lg1' x1='5'/>
+        <clipPath id='clip1'><rect width='50' height='50'/></clipPath>
+        <filter id='filter1'><feGaussianBlur stdDeviation='2'/></filter>
+    </defs>
+    <g fill='blue' stroke='green' stroke-width='2'>
+        <rect x='10' y='10' width='20' height='20' fill='url(#lg2)' clip-path='url(#clip1)'/>
+        <circle cx='50' cy='50' r='10' opacity='0.5' filter='url(#filter1)'/>
+        <path d='M 10 10 L 20 20 L 30 10' transform='translate(10 20) scale(2)'/>
+    </g>
+    <rect x='10' y='10' width='20' height='20' fill='url(#lg1)' class='a b'/>
+    <use xlink:href='#clip1' x='5'/>
+</svg>";
+
+    // Hash maps are seeded differently on each creation, so running the pipeline
+    // multiple times in the same process is enough to catch an order-dependent output.
+    fn check_deterministic(options: CleaningOptions, write_options: WriteOptions) {
+        let cleaner = Cleaner::new().cleaning_options(options)
+                                    .write_options(write_options)
+                                    .multipass(true);
+
+        let first = cleaner.clean_str(DETERMINISTIC_TEXT).unwrap();
+        for _ in 0..5 {
+            assert_eq!(cleaner.clean_str(DETERMINISTIC_TEXT).unwrap(), first);
+        }
+    }
 
-    // Independent task, doesn't impact any other tasks.
-    if options.remove_unreferenced_ids {
-        remove_unreferenced_ids(doc);
+    #[test]
+    fn deterministic_1() {
+        check_deterministic(CleaningOptions::recommended(), Cleaner::recommended_write_options());
     }
 
-    // Independent task, doesn't impact any other tasks.
-    if options.trim_ids {
-        trim_ids(doc);
+    #[test]
+    fn deterministic_2() {
+        use svgdom::AttributesOrder;
+
+        let mut opt = CleaningOptions::recommended();
+        opt.styles = ::StylesMode::Css;
+        opt.join_style_attributes = ::StyleJoinMode::All;
+        opt.sort_attributes = ::SortAttributesMode::Frequency;
+        opt.sort_defs = ::SortDefsMode::FirstUse;
+        opt.ungroup_defs = false;
+
+        let mut write_opt = Cleaner::recommended_write_options();
+        write_opt.attributes_order = AttributesOrder::AsIs;
+
+        check_deterministic(opt, write_opt);
     }
 
-    // Independent task, doesn't impact any other tasks.
-    if options.remove_version {
-        remove_version(doc);
+    #[test]
+    fn jobs_1() {
+        // Enough paths to be processed by multiple threads.
+        let mut text = String::from("<svg xmlns='http://www.w3.org/2000/svg'>");
+        for i in 0..200 {
+            text.push_str(&format!("<path transform='translate({0} 5)' \
+                                    d='M {0} 10 C 10 20 30 40 {0} 50 L 60 {0} L 60 {0} Z'/>", i));
+        }
+        text.push_str("</svg>");
+
+        let mut opt = CleaningOptions::recommended();
+        opt.apply_transform_to_paths = true;
+
+        let clean = |jobs| {
+            let opt = CleaningOptions { jobs, .. opt.clone() };
+            Cleaner::new().cleaning_options(opt).clean_str(&text).unwrap()
+        };
+
+        assert_eq!(clean(4), clean(1));
     }
 
-    // Run at last, because it can remove 'defs' element which is used by many algorithms.
-    if options.ungroup_defs {
-        ungroup_defs(doc);
+    // Malformations are fixed, unless the strict mode is enabled.
+    #[test]
+    fn recovery_1() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'>\n\
+                    <text x=10 y='q' fill='red' fill='blue'>AT&T&nbsp;</text>\n\
+                    <g></g></g>";
+
+        let mut opt = CleaningOptions::recommended();
+        opt.remove_unreferenced_ids = false;
+        let res = clean_str(text, &opt).unwrap();
+        assert_eq!(res, "<svg xmlns=\"http://www.w3.org/2000/svg\">\
+                         <text fill=\"red\" x=\"10\">AT&amp;T\u{a0}</text></svg>");
+
+        let e = Cleaner::new().strict(true).clean_str(text).err().unwrap();
+        assert_eq!(e.to_string(), "unclosed element 'svg' at 1:1");
     }
 
-    remove_empty_defs(doc);
-    fix_xmlns_attribute(doc, options.remove_xmlns_xlink_attribute);
+    #[test]
+    fn recovery_2() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'><rect y='q' x='1e'/></svg>";
 
-    remove_default_transform(doc);
+        let mut recoveries = Vec::new();
+        let opt = Cleaner::recommended_parse_options();
+        let doc = parse_data_with_recovery(text, &opt, &mut recoveries).unwrap();
+        assert_eq!(doc.to_string(), "<svg xmlns=\"http://www.w3.org/2000/svg\">\n    <rect/>\n</svg>\n");
+        assert_eq!(recoveries.iter().map(|r| r.to_string()).collect::<Vec<_>>(),
+                   vec!["invalid value of the 'y' attribute", "invalid value of the 'x' attribute"]);
 
-    // NOTE: Must be run at last, since it breaks the linking.
-    join_style_attributes(doc, options.join_style_attributes, opt);
+        assert!(parse_data(text, &opt).is_err());
+    }
 
-    Ok(())
-}
+    #[test]
+    fn warnings_1() {
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'>\n\
+                    <rect width='10' height='10' x='q'/>";
 
-pub fn write_buffer(doc: &Document, opt: &WriteOptions, buf: &mut Vec<u8>) {
-    doc.write_buf_opt(opt, buf);
-}
+        let (_, warnings) = Cleaner::new().clean_str_with_warnings(text).unwrap();
+        assert_eq!(warnings.iter().map(|w| (w.code.as_str(), w.to_string())).collect::<Vec<_>>(),
+                   vec![("unclosed-element", "unclosed element 'svg' at 1:1".to_string()),
+                        ("invalid-attribute", "invalid value of the 'x' attribute".to_string())]);
+    }
 
-pub fn write_stdout(data: &[u8]) -> Result<(), io::Error> {
-    io::stdout().write_all(data)?;
-    Ok(())
-}
+    #[cfg(feature = "fs")]
+    #[test]
+    fn replace_file_1() {
+        let dir = ::std::env::temp_dir().join(format!("svgcleaner-replace-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.svg");
+        let path_str = path.to_str().unwrap();
 
-pub fn save_file(data: &[u8], path: &str) -> Result<(), io::Error> {
-    let mut f = fs::File::create(path)?;
-    f.write_all(data)?;
+        save_file(b"old", path_str).unwrap();
+        replace_file(b"new", path_str, Some(".bak")).unwrap();
 
-    Ok(())
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(fs::read(dir.join("test.svg.bak")).unwrap(), b"old");
+        // Only the file and its backup are left.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -16,7 +16,10 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
+use std::fs;
 use std::ops::Index;
+use std::path::Path;
+use std::thread;
 use std::io::{
     stderr,
     Write,
@@ -25,17 +28,31 @@ use std::io::{
 use clap::{
     Arg,
     App,
-    ArgMatches,
 };
+pub use clap::ArgMatches;
 
+use log::LevelFilter;
 use svgdom::{
     ParseOptions,
     WriteOptions,
 };
 
+use config::{
+    self,
+    Value,
+};
+use svgo;
+pub use task::utils::matches_pattern;
+use task::utils;
 use {
     CleaningOptions,
+    ExternalRefsMode,
     StyleJoinMode,
+    SortAttributesMode,
+    SortDefsMode,
+    HrefStyle,
+    StylesMode,
+    TaskRegistry,
 };
 
 #[derive(Debug,Clone,Copy,PartialEq)]
@@ -50,53 +67,121 @@ pub enum OutputTo<'a> {
     File(&'a str),
 }
 
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum WarningsMode {
+    Human,
+    Json,
+    Quiet,
+}
+
 #[derive(Clone,Copy)]
 pub enum Key {
     RemoveComments,
     RemoveDeclarations,
+    RemoveProcessingInstructions,
+    RemoveDoctype,
+    ConvertCdata,
     RemoveNonsvgElements,
     RemoveUnusedDefs,
     ConvertShapes,
+    ConvertShapesToPaths,
     RemoveTitle,
     RemoveDesc,
     RemoveMetadata,
+    Metadata,
+    KeepAccessibility,
+    RemoveAnimations,
+    RemoveScripts,
+    RemoveForeignObjects,
+    Sanitize,
+    ExternalRefs,
+    ResolveSwitch,
+    SystemLanguage,
+    RemoveEditorData,
+    RemoveInkscapeData,
+    RemoveSodipodiData,
+    RemoveAiData,
+    RemoveSketchData,
+    RemoveFigmaData,
     RemoveDuplLinearGradients,
     RemoveDuplRadialGradients,
+    SimplifyFilters,
     RemoveDuplFeGaussianBlur,
+    RemoveDuplMarkers,
+    RemoveDuplPatterns,
+    OptimizeImages,
+    RemoveDuplImages,
+    ExtractImages,
     UngroupGroups,
+    MergeTransforms,
     UngroupDefs,
     GroupByStyle,
+    CollapseAttributes,
     MergeGradients,
     RegroupGradientStops,
     RemoveInvalidStops,
     RemoveInvisibleElements,
+    RemoveEmptyElements,
+    NormalizeText,
+    FlattenTspans,
+    RemoveOutsideElements,
+    RemoveNoopMasking,
+    ApplyClipPaths,
     ResolveUse,
+    OptimizeSymbols,
+    FlattenNestedSvg,
+    MergePaths,
 
     RemoveVersion,
     RemoveNonsvgAttributes,
+    RemoveConditionalAttributes,
     RemoveUnreferencedIds,
     TrimIds,
+    KeepIds,
+    RemoveUnusedClasses,
+    TrimClasses,
     RemoveTextAttributes,
     RemoveUnusedCoordinates,
     RemoveDefaultAttributes,
     RemoveXmlnsXlinkAttribute,
+    RemoveUnusedNamespaces,
     RemoveNeedlessAttributes,
     RemoveGradientAttributes,
+    MergeOpacity,
     JoinStyleAttributes,
+    Styles,
+    Define,
+    ConvertUnits,
+    Dpi,
     ApplyTransformToGradients,
     ApplyTransformToShapes,
+    ApplyAnyTransformToShapes,
+    FitViewbox,
+    ViewboxPadding,
+    TranslateToOrigin,
+    ScaleCoordinates,
     RemoveUnresolvedClasses,
 
     PathsToRelative,
     RemoveUnusedSegments,
     ConvertSegments,
+    ReorderSubpaths,
     ApplyTransformToPaths,
+    SimplifyPaths,
     TrimPaths,
     JoinArcToFlags,
     RemoveDuplCmdInPaths,
     UseImplicitCommands,
 
     TrimColors,
+    MinifyColors,
+    UseExponentNotation,
     AppendNewline,
     SimplifyTransforms,
     CoordinatesPrecision,
@@ -105,12 +190,47 @@ pub enum Key {
     PathsCoordinatesPrecision,
     ListSeparator,
     Indent,
+    SortAttributes,
+    SortDefs,
+    QuoteChar,
+    HrefStyle,
+    Pretty,
 
     NoDefaults,
     Multipass,
+    Strict,
+    Compress,
     AllowBiggerFile,
+    Verify,
+    VerifyThreshold,
     CopyOnError,
+    InPlace,
+    BackupSuffix,
     Quiet,
+    Verbose,
+    LogFormat,
+    Recursive,
+    Pattern,
+    Jobs,
+    StreamThreshold,
+    StreamOnly,
+    MaxMemory,
+    EnableTask,
+    DisableTask,
+    TaskOrder,
+    Preset,
+    Report,
+    ReportFile,
+    Warnings,
+    Deterministic,
+    VerboseStats,
+    DryRun,
+    Diff,
+    Check,
+    Bench,
+    Config,
+    WriteConfig,
+    SvgoConfig,
     Stdout,
 }
 
@@ -127,49 +247,104 @@ impl<'a> Index<Key> for KeysData<'a> {
 pub static KEYS: &'static KeysData<'static> = &KeysData(&[
     "remove-comments",
     "remove-declarations",
+    "remove-processing-instructions",
+    "remove-doctype",
+    "convert-cdata",
     "remove-nonsvg-elements",
     "remove-unused-defs",
     "convert-shapes",
+    "convert-shapes-to-paths",
     "remove-title",
     "remove-desc",
     "remove-metadata",
+    "metadata",
+    "keep-accessibility",
+    "remove-animations",
+    "remove-scripts",
+    "remove-foreign-objects",
+    "sanitize",
+    "external-refs",
+    "resolve-switch",
+    "system-language",
+    "remove-editor-data",
+    "remove-inkscape-data",
+    "remove-sodipodi-data",
+    "remove-ai-data",
+    "remove-sketch-data",
+    "remove-figma-data",
     "remove-dupl-lineargradient",
     "remove-dupl-radialgradient",
+    "simplify-filters",
     "remove-dupl-fegaussianblur",
+    "remove-dupl-markers",
+    "remove-dupl-patterns",
+    "optimize-images",
+    "remove-dupl-images",
+    "extract-images",
     "ungroup-groups",
+    "merge-transforms",
     "ungroup-defs",
     "group-by-style",
+    "collapse-attributes",
     "merge-gradients",
     "regroup-gradient-stops",
     "remove-invalid-stops",
     "remove-invisible-elements",
+    "remove-empty-elements",
+    "normalize-text",
+    "flatten-tspans",
+    "remove-outside-elements",
+    "remove-noop-masking",
+    "apply-clip-paths",
     "resolve-use",
+    "optimize-symbols",
+    "flatten-nested-svg",
+    "merge-paths",
 
     "remove-version",
     "remove-nonsvg-attributes",
+    "remove-conditional-attributes",
     "remove-unreferenced-ids",
     "trim-ids",
+    "keep-ids",
+    "remove-unused-classes",
+    "trim-classes",
     "remove-text-attributes",
     "remove-unused-coordinates",
     "remove-default-attributes",
     "remove-xmlns-xlink-attribute",
+    "remove-unused-namespaces",
     "remove-needless-attributes",
     "remove-gradient-attributes",
+    "merge-opacity",
     "join-style-attributes",
+    "styles",
+    "define",
+    "convert-units",
+    "dpi",
     "apply-transform-to-gradients",
     "apply-transform-to-shapes",
+    "apply-any-transform-to-shapes",
+    "fit-viewbox",
+    "viewbox-padding",
+    "translate-to-origin",
+    "scale-coordinates",
     "remove-unresolved-classes",
 
     "paths-to-relative",
     "remove-unused-segments",
     "convert-segments",
+    "reorder-subpaths",
     "apply-transform-to-paths",
+    "simplify-paths",
     "trim-paths",
     "join-arcto-flags",
     "remove-dupl-cmd-in-paths",
     "use-implicit-cmds",
 
     "trim-colors",
+    "minify-colors",
+    "use-exponent-notation",
     "append-newline",
     "simplify-transforms",
     "coordinates-precision",
@@ -178,12 +353,47 @@ pub static KEYS: &'static KeysData<'static> = &KeysData(&[
     "paths-coordinates-precision",
     "list-separator",
     "indent",
+    "sort-attributes",
+    "sort-defs",
+    "quote-char",
+    "href-style",
+    "pretty",
 
     "no-defaults",
     "multipass",
+    "strict",
+    "compress",
     "allow-bigger-file",
+    "verify",
+    "verify-threshold",
     "copy-on-error",
+    "in-place",
+    "backup-suffix",
     "quiet",
+    "verbose",
+    "log-format",
+    "recursive",
+    "pattern",
+    "jobs",
+    "stream-threshold",
+    "stream-only",
+    "max-memory",
+    "enable-task",
+    "disable-task",
+    "task-order",
+    "preset",
+    "report",
+    "report-file",
+    "warnings",
+    "deterministic",
+    "verbose-stats",
+    "dry-run",
+    "diff",
+    "check",
+    "bench",
+    "config",
+    "write-config",
+    "svgo-config",
     "stdout",
 ]);
 
@@ -217,13 +427,15 @@ pub fn prepare_app<'a, 'b>() -> App<'a, 'b> {
         .help(include_str!("../data/help.txt"))
         .version(env!("CARGO_PKG_VERSION"))
         .arg(Arg::with_name("in-file")
-            .required(true)
+            .required_unless(KEYS[Key::WriteConfig])
             .index(1)
-            .validator(is_svg))
+            .validator(is_svg_or_dir))
         .arg(Arg::with_name("out-file")
-            .required_unless(KEYS[Key::Stdout])
+            .required_unless_one(&[KEYS[Key::Stdout], KEYS[Key::WriteConfig],
+                                   KEYS[Key::DryRun], KEYS[Key::Diff], KEYS[Key::Bench],
+                                   KEYS[Key::InPlace]])
             .index(2)
-            .validator(is_svg))
+            .validator(is_svg_or_dir))
         .arg(Arg::with_name(KEYS[Key::Stdout])
             .short("c")
             .long(KEYS[Key::Stdout]))
@@ -231,59 +443,257 @@ pub fn prepare_app<'a, 'b>() -> App<'a, 'b> {
             .long(KEYS[Key::NoDefaults]))
         .arg(Arg::with_name(KEYS[Key::Multipass])
             .long(KEYS[Key::Multipass]))
+        .arg(Arg::with_name(KEYS[Key::Strict])
+            .long(KEYS[Key::Strict]))
+        .arg(Arg::with_name(KEYS[Key::Compress])
+            .short("z")
+            .long(KEYS[Key::Compress]))
         .arg(Arg::with_name(KEYS[Key::AllowBiggerFile])
             .long(KEYS[Key::AllowBiggerFile]))
+        .arg(Arg::with_name(KEYS[Key::Verify])
+            .long(KEYS[Key::Verify]))
+        .arg(Arg::with_name(KEYS[Key::VerifyThreshold])
+            .long(KEYS[Key::VerifyThreshold])
+            .value_name("PERCENT")
+            .validator(is_percent)
+            .default_value("0.1"))
         .arg(Arg::with_name(KEYS[Key::CopyOnError])
             .long(KEYS[Key::CopyOnError]))
+        .arg(Arg::with_name(KEYS[Key::InPlace])
+            .long(KEYS[Key::InPlace])
+            .conflicts_with_all(&["out-file", KEYS[Key::Stdout], KEYS[Key::Compress]]))
+        .arg(Arg::with_name(KEYS[Key::BackupSuffix])
+            .long(KEYS[Key::BackupSuffix])
+            .value_name("SUFFIX")
+            .requires(KEYS[Key::InPlace]))
         .arg(Arg::with_name(KEYS[Key::Quiet])
-            .long(KEYS[Key::Quiet]))
+            .short("q")
+            .long(KEYS[Key::Quiet])
+            .conflicts_with(KEYS[Key::Verbose]))
+        .arg(Arg::with_name(KEYS[Key::Verbose])
+            .short("v")
+            .long(KEYS[Key::Verbose])
+            .multiple(true))
+        .arg(Arg::with_name(KEYS[Key::LogFormat])
+            .long(KEYS[Key::LogFormat])
+            .value_name("FORMAT")
+            .possible_values(&["text", "json"])
+            .default_value("text"))
+        .arg(Arg::with_name(KEYS[Key::Recursive])
+            .short("r")
+            .long(KEYS[Key::Recursive]))
+        .arg(Arg::with_name(KEYS[Key::Pattern])
+            .long(KEYS[Key::Pattern])
+            .value_name("PATTERN")
+            .default_value("*.svg"))
+        .arg(Arg::with_name(KEYS[Key::Jobs])
+            .short("j")
+            .long(KEYS[Key::Jobs])
+            .value_name("NUM")
+            .validator(is_jobs)
+            .default_value("0"))
+        .arg(Arg::with_name(KEYS[Key::StreamThreshold])
+            .long(KEYS[Key::StreamThreshold])
+            .value_name("BYTES")
+            .validator(is_size)
+            .default_value("0"))
+        .arg(Arg::with_name(KEYS[Key::StreamOnly])
+            .long(KEYS[Key::StreamOnly]))
+        .arg(Arg::with_name(KEYS[Key::MaxMemory])
+            .long(KEYS[Key::MaxMemory])
+            .value_name("MB")
+            .validator(is_size)
+            .default_value("0"))
+        .arg(Arg::with_name(KEYS[Key::EnableTask])
+            .long(KEYS[Key::EnableTask])
+            .value_name("NAMES")
+            .validator(is_task_list))
+        .arg(Arg::with_name(KEYS[Key::DisableTask])
+            .long(KEYS[Key::DisableTask])
+            .value_name("NAMES")
+            .validator(is_task_list))
+        .arg(Arg::with_name(KEYS[Key::TaskOrder])
+            .long(KEYS[Key::TaskOrder])
+            .value_name("NAMES")
+            .validator(is_task_list))
+        .arg(Arg::with_name(KEYS[Key::Preset])
+            .long(KEYS[Key::Preset])
+            .value_name("NAME")
+            .possible_values(&["safe", "default", "aggressive"]))
+        .arg(Arg::with_name(KEYS[Key::Report])
+            .long(KEYS[Key::Report])
+            .value_name("FORMAT")
+            .possible_values(&["json"]))
+        .arg(Arg::with_name(KEYS[Key::ReportFile])
+            .long(KEYS[Key::ReportFile])
+            .value_name("FILE")
+            .requires(KEYS[Key::Report]))
+        .arg(Arg::with_name(KEYS[Key::Warnings])
+            .long(KEYS[Key::Warnings])
+            .value_name("FORMAT")
+            .possible_values(&["human", "json", "quiet"])
+            .default_value("human"))
+        .arg(gen_flag!(Key::Deterministic, "true"))
+        .arg(Arg::with_name(KEYS[Key::VerboseStats])
+            .long(KEYS[Key::VerboseStats]))
+        .arg(Arg::with_name(KEYS[Key::DryRun])
+            .long(KEYS[Key::DryRun]))
+        .arg(Arg::with_name(KEYS[Key::Diff])
+            .long(KEYS[Key::Diff]))
+        .arg(Arg::with_name(KEYS[Key::Check])
+            .long(KEYS[Key::Check])
+            .requires("out-file"))
+        // Used for performance testing, so it's not listed in the help.
+        .arg(Arg::with_name(KEYS[Key::Bench])
+            .long(KEYS[Key::Bench])
+            .hidden(true))
+        .arg(Arg::with_name(KEYS[Key::Config])
+            .long(KEYS[Key::Config])
+            .value_name("FILE"))
+        .arg(Arg::with_name(KEYS[Key::WriteConfig])
+            .long(KEYS[Key::WriteConfig])
+            .value_name("FILE"))
+        .arg(Arg::with_name(KEYS[Key::SvgoConfig])
+            .long(KEYS[Key::SvgoConfig])
+            .value_name("FILE"))
 
         // elements
         .arg(gen_flag!(Key::RemoveComments, "true"))
         .arg(gen_flag!(Key::RemoveDeclarations, "true"))
+        .arg(gen_flag!(Key::RemoveProcessingInstructions, "true"))
+        .arg(gen_flag!(Key::RemoveDoctype, "true"))
+        .arg(gen_flag!(Key::ConvertCdata, "true"))
         .arg(gen_flag!(Key::RemoveNonsvgElements, "true"))
         .arg(gen_flag!(Key::RemoveUnusedDefs, "true"))
         .arg(gen_flag!(Key::ConvertShapes, "true"))
+        .arg(gen_flag!(Key::ConvertShapesToPaths, "false"))
         .arg(gen_flag!(Key::RemoveTitle, "true"))
         .arg(gen_flag!(Key::RemoveDesc, "true"))
         .arg(gen_flag!(Key::RemoveMetadata, "true"))
+        .arg(Arg::with_name(KEYS[Key::Metadata])
+            .long(KEYS[Key::Metadata])
+            .value_name("MODE")
+            .possible_values(&["keep", "remove", "keep-license"]))
+        .arg(gen_flag!(Key::KeepAccessibility, "false"))
+        .arg(gen_flag!(Key::RemoveAnimations, "false"))
+        .arg(gen_flag!(Key::RemoveScripts, "false"))
+        .arg(gen_flag!(Key::RemoveForeignObjects, "false"))
+        .arg(gen_flag!(Key::Sanitize, "false"))
+        .arg(Arg::with_name(KEYS[Key::ExternalRefs])
+            .long(KEYS[Key::ExternalRefs])
+            .value_name("MODE")
+            .possible_values(&["keep", "remove", "embed"])
+            .default_value("keep"))
+        .arg(gen_flag!(Key::ResolveSwitch, "false"))
+        .arg(Arg::with_name(KEYS[Key::SystemLanguage])
+            .long(KEYS[Key::SystemLanguage])
+            .value_name("LANGS")
+            .default_value("en"))
+        .arg(gen_flag!(Key::RemoveEditorData, "true"))
+        .arg(gen_flag!(Key::RemoveInkscapeData, "true"))
+        .arg(gen_flag!(Key::RemoveSodipodiData, "true"))
+        .arg(gen_flag!(Key::RemoveAiData, "true"))
+        .arg(gen_flag!(Key::RemoveSketchData, "true"))
+        .arg(gen_flag!(Key::RemoveFigmaData, "true"))
         .arg(gen_flag!(Key::RemoveDuplLinearGradients, "true"))
         .arg(gen_flag!(Key::RemoveDuplRadialGradients, "true"))
+        .arg(gen_flag!(Key::SimplifyFilters, "true"))
         .arg(gen_flag!(Key::RemoveDuplFeGaussianBlur, "true"))
+        .arg(gen_flag!(Key::RemoveDuplMarkers, "true"))
+        .arg(gen_flag!(Key::RemoveDuplPatterns, "true"))
+        .arg(gen_flag!(Key::OptimizeImages, "false"))
+        .arg(gen_flag!(Key::RemoveDuplImages, "false"))
+        .arg(Arg::with_name(KEYS[Key::ExtractImages])
+            .long(KEYS[Key::ExtractImages])
+            .value_name("DIR"))
         .arg(gen_flag!(Key::UngroupGroups, "true"))
+        .arg(gen_flag!(Key::MergeTransforms, "true"))
         .arg(gen_flag!(Key::UngroupDefs, "true"))
         .arg(gen_flag!(Key::GroupByStyle, "true"))
+        .arg(gen_flag!(Key::CollapseAttributes, "true"))
         .arg(gen_flag!(Key::MergeGradients, "true"))
         .arg(gen_flag!(Key::RegroupGradientStops, "true"))
         .arg(gen_flag!(Key::RemoveInvalidStops, "true"))
         .arg(gen_flag!(Key::RemoveInvisibleElements, "true"))
+        .arg(gen_flag!(Key::RemoveEmptyElements, "true"))
+        .arg(gen_flag!(Key::NormalizeText, "true"))
+        .arg(gen_flag!(Key::FlattenTspans, "true"))
+        .arg(gen_flag!(Key::RemoveOutsideElements, "false"))
+        .arg(gen_flag!(Key::RemoveNoopMasking, "true"))
+        .arg(gen_flag!(Key::ApplyClipPaths, "true"))
         .arg(gen_flag!(Key::ResolveUse, "true"))
+        .arg(gen_flag!(Key::OptimizeSymbols, "true"))
+        .arg(gen_flag!(Key::FlattenNestedSvg, "true"))
+        .arg(gen_flag!(Key::MergePaths, "true"))
 
         // attributes
         .arg(gen_flag!(Key::RemoveVersion, "true"))
         .arg(gen_flag!(Key::RemoveNonsvgAttributes, "true"))
+        .arg(gen_flag!(Key::RemoveConditionalAttributes, "false"))
         .arg(gen_flag!(Key::RemoveUnreferencedIds, "true"))
         .arg(gen_flag!(Key::TrimIds, "true"))
+        .arg(Arg::with_name(KEYS[Key::KeepIds])
+            .long(KEYS[Key::KeepIds])
+            .value_name("IDS")
+            .validator(is_id_patterns))
+        .arg(gen_flag!(Key::RemoveUnusedClasses, "true"))
+        .arg(gen_flag!(Key::TrimClasses, "true"))
         .arg(gen_flag!(Key::RemoveTextAttributes, "true"))
         .arg(gen_flag!(Key::RemoveUnusedCoordinates, "true"))
         .arg(gen_flag!(Key::RemoveDefaultAttributes, "true"))
         .arg(gen_flag!(Key::RemoveXmlnsXlinkAttribute, "true"))
+        .arg(gen_flag!(Key::RemoveUnusedNamespaces, "true"))
         .arg(gen_flag!(Key::RemoveNeedlessAttributes, "true"))
         .arg(gen_flag!(Key::RemoveGradientAttributes, "false"))
+        .arg(gen_flag!(Key::MergeOpacity, "true"))
         .arg(Arg::with_name(KEYS[Key::JoinStyleAttributes])
             .long(KEYS[Key::JoinStyleAttributes])
             .value_name("VALUE")
             .possible_values(&["no", "some", "all"])
             .default_value("some"))
+        .arg(Arg::with_name(KEYS[Key::Styles])
+            .long(KEYS[Key::Styles])
+            .value_name("VALUE")
+            .possible_values(&["presentation", "css", "auto"])
+            .default_value("presentation"))
+        .arg(Arg::with_name(KEYS[Key::Define])
+            .long(KEYS[Key::Define])
+            .value_name("VARS")
+            .validator(is_variables))
+        .arg(gen_flag!(Key::ConvertUnits, "false"))
+        .arg(Arg::with_name(KEYS[Key::Dpi])
+            .long(KEYS[Key::Dpi])
+            .value_name("NUM")
+            .validator(is_dpi)
+            .default_value("96"))
         .arg(gen_flag!(Key::ApplyTransformToGradients, "true"))
         .arg(gen_flag!(Key::ApplyTransformToShapes, "true"))
+        .arg(gen_flag!(Key::ApplyAnyTransformToShapes, "false"))
+        .arg(gen_flag!(Key::FitViewbox, "false"))
+        .arg(Arg::with_name(KEYS[Key::ViewboxPadding])
+            .long(KEYS[Key::ViewboxPadding])
+            .value_name("NUM")
+            .validator(is_padding)
+            .default_value("0"))
+        .arg(gen_flag!(Key::TranslateToOrigin, "false"))
+        .arg(Arg::with_name(KEYS[Key::ScaleCoordinates])
+            .long(KEYS[Key::ScaleCoordinates])
+            .value_name("NUM")
+            .validator(is_scale_factor)
+            .default_value("1"))
         .arg(gen_flag!(Key::RemoveUnresolvedClasses, "true"))
 
         // paths
         .arg(gen_flag!(Key::PathsToRelative, "true"))
         .arg(gen_flag!(Key::RemoveUnusedSegments, "true"))
         .arg(gen_flag!(Key::ConvertSegments, "true"))
+        .arg(gen_flag!(Key::ReorderSubpaths, "true"))
         .arg(gen_flag!(Key::ApplyTransformToPaths, "false"))
+        .arg(Arg::with_name(KEYS[Key::SimplifyPaths])
+            .long(KEYS[Key::SimplifyPaths])
+            .value_name("NUM")
+            .validator(is_tolerance)
+            .default_value("0"))
         .arg(gen_flag!(Key::TrimPaths, "true"))
         .arg(gen_flag!(Key::JoinArcToFlags, "false"))
         .arg(gen_flag!(Key::RemoveDuplCmdInPaths, "true"))
@@ -291,6 +701,8 @@ pub fn prepare_app<'a, 'b>() -> App<'a, 'b> {
 
         // output
         .arg(gen_flag!(Key::TrimColors, "true"))
+        .arg(gen_flag!(Key::MinifyColors, "true"))
+        .arg(gen_flag!(Key::UseExponentNotation, "false"))
         .arg(gen_flag!(Key::AppendNewline, "false"))
         .arg(gen_flag!(Key::SimplifyTransforms, "true"))
         .arg(gen_precision!(Key::CoordinatesPrecision, "6"))
@@ -300,23 +712,59 @@ pub fn prepare_app<'a, 'b>() -> App<'a, 'b> {
         .arg(Arg::with_name(KEYS[Key::ListSeparator])
             .long(KEYS[Key::ListSeparator])
             .value_name("SEPARATOR")
-            .possible_values(&["space", "comma", "comma-space"])
+            .possible_values(&["space", "comma", "comma-space", "minimal"])
             .default_value("space"))
         .arg(Arg::with_name(KEYS[Key::Indent])
             .long(KEYS[Key::Indent])
             .value_name("INDENT")
             .possible_values(&["none", "0", "1", "2", "3", "4", "tabs"])
             .default_value("none"))
+        .arg(Arg::with_name(KEYS[Key::SortAttributes])
+            .long(KEYS[Key::SortAttributes])
+            .value_name("ORDER")
+            .possible_values(&["alphabetical", "render-friendly", "frequency"])
+            .default_value("alphabetical"))
+        .arg(Arg::with_name(KEYS[Key::SortDefs])
+            .long(KEYS[Key::SortDefs])
+            .value_name("ORDER")
+            .possible_values(&["no", "first-use", "type"])
+            .default_value("no"))
+        .arg(Arg::with_name(KEYS[Key::QuoteChar])
+            .long(KEYS[Key::QuoteChar])
+            .value_name("QUOTE")
+            .possible_values(&["double", "single"])
+            .default_value("double"))
+        .arg(Arg::with_name(KEYS[Key::HrefStyle])
+            .long(KEYS[Key::HrefStyle])
+            .value_name("STYLE")
+            .possible_values(&["svg2", "xlink", "auto"])
+            .default_value("auto"))
+        .arg(Arg::with_name(KEYS[Key::Pretty])
+            .long(KEYS[Key::Pretty]))
 }
 
-fn is_svg(val: String) -> Result<(), String> {
-    if val.ends_with(".svg") || val.ends_with(".SVG") || val == "-" {
+fn is_svg_or_dir(val: String) -> Result<(), String> {
+    if is_svg_name(&val) || is_svgz_name(&val) || val == "-" {
+        return Ok(());
+    }
+
+    // A directory for the batch processing. The output one may not exist yet.
+    let path = Path::new(&val);
+    if path.is_dir() || val.ends_with('/') || path.extension().is_none() {
         Ok(())
     } else {
         Err(String::from("The file format must be SVG."))
     }
 }
 
+fn is_svg_name(name: &str) -> bool {
+    name.ends_with(".svg") || name.ends_with(".SVG")
+}
+
+pub fn is_svgz_name(name: &str) -> bool {
+    name.ends_with(".svgz") || name.ends_with(".SVGZ")
+}
+
 fn is_precision(val: String) -> Result<(), String> {
     let n = match val.parse::<u8>() {
         Ok(v) => v,
@@ -330,6 +778,117 @@ fn is_precision(val: String) -> Result<(), String> {
     }
 }
 
+fn is_dpi(val: String) -> Result<(), String> {
+    let n = match val.parse::<u32>() {
+        Ok(v) => v,
+        Err(e) => return Err(format!("{}", e)),
+    };
+
+    if n == 0 || n > 1200 {
+        Err(String::from("DPI should be in range 1..1200."))
+    } else {
+        Ok(())
+    }
+}
+
+fn is_tolerance(val: String) -> Result<(), String> {
+    match val.parse::<f64>() {
+        Ok(v) if v.is_finite() && v >= 0.0 => Ok(()),
+        Ok(_) => Err(String::from("Tolerance should be a positive number.")),
+        Err(e) => Err(format!("{}", e)),
+    }
+}
+
+fn is_padding(val: String) -> Result<(), String> {
+    match val.parse::<f64>() {
+        Ok(v) if v.is_finite() && v >= 0.0 => Ok(()),
+        Ok(_) => Err(String::from("Padding should be a positive number.")),
+        Err(e) => Err(format!("{}", e)),
+    }
+}
+
+fn is_scale_factor(val: String) -> Result<(), String> {
+    match val.parse::<f64>() {
+        Ok(v) if v.is_finite() && v > 0.0 => Ok(()),
+        Ok(_) => Err(String::from("Scale factor should be a positive number.")),
+        Err(e) => Err(format!("{}", e)),
+    }
+}
+
+fn is_percent(val: String) -> Result<(), String> {
+    match val.parse::<f64>() {
+        Ok(v) if (0.0..=100.0).contains(&v) => Ok(()),
+        Ok(_) => Err(String::from("Percentage should be in range 0..100.")),
+        Err(e) => Err(format!("{}", e)),
+    }
+}
+
+fn is_jobs(val: String) -> Result<(), String> {
+    match val.parse::<u32>() {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("{}", e)),
+    }
+}
+
+fn is_size(val: String) -> Result<(), String> {
+    match val.parse::<usize>() {
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("{}", e)),
+    }
+}
+
+fn is_task_list(val: String) -> Result<(), String> {
+    let registry = TaskRegistry::new();
+    for name in task_list(&val) {
+        if !registry.contains(name) {
+            return Err(format!("Unknown task '{}'. Available tasks: {}.",
+                               name, registry.names().join(", ")));
+        }
+    }
+
+    Ok(())
+}
+
+// Splits a comma-separated list of task names or id patterns.
+fn task_list(val: &str) -> Vec<&str> {
+    val.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).collect()
+}
+
+fn is_id_patterns(val: String) -> Result<(), String> {
+    for pattern in task_list(&val) {
+        if utils::new_id_regex(pattern).is_err() {
+            return Err(format!("Invalid regular expression '{}'.", pattern));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_variables(val: String) -> Result<(), String> {
+    match variables_list(&val) {
+        Some(_) => Ok(()),
+        None => Err(String::from("Invalid variables list. Expected 'name=value;name2=value2'.")),
+    }
+}
+
+// Splits a semicolon-separated list of CSS variables, like 'main=red;size=2'.
+//
+// Values can contain commas, like 'rgb(0,0,0)', so we are using semicolons, like CSS does.
+fn variables_list(val: &str) -> Option<Vec<(String, String)>> {
+    let mut list = Vec::new();
+    for item in val.split(';').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let idx = item.find('=')?;
+        let name = item[..idx].trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        list.push((name.to_string(), item[idx + 1..].trim().to_string()));
+    }
+
+    Some(list)
+}
+
 fn is_flag(val: String) -> Result<(), String> {
     match val.as_ref() {
         "true" | "false" |
@@ -340,7 +899,11 @@ fn is_flag(val: String) -> Result<(), String> {
 }
 
 fn get_flag(args: &ArgMatches, key: Key) -> bool {
-    match args.value_of(KEYS[key]).unwrap() {
+    parse_flag(args.value_of(KEYS[key]).unwrap())
+}
+
+fn parse_flag(value: &str) -> bool {
+    match value {
         "true" | "yes" | "y" => true,
         "false" | "no" | "n" => false,
         _ => unreachable!(), // Unreachable because we already validated values at is_flag().
@@ -373,10 +936,28 @@ pub fn check_values(args: &ArgMatches) -> bool {
         return false;
     }
 
+    if !check_value(args, Key::PathsToRelative, Key::ReorderSubpaths) {
+        return false;
+    }
+
     if !check_value(args, Key::PathsToRelative, Key::ApplyTransformToPaths) {
         return false;
     }
 
+    if !check_value(args, Key::ApplyTransformToShapes, Key::ApplyAnyTransformToShapes) {
+        return false;
+    }
+
+    if let (Some(enabled), Some(disabled)) = (args.value_of(KEYS[Key::EnableTask]),
+                                              args.value_of(KEYS[Key::DisableTask])) {
+        let disabled = task_list(disabled);
+        if let Some(name) = task_list(enabled).iter().find(|n| disabled.contains(n)) {
+            writeln!(stderr(), "Error: The '{}' task can't be enabled and disabled at the same time.",
+                     name).unwrap();
+            return false;
+        }
+    }
+
     true
 }
 
@@ -445,9 +1026,40 @@ pub fn gen_parse_options(args: &ArgMatches) -> ParseOptions {
     flags.resolve_inv(&mut opt.parse_unknown_attributes, Key::RemoveNonsvgAttributes);
     flags.resolve(&mut opt.skip_unresolved_classes, Key::RemoveUnresolvedClasses);
 
+    // Non-SVG attributes will be removed by the 'remove-nonsvg-attributes' task instead.
+    let mut keep_accessibility = false;
+    flags.resolve(&mut keep_accessibility, Key::KeepAccessibility);
+    if keep_accessibility {
+        opt.parse_unknown_attributes = true;
+    }
+
     opt
 }
 
+// Makes the output readable, unless the corresponding options were set explicitly.
+fn apply_pretty(args: &ArgMatches, opt: &mut WriteOptions) {
+    use svgdom::Indent;
+
+    let is_set = |key: Key| args.occurrences_of(KEYS[key]) != 0;
+
+    if !is_set(Key::Indent) {
+        opt.indent = Indent::Spaces(2);
+    }
+
+    let flags = [
+        (&mut opt.use_compact_path_notation, Key::TrimPaths),
+        (&mut opt.remove_duplicated_path_commands, Key::RemoveDuplCmdInPaths),
+        (&mut opt.join_arc_to_flags, Key::JoinArcToFlags),
+        (&mut opt.use_implicit_lineto_commands, Key::UseImplicitCommands),
+    ];
+
+    for (value, key) in flags {
+        if !is_set(key) {
+            *value = false;
+        }
+    }
+}
+
 pub fn gen_write_options(args: &ArgMatches) -> WriteOptions {
     use svgdom::{
         Indent,
@@ -487,6 +1099,8 @@ pub fn gen_write_options(args: &ArgMatches) -> WriteOptions {
         "space"         => ListSeparator::Space,
         "comma"         => ListSeparator::Comma,
         "comma-space"   => ListSeparator::CommaSpace,
+        // Separators are removed by the 'list-separator' task, where possible.
+        "minimal"       => ListSeparator::Space,
         _ => unreachable!(), // clap will validate the input.
     };
 
@@ -501,43 +1115,163 @@ pub fn gen_write_options(args: &ArgMatches) -> WriteOptions {
         _ => unreachable!(), // clap will validate the input.
     };
 
+    opt.attributes_order = match args.value_of(KEYS[Key::SortAttributes]).unwrap() {
+        "alphabetical"      => AttributesOrder::Alphabetical,
+        "render-friendly"   => AttributesOrder::Specification,
+        // Attributes are sorted by the 'sort-attributes' task.
+        "frequency"         => AttributesOrder::AsIs,
+        _ => unreachable!(), // clap will validate the input.
+    };
+
+    opt.use_single_quote = match args.value_of(KEYS[Key::QuoteChar]).unwrap() {
+        "double"    => false,
+        "single"    => true,
+        _ => unreachable!(), // clap will validate the input.
+    };
+
+    if args.is_present(KEYS[Key::Pretty]) {
+        apply_pretty(args, &mut opt);
+    }
+
     opt
 }
 
+// '--metadata' sets the 'title', 'desc' and 'metadata' flags, which are not set explicitly.
+fn resolve_metadata(args: &ArgMatches, opt: &mut CleaningOptions) {
+    let (remove_text, remove_metadata, keep_license) = match args.value_of(KEYS[Key::Metadata]) {
+        Some("keep")            => (false, false, false),
+        Some("remove")          => (true, true, false),
+        Some("keep-license")    => (false, true, true),
+        None => return,
+        _ => unreachable!(), // clap will validate the input.
+    };
+
+    let is_set = |key: Key| args.occurrences_of(KEYS[key]) != 0;
+    if !is_set(Key::RemoveTitle) {
+        opt.remove_title = remove_text;
+    }
+    if !is_set(Key::RemoveDesc) {
+        opt.remove_desc = remove_text;
+    }
+    if !is_set(Key::RemoveMetadata) {
+        opt.remove_metadata = remove_metadata;
+    }
+    opt.keep_license = keep_license;
+}
+
+// '--remove-editor-data' sets all the editor flags, which are not set explicitly.
+fn resolve_editor_data(args: &ArgMatches, flags: &Flags, opt: &mut CleaningOptions) {
+    let editor_flags = [
+        (&mut opt.remove_inkscape_data, Key::RemoveInkscapeData),
+        (&mut opt.remove_sodipodi_data, Key::RemoveSodipodiData),
+        (&mut opt.remove_ai_data, Key::RemoveAiData),
+        (&mut opt.remove_sketch_data, Key::RemoveSketchData),
+        (&mut opt.remove_figma_data, Key::RemoveFigmaData),
+    ];
+
+    let is_all_set = args.occurrences_of(KEYS[Key::RemoveEditorData]) != 0;
+    for (value, key) in editor_flags {
+        if is_all_set && args.occurrences_of(KEYS[key]) == 0 {
+            *value = get_flag(args, Key::RemoveEditorData);
+        } else {
+            flags.resolve(value, key);
+        }
+    }
+}
+
 pub fn gen_cleaning_options(args: &ArgMatches) -> CleaningOptions {
     let flags = Flags::new(args);
 
     // All cleaning options are disabled by default.
     let mut opt = CleaningOptions::default();
 
+    flags.resolve(&mut opt.remove_processing_instructions, Key::RemoveProcessingInstructions);
+    flags.resolve(&mut opt.remove_doctype, Key::RemoveDoctype);
+    flags.resolve(&mut opt.convert_cdata, Key::ConvertCdata);
     flags.resolve(&mut opt.remove_unused_defs, Key::RemoveUnusedDefs);
     flags.resolve(&mut opt.convert_shapes, Key::ConvertShapes);
+    flags.resolve(&mut opt.convert_shapes_to_paths, Key::ConvertShapesToPaths);
     flags.resolve(&mut opt.remove_title, Key::RemoveTitle);
     flags.resolve(&mut opt.remove_desc, Key::RemoveDesc);
     flags.resolve(&mut opt.remove_metadata, Key::RemoveMetadata);
+    resolve_metadata(args, &mut opt);
+    flags.resolve(&mut opt.keep_accessibility, Key::KeepAccessibility);
+    if opt.keep_accessibility {
+        // See 'gen_parse_options'.
+        let mut parse_unknown_attributes = true;
+        flags.resolve_inv(&mut parse_unknown_attributes, Key::RemoveNonsvgAttributes);
+        opt.remove_nonsvg_attributes = !parse_unknown_attributes;
+    }
+    flags.resolve(&mut opt.remove_animations, Key::RemoveAnimations);
+    flags.resolve(&mut opt.remove_scripts, Key::RemoveScripts);
+    flags.resolve(&mut opt.remove_foreign_objects, Key::RemoveForeignObjects);
+    flags.resolve(&mut opt.sanitize, Key::Sanitize);
+
+    opt.external_refs = match args.value_of(KEYS[Key::ExternalRefs]).unwrap() {
+        "keep"      => ExternalRefsMode::Keep,
+        "remove"    => ExternalRefsMode::Remove,
+        "embed"     => ExternalRefsMode::Embed,
+        _ => unreachable!(), // clap will validate the input.
+    };
+
+    flags.resolve(&mut opt.resolve_switch, Key::ResolveSwitch);
+    if let Some(langs) = args.value_of(KEYS[Key::SystemLanguage]) {
+        opt.system_language = task_list(langs).iter().map(|s| s.to_string()).collect();
+    }
+
+    resolve_editor_data(args, &flags, &mut opt);
     flags.resolve(&mut opt.remove_dupl_linear_gradients, Key::RemoveDuplLinearGradients);
     flags.resolve(&mut opt.remove_dupl_radial_gradients, Key::RemoveDuplRadialGradients);
+    flags.resolve(&mut opt.simplify_filters, Key::SimplifyFilters);
     flags.resolve(&mut opt.remove_dupl_fe_gaussian_blur, Key::RemoveDuplFeGaussianBlur);
+    flags.resolve(&mut opt.remove_dupl_markers, Key::RemoveDuplMarkers);
+    flags.resolve(&mut opt.remove_dupl_patterns, Key::RemoveDuplPatterns);
+    flags.resolve(&mut opt.optimize_images, Key::OptimizeImages);
+    flags.resolve(&mut opt.remove_dupl_images, Key::RemoveDuplImages);
+    opt.extract_images = args.value_of(KEYS[Key::ExtractImages]).map(String::from);
     flags.resolve(&mut opt.ungroup_groups, Key::UngroupGroups);
+    flags.resolve(&mut opt.merge_transforms, Key::MergeTransforms);
     flags.resolve(&mut opt.ungroup_defs, Key::UngroupDefs);
     flags.resolve(&mut opt.group_by_style, Key::GroupByStyle);
+    flags.resolve(&mut opt.collapse_attributes, Key::CollapseAttributes);
     flags.resolve(&mut opt.merge_gradients, Key::MergeGradients);
     flags.resolve(&mut opt.regroup_gradient_stops, Key::RegroupGradientStops);
     flags.resolve(&mut opt.remove_invalid_stops, Key::RemoveInvalidStops);
     flags.resolve(&mut opt.remove_invisible_elements, Key::RemoveInvisibleElements);
+    flags.resolve(&mut opt.remove_empty_elements, Key::RemoveEmptyElements);
+    flags.resolve(&mut opt.normalize_text, Key::NormalizeText);
+    flags.resolve(&mut opt.flatten_tspans, Key::FlattenTspans);
+    flags.resolve(&mut opt.remove_outside_elements, Key::RemoveOutsideElements);
+    flags.resolve(&mut opt.remove_noop_masking, Key::RemoveNoopMasking);
+    flags.resolve(&mut opt.apply_clip_paths, Key::ApplyClipPaths);
     flags.resolve(&mut opt.resolve_use, Key::ResolveUse);
+    flags.resolve(&mut opt.optimize_symbols, Key::OptimizeSymbols);
+    flags.resolve(&mut opt.flatten_nested_svg, Key::FlattenNestedSvg);
+    flags.resolve(&mut opt.merge_paths, Key::MergePaths);
 
     flags.resolve(&mut opt.remove_version, Key::RemoveVersion);
+    flags.resolve(&mut opt.remove_conditional_attributes, Key::RemoveConditionalAttributes);
     flags.resolve(&mut opt.remove_unreferenced_ids, Key::RemoveUnreferencedIds);
     flags.resolve(&mut opt.trim_ids, Key::TrimIds);
+    if let Some(ids) = args.value_of(KEYS[Key::KeepIds]) {
+        opt.keep_ids = task_list(ids).iter().map(|s| s.to_string()).collect();
+    }
+    flags.resolve(&mut opt.remove_unused_classes, Key::RemoveUnusedClasses);
+    flags.resolve(&mut opt.trim_classes, Key::TrimClasses);
     flags.resolve(&mut opt.remove_text_attributes, Key::RemoveTextAttributes);
     flags.resolve(&mut opt.remove_unused_coordinates, Key::RemoveUnusedCoordinates);
     flags.resolve(&mut opt.remove_default_attributes, Key::RemoveDefaultAttributes);
     flags.resolve(&mut opt.remove_xmlns_xlink_attribute, Key::RemoveXmlnsXlinkAttribute);
+    flags.resolve(&mut opt.remove_unused_namespaces, Key::RemoveUnusedNamespaces);
     flags.resolve(&mut opt.remove_needless_attributes, Key::RemoveNeedlessAttributes);
     flags.resolve(&mut opt.remove_gradient_attributes, Key::RemoveGradientAttributes);
+    flags.resolve(&mut opt.merge_opacity, Key::MergeOpacity);
+    flags.resolve(&mut opt.convert_units, Key::ConvertUnits);
     flags.resolve(&mut opt.apply_transform_to_gradients, Key::ApplyTransformToGradients);
     flags.resolve(&mut opt.apply_transform_to_shapes, Key::ApplyTransformToShapes);
+    flags.resolve(&mut opt.apply_any_transform_to_shapes, Key::ApplyAnyTransformToShapes);
+    flags.resolve(&mut opt.fit_viewbox, Key::FitViewbox);
+    flags.resolve(&mut opt.translate_to_origin, Key::TranslateToOrigin);
 
     opt.join_style_attributes = match args.value_of(KEYS[Key::JoinStyleAttributes]).unwrap() {
         "no"    => StyleJoinMode::None,
@@ -546,45 +1280,468 @@ pub fn gen_cleaning_options(args: &ArgMatches) -> CleaningOptions {
         _ => unreachable!(), // clap will validate the input.
     };
 
+    opt.styles = match args.value_of(KEYS[Key::Styles]).unwrap() {
+        "presentation"  => StylesMode::Presentation,
+        "css"           => StylesMode::Css,
+        "auto"          => StylesMode::Auto,
+        _ => unreachable!(), // clap will validate the input.
+    };
+
+    if let Some(vars) = args.value_of(KEYS[Key::Define]) {
+        // Validated by clap.
+        opt.css_variables = variables_list(vars).unwrap();
+    }
+
+    opt.sort_attributes = match args.value_of(KEYS[Key::SortAttributes]).unwrap() {
+        "alphabetical"      => SortAttributesMode::Alphabetical,
+        "render-friendly"   => SortAttributesMode::RenderFriendly,
+        "frequency"         => SortAttributesMode::Frequency,
+        _ => unreachable!(), // clap will validate the input.
+    };
+
+    opt.sort_defs = match args.value_of(KEYS[Key::SortDefs]).unwrap() {
+        "no"        => SortDefsMode::None,
+        "first-use" => SortDefsMode::FirstUse,
+        "type"      => SortDefsMode::Type,
+        _ => unreachable!(), // clap will validate the input.
+    };
+
+    opt.href_style = match args.value_of(KEYS[Key::HrefStyle]).unwrap() {
+        "svg2"  => HrefStyle::Svg2,
+        "xlink" => HrefStyle::Xlink,
+        "auto"  => HrefStyle::Auto,
+        _ => unreachable!(), // clap will validate the input.
+    };
+
     flags.resolve(&mut opt.paths_to_relative, Key::PathsToRelative);
     flags.resolve(&mut opt.remove_unused_segments, Key::RemoveUnusedSegments);
     flags.resolve(&mut opt.convert_segments, Key::ConvertSegments);
+    flags.resolve(&mut opt.reorder_subpaths, Key::ReorderSubpaths);
     flags.resolve(&mut opt.apply_transform_to_paths, Key::ApplyTransformToPaths);
+    flags.resolve(&mut opt.minify_colors, Key::MinifyColors);
+    flags.resolve(&mut opt.use_exponent_notation, Key::UseExponentNotation);
+    opt.minimal_list_separators = args.value_of(KEYS[Key::ListSeparator]) == Some("minimal");
     flags.resolve(&mut opt.append_newline, Key::AppendNewline);
 
+    opt.dpi = value_t!(args, KEYS[Key::Dpi], u32).unwrap();
+    opt.simplify_paths = value_t!(args, KEYS[Key::SimplifyPaths], f64).unwrap();
+    opt.viewbox_padding = value_t!(args, KEYS[Key::ViewboxPadding], f64).unwrap();
+    opt.scale_coordinates = value_t!(args, KEYS[Key::ScaleCoordinates], f64).unwrap();
+
     opt.coordinates_precision = value_t!(args, KEYS[Key::CoordinatesPrecision], u8).unwrap();
     opt.properties_precision = value_t!(args, KEYS[Key::PropertiesPrecision], u8).unwrap();
     opt.paths_coordinates_precision
         = value_t!(args, KEYS[Key::PathsCoordinatesPrecision], u8).unwrap();
     opt.transforms_precision   = value_t!(args, KEYS[Key::TransformsPrecision], u8).unwrap();
 
+    // Files are already cleaned in parallel in the batch mode.
+    opt.jobs = if is_batch_mode(args) { 1 } else { jobs(args) };
+
     opt
 }
 
-pub fn input<'a>(args: &'a ArgMatches) -> InputFrom<'a> {
-    let in_file = args.value_of("in-file").unwrap();
-    let out_file = args.value_of("out-file");
+// Applies '--enable-task', '--disable-task' and '--task-order' to the built-in tasks.
+pub fn gen_task_registry(args: &ArgMatches) -> TaskRegistry {
+    let mut registry = TaskRegistry::new();
 
-    if in_file == "-" && args.is_present(KEYS[Key::Stdout]) {
-        InputFrom::Stdin
-    } else if let Some("-") = out_file {
-        InputFrom::Stdin
-    } else {
-        InputFrom::File(in_file)
+    // Task names are already validated by 'is_task_list', so we can safely unwrap.
+    if let Some(names) = args.value_of(KEYS[Key::TaskOrder]) {
+        registry.reorder(&task_list(names)).unwrap();
+    }
+
+    if let Some(names) = args.value_of(KEYS[Key::EnableTask]) {
+        for name in task_list(names) {
+            registry.enable(name).unwrap();
+        }
+    }
+
+    if let Some(names) = args.value_of(KEYS[Key::DisableTask]) {
+        for name in task_list(names) {
+            registry.remove(name).unwrap();
+        }
     }
+
+    registry
 }
 
-pub fn output<'a>(args: &'a ArgMatches) -> OutputTo<'a> {
-    let in_file = args.value_of("in-file").unwrap();
-    let out_file = args.value_of("out-file");
+pub fn is_batch_mode(args: &ArgMatches) -> bool {
+    Path::new(args.value_of("in-file").unwrap()).is_dir()
+}
+
+// Checks that the output should be compressed.
+pub fn is_compress(args: &ArgMatches, output: OutputTo) -> bool {
+    if args.is_present(KEYS[Key::Compress]) {
+        return true;
+    }
+
+    match output {
+        OutputTo::File(path) => is_svgz_name(path),
+        OutputTo::Stdout => false,
+    }
+}
+
+// Returns a number of worker threads for the batch mode.
+pub fn jobs(args: &ArgMatches) -> usize {
+    match value_t!(args, KEYS[Key::Jobs], usize).unwrap() {
+        // Use all the available CPUs.
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    }
+}
+
+// Checks that a document of the specified size should be stripped without building a DOM first.
+pub fn is_streamed(args: &ArgMatches, size: usize) -> bool {
+    match value_t!(args, KEYS[Key::StreamThreshold], usize).unwrap() {
+        // Without a threshold, '--stream-only' streams all documents.
+        0 => args.is_present(KEYS[Key::StreamOnly]),
+        threshold => size > threshold,
+    }
+}
+
+// Returns a memory limit in bytes.
+pub fn max_memory(args: &ArgMatches) -> Option<usize> {
+    match value_t!(args, KEYS[Key::MaxMemory], usize).unwrap() {
+        0 => None,
+        n => Some(n.saturating_mul(1024 * 1024)),
+    }
+}
+
+// Returns a maximum percentage of different pixels for '--verify'.
+pub fn verify_threshold(args: &ArgMatches) -> f64 {
+    value_t!(args, KEYS[Key::VerifyThreshold], f64).unwrap()
+}
+
+// '-' as a file name means the standard input.
+pub fn input<'a>(args: &'a ArgMatches) -> InputFrom<'a> {
+    match args.value_of("in-file").unwrap() {
+        "-" => InputFrom::Stdin,
+        in_file => InputFrom::File(in_file),
+    }
+}
 
+// '-' as a file name means the standard output.
+//
+// The output file is optional in the dry-run mode, since nothing is written anyway.
+pub fn output<'a>(args: &'a ArgMatches) -> OutputTo<'a> {
     if args.is_present(KEYS[Key::Stdout]) {
-        OutputTo::Stdout
-    } else if let Some("-") = out_file {
-        OutputTo::File(in_file)
+        return OutputTo::Stdout;
+    }
+
+    if is_in_place(args) {
+        return match input(args) {
+            InputFrom::Stdin => OutputTo::Stdout,
+            InputFrom::File(path) => OutputTo::File(path),
+        };
+    }
+
+    match args.value_of("out-file") {
+        None | Some("-") => OutputTo::Stdout,
+        Some(out_file) => OutputTo::File(out_file),
+    }
+}
+
+// '--in-place' replaces input files, so the output is the same as the input.
+pub fn is_in_place(args: &ArgMatches) -> bool {
+    args.is_present(KEYS[Key::InPlace])
+}
+
+// Zeroes timings in the report, which are the only thing that differs between runs.
+pub fn is_deterministic(args: &ArgMatches) -> bool {
+    let mut flag = false;
+    Flags::new(args).resolve(&mut flag, Key::Deterministic);
+    flag
+}
+
+// '-v' enables the info level with a summary of each task
+// and '-vv' the debug level with each removed element.
+pub fn log_level(args: &ArgMatches) -> LevelFilter {
+    match args.occurrences_of(KEYS[Key::Verbose]) {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    }
+}
+
+pub fn log_format(args: &ArgMatches) -> LogFormat {
+    match args.value_of(KEYS[Key::LogFormat]).unwrap() {
+        "text" => LogFormat::Text,
+        "json" => LogFormat::Json,
+        _ => unreachable!(), // clap will validate the input.
+    }
+}
+
+pub fn warnings_mode(args: &ArgMatches) -> WarningsMode {
+    match args.value_of(KEYS[Key::Warnings]).unwrap() {
+        "human" => WarningsMode::Human,
+        "json"  => WarningsMode::Json,
+        "quiet" => WarningsMode::Quiet,
+        _ => unreachable!(), // clap will validate the input.
+    }
+}
+
+pub fn is_dry_run(args: &ArgMatches) -> bool {
+       args.is_present(KEYS[Key::DryRun])
+    || args.is_present(KEYS[Key::Diff])
+    || args.is_present(KEYS[Key::Bench])
+}
+
+// Doesn't touch ids, text and accessibility data.
+static SAFE_PRESET: &[(Key, &str)] = &[
+    (Key::RemoveTitle, "false"),
+    (Key::RemoveDesc, "false"),
+    (Key::UngroupDefs, "false"),
+    (Key::MergePaths, "false"),
+    (Key::RemoveUnreferencedIds, "false"),
+    (Key::TrimIds, "false"),
+    (Key::TrimClasses, "false"),
+    (Key::RemoveTextAttributes, "false"),
+    (Key::ApplyTransformToShapes, "false"),
+];
+
+// Enables lossy options.
+static AGGRESSIVE_PRESET: &[(Key, &str)] = &[
+    (Key::RemoveOutsideElements, "true"),
+    (Key::RemoveGradientAttributes, "true"),
+    (Key::Styles, "auto"),
+    (Key::ApplyAnyTransformToShapes, "true"),
+    (Key::ApplyTransformToPaths, "true"),
+    (Key::SimplifyPaths, "0.1"),
+    (Key::JoinArcToFlags, "true"),
+    (Key::UseExponentNotation, "true"),
+    (Key::ListSeparator, "minimal"),
+    (Key::CoordinatesPrecision, "2"),
+    (Key::PropertiesPrecision, "2"),
+    (Key::TransformsPrecision, "5"),
+    (Key::PathsCoordinatesPrecision, "2"),
+];
+
+// Converts a preset into command-line arguments.
+//
+// Like in the 'config_args', values set via the command line are skipped.
+pub fn preset_args(args: &ArgMatches, name: &str) -> Vec<String> {
+    let preset = match name {
+        "safe" => SAFE_PRESET,
+        "aggressive" => AGGRESSIVE_PRESET,
+        _ => &[],
+    };
+
+    preset.iter()
+        .filter(|&&(key, _)| args.occurrences_of(KEYS[key]) == 0)
+        .map(|&(key, value)| format!("--{}={}", KEYS[key], value))
+        .collect()
+}
+
+#[derive(Clone,Copy,PartialEq)]
+enum KeyKind {
+    // A flag with a 'true' or 'false' value.
+    Flag,
+    // A flag without a value.
+    Switch,
+    Number,
+    String,
+}
+
+static SWITCH_KEYS: &[Key] = &[
+    Key::NoDefaults,
+    Key::Multipass,
+    Key::Strict,
+    Key::Compress,
+    Key::AllowBiggerFile,
+    Key::Verify,
+    Key::CopyOnError,
+    Key::InPlace,
+    Key::Quiet,
+    Key::Verbose,
+    Key::Recursive,
+    Key::VerboseStats,
+    Key::DryRun,
+    Key::Diff,
+    Key::Check,
+    Key::Bench,
+    Key::Stdout,
+    Key::Pretty,
+    Key::StreamOnly,
+];
+
+static NUMBER_KEYS: &[Key] = &[
+    Key::Dpi,
+    Key::ViewboxPadding,
+    Key::ScaleCoordinates,
+    Key::VerifyThreshold,
+    Key::SimplifyPaths,
+    Key::CoordinatesPrecision,
+    Key::PropertiesPrecision,
+    Key::TransformsPrecision,
+    Key::PathsCoordinatesPrecision,
+    Key::Jobs,
+    Key::StreamThreshold,
+    Key::MaxMemory,
+];
+
+static STRING_KEYS: &[Key] = &[
+    Key::Metadata,
+    Key::ExternalRefs,
+    Key::SystemLanguage,
+    Key::ExtractImages,
+    Key::JoinStyleAttributes,
+    Key::Styles,
+    Key::Define,
+    Key::ListSeparator,
+    Key::Indent,
+    Key::SortAttributes,
+    Key::SortDefs,
+    Key::QuoteChar,
+    Key::HrefStyle,
+    Key::KeepIds,
+    Key::Pattern,
+    Key::EnableTask,
+    Key::DisableTask,
+    Key::TaskOrder,
+    Key::Preset,
+    Key::Report,
+    Key::ReportFile,
+    Key::Warnings,
+    Key::LogFormat,
+    Key::BackupSuffix,
+    Key::Config,
+    Key::WriteConfig,
+    Key::SvgoConfig,
+];
+
+// Keys that are not written by '--write-config'.
+static SKIPPED_KEYS: &[Key] = &[
+    Key::NoDefaults,
+    Key::Verbose,
+    Key::InPlace,
+    Key::BackupSuffix,
+    Key::DryRun,
+    Key::Diff,
+    Key::Check,
+    Key::Bench,
+    Key::Preset,
+    Key::Config,
+    Key::WriteConfig,
+    Key::SvgoConfig,
+    Key::Stdout,
+];
+
+static CONFIG_SECTIONS: &[(Key, &str)] = &[
+    (Key::RemoveComments, "Elements"),
+    (Key::RemoveVersion, "Attributes"),
+    (Key::PathsToRelative, "Paths"),
+    (Key::TrimColors, "Output"),
+    (Key::Multipass, "Other"),
+];
+
+fn key_kind(name: &str) -> Option<KeyKind> {
+    let contains = |keys: &[Key]| keys.iter().any(|&k| KEYS[k] == name);
+
+    if !KEYS.0.contains(&name) {
+        None
+    } else if contains(SWITCH_KEYS) {
+        Some(KeyKind::Switch)
+    } else if contains(NUMBER_KEYS) {
+        Some(KeyKind::Number)
+    } else if contains(STRING_KEYS) {
+        Some(KeyKind::String)
     } else {
-        OutputTo::File(out_file.unwrap())
+        Some(KeyKind::Flag)
+    }
+}
+
+// Loads a configuration file and converts it into command-line arguments.
+//
+// Values, which are already set via the command line, are skipped,
+// so the command line always takes precedence.
+pub fn config_args(args: &ArgMatches, path: &str) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read '{}': {}", path, e))?;
+    let list = config::parse(&text)
+        .map_err(|e| format!("failed to parse '{}': {}", path, e))?;
+
+    to_args(args, list, path)
+}
+
+// Loads an SVGO config in the JSON form and converts it into command-line arguments.
+//
+// Values, which are already set via the command line or '--config', are skipped.
+pub fn svgo_config_args(args: &ArgMatches, path: &str) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read '{}': {}", path, e))?;
+    let svgo_config = svgo::parse(&text)
+        .map_err(|e| format!("failed to parse '{}': {}", path, e))?;
+
+    for msg in &svgo_config.warnings {
+        warn!("{} in '{}'.", msg, path);
+    }
+
+    to_args(args, svgo_config.options, path)
+}
+
+fn to_args(args: &ArgMatches, list: Vec<(String, Value)>, path: &str) -> Result<Vec<String>, String> {
+    let mut flags = Vec::new();
+    for (key, value) in list {
+        let kind = match key_kind(&key) {
+            Some(kind) => kind,
+            None => return Err(format!("unknown option '{}' in '{}'", key, path)),
+        };
+
+        if key == KEYS[Key::Config] || key == KEYS[Key::WriteConfig] || key == KEYS[Key::SvgoConfig] {
+            return Err(format!("'{}' cannot be set in a config file", key));
+        }
+
+        if args.occurrences_of(&key) != 0 {
+            continue;
+        }
+
+        match (kind, value) {
+            (KeyKind::Switch, Value::Bool(true)) => flags.push(format!("--{}", key)),
+            (KeyKind::Switch, Value::Bool(false)) => {}
+            (KeyKind::Switch, _) => return Err(format!("'{}' must be a boolean", key)),
+            (_, value) => flags.push(format!("--{}={}", key, value.to_arg())),
+        }
+    }
+
+    Ok(flags)
+}
+
+// Generates a configuration file from the current effective options.
+pub fn gen_config(args: &ArgMatches) -> String {
+    let is_no_defaults = args.is_present(KEYS[Key::NoDefaults]);
+
+    let mut text = String::new();
+    for name in KEYS.0 {
+        if SKIPPED_KEYS.iter().any(|&k| KEYS[k] == *name) {
+            continue;
+        }
+
+        if let Some(&(_, title)) = CONFIG_SECTIONS.iter().find(|&&(k, _)| KEYS[k] == *name) {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("# {}\n", title));
+        }
+
+        let value = match key_kind(name).unwrap() {
+            KeyKind::Flag => {
+                // See Flags::_resolve.
+                if is_no_defaults && args.occurrences_of(name) == 0 {
+                    Value::Bool(false)
+                } else {
+                    Value::Bool(parse_flag(args.value_of(name).unwrap()))
+                }
+            }
+            KeyKind::Switch => Value::Bool(args.is_present(name)),
+            // Options without a default value are skipped when not set.
+            KeyKind::Number | KeyKind::String if args.value_of(name).is_none() => continue,
+            KeyKind::Number => Value::Number(args.value_of(name).unwrap().to_string()),
+            KeyKind::String => Value::String(args.value_of(name).unwrap().to_string()),
+        };
+
+        text.push_str(&format!("{} = {}\n", name, value));
     }
+
+    text
 }
 
 #[cfg(test)]
@@ -648,42 +1805,306 @@ mod tests {
     }
 
     #[test]
-    fn default_1() {
+    fn cli_5() {
         let app = prepare_app();
         let args = app.get_matches_from_safe(&[
             "svgcleaner",
+            "--apply-transform-to-shapes=false",
+            "--apply-any-transform-to-shapes=true",
             "in.svg",
             "out.svg",
         ]).unwrap();
 
-        let parse_opt = gen_parse_options(&args);
-        let write_opt = gen_write_options(&args);
-        let cleaning_opt = gen_cleaning_options(&args);
-
-        assert_eq!(parse_opt.parse_comments, false);
-        assert_eq!(parse_opt.parse_declarations, false);
+        assert!(!check_values(&args));
+    }
 
-        assert_eq!(write_opt.use_compact_path_notation, true);
-        assert_eq!(write_opt.remove_duplicated_path_commands, true);
+    #[test]
+    fn dpi_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--dpi=0",
+            "in.svg",
+            "out.svg",
+        ]);
 
-        assert_eq!(cleaning_opt.remove_unused_defs, true);
-        assert_eq!(cleaning_opt.convert_shapes, true);
-        assert_eq!(cleaning_opt.remove_gradient_attributes, false);
+        assert!(args.is_err());
     }
 
     #[test]
-    fn no_defaults_1() {
+    fn simplify_paths_1() {
         let app = prepare_app();
         let args = app.get_matches_from_safe(&[
             "svgcleaner",
-            "--no-defaults",
+            "--simplify-paths=-1",
             "in.svg",
             "out.svg",
-        ]).unwrap();
+        ]);
 
-        let parse_opt = gen_parse_options(&args);
-        let write_opt = gen_write_options(&args);
-        let cleaning_opt = gen_cleaning_options(&args);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn scale_coordinates_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--scale-coordinates=10",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let opt = gen_cleaning_options(&args);
+        assert_eq!(opt.scale_coordinates, 10.0);
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--scale-coordinates=0",
+            "in.svg",
+            "out.svg",
+        ]);
+
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn viewbox_padding_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--fit-viewbox=yes",
+            "--viewbox-padding=2.5",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let opt = gen_cleaning_options(&args);
+        assert!(opt.fit_viewbox);
+        assert_eq!(opt.viewbox_padding, 2.5);
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--viewbox-padding=-1",
+            "in.svg",
+            "out.svg",
+        ]);
+
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn styles_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--styles=css",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let cleaning_opt = gen_cleaning_options(&args);
+        assert!(cleaning_opt.styles == StylesMode::Css);
+    }
+
+    #[test]
+    fn sort_attributes_1() {
+        use svgdom::AttributesOrder;
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--sort-attributes=frequency",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let cleaning_opt = gen_cleaning_options(&args);
+        assert!(cleaning_opt.sort_attributes == SortAttributesMode::Frequency);
+
+        let write_opt = gen_write_options(&args);
+        assert!(write_opt.attributes_order == AttributesOrder::AsIs);
+    }
+
+    #[test]
+    fn deterministic_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&["svgcleaner", "in.svg", "out.svg"]).unwrap();
+        assert!(is_deterministic(&args));
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--deterministic=no",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+        assert!(!is_deterministic(&args));
+    }
+
+    #[test]
+    fn pretty_1() {
+        use svgdom::Indent;
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--pretty",
+            "--quote-char=single",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let write_opt = gen_write_options(&args);
+        assert!(write_opt.indent == Indent::Spaces(2));
+        assert!(write_opt.use_single_quote);
+        assert!(!write_opt.use_compact_path_notation);
+        assert!(!write_opt.use_implicit_lineto_commands);
+    }
+
+    // Explicitly set options are preserved.
+    #[test]
+    fn pretty_2() {
+        use svgdom::Indent;
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--pretty",
+            "--indent=tabs",
+            "--trim-paths=yes",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let write_opt = gen_write_options(&args);
+        assert!(write_opt.indent == Indent::Tabs);
+        assert!(write_opt.use_compact_path_notation);
+        assert!(!write_opt.join_arc_to_flags);
+    }
+
+    #[test]
+    fn list_separator_1() {
+        use svgdom::ListSeparator;
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--list-separator=minimal",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let cleaning_opt = gen_cleaning_options(&args);
+        assert!(cleaning_opt.minimal_list_separators);
+
+        let write_opt = gen_write_options(&args);
+        assert!(write_opt.list_separator == ListSeparator::Space);
+    }
+
+    #[test]
+    fn sort_defs_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--sort-defs=first-use",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let cleaning_opt = gen_cleaning_options(&args);
+        assert!(cleaning_opt.sort_defs == SortDefsMode::FirstUse);
+    }
+
+    #[test]
+    fn href_style_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--href-style=svg2",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let cleaning_opt = gen_cleaning_options(&args);
+        assert!(cleaning_opt.href_style == HrefStyle::Svg2);
+    }
+
+    #[test]
+    fn default_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let parse_opt = gen_parse_options(&args);
+        let write_opt = gen_write_options(&args);
+        let cleaning_opt = gen_cleaning_options(&args);
+
+        assert_eq!(parse_opt.parse_comments, false);
+        assert_eq!(parse_opt.parse_declarations, false);
+
+        assert_eq!(write_opt.use_compact_path_notation, true);
+        assert_eq!(write_opt.remove_duplicated_path_commands, true);
+
+        assert_eq!(cleaning_opt.remove_unused_defs, true);
+        assert_eq!(cleaning_opt.convert_shapes, true);
+        assert_eq!(cleaning_opt.remove_gradient_attributes, false);
+    }
+
+    #[test]
+    fn default_2() {
+        use cleaner::Cleaner;
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        // The library should use the same defaults as the CLI.
+        let parse_opt = gen_parse_options(&args);
+        let lib_parse_opt = Cleaner::recommended_parse_options();
+        assert_eq!(parse_opt.parse_comments, lib_parse_opt.parse_comments);
+        assert_eq!(parse_opt.parse_declarations, lib_parse_opt.parse_declarations);
+        assert_eq!(parse_opt.parse_unknown_elements, lib_parse_opt.parse_unknown_elements);
+        assert_eq!(parse_opt.parse_unknown_attributes, lib_parse_opt.parse_unknown_attributes);
+        assert_eq!(parse_opt.skip_unresolved_classes, lib_parse_opt.skip_unresolved_classes);
+
+        let write_opt = gen_write_options(&args);
+        let lib_write_opt = Cleaner::recommended_write_options();
+        assert!(write_opt.indent == lib_write_opt.indent);
+        assert!(write_opt.list_separator == lib_write_opt.list_separator);
+        assert_eq!(write_opt.trim_hex_colors, lib_write_opt.trim_hex_colors);
+        assert_eq!(write_opt.use_compact_path_notation, lib_write_opt.use_compact_path_notation);
+        assert_eq!(write_opt.join_arc_to_flags, lib_write_opt.join_arc_to_flags);
+        assert_eq!(write_opt.remove_duplicated_path_commands,
+                   lib_write_opt.remove_duplicated_path_commands);
+        assert_eq!(write_opt.use_implicit_lineto_commands,
+                   lib_write_opt.use_implicit_lineto_commands);
+        assert_eq!(write_opt.simplify_transform_matrices,
+                   lib_write_opt.simplify_transform_matrices);
+
+        assert!(gen_cleaning_options(&args) == CleaningOptions::recommended());
+    }
+
+    #[test]
+    fn no_defaults_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--no-defaults",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let parse_opt = gen_parse_options(&args);
+        let write_opt = gen_write_options(&args);
+        let cleaning_opt = gen_cleaning_options(&args);
 
         assert_eq!(parse_opt.parse_comments, true);
         assert_eq!(parse_opt.parse_declarations, true);
@@ -764,15 +2185,489 @@ mod tests {
     }
 
     #[test]
-    fn std_3() {
+    fn jobs_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--jobs=4",
+            "in",
+            "out",
+        ]).unwrap();
+
+        assert_eq!(jobs(&args), 4);
+    }
+
+    #[test]
+    fn jobs_2() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--jobs=-1",
+            "in",
+            "out",
+        ]);
+
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn stream_1() {
         let app = prepare_app();
         let args = app.get_matches_from_safe(&[
             "svgcleaner",
+            "--stream-threshold=100",
+            "in.svg",
             "out.svg",
+        ]).unwrap();
+
+        assert!(!is_streamed(&args, 100));
+        assert!(is_streamed(&args, 101));
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--stream-only",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        assert!(is_streamed(&args, 1));
+    }
+
+    #[test]
+    fn max_memory_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--max-memory=2",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        assert_eq!(max_memory(&args), Some(2 * 1024 * 1024));
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&["svgcleaner", "in.svg", "out.svg"]).unwrap();
+        assert_eq!(max_memory(&args), None);
+    }
+
+    #[test]
+    fn bench_1() {
+        // The output is not required and nothing is written.
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&["svgcleaner", "--bench", "corpus/"]).unwrap();
+        assert!(is_dry_run(&args));
+    }
+
+    #[test]
+    fn in_place_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--in-place",
+            "--backup-suffix=.bak",
+            "in.svg",
+        ]).unwrap();
+
+        assert!(is_in_place(&args));
+        assert_eq!(output(&args), OutputTo::File("in.svg"));
+        assert_eq!(args.value_of(KEYS[Key::BackupSuffix]), Some(".bak"));
+
+        // The output can't be set and a backup requires '--in-place'.
+        assert!(prepare_app().get_matches_from_safe(&[
+            "svgcleaner", "--in-place", "in.svg", "out.svg",
+        ]).is_err());
+        assert!(prepare_app().get_matches_from_safe(&[
+            "svgcleaner", "--backup-suffix=.bak", "in.svg", "out.svg",
+        ]).is_err());
+    }
+
+    #[test]
+    fn verbose_1() {
+        let level = |flags: &[&str]| {
+            let mut argv = vec!["svgcleaner", "in.svg", "out.svg"];
+            argv.extend_from_slice(flags);
+            log_level(&prepare_app().get_matches_from_safe(&argv).unwrap())
+        };
+
+        assert_eq!(level(&[]), LevelFilter::Warn);
+        assert_eq!(level(&["-q"]), LevelFilter::Warn);
+        assert_eq!(level(&["-v"]), LevelFilter::Info);
+        assert_eq!(level(&["-vv"]), LevelFilter::Debug);
+        assert_eq!(level(&["-v", "--verbose", "-v"]), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn warnings_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&["svgcleaner", "in.svg", "out.svg"]).unwrap();
+        assert_eq!(warnings_mode(&args), WarningsMode::Human);
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&["svgcleaner", "--warnings=json", "in.svg", "out.svg"])
+            .unwrap();
+        assert_eq!(warnings_mode(&args), WarningsMode::Json);
+    }
+
+    #[test]
+    fn pattern_1() {
+        assert!(matches_pattern("in.svg", "*.svg"));
+        assert!(matches_pattern("in.svg", "i?.svg"));
+        assert!(matches_pattern("icon-1.svg", "icon-*.svg"));
+        assert!(!matches_pattern("in.svgz", "*.svg"));
+        assert!(!matches_pattern("in.SVG", "*.svg"));
+        assert!(!matches_pattern("in.svg", "?.svg"));
+    }
+
+    #[test]
+    fn compress_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "in.svg",
+            "out.svgz",
+        ]).unwrap();
+
+        assert!(is_compress(&args, output(&args)));
+    }
+
+    #[test]
+    fn compress_2() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--compress",
+            "in.svgz",
+            "-",
+        ]).unwrap();
+
+        assert!(is_compress(&args, output(&args)));
+    }
+
+    #[test]
+    fn compress_3() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "in.svgz",
+            "out.svg",
+        ]).unwrap();
+
+        assert!(!is_compress(&args, output(&args)));
+    }
+
+    #[test]
+    fn std_3() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "in.svg",
+            "-",
+        ]).unwrap();
+
+        assert_eq!(input(&args), InputFrom::File("in.svg"));
+        assert_eq!(output(&args), OutputTo::Stdout);
+    }
+
+    #[test]
+    fn std_4() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
             "-",
+            "out.svg",
         ]).unwrap();
 
         assert_eq!(input(&args), InputFrom::Stdin);
         assert_eq!(output(&args), OutputTo::File("out.svg"));
     }
+
+    #[test]
+    fn std_5() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "-",
+            "-",
+        ]).unwrap();
+
+        assert_eq!(input(&args), InputFrom::Stdin);
+        assert_eq!(output(&args), OutputTo::Stdout);
+    }
+
+    #[test]
+    fn config_1() {
+        let path = ::std::env::temp_dir().join("svgcleaner-config-1.toml");
+        fs::write(&path, "indent = 2\nmultipass = true\ntrim-ids = false\nquiet = false\n").unwrap();
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--trim-ids=true",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let flags = config_args(&args, path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(flags, vec!["--indent=2", "--multipass"]);
+    }
+
+    #[test]
+    fn config_2() {
+        let path = ::std::env::temp_dir().join("svgcleaner-config-2.toml");
+        fs::write(&path, "write-config = 'out.toml'\n").unwrap();
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let res = config_args(&args, path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn svgo_config_1() {
+        let path = ::std::env::temp_dir().join("svgcleaner-svgo-config-1.json");
+        fs::write(&path, r#"{"multipass": true, "plugins": ["removeTitle", "removeComments"]}"#).unwrap();
+
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--remove-comments=no",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let flags = svgo_config_args(&args, path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(flags, vec!["--no-defaults", "--remove-title=true", "--multipass"]);
+    }
+
+    #[test]
+    fn write_config_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--no-defaults",
+            "--trim-ids=yes",
+            "--indent=2",
+            "--write-config=-",
+        ]).unwrap();
+
+        let text = gen_config(&args);
+        assert!(text.contains("\ntrim-ids = true\n"));
+        assert!(text.contains("\nremove-title = false\n"));
+        assert!(text.contains("\nindent = \"2\"\n"));
+        assert!(text.contains("\ndpi = 96\n"));
+        assert!(!text.contains("no-defaults"));
+    }
+
+    #[test]
+    fn task_registry_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--disable-task=remove-title, ungroup-groups",
+            "--task-order=group-defs,fix-invalid-attributes",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let names = gen_task_registry(&args).names();
+        assert!(!names.contains(&"remove-title"));
+        assert!(!names.contains(&"ungroup-groups"));
+        assert_eq!(&names[5..7], &["group-defs", "fix-invalid-attributes"]);
+    }
+
+    #[test]
+    fn task_registry_2() {
+        let app = prepare_app();
+        let res = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--enable-task=unknown",
+            "in.svg",
+            "out.svg",
+        ]);
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn metadata_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--metadata=keep-license",
+            "--remove-desc=yes",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let opt = gen_cleaning_options(&args);
+        assert_eq!(opt.remove_title, false);
+        assert_eq!(opt.remove_desc, true);
+        assert_eq!(opt.remove_metadata, true);
+        assert_eq!(opt.keep_license, true);
+    }
+
+    #[test]
+    fn prolog_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--remove-doctype=no",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let opt = gen_cleaning_options(&args);
+        assert!(opt.remove_processing_instructions);
+        assert!(!opt.remove_doctype);
+        assert!(opt.convert_cdata);
+    }
+
+    #[test]
+    fn keep_accessibility_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--keep-accessibility=yes",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let parse_opt = gen_parse_options(&args);
+        assert!(parse_opt.parse_unknown_attributes);
+
+        let opt = gen_cleaning_options(&args);
+        assert!(opt.keep_accessibility);
+        assert!(opt.remove_nonsvg_attributes);
+    }
+
+    #[test]
+    fn editor_data_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--remove-editor-data=no",
+            "--remove-sodipodi-data=yes",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let opt = gen_cleaning_options(&args);
+        assert_eq!(opt.remove_inkscape_data, false);
+        assert_eq!(opt.remove_sodipodi_data, true);
+        assert_eq!(opt.remove_ai_data, false);
+    }
+
+    #[test]
+    fn keep_ids_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--keep-ids=icon-.*, logo",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let opt = gen_cleaning_options(&args);
+        assert_eq!(opt.keep_ids, vec!["icon-.*".to_string(), "logo".to_string()]);
+    }
+
+    #[test]
+    fn define_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--define=main=rgb(0, 128, 0); --size = 2",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let opt = gen_cleaning_options(&args);
+        assert_eq!(opt.css_variables, vec![
+            ("main".to_string(), "rgb(0, 128, 0)".to_string()),
+            ("--size".to_string(), "2".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn define_2() {
+        let app = prepare_app();
+        let res = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--define=main",
+            "in.svg",
+            "out.svg",
+        ]);
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn system_language_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--resolve-switch=yes",
+            "--system-language=de, en-US",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let opt = gen_cleaning_options(&args);
+        assert!(opt.resolve_switch);
+        assert_eq!(opt.system_language, vec!["de".to_string(), "en-US".to_string()]);
+    }
+
+    #[test]
+    fn preset_1() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--preset=safe",
+            "--trim-ids=true",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        let flags = preset_args(&args, "safe");
+        assert!(flags.contains(&"--remove-title=false".to_string()));
+        assert!(!flags.iter().any(|f| f.starts_with("--trim-ids")));
+    }
+
+    #[test]
+    fn preset_2() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--preset=default",
+            "in.svg",
+            "out.svg",
+        ]).unwrap();
+
+        assert!(preset_args(&args, "default").is_empty());
+    }
+
+    #[test]
+    fn preset_3() {
+        let app = prepare_app();
+        let args = app.get_matches_from_safe(&[
+            "svgcleaner",
+            "--preset=fast",
+            "in.svg",
+            "out.svg",
+        ]);
+
+        assert!(args.is_err());
+    }
 }
@@ -0,0 +1,466 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Structural comparison of SVG documents.
+
+use std::cmp;
+use std::fmt;
+
+use svgdom::{
+    AttributeId as AId,
+    AttributeValue,
+    Document,
+    ElementId as EId,
+    LengthUnit,
+    Node,
+    NodeType,
+};
+
+// A relative tolerance of the numbers comparison.
+//
+// Cleaned files usually have rounded numbers, which should not be reported.
+const NUMBER_TOLERANCE: f64 = 0.001;
+
+/// A list of semantic differences between two documents.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct DiffReport {
+    /// Differences in the document order.
+    pub differences: Vec<Difference>,
+}
+
+impl DiffReport {
+    /// Checks that documents are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for diff in &self.differences {
+            writeln!(f, "{}", diff)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A single difference between two documents.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Difference {
+    /// A path to the element, like `/svg/g[1]/rect[2]`.
+    ///
+    /// Indexes are counted from 1 among the siblings with the same name.
+    pub path: String,
+    /// A kind of the difference.
+    pub kind: DifferenceKind,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+/// A kind of the difference.
+#[derive(Clone,Debug,PartialEq)]
+pub enum DifferenceKind {
+    /// An element is replaced with an element with another name.
+    ElementChanged(String, String),
+    /// An element is removed.
+    ElementRemoved(String),
+    /// An element is added.
+    ElementAdded(String),
+    /// An attribute is removed. Contains a name and a value.
+    AttributeRemoved(String, String),
+    /// An attribute is added. Contains a name and a value.
+    AttributeAdded(String, String),
+    /// An attribute value is changed. Contains a name, an old and a new value.
+    AttributeChanged(String, String, String),
+    /// A text is changed.
+    TextChanged(String, String),
+}
+
+impl fmt::Display for DifferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DifferenceKind::ElementChanged(ref old, ref new) => {
+                write!(f, "element '{}' is replaced with '{}'", old, new)
+            }
+            DifferenceKind::ElementRemoved(ref name) => {
+                write!(f, "element '{}' is removed", name)
+            }
+            DifferenceKind::ElementAdded(ref name) => {
+                write!(f, "element '{}' is added", name)
+            }
+            DifferenceKind::AttributeRemoved(ref name, ref value) => {
+                write!(f, "attribute '{}=\"{}\"' is removed", name, value)
+            }
+            DifferenceKind::AttributeAdded(ref name, ref value) => {
+                write!(f, "attribute '{}=\"{}\"' is added", name, value)
+            }
+            DifferenceKind::AttributeChanged(ref name, ref old, ref new) => {
+                write!(f, "attribute '{}' is changed from '{}' to '{}'", name, old, new)
+            }
+            DifferenceKind::TextChanged(ref old, ref new) => {
+                write!(f, "text is changed from '{}' to '{}'", old, new)
+            }
+        }
+    }
+}
+
+/// Compares two documents structurally.
+///
+/// Unlike a text comparison, it ignores the differences, which do not affect the document:
+///
+/// - Formatting, comments and the attributes order.
+/// - Values notation, like `#ff0000` and `red` or relative and absolute path segments.
+///   Numbers are compared with a 0.1% tolerance.
+/// - `id` attributes, since they can be renamed. Links are compared by the referenced elements.
+/// - The order of the `defs` element children.
+///
+/// This is useful to check that cleaning options didn't change a document unexpectedly.
+pub fn compare_documents(doc1: &Document, doc2: &Document) -> DiffReport {
+    let mut report = DiffReport::default();
+
+    match (doc1.svg_element(), doc2.svg_element()) {
+        (Some(svg1), Some(svg2)) => compare_nodes(&svg1, &svg2, "", &mut report),
+        (Some(svg1), None) => {
+            report.push(&node_path("", &svg1), DifferenceKind::ElementRemoved(tag_name(&svg1)));
+        }
+        (None, Some(svg2)) => {
+            report.push(&node_path("", &svg2), DifferenceKind::ElementAdded(tag_name(&svg2)));
+        }
+        (None, None) => {}
+    }
+
+    report
+}
+
+impl DiffReport {
+    fn push(&mut self, path: &str, kind: DifferenceKind) {
+        self.differences.push(Difference { path: path.to_string(), kind });
+    }
+}
+
+fn compare_nodes(node1: &Node, node2: &Node, parent_path: &str, report: &mut DiffReport) {
+    let path = node_path(parent_path, node1);
+
+    if node1.node_type() == NodeType::Text || node2.node_type() == NodeType::Text {
+        let (text1, text2) = (node_text(node1), node_text(node2));
+        if text1 != text2 {
+            report.push(&path, DifferenceKind::TextChanged(text1, text2));
+        }
+        return;
+    }
+
+    if tag_name(node1) != tag_name(node2) {
+        report.push(&path, DifferenceKind::ElementChanged(tag_name(node1), tag_name(node2)));
+        return;
+    }
+
+    compare_attributes(node1, node2, &path, report);
+
+    let mut children1 = children(node1);
+    let mut children2 = children(node2);
+
+    if node1.is_tag_name(EId::Defs) {
+        children1.sort_by_key(subtree_key);
+        children2.sort_by_key(subtree_key);
+    }
+
+    let len = cmp::max(children1.len(), children2.len());
+    for i in 0..len {
+        match (children1.get(i), children2.get(i)) {
+            (Some(c1), Some(c2)) => compare_nodes(c1, c2, &path, report),
+            (Some(c1), None) => {
+                report.push(&node_path(&path, c1), DifferenceKind::ElementRemoved(tag_name(c1)));
+            }
+            (None, Some(c2)) => {
+                report.push(&node_path(&path, c2), DifferenceKind::ElementAdded(tag_name(c2)));
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn compare_attributes(node1: &Node, node2: &Node, path: &str, report: &mut DiffReport) {
+    let attrs1 = node1.attributes();
+    let attrs2 = node2.attributes();
+
+    for attr in attrs1.iter().filter(|a| !a.has_id(AId::Id)) {
+        let name = format!("{:?}", attr.name);
+        match attrs2.iter().find(|a| a.name == attr.name) {
+            Some(attr2) => {
+                if !is_same_value(&attr.value, &attr2.value) {
+                    let kind = DifferenceKind::AttributeChanged(name, value_to_string(&attr.value),
+                                                                value_to_string(&attr2.value));
+                    report.push(path, kind);
+                }
+            }
+            None => {
+                report.push(path, DifferenceKind::AttributeRemoved(name, value_to_string(&attr.value)));
+            }
+        }
+    }
+
+    for attr in attrs2.iter().filter(|a| !a.has_id(AId::Id)) {
+        if !attrs1.iter().any(|a| a.name == attr.name) {
+            let name = format!("{:?}", attr.name);
+            report.push(path, DifferenceKind::AttributeAdded(name, value_to_string(&attr.value)));
+        }
+    }
+}
+
+fn is_same_value(value1: &AttributeValue, value2: &AttributeValue) -> bool {
+    match (value1, value2) {
+        (AttributeValue::Link(n1), AttributeValue::Link(n2))
+        | (AttributeValue::FuncLink(n1), AttributeValue::FuncLink(n2)) => {
+            shallow_key(n1) == shallow_key(n2)
+        }
+        (AttributeValue::Number(n1), AttributeValue::Number(n2)) => is_same_number(*n1, *n2),
+        (AttributeValue::Length(l1), AttributeValue::Length(l2)) => {
+            // Pixels are the default units.
+            let unit = |u| if u == LengthUnit::Px { LengthUnit::None } else { u };
+            unit(l1.unit) == unit(l2.unit) && is_same_number(l1.num, l2.num)
+        }
+        (AttributeValue::Transform(ts1), AttributeValue::Transform(ts2)) => {
+            [ts1.a, ts1.b, ts1.c, ts1.d, ts1.e, ts1.f].iter()
+                .zip(&[ts2.a, ts2.b, ts2.c, ts2.d, ts2.e, ts2.f])
+                .all(|(&n1, &n2)| is_same_number(n1, n2))
+        }
+        (AttributeValue::Path(p1), AttributeValue::Path(p2)) => {
+            let (mut p1, mut p2) = (p1.clone(), p2.clone());
+            p1.conv_to_absolute();
+            p2.conv_to_absolute();
+            is_same_text(&p1.to_string(), &p2.to_string())
+        }
+        _ => is_same_text(&value1.to_string(), &value2.to_string()),
+    }
+}
+
+fn is_same_number(n1: f64, n2: f64) -> bool {
+    let max = n1.abs().max(n2.abs()).max(1.0);
+    (n1 - n2).abs() <= max * NUMBER_TOLERANCE
+}
+
+// Compares texts, treating numbers inside them as numbers.
+fn is_same_text(text1: &str, text2: &str) -> bool {
+    let tokens1 = tokenize(text1);
+    let tokens2 = tokenize(text2);
+
+    tokens1.len() == tokens2.len() && tokens1.iter().zip(&tokens2).all(|pair| match pair {
+        (Token::Number(n1), Token::Number(n2)) => is_same_number(*n1, *n2),
+        (t1, t2) => t1 == t2,
+    })
+}
+
+#[derive(PartialEq)]
+enum Token {
+    Number(f64),
+    Char(char),
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_ascii_digit() || c == '.' || c == '-' || c == '+' {
+            let mut end = start + c.len_utf8();
+            while let Some(&(idx, c)) = chars.peek() {
+                let is_exp_sign = (c == '-' || c == '+') && text[..idx].ends_with(['e', 'E']);
+                if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || is_exp_sign {
+                    end = idx + c.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+
+            match text[start..end].parse() {
+                Ok(n) => tokens.push(Token::Number(n)),
+                Err(_) => tokens.extend(text[start..end].chars().map(Token::Char)),
+            }
+        } else if !c.is_whitespace() && c != ',' {
+            tokens.push(Token::Char(c));
+        }
+    }
+
+    tokens
+}
+
+fn is_link(value: &AttributeValue) -> bool {
+    matches!(value, AttributeValue::Link(_) | AttributeValue::FuncLink(_))
+}
+
+fn value_to_string(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::Link(node) | AttributeValue::FuncLink(node) => {
+            format!("link to '{}'", tag_name(node))
+        }
+        _ => value.to_string(),
+    }
+}
+
+// Elements and non-empty text nodes.
+fn children(node: &Node) -> Vec<Node> {
+    node.children().filter(|n| match n.node_type() {
+        NodeType::Element => true,
+        NodeType::Text => !n.text().trim().is_empty(),
+        _ => false,
+    }).collect()
+}
+
+fn node_text(node: &Node) -> String {
+    if node.node_type() == NodeType::Text {
+        node.text().trim().to_string()
+    } else {
+        format!("<{}>", tag_name(node))
+    }
+}
+
+fn tag_name(node: &Node) -> String {
+    match node.tag_name() {
+        Some(name) => format!("{:?}", *name),
+        None => String::from("#text"),
+    }
+}
+
+fn node_path(parent_path: &str, node: &Node) -> String {
+    let name = tag_name(node);
+    let mut idx = 1;
+    let mut prev = node.previous_sibling();
+    while let Some(n) = prev {
+        if n.node_type() == node.node_type() && tag_name(&n) == name {
+            idx += 1;
+        }
+        prev = n.previous_sibling();
+    }
+
+    format!("{}/{}[{}]", parent_path, name, idx)
+}
+
+// A description of an element without ids and links,
+// which is used to compare links.
+fn shallow_key(node: &Node) -> String {
+    let mut attrs: Vec<String> = node.attributes().iter()
+        .filter(|a| !a.has_id(AId::Id) && !is_link(&a.value))
+        .map(|a| format!("{:?}={}", a.name, a.value))
+        .collect();
+    attrs.sort();
+
+    format!("{}[{}]", tag_name(node), attrs.join(";"))
+}
+
+// A description of an element with children, which is used to sort the 'defs' children.
+fn subtree_key(node: &Node) -> String {
+    if node.node_type() == NodeType::Text {
+        return node_text(node);
+    }
+
+    let mut key = shallow_key(node);
+    for child in children(node) {
+        key.push_str(&subtree_key(&child));
+    }
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! test {
+        ($name:ident, $text1:expr, $text2:expr, $result:expr) => (
+            #[test]
+            fn $name() {
+                let doc1 = Document::from_str($text1).unwrap();
+                let doc2 = Document::from_str($text2).unwrap();
+                assert_eq!(compare_documents(&doc1, &doc2).to_string(), $result);
+            }
+        )
+    }
+
+    test!(equal_1,
+        "<svg><!-- Comment --><rect x='10' fill='#ff0000' width='10px'/></svg>",
+        "<svg>\n    <rect width='10' fill='red' x='10.00001'/>\n</svg>",
+        "");
+
+    test!(equal_2,
+        "<svg><path d='M10 10 L 20 20 l 10 10'/></svg>",
+        "<svg><path d='m10 10 10 10 10 10'/></svg>",
+        "");
+
+    test!(equal_3,
+        "<svg><g transform='translate(10 20)'/></svg>",
+        "<svg><g transform='matrix(1 0 0 1 10 20)'/></svg>",
+        "");
+
+    // Ids and reordered 'defs' are ignored.
+    test!(equal_4,
+"<svg>
+    <defs>
+        <linearGradient id='lg1'><stop offset='0'/></linearGradient>
+        <radialGradient id='rg1'><stop offset='1'/></radialGradient>
+    </defs>
+    <rect id='rect1' fill='url(#lg1)' stroke='url(#rg1)'/>
+</svg>",
+"<svg>
+    <defs>
+        <radialGradient id='a'><stop offset='1'/></radialGradient>
+        <linearGradient id='b'><stop offset='0'/></linearGradient>
+    </defs>
+    <rect fill='url(#b)' stroke='url(#a)'/>
+</svg>",
+        "");
+
+    test!(diff_attr_1,
+        "<svg><rect x='10' y='5' fill='red'/></svg>",
+        "<svg><rect x='11' width='5' fill='red'/></svg>",
+"/svg[1]/rect[1]: attribute 'x' is changed from '10' to '11'
+/svg[1]/rect[1]: attribute 'y=\"5\"' is removed
+/svg[1]/rect[1]: attribute 'width=\"5\"' is added
+");
+
+    test!(diff_elem_1,
+        "<svg><g/><rect/><rect/></svg>",
+        "<svg><g/><circle/></svg>",
+"/svg[1]/rect[1]: element 'rect' is replaced with 'circle'
+/svg[1]/rect[2]: element 'rect' is removed
+");
+
+    test!(diff_text_1,
+        "<svg><text>Text</text></svg>",
+        "<svg><text>Text 2</text></svg>",
+        "/svg[1]/text[1]/#text[1]: text is changed from 'Text' to 'Text 2'\n");
+
+    test!(diff_link_1,
+"<svg>
+    <linearGradient id='lg1'/>
+    <radialGradient id='rg1'/>
+    <rect fill='url(#lg1)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1'/>
+    <radialGradient id='rg1'/>
+    <rect fill='url(#rg1)'/>
+</svg>",
+"/svg[1]/rect[1]: attribute 'fill' is changed from 'link to 'linearGradient'' \
+to 'link to 'radialGradient''
+");
+}
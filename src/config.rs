@@ -0,0 +1,164 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Reads and writes configuration files in the TOML format.
+//
+// A configuration is a list of `key = value` pairs, where the value
+// is a boolean, a number or a string. Keys can be grouped into tables,
+// which are ignored, since all option names are unique.
+
+use std::fmt;
+
+use toml;
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(String),
+    String(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(ref n) => write!(f, "{}", n),
+            Value::String(ref s) => write!(f, "{}", toml::Value::String(s.clone())),
+        }
+    }
+}
+
+impl Value {
+    /// Returns a value in the command-line form.
+    pub fn to_arg(&self) -> String {
+        match *self {
+            Value::Bool(b) => b.to_string(),
+            Value::Number(ref n) => n.clone(),
+            Value::String(ref s) => s.clone(),
+        }
+    }
+}
+
+/// Parses a configuration file into a list of key-value pairs.
+pub fn parse(text: &str) -> Result<Vec<(String, Value)>, String> {
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| {
+        match e.span() {
+            Some(span) => {
+                let line = text[..span.start].matches('\n').count() + 1;
+                format!("{} at line {}", e.message().trim_end_matches('.'), line)
+            }
+            None => e.message().to_string(),
+        }
+    })?;
+
+    let mut list = Vec::new();
+    flatten(table, &mut list)?;
+    Ok(list)
+}
+
+fn flatten(table: toml::Table, list: &mut Vec<(String, Value)>) -> Result<(), String> {
+    for (key, value) in table {
+        let value = match value {
+            toml::Value::Boolean(b) => Value::Bool(b),
+            toml::Value::Integer(n) => Value::Number(n.to_string()),
+            toml::Value::Float(n) if n.is_finite() => Value::Number(n.to_string()),
+            toml::Value::String(s) => Value::String(s),
+            toml::Value::Table(t) => {
+                flatten(t, list)?;
+                continue;
+            }
+            _ => return Err(format!("invalid value of '{}'", key)),
+        };
+
+        if list.iter().any(|(k, _)| *k == key) {
+            return Err(format!("duplicated key '{}'", key));
+        }
+
+        list.push((key, value));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_1() {
+        let text = "\
+# Comment
+remove-comments = true
+indent = \"2\" # Comment
+pattern = '*.svg'
+dpi = 96
+simplify-paths = 0.5
+";
+
+        assert_eq!(parse(text).unwrap(), vec![
+            ("remove-comments".to_string(), Value::Bool(true)),
+            ("indent".to_string(), Value::String("2".to_string())),
+            ("pattern".to_string(), Value::String("*.svg".to_string())),
+            ("dpi".to_string(), Value::Number("96".to_string())),
+            ("simplify-paths".to_string(), Value::Number("0.5".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn parse_2() {
+        assert_eq!(parse("pattern = \"#\\\"a\\\"\"").unwrap(), vec![
+            ("pattern".to_string(), Value::String("#\"a\"".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn parse_3() {
+        let text = "\
+[elements]
+remove-title = false
+
+[output]
+indent = '''
+2'''
+precision-coordinates = 1_0
+";
+
+        assert_eq!(parse(text).unwrap(), vec![
+            ("remove-title".to_string(), Value::Bool(false)),
+            ("indent".to_string(), Value::String("2".to_string())),
+            ("precision-coordinates".to_string(), Value::Number("10".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn parse_err_1() {
+        assert_eq!(parse("\nindent").unwrap_err(), "key with no value, expected `=` at line 2");
+        assert!(parse("indent = none").is_err());
+        assert!(parse("dpi = 1\ndpi = 2").is_err());
+        assert_eq!(parse("dpi = [1]").unwrap_err(), "invalid value of 'dpi'");
+        assert_eq!(parse("dpi = 1\n[a]\ndpi = 2").unwrap_err(), "duplicated key 'dpi'");
+    }
+
+    #[test]
+    fn write_1() {
+        assert_eq!(Value::Bool(false).to_string(), "false");
+        assert_eq!(Value::Number("6".to_string()).to_string(), "6");
+        assert_eq!(Value::String("a\"b".to_string()).to_string(), "'a\"b'");
+        assert_eq!(Value::String("2".to_string()).to_string(), "\"2\"");
+    }
+}
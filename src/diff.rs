@@ -0,0 +1,309 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::cmp;
+
+// A number of unchanged lines around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Generates a unified diff between two texts.
+///
+/// Returns an empty string if texts are equal.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let mut ops = Vec::with_capacity(cmp::max(old.len(), new.len()));
+    diff(&old, &new, &mut ops);
+
+    // Deleted lines are printed before the inserted ones in each block of changes.
+    for block in ops.split_mut(|op| matches!(*op, Op::Equal(_))) {
+        block.sort_by_key(|op| matches!(*op, Op::Insert(_)));
+    }
+
+    let changes: Vec<usize> = ops.iter().enumerate()
+        .filter(|&(_, op)| !matches!(*op, Op::Equal(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Split changes into hunks. Changes that are close to each other
+    // are merged, so their contexts do not overlap.
+    let mut hunks = Vec::new();
+    let mut start = changes[0];
+    let mut end = changes[0] + 1;
+    for &idx in &changes[1..] {
+        if idx - end > CONTEXT * 2 {
+            hunks.push((start, end));
+            start = idx;
+        }
+        end = idx + 1;
+    }
+    hunks.push((start, end));
+
+    let mut text = format!("--- {}\n+++ {}\n", old_name, new_name);
+    for (start, end) in hunks {
+        let start = start.saturating_sub(CONTEXT);
+        let end = cmp::min(end + CONTEXT, ops.len());
+        write_hunk(&ops, start, end, &mut text);
+    }
+
+    text
+}
+
+fn write_hunk(ops: &[Op], start: usize, end: usize, text: &mut String) {
+    let is_old = |op: &&Op| !matches!(**op, Op::Insert(_));
+    let is_new = |op: &&Op| !matches!(**op, Op::Delete(_));
+
+    let old_before = ops[..start].iter().filter(is_old).count();
+    let new_before = ops[..start].iter().filter(is_new).count();
+    let old_len = ops[start..end].iter().filter(is_old).count();
+    let new_len = ops[start..end].iter().filter(is_new).count();
+
+    // An empty range is referenced by the line before it.
+    let old_start = if old_len == 0 { old_before } else { old_before + 1 };
+    let new_start = if new_len == 0 { new_before } else { new_before + 1 };
+
+    text.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_len, new_start, new_len));
+    for op in &ops[start..end] {
+        let (prefix, line) = match *op {
+            Op::Equal(line) => (' ', line),
+            Op::Delete(line) => ('-', line),
+            Op::Insert(line) => ('+', line),
+        };
+
+        text.push(prefix);
+        text.push_str(line);
+        text.push('\n');
+    }
+}
+
+// Calculates the shortest edit script using the linear space
+// variation of the Myers' algorithm.
+fn diff<'a>(old: &[&'a str], new: &[&'a str], ops: &mut Vec<Op<'a>>) {
+    let prefix = old.iter().zip(new).take_while(|&(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev())
+        .take_while(|&(a, b)| a == b).count();
+
+    ops.extend(old[..prefix].iter().map(|line| Op::Equal(line)));
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+    if old_mid.is_empty() || new_mid.is_empty() {
+        ops.extend(old_mid.iter().map(|line| Op::Delete(line)));
+        ops.extend(new_mid.iter().map(|line| Op::Insert(line)));
+    } else {
+        match bisect(old_mid, new_mid) {
+            Some((x, y)) => {
+                diff(&old_mid[..x], &new_mid[..y], ops);
+                diff(&old_mid[x..], &new_mid[y..], ops);
+            }
+            None => {
+                ops.extend(old_mid.iter().map(|line| Op::Delete(line)));
+                ops.extend(new_mid.iter().map(|line| Op::Insert(line)));
+            }
+        }
+    }
+
+    ops.extend(old[old.len() - suffix..].iter().map(|line| Op::Equal(line)));
+}
+
+// Finds the middle snake of the edit graph by walking
+// from both ends at the same time.
+//
+// Returns `None` if texts have nothing in common.
+fn bisect(old: &[&str], new: &[&str]) -> Option<(usize, usize)> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max_d = (n + m + 1) / 2;
+    let offset = max_d;
+    let len = (2 * max_d + 2) as usize;
+
+    // Furthest reaching x for each diagonal in the forward and the reverse directions.
+    let mut v1 = vec![-1isize; len];
+    let mut v2 = vec![-1isize; len];
+    v1[offset as usize + 1] = 0;
+    v2[offset as usize + 1] = 0;
+
+    let delta = n - m;
+    // With an odd delta, paths overlap during the forward pass.
+    let front = delta % 2 != 0;
+
+    // Diagonals that went out of the graph are skipped.
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+    for d in 0..max_d {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let k1_idx = (offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && v1[k1_idx - 1] < v1[k1_idx + 1]) {
+                v1[k1_idx + 1]
+            } else {
+                v1[k1_idx - 1] + 1
+            };
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && old[x1 as usize] == new[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            v1[k1_idx] = x1;
+
+            if x1 > n {
+                k1_end += 2;
+            } else if y1 > m {
+                k1_start += 2;
+            } else if front {
+                let k2_idx = offset + delta - k1;
+                if k2_idx >= 0 && k2_idx < len as isize && v2[k2_idx as usize] != -1 {
+                    let x2 = n - v2[k2_idx as usize];
+                    if x1 >= x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+
+            k1 += 2;
+        }
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let k2_idx = (offset + k2) as usize;
+            let mut x2 = if k2 == -d || (k2 != d && v2[k2_idx - 1] < v2[k2_idx + 1]) {
+                v2[k2_idx + 1]
+            } else {
+                v2[k2_idx - 1] + 1
+            };
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && old[(n - x2 - 1) as usize] == new[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            v2[k2_idx] = x2;
+
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !front {
+                let k1_idx = offset + delta - k2;
+                if k1_idx >= 0 && k1_idx < len as isize && v1[k1_idx as usize] != -1 {
+                    let x1 = v1[k1_idx as usize];
+                    let y1 = offset + x1 - k1_idx;
+                    if x1 >= n - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+
+            k2 += 2;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_1() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new"), "");
+    }
+
+    #[test]
+    fn diff_2() {
+        assert_eq!(unified_diff("a\nb\nc\n", "a\nc\nd\n", "old", "new"),
+"--- old
++++ new
+@@ -1,3 +1,3 @@
+ a
+-b
+ c
++d
+");
+    }
+
+    #[test]
+    fn diff_3() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n12\n";
+        assert_eq!(unified_diff(old, new, "old", "new"),
+"--- old
++++ new
+@@ -1,3 +1,4 @@
++0
+ 1
+ 2
+ 3
+@@ -8,5 +9,4 @@
+ 8
+ 9
+ 10
+-11
+ 12
+");
+    }
+
+    #[test]
+    fn diff_4() {
+        assert_eq!(unified_diff("a\nc\na\n", "c\nb\n", "old", "new"),
+"--- old
++++ new
+@@ -1,3 +1,2 @@
+-a
+ c
+-a
++b
+");
+    }
+
+    #[test]
+    fn diff_5() {
+        assert_eq!(unified_diff("", "a\n", "old", "new"),
+"--- old
++++ new
+@@ -0,0 +1,1 @@
++a
+");
+    }
+
+    #[test]
+    fn diff_6() {
+        assert_eq!(unified_diff("a\nb\nc\n", "x\ny\n", "old", "new"),
+"--- old
++++ new
+@@ -1,3 +1,2 @@
+-a
+-b
+-c
++x
++y
+");
+    }
+}
@@ -0,0 +1,259 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::borrow::Cow;
+
+use svgdom::{
+    AttributeId as AId,
+    AttributeValue,
+    Document,
+    ElementId,
+    NodeType,
+    ParseOptions,
+};
+use task::find_styles;
+use task::utils::css::{
+    selector_names,
+    selectors,
+    split_rules,
+};
+
+// A class names prefix from the Private Use Area.
+//
+// The parser removes resolved classes, so we are adding a marked copy,
+// which will be preserved as unresolved.
+const CLASS_MARKER: char = '\u{E002}';
+
+/// Rules from embedded stylesheets that cannot be resolved into attributes.
+///
+/// The parser supports only static rules, so at-rules, like `@media`, and rules with
+/// pseudo-classes, like `:hover`, are removed from stylesheets before parsing
+/// and inserted back into a `style` element after parsing as is.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct DynamicCss {
+    css: Vec<String>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+}
+
+impl DynamicCss {
+    /// Removes dynamic rules from embedded stylesheets.
+    ///
+    /// Classes used by such rules are duplicated with a marker, so the parser will keep them.
+    /// Use `restore_classes` after parsing to remove markers.
+    ///
+    /// Returns the text that should be parsed instead of the original one.
+    pub fn extract<'a>(text: &'a str) -> (Cow<'a, str>, DynamicCss) {
+        let mut dynamic = DynamicCss::default();
+        if !text.contains("<style") {
+            return (Cow::Borrowed(text), dynamic);
+        }
+
+        let mut new_text = String::with_capacity(text.len());
+        let mut pos = 0;
+        for (start, end) in find_styles(text) {
+            new_text.push_str(&text[pos..start]);
+
+            let css = &text[start..end];
+            match split_rules(css) {
+                Some(rules) => {
+                    for (rule_start, rule_end) in rules {
+                        let rule = &css[rule_start..rule_end];
+                        if is_dynamic(rule) {
+                            dynamic.collect_names(rule);
+                            dynamic.css.push(rule.to_string());
+                        } else {
+                            new_text.push_str(rule);
+                        }
+                    }
+                }
+                None => new_text.push_str(css),
+            }
+
+            pos = end;
+        }
+        new_text.push_str(&text[pos..]);
+
+        if dynamic.is_empty() {
+            return (Cow::Borrowed(text), dynamic);
+        }
+
+        let new_text = dynamic.mark_classes(&new_text);
+        (Cow::Owned(new_text), dynamic)
+    }
+
+    /// Checks that there is nothing to write.
+    pub fn is_empty(&self) -> bool {
+        self.css.is_empty()
+    }
+
+    /// Returns parsing options, which are preserving unresolved classes when needed.
+    pub fn parse_options(&self, opt: &ParseOptions) -> ParseOptions {
+        ParseOptions {
+            skip_unresolved_classes: opt.skip_unresolved_classes && self.is_empty(),
+            .. *opt
+        }
+    }
+
+    /// Returns ids referenced by dynamic rules.
+    ///
+    /// Rules are not visible to the cleaning tasks, so such elements must be kept.
+    pub fn referenced_ids(&self) -> Vec<String> {
+        self.ids.clone()
+    }
+
+    /// Removes markers from classes, which were marked by `extract`.
+    pub fn restore_classes(&self, doc: &Document) {
+        if self.classes.is_empty() {
+            return;
+        }
+
+        for mut node in doc.descendants() {
+            let mut attrs = node.attributes_mut();
+            if let Some(&mut AttributeValue::String(ref mut s)) = attrs.get_value_mut(AId::Class) {
+                if s.contains(CLASS_MARKER) {
+                    let mut names: Vec<&str> = Vec::new();
+                    for name in s.split_whitespace().map(|n| n.trim_start_matches(CLASS_MARKER)) {
+                        if !names.contains(&name) {
+                            names.push(name);
+                        }
+                    }
+                    *s = names.join(" ");
+                }
+            }
+        }
+    }
+
+    /// Inserts dynamic rules into a new `style` element at the start of the root element.
+    ///
+    /// This way they are visible to the cleaning tasks.
+    pub fn insert(&self, doc: &mut Document) {
+        if self.is_empty() {
+            return;
+        }
+
+        let css = self.css.iter().map(|s| s.trim()).collect::<Vec<_>>().join("");
+
+        // doc must contain 'svg' node, so we can safely unwrap.
+        let mut svg = doc.svg_element().unwrap();
+        let mut style_node = doc.create_element(ElementId::Style);
+        // Keep selectors like 'a > b' readable.
+        let node_type = if css.contains(['<', '>', '&']) { NodeType::Cdata } else { NodeType::Text };
+        let text_node = doc.create_node(node_type, &css);
+        style_node.append(&text_node);
+        svg.prepend(&style_node);
+    }
+
+    fn collect_names(&mut self, rule: &str) {
+        for selector in selectors(rule) {
+            for (names, prefix) in [(&mut self.ids, '#'), (&mut self.classes, '.')] {
+                for name in selector_names(selector, prefix) {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+    }
+
+    // Adds marked copies of the used classes to 'class' attributes.
+    fn mark_classes(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut s = text;
+        while let Some(idx) = s.find("class=") {
+            let value_start = idx + 7;
+            let quote = match s[idx + 6..].chars().next() {
+                Some(c) if c == '"' || c == '\'' => c,
+                _ => {
+                    out.push_str(&s[..idx + 6]);
+                    s = &s[idx + 6..];
+                    continue;
+                }
+            };
+
+            // Skip attributes like 'data-class'.
+            let is_attr = s[..idx].chars().next_back().is_some_and(char::is_whitespace);
+            let value_end = match s[value_start..].find(quote) {
+                Some(len) if is_attr => value_start + len,
+                _ => {
+                    out.push_str(&s[..value_start]);
+                    s = &s[value_start..];
+                    continue;
+                }
+            };
+
+            out.push_str(&s[..value_start]);
+            let mut classes = Vec::new();
+            for name in s[value_start..value_end].split_whitespace() {
+                classes.push(name.to_string());
+                if self.classes.iter().any(|c| c == name) {
+                    classes.push(format!("{}{}", CLASS_MARKER, name));
+                }
+            }
+            out.push_str(&classes.join(" "));
+
+            s = &s[value_end..];
+        }
+        out.push_str(s);
+
+        out
+    }
+}
+
+// Checks that a rule cannot be resolved by the parser.
+fn is_dynamic(rule: &str) -> bool {
+    let rule = rule.trim_start();
+    rule.starts_with('@') || rule.find('{').is_some_and(|idx| rule[..idx].contains(':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_1() {
+        let text = "<svg><style>.a{fill:red} .a:hover{fill:blue} \
+                    @media (max-width: 10px) { #b { fill: green } } rect{stroke:red}</style>\
+                    <rect class='a c' id='b'/></svg>";
+        let (new_text, dynamic) = DynamicCss::extract(text);
+        assert_eq!(new_text, "<svg><style>.a{fill:red} rect{stroke:red}</style>\
+                              <rect class='a \u{E002}a c' id='b'/></svg>");
+        assert_eq!(dynamic.css, vec![" .a:hover{fill:blue}",
+                                     " @media (max-width: 10px) { #b { fill: green } }"]);
+        assert_eq!(dynamic.referenced_ids(), vec!["b".to_string()]);
+        assert_eq!(dynamic.classes, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn extract_2() {
+        // Static rules only.
+        let text = "<svg><style>.a{fill:red}</style><rect class='a'/></svg>";
+        let (new_text, dynamic) = DynamicCss::extract(text);
+        assert_eq!(new_text, text);
+        assert!(dynamic.is_empty());
+    }
+
+    #[test]
+    fn insert_1() {
+        let dynamic = DynamicCss { css: vec![" .a:hover{fill:red}".to_string()], .. DynamicCss::default() };
+
+        let mut doc = Document::from_str("<svg><rect/></svg>").unwrap();
+        dynamic.insert(&mut doc);
+        assert_eq!(doc.to_string(), "<svg>\n    <style>.a:hover{fill:red}</style>\n    <rect/>\n</svg>\n");
+    }
+}
@@ -16,8 +16,34 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
+use std::fmt;
+use std::io;
+
 use svgdom;
 
+/// An SVG feature that can't be cleaned safely.
+#[derive(Clone,Debug,PartialEq)]
+pub enum Feature {
+    /// Conditional processing attributes.
+    ConditionalProcessing,
+    /// An `xlink:href` attribute referencing an external object.
+    ExternalHref(String),
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Feature::ConditionalProcessing => {
+                write!(f, "conditional processing attributes is not supported")
+            }
+            Feature::ExternalHref(ref link) => {
+                write!(f, "the 'xlink:href' attribute is referencing an external object '{}', \
+                           which is not supported", link)
+            }
+        }
+    }
+}
+
 error_chain! {
     types {
         Error, ErrorKind, ResultExt, Result;
@@ -28,6 +54,23 @@ error_chain! {
     }
 
     errors {
+        /// The input is not a valid SVG.
+        ///
+        /// The line and the column are counted from 1.
+        ParseError(line: usize, column: usize, msg: String) {
+            display("{} at {}:{}", msg, line, column)
+        }
+
+        /// The input contains an SVG feature, which can't be cleaned safely.
+        UnsupportedFeature(feature: Feature) {
+            display("{}", feature)
+        }
+
+        /// IO errors.
+        Io(e: io::Error) {
+            display("{}", e)
+        }
+
         UnresolvedAttribute(attr_name: String) {
             display("failed to resolved attribute '{}'", attr_name)
         }
@@ -36,21 +79,107 @@ error_chain! {
             display("the attribute '{}' is missing in the '{}' element", attr_name, tag_name)
         }
 
-        ScriptingIsNotSupported {
-            display("scripting is not supported")
+        /// The cleaned file is bigger than the original one.
+        BiggerFile {
+            display("cleaned file is bigger than original")
+        }
+
+        /// The cleaned file is rendered differently from the original one.
+        ///
+        /// Contains a percentage of different pixels.
+        RenderingMismatch(diff: f64) {
+            display("cleaned file is rendered differently from original ({:.2}% of pixels)", diff)
+        }
+
+        /// Cleaning requires more memory than allowed.
+        ///
+        /// Contains the limit in megabytes.
+        MemoryLimit(limit: usize) {
+            display("the document requires more than {} MB of memory", limit)
+        }
+
+        /// Options in the JSON form are invalid.
+        InvalidOptions(msg: String) {
+            display("invalid options: {}", msg)
         }
 
-        AnimationIsNotSupported {
-            display("animation is not supported")
+        /// A task with the specified name is not registered.
+        UnknownTask(name: String) {
+            display("unknown task '{}'", name)
         }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Error {
+        ErrorKind::Io(value).into()
+    }
+}
 
-        ConditionalProcessingIsNotSupported {
-            display("conditional processing attributes is not supported")
+impl Error {
+    /// Converts a parsing error, extracting its position when possible.
+    pub fn from_parse_error(e: svgdom::Error) -> Error {
+        use std::error::Error as StdError;
+
+        // Errors from the underlying parsers are nested and store positions
+        // in different ways, but all of them end with 'at line:column'.
+        let mut pos = None;
+        let mut cause: Option<&dyn StdError> = Some(&e);
+        while let Some(err) = cause {
+            if let Some(p) = parse_error_pos(&err.to_string()) {
+                pos = Some(p);
+            }
+            cause = err.source();
         }
 
-        ExternalHrefIsNotSupported(ref_data: String) {
-            display("the 'xlink:href' attribute is referencing an external object '{}', \
-                     which is not supported", ref_data)
+        match pos {
+            Some((msg, line, column)) => ErrorKind::ParseError(line, column, msg).into(),
+            None => e.into(),
         }
     }
 }
+
+// Splits 'message at 1:2' into a message, a line and a column.
+fn parse_error_pos(text: &str) -> Option<(String, usize, usize)> {
+    let idx = text.rfind(" at ")?;
+    let mut iter = text[idx + 4..].split(':');
+    let line = iter.next()?.parse().ok()?;
+    let column = iter.next()?.parse().ok()?;
+    if iter.next().is_some() {
+        return None;
+    }
+
+    Some((text[..idx].to_string(), line, column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ChainedErrorExt};
+
+    #[test]
+    fn parse_error_1() {
+        let e = Document::from_str("<svg>\n<rect x='q'/></svg>").err().unwrap();
+        match *Error::from_parse_error(e).kind() {
+            ErrorKind::ParseError(line, column, ref msg) => {
+                assert_eq!((line, column, msg.as_str()), (2, 10, "invalid number"));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn parse_error_2() {
+        let e = Document::from_str("<g/>").err().unwrap();
+        let e = Error::from_parse_error(e);
+        assert_eq!(e.full_chain(), "Error: the document does not have an SVG element");
+    }
+
+    #[test]
+    fn parse_error_pos_1() {
+        assert_eq!(parse_error_pos("invalid number at 1:22"),
+                   Some(("invalid number".to_string(), 1, 22)));
+        assert_eq!(parse_error_pos("invalid number"), None);
+        assert_eq!(parse_error_pos("invalid at number"), None);
+    }
+}
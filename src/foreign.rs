@@ -0,0 +1,401 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::WriteOptions;
+
+// Placeholder delimiters from the Private Use Area, which are not used by real content.
+const PLACEHOLDER_START: char = '\u{E000}';
+const PLACEHOLDER_END: char = '\u{E001}';
+
+#[derive(Clone,Debug,PartialEq)]
+struct Item {
+    content: String,
+    // Namespace declarations from the parent elements, which are used by the content.
+    namespaces: Vec<(String, String)>,
+}
+
+/// The content of `foreignObject` elements.
+///
+/// The parser drops whitespaces and SVG attributes of non-SVG elements,
+/// so the content is treated as opaque: it's replaced with placeholders
+/// before parsing and written back after cleaning as is.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct ForeignContent {
+    items: Vec<Item>,
+}
+
+impl ForeignContent {
+    /// Replaces the content of `foreignObject` elements with placeholders.
+    ///
+    /// Returns the text that should be parsed instead of the original one.
+    pub fn extract(text: &str) -> (String, ForeignContent) {
+        let mut items = Vec::new();
+        let mut namespaces = Vec::new();
+        let mut out = String::with_capacity(text.len());
+
+        let mut s = text;
+        while let Some(idx) = s.find('<') {
+            out.push_str(&s[..idx]);
+            s = &s[idx..];
+
+            let len = match markup_len(s) {
+                Some(len) => len,
+                None => break,
+            };
+
+            let tag = &s[..len];
+            out.push_str(tag);
+            s = &s[len..];
+
+            if tag.starts_with("<!") || tag.starts_with("<?") || tag.starts_with("</") {
+                continue;
+            }
+
+            collect_namespaces(tag, &mut namespaces);
+
+            let name = tag_name(tag);
+            let is_foreign_object = name == "foreignObject" || name.ends_with(":foreignObject");
+            if !is_foreign_object || tag.ends_with("/>") {
+                continue;
+            }
+
+            let content_len = match content_len(s, name) {
+                Some(len) => len,
+                None => break,
+            };
+
+            let content = &s[..content_len];
+            if is_opaque(content) {
+                out.push(PLACEHOLDER_START);
+                out.push_str(&items.len().to_string());
+                out.push(PLACEHOLDER_END);
+
+                let used = namespaces.iter()
+                    .filter(|&(prefix, _)| {
+                           content.contains(&format!("{}:", prefix))
+                        && !content.contains(&format!("xmlns:{}=", prefix))
+                    })
+                    .cloned()
+                    .collect();
+
+                items.push(Item { content: content.to_string(), namespaces: used });
+            } else {
+                out.push_str(content);
+            }
+
+            s = &s[content_len..];
+        }
+
+        out.push_str(s);
+
+        (out, ForeignContent { items })
+    }
+
+    /// Checks that there is nothing to write.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns ids referenced by the content, like `url(#id)` and `href="#id"`.
+    ///
+    /// The content is not visible to the cleaning tasks, so such elements must be kept.
+    pub fn referenced_ids(&self) -> Vec<String> {
+        let mut ids = Vec::new();
+        for item in &self.items {
+            let s = &item.content;
+            for (idx, _) in s.match_indices('#') {
+                let prefix = s[..idx].trim_end_matches(['\'', '"']);
+                if !(prefix.ends_with("url(") || prefix.ends_with("href=")) {
+                    continue;
+                }
+
+                let id: String = s[idx + 1..].chars()
+                    .take_while(|c| c.is_alphanumeric() || "-_.:".contains(*c))
+                    .collect();
+
+                if !id.is_empty() && !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        ids
+    }
+
+    /// Replaces placeholders in the written document with the original content.
+    ///
+    /// Indentation around placeholders is removed and used namespace declarations
+    /// are added to the `foreignObject` element.
+    /// Placeholders of removed elements are simply skipped.
+    pub fn write(&self, opt: &WriteOptions, buf: &mut Vec<u8>) {
+        if self.is_empty() {
+            return;
+        }
+
+        let text = match String::from_utf8(buf.clone()) {
+            Ok(text) => text,
+            Err(_) => return,
+        };
+
+        let quote = if opt.use_single_quote { '\'' } else { '"' };
+
+        let mut out = String::with_capacity(text.len());
+        let mut s = text.as_str();
+        while let Some(start) = s.find(PLACEHOLDER_START) {
+            let end = match s[start..].find(PLACEHOLDER_END) {
+                Some(idx) => start + idx,
+                None => break,
+            };
+
+            let item = match s[start + PLACEHOLDER_START.len_utf8()..end].parse::<usize>() {
+                Ok(idx) if idx < self.items.len() => &self.items[idx],
+                _ => break,
+            };
+
+            let before = s[..start].trim_end();
+            out.push_str(before);
+
+            // Add namespaces to the start tag, which is already written.
+            if out.ends_with('>') {
+                out.pop();
+                let tag_start = out.rfind('<').unwrap_or(0);
+                for (prefix, uri) in &item.namespaces {
+                    let attr = format!("xmlns:{}=", prefix);
+                    if !out[tag_start..].contains(&attr) {
+                        out.push_str(&format!(" {}{}{}{}", attr, quote, uri, quote));
+                    }
+                }
+                out.push('>');
+            }
+
+            out.push_str(&item.content);
+
+            s = s[end + PLACEHOLDER_END.len_utf8()..].trim_start();
+        }
+
+        out.push_str(s);
+
+        *buf = out.into_bytes();
+    }
+}
+
+// Returns the length of a tag, a comment, a CDATA section, a processing instruction
+// or a DOCTYPE at the start of the text.
+pub fn markup_len(s: &str) -> Option<usize> {
+    let find_end = |pat: &str| s.find(pat).map(|idx| idx + pat.len());
+
+    if s.starts_with("<!--") {
+        return find_end("-->");
+    }
+
+    if s.starts_with("<![CDATA[") {
+        return find_end("]]>");
+    }
+
+    if s.starts_with("<?") {
+        return find_end("?>");
+    }
+
+    let mut quote = None;
+    let mut depth = 0;
+    for (idx, c) in s.char_indices() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+            }
+            None => {
+                match c {
+                    '"' | '\'' => quote = Some(c),
+                    // A DOCTYPE internal subset.
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    '>' if depth == 0 => return Some(idx + 1),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    None
+}
+
+pub fn tag_name(tag: &str) -> &str {
+    let s = &tag[1..];
+    let end = s.find(|c: char| c.is_whitespace() || c == '/' || c == '>').unwrap_or(s.len());
+    &s[..end]
+}
+
+// Returns the length of the element content before the closing tag.
+fn content_len(text: &str, name: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut s = text;
+    while let Some(idx) = s.find('<') {
+        let pos = text.len() - s.len() + idx;
+        let len = markup_len(&s[idx..])?;
+        let tag = &s[idx..idx + len];
+
+        if tag.starts_with("</") {
+            if tag[2..tag.len() - 1].trim() == name {
+                if depth == 0 {
+                    return Some(pos);
+                }
+                depth -= 1;
+            }
+        } else if !tag.starts_with("<!") && !tag.starts_with("<?") && !tag.ends_with("/>")
+                  && tag_name(tag) == name {
+            depth += 1;
+        }
+
+        s = &s[idx + len..];
+    }
+
+    None
+}
+
+pub fn collect_namespaces(tag: &str, namespaces: &mut Vec<(String, String)>) {
+    for (idx, _) in tag.match_indices("xmlns:") {
+        let s = &tag[idx + 6..];
+        let eq = match s.find('=') {
+            Some(eq) => eq,
+            None => continue,
+        };
+
+        let prefix = s[..eq].trim();
+        let value = s[eq + 1..].trim_start();
+        let quote = match value.chars().next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => continue,
+        };
+
+        if let Some(end) = value[1..].find(quote) {
+            let uri = &value[1..end + 1];
+            if !namespaces.iter().any(|(p, _)| p == prefix) {
+                namespaces.push((prefix.to_string(), uri.to_string()));
+            }
+        }
+    }
+}
+
+// Checks that the content can be written back as is.
+fn is_opaque(content: &str) -> bool {
+    if content.trim().is_empty() {
+        return false;
+    }
+
+    // Already used by the placeholders.
+    if content.contains(PLACEHOLDER_START) || content.contains(PLACEHOLDER_END) {
+        return false;
+    }
+
+    // Custom entities are resolved by the parser and the DOCTYPE internal subset is removed,
+    // so such content must be parsed.
+    for (idx, _) in content.match_indices('&') {
+        let name = &content[idx + 1..];
+        let end = match name.find(';') {
+            Some(end) => end,
+            None => return false,
+        };
+
+        let name = &name[..end];
+        if !(name.starts_with('#') || ["lt", "gt", "amp", "quot", "apos"].contains(&name)) {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clean(text: &str, written: &str) -> String {
+        let (_, foreign) = ForeignContent::extract(text);
+        let mut buf = written.as_bytes().to_vec();
+        foreign.write(&WriteOptions::default(), &mut buf);
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn extract_1() {
+        let text = "<svg><foreignObject width='10'>\
+                    <div xmlns='http://www.w3.org/1999/xhtml' style='color:red'>  Text</div>\
+                    </foreignObject></svg>";
+        let (new_text, foreign) = ForeignContent::extract(text);
+        assert_eq!(new_text, "<svg><foreignObject width='10'>\u{E000}0\u{E001}</foreignObject></svg>");
+        assert_eq!(foreign.items[0].content,
+                   "<div xmlns='http://www.w3.org/1999/xhtml' style='color:red'>  Text</div>");
+    }
+
+    #[test]
+    fn extract_2() {
+        // Empty, commented out and entities.
+        let text = "<svg><foreignObject/><foreignObject> </foreignObject>\
+                    <!--<foreignObject><p/></foreignObject>-->\
+                    <foreignObject>&ent;</foreignObject></svg>";
+        let (new_text, foreign) = ForeignContent::extract(text);
+        assert_eq!(new_text, text);
+        assert!(foreign.is_empty());
+    }
+
+    #[test]
+    fn extract_3() {
+        // Nested elements.
+        let text = "<svg><foreignObject><foreignObject><p/></foreignObject></foreignObject>\
+                    <foreignObject><![CDATA[</foreignObject>]]></foreignObject></svg>";
+        let (new_text, foreign) = ForeignContent::extract(text);
+        assert_eq!(new_text, "<svg><foreignObject>\u{E000}0\u{E001}</foreignObject>\
+                              <foreignObject>\u{E000}1\u{E001}</foreignObject></svg>");
+        assert_eq!(foreign.items[0].content, "<foreignObject><p/></foreignObject>");
+        assert_eq!(foreign.items[1].content, "<![CDATA[</foreignObject>]]>");
+    }
+
+    #[test]
+    fn write_1() {
+        let text = "<svg><foreignObject><p>  Text </p></foreignObject></svg>";
+        assert_eq!(clean(text, "<svg>\n    <foreignObject>\n        \u{E000}0\u{E001}\n    </foreignObject>\n</svg>"),
+                   "<svg>\n    <foreignObject><p>  Text </p></foreignObject>\n</svg>");
+    }
+
+    #[test]
+    fn write_2() {
+        // Removed element.
+        let text = "<svg><foreignObject><p/></foreignObject></svg>";
+        assert_eq!(clean(text, "<svg/>"), "<svg/>");
+    }
+
+    #[test]
+    fn namespaces_1() {
+        let text = "<svg xmlns:html='http://www.w3.org/1999/xhtml' xmlns:a='http://a'>\
+                    <foreignObject><html:p/></foreignObject></svg>";
+        assert_eq!(clean(text, "<svg><foreignObject>\u{E000}0\u{E001}</foreignObject></svg>"),
+                   "<svg><foreignObject xmlns:html=\"http://www.w3.org/1999/xhtml\">\
+                    <html:p/></foreignObject></svg>");
+    }
+
+    #[test]
+    fn referenced_ids_1() {
+        let text = "<svg><foreignObject>\
+                    <svg><rect fill='url(#lg1)'/><use xlink:href=\"#rect1\"/><a href='#'/></svg>\
+                    </foreignObject></svg>";
+        let (_, foreign) = ForeignContent::extract(text);
+        assert_eq!(foreign.referenced_ids(), vec!["lg1".to_string(), "rect1".to_string()]);
+    }
+}
@@ -0,0 +1,138 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Reads and writes gzip data, which is used by SVGZ files,
+// and zlib data, which is used by PNG.
+
+use std::io::{
+    self,
+    Read,
+    Write,
+};
+
+use flate2::Compression;
+use flate2::read::{
+    GzDecoder,
+    ZlibDecoder,
+};
+use flate2::write::{
+    GzEncoder,
+    ZlibEncoder,
+};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Decompressed data is limited to prevent zip bombs.
+const MAX_DECOMPRESSED_SIZE: usize = 512 * 1024 * 1024;
+
+/// Checks that data starts with a gzip header.
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&GZIP_MAGIC)
+}
+
+/// Decompresses gzip data.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+    read_limited(GzDecoder::new(data), MAX_DECOMPRESSED_SIZE)
+}
+
+/// Decompresses zlib data.
+///
+/// Returns an error when the decompressed data is bigger than `limit`.
+pub fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, io::Error> {
+    read_limited(ZlibDecoder::new(data), limit)
+}
+
+fn read_limited<R: Read>(reader: R, limit: usize) -> Result<Vec<u8>, io::Error> {
+    let mut out = Vec::new();
+    // Read one byte more to detect an overflow.
+    reader.take(limit as u64 + 1).read_to_end(&mut out)?;
+
+    if out.len() > limit {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "decompressed data is too big"));
+    }
+
+    Ok(out)
+}
+
+/// Compresses data into the gzip format.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    // Writing into a vector cannot fail.
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Compresses data into the zlib format.
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_1() {
+        let data = b"<svg><rect width='10' height='10'/><rect width='10' height='10'/></svg>";
+        let compressed = compress(data);
+        assert!(is_gzip(&compressed));
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompress(&compressed).unwrap(), data.to_vec());
+    }
+
+    #[test]
+    fn round_trip_2() {
+        assert_eq!(decompress(&compress(b"")).unwrap(), Vec::<u8>::new());
+    }
+
+    // Produced by zlib.
+    #[test]
+    fn zlib_decompress_1() {
+        let data = [0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x06, 0x2c, 0x02, 0x15];
+        assert_eq!(zlib_decompress(&data, 5).unwrap(), b"hello".to_vec());
+        assert!(zlib_decompress(&data, 4).is_err());
+    }
+
+    #[test]
+    fn zlib_decompress_2() {
+        let data = [0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x06, 0x2c, 0x02, 0x16];
+        assert!(zlib_decompress(&data, 100).is_err());
+    }
+
+    #[test]
+    fn zlib_round_trip_1() {
+        let data = b"<svg><rect width='10' height='10'/><rect width='10' height='10'/></svg>";
+        assert_eq!(zlib_decompress(&zlib_compress(data), 100).unwrap(), data.to_vec());
+    }
+
+    #[test]
+    fn decompress_invalid_1() {
+        let mut data = compress(b"<svg/>");
+        let len = data.len();
+        // Break the checksum.
+        data[len - 8] ^= 0xff;
+        assert!(decompress(&data).is_err());
+    }
+
+    #[test]
+    fn decompress_invalid_2() {
+        assert!(decompress(b"<svg/>").is_err());
+    }
+}
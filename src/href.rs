@@ -0,0 +1,229 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::borrow::Cow;
+
+use foreign::markup_len;
+use options::HrefStyle;
+
+/// Converts SVG 2 `href` attributes into `xlink:href`, since only the latter is supported
+/// by the parser.
+///
+/// When both are set, `href` has a priority, so `xlink:href` is removed.
+pub fn normalize<'a>(text: &'a str) -> Cow<'a, str> {
+    if !text.contains("href") {
+        return Cow::Borrowed(text);
+    }
+
+    match rewrite_tags(text, |tag| {
+        let attrs = attributes(tag);
+        let href = attrs.iter().find(|a| a.name == "href")?;
+
+        let mut new_tag = String::with_capacity(tag.len() + 6);
+        new_tag.push_str(&tag[..href.name_start]);
+        new_tag.push_str("xlink:href");
+        new_tag.push_str(&tag[href.name_start + 4..]);
+
+        if let Some(xlink) = attrs.iter().find(|a| a.name == "xlink:href") {
+            // 'xlink:href' is after 'href' when its position is after the renamed name.
+            let shift = if xlink.start > href.name_start { 6 } else { 0 };
+            new_tag.replace_range(xlink.start + shift..xlink.end + shift, "");
+        }
+
+        Some(new_tag)
+    }) {
+        Some(new_text) => Cow::Owned(new_text),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// Resolves `HrefStyle::Auto` using the input text.
+///
+/// SVG 2 style is used only when the input doesn't have any `xlink:href` attribute.
+pub fn resolve_style(style: HrefStyle, text: &str) -> HrefStyle {
+    if style != HrefStyle::Auto {
+        return style;
+    }
+
+    let mut has_href = false;
+    let mut has_xlink = false;
+    for_each_tag(text, |tag| {
+        for attr in attributes(tag) {
+            match attr.name {
+                "href" => has_href = true,
+                "xlink:href" => has_xlink = true,
+                _ => {}
+            }
+        }
+    });
+
+    if has_href && !has_xlink { HrefStyle::Svg2 } else { HrefStyle::Xlink }
+}
+
+/// Replaces `xlink:href` attributes with `href` in the written document
+/// when SVG 2 style is used.
+pub fn write(style: HrefStyle, buf: &mut Vec<u8>) {
+    if style != HrefStyle::Svg2 {
+        return;
+    }
+
+    // The writer always produces a valid UTF-8.
+    let new_text = rewrite_tags(::std::str::from_utf8(buf).unwrap(), |tag| {
+        let attr = attributes(tag).into_iter().find(|a| a.name == "xlink:href")?;
+        Some(format!("{}{}", &tag[..attr.name_start], &tag[attr.name_start + 6..]))
+    });
+
+    if let Some(new_text) = new_text {
+        *buf = new_text.into_bytes();
+    }
+}
+
+struct Attr<'a> {
+    // A start position, including a preceding whitespace.
+    start: usize,
+    name_start: usize,
+    name: &'a str,
+    end: usize,
+}
+
+// Returns attributes of a start tag.
+fn attributes<'a>(tag: &'a str) -> Vec<Attr<'a>> {
+    let bytes = tag.as_bytes();
+    let is_space = |c: u8| c == b' ' || c == b'\t' || c == b'\n' || c == b'\r';
+
+    let mut attrs = Vec::new();
+    let mut i = tag.find(|c: char| c.is_whitespace() || c == '/' || c == '>').unwrap_or(0);
+    loop {
+        let start = i;
+        while i < bytes.len() && is_space(bytes[i]) {
+            i += 1;
+        }
+
+        if i >= bytes.len() || bytes[i] == b'/' || bytes[i] == b'>' {
+            break;
+        }
+
+        let name_start = i;
+        while i < bytes.len() && bytes[i] != b'=' && !is_space(bytes[i]) {
+            i += 1;
+        }
+        let name = &tag[name_start..i];
+
+        while i < bytes.len() && (is_space(bytes[i]) || bytes[i] == b'=') {
+            i += 1;
+        }
+
+        if i >= bytes.len() || !(bytes[i] == b'"' || bytes[i] == b'\'') {
+            break;
+        }
+
+        let quote = bytes[i];
+        i = match bytes[i + 1..].iter().position(|c| *c == quote) {
+            Some(idx) => i + idx + 2,
+            None => break,
+        };
+
+        attrs.push(Attr { start, name_start, name, end: i });
+    }
+
+    attrs
+}
+
+fn for_each_tag<F: FnMut(&str)>(text: &str, mut f: F) {
+    rewrite_tags(text, |tag| {
+        f(tag);
+        None
+    });
+}
+
+// Calls the function for each start tag and replaces the tag with the returned one.
+//
+// Returns `None` when nothing was replaced.
+fn rewrite_tags<F: FnMut(&str) -> Option<String>>(text: &str, mut f: F) -> Option<String> {
+    let mut new_text = String::new();
+    let mut is_changed = false;
+
+    let mut s = text;
+    while let Some(idx) = s.find('<') {
+        new_text.push_str(&s[..idx]);
+        s = &s[idx..];
+
+        let len = match markup_len(s) {
+            Some(len) => len,
+            None => break,
+        };
+
+        let tag = &s[..len];
+        let is_start_tag = !(tag.starts_with("<!") || tag.starts_with("<?") || tag.starts_with("</"));
+        match if is_start_tag { f(tag) } else { None } {
+            Some(new_tag) => {
+                new_text.push_str(&new_tag);
+                is_changed = true;
+            }
+            None => new_text.push_str(tag),
+        }
+
+        s = &s[len..];
+    }
+
+    new_text.push_str(s);
+
+    if is_changed { Some(new_text) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_1() {
+        assert_eq!(normalize("<svg><use href='#a' x='1'/><a\nhref=\"#b\"/></svg>"),
+                   "<svg><use xlink:href='#a' x='1'/><a\nxlink:href=\"#b\"/></svg>");
+    }
+
+    #[test]
+    fn normalize_2() {
+        // 'href' has a priority.
+        assert_eq!(normalize("<svg><use xlink:href='#a' href='#b'/><use href='#b' xlink:href='#a'/></svg>"),
+                   "<svg><use xlink:href='#b'/><use xlink:href='#b'/></svg>");
+    }
+
+    #[test]
+    fn normalize_3() {
+        // Not an attribute.
+        let text = "<svg><!-- <use href='#a'/> --><text>href='#a'</text><use data-href='#a'/></svg>";
+        assert_eq!(normalize(text), text);
+    }
+
+    #[test]
+    fn resolve_style_1() {
+        assert!(resolve_style(HrefStyle::Auto, "<svg><use href='#a'/></svg>") == HrefStyle::Svg2);
+        assert!(resolve_style(HrefStyle::Auto, "<svg><use xlink:href='#a'/><use href='#a'/></svg>")
+                == HrefStyle::Xlink);
+        assert!(resolve_style(HrefStyle::Auto, "<svg/>") == HrefStyle::Xlink);
+        assert!(resolve_style(HrefStyle::Svg2, "<svg/>") == HrefStyle::Svg2);
+    }
+
+    #[test]
+    fn write_1() {
+        let mut buf = b"<svg><use xlink:href=\"#a\"/><text>xlink:href</text></svg>".to_vec();
+        write(HrefStyle::Svg2, &mut buf);
+        assert_eq!(String::from_utf8(buf).unwrap(),
+                   "<svg><use href=\"#a\"/><text>xlink:href</text></svg>");
+    }
+}
@@ -0,0 +1,199 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// A minimal JSON parser for configuration files.
+//
+// Numbers are stored as `f64` and objects keep the keys order.
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Parses a JSON document.
+pub fn parse(text: &str) -> Result<Json, String> {
+    let mut s = Stream { text, pos: 0 };
+    let value = s.parse_value()?;
+    s.skip_spaces();
+    if s.pos != text.len() {
+        return Err(s.error("unexpected data"));
+    }
+
+    Ok(value)
+}
+
+struct Stream<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Stream<'a> {
+    fn error(&self, msg: &str) -> String {
+        let line = self.text[..self.pos].matches('\n').count() + 1;
+        format!("{} at line {}", msg, line)
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn consume(&mut self, c: char) -> Result<(), String> {
+        self.skip_spaces();
+        if self.text[self.pos..].starts_with(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn try_consume(&mut self, c: char) -> bool {
+        self.skip_spaces();
+        if self.text[self.pos..].starts_with(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        self.skip_spaces();
+        let rest = &self.text[self.pos..];
+        for &(word, ref value) in &[("null", Json::Null), ("true", Json::Bool(true)), ("false", Json::Bool(false))] {
+            if rest.starts_with(word) {
+                self.pos += word.len();
+                return Ok(value.clone());
+            }
+        }
+
+        match rest.chars().next() {
+            Some('"') => self.parse_string().map(Json::String),
+            Some('[') => {
+                self.pos += 1;
+                let mut list = Vec::new();
+                if self.try_consume(']') {
+                    return Ok(Json::Array(list));
+                }
+
+                loop {
+                    list.push(self.parse_value()?);
+                    if !self.try_consume(',') {
+                        break;
+                    }
+                }
+                self.consume(']')?;
+
+                Ok(Json::Array(list))
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if self.try_consume('}') {
+                    return Ok(Json::Object(items));
+                }
+
+                loop {
+                    self.skip_spaces();
+                    let key = self.parse_string()?;
+                    self.consume(':')?;
+                    items.push((key, self.parse_value()?));
+                    if !self.try_consume(',') {
+                        break;
+                    }
+                }
+                self.consume('}')?;
+
+                Ok(Json::Object(items))
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let len = rest.find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                              .unwrap_or(rest.len());
+                let n = rest[..len].parse::<f64>().map_err(|_| self.error("invalid number"))?;
+                self.pos += len;
+                Ok(Json::Number(n))
+            }
+            _ => Err(self.error("invalid value")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        if !self.text[self.pos..].starts_with('"') {
+            return Err(self.error("expected a string"));
+        }
+
+        let mut s = String::new();
+        let mut chars = self.text[self.pos + 1..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 2;
+                    return Ok(s);
+                }
+                '\\' => {
+                    match chars.next().map(|(_, c)| c) {
+                        Some('"') => s.push('"'),
+                        Some('\\') => s.push('\\'),
+                        Some('/') => s.push('/'),
+                        Some('n') => s.push('\n'),
+                        Some('t') => s.push('\t'),
+                        Some('r') => s.push('\r'),
+                        Some('u') => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            let c = u32::from_str_radix(&hex, 16).ok().and_then(::std::char::from_u32);
+                            s.push(c.ok_or_else(|| self.error("invalid escape"))?);
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                _ => s.push(c),
+            }
+        }
+
+        Err(self.error("unterminated string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_1() {
+        assert_eq!(parse(r#" {"a": [1, -2.5e1, "\"A"], "b": {}, "c": [true, false, null]} "#).unwrap(),
+                   Json::Object(vec![
+                       ("a".to_string(), Json::Array(vec![
+                           Json::Number(1.0), Json::Number(-25.0), Json::String("\"A".to_string()),
+                       ])),
+                       ("b".to_string(), Json::Object(Vec::new())),
+                       ("c".to_string(), Json::Array(vec![Json::Bool(true), Json::Bool(false), Json::Null])),
+                   ]));
+    }
+
+    #[test]
+    fn parse_err_1() {
+        assert_eq!(parse("{\"a\": 1,\n}").unwrap_err(), "expected a string at line 2");
+        assert_eq!(parse("[1 2]").unwrap_err(), "expected ']' at line 1");
+    }
+}
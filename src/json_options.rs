@@ -0,0 +1,356 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Cleaning options in the JSON form, which is used by the bindings.
+//
+// Options have the same names and values as in the CLI, like `{"remove-title": false}`.
+// Not specified options are set to the recommended values.
+
+use svgdom::{
+    AttributesOrder,
+    Indent,
+    ListSeparator,
+    ParseOptions,
+    WriteOptions,
+};
+
+use cleaner::Cleaner;
+use json::{
+    self,
+    Json,
+};
+use options::{
+    CleaningOptions,
+    HrefStyle,
+    SortAttributesMode,
+    SortDefsMode,
+    StyleJoinMode,
+    StylesMode,
+};
+use task::utils;
+
+type Flag<T> = fn(&mut T) -> &mut bool;
+
+static CLEANING_FLAGS: &[(&str, Flag<CleaningOptions>)] = &[
+    ("remove-processing-instructions", |o| &mut o.remove_processing_instructions),
+    ("remove-doctype", |o| &mut o.remove_doctype),
+    ("convert-cdata", |o| &mut o.convert_cdata),
+    ("remove-unused-defs", |o| &mut o.remove_unused_defs),
+    ("convert-shapes", |o| &mut o.convert_shapes),
+    ("convert-shapes-to-paths", |o| &mut o.convert_shapes_to_paths),
+    ("remove-title", |o| &mut o.remove_title),
+    ("remove-desc", |o| &mut o.remove_desc),
+    ("remove-metadata", |o| &mut o.remove_metadata),
+    ("keep-accessibility", |o| &mut o.keep_accessibility),
+    ("remove-animations", |o| &mut o.remove_animations),
+    ("remove-scripts", |o| &mut o.remove_scripts),
+    ("remove-foreign-objects", |o| &mut o.remove_foreign_objects),
+    ("sanitize", |o| &mut o.sanitize),
+    ("resolve-switch", |o| &mut o.resolve_switch),
+    ("remove-inkscape-data", |o| &mut o.remove_inkscape_data),
+    ("remove-sodipodi-data", |o| &mut o.remove_sodipodi_data),
+    ("remove-ai-data", |o| &mut o.remove_ai_data),
+    ("remove-sketch-data", |o| &mut o.remove_sketch_data),
+    ("remove-figma-data", |o| &mut o.remove_figma_data),
+    ("remove-dupl-lineargradient", |o| &mut o.remove_dupl_linear_gradients),
+    ("remove-dupl-radialgradient", |o| &mut o.remove_dupl_radial_gradients),
+    ("simplify-filters", |o| &mut o.simplify_filters),
+    ("remove-dupl-fegaussianblur", |o| &mut o.remove_dupl_fe_gaussian_blur),
+    ("remove-dupl-markers", |o| &mut o.remove_dupl_markers),
+    ("remove-dupl-patterns", |o| &mut o.remove_dupl_patterns),
+    ("optimize-images", |o| &mut o.optimize_images),
+    ("remove-dupl-images", |o| &mut o.remove_dupl_images),
+    ("ungroup-groups", |o| &mut o.ungroup_groups),
+    ("merge-transforms", |o| &mut o.merge_transforms),
+    ("ungroup-defs", |o| &mut o.ungroup_defs),
+    ("group-by-style", |o| &mut o.group_by_style),
+    ("collapse-attributes", |o| &mut o.collapse_attributes),
+    ("merge-gradients", |o| &mut o.merge_gradients),
+    ("regroup-gradient-stops", |o| &mut o.regroup_gradient_stops),
+    ("remove-invalid-stops", |o| &mut o.remove_invalid_stops),
+    ("remove-invisible-elements", |o| &mut o.remove_invisible_elements),
+    ("remove-empty-elements", |o| &mut o.remove_empty_elements),
+    ("normalize-text", |o| &mut o.normalize_text),
+    ("flatten-tspans", |o| &mut o.flatten_tspans),
+    ("remove-outside-elements", |o| &mut o.remove_outside_elements),
+    ("remove-noop-masking", |o| &mut o.remove_noop_masking),
+    ("apply-clip-paths", |o| &mut o.apply_clip_paths),
+    ("resolve-use", |o| &mut o.resolve_use),
+    ("optimize-symbols", |o| &mut o.optimize_symbols),
+    ("flatten-nested-svg", |o| &mut o.flatten_nested_svg),
+    ("merge-paths", |o| &mut o.merge_paths),
+    ("remove-version", |o| &mut o.remove_version),
+    ("remove-conditional-attributes", |o| &mut o.remove_conditional_attributes),
+    ("remove-unreferenced-ids", |o| &mut o.remove_unreferenced_ids),
+    ("trim-ids", |o| &mut o.trim_ids),
+    ("remove-unused-classes", |o| &mut o.remove_unused_classes),
+    ("trim-classes", |o| &mut o.trim_classes),
+    ("remove-text-attributes", |o| &mut o.remove_text_attributes),
+    ("remove-unused-coordinates", |o| &mut o.remove_unused_coordinates),
+    ("remove-default-attributes", |o| &mut o.remove_default_attributes),
+    ("remove-xmlns-xlink-attribute", |o| &mut o.remove_xmlns_xlink_attribute),
+    ("remove-unused-namespaces", |o| &mut o.remove_unused_namespaces),
+    ("remove-needless-attributes", |o| &mut o.remove_needless_attributes),
+    ("remove-gradient-attributes", |o| &mut o.remove_gradient_attributes),
+    ("merge-opacity", |o| &mut o.merge_opacity),
+    ("convert-units", |o| &mut o.convert_units),
+    ("apply-transform-to-gradients", |o| &mut o.apply_transform_to_gradients),
+    ("apply-transform-to-shapes", |o| &mut o.apply_transform_to_shapes),
+    ("apply-any-transform-to-shapes", |o| &mut o.apply_any_transform_to_shapes),
+    ("fit-viewbox", |o| &mut o.fit_viewbox),
+    ("translate-to-origin", |o| &mut o.translate_to_origin),
+    ("paths-to-relative", |o| &mut o.paths_to_relative),
+    ("remove-unused-segments", |o| &mut o.remove_unused_segments),
+    ("convert-segments", |o| &mut o.convert_segments),
+    ("reorder-subpaths", |o| &mut o.reorder_subpaths),
+    ("apply-transform-to-paths", |o| &mut o.apply_transform_to_paths),
+    ("minify-colors", |o| &mut o.minify_colors),
+    ("use-exponent-notation", |o| &mut o.use_exponent_notation),
+    ("append-newline", |o| &mut o.append_newline),
+];
+
+static WRITE_FLAGS: &[(&str, Flag<WriteOptions>)] = &[
+    ("trim-paths", |o| &mut o.use_compact_path_notation),
+    ("join-arcto-flags", |o| &mut o.join_arc_to_flags),
+    ("remove-dupl-cmd-in-paths", |o| &mut o.remove_duplicated_path_commands),
+    ("use-implicit-cmds", |o| &mut o.use_implicit_lineto_commands),
+    ("simplify-transforms", |o| &mut o.simplify_transform_matrices),
+    ("trim-colors", |o| &mut o.trim_hex_colors),
+];
+
+// Parsing flags are inverted, like 'remove-comments' disables 'parse_comments'.
+static PARSE_FLAGS: &[(&str, Flag<ParseOptions>)] = &[
+    ("remove-comments", |o| &mut o.parse_comments),
+    ("remove-declarations", |o| &mut o.parse_declarations),
+    ("remove-nonsvg-elements", |o| &mut o.parse_unknown_elements),
+    ("remove-nonsvg-attributes", |o| &mut o.parse_unknown_attributes),
+];
+
+/// Options parsed from the JSON form.
+pub struct Options {
+    pub parse: ParseOptions,
+    pub write: WriteOptions,
+    pub cleaning: CleaningOptions,
+    pub multipass: bool,
+    pub strict: bool,
+}
+
+/// Parses options in the JSON form.
+pub fn parse(text: &str) -> Result<Options, String> {
+    let items = match json::parse(text)? {
+        Json::Object(items) => items,
+        _ => return Err("expected an object".to_string()),
+    };
+
+    let mut opt = Options {
+        parse: Cleaner::recommended_parse_options(),
+        write: Cleaner::recommended_write_options(),
+        cleaning: CleaningOptions::recommended(),
+        multipass: false,
+        strict: false,
+    };
+
+    let mut remove_nonsvg_attributes = !opt.parse.parse_unknown_attributes;
+    for (name, value) in &items {
+        let name = name.as_str();
+        if let Some(&(_, flag)) = CLEANING_FLAGS.iter().find(|&&(n, _)| n == name) {
+            *flag(&mut opt.cleaning) = to_bool(name, value)?;
+        } else if let Some(&(_, flag)) = WRITE_FLAGS.iter().find(|&&(n, _)| n == name) {
+            *flag(&mut opt.write) = to_bool(name, value)?;
+        } else if let Some(&(_, flag)) = PARSE_FLAGS.iter().find(|&&(n, _)| n == name) {
+            *flag(&mut opt.parse) = !to_bool(name, value)?;
+            if name == "remove-nonsvg-attributes" {
+                remove_nonsvg_attributes = to_bool(name, value)?;
+            }
+        } else {
+            parse_value(name, value, &mut opt)?;
+        }
+    }
+
+    // See 'cli::gen_parse_options'.
+    if opt.cleaning.keep_accessibility {
+        opt.parse.parse_unknown_attributes = true;
+        opt.cleaning.remove_nonsvg_attributes = remove_nonsvg_attributes;
+    }
+
+    Ok(opt)
+}
+
+fn parse_value(name: &str, value: &Json, opt: &mut Options) -> Result<(), String> {
+    let err = || format!("invalid value of '{}'", name);
+
+    match name {
+        "multipass" => opt.multipass = to_bool(name, value)?,
+        "strict" => opt.strict = to_bool(name, value)?,
+        "remove-unresolved-classes" => opt.parse.skip_unresolved_classes = to_bool(name, value)?,
+        "keep-ids" => {
+            opt.cleaning.keep_ids = match *value {
+                Json::Array(ref list) => {
+                    list.iter().map(|v| to_str(name, v).map(String::from)).collect::<Result<_, _>>()?
+                }
+                _ => to_str(name, value)?.split(',').map(|s| s.trim().to_string()).collect(),
+            };
+
+            if opt.cleaning.keep_ids.iter().any(|p| utils::new_id_regex(p).is_err()) {
+                return Err(err());
+            }
+        }
+        "indent" => {
+            opt.write.indent = match *value {
+                Json::Number(n) if (0.0..=4.0).contains(&n) && n.fract() == 0.0 => Indent::Spaces(n as u8),
+                Json::String(ref s) if s == "none" => Indent::None,
+                Json::String(ref s) if s == "tabs" => Indent::Tabs,
+                _ => return Err(err()),
+            };
+        }
+        "quote-char" => {
+            opt.write.use_single_quote = match to_str(name, value)? {
+                "double" => false,
+                "single" => true,
+                _ => return Err(err()),
+            };
+        }
+        "list-separator" => {
+            let s = to_str(name, value)?;
+            opt.write.list_separator = match s {
+                "space" | "minimal" => ListSeparator::Space,
+                "comma" => ListSeparator::Comma,
+                "comma-space" => ListSeparator::CommaSpace,
+                _ => return Err(err()),
+            };
+            opt.cleaning.minimal_list_separators = s == "minimal";
+        }
+        "sort-attributes" => {
+            let (mode, order) = match to_str(name, value)? {
+                "alphabetical" => (SortAttributesMode::Alphabetical, AttributesOrder::Alphabetical),
+                "render-friendly" => (SortAttributesMode::RenderFriendly, AttributesOrder::Specification),
+                "frequency" => (SortAttributesMode::Frequency, AttributesOrder::AsIs),
+                _ => return Err(err()),
+            };
+            opt.cleaning.sort_attributes = mode;
+            opt.write.attributes_order = order;
+        }
+        "sort-defs" => {
+            opt.cleaning.sort_defs = match to_str(name, value)? {
+                "no" => SortDefsMode::None,
+                "first-use" => SortDefsMode::FirstUse,
+                "type" => SortDefsMode::Type,
+                _ => return Err(err()),
+            };
+        }
+        "href-style" => {
+            opt.cleaning.href_style = match to_str(name, value)? {
+                "svg2" => HrefStyle::Svg2,
+                "xlink" => HrefStyle::Xlink,
+                "auto" => HrefStyle::Auto,
+                _ => return Err(err()),
+            };
+        }
+        "join-style-attributes" => {
+            opt.cleaning.join_style_attributes = match to_str(name, value)? {
+                "no" => StyleJoinMode::None,
+                "some" => StyleJoinMode::Some,
+                "all" => StyleJoinMode::All,
+                _ => return Err(err()),
+            };
+        }
+        "styles" => {
+            opt.cleaning.styles = match to_str(name, value)? {
+                "presentation" => StylesMode::Presentation,
+                "css" => StylesMode::Css,
+                "auto" => StylesMode::Auto,
+                _ => return Err(err()),
+            };
+        }
+        "coordinates-precision" => opt.cleaning.coordinates_precision = to_precision(name, value)?,
+        "properties-precision" => opt.cleaning.properties_precision = to_precision(name, value)?,
+        "paths-coordinates-precision" => {
+            opt.cleaning.paths_coordinates_precision = to_precision(name, value)?;
+        }
+        "transforms-precision" => opt.cleaning.transforms_precision = to_precision(name, value)?,
+        "simplify-paths" => {
+            opt.cleaning.simplify_paths = match *value {
+                Json::Number(n) if n >= 0.0 => n,
+                _ => return Err(err()),
+            };
+        }
+        _ => return Err(format!("unknown option '{}'", name)),
+    }
+
+    Ok(())
+}
+
+fn to_bool(name: &str, value: &Json) -> Result<bool, String> {
+    match *value {
+        Json::Bool(b) => Ok(b),
+        _ => Err(format!("'{}' must be a boolean", name)),
+    }
+}
+
+fn to_str<'a>(name: &str, value: &'a Json) -> Result<&'a str, String> {
+    match *value {
+        Json::String(ref s) => Ok(s),
+        _ => Err(format!("'{}' must be a string", name)),
+    }
+}
+
+fn to_precision(name: &str, value: &Json) -> Result<u8, String> {
+    match *value {
+        Json::Number(n) if (1.0..=12.0).contains(&n) && n.fract() == 0.0 => Ok(n as u8),
+        _ => Err(format!("'{}' must be an integer in the 1..12 range", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_1() {
+        let opt = parse(r#"{
+            "remove-title": false,
+            "trim-colors": false,
+            "remove-comments": false,
+            "multipass": true,
+            "indent": 2,
+            "keep-ids": ["logo", "icon-.*"],
+            "coordinates-precision": 3
+        }"#).unwrap();
+
+        assert!(!opt.cleaning.remove_title);
+        assert!(opt.cleaning.remove_desc);
+        assert!(!opt.write.trim_hex_colors);
+        assert!(opt.parse.parse_comments);
+        assert!(opt.multipass);
+        assert!(opt.write.indent == Indent::Spaces(2));
+        assert_eq!(opt.cleaning.keep_ids, vec!["logo".to_string(), "icon-.*".to_string()]);
+        assert_eq!(opt.cleaning.coordinates_precision, 3);
+    }
+
+    #[test]
+    fn parse_err_1() {
+        let err = |text| parse(text).err().unwrap();
+        assert_eq!(err("[]"), "expected an object");
+        assert_eq!(err(r#"{"remove-titles": true}"#), "unknown option 'remove-titles'");
+        assert_eq!(err(r#"{"remove-title": "yes"}"#), "'remove-title' must be a boolean");
+        assert_eq!(err(r#"{"indent": 5}"#), "invalid value of 'indent'");
+        assert_eq!(err(r#"{"keep-ids": "icon-("}"#), "invalid value of 'keep-ids'");
+        assert_eq!(err(r#"{"coordinates-precision": 0}"#),
+                   "'coordinates-precision' must be an integer in the 1..12 range");
+    }
+}
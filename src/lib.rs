@@ -16,7 +16,9 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
-#![forbid(unsafe_code)]
+// Only the C bindings require unsafe code.
+#![cfg_attr(not(feature = "capi"), forbid(unsafe_code))]
+#![cfg_attr(feature = "capi", deny(unsafe_code))]
 
 #[cfg(feature = "cli-parsing")]
 #[macro_use]
@@ -26,7 +28,17 @@ extern crate clap;
 extern crate log;
 #[macro_use]
 extern crate error_chain;
+extern crate flate2;
+extern crate regex;
+extern crate simplecss;
 extern crate svgdom;
+#[cfg(feature = "cli-parsing")]
+extern crate toml;
+extern crate xmlparser;
+#[cfg(feature = "verify")]
+extern crate resvg;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
 
 pub use svgdom::{
     ChainedErrorExt,
@@ -34,16 +46,69 @@ pub use svgdom::{
     WriteOptions,
 };
 
+pub use cleaner::{
+    clean_str,
+    Cleaner,
+};
+pub use compare::{
+    compare_documents,
+    DiffReport,
+    Difference,
+    DifferenceKind,
+};
 pub use options::*;
 pub use error::{
     Error,
     ErrorKind,
+    Feature,
+};
+pub use recover::{
+    Recovery,
+    RecoveryKind,
+};
+pub use registry::{
+    Position,
+    TaskRegistry,
+};
+pub use report::{
+    Report,
+    TaskStats,
+};
+pub use warning::{
+    Warning,
+    WarningCode,
 };
 
 #[cfg(feature = "cli-parsing")]
 pub mod cli;
 
+mod bytes;
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod cleaner;
+mod compare;
+#[cfg(feature = "cli-parsing")]
+mod config;
+#[cfg(feature = "cli-parsing")]
+pub mod diff;
 mod error;
+mod gzip;
 mod options;
+mod dynamic_css;
+mod foreign;
+mod href;
+mod json;
+mod json_options;
+mod prolog;
+mod recover;
+mod registry;
+pub mod report;
+mod stream;
+#[cfg(feature = "cli-parsing")]
+mod svgo;
 mod task;
+#[cfg(feature = "cli-parsing")]
+pub mod verify;
+pub mod warning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -20,10 +20,27 @@ extern crate svgcleaner;
 extern crate log;
 extern crate fern;
 
-use std::fmt;
+mod memory;
+
+use std::env;
+use std::ffi::OsString;
 use std::fs;
+use std::io;
+use std::mem;
+use std::process;
 use std::str;
-use std::path::Path;
+use std::thread;
+use std::sync::Mutex;
+use std::time::Instant;
+use std::sync::atomic::{
+    AtomicBool,
+    AtomicUsize,
+    Ordering,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
 use std::io::{
     stderr,
     Write,
@@ -31,16 +48,44 @@ use std::io::{
 
 use svgcleaner::cli::{
     self,
+    ArgMatches,
     InputFrom,
+    LogFormat,
     OutputTo,
     Key,
+    WarningsMode,
     KEYS,
 };
 use svgcleaner::{
     cleaner,
+    compare_documents,
+    diff,
+    report,
+    verify,
+    warning,
     ChainedErrorExt,
+    CleaningOptions,
+    DiffReport,
+    Error,
+    ErrorKind,
+    ParseOptions,
+    Report,
+    TaskRegistry,
+    Warning,
+    WarningCode,
+    WriteOptions,
 };
 
+#[global_allocator]
+static ALLOCATOR: memory::CountingAllocator = memory::CountingAllocator;
+
+// Set by '--log-format'. The logger is created before arguments are parsed.
+static LOG_JSON: AtomicBool = AtomicBool::new(false);
+
+// A DOM takes several times more memory than the text it's parsed from.
+// Used only to fail early, the actual usage is checked after each task.
+const DOM_SIZE_FACTOR: usize = 12;
+
 macro_rules! try_msg {
     ($e:expr) => ({
         match $e {
@@ -53,15 +98,231 @@ macro_rules! try_msg {
     })
 }
 
+struct Options {
+    parse: ParseOptions,
+    write: WriteOptions,
+    cleaning: CleaningOptions,
+    tasks: TaskRegistry,
+}
+
+// Sizes of an uncompressed data and the actual input and output ones.
+#[derive(Clone,Copy,Default)]
+struct Stats {
+    input_size: usize,
+    output_size: usize,
+    input_file_size: usize,
+    output_file_size: usize,
+    // Either the input or the output is compressed.
+    is_compressed: bool,
+}
+
+impl Stats {
+    fn add(&mut self, other: &Stats) {
+        self.input_size += other.input_size;
+        self.output_size += other.output_size;
+        self.input_file_size += other.input_file_size;
+        self.output_file_size += other.output_file_size;
+        self.is_compressed |= other.is_compressed;
+    }
+}
+
 fn main() {
+    // The level is set by 'log::set_max_level', so it can be changed by '--verbose'.
     fern::Dispatch::new()
-        .format(log_format)
-        .level(log::LevelFilter::Warn)
-        .chain(std::io::stderr())
+        .level(log::LevelFilter::Trace)
+        .chain(fern::Output::call(log_record))
         .apply().unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let mut argv: Vec<OsString> = env::args_os().collect();
+    let mut args = parse_args(&argv);
+
+    // Options from the config file and the preset are inserted before the command-line ones,
+    // which are skipped by the 'cli::config_args' and 'cli::preset_args'.
+    // So the command line always takes precedence over the config file,
+    // the config file takes precedence over the SVGO config
+    // and the SVGO config takes precedence over the preset.
+    if let Some(path) = args.value_of(KEYS[Key::Config]).map(String::from) {
+        let flags = try_msg!(cli::config_args(&args, &path));
+        argv = insert_flags(&argv, flags);
+        args = parse_args(&argv);
+    }
+
+    if let Some(path) = args.value_of(KEYS[Key::SvgoConfig]).map(String::from) {
+        let flags = try_msg!(cli::svgo_config_args(&args, &path));
+        argv = insert_flags(&argv, flags);
+        args = parse_args(&argv);
+    }
+
+    if let Some(name) = args.value_of(KEYS[Key::Preset]).map(String::from) {
+        let flags = cli::preset_args(&args, &name);
+        argv = insert_flags(&argv, flags);
+        args = parse_args(&argv);
+    }
+
+    if !cli::check_values(&args) { return; }
+
+    log::set_max_level(cli::log_level(&args));
+    LOG_JSON.store(cli::log_format(&args) == LogFormat::Json, Ordering::Relaxed);
+
+    if cli::max_memory(&args).is_some() || args.is_present(KEYS[Key::VerboseStats]) {
+        memory::enable();
+    }
+
+    if let Some(path) = args.value_of(KEYS[Key::WriteConfig]) {
+        let text = cli::gen_config(&args);
+        let res = if path == "-" {
+            io::stdout().write_all(text.as_bytes())
+        } else {
+            fs::write(path, text)
+        };
+        try_msg!(res);
+        return;
+    }
+    let mut tasks = cli::gen_task_registry(&args);
+    if let Some(limit) = cli::max_memory(&args) {
+        tasks.set_checkpoint(move || memory::check(limit, 0));
+    }
+
+    let opt = Options {
+        parse: cli::gen_parse_options(&args),
+        write: cli::gen_write_options(&args),
+        cleaning: cli::gen_cleaning_options(&args),
+        tasks,
+    };
 
+    if args.is_present(KEYS[Key::Check]) {
+        let report = try_msg!(check_files(&args, &opt));
+        if !report.is_empty() {
+            print!("{}", report);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args.is_present(KEYS[Key::Bench]) {
+        bench_dir(&args, &opt);
+        return;
+    }
+
+    if args.is_present(KEYS[Key::Verify]) && !verify::is_renderer_available() {
+        writeln!(stderr(), "Error: '--verify' requires svgcleaner to be built \
+                            with the 'verify' feature.").unwrap();
+        return;
+    }
+
+    if cli::is_batch_mode(&args) {
+        process_dir(&args, &opt);
+        return;
+    }
+
+    let input = cli::input(&args);
+    let output = cli::output(&args);
+
+    if let InputFrom::File(path) = input {
+        if !Path::new(path).exists() {
+            writeln!(stderr(), "Error: input file does not exist.").unwrap();
+            return;
+        }
+    }
+
+    let mut report = Report::default();
+    let res = if is_report_needed(&args) {
+        process(&args, &opt, input, output, Some(&mut report))
+    } else {
+        process(&args, &opt, input, output, None)
+    };
+
+    match res {
+        Ok(stats) => {
+            if !is_quiet(&args) {
+                let ratio = calc_ratio(stats.input_size, stats.output_size);
+                if cli::is_dry_run(&args) {
+                    writeln!(stderr(), "Your image would be {:.2}% smaller: {}B -> {}B.",
+                             ratio, stats.input_size, stats.output_size).unwrap();
+                } else {
+                    writeln!(stderr(), "Your image is {:.2}% smaller now.", ratio).unwrap();
+                }
+                print_compressed_stats(&stats);
+
+                if args.is_present(KEYS[Key::DryRun]) {
+                    write!(stderr(), "Changes:\n{}", report::report_to_changes(&report)).unwrap();
+                }
+            }
+
+            if args.is_present(KEYS[Key::VerboseStats]) {
+                write!(stderr(), "{}", report::report_to_table(&report)).unwrap();
+                print_peak_memory();
+            }
+
+            if args.is_present(KEYS[Key::Report]) {
+                let path = match input {
+                    InputFrom::Stdin => "-",
+                    InputFrom::File(path) => path,
+                };
+
+                try_msg!(write_report(&args, &[(path.to_string(), report)]));
+            }
+        }
+        Err(e) => {
+            writeln!(stderr(), "{}.", e.full_chain()).unwrap();
+            print_hint(&e);
+        }
+    }
+}
+
+// Compares the input and the output files structurally.
+fn check_files(args: &ArgMatches, opt: &Options) -> Result<DiffReport, Error> {
+    let load = |path: &str| -> Result<_, Error> {
+        let data = cleaner::decode_data(&cleaner::load_file(path)?)?;
+        cleaner::parse_data(&data, &opt.parse)
+    };
+
+    let doc1 = load(args.value_of("in-file").unwrap())?;
+    let doc2 = load(args.value_of("out-file").unwrap())?;
+
+    Ok(compare_documents(&doc1, &doc2))
+}
+
+fn is_report_needed(args: &ArgMatches) -> bool {
+    args.is_present(KEYS[Key::Report])
+        || args.is_present(KEYS[Key::VerboseStats])
+        || args.is_present(KEYS[Key::DryRun])
+}
+
+// The report is printed to stderr by default, so other messages should be suppressed.
+fn is_quiet(args: &ArgMatches) -> bool {
+    args.is_present(KEYS[Key::Quiet])
+        || (args.is_present(KEYS[Key::Report]) && !args.is_present(KEYS[Key::ReportFile]))
+}
+
+fn write_report(args: &ArgMatches, reports: &[(String, Report)]) -> Result<(), io::Error> {
+    let text = if cli::is_deterministic(args) {
+        let mut reports = reports.to_vec();
+        for &mut (_, ref mut report) in &mut reports {
+            report.reset_elapsed();
+        }
+
+        report::reports_to_json(&reports)
+    } else {
+        report::reports_to_json(reports)
+    };
+    match args.value_of(KEYS[Key::ReportFile]) {
+        Some(path) => fs::write(path, text),
+        None => stderr().write_all(text.as_bytes()),
+    }
+}
+
+fn insert_flags(argv: &[OsString], flags: Vec<String>) -> Vec<OsString> {
+    let mut new_argv = vec![argv[0].clone()];
+    new_argv.extend(flags.into_iter().map(OsString::from));
+    new_argv.extend(argv[1..].iter().cloned());
+    new_argv
+}
+
+fn parse_args(argv: &[OsString]) -> ArgMatches<'static> {
     let app = cli::prepare_app();
-    let args = match app.get_matches_safe() {
+    match app.get_matches_from_safe(argv) {
         Ok(a) => a,
         Err(mut e) => {
             // Change case before printing an error to match svgcleaner's format.
@@ -70,132 +331,590 @@ fn main() {
             }
             e.exit();
         }
+    }
+}
+
+// Cleans all the matched files from the input directory
+// and saves them into the output one, preserving the directory structure.
+fn process_dir(args: &ArgMatches, opt: &Options) {
+    let in_dir = Path::new(args.value_of("in-file").unwrap());
+    let out_dir = match args.value_of("out-file") {
+        Some(path) if path != "-" && !args.is_present(KEYS[Key::Stdout]) => Path::new(path),
+        _ if cli::is_in_place(args) => in_dir,
+        // Output paths are still used by '--diff' and to detect compression.
+        _ if cli::is_dry_run(args) => in_dir,
+        _ => {
+            writeln!(stderr(), "Error: an output directory must be set in the batch mode.").unwrap();
+            return;
+        }
     };
 
-    if !cli::check_values(&args) { return; }
-    let parse_opt = cli::gen_parse_options(&args);
-    let write_opt = cli::gen_write_options(&args);
-    let cleaning_opt = cli::gen_cleaning_options(&args);
+    if out_dir.is_file() {
+        writeln!(stderr(), "Error: an output path must be a directory in the batch mode.").unwrap();
+        return;
+    }
 
-    let input = cli::input(&args);
-    let output = cli::output(&args);
+    let pattern = args.value_of(KEYS[Key::Pattern]).unwrap();
+    let mut files = Vec::new();
+    try_msg!(collect_files(in_dir, args.is_present(KEYS[Key::Recursive]), pattern, &mut files));
+    files.sort();
 
-    if let InputFrom::File(path) = input {
-        if !Path::new(path).exists() {
-            writeln!(stderr(), "Error: input file does not exist.").unwrap();
-            return;
+    let mut jobs = Vec::with_capacity(files.len());
+    for in_path in files {
+        // The path is always inside the input directory.
+        let mut out_path = out_dir.join(in_path.strip_prefix(in_dir).unwrap());
+
+        if args.is_present(KEYS[Key::Compress]) && !cli::is_svgz_name(&out_path.to_string_lossy()) {
+            out_path.set_extension("svgz");
+        }
+
+        let (in_file, out_file) = match (in_path.to_str(), out_path.to_str()) {
+            (Some(in_file), Some(out_file)) => (in_file, out_file),
+            _ => {
+                writeln!(stderr(), "Error: {}: invalid file name.", in_path.display()).unwrap();
+                continue;
+            }
+        };
+
+        if cli::is_dry_run(args) {
+            jobs.push((in_file.to_string(), out_file.to_string()));
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                writeln!(stderr(), "Error: {}: {}.", parent.display(), e).unwrap();
+                continue;
+            }
+        }
+
+        jobs.push((in_file.to_string(), out_file.to_string()));
+    }
+
+    // Files are independent, so each worker simply takes the next one from the list.
+    let next_job = AtomicUsize::new(0);
+    let total_stats = Mutex::new(Stats::default());
+    let reports = Mutex::new(Vec::new());
+    thread::scope(|s| {
+        for _ in 0..cli::jobs(args).min(jobs.len()) {
+            s.spawn(|| {
+                while let Some(job) = jobs.get(next_job.fetch_add(1, Ordering::SeqCst)) {
+                    process_job(args, opt, job, &total_stats, &reports);
+                }
+            });
+        }
+    });
+
+    let stats = total_stats.into_inner().unwrap();
+    if !is_quiet(args) && stats.input_size != 0 {
+        let ratio = calc_ratio(stats.input_size, stats.output_size);
+        if cli::is_dry_run(args) {
+            writeln!(stderr(), "Your images would be {:.2}% smaller: {}B -> {}B.",
+                     ratio, stats.input_size, stats.output_size).unwrap();
+        } else {
+            writeln!(stderr(), "Your images are {:.2}% smaller now.", ratio).unwrap();
+        }
+        print_compressed_stats(&stats);
+    }
+
+    if args.is_present(KEYS[Key::Report]) {
+        // Files are processed in parallel, so the order is random.
+        let mut reports = reports.lock().unwrap();
+        reports.sort_by(|a, b| a.0.cmp(&b.0));
+        try_msg!(write_report(args, &reports));
+    }
+
+    if args.is_present(KEYS[Key::VerboseStats]) {
+        // Print statistics of all files combined.
+        let mut total = Report::default();
+        for &(_, ref report) in reports.lock().unwrap().iter() {
+            total.merge(report);
+        }
+
+        write!(stderr(), "{}", report::report_to_table(&total)).unwrap();
+        print_peak_memory();
+    }
+}
+
+// Cleans all the matched files from the corpus directory without saving them
+// and prints the throughput of each task.
+//
+// Files are processed sequentially, so the timings are not affected by other jobs.
+fn bench_dir(args: &ArgMatches, opt: &Options) {
+    let in_dir = Path::new(args.value_of("in-file").unwrap());
+    if !in_dir.is_dir() {
+        writeln!(stderr(), "Error: a corpus directory must be set in the benchmark mode.").unwrap();
+        return;
+    }
+
+    let pattern = args.value_of(KEYS[Key::Pattern]).unwrap();
+    let mut files = Vec::new();
+    try_msg!(collect_files(in_dir, args.is_present(KEYS[Key::Recursive]), pattern, &mut files));
+    files.sort();
+
+    let mut total = Report::default();
+    let mut count = 0;
+    for path in files {
+        let file = match path.to_str() {
+            Some(file) => file,
+            None => {
+                writeln!(stderr(), "Error: {}: invalid file name.", path.display()).unwrap();
+                continue;
+            }
+        };
+
+        // Collecting a report serializes the document after each task,
+        // so the total time is measured on a separate run without it.
+        let mut report = Report::default();
+        let start = Instant::now();
+        let res = process(args, opt, InputFrom::File(file), OutputTo::File(file), None)
+            .and_then(|_| {
+                let elapsed = start.elapsed();
+                // Warnings are already printed by the first run.
+                let (res, _) = warning::collect(|| {
+                    clean_file(args, opt, InputFrom::File(file), OutputTo::File(file), Some(&mut report))
+                });
+                res.map(|_| elapsed)
+            });
+
+        match res {
+            Ok(elapsed) => {
+                report.elapsed = elapsed;
+                total.merge(&report);
+                count += 1;
+            }
+            Err(e) => {
+                writeln!(stderr(), "{}: {}.", file, e.full_chain()).unwrap();
+            }
+        }
+    }
+
+    println!("Files: {}, {}B.", count, total.input_size);
+    print!("{}", report::report_to_throughput(&total));
+}
+
+fn process_job(
+    args: &ArgMatches,
+    opt: &Options,
+    &(ref in_file, ref out_file): &(String, String),
+    total_stats: &Mutex<Stats>,
+    reports: &Mutex<Vec<(String, Report)>>
+) {
+    let mut report = Report::default();
+    let res = if is_report_needed(args) {
+        process(args, opt, InputFrom::File(in_file), OutputTo::File(out_file), Some(&mut report))
+    } else {
+        process(args, opt, InputFrom::File(in_file), OutputTo::File(out_file), None)
+    };
+
+    match res {
+        Ok(stats) => {
+            total_stats.lock().unwrap().add(&stats);
+
+            if is_report_needed(args) {
+                reports.lock().unwrap().push((in_file.clone(), report));
+            }
+
+            if !is_quiet(args) {
+                let ratio = calc_ratio(stats.input_size, stats.output_size);
+                if cli::is_dry_run(args) {
+                    writeln!(stderr(), "{}: would be {:.2}% smaller.", in_file, ratio).unwrap();
+                } else {
+                    writeln!(stderr(), "{}: {:.2}% smaller.", in_file, ratio).unwrap();
+                }
+            }
+        }
+        Err(e) => {
+            writeln!(stderr(), "{}: {}.", in_file, e.full_chain()).unwrap();
+            print_hint(&e);
+        }
+    }
+}
+
+fn collect_files(
+    dir: &Path,
+    recursive: bool,
+    pattern: &str,
+    files: &mut Vec<PathBuf>
+) -> Result<(), io::Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if recursive {
+                collect_files(&path, recursive, pattern, files)?;
+            }
+        } else {
+            let is_matched = path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| cli::matches_pattern(name, pattern));
+
+            if is_matched {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Cleans a single file and prints warnings, which were reported during cleaning.
+fn process(
+    args: &ArgMatches,
+    opt: &Options,
+    input: InputFrom,
+    output: OutputTo,
+    report: Option<&mut Report>
+) -> Result<Stats, Error> {
+    let (res, warnings) = warning::collect(|| clean_file(args, opt, input, output, report));
+    print_warnings(args, input, &warnings);
+    res
+}
+
+fn print_warnings(args: &ArgMatches, input: InputFrom, warnings: &[Warning]) {
+    let path = match input {
+        InputFrom::Stdin => "-",
+        InputFrom::File(path) => path,
+    };
+
+    for w in warnings {
+        match cli::warnings_mode(args) {
+            WarningsMode::Human => writeln!(stderr(), "Warning: {}.", w).unwrap(),
+            WarningsMode::Json => writeln!(stderr(), "{}", report::warning_to_json(path, w)).unwrap(),
+            WarningsMode::Quiet => {}
         }
     }
+}
+
+// Cleans a single file and returns its statistics.
+fn clean_file(
+    args: &ArgMatches,
+    opt: &Options,
+    input: InputFrom,
+    output: OutputTo,
+    mut report: Option<&mut Report>
+) -> Result<Stats, Error> {
+    let start = Instant::now();
 
     // Load data.
     let raw = match input {
-        InputFrom::Stdin => try_msg!(cleaner::load_stdin()),
-        InputFrom::File(path) => try_msg!(cleaner::load_file(path)),
-    };
+        InputFrom::Stdin => cleaner::load_stdin(),
+        InputFrom::File(path) => cleaner::load_file(path),
+    }?;
+
+    let data = cleaner::decode_data(&raw)?;
 
-    let on_err = || {
-        // Copy original file to destination
-        // only when both files are specified.
-        let in_file  = if let InputFrom::File(s) = input  { Some(s) } else { None };
-        let out_file = if let OutputTo::File(s)  = output { Some(s) } else { None };
+    let on_err = |e: Error| {
+        // Copy original data to destination.
+        if args.is_present(KEYS[Key::CopyOnError]) && !cli::is_dry_run(args) {
+            // Do not rewrite a file with itself.
+            let is_same_file = match (input, output) {
+                (InputFrom::File(inf), OutputTo::File(outf)) => inf == outf,
+                _ => false,
+            };
 
-        if     in_file.is_some()
-            && out_file.is_some()
-            && args.is_present(KEYS[Key::CopyOnError])
-        {
-            let inf = in_file.unwrap();
-            let outf = out_file.unwrap();
-            // Copy a file only when paths are different.
-            if inf != outf {
-                try_msg!(fs::copy(inf, outf));
+            if !is_same_file {
+                if let Err(e) = write_output(args, &raw, output) {
+                    return e.into();
+                }
             }
         }
 
-        std::process::exit(0);
+        e
     };
 
-    let input_size = raw.len();
-    let mut buf = raw.into_bytes();
+    // External references are resolved relative to the input file.
+    let mut cleaning = opt.cleaning.clone();
+    if let InputFrom::File(path) = input {
+        cleaning.resources_dir = parent_dir(path);
+    }
+
+    // Extracted images are written relative to the output file.
+    if let OutputTo::File(path) = output {
+        cleaning.output_dir = parent_dir(path);
+    }
+
+    // Dry run should not write anything.
+    if cli::is_dry_run(args) {
+        cleaning.extract_images = None;
+    }
+
+    // Fix malformations, which are accepted by browsers, but not by the parser.
+    let is_strict = args.is_present(KEYS[Key::Strict]);
+    let (fixed, recoveries) = cleaner::fix_markup(&data);
+    if is_strict {
+        if let Some(r) = recoveries.first() {
+            return Err(on_err(r.clone().into()));
+        }
+    }
+
+    for r in recoveries {
+        warning::add(r.into());
+    }
+
+    // Huge documents are stripped without building a DOM first.
+    let stripped = if cli::is_streamed(args, data.len()) {
+        let mut buf = Vec::new();
+        if let Err(e) = cleaner::strip_stream(fixed.as_bytes(), &mut buf, &opt.parse, &cleaning) {
+            return Err(on_err(e.into()));
+        }
+
+        // The input is a valid UTF-8 and it's written by whole chunks.
+        Some(String::from_utf8(buf).unwrap())
+    } else {
+        None
+    };
+    let source: &str = match stripped {
+        Some(ref s) => s,
+        None => &fixed,
+    };
+    let is_stream_only = stripped.is_some() && args.is_present(KEYS[Key::StreamOnly]);
+
+    let input_size = data.len();
+    let mut buf = Vec::with_capacity(input_size);
+    // The output of the previous pass in the multipass mode.
+    let mut prev_buf = Vec::new();
     let mut prev_size = 0;
 
     loop {
+        if is_stream_only {
+            buf.extend_from_slice(source.as_bytes());
+            break;
+        }
+
         // Parse it.
         //
-        // 'buf' is either an input data or cleaned data in the multipass mode.
+        // The input data on the first pass and cleaned data in the multipass mode.
         //
         // We can't reuse cleaned doc, because 'join_style_attributes', if enabled, breaks it.
-        let mut doc = match cleaner::parse_data(str::from_utf8(&buf).unwrap(), &parse_opt) {
-            Ok(d) => d,
-            Err(e) => {
-                writeln!(stderr(), "{}.", e.full_chain()).unwrap();
-                on_err();
-                return;
+        let text = if prev_size == 0 { source } else { str::from_utf8(&prev_buf).unwrap() };
+        // The parser doesn't preserve processing instructions and DOCTYPE.
+        let prolog = cleaner::Prolog::parse(text);
+        // The parser doesn't preserve the 'foreignObject' content either.
+        let (text, foreign) = cleaner::ForeignContent::extract(text);
+        let text = cleaner::resolve_css_variables(&text, &cleaning.css_variables);
+        // And CSS rules like ':hover' and '@media'.
+        let (text, dynamic_css) = cleaner::DynamicCss::extract(&text);
+
+        // Fail before building a DOM, which doesn't fit into the limit.
+        if let Err(e) = check_memory(args, text.len() * DOM_SIZE_FACTOR) {
+            return Err(on_err(e));
+        }
+
+        let parse_opt = dynamic_css.parse_options(&opt.parse);
+        let res = if is_strict {
+            cleaner::parse_data(&text, &parse_opt)
+        } else {
+            let mut recoveries = Vec::new();
+            let res = cleaner::parse_data_with_recovery(&text, &parse_opt, &mut recoveries);
+            for r in recoveries {
+                warning::add(r.into());
             }
+            res
         };
 
-        // Clean document.
-        match cleaner::clean_doc(&mut doc, &cleaning_opt, &write_opt) {
-            Ok(_) => {}
-            Err(e) => {
-                writeln!(stderr(), "{}.", e.full_chain()).unwrap();
-                on_err();
-                break;
-            }
+        let mut doc = match res {
+            Ok(d) => d,
+            Err(e) => return Err(on_err(e)),
+        };
+
+        // The estimate above can be too optimistic, so check the actual usage too.
+        if let Err(e) = check_memory(args, 0) {
+            return Err(on_err(e));
         }
 
+        dynamic_css.restore_classes(&doc);
+        dynamic_css.insert(&mut doc);
 
-        // Clear buffer.
+        // Elements referenced from the 'foreignObject' content are not visible to tasks.
+        let mut pass_cleaning = cleaning.clone();
+        if !cleaning.remove_foreign_objects {
+            pass_cleaning.keep_ids.extend(foreign.referenced_ids());
+        }
+        pass_cleaning.keep_ids.extend(dynamic_css.referenced_ids());
+        pass_cleaning.href_style = cleaner::resolve_href_style(cleaning.href_style, &text);
+
+        // Clean document.
         //
-        // We are reusing the same buffer for input and output data.
+        // The memory limit is checked after each task by the registry checkpoint.
+        let report = report.as_deref_mut();
+        if let Err(e) = opt.tasks.run(&mut doc, &pass_cleaning, &opt.write, report) {
+            return Err(on_err(e));
+        }
+
+        // Clear buffer.
         buf.clear();
 
         // Write buffer.
-        cleaner::write_buffer(&doc, &write_opt, &mut buf);
+        cleaner::write_buffer(&doc, &opt.write, &mut buf);
+        cleaner::write_href(pass_cleaning.href_style, &mut buf);
+        foreign.write(&opt.write, &mut buf);
+        prolog.write(&opt.cleaning, &opt.write, &mut buf);
 
         if !args.is_present(KEYS[Key::Multipass]) {
             // Do not repeat without '--multipass'.
             break;
         }
 
-        // If size is unchanged - exit from the loop.
-        if prev_size == buf.len() {
+        // If size is not reduced - exit from the loop and use the previous output.
+        //
+        // Tasks like 'ungroup_groups' and 'group_by_style' can undo each other,
+        // so we should not rely on an unchanged size only.
+        if prev_size != 0 && buf.len() >= prev_size {
+            mem::swap(&mut buf, &mut prev_buf);
             break;
         }
 
         prev_size = buf.len();
+        mem::swap(&mut buf, &mut prev_buf);
     }
 
     // Check that cleaned file is smaller.
-    if !args.is_present(KEYS[Key::AllowBiggerFile]) {
-        if buf.len() > input_size {
-            writeln!(stderr(), "Error: cleaned file is bigger than original.").unwrap();
-            on_err();
-            return;
+    if !args.is_present(KEYS[Key::AllowBiggerFile]) && buf.len() > input_size {
+        return Err(on_err(ErrorKind::BiggerFile.into()));
+    }
+
+    // Check that cleaned file is rendered the same way as the original one.
+    if args.is_present(KEYS[Key::Verify]) {
+        let resources_dir = cleaning.resources_dir.as_deref();
+        // The renderer doesn't accept malformed files either, so the fixed input is used.
+        let res = verify::render(fixed.as_bytes(), resources_dir).and_then(|before| {
+            verify::render(&buf, resources_dir).map(|after| verify::compare(&before, &after))
+        });
+
+        match res {
+            Ok(diff) if diff > cli::verify_threshold(args) => {
+                return Err(on_err(ErrorKind::RenderingMismatch(diff).into()));
+            }
+            Ok(_) => {}
+            Err(e) => return Err(on_err(e.into())),
         }
     }
 
     // Optionally add a newline to the end of the file.
     // This is placed after the check for if the file is smaller. It's OK if the
     // file grows when adding a newline, since the user explicitly wanted that.
-    if cleaning_opt.append_newline {
+    if opt.cleaning.append_newline {
         buf.push(b'\n');
     }
 
+    if args.is_present(KEYS[Key::Diff]) {
+        write_diff(input, output, &data, &buf, &opt.write)?;
+    }
+
+    let output_size = buf.len();
+    if cli::is_compress(args, output) {
+        buf = cleaner::compress_data(&buf);
+    }
+
     // Save buffer.
+    if !cli::is_dry_run(args) {
+        write_output(args, &buf, output)?;
+    }
+
+    if let Some(report) = report {
+        report.input_size = input_size;
+        report.output_size = output_size;
+        report.elapsed = start.elapsed();
+    }
+
+    Ok(Stats {
+        input_size,
+        output_size,
+        input_file_size: raw.len(),
+        output_file_size: buf.len(),
+        is_compressed: cleaner::is_compressed(&raw) || cli::is_compress(args, output),
+    })
+}
+
+fn write_output(args: &ArgMatches, data: &[u8], output: OutputTo) -> Result<(), io::Error> {
     match output {
-        OutputTo::Stdout => try_msg!(cleaner::write_stdout(&buf[..])),
-        OutputTo::File(path) => try_msg!(cleaner::save_file(&buf[..], path)),
+        OutputTo::Stdout => cleaner::write_stdout(data),
+        OutputTo::File(path) if cli::is_in_place(args) => {
+            cleaner::replace_file(data, path, args.value_of(KEYS[Key::BackupSuffix]))
+        }
+        OutputTo::File(path) => cleaner::save_file(data, path),
+    }
+}
+
+// Prints a unified diff of the pretty-printed input and output data to stdout.
+fn write_diff(
+    input: InputFrom,
+    output: OutputTo,
+    old: &str,
+    new: &[u8],
+    opt: &WriteOptions
+) -> Result<(), io::Error> {
+    // Fallback to the original text if it can't be parsed without the cleaner's preprocessing.
+    let pretty = |text: &str| cleaner::pretty_print(text, opt).unwrap_or_else(|_| text.to_string());
+
+    let old_name = match input {
+        InputFrom::Stdin => "-",
+        InputFrom::File(path) => path,
+    };
+    let new_name = match output {
+        OutputTo::File(path) if path != old_name => path.to_string(),
+        _ => format!("{} (cleaned)", old_name),
+    };
+
+    // The writer always produces a valid UTF-8.
+    let new = str::from_utf8(new).unwrap();
+    let text = diff::unified_diff(&pretty(old), &pretty(new), old_name, &new_name);
+
+    // Lock stdout, so diffs from parallel jobs are not mixed.
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(text.as_bytes())
+}
+
+// Checks that the allocated memory with an additional amount fits into '--max-memory'.
+fn check_memory(args: &ArgMatches, additional: usize) -> Result<(), Error> {
+    match cli::max_memory(args) {
+        Some(limit) => memory::check(limit, additional),
+        None => Ok(()),
     }
+}
+
+fn print_hint(e: &Error) {
+    if let ErrorKind::MemoryLimit(_) = *e.kind() {
+        writeln!(stderr(), "Hint: use '--stream-threshold' to strip huge files \
+                            without building a DOM.").unwrap();
+    }
+}
 
-    if !args.is_present(KEYS[Key::Quiet]) {
-        let ratio = 100.0 - (buf.len() as f64) / (input_size as f64) * 100.0;
-        writeln!(stderr(), "Your image is {:.2}% smaller now.", ratio).unwrap();
+fn print_peak_memory() {
+    writeln!(stderr(), "Peak memory usage: {:.1}MB.",
+             memory::peak() as f64 / 1024.0 / 1024.0).unwrap();
+}
+
+fn print_compressed_stats(stats: &Stats) {
+    if stats.is_compressed {
+        let ratio = calc_ratio(stats.input_file_size, stats.output_file_size);
+        writeln!(stderr(), "File size: {}B -> {}B ({:.2}% smaller).",
+                 stats.input_file_size, stats.output_file_size, ratio).unwrap();
     }
 }
 
-fn log_format(out: fern::FormatCallback, message: &fmt::Arguments, record: &log::Record) {
+fn parent_dir(path: &str) -> Option<String> {
+    Path::new(path).parent().and_then(|p| p.to_str()).map(String::from)
+}
+
+fn calc_ratio(input_size: usize, output_size: usize) -> f64 {
+    100.0 - (output_size as f64) / (input_size as f64) * 100.0
+}
+
+// Warnings from the parser are collected along with the cleaning ones,
+// so they are printed in the format set by '--warnings'.
+fn log_record(record: &log::Record) {
     use log::Level;
 
+    if record.level() == Level::Warn {
+        let msg = record.args().to_string();
+        let w = Warning::new(WarningCode::Parser, msg.trim_end_matches('.'));
+        if warning::try_add(w).is_ok() {
+            return;
+        }
+    }
+
     let lvl = match record.level() {
         Level::Error => "Error",
         Level::Warn => "Warning",
@@ -204,9 +923,14 @@ fn log_format(out: fern::FormatCallback, message: &fmt::Arguments, record: &log:
         Level::Trace => "Trace",
     };
 
-    out.finish(format_args!(
-        "{}: {}",
-        lvl,
-        message
-    ));
+    let msg = record.args().to_string();
+    if LOG_JSON.load(Ordering::Relaxed) {
+        let msg = msg.trim_end_matches('.');
+        writeln!(stderr(), "{}", report::log_to_json(lvl, record.target(), msg)).unwrap();
+    } else if record.level() <= Level::Warn {
+        writeln!(stderr(), "{}: {}", lvl, msg).unwrap();
+    } else {
+        // Tasks use their names as targets.
+        writeln!(stderr(), "{}: {}: {}.", lvl, record.target(), msg).unwrap();
+    }
 }
@@ -0,0 +1,118 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Memory accounting for the CLI.
+
+use std::alloc::{
+    GlobalAlloc,
+    Layout,
+    System,
+};
+use std::sync::atomic::{
+    AtomicBool,
+    AtomicIsize,
+    AtomicUsize,
+    Ordering,
+};
+
+use svgcleaner::{
+    Error,
+    ErrorKind,
+};
+
+// Counting is disabled by default, so the atomic operations are not paid for
+// when neither '--max-memory' nor '--verbose-stats' are set.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// Memory allocated before counting was enabled and freed after is subtracted too,
+// so the counter can be slightly negative.
+static ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+// The system allocator, which counts the allocated memory once enabled.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            sub(layout.size());
+            add(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn add(size: usize) {
+    if ENABLED.load(Ordering::Relaxed) {
+        let current = ALLOCATED.fetch_add(size as isize, Ordering::Relaxed) + size as isize;
+        PEAK.fetch_max(current.max(0) as usize, Ordering::Relaxed);
+    }
+}
+
+fn sub(size: usize) {
+    if ENABLED.load(Ordering::Relaxed) {
+        ALLOCATED.fetch_sub(size as isize, Ordering::Relaxed);
+    }
+}
+
+// Starts counting the allocated memory.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+// Returns the currently allocated memory in bytes.
+pub fn allocated() -> usize {
+    ALLOCATED.load(Ordering::Relaxed).max(0) as usize
+}
+
+// Checks that the allocated memory with an additional amount fits into the limit.
+pub fn check(limit: usize, additional: usize) -> Result<(), Error> {
+    if allocated().saturating_add(additional) > limit {
+        Err(ErrorKind::MemoryLimit(limit / 1024 / 1024).into())
+    } else {
+        Ok(())
+    }
+}
+
+// Returns the peak allocated memory in bytes.
+//
+// Files are processed in parallel in the batch mode, so it's a peak of the whole process.
+pub fn peak() -> usize {
+    PEAK.load(Ordering::Relaxed)
+}
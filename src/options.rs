@@ -23,44 +23,172 @@ pub enum StyleJoinMode {
     All,
 }
 
+#[derive(Clone,Copy,PartialEq)]
+pub enum StylesMode {
+    Presentation,
+    Css,
+    Auto,
+}
+
+#[derive(Clone,Copy,PartialEq)]
+pub enum ExternalRefsMode {
+    Keep,
+    Remove,
+    // Inlines small local images as data URIs and keeps other references.
+    Embed,
+}
+
+#[derive(Clone,Copy,PartialEq)]
+pub enum SortDefsMode {
+    None,
+    // Sorts by the first reference in the document order.
+    FirstUse,
+    // Sorts by the element name and then by the 'id'.
+    Type,
+}
+
+#[derive(Clone,Copy,PartialEq)]
+pub enum HrefStyle {
+    // Uses SVG 2 style when the input doesn't have any 'xlink:href' attribute.
+    Auto,
+    // Writes plain 'href'.
+    Svg2,
+    // Writes 'xlink:href'.
+    Xlink,
+}
+
+#[derive(Clone,Copy,PartialEq)]
+pub enum SortAttributesMode {
+    Alphabetical,
+    // Uses the order from the SVG spec, so presentation attributes come first.
+    RenderFriendly,
+    // Sorts by the number of occurrences in the document. Requires 'AttributesOrder::AsIs'.
+    Frequency,
+}
+
 // Documentation can be found in: docs/svgcleaner.adoc
+#[derive(Clone,PartialEq)]
 pub struct CleaningOptions {
+    pub remove_processing_instructions: bool,
+    pub remove_doctype: bool,
+    pub convert_cdata: bool,
     pub remove_unused_defs: bool,
     pub convert_shapes: bool,
+    pub convert_shapes_to_paths: bool,
     pub remove_title: bool,
     pub remove_desc: bool,
     pub remove_metadata: bool,
+    // Keep license-related RDF data, when 'metadata' is removed.
+    pub keep_license: bool,
+    // Protects 'title', 'desc' and accessibility attributes from removal.
+    pub keep_accessibility: bool,
+    // Animations are preserved by default.
+    pub remove_animations: bool,
+    // Scripts are preserved by default.
+    pub remove_scripts: bool,
+    // The 'foreignObject' content is preserved as is by default.
+    pub remove_foreign_objects: bool,
+    // Removes all the active and external content. Implies 'remove_scripts'.
+    pub sanitize: bool,
+    pub external_refs: ExternalRefsMode,
+    // A directory, which is used to resolve relative external references.
+    // Local references are not validated or embedded when not set.
+    pub resources_dir: Option<String>,
+    // Replaces 'switch' elements with their first matching child.
+    pub resolve_switch: bool,
+    // User languages, which are used to evaluate the 'systemLanguage' attribute.
+    pub system_language: Vec<String>,
+    // Non-SVG attributes are usually removed by the parser,
+    // but to keep the accessibility attributes we have to remove them after parsing.
+    pub remove_nonsvg_attributes: bool,
+    // Evaluates 'requiredFeatures' and 'requiredExtensions' outside of 'switch' elements.
+    pub remove_conditional_attributes: bool,
+    pub remove_inkscape_data: bool,
+    pub remove_sodipodi_data: bool,
+    pub remove_ai_data: bool,
+    pub remove_sketch_data: bool,
+    pub remove_figma_data: bool,
     pub remove_dupl_linear_gradients: bool,
     pub remove_dupl_radial_gradients: bool,
+    pub simplify_filters: bool,
     pub remove_dupl_fe_gaussian_blur: bool,
+    pub remove_dupl_markers: bool,
+    pub remove_dupl_patterns: bool,
+    pub optimize_images: bool,
+    pub remove_dupl_images: bool,
+    // A directory to write embedded images to, relative to 'output_dir'.
+    pub extract_images: Option<String>,
+    // A directory of the output file. The current directory is used when not set.
+    pub output_dir: Option<String>,
     pub ungroup_groups: bool,
+    pub merge_transforms: bool,
     pub ungroup_defs: bool,
     pub group_by_style: bool,
+    pub collapse_attributes: bool,
     pub merge_gradients: bool,
     pub regroup_gradient_stops: bool,
     pub remove_invalid_stops: bool,
     pub remove_invisible_elements: bool,
+    pub remove_empty_elements: bool,
+    pub normalize_text: bool,
+    pub flatten_tspans: bool,
+    pub remove_outside_elements: bool,
+    pub remove_noop_masking: bool,
+    pub apply_clip_paths: bool,
     pub resolve_use: bool,
+    pub optimize_symbols: bool,
+    pub flatten_nested_svg: bool,
+    pub merge_paths: bool,
 
     pub remove_version: bool,
     pub remove_unreferenced_ids: bool,
     pub trim_ids: bool,
+    // Regular expressions, which must match the whole id.
+    // Matched elements are never removed and their ids are never changed.
+    pub keep_ids: Vec<String>,
+    pub remove_unused_classes: bool,
+    pub trim_classes: bool,
     pub remove_text_attributes: bool,
     pub remove_unused_coordinates: bool,
     pub remove_default_attributes: bool,
     pub remove_xmlns_xlink_attribute: bool,
+    pub remove_unused_namespaces: bool,
     pub remove_needless_attributes: bool,
     pub remove_gradient_attributes: bool,
+    pub merge_opacity: bool,
     pub join_style_attributes: StyleJoinMode,
+    pub styles: StylesMode,
+    // Name and value pairs. Names can be set with or without the '--' prefix.
+    pub css_variables: Vec<(String, String)>,
+    pub convert_units: bool,
     pub apply_transform_to_gradients: bool,
     pub apply_transform_to_shapes: bool,
+    pub apply_any_transform_to_shapes: bool,
+    pub fit_viewbox: bool,
+    pub translate_to_origin: bool,
 
     pub paths_to_relative: bool,
     pub remove_unused_segments: bool,
     pub convert_segments: bool,
+    pub reorder_subpaths: bool,
+    pub minify_colors: bool,
+    pub use_exponent_notation: bool,
+    pub minimal_list_separators: bool,
     pub append_newline: bool,
+    pub sort_attributes: SortAttributesMode,
+    pub sort_defs: SortDefsMode,
+    pub href_style: HrefStyle,
     pub apply_transform_to_paths: bool,
 
+    // 1..1200
+    pub dpi: u32,
+    // 0 - disabled
+    pub simplify_paths: f64,
+    // >= 0
+    pub viewbox_padding: f64,
+    // > 0
+    pub scale_coordinates: f64,
+
     // 1..12
     pub coordinates_precision: u8,
     // 1..12
@@ -69,52 +197,217 @@ pub struct CleaningOptions {
     pub paths_coordinates_precision: u8,
     // 1..12
     pub transforms_precision: u8,
+
+    // A number of threads for CPU-heavy tasks. The output doesn't depend on it.
+    // 0 and 1 - disabled
+    pub jobs: usize,
 }
 
 // Should all be 'false'.
 impl Default for CleaningOptions {
     fn default() -> CleaningOptions {
         CleaningOptions {
+            remove_processing_instructions: false,
+            remove_doctype: false,
+            convert_cdata: false,
             remove_unused_defs: false,
             convert_shapes: false,
+            convert_shapes_to_paths: false,
             remove_title: false,
             remove_desc: false,
             remove_metadata: false,
+            keep_license: false,
+            keep_accessibility: false,
+            remove_animations: false,
+            remove_scripts: false,
+            remove_foreign_objects: false,
+            sanitize: false,
+            external_refs: ExternalRefsMode::Keep,
+            resources_dir: None,
+            resolve_switch: false,
+            system_language: vec![String::from("en")],
+            remove_nonsvg_attributes: false,
+            remove_conditional_attributes: false,
+            remove_inkscape_data: false,
+            remove_sodipodi_data: false,
+            remove_ai_data: false,
+            remove_sketch_data: false,
+            remove_figma_data: false,
             remove_dupl_linear_gradients: false,
             remove_dupl_radial_gradients: false,
+            simplify_filters: false,
             remove_dupl_fe_gaussian_blur: false,
+            remove_dupl_markers: false,
+            remove_dupl_patterns: false,
+            optimize_images: false,
+            remove_dupl_images: false,
+            extract_images: None,
+            output_dir: None,
             ungroup_groups: false,
+            merge_transforms: false,
             ungroup_defs: false,
             group_by_style: false,
+            collapse_attributes: false,
             merge_gradients: false,
             regroup_gradient_stops: false,
             remove_invalid_stops: false,
             remove_invisible_elements: false,
+            remove_empty_elements: false,
+            normalize_text: false,
+            flatten_tspans: false,
+            remove_outside_elements: false,
+            remove_noop_masking: false,
+            apply_clip_paths: false,
             resolve_use: false,
+            optimize_symbols: false,
+            flatten_nested_svg: false,
+            merge_paths: false,
 
             remove_version: false,
             remove_unreferenced_ids: false,
             trim_ids: false,
+            keep_ids: Vec::new(),
+            remove_unused_classes: false,
+            trim_classes: false,
             remove_text_attributes: false,
             remove_unused_coordinates: false,
             remove_default_attributes: false,
             remove_xmlns_xlink_attribute: false,
+            remove_unused_namespaces: false,
             remove_needless_attributes: false,
             remove_gradient_attributes: false,
+            merge_opacity: false,
             join_style_attributes: StyleJoinMode::None,
+            styles: StylesMode::Presentation,
+            css_variables: Vec::new(),
+            convert_units: false,
             apply_transform_to_gradients: false,
             apply_transform_to_shapes: false,
+            apply_any_transform_to_shapes: false,
+            fit_viewbox: false,
+            translate_to_origin: false,
 
             paths_to_relative: false,
             remove_unused_segments: false,
             convert_segments: false,
+            reorder_subpaths: false,
+            minify_colors: false,
+            use_exponent_notation: false,
+            minimal_list_separators: false,
             append_newline: false,
+            sort_attributes: SortAttributesMode::Alphabetical,
+            sort_defs: SortDefsMode::None,
+            href_style: HrefStyle::Auto,
             apply_transform_to_paths: false,
 
+            dpi: 96,
+            simplify_paths: 0.0,
+            viewbox_padding: 0.0,
+            scale_coordinates: 1.0,
+
             coordinates_precision: 6,
             properties_precision: 6,
             paths_coordinates_precision: 8,
             transforms_precision: 8,
+
+            jobs: 1,
+        }
+    }
+}
+
+impl CleaningOptions {
+    /// Returns options that are used by the CLI by default.
+    pub fn recommended() -> CleaningOptions {
+        CleaningOptions {
+            remove_processing_instructions: true,
+            remove_doctype: true,
+            convert_cdata: true,
+            remove_unused_defs: true,
+            convert_shapes: true,
+            convert_shapes_to_paths: false,
+            remove_title: true,
+            remove_desc: true,
+            remove_metadata: true,
+            keep_license: false,
+            keep_accessibility: false,
+            remove_animations: false,
+            remove_scripts: false,
+            remove_foreign_objects: false,
+            sanitize: false,
+            external_refs: ExternalRefsMode::Keep,
+            resources_dir: None,
+            resolve_switch: false,
+            system_language: vec![String::from("en")],
+            remove_nonsvg_attributes: false,
+            remove_conditional_attributes: false,
+            remove_inkscape_data: true,
+            remove_sodipodi_data: true,
+            remove_ai_data: true,
+            remove_sketch_data: true,
+            remove_figma_data: true,
+            remove_dupl_linear_gradients: true,
+            remove_dupl_radial_gradients: true,
+            simplify_filters: true,
+            remove_dupl_fe_gaussian_blur: true,
+            remove_dupl_markers: true,
+            remove_dupl_patterns: true,
+            optimize_images: false,
+            remove_dupl_images: false,
+            extract_images: None,
+            output_dir: None,
+            ungroup_groups: true,
+            merge_transforms: true,
+            ungroup_defs: true,
+            group_by_style: true,
+            collapse_attributes: true,
+            merge_gradients: true,
+            regroup_gradient_stops: true,
+            remove_invalid_stops: true,
+            remove_invisible_elements: true,
+            remove_empty_elements: true,
+            normalize_text: true,
+            flatten_tspans: true,
+            remove_outside_elements: false,
+            remove_noop_masking: true,
+            apply_clip_paths: true,
+            resolve_use: true,
+            optimize_symbols: true,
+            flatten_nested_svg: true,
+            merge_paths: true,
+
+            remove_version: true,
+            remove_unreferenced_ids: true,
+            trim_ids: true,
+            keep_ids: Vec::new(),
+            remove_unused_classes: true,
+            trim_classes: true,
+            remove_text_attributes: true,
+            remove_unused_coordinates: true,
+            remove_default_attributes: true,
+            remove_xmlns_xlink_attribute: true,
+            remove_unused_namespaces: true,
+            remove_needless_attributes: true,
+            remove_gradient_attributes: false,
+            merge_opacity: true,
+            join_style_attributes: StyleJoinMode::Some,
+            styles: StylesMode::Presentation,
+            css_variables: Vec::new(),
+            convert_units: false,
+            apply_transform_to_gradients: true,
+            apply_transform_to_shapes: true,
+            apply_any_transform_to_shapes: false,
+            fit_viewbox: false,
+            translate_to_origin: false,
+
+            paths_to_relative: true,
+            remove_unused_segments: true,
+            convert_segments: true,
+            reorder_subpaths: true,
+            minify_colors: true,
+            append_newline: false,
+            apply_transform_to_paths: false,
+
+            .. CleaningOptions::default()
         }
     }
 }
@@ -0,0 +1,231 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Indent,
+    WriteOptions,
+};
+
+use options::CleaningOptions;
+
+#[derive(Clone,Debug,PartialEq)]
+enum Item {
+    ProcessingInstruction(String),
+    Doctype(String),
+}
+
+/// Processing instructions and a DOCTYPE from the XML prolog.
+///
+/// The parser doesn't preserve them, so they are extracted from the input text
+/// and written back after cleaning.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Prolog {
+    items: Vec<Item>,
+}
+
+impl Prolog {
+    /// Extracts processing instructions and a DOCTYPE preceding the root element.
+    ///
+    /// The XML declaration is skipped, since it's preserved by the parser.
+    /// Entities are resolved by the parser, so the DOCTYPE internal subset is removed.
+    pub fn parse(text: &str) -> Prolog {
+        let mut items = Vec::new();
+        let mut s = text.trim_start_matches('\u{feff}');
+
+        loop {
+            s = s.trim_start();
+
+            if s.starts_with("<?") {
+                let end = match s.find("?>") {
+                    Some(idx) => idx + 2,
+                    None => break,
+                };
+
+                if !is_xml_declaration(&s[..end]) {
+                    items.push(Item::ProcessingInstruction(s[..end].to_string()));
+                }
+
+                s = &s[end..];
+            } else if s.starts_with("<!--") {
+                s = match s.find("-->") {
+                    Some(idx) => &s[idx + 3..],
+                    None => break,
+                };
+            } else if s.starts_with("<!DOCTYPE") {
+                let (doctype, len) = match parse_doctype(s) {
+                    Some(v) => v,
+                    None => break,
+                };
+
+                items.push(Item::Doctype(doctype));
+                s = &s[len..];
+            } else {
+                break;
+            }
+        }
+
+        Prolog { items }
+    }
+
+    /// Checks that there is nothing to write.
+    pub fn is_empty(&self, options: &CleaningOptions) -> bool {
+        !self.items.iter().any(|item| is_kept(item, options))
+    }
+
+    /// Inserts kept items after the XML declaration of the written document.
+    pub fn write(&self, options: &CleaningOptions, opt: &WriteOptions, buf: &mut Vec<u8>) {
+        if self.is_empty(options) {
+            return;
+        }
+
+        let separator: &[u8] = if opt.indent == Indent::None { b"" } else { b"\n" };
+
+        let mut data = Vec::new();
+        for item in self.items.iter().filter(|item| is_kept(item, options)) {
+            match *item {
+                Item::ProcessingInstruction(ref s) | Item::Doctype(ref s) => {
+                    data.extend_from_slice(s.as_bytes());
+                }
+            }
+            data.extend_from_slice(separator);
+        }
+
+        let mut pos = 0;
+        if buf.starts_with(b"<?xml") {
+            if let Some(idx) = buf.windows(2).position(|w| w == b"?>") {
+                pos = idx + 2;
+                // Skip a newline after the declaration too.
+                if buf[pos..].starts_with(separator) {
+                    pos += separator.len();
+                }
+            }
+        }
+
+        buf.splice(pos..pos, data);
+    }
+}
+
+fn is_kept(item: &Item, options: &CleaningOptions) -> bool {
+    match *item {
+        Item::ProcessingInstruction(_) => !options.remove_processing_instructions,
+        Item::Doctype(_) => !options.remove_doctype,
+    }
+}
+
+fn is_xml_declaration(s: &str) -> bool {
+    match s[2..].find(|c: char| c.is_whitespace() || c == '?') {
+        Some(idx) => &s[2..idx + 2] == "xml",
+        None => false,
+    }
+}
+
+// Returns a DOCTYPE without the internal subset and its length in the original text.
+fn parse_doctype(s: &str) -> Option<(String, usize)> {
+    let mut quote = None;
+    let mut subset_start = None;
+    let mut depth = 0;
+    for (idx, c) in s.char_indices() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+            }
+            None => {
+                match c {
+                    '"' | '\'' => quote = Some(c),
+                    '[' => {
+                        if depth == 0 {
+                            subset_start = Some(idx);
+                        }
+                        depth += 1;
+                    }
+                    ']' => depth -= 1,
+                    '>' if depth == 0 => {
+                        let doctype = match subset_start {
+                            Some(start) => format!("{}>", s[..start].trim_end()),
+                            None => s[..idx + 1].to_string(),
+                        };
+
+                        return Some((doctype, idx + 1));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keep_all() -> CleaningOptions {
+        CleaningOptions::default()
+    }
+
+    fn write(text: &str, out: &str, options: &CleaningOptions) -> String {
+        let mut buf = out.as_bytes().to_vec();
+        Prolog::parse(text).write(options, &WriteOptions::default(), &mut buf);
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn parse_1() {
+        let text = "\u{feff}<?xml version='1.0'?>\n\
+                    <?xml-stylesheet href='style.css'?>\n\
+                    <!-- Comment -->\n\
+                    <!DOCTYPE svg PUBLIC '-//W3C//DTD SVG 1.1//EN' 'svg11.dtd' [\n\
+                        <!ENTITY ns 'http://example.com/[ns]>'>\n\
+                    ]>\n\
+                    <svg/>";
+
+        assert_eq!(Prolog::parse(text).items, vec![
+            Item::ProcessingInstruction("<?xml-stylesheet href='style.css'?>".to_string()),
+            Item::Doctype("<!DOCTYPE svg PUBLIC '-//W3C//DTD SVG 1.1//EN' 'svg11.dtd'>".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn parse_2() {
+        assert_eq!(Prolog::parse("<svg><?pi?></svg>"), Prolog::default());
+        assert_eq!(Prolog::parse("<?xmlfoo?><svg/>").items,
+                   vec![Item::ProcessingInstruction("<?xmlfoo?>".to_string())]);
+    }
+
+    #[test]
+    fn write_1() {
+        let text = "<?xml version='1.0'?><?pi?><!DOCTYPE svg><svg/>";
+        assert_eq!(write(text, "<?xml version=\"1.0\"?>\n<svg/>", &keep_all()),
+                   "<?xml version=\"1.0\"?>\n<?pi?>\n<!DOCTYPE svg>\n<svg/>");
+        assert_eq!(write(text, "<svg/>", &keep_all()), "<?pi?>\n<!DOCTYPE svg>\n<svg/>");
+    }
+
+    #[test]
+    fn write_2() {
+        let text = "<?pi?><!DOCTYPE svg><svg/>";
+        let mut options = keep_all();
+        options.remove_doctype = true;
+        assert_eq!(write(text, "<svg/>", &options), "<?pi?>\n<svg/>");
+
+        options.remove_processing_instructions = true;
+        assert_eq!(write(text, "<svg/>", &options), "<svg/>");
+    }
+}
@@ -0,0 +1,679 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+
+use error::{
+    Error,
+    ErrorKind,
+};
+
+/// A kind of a malformation, which was fixed before parsing.
+#[derive(Clone,Debug,PartialEq)]
+pub enum RecoveryKind {
+    /// An `&`, which doesn't start a reference, like in `AT&T`. It's escaped.
+    StrayAmpersand,
+    /// An HTML entity, like `&nbsp;`. It's replaced with a character reference.
+    HtmlEntity(String),
+    /// A `<`, which doesn't start a tag. It's escaped.
+    StrayLessThan,
+    /// An attribute value without quotes. It's quoted.
+    UnquotedAttribute(String),
+    /// An attribute, which is already set on the element. The first one is kept.
+    DuplicatedAttribute(String),
+    /// An element without a closing tag. It's closed along with its parent.
+    UnclosedElement(String),
+    /// A closing tag without an opening one. It's removed.
+    UnexpectedClosingTag(String),
+    /// An attribute with an invalid value. It's removed.
+    InvalidAttribute(String),
+}
+
+impl fmt::Display for RecoveryKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecoveryKind::StrayAmpersand => write!(f, "unescaped '&'"),
+            RecoveryKind::HtmlEntity(ref name) => write!(f, "HTML entity '&{};'", name),
+            RecoveryKind::StrayLessThan => write!(f, "unescaped '<'"),
+            RecoveryKind::UnquotedAttribute(ref name) => {
+                write!(f, "unquoted value of the '{}' attribute", name)
+            }
+            RecoveryKind::DuplicatedAttribute(ref name) => {
+                write!(f, "duplicated attribute '{}'", name)
+            }
+            RecoveryKind::UnclosedElement(ref name) => write!(f, "unclosed element '{}'", name),
+            RecoveryKind::UnexpectedClosingTag(ref name) => {
+                write!(f, "unexpected closing tag '{}'", name)
+            }
+            RecoveryKind::InvalidAttribute(ref name) => {
+                write!(f, "invalid value of the '{}' attribute", name)
+            }
+        }
+    }
+}
+
+/// A malformation, which was fixed before parsing.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Recovery {
+    /// A position in the input text. The line and the column are counted from 1.
+    ///
+    /// Not set for invalid attributes, which are detected in the preprocessed text.
+    pub pos: Option<(usize, usize)>,
+    /// A kind of the malformation.
+    pub kind: RecoveryKind,
+}
+
+impl fmt::Display for Recovery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.pos {
+            Some((line, column)) => write!(f, "{} at {}:{}", self.kind, line, column),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl From<Recovery> for Error {
+    fn from(value: Recovery) -> Error {
+        match value.pos {
+            Some((line, column)) => ErrorKind::ParseError(line, column, value.kind.to_string()).into(),
+            None => value.kind.to_string().into(),
+        }
+    }
+}
+
+// Entities from HTML, which are commonly found in SVG files exported by web tools.
+//
+// Sorted by name.
+static HTML_ENTITIES: &[(&str, u32)] = &[
+    ("bull", 8226),
+    ("cent", 162),
+    ("copy", 169),
+    ("darr", 8595),
+    ("deg", 176),
+    ("divide", 247),
+    ("emsp", 8195),
+    ("ensp", 8194),
+    ("euro", 8364),
+    ("frac12", 189),
+    ("frac14", 188),
+    ("frac34", 190),
+    ("harr", 8596),
+    ("hellip", 8230),
+    ("laquo", 171),
+    ("larr", 8592),
+    ("ldquo", 8220),
+    ("lsquo", 8216),
+    ("mdash", 8212),
+    ("micro", 181),
+    ("middot", 183),
+    ("nbsp", 160),
+    ("ndash", 8211),
+    ("para", 182),
+    ("plusmn", 177),
+    ("pound", 163),
+    ("raquo", 187),
+    ("rarr", 8594),
+    ("rdquo", 8221),
+    ("reg", 174),
+    ("rsquo", 8217),
+    ("sect", 167),
+    ("shy", 173),
+    ("thinsp", 8201),
+    ("times", 215),
+    ("trade", 8482),
+    ("uarr", 8593),
+    ("yen", 165),
+];
+
+/// Fixes malformations, which are accepted by browsers, but not by the parser.
+///
+/// Stray `&` and `<` are escaped, HTML entities are replaced with character references,
+/// attribute values are quoted, duplicated attributes and unexpected closing tags are removed
+/// and unclosed elements are closed.
+///
+/// Returns the input text when there is nothing to fix.
+pub fn fix_markup<'a>(text: &'a str) -> (Cow<'a, str>, Vec<Recovery>) {
+    let mut fixer = Fixer {
+        text,
+        out: String::new(),
+        copied: 0,
+        recoveries: Vec::new(),
+        stack: Vec::new(),
+        entities: Vec::new(),
+    };
+
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while let Some(idx) = bytes[i..].iter().position(|&c| c == b'<' || c == b'&') {
+        i += idx;
+        i = if bytes[i] == b'<' { fixer.markup(i) } else { fixer.reference(i) };
+    }
+
+    fixer.finish()
+}
+
+/// Removes an attribute, which value contains the specified position.
+///
+/// Returns the new text and the name of the removed attribute.
+pub fn remove_attribute(text: &str, line: usize, column: usize) -> Option<(String, String)> {
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        _ => text.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+
+    let offset = line_start + column.checked_sub(1)?;
+    if offset >= text.len() || !text.is_char_boundary(offset) {
+        return None;
+    }
+
+    let tag_start = text[..offset].rfind('<')?;
+    let attr = Attributes::new(text, tag_name_end(text, tag_start))
+        .find(|a| a.value.start <= offset + 1 && offset <= a.value.end)?;
+
+    let mut new_text = String::with_capacity(text.len());
+    new_text.push_str(&text[..attr.start]);
+    new_text.push_str(&text[attr.end()..]);
+    Some((new_text, attr.name.to_string()))
+}
+
+struct Fixer<'a> {
+    text: &'a str,
+    out: String,
+    // The end of the text, which is already copied to the output.
+    copied: usize,
+    recoveries: Vec<(usize, RecoveryKind)>,
+    // Open elements and positions of their start tags.
+    stack: Vec<(&'a str, usize)>,
+    // Entities declared in the DOCTYPE.
+    entities: Vec<&'a str>,
+}
+
+impl<'a> Fixer<'a> {
+    // Replaces a range of the input text. Ranges must be replaced in order.
+    fn replace(&mut self, range: Range<usize>, new_text: &str) {
+        self.out.push_str(&self.text[self.copied..range.start]);
+        self.out.push_str(new_text);
+        self.copied = range.end;
+    }
+
+    fn recover(&mut self, offset: usize, kind: RecoveryKind) {
+        self.recoveries.push((offset, kind));
+    }
+
+    // Processes an '&' and returns a position after it.
+    fn reference(&mut self, i: usize) -> usize {
+        let s = &self.text[i + 1..];
+        let len = s.find(|c: char| !(c.is_alphanumeric() || "#_-.:".contains(c)))
+                   .unwrap_or(s.len());
+
+        if len != 0 && s[len..].starts_with(';') {
+            let name = &s[..len];
+            if is_valid_reference(name, &self.entities) {
+                return i + len + 2;
+            }
+
+            if let Ok(idx) = HTML_ENTITIES.binary_search_by(|&(n, _)| n.cmp(name)) {
+                let new_text = format!("&#{};", HTML_ENTITIES[idx].1);
+                self.replace(i..i + len + 2, &new_text);
+                self.recover(i, RecoveryKind::HtmlEntity(name.to_string()));
+                return i + len + 2;
+            }
+        }
+
+        self.replace(i..i + 1, "&amp;");
+        self.recover(i, RecoveryKind::StrayAmpersand);
+        i + 1
+    }
+
+    // Processes references in an attribute value.
+    fn references(&mut self, range: Range<usize>) {
+        let mut i = range.start;
+        while let Some(idx) = self.text[i..range.end].find('&') {
+            i = self.reference(i + idx);
+        }
+    }
+
+    // Processes a markup starting with '<' and returns a position after it.
+    fn markup(&mut self, i: usize) -> usize {
+        let text = self.text;
+        let s = &text[i..];
+
+        // The content of scripts and styles is a plain text in HTML.
+        if let Some(&(name, _)) = self.stack.last() {
+            if (name == "script" || name == "style") && !s.starts_with("<!")
+                && !(s[1..].starts_with('/') && s[2..].starts_with(name)) {
+                return self.stray_less_than(i);
+            }
+        }
+
+        if s.starts_with("<!--") {
+            skip_to(text, i, "-->")
+        } else if s.starts_with("<![CDATA[") {
+            skip_to(text, i, "]]>")
+        } else if s.starts_with("<?") {
+            skip_to(text, i, "?>")
+        } else if s.starts_with("<!") {
+            self.doctype(i)
+        } else if s.starts_with("</") {
+            self.end_tag(i)
+        } else if s[1..].starts_with(is_name_start) {
+            self.start_tag(i)
+        } else {
+            self.stray_less_than(i)
+        }
+    }
+
+    fn stray_less_than(&mut self, i: usize) -> usize {
+        self.replace(i..i + 1, "&lt;");
+        self.recover(i, RecoveryKind::StrayLessThan);
+        i + 1
+    }
+
+    // Collects declared entities, so references to them are not escaped.
+    fn doctype(&mut self, i: usize) -> usize {
+        let text = self.text;
+        let s = &text[i..];
+        let len = match (s.find('['), s.find('>')) {
+            (Some(a), Some(b)) if a < b => {
+                s[a..].find(']').and_then(|c| s[a + c..].find('>').map(|d| a + c + d + 1))
+            }
+            (_, Some(b)) => Some(b + 1),
+            _ => None,
+        };
+
+        let doctype = &s[..len.unwrap_or(s.len())];
+        for (idx, _) in doctype.match_indices("<!ENTITY") {
+            let decl = doctype[idx + 8..].trim_start();
+            if let Some(name) = decl.split_whitespace().next() {
+                if name != "%" {
+                    self.entities.push(name);
+                }
+            }
+        }
+
+        i + doctype.len()
+    }
+
+    fn start_tag(&mut self, i: usize) -> usize {
+        let text = self.text;
+        let name_end = tag_name_end(text, i);
+        let name = &text[i + 1..name_end];
+
+        let mut attrs = Attributes::new(text, name_end);
+        let mut names: Vec<&str> = Vec::new();
+        for attr in attrs.by_ref() {
+            // Browsers keep the first attribute.
+            if names.contains(&attr.name) {
+                self.replace(attr.start..attr.end(), "");
+                self.recover(attr.name_pos, RecoveryKind::DuplicatedAttribute(attr.name.to_string()));
+                continue;
+            }
+            names.push(attr.name);
+
+            if attr.quote.is_some() {
+                self.references(attr.value.clone());
+            } else {
+                let quote = if text[attr.value.clone()].contains('"') { "'" } else { "\"" };
+                self.replace(attr.value.start..attr.value.start, quote);
+                self.references(attr.value.clone());
+                self.replace(attr.value.end..attr.value.end, quote);
+                self.recover(attr.name_pos, RecoveryKind::UnquotedAttribute(attr.name.to_string()));
+            }
+        }
+
+        match attrs.end {
+            Some(end) => {
+                if !attrs.is_empty {
+                    self.stack.push((name, i));
+                }
+
+                end
+            }
+            // A malformed tag is left as is, so the parser can report it.
+            None => skip_to(text, i, ">"),
+        }
+    }
+
+    fn end_tag(&mut self, i: usize) -> usize {
+        let text = self.text;
+        let name_end = tag_name_end(text, i + 1);
+        let name = &text[i + 2..name_end];
+        let end = match text[name_end..].find('>') {
+            Some(idx) => name_end + idx + 1,
+            None => return text.len(),
+        };
+
+        match self.stack.iter().rposition(|&(n, _)| n == name) {
+            Some(idx) => {
+                let closing = self.close_elements(idx + 1);
+                if !closing.is_empty() {
+                    self.replace(i..i, &closing);
+                }
+                self.stack.pop();
+            }
+            None => {
+                self.replace(i..end, "");
+                self.recover(i, RecoveryKind::UnexpectedClosingTag(name.to_string()));
+            }
+        }
+
+        end
+    }
+
+    // Closes elements above the specified stack depth and returns their closing tags.
+    fn close_elements(&mut self, depth: usize) -> String {
+        let mut closing = String::new();
+        while self.stack.len() > depth {
+            let (name, pos) = self.stack.pop().unwrap();
+            closing.push_str("</");
+            closing.push_str(name);
+            closing.push('>');
+            self.recover(pos, RecoveryKind::UnclosedElement(name.to_string()));
+        }
+
+        closing
+    }
+
+    fn finish(mut self) -> (Cow<'a, str>, Vec<Recovery>) {
+        let text = self.text;
+        let closing = self.close_elements(0);
+        if !closing.is_empty() {
+            self.replace(text.len()..text.len(), &closing);
+        }
+
+        if self.recoveries.is_empty() {
+            return (Cow::Borrowed(text), Vec::new());
+        }
+
+        self.out.push_str(&text[self.copied..]);
+
+        // Unclosed elements are detected after elements inside them.
+        self.recoveries.sort_by_key(|&(offset, _)| offset);
+
+        let mut recoveries = Vec::with_capacity(self.recoveries.len());
+        let mut line = 1;
+        let mut line_start = 0;
+        let mut prev = 0;
+        for (offset, kind) in self.recoveries {
+            for (idx, _) in text[prev..offset].match_indices('\n') {
+                line += 1;
+                line_start = prev + idx + 1;
+            }
+            prev = offset;
+
+            recoveries.push(Recovery {
+                pos: Some((line, offset - line_start + 1)),
+                kind,
+            });
+        }
+
+        (Cow::Owned(self.out), recoveries)
+    }
+}
+
+// An attribute of a start tag.
+struct Attribute<'a> {
+    name: &'a str,
+    name_pos: usize,
+    // The start of whitespaces before the attribute.
+    start: usize,
+    // The value without quotes.
+    value: Range<usize>,
+    quote: Option<u8>,
+}
+
+impl<'a> Attribute<'a> {
+    fn end(&self) -> usize {
+        self.value.end + if self.quote.is_some() { 1 } else { 0 }
+    }
+}
+
+// Iterates over attributes of a start tag.
+struct Attributes<'a> {
+    text: &'a str,
+    pos: usize,
+    // A position after the tag. Not set for a malformed tag.
+    end: Option<usize>,
+    // The tag is closed with '/>'.
+    is_empty: bool,
+}
+
+impl<'a> Attributes<'a> {
+    fn new(text: &'a str, pos: usize) -> Attributes<'a> {
+        Attributes {
+            text,
+            pos,
+            end: None,
+            is_empty: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Attributes<'a> {
+    type Item = Attribute<'a>;
+
+    fn next(&mut self) -> Option<Attribute<'a>> {
+        let bytes = self.text.as_bytes();
+        let start = self.pos;
+        let mut i = skip_spaces(bytes, start);
+        // Stop on the first error.
+        self.pos = bytes.len();
+
+        if bytes[i..].starts_with(b"/>") {
+            self.end = Some(i + 2);
+            self.is_empty = true;
+            return None;
+        }
+
+        if bytes[i..].starts_with(b">") {
+            self.end = Some(i + 1);
+            return None;
+        }
+
+        let name_pos = i;
+        while i < bytes.len() && !is_space(bytes[i]) && !b"=<>/'\"".contains(&bytes[i]) {
+            i += 1;
+        }
+
+        if i == name_pos {
+            return None;
+        }
+        let name = &self.text[name_pos..i];
+
+        i = skip_spaces(bytes, i);
+        if !bytes[i..].starts_with(b"=") {
+            return None;
+        }
+        i = skip_spaces(bytes, i + 1);
+
+        let (value, quote) = match bytes.get(i).cloned() {
+            Some(quote @ b'"') | Some(quote @ b'\'') => {
+                let len = bytes[i + 1..].iter().position(|&c| c == quote)?;
+                (i + 1..i + 1 + len, Some(quote))
+            }
+            Some(_) => {
+                let value_start = i;
+                while i < bytes.len() && !is_space(bytes[i]) && !b"<>".contains(&bytes[i])
+                      && !bytes[i..].starts_with(b"/>") {
+                    i += 1;
+                }
+
+                if i == value_start {
+                    return None;
+                }
+
+                (value_start..i, None)
+            }
+            None => return None,
+        };
+
+        let attr = Attribute { name, name_pos, start, value, quote };
+        self.pos = attr.end();
+        Some(attr)
+    }
+}
+
+// Returns a position after the tag name of a tag starting at the specified position.
+fn tag_name_end(text: &str, i: usize) -> usize {
+    let s = &text[i + 1..];
+    i + 1 + s.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(s.len())
+}
+
+// Returns a position after the first occurrence of the pattern or the end of the text.
+fn skip_to(text: &str, i: usize, pattern: &str) -> usize {
+    match text[i..].find(pattern) {
+        Some(idx) => i + idx + pattern.len(),
+        None => text.len(),
+    }
+}
+
+fn skip_spaces(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && is_space(bytes[i]) {
+        i += 1;
+    }
+
+    i
+}
+
+fn is_space(c: u8) -> bool {
+    c == b' ' || c == b'\t' || c == b'\n' || c == b'\r'
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == ':'
+}
+
+fn is_valid_reference(name: &str, entities: &[&str]) -> bool {
+    if let Some(hex) = name.strip_prefix("#x") {
+        !hex.is_empty() && hex.bytes().all(|c| c.is_ascii_hexdigit())
+    } else if let Some(dec) = name.strip_prefix('#') {
+        !dec.is_empty() && dec.bytes().all(|c| c.is_ascii_digit())
+    } else {
+        ["lt", "gt", "amp", "apos", "quot"].contains(&name) || entities.contains(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(text: &str) -> (String, Vec<String>) {
+        let (new_text, recoveries) = fix_markup(text);
+        (new_text.into_owned(), recoveries.iter().map(|r| r.to_string()).collect())
+    }
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr, $recoveries:expr) => (
+            #[test]
+            fn $name() {
+                let (text, recoveries) = fix($in_text);
+                assert_eq!(text, $out_text);
+                assert_eq!(recoveries, $recoveries as &[&str]);
+            }
+        )
+    }
+
+    test!(valid_1,
+"<?xml version='1.0'?>
+<!DOCTYPE svg [<!ENTITY ns 'http://www.w3.org/2000/svg'>]>
+<svg xmlns='&ns;'>
+    <!-- a < b & c -->
+    <text>&lt;&#160;&#xA0;<![CDATA[ a < b & c ]]></text>
+</svg>",
+"<?xml version='1.0'?>
+<!DOCTYPE svg [<!ENTITY ns 'http://www.w3.org/2000/svg'>]>
+<svg xmlns='&ns;'>
+    <!-- a < b & c -->
+    <text>&lt;&#160;&#xA0;<![CDATA[ a < b & c ]]></text>
+</svg>",
+    &[]);
+
+    test!(ampersand_1,
+"<svg>\n<text>AT&T &amp; &#xZ; &unknown;</text></svg>",
+"<svg>\n<text>AT&amp;T &amp; &amp;#xZ; &amp;unknown;</text></svg>",
+    &["unescaped '&' at 2:9", "unescaped '&' at 2:18", "unescaped '&' at 2:24"]);
+
+    test!(ampersand_2,
+"<svg><a href='/?a=1&b=2'/></svg>",
+"<svg><a href='/?a=1&amp;b=2'/></svg>",
+    &["unescaped '&' at 1:20"]);
+
+    test!(html_entity_1,
+"<svg><text>&copy;&nbsp;2018</text></svg>",
+"<svg><text>&#169;&#160;2018</text></svg>",
+    &["HTML entity '&copy;' at 1:12", "HTML entity '&nbsp;' at 1:18"]);
+
+    test!(less_than_1,
+"<svg><text>a < b</text><script>if (a<b) {}</script></svg>",
+"<svg><text>a &lt; b</text><script>if (a&lt;b) {}</script></svg>",
+    &["unescaped '<' at 1:14", "unescaped '<' at 1:37"]);
+
+    test!(unquoted_1,
+"<svg><rect width=10 height=20/><a title=a\"b/></svg>",
+"<svg><rect width=\"10\" height=\"20\"/><a title='a\"b'/></svg>",
+    &["unquoted value of the 'width' attribute at 1:12",
+      "unquoted value of the 'height' attribute at 1:21",
+      "unquoted value of the 'title' attribute at 1:35"]);
+
+    test!(duplicated_1,
+"<svg><rect fill='red' x='1' fill='blue'/></svg>",
+"<svg><rect fill='red' x='1'/></svg>",
+    &["duplicated attribute 'fill' at 1:29"]);
+
+    test!(unclosed_1,
+"<svg>\n<g>\n<rect>\n</svg>",
+"<svg>\n<g>\n<rect>\n</rect></g></svg>",
+    &["unclosed element 'g' at 2:1", "unclosed element 'rect' at 3:1"]);
+
+    test!(unclosed_2,
+"<svg><g><rect/>",
+"<svg><g><rect/></g></svg>",
+    &["unclosed element 'svg' at 1:1", "unclosed element 'g' at 1:6"]);
+
+    test!(closing_tag_1,
+"<svg><g></g></g></svg>",
+"<svg><g></g></svg>",
+    &["unexpected closing tag 'g' at 1:13"]);
+
+    test!(malformed_1,
+"<svg><rect x='1' =/></svg>",
+"<svg><rect x='1' =/></svg>",
+    &[]);
+
+    #[test]
+    fn borrowed_1() {
+        let text = "<svg><rect x='1'/></svg>";
+        match fix_markup(text).0 {
+            Cow::Borrowed(s) => assert_eq!(s, text),
+            Cow::Owned(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn remove_attribute_1() {
+        let text = "<svg>\n<rect x='1' y='q' width=\"2\"/></svg>";
+        assert_eq!(remove_attribute(text, 2, 16),
+                   Some(("<svg>\n<rect x='1' width=\"2\"/></svg>".to_string(), "y".to_string())));
+        // Not inside a value.
+        assert_eq!(remove_attribute(text, 2, 2), None);
+        assert_eq!(remove_attribute(text, 5, 1), None);
+    }
+}
@@ -0,0 +1,865 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::time::Instant;
+
+use log::Level;
+use svgdom::{
+    Document,
+    ElementId,
+    Node,
+    NodeType,
+    WriteBuffer,
+    WriteOptions,
+};
+
+use options::{
+    CleaningOptions,
+    HrefStyle,
+    SortAttributesMode,
+    SortDefsMode,
+};
+use report::{
+    Report,
+    TaskStats,
+};
+use task::*;
+use error::{
+    Error,
+    ErrorKind,
+};
+
+type TaskFn = Box<dyn Fn(&mut Document, &CleaningOptions, &WriteOptions) -> Result<(), Error> + Send + Sync>;
+type CheckpointFn = Box<dyn Fn() -> Result<(), Error> + Send + Sync>;
+
+struct Task {
+    name: &'static str,
+    is_enabled: fn(&CleaningOptions) -> bool,
+    func: TaskFn,
+}
+
+/// A task position relative to other tasks.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum Position<'a> {
+    /// Before all tasks.
+    First,
+    /// After all tasks.
+    Last,
+    /// Before the first task with the specified name.
+    Before(&'a str),
+    /// After the first task with the specified name.
+    After(&'a str),
+}
+
+/// An ordered list of cleaning tasks.
+///
+/// Contains all built-in tasks by default. Each built-in task is named
+/// after the CLI flag that controls it, like `remove-title` or `merge-paths`.
+/// Some tasks are executed more than once, like `ungroup-groups`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut registry = svgcleaner::TaskRegistry::new();
+/// registry.register("remove-foreign-objects", Position::Before("ungroup-groups"), |doc, _| {
+///     doc.drain(|n| n.is_tag_name(svgdom::ElementId::ForeignObject));
+///     Ok(())
+/// })?;
+/// ```
+pub struct TaskRegistry {
+    tasks: Vec<Task>,
+    checkpoint: Option<CheckpointFn>,
+}
+
+// Creates a built-in task, which is enabled only when the specified option is set.
+macro_rules! builtin {
+    ($name:expr, |$doc:ident, $options:ident, $opt:ident| $task:expr) => (
+        builtin!($name, |_| true, |$doc, $options, $opt| $task)
+    );
+    ($name:expr, $is_enabled:expr, |$doc:ident, $options:ident, $opt:ident| $task:expr) => (
+        Task {
+            name: $name,
+            is_enabled: $is_enabled,
+            func: Box::new(|$doc: &mut Document, $options: &CleaningOptions, $opt: &WriteOptions| -> Result<(), Error> {
+                $task;
+                Ok(())
+            }),
+        }
+    );
+}
+
+impl TaskRegistry {
+    /// Creates a new registry with all built-in tasks.
+    pub fn new() -> TaskRegistry {
+        // NOTE: Order is important.
+        //       Methods should not depend on each other, but for performance reasons
+        //       they should be executed in this order.
+        let tasks = vec![
+            // Run before everything else, so other tasks will never see unsafe data.
+            builtin!("sanitize", |o| o.sanitize, |doc, _options, _opt| sanitize(doc)),
+
+            // Run before everything else too, because other tasks don't expect external links.
+            builtin!("process-external-references",
+                     |doc, options, _opt| process_external_refs(doc, options)),
+
+            // Run before other tasks, so they will not process unused 'switch' branches.
+            builtin!("resolve-switch", |o| o.resolve_switch,
+                     |doc, options, _opt| resolve_switch(doc, &options.system_language)),
+            builtin!("remove-conditional-attributes", |o| o.remove_conditional_attributes,
+                     |doc, options, _opt| {
+                remove_conditional_attributes(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Prepare our document.
+            // This methods is not optional.
+            builtin!("resolve-attributes", |doc, _options, _opt| {
+                resolve_linear_gradient_attributes(doc);
+                resolve_radial_gradient_attributes(doc);
+                resolve_stop_attributes(doc)?;
+                resolve_inherit(doc)?;
+            }),
+            builtin!("fix-invalid-attributes", |doc, _options, _opt| fix_invalid_attributes(doc)),
+            builtin!("group-defs", |doc, _options, _opt| group_defs(doc)),
+
+            // Patterns inheritance is not resolved by the parser like for gradients,
+            // so merge patterns linked via 'xlink:href' when possible.
+            // This allows other tasks to process them as regular ones.
+            builtin!("resolve-patterns", |doc, options, _opt| {
+                resolve_patterns(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Does the same as the parser, when accessibility attributes should be preserved.
+            builtin!("remove-nonsvg-attributes", |o| o.remove_nonsvg_attributes,
+                     |doc, _options, _opt| remove_nonsvg_attributes(doc)),
+
+            // Run before 'round_numbers', so converted values will be rounded too.
+            builtin!("convert-units", |o| o.convert_units,
+                     |doc, options, _opt| convert_units(doc, options.dpi)),
+
+            // Round numbers before everything else.
+            builtin!("round-numbers", |doc, options, _opt| round_numbers(doc, options)),
+
+            // Manipulate with tree structure.
+            // Do not remove any attributes before this methods
+            // since they uses them.
+
+            // Independent task, doesn't impact any other tasks.
+            builtin!("convert-cdata", |o| o.convert_cdata,
+                     |doc, _options, _opt| convert_cdata(doc)),
+
+            // Independent task, doesn't impact any other tasks..
+            builtin!("remove-title", |o| o.remove_title && !o.keep_accessibility,
+                     |doc, _options, _opt| remove_element(doc, ElementId::Title)),
+
+            // Independent task, doesn't impact any other tasks.
+            builtin!("remove-desc", |o| o.remove_desc && !o.keep_accessibility,
+                     |doc, _options, _opt| remove_element(doc, ElementId::Desc)),
+
+            // Independent task, doesn't impact any other tasks.
+            builtin!("remove-metadata", |o| o.remove_metadata,
+                     |doc, options, _opt| remove_metadata(doc, options.keep_license)),
+
+            // Run before all the tasks which are preserving animations.
+            builtin!("remove-animations", |o| o.remove_animations,
+                     |doc, _options, _opt| remove_animations(doc)),
+
+            // Run before all the tasks which are preserving scripts references.
+            builtin!("remove-scripts", |o| o.remove_scripts,
+                     |doc, _options, _opt| remove_scripts(doc)),
+
+            // Independent task, doesn't impact any other tasks.
+            builtin!("remove-foreign-objects", |o| o.remove_foreign_objects,
+                     |doc, _options, _opt| remove_element(doc, ElementId::ForeignObject)),
+
+            // Independent task, doesn't impact any other tasks.
+            builtin!("remove-editor-data",
+                     |o| o.remove_inkscape_data || o.remove_sodipodi_data || o.remove_ai_data
+                         || o.remove_sketch_data || o.remove_figma_data,
+                     |doc, options, _opt| remove_editor_data(doc, options)),
+
+            builtin!("remove-unused-defs", |o| o.remove_unused_defs,
+                     |doc, options, _opt| {
+                remove_unused_defs(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Impact only 'linearGradient' and 'radialGradient'.
+            builtin!("remove-invalid-stops", |o| o.remove_invalid_stops,
+                     |doc, _options, _opt| remove_invalid_stops(doc)),
+
+            // Apply transform to gradients before processing to simplify duplicates
+            // detecting and merging.
+            builtin!("apply-transform-to-gradients", |o| o.apply_transform_to_gradients,
+                     |doc, _options, _opt| apply_transforms::apply_transform_to_gradients(doc)),
+
+            builtin!("remove-dupl-lineargradient", |o| o.remove_dupl_linear_gradients,
+                     |doc, options, _opt| {
+                remove_dupl_linear_gradients(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            builtin!("remove-dupl-radialgradient", |o| o.remove_dupl_radial_gradients,
+                     |doc, options, _opt| {
+                remove_dupl_radial_gradients(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Run before 'remove_dupl_fe_gaussian_blur', because simplified filters
+            // can become duplicates.
+            builtin!("simplify-filters", |o| o.simplify_filters,
+                     |doc, options, _opt| {
+                simplify_filters(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            builtin!("remove-dupl-fegaussianblur", |o| o.remove_dupl_fe_gaussian_blur,
+                     |doc, options, _opt| {
+                remove_dupl_fe_gaussian_blur(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            builtin!("remove-dupl-markers", |o| o.remove_dupl_markers,
+                     |doc, options, _opt| {
+                remove_dupl_markers(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            builtin!("remove-dupl-patterns", |o| o.remove_dupl_patterns,
+                     |doc, options, _opt| {
+                remove_dupl_patterns(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Run before 'remove_dupl_images', so optimized images are compared.
+            builtin!("optimize-images", |o| o.optimize_images,
+                     |doc, _options, _opt| images::optimize_images(doc)),
+
+            // Run before 'remove_dupl_images', because short links are not worth sharing.
+            #[cfg(feature = "fs")]
+            builtin!("extract-images", |o| o.extract_images.is_some(),
+                     |doc, options, _opt| {
+                let dir = options.extract_images.as_ref().unwrap();
+                images::extract_images(doc, dir, options.output_dir.as_deref())?
+            }),
+
+            builtin!("remove-dupl-images", |o| o.remove_dupl_images,
+                     |doc, options, _opt| {
+                images::remove_dupl_images(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            builtin!("merge-gradients", |o| o.merge_gradients,
+                     |doc, _options, _opt| merge_gradients(doc)),
+
+            // Do it again, because something may changed after gradients processing.
+            builtin!("apply-transform-to-gradients", |o| o.apply_transform_to_gradients,
+                     |doc, _options, _opt| apply_transforms::apply_transform_to_gradients(doc)),
+
+            // Run before 'ungroup_groups' and 'apply_transform_to_shapes',
+            // because resolved elements can be processed by them too.
+            builtin!("resolve-use", |o| o.resolve_use,
+                     |doc, options, _opt| {
+                resolve_use(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Run after 'resolve_use', so single-use elements are already resolved.
+            builtin!("optimize-symbols", |o| o.optimize_symbols,
+                     |doc, options, opt| {
+                optimize_symbols(doc, &utils::kept_ids(doc, &options.keep_ids), opt)
+            }),
+
+            // Run before 'remove_noop_masking' and 'apply_clip_paths',
+            // so the created viewport clip paths can be removed or applied.
+            builtin!("flatten-nested-svg", |o| o.flatten_nested_svg,
+                     |doc, options, _opt| {
+                flatten_nested_svg(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Run before 'convert_shapes_to_paths', because a mask region
+            // can be calculated only for basic shapes.
+            builtin!("remove-noop-masking", |o| o.remove_noop_masking,
+                     |doc, options, _opt| {
+                remove_noop_masking(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Run before 'convert_shapes_to_paths'.
+            builtin!("apply-clip-paths", |o| o.apply_clip_paths,
+                     |doc, options, _opt| {
+                apply_clip_paths(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Run before 'apply_transform_to_shapes' and 'process_paths',
+            // because it impact transform processing.
+            builtin!("ungroup-groups", |o| o.ungroup_groups,
+                     |doc, options, _opt| ungroup_groups(doc, options)),
+
+            // Run after 'ungroup_groups', which handles most of such groups already.
+            // Run before 'apply_transform_to_shapes', so merged transforms can be applied.
+            builtin!("merge-transforms", |o| o.merge_transforms,
+                     |doc, _options, _opt| merge_transforms(doc)),
+
+            // Run before 'convert_shapes_to_paths'.
+            builtin!("apply-transform-to-shapes", |o| o.apply_transform_to_shapes,
+                     |doc, options, _opt| {
+                // Fold groups transforms down to the shapes first.
+                // 'doc' must contain 'svg' node, so we can safely unwrap.
+                apply_transforms::prepare_transforms(&doc.svg_element().unwrap(), true, options);
+                apply_transforms::apply_transform_to_shapes(doc);
+            }),
+
+            builtin!("apply-any-transform-to-shapes", |o| o.apply_any_transform_to_shapes,
+                     |doc, options, _opt| apply_transforms::apply_any_transform_to_shapes(doc, options)),
+
+            // Impact only shapes.
+            builtin!("convert-shapes", |o| o.convert_shapes,
+                     |doc, _options, _opt| convert_shapes_to_paths(doc)),
+
+            // Run after 'convert_shapes_to_paths', so only the remaining shapes are checked,
+            // and before 'merge_paths', so converted shapes can be merged.
+            builtin!("convert-shapes-to-paths", |o| o.convert_shapes_to_paths,
+                     |doc, options, opt| convert_shapes_to_shorter_paths(doc, options, opt)),
+
+            // Run after 'convert_shapes_to_paths', so converted shapes can be merged too,
+            // and before 'process_paths', because paths are joined in absolute coordinates.
+            builtin!("merge-paths", |o| o.merge_paths,
+                     |doc, options, _opt| {
+                merge_paths(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // NOTE: Run before 'remove_invisible_elements', because this method can remove all
+            //       segments from the path which makes it invisible.
+
+            // We only process path's segments if 'PathsToRelative' is enabled.
+            builtin!("apply-transform-to-paths",
+                     |o| o.paths_to_relative && o.apply_transform_to_paths,
+                     |doc, options, _opt| apply_transforms::apply_transform_to_paths(doc, options.jobs)),
+
+            builtin!("paths-to-relative", |o| o.paths_to_relative,
+                     |doc, options, _opt| paths::process_paths(doc, options)),
+
+            // Run after transforms and paths processing, so bounding boxes are calculated
+            // using the final coordinates.
+            builtin!("remove-outside-elements", |o| o.remove_outside_elements,
+                     |doc, options, _opt| {
+                remove_outside_elements(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            builtin!("remove-invisible-elements", |o| o.remove_invisible_elements,
+                     |doc, options, _opt| {
+                remove_invisible_elements(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Run after all the elements removing, because they can leave
+            // duplicated spaces between text nodes.
+            builtin!("normalize-text", |o| o.normalize_text,
+                     |doc, _options, _opt| normalize_text(doc)),
+
+            // Run after all the elements removing, because they can leave empty containers.
+            builtin!("remove-empty-elements", |o| o.remove_empty_elements,
+                     |doc, options, _opt| remove_empty_elements(doc, options)),
+
+            // Run after all the elements removing.
+            builtin!("fit-viewbox", |o| o.fit_viewbox, |doc, options, _opt| {
+                fit_viewbox(doc, options.viewbox_padding, options.coordinates_precision)
+            }),
+
+            // Run after 'fit_viewbox', because it can change the 'viewBox' origin.
+            builtin!("translate-to-origin", |o| o.translate_to_origin,
+                     |doc, options, _opt| translate_to_origin(doc, options)),
+
+            builtin!("scale-coordinates", |o| o.scale_coordinates != 1.0, |doc, options, _opt| {
+                scale_coordinates(doc, options.scale_coordinates, options)
+            }),
+
+            // Impact only 'linearGradient' and 'radialGradient'.
+            builtin!("regroup-gradient-stops", |o| o.regroup_gradient_stops,
+                     |doc, _options, _opt| regroup_gradient_stops(doc)),
+
+            // Ungroup again.
+            builtin!("ungroup-groups", |o| o.ungroup_groups,
+                     |doc, options, _opt| ungroup_groups(doc, options)),
+
+            // Now we can remove any unneeded attributes.
+
+            // Run before 'remove_default_attributes', because merged values can became default.
+            builtin!("merge-opacity", |o| o.merge_opacity,
+                     |doc, options, _opt| merge_opacity(doc, options)),
+
+            builtin!("remove-default-attributes", |o| o.remove_default_attributes,
+                     |doc, _options, _opt| remove_default_attributes(doc)),
+
+            builtin!("remove-text-attributes", |o| o.remove_text_attributes,
+                     |doc, _options, _opt| remove_text_attributes(doc)),
+
+            builtin!("remove-needless-attributes", |o| o.remove_needless_attributes,
+                     |doc, _options, _opt| remove_needless_attributes(doc)),
+
+            builtin!("remove-gradient-attributes", |o| o.remove_gradient_attributes,
+                     |doc, _options, _opt| remove_gradient_attributes(doc)),
+
+            builtin!("remove-unused-coordinates", |o| o.remove_unused_coordinates,
+                     |doc, _options, _opt| remove_unused_coordinates(doc)),
+
+            // Ungroup again.
+            builtin!("ungroup-groups", |o| o.ungroup_groups,
+                     |doc, options, _opt| ungroup_groups(doc, options)),
+
+            // Run only after attributes processed, because
+            // there is no point in grouping default/unneeded attributes.
+            builtin!("group-by-style", |o| o.group_by_style,
+                     |doc, _options, opt| group_by_style(doc, opt)),
+
+            // Run after 'group_by_style', so attributes of the created groups
+            // can be moved further.
+            builtin!("collapse-attributes", |o| o.collapse_attributes,
+                     |doc, options, opt| collapse_attributes(doc, options, opt)),
+
+            // Final fixes.
+            // List of things that can't break anything.
+
+            // Independent task, doesn't impact any other tasks.
+            builtin!("remove-unreferenced-ids", |o| o.remove_unreferenced_ids,
+                     |doc, options, _opt| {
+                remove_unreferenced_ids(doc, &utils::kept_ids(doc, &options.keep_ids))
+            }),
+
+            // Run after 'remove_unreferenced_ids', because 'tspan' elements with 'id'
+            // are not flattened.
+            builtin!("flatten-tspans", |o| o.flatten_tspans,
+                     |doc, options, _opt| flatten_tspans(doc, options)),
+
+            // Independent task, doesn't impact any other tasks.
+            builtin!("trim-ids", |o| o.trim_ids,
+                     |doc, options, _opt| trim_ids(doc, &utils::kept_ids(doc, &options.keep_ids))),
+
+            // Run before 'styles', so generated classes are not affected.
+            builtin!("remove-unused-classes", |o| o.remove_unused_classes,
+                     |doc, _options, _opt| remove_unused_classes(doc)),
+
+            // Run after 'remove-unused-classes', so only used classes are counted.
+            builtin!("trim-classes", |o| o.trim_classes,
+                     |doc, _options, _opt| trim_classes(doc)),
+
+            // Independent task, doesn't impact any other tasks.
+            builtin!("remove-version", |o| o.remove_version,
+                     |doc, _options, _opt| remove_version(doc)),
+
+            // Run after 'trim-ids', so elements are sorted by the final ids.
+            // Run before 'ungroup-defs', which keeps the order of ungrouped elements.
+            builtin!("sort-defs", |o| o.sort_defs != SortDefsMode::None,
+                     |doc, options, _opt| sort_defs(doc, options.sort_defs)),
+
+            // Run at last, because it can remove 'defs' element which is used by many algorithms.
+            builtin!("ungroup-defs", |o| o.ungroup_defs,
+                     |doc, _options, _opt| ungroup_defs(doc)),
+
+            builtin!("remove-empty-defs", |doc, _options, _opt| remove_empty_defs(doc)),
+            builtin!("remove-xmlns-xlink-attribute", |doc, options, _opt| {
+                fix_xmlns_attribute(doc, options.remove_xmlns_xlink_attribute,
+                                    options.href_style == HrefStyle::Svg2)
+            }),
+            // Run after all elements and attributes removal.
+            builtin!("remove-unused-namespaces", |o| o.remove_unused_namespaces,
+                     |doc, _options, _opt| remove_unused_namespaces(doc)),
+
+            builtin!("simplify-transforms", |doc, options, _opt| remove_default_transform(doc, options)),
+
+            // NOTE: Must be run at last, since colors are stored as strings after it.
+            builtin!("minify-colors", |o| o.minify_colors,
+                     |doc, _options, opt| minify_colors(doc, opt)),
+
+            // NOTE: Must be run at last, since it breaks the linking.
+            //       Run before 'join_style_attributes', so leftover attributes can still be joined.
+            builtin!("styles", |doc, options, opt| convert_styles_to_css(doc, options.styles, opt)),
+
+            // NOTE: Must be run at last, since it breaks the linking.
+            builtin!("join-style-attributes", |doc, options, opt| {
+                join_style_attributes(doc, options.join_style_attributes, opt)
+            }),
+
+            // NOTE: Must be run at last, since transforms are stored as strings after it.
+            builtin!("decompose-transforms", |doc, options, opt| {
+                decompose_transforms(doc, options, opt)
+            }),
+
+            // NOTE: Must be run at last, since values are stored as strings after it.
+            //       Run before 'use_exponent_notation', which skips strings.
+            builtin!("list-separator", |o| o.minimal_list_separators, |doc, options, opt| {
+                minify_list_separators(doc, options.use_exponent_notation, opt)
+            }),
+
+            // NOTE: Must be run at last, since numbers are stored as strings after it.
+            builtin!("use-exponent-notation", |o| o.use_exponent_notation,
+                     |doc, _options, opt| use_exponent_notation(doc, opt)),
+
+            // Run at last, so all attributes are already in their final form.
+            builtin!("sort-attributes", |o| o.sort_attributes == SortAttributesMode::Frequency,
+                     |doc, _options, _opt| sort_attributes(doc)),
+        ];
+
+        TaskRegistry { tasks, checkpoint: None }
+    }
+
+    /// Sets a function, which is called after each task.
+    ///
+    /// An error returned by it stops the cleaning. Can be used to enforce
+    /// resource limits, like the memory usage.
+    pub fn set_checkpoint<F>(&mut self, func: F)
+        where F: Fn() -> Result<(), Error> + Send + Sync + 'static
+    {
+        self.checkpoint = Some(Box::new(func));
+    }
+
+    /// Registers a custom task at the specified position.
+    ///
+    /// Custom tasks are always executed, regardless of the cleaning options.
+    ///
+    /// Returns an error if the task referenced by `position` is not registered.
+    pub fn register<F>(&mut self, name: &'static str, position: Position, func: F)
+        -> Result<(), Error>
+        where F: Fn(&mut Document, &CleaningOptions) -> Result<(), Error> + Send + Sync + 'static
+    {
+        let idx = match position {
+            Position::First => 0,
+            Position::Last => self.tasks.len(),
+            Position::Before(target) => self.position(target)?,
+            Position::After(target) => self.position(target)? + 1,
+        };
+
+        self.tasks.insert(idx, Task {
+            name,
+            is_enabled: |_| true,
+            func: Box::new(move |doc, options, _| func(doc, options)),
+        });
+
+        Ok(())
+    }
+
+    /// Removes all tasks with the specified name.
+    ///
+    /// Returns an error if the task is not registered.
+    pub fn remove(&mut self, name: &str) -> Result<(), Error> {
+        self.position(name)?;
+        self.tasks.retain(|t| t.name != name);
+        Ok(())
+    }
+
+    /// Enables all tasks with the specified name, regardless of the cleaning options.
+    ///
+    /// Returns an error if the task is not registered.
+    pub fn enable(&mut self, name: &str) -> Result<(), Error> {
+        self.position(name)?;
+        for task in self.tasks.iter_mut().filter(|t| t.name == name) {
+            task.is_enabled = |_| true;
+        }
+
+        Ok(())
+    }
+
+    /// Changes the execution order of the specified tasks.
+    ///
+    /// Tasks are executed in the order of `names`, in place of their original positions.
+    /// Other tasks are not moved. Tasks that are executed multiple times keep their
+    /// relative order.
+    ///
+    /// Returns an error if any of the tasks is not registered.
+    pub fn reorder(&mut self, names: &[&str]) -> Result<(), Error> {
+        for name in names {
+            self.position(name)?;
+        }
+
+        let order = |task: &Task| names.iter().position(|n| *n == task.name);
+
+        let slots: Vec<usize> = (0..self.tasks.len())
+            .filter(|&i| order(&self.tasks[i]).is_some())
+            .collect();
+
+        // Extract the reordered tasks, preserving the positions of other ones.
+        let mut moved = Vec::with_capacity(slots.len());
+        for &i in slots.iter().rev() {
+            moved.push(self.tasks.remove(i));
+        }
+        moved.reverse();
+        // A stable sort, so duplicated tasks keep their relative order.
+        moved.sort_by_key(|t| order(t));
+
+        for (i, task) in slots.into_iter().zip(moved) {
+            self.tasks.insert(i, task);
+        }
+
+        Ok(())
+    }
+
+    /// Returns names of all registered tasks in the execution order.
+    ///
+    /// Tasks that are executed multiple times are listed only once.
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = Vec::new();
+        for task in &self.tasks {
+            if !names.contains(&task.name) {
+                names.push(task.name);
+            }
+        }
+
+        names
+    }
+
+    /// Checks that the task with the specified name is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.tasks.iter().any(|t| t.name == name)
+    }
+
+    fn position(&self, name: &str) -> Result<usize, Error> {
+        match self.tasks.iter().position(|t| t.name == name) {
+            Some(idx) => Ok(idx),
+            None => Err(ErrorKind::UnknownTask(name.to_string()).into()),
+        }
+    }
+
+    /// Executes all enabled tasks.
+    ///
+    /// Statistics of each executed task are recorded to the `report`, if set.
+    pub fn run(
+        &self,
+        doc: &mut Document,
+        options: &CleaningOptions,
+        opt: &WriteOptions,
+        mut report: Option<&mut Report>
+    ) -> Result<(), Error> {
+        preclean_checks(doc, options)?;
+
+        for task in self.tasks.iter().filter(|t| (t.is_enabled)(options)) {
+            let state = report.as_ref().map(|_| TaskState::new(doc, opt));
+            let log = TaskLog::new(doc);
+            (task.func)(doc, options, opt)?;
+            if let (Some(state), Some(report)) = (state, report.as_mut()) {
+                report.add_task(state.finish(task.name, doc, opt));
+            }
+            if let Some(log) = log {
+                log.finish(task.name, doc);
+            }
+            if let Some(ref checkpoint) = self.checkpoint {
+                checkpoint()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for TaskRegistry {
+    fn default() -> TaskRegistry {
+        TaskRegistry::new()
+    }
+}
+
+// A document state before a task execution.
+struct TaskState {
+    start: Instant,
+    size: usize,
+    elements: usize,
+}
+
+impl TaskState {
+    fn new(doc: &Document, opt: &WriteOptions) -> TaskState {
+        TaskState {
+            size: doc_size(doc, opt),
+            elements: count_elements(doc),
+            // Start the timer at last, so the measurement itself is not included.
+            start: Instant::now(),
+        }
+    }
+
+    fn finish(self, name: &'static str, doc: &Document, opt: &WriteOptions) -> TaskStats {
+        let elapsed = self.start.elapsed();
+
+        TaskStats {
+            name,
+            elapsed,
+            saved: self.size as i64 - doc_size(doc, opt) as i64,
+            removed_elements: self.elements as i64 - count_elements(doc) as i64,
+        }
+    }
+}
+
+// Logs elements changed by a task, using the task name as a log target.
+//
+// The number of removed elements is logged on the info level
+// and each removed element on the debug level.
+struct TaskLog {
+    elements: usize,
+    // Elements before the task execution. Collected only on the debug level.
+    nodes: Vec<Node>,
+}
+
+impl TaskLog {
+    fn new(doc: &Document) -> Option<TaskLog> {
+        if !log_enabled!(Level::Info) {
+            return None;
+        }
+
+        let nodes = if log_enabled!(Level::Debug) {
+            doc.descendants().filter(|n| n.node_type() == NodeType::Element).collect()
+        } else {
+            Vec::new()
+        };
+
+        Some(TaskLog {
+            elements: count_elements(doc),
+            nodes,
+        })
+    }
+
+    fn finish(self, name: &'static str, doc: &Document) {
+        // Only roots of removed subtrees are logged.
+        for node in self.nodes.iter().filter(|n| n.parent().is_none()) {
+            let id = node.id();
+            if id.is_empty() {
+                debug!(target: name, "removed the '{:?}' element", *node.tag_name().unwrap());
+            } else {
+                debug!(target: name, "removed the '{:?}' element with id '{}'",
+                       *node.tag_name().unwrap(), *id);
+            }
+        }
+
+        let elements = count_elements(doc);
+        if elements < self.elements {
+            info!(target: name, "{} element(s) removed", self.elements - elements);
+        } else if elements > self.elements {
+            info!(target: name, "{} element(s) added", elements - self.elements);
+        }
+    }
+}
+
+fn doc_size(doc: &Document, opt: &WriteOptions) -> usize {
+    let mut buf = Vec::new();
+    doc.write_buf_opt(opt, &mut buf);
+    buf.len()
+}
+
+fn count_elements(doc: &Document) -> usize {
+    doc.descendants().filter(|n| n.node_type() == NodeType::Element).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::ChainedErrorExt;
+    use cleaner::Cleaner;
+
+    #[test]
+    fn register_1() {
+        let mut registry = TaskRegistry::new();
+        registry.register("first", Position::First, |_, _| Ok(())).unwrap();
+        registry.register("last", Position::Last, |_, _| Ok(())).unwrap();
+        registry.register("before", Position::Before("group-defs"), |_, _| Ok(())).unwrap();
+        registry.register("after", Position::After("group-defs"), |_, _| Ok(())).unwrap();
+
+        let names = registry.names();
+        assert_eq!(names[0], "first");
+        assert_eq!(names[names.len() - 1], "last");
+        assert_eq!(&names[6..10], &["fix-invalid-attributes", "before", "group-defs", "after"]);
+    }
+
+    #[test]
+    fn register_2() {
+        let mut registry = TaskRegistry::new();
+        let e = registry.register("task", Position::After("unknown"), |_, _| Ok(())).unwrap_err();
+        assert_eq!(e.full_chain(), "Error: unknown task 'unknown'");
+        assert!(!registry.contains("task"));
+    }
+
+    #[test]
+    fn remove_1() {
+        let mut registry = TaskRegistry::new();
+        registry.remove("ungroup-groups").unwrap();
+        assert!(!registry.contains("ungroup-groups"));
+        assert!(registry.remove("ungroup-groups").is_err());
+    }
+
+    #[test]
+    fn reorder_1() {
+        let mut registry = TaskRegistry::new();
+        registry.reorder(&["round-numbers", "group-defs", "fix-invalid-attributes"]).unwrap();
+        assert_eq!(&registry.names()[4..11], &["resolve-attributes", "round-numbers", "group-defs",
+                                               "resolve-patterns", "remove-nonsvg-attributes",
+                                               "convert-units", "fix-invalid-attributes"]);
+    }
+
+    #[test]
+    fn reorder_2() {
+        let mut registry = TaskRegistry::new();
+        registry.reorder(&["ungroup-groups", "resolve-use"]).unwrap();
+        let idx: Vec<usize> = registry.tasks.iter().enumerate()
+            .filter(|&(_, t)| t.name == "ungroup-groups" || t.name == "resolve-use")
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(idx.len(), 4);
+        assert_eq!(registry.tasks[idx[3]].name, "resolve-use");
+
+        assert!(registry.reorder(&["unknown"]).is_err());
+    }
+
+    #[test]
+    fn enable_1() {
+        let mut registry = TaskRegistry::new();
+        registry.enable("remove-outside-elements").unwrap();
+
+        let mut opt = CleaningOptions::recommended();
+        opt.remove_outside_elements = false;
+
+        let text = "<svg xmlns='http://www.w3.org/2000/svg' width='10' height='10' viewBox='0 0 10 10'>\
+                    <rect x='20' width='10' height='10'/></svg>";
+        let res = Cleaner::new().cleaning_options(opt).task_registry(registry).clean_str(text);
+        assert_eq!(res.unwrap(), "<svg height=\"10\" viewBox=\"0 0 10 10\" width=\"10\" \
+                                  xmlns=\"http://www.w3.org/2000/svg\"/>");
+    }
+
+    #[test]
+    fn run_1() {
+        let mut registry = TaskRegistry::new();
+        // Must be run before shapes are converted to paths.
+        registry.register("remove-rects", Position::Before("convert-shapes"), |doc, _| {
+            doc.drain(|n| n.is_tag_name(ElementId::Rect));
+            Ok(())
+        }).unwrap();
+        registry.remove("remove-title").unwrap();
+
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'>\
+                    <title>Title</title><rect width='10' height='10'/><circle r='5'/></svg>";
+        let res = Cleaner::new().task_registry(registry).clean_str(text).unwrap();
+        assert_eq!(res, "<svg xmlns=\"http://www.w3.org/2000/svg\">\
+                         <title>Title</title><circle r=\"5\"/></svg>");
+    }
+
+    #[test]
+    fn run_2() {
+        let mut registry = TaskRegistry::new();
+        registry.register("fail", Position::First, |_, _| {
+            Err(ErrorKind::MissingAttribute("svg".to_string(), "width".to_string()).into())
+        }).unwrap();
+
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'/>";
+        assert!(Cleaner::new().task_registry(registry).clean_str(text).is_err());
+    }
+
+    #[test]
+    fn checkpoint_1() {
+        let mut registry = TaskRegistry::new();
+        registry.set_checkpoint(|| Err(ErrorKind::MemoryLimit(1).into()));
+
+        let text = "<svg xmlns='http://www.w3.org/2000/svg'/>";
+        let res = Cleaner::new().task_registry(registry).clean_str(text);
+        match res.unwrap_err().kind() {
+            ErrorKind::MemoryLimit(1) => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+}
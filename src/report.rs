@@ -0,0 +1,401 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use warning::Warning;
+
+/// Statistics of a single cleaning task.
+#[derive(Clone,Debug)]
+pub struct TaskStats {
+    /// A task name. Usually matches the CLI flag name.
+    pub name: &'static str,
+    /// A total execution time.
+    pub elapsed: Duration,
+    /// A number of bytes removed from the document. Can be negative.
+    pub saved: i64,
+    /// A number of removed elements. Can be negative.
+    pub removed_elements: i64,
+}
+
+/// A cleaning report.
+///
+/// Collected by `cleaner::clean_doc_with_report`.
+#[derive(Clone,Debug,Default)]
+pub struct Report {
+    /// An input data size.
+    pub input_size: usize,
+    /// An output data size.
+    pub output_size: usize,
+    /// A total processing time, including parsing and writing.
+    pub elapsed: Duration,
+    /// Statistics of the executed tasks in order of the first execution.
+    pub tasks: Vec<TaskStats>,
+}
+
+impl Report {
+    /// Adds task statistics.
+    ///
+    /// Statistics of the same task are accumulated, because some tasks
+    /// are executed multiple times.
+    pub fn add_task(&mut self, stats: TaskStats) {
+        match self.tasks.iter_mut().find(|t| t.name == stats.name) {
+            Some(task) => {
+                task.elapsed += stats.elapsed;
+                task.saved += stats.saved;
+                task.removed_elements += stats.removed_elements;
+            }
+            None => self.tasks.push(stats),
+        }
+    }
+
+    /// Merges another report into this one.
+    pub fn merge(&mut self, other: &Report) {
+        self.input_size += other.input_size;
+        self.output_size += other.output_size;
+        self.elapsed += other.elapsed;
+
+        for task in &other.tasks {
+            self.add_task(task.clone());
+        }
+    }
+
+    /// Resets all execution times.
+    ///
+    /// Timings differ between runs, so they should be reset when the report must be reproducible.
+    pub fn reset_elapsed(&mut self) {
+        self.elapsed = Duration::from_secs(0);
+        for task in &mut self.tasks {
+            task.elapsed = Duration::from_secs(0);
+        }
+    }
+
+    /// Returns a total number of removed elements.
+    pub fn removed_elements(&self) -> i64 {
+        self.tasks.iter().map(|t| t.removed_elements).sum()
+    }
+}
+
+/// Converts reports of the processed files to JSON.
+pub fn reports_to_json(reports: &[(String, Report)]) -> String {
+    let mut s = String::new();
+    s.push_str("{\n");
+    s.push_str("  \"files\": [");
+    for (i, (path, report)) in reports.iter().enumerate() {
+        s.push_str(if i == 0 { "\n" } else { ",\n" });
+        write_report(path, report, &mut s);
+    }
+    s.push_str(if reports.is_empty() { "],\n" } else { "\n  ],\n" });
+
+    let input_size: usize = reports.iter().map(|r| r.1.input_size).sum();
+    let output_size: usize = reports.iter().map(|r| r.1.output_size).sum();
+    writeln!(s, "  \"input_size\": {},", input_size).unwrap();
+    writeln!(s, "  \"output_size\": {}", output_size).unwrap();
+    s.push_str("}\n");
+    s
+}
+
+/// Converts a report to a human-readable table.
+pub fn report_to_table(report: &Report) -> String {
+    let mut s = String::new();
+    writeln!(s, "{:<32} {:>10} {:>10} {:>10}", "Task", "Time, ms", "Saved, B", "Elements").unwrap();
+
+    for task in &report.tasks {
+        writeln!(s, "{:<32} {:>10.3} {:>10} {:>10}",
+                 task.name, duration_ms(task.elapsed), task.saved, task.removed_elements).unwrap();
+    }
+
+    let elapsed = report.tasks.iter().fold(Duration::from_secs(0), |d, t| d + t.elapsed);
+    let saved: i64 = report.tasks.iter().map(|t| t.saved).sum();
+    writeln!(s, "{:<32} {:>10.3} {:>10} {:>10}",
+             "Total", duration_ms(elapsed), saved, report.removed_elements()).unwrap();
+
+    s
+}
+
+/// Converts a report to a table with the throughput of each task.
+///
+/// The throughput is the input size divided by the execution time,
+/// so the numbers don't depend on the amount of data.
+pub fn report_to_throughput(report: &Report) -> String {
+    let mut s = String::new();
+    writeln!(s, "{:<32} {:>10} {:>10}", "Task", "Time, ms", "MB/s").unwrap();
+
+    for task in &report.tasks {
+        writeln!(s, "{:<32} {:>10.3} {:>10.1}", task.name, duration_ms(task.elapsed),
+                 throughput(report.input_size, task.elapsed)).unwrap();
+    }
+
+    // Includes parsing and writing.
+    writeln!(s, "{:<32} {:>10.3} {:>10.1}", "Total", duration_ms(report.elapsed),
+             throughput(report.input_size, report.elapsed)).unwrap();
+
+    s
+}
+
+/// Lists tasks that changed the document.
+pub fn report_to_changes(report: &Report) -> String {
+    let mut s = String::new();
+    for task in report.tasks.iter().filter(|t| t.saved != 0 || t.removed_elements != 0) {
+        write!(s, "    {}: {}B saved", task.name, task.saved).unwrap();
+        if task.removed_elements > 0 {
+            write!(s, ", {} element(s) removed", task.removed_elements).unwrap();
+        } else if task.removed_elements < 0 {
+            write!(s, ", {} element(s) added", -task.removed_elements).unwrap();
+        }
+        s.push('\n');
+    }
+
+    if s.is_empty() {
+        s.push_str("    no changes\n");
+    }
+
+    s
+}
+
+/// Converts a warning to a single-line JSON object.
+///
+/// The position is written only when it's known.
+pub fn warning_to_json(path: &str, warning: &Warning) -> String {
+    let mut s = String::new();
+    write!(s, "{{ \"path\": {}, \"code\": {}, \"message\": {}",
+           json_string(path), json_string(warning.code.as_str()),
+           json_string(&warning.message)).unwrap();
+    if let Some((line, column)) = warning.pos {
+        write!(s, ", \"line\": {}, \"column\": {}", line, column).unwrap();
+    }
+    s.push_str(" }");
+    s
+}
+
+/// Converts a log message to a single-line JSON object.
+pub fn log_to_json(level: &str, target: &str, message: &str) -> String {
+    format!("{{ \"level\": {}, \"target\": {}, \"message\": {} }}",
+            json_string(&level.to_lowercase()), json_string(target), json_string(message))
+}
+
+fn write_report(path: &str, report: &Report, s: &mut String) {
+    s.push_str("    {\n");
+    writeln!(s, "      \"path\": {},", json_string(path)).unwrap();
+    writeln!(s, "      \"input_size\": {},", report.input_size).unwrap();
+    writeln!(s, "      \"output_size\": {},", report.output_size).unwrap();
+    writeln!(s, "      \"removed_elements\": {},", report.removed_elements()).unwrap();
+    writeln!(s, "      \"elapsed_ms\": {:.3},", duration_ms(report.elapsed)).unwrap();
+    s.push_str("      \"tasks\": [");
+    for (i, task) in report.tasks.iter().enumerate() {
+        s.push_str(if i == 0 { "\n" } else { ",\n" });
+        write!(s, "        {{ \"name\": {}, \"saved\": {}, \"removed_elements\": {}, \
+                   \"elapsed_ms\": {:.3} }}",
+               json_string(task.name), task.saved, task.removed_elements,
+               duration_ms(task.elapsed)).unwrap();
+    }
+    s.push_str(if report.tasks.is_empty() { "]\n" } else { "\n      ]\n" });
+    s.push_str("    }");
+}
+
+fn duration_ms(d: Duration) -> f64 {
+    d.as_secs() as f64 * 1000.0 + d.subsec_nanos() as f64 / 1_000_000.0
+}
+
+// Megabytes per second. Infinite when the time is too small to be measured.
+fn throughput(size: usize, d: Duration) -> f64 {
+    size as f64 / 1024.0 / 1024.0 / (duration_ms(d) / 1000.0)
+}
+
+fn json_string(text: &str) -> String {
+    let mut s = String::with_capacity(text.len() + 2);
+    s.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(s, "\\u{:04x}", c as u32).unwrap(),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &'static str, saved: i64, removed_elements: i64) -> TaskStats {
+        TaskStats {
+            name,
+            elapsed: Duration::from_millis(1),
+            saved,
+            removed_elements,
+        }
+    }
+
+    #[test]
+    fn add_task_1() {
+        let mut report = Report::default();
+        report.add_task(task("ungroup-groups", 10, 1));
+        report.add_task(task("remove-title", 20, 1));
+        report.add_task(task("ungroup-groups", 5, 2));
+
+        assert_eq!(report.tasks.len(), 2);
+        assert_eq!(report.tasks[0].name, "ungroup-groups");
+        assert_eq!(report.tasks[0].saved, 15);
+        assert_eq!(report.tasks[0].elapsed, Duration::from_millis(2));
+        assert_eq!(report.removed_elements(), 4);
+    }
+
+    #[test]
+    fn merge_1() {
+        let mut report1 = Report::default();
+        report1.add_task(task("remove-title", 20, 1));
+
+        let mut report2 = Report::default();
+        report2.add_task(task("remove-desc", 10, 1));
+        report2.add_task(task("remove-title", 5, 1));
+
+        report1.merge(&report2);
+
+        assert_eq!(report1.tasks.len(), 2);
+        assert_eq!(report1.tasks[0].saved, 25);
+        assert_eq!(report1.tasks[1].name, "remove-desc");
+    }
+
+    #[test]
+    fn reset_elapsed_1() {
+        let mut report = Report {
+            elapsed: Duration::from_millis(5),
+            .. Report::default()
+        };
+        report.add_task(task("remove-title", 20, 1));
+        report.reset_elapsed();
+
+        assert_eq!(report.elapsed, Duration::from_secs(0));
+        assert_eq!(report.tasks[0].elapsed, Duration::from_secs(0));
+        assert_eq!(report.tasks[0].saved, 20);
+    }
+
+    #[test]
+    fn table_1() {
+        let mut report = Report::default();
+        report.add_task(task("remove-title", 20, 1));
+        report.add_task(task("merge-paths", -5, 2));
+
+        assert_eq!(report_to_table(&report),
+"Task                               Time, ms   Saved, B   Elements
+remove-title                          1.000         20          1
+merge-paths                           1.000         -5          2
+Total                                 2.000         15          3
+");
+    }
+
+    #[test]
+    fn throughput_1() {
+        let mut report = Report {
+            input_size: 1024 * 1024,
+            elapsed: Duration::from_millis(4),
+            .. Report::default()
+        };
+        report.add_task(task("remove-title", 20, 1));
+
+        assert_eq!(report_to_throughput(&report),
+"Task                               Time, ms       MB/s
+remove-title                          1.000     1000.0
+Total                                 4.000      250.0
+");
+    }
+
+    #[test]
+    fn changes_1() {
+        let mut report = Report::default();
+        report.add_task(task("remove-title", 20, 1));
+        report.add_task(task("remove-desc", 0, 0));
+        report.add_task(task("group-defs", -7, -1));
+
+        assert_eq!(report_to_changes(&report),
+"    remove-title: 20B saved, 1 element(s) removed
+    group-defs: -7B saved, 1 element(s) added
+");
+        assert_eq!(report_to_changes(&Report::default()), "    no changes\n");
+    }
+
+    #[test]
+    fn json_1() {
+        let mut report = Report {
+            input_size: 100,
+            output_size: 80,
+            .. Report::default()
+        };
+        report.add_task(task("remove-title", 20, 1));
+
+        assert_eq!(reports_to_json(&[("a\"b.svg".to_string(), report)]),
+"{
+  \"files\": [
+    {
+      \"path\": \"a\\\"b.svg\",
+      \"input_size\": 100,
+      \"output_size\": 80,
+      \"removed_elements\": 1,
+      \"elapsed_ms\": 0.000,
+      \"tasks\": [
+        { \"name\": \"remove-title\", \"saved\": 20, \"removed_elements\": 1, \"elapsed_ms\": 1.000 }
+      ]
+    }
+  ],
+  \"input_size\": 100,
+  \"output_size\": 80
+}
+");
+    }
+
+    #[test]
+    fn warning_json_1() {
+        use warning::WarningCode;
+
+        let mut warning = Warning::new(WarningCode::UnclosedElement, "unclosed element 'g'");
+        assert_eq!(warning_to_json("in.svg", &warning),
+                   "{ \"path\": \"in.svg\", \"code\": \"unclosed-element\", \
+                    \"message\": \"unclosed element 'g'\" }");
+
+        warning.pos = Some((2, 5));
+        assert_eq!(warning_to_json("-", &warning),
+                   "{ \"path\": \"-\", \"code\": \"unclosed-element\", \
+                    \"message\": \"unclosed element 'g'\", \"line\": 2, \"column\": 5 }");
+    }
+
+    #[test]
+    fn log_json_1() {
+        assert_eq!(log_to_json("Info", "remove-title", "1 element(s) removed"),
+                   "{ \"level\": \"info\", \"target\": \"remove-title\", \
+                    \"message\": \"1 element(s) removed\" }");
+    }
+
+    #[test]
+    fn json_2() {
+        assert_eq!(reports_to_json(&[]),
+"{
+  \"files\": [],
+  \"input_size\": 0,
+  \"output_size\": 0
+}
+");
+    }
+}
@@ -0,0 +1,340 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::io::{
+    self,
+    BufRead,
+    Write,
+};
+use std::str;
+
+use svgdom::ParseOptions;
+
+use foreign::{
+    collect_namespaces,
+    markup_len,
+    tag_name,
+};
+use options::CleaningOptions;
+use task::is_editor_prefix;
+use task::utils;
+
+/// Removes comments, metadata, editor data and whitespaces between elements
+/// without building a DOM.
+///
+/// The input is processed markup by markup, so the memory usage doesn't depend on its size.
+/// Only options, which don't require a DOM, are applied: `parse_comments`,
+/// `remove_metadata` (unless `keep_license` is set) and the `remove_*_data` ones.
+///
+/// Whitespaces are preserved inside `text` and `foreignObject` elements
+/// and elements with `xml:space="preserve"`.
+pub fn strip<R: BufRead, W: Write>(
+    mut input: R,
+    mut output: W,
+    parse_options: &ParseOptions,
+    options: &CleaningOptions
+) -> Result<(), io::Error> {
+    let mut stripper = Stripper {
+        remove_comments: !parse_options.parse_comments,
+        options,
+        namespaces: Vec::new(),
+        stack: Vec::new(),
+        skip_depth: None,
+    };
+
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if input.read_until(b'<', &mut buf)? == 0 {
+            break;
+        }
+
+        let has_markup = buf.last() == Some(&b'<');
+        if has_markup {
+            buf.pop();
+        }
+
+        stripper.text(to_str(&buf)?, &mut output)?;
+
+        if !has_markup {
+            break;
+        }
+
+        let markup = read_markup(&mut input)?;
+        stripper.markup(&markup, &mut output)?;
+    }
+
+    output.flush()
+}
+
+struct Stripper<'a> {
+    remove_comments: bool,
+    options: &'a CleaningOptions,
+    namespaces: Vec<(String, String)>,
+    // Whether whitespaces are preserved inside each open element.
+    stack: Vec<bool>,
+    // The nesting depth inside a removed element.
+    skip_depth: Option<usize>,
+}
+
+impl<'a> Stripper<'a> {
+    fn text<W: Write>(&self, text: &str, output: &mut W) -> Result<(), io::Error> {
+        if self.skip_depth.is_some() {
+            return Ok(());
+        }
+
+        let is_preserved = self.stack.last().cloned().unwrap_or(false);
+        if text.trim().is_empty() && !is_preserved {
+            return Ok(());
+        }
+
+        output.write_all(text.as_bytes())
+    }
+
+    fn markup<W: Write>(&mut self, markup: &str, output: &mut W) -> Result<(), io::Error> {
+        if markup.starts_with("<!--") {
+            if self.skip_depth.is_some() || self.remove_comments {
+                return Ok(());
+            }
+        } else if markup.starts_with("</") {
+            match self.skip_depth {
+                Some(0) => self.skip_depth = None,
+                Some(depth) => self.skip_depth = Some(depth - 1),
+                None => {
+                    self.stack.pop();
+                    output.write_all(markup.as_bytes())?;
+                }
+            }
+
+            return Ok(());
+        } else if !markup.starts_with("<!") && !markup.starts_with("<?") {
+            return self.start_tag(markup, output);
+        } else if self.skip_depth.is_some() {
+            return Ok(());
+        }
+
+        output.write_all(markup.as_bytes())
+    }
+
+    fn start_tag<W: Write>(&mut self, tag: &str, output: &mut W) -> Result<(), io::Error> {
+        let is_empty = tag.ends_with("/>");
+
+        if let Some(depth) = self.skip_depth {
+            if !is_empty {
+                self.skip_depth = Some(depth + 1);
+            }
+
+            return Ok(());
+        }
+
+        collect_namespaces(tag, &mut self.namespaces);
+
+        let name = tag_name(tag);
+        let is_removable = match utils::split_prefix(name) {
+            Some((prefix, _)) => self.is_editor_prefix(prefix),
+            None => name == "metadata" && self.options.remove_metadata && !self.options.keep_license,
+        };
+
+        if is_removable {
+            if !is_empty {
+                self.skip_depth = Some(0);
+            }
+
+            return Ok(());
+        }
+
+        if !is_empty {
+            let is_preserved = self.stack.last().cloned().unwrap_or(false)
+                || name == "text"
+                || name == "foreignObject"
+                || tag.contains("xml:space=\"preserve\"")
+                || tag.contains("xml:space='preserve'");
+            self.stack.push(is_preserved);
+        }
+
+        let tag = strip_attributes(tag, |name| {
+            let prefix = name.strip_prefix("xmlns:")
+                .or_else(|| utils::split_prefix(name).map(|(prefix, _)| prefix));
+            prefix.is_some_and(|prefix| self.is_editor_prefix(prefix))
+        });
+
+        output.write_all(tag.as_bytes())
+    }
+
+    fn is_editor_prefix(&self, prefix: &str) -> bool {
+        let uri = self.namespaces.iter().find(|(p, _)| p == prefix).map(|(_, uri)| uri.as_str());
+        is_editor_prefix(prefix, uri, self.options)
+    }
+}
+
+// Reads a markup after the '<' character.
+fn read_markup<R: BufRead>(input: &mut R) -> Result<String, io::Error> {
+    let mut buf = vec![b'<'];
+    loop {
+        if input.read_until(b'>', &mut buf)? == 0 || buf.last() != Some(&b'>') {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "unclosed markup"));
+        }
+
+        // The '>' character can be a part of an attribute value, a comment or a CDATA.
+        let s = to_str(&buf)?;
+        let is_complete = if s.starts_with("<!--") {
+            s.len() >= 7 && s.ends_with("-->")
+        } else if s.starts_with("<![CDATA[") {
+            s.ends_with("]]>")
+        } else if s.starts_with("<?") {
+            s.ends_with("?>")
+        } else {
+            markup_len(s).is_some()
+        };
+
+        if is_complete {
+            return Ok(s.to_string());
+        }
+    }
+}
+
+// Removes attributes with the matched names from a start tag.
+fn strip_attributes<F: Fn(&str) -> bool>(tag: &str, is_removable: F) -> String {
+    let name_end = 1 + tag_name(tag).len();
+
+    let mut out = String::with_capacity(tag.len());
+    out.push_str(&tag[..name_end]);
+
+    let mut s = &tag[name_end..];
+    loop {
+        let attr = s.trim_start();
+        let name_len = attr.find(|c: char| c == '=' || c == '/' || c == '>' || c.is_whitespace())
+                           .unwrap_or(attr.len());
+        let rest = attr[name_len..].trim_start();
+        if name_len == 0 || !rest.starts_with('=') {
+            break;
+        }
+
+        let value = rest[1..].trim_start();
+        let quote = match value.chars().next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => break,
+        };
+
+        let len = match value[1..].find(quote) {
+            // The length of the whitespace, the name and the value with quotes.
+            Some(idx) => s.len() - value.len() + idx + 2,
+            None => break,
+        };
+
+        if !is_removable(&attr[..name_len]) {
+            out.push_str(&s[..len]);
+        }
+
+        s = &s[len..];
+    }
+
+    out.push_str(s);
+    out
+}
+
+// Chunks are split by ASCII characters, so a valid UTF-8 input produces valid chunks.
+fn to_str(data: &[u8]) -> Result<&str, io::Error> {
+    str::from_utf8(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip_str(text: &str, options: &CleaningOptions) -> String {
+        let parse_options = ParseOptions {
+            parse_comments: false,
+            ..ParseOptions::default()
+        };
+
+        let mut buf = Vec::new();
+        strip(text.as_bytes(), &mut buf, &parse_options, options).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn options() -> CleaningOptions {
+        CleaningOptions {
+            remove_metadata: true,
+            remove_inkscape_data: true,
+            remove_sodipodi_data: true,
+            ..CleaningOptions::default()
+        }
+    }
+
+    #[test]
+    fn strip_1() {
+        let text = "<?xml version='1.0'?>\n<!-- Comment -->\n\
+                    <svg xmlns='http://www.w3.org/2000/svg'>\n    \
+                    <metadata><rdf:RDF><cc:Work/></rdf:RDF></metadata>\n    \
+                    <rect width='10'/>\n</svg>\n";
+        assert_eq!(strip_str(text, &options()),
+                   "<?xml version='1.0'?><svg xmlns='http://www.w3.org/2000/svg'><rect width='10'/></svg>");
+    }
+
+    #[test]
+    fn strip_2() {
+        // Editor elements, attributes and namespaces.
+        let text = "<svg xmlns='http://www.w3.org/2000/svg' \
+                    xmlns:inkscape='http://www.inkscape.org/namespaces/inkscape' \
+                    xmlns:sodipodi='http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd'>\
+                    <sodipodi:namedview><inkscape:grid/></sodipodi:namedview>\
+                    <g inkscape:label=\"Layer 1\" inkscape:groupmode='layer' id='g1'/></svg>";
+        assert_eq!(strip_str(text, &options()),
+                   "<svg xmlns='http://www.w3.org/2000/svg'><g id='g1'/></svg>");
+    }
+
+    #[test]
+    fn strip_3() {
+        // Disabled options.
+        let text = "<svg xmlns:inkscape='http://www.inkscape.org/namespaces/inkscape'>\
+                    <metadata/><g inkscape:label='Layer 1'/></svg>";
+        assert_eq!(strip_str(text, &CleaningOptions::default()), text);
+    }
+
+    #[test]
+    fn strip_4() {
+        // Whitespaces inside text.
+        let text = "<svg>\n  <text>\n    <tspan>a</tspan> <tspan>b</tspan>\n  </text>\n  \
+                    <g xml:space='preserve'> </g>\n</svg>";
+        assert_eq!(strip_str(text, &options()),
+                   "<svg><text>\n    <tspan>a</tspan> <tspan>b</tspan>\n  </text>\
+                    <g xml:space='preserve'> </g></svg>");
+    }
+
+    #[test]
+    fn strip_5() {
+        // The '>' character inside attributes, comments and CDATA.
+        let text = "<svg><rect title='a > b'/><!-- <a> --><style><![CDATA[a > b {}]]></style></svg>";
+        assert_eq!(strip_str(text, &options()),
+                   "<svg><rect title='a > b'/><style><![CDATA[a > b {}]]></style></svg>");
+    }
+
+    #[test]
+    fn strip_6() {
+        // Nested elements inside a removed one.
+        let text = "<svg><metadata><metadata><a/></metadata><b></b></metadata><rect/></svg>";
+        assert_eq!(strip_str(text, &options()), "<svg><rect/></svg>");
+    }
+
+    #[test]
+    fn strip_err_1() {
+        let mut buf = Vec::new();
+        assert!(strip("<svg><rect".as_bytes(), &mut buf, &ParseOptions::default(), &options()).is_err());
+    }
+}
@@ -0,0 +1,452 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// A converter of SVGO configuration files in the JSON form into svgcleaner options.
+//
+// Only plugins with svgcleaner equivalents are mapped. Explicitly enabled plugins
+// without them are reported as warnings, so users can see what is not migrated.
+
+use regex;
+
+use config::Value;
+use json::{
+    self,
+    Json,
+};
+
+// Plugins enabled by the SVGO 'preset-default'.
+static PRESET_DEFAULT: &[&str] = &[
+    "removeDoctype",
+    "removeXMLProcInst",
+    "removeComments",
+    "removeMetadata",
+    "removeEditorsNSData",
+    "cleanupAttrs",
+    "mergeStyles",
+    "inlineStyles",
+    "minifyStyles",
+    "cleanupIds",
+    "removeUselessDefs",
+    "cleanupNumericValues",
+    "convertColors",
+    "removeUnknownsAndDefaults",
+    "removeNonInheritableGroupAttrs",
+    "removeUselessStrokeAndFill",
+    "removeViewBox",
+    "cleanupEnableBackground",
+    "removeHiddenElems",
+    "removeEmptyText",
+    "convertShapeToPath",
+    "convertEllipseToCircle",
+    "moveElemsAttrsToGroup",
+    "moveGroupAttrsToElems",
+    "collapseGroups",
+    "convertPathData",
+    "convertTransform",
+    "removeEmptyAttrs",
+    "removeEmptyContainers",
+    "mergePaths",
+    "removeUnusedNS",
+    "sortAttrs",
+    "sortDefsChildren",
+    "removeTitle",
+    "removeDesc",
+];
+
+// Plugins, which are always performed by svgcleaner.
+static BUILTIN_PLUGINS: &[&str] = &[
+    "cleanupAttrs",
+    "mergeStyles",
+    "inlineStyles",
+    "minifyStyles",
+    "cleanupNumericValues",
+    "cleanupListOfValues",
+    "removeEmptyAttrs",
+    "sortAttrs",
+];
+
+// SVGO plugins and svgcleaner options with the same effect.
+//
+// Options with the 'true' value are flags and are set to 'false' when a plugin is disabled.
+static PLUGINS: &[(&str, &[(&str, &str)])] = &[
+    ("removeDoctype", &[("remove-doctype", "true")]),
+    ("removeXMLProcInst", &[("remove-declarations", "true")]),
+    ("removeComments", &[("remove-comments", "true")]),
+    ("removeMetadata", &[("remove-metadata", "true")]),
+    ("removeEditorsNSData", &[("remove-editor-data", "true")]),
+    ("cleanupIds", &[("remove-unreferenced-ids", "true"), ("trim-ids", "true")]),
+    ("removeUselessDefs", &[("remove-unused-defs", "true")]),
+    ("convertColors", &[("trim-colors", "true"), ("minify-colors", "true")]),
+    ("removeUnknownsAndDefaults", &[
+        ("remove-nonsvg-elements", "true"),
+        ("remove-nonsvg-attributes", "true"),
+        ("remove-default-attributes", "true"),
+        ("remove-needless-attributes", "true"),
+    ]),
+    ("removeNonInheritableGroupAttrs", &[("remove-needless-attributes", "true")]),
+    ("removeHiddenElems", &[("remove-invisible-elements", "true")]),
+    ("removeEmptyText", &[("remove-empty-elements", "true")]),
+    ("removeEmptyContainers", &[("remove-empty-elements", "true")]),
+    ("convertShapeToPath", &[("convert-shapes", "true")]),
+    ("moveElemsAttrsToGroup", &[("group-by-style", "true")]),
+    ("moveGroupAttrsToElems", &[("collapse-attributes", "true")]),
+    ("collapseGroups", &[("ungroup-groups", "true")]),
+    ("convertPathData", &[
+        ("paths-to-relative", "true"),
+        ("remove-unused-segments", "true"),
+        ("convert-segments", "true"),
+        ("trim-paths", "true"),
+        ("remove-dupl-cmd-in-paths", "true"),
+        ("use-implicit-cmds", "true"),
+    ]),
+    ("convertTransform", &[("merge-transforms", "true"), ("simplify-transforms", "true")]),
+    ("mergePaths", &[("merge-paths", "true")]),
+    ("removeUnusedNS", &[("remove-unused-namespaces", "true")]),
+    ("sortDefsChildren", &[("sort-defs", "type")]),
+    ("removeTitle", &[("remove-title", "true")]),
+    ("removeDesc", &[("remove-desc", "true")]),
+    ("removeScriptElement", &[("remove-scripts", "true")]),
+    ("removeScripts", &[("remove-scripts", "true")]),
+    ("removeOffCanvasPaths", &[("remove-outside-elements", "true")]),
+    ("removeXlink", &[("href-style", "svg2")]),
+];
+
+/// Options converted from an SVGO config.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Config {
+    pub options: Vec<(String, Value)>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Default)]
+struct State {
+    enabled: Vec<String>,
+    disabled: Vec<String>,
+    // Values from the plugins parameters, which take precedence over the plugins state.
+    values: Vec<(String, Value)>,
+    keep_ids: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl State {
+    fn set(&mut self, key: &str, value: Value) {
+        self.values.retain(|(k, _)| k != key);
+        self.values.push((key.to_string(), value));
+    }
+
+    fn warn(&mut self, msg: String) {
+        if !self.warnings.contains(&msg) {
+            self.warnings.push(msg);
+        }
+    }
+}
+
+/// Parses an SVGO config in the JSON form.
+pub fn parse(text: &str) -> Result<Config, String> {
+    let root = match json::parse(text)? {
+        Json::Object(root) => root,
+        _ => return Err("expected an object".to_string()),
+    };
+
+    let mut state = State::default();
+    let mut has_preset = false;
+    for (key, value) in &root {
+        match (key.as_str(), value) {
+            ("multipass", Json::Bool(b)) => state.set("multipass", Value::Bool(*b)),
+            ("floatPrecision", Json::Number(n)) => {
+                state.set("coordinates-precision", precision(*n));
+                state.set("paths-coordinates-precision", precision(*n));
+            }
+            ("js2svg", Json::Object(items)) => parse_js2svg(items, &mut state),
+            ("plugins", Json::Array(plugins)) => {
+                for plugin in plugins {
+                    has_preset |= parse_plugin(plugin, &mut state)?;
+                }
+            }
+            // Not related to the cleaning.
+            ("path", _) => {}
+            (name, _) => state.warn(format!("option '{}' is not supported", name)),
+        }
+    }
+
+    // SVGO runs only the listed plugins.
+    let mut options = Vec::new();
+    if !has_preset {
+        options.push(("no-defaults".to_string(), Value::Bool(true)));
+    }
+
+    for &(plugin, keys) in PLUGINS {
+        let is_enabled = state.enabled.iter().any(|p| p == plugin);
+        for &(key, value) in keys {
+            let is_set = options.iter().any(|(k, _)| k == key);
+            if is_enabled {
+                options.retain(|(k, _)| k != key);
+                options.push((key.to_string(), to_value(value)));
+            } else if !is_set && value == "true" && state.disabled.iter().any(|p| p == plugin) {
+                options.push((key.to_string(), Value::Bool(false)));
+            }
+        }
+    }
+
+    if !state.keep_ids.is_empty() {
+        let ids = state.keep_ids.join(",");
+        state.set("keep-ids", Value::String(ids));
+    }
+
+    for (key, value) in state.values {
+        options.retain(|(k, _)| *k != key);
+        options.push((key, value));
+    }
+
+    Ok(Config { options, warnings: state.warnings })
+}
+
+fn parse_js2svg(items: &[(String, Json)], state: &mut State) {
+    let mut is_pretty = false;
+    let mut indent = 4.0;
+    for (key, value) in items {
+        match (key.as_str(), value) {
+            ("pretty", Json::Bool(b)) => is_pretty = *b,
+            ("indent", Json::Number(n)) => indent = *n,
+            ("finalNewline", Json::Bool(b)) => state.set("append-newline", Value::Bool(*b)),
+            (name, _) => state.warn(format!("option 'js2svg.{}' is not supported", name)),
+        }
+    }
+
+    if is_pretty {
+        let indent = (indent.round() as i64).clamp(0, 4);
+        state.set("indent", Value::String(indent.to_string()));
+    }
+}
+
+// Returns `true` for the 'preset-default'.
+fn parse_plugin(plugin: &Json, state: &mut State) -> Result<bool, String> {
+    let (name, params) = match *plugin {
+        Json::String(ref name) => (name.as_str(), None),
+        Json::Object(ref items) => {
+            let name = match get(items, "name") {
+                Some(Json::String(name)) => name.as_str(),
+                _ => return Err("a plugin must have a name".to_string()),
+            };
+
+            let params = match get(items, "params") {
+                Some(Json::Object(params)) => Some(params.as_slice()),
+                _ => None,
+            };
+
+            (name, params)
+        }
+        _ => return Err("a plugin must be a string or an object".to_string()),
+    };
+
+    if name != "preset-default" {
+        enable_plugin(name, params, true, state);
+        return Ok(false);
+    }
+
+    let params = params.unwrap_or(&[]);
+    let overrides = match get(params, "overrides") {
+        Some(Json::Object(overrides)) => overrides.as_slice(),
+        _ => &[],
+    };
+
+    for (key, value) in params {
+        match (key.as_str(), value) {
+            ("overrides", _) => {}
+            ("floatPrecision", Json::Number(n)) => {
+                state.set("coordinates-precision", precision(*n));
+                state.set("paths-coordinates-precision", precision(*n));
+            }
+            (name, _) => state.warn(format!("parameter '{}' of 'preset-default' is not supported", name)),
+        }
+    }
+
+    for name in PRESET_DEFAULT {
+        match get(overrides, name) {
+            Some(Json::Bool(false)) => {
+                state.disabled.push(name.to_string());
+            }
+            Some(Json::Object(params)) => enable_plugin(name, Some(params), true, state),
+            _ => enable_plugin(name, None, false, state),
+        }
+    }
+
+    for (name, _) in overrides {
+        if !PRESET_DEFAULT.contains(&name.as_str()) {
+            state.warn(format!("plugin '{}' is not a part of 'preset-default'", name));
+        }
+    }
+
+    Ok(true)
+}
+
+fn enable_plugin(name: &str, params: Option<&[(String, Json)]>, is_explicit: bool, state: &mut State) {
+    let is_mapped = PLUGINS.iter().any(|&(p, _)| p == name);
+    if !is_mapped && !BUILTIN_PLUGINS.contains(&name) {
+        // Plugins of the 'preset-default' without an equivalent are skipped silently.
+        if is_explicit {
+            state.warn(format!("plugin '{}' is not supported", name));
+        }
+        return;
+    }
+
+    state.enabled.push(name.to_string());
+
+    for (key, value) in params.unwrap_or(&[]) {
+        match (name, key.as_str(), value) {
+            ("cleanupIds", "remove", Json::Bool(b)) => state.set("remove-unreferenced-ids", Value::Bool(*b)),
+            ("cleanupIds", "minify", Json::Bool(b)) => state.set("trim-ids", Value::Bool(*b)),
+            ("cleanupIds", "preserve", Json::Array(ids)) => {
+                for id in ids {
+                    if let Json::String(id) = id {
+                        state.keep_ids.push(regex::escape(id));
+                    }
+                }
+            }
+            ("cleanupIds", "preservePrefixes", Json::Array(prefixes)) => {
+                for prefix in prefixes {
+                    if let Json::String(prefix) = prefix {
+                        state.keep_ids.push(format!("{}.*", regex::escape(prefix)));
+                    }
+                }
+            }
+            ("cleanupNumericValues", "floatPrecision", Json::Number(n)) => {
+                state.set("coordinates-precision", precision(*n));
+            }
+            ("convertPathData", "floatPrecision", Json::Number(n)) => {
+                state.set("paths-coordinates-precision", precision(*n));
+            }
+            ("convertTransform", "floatPrecision", Json::Number(n)) => {
+                state.set("transforms-precision", precision(*n));
+            }
+            _ => state.warn(format!("parameter '{}' of '{}' is not supported", key, name)),
+        }
+    }
+}
+
+// SVGO precision is a number of digits after the decimal point,
+// so this is only an approximation.
+fn precision(n: f64) -> Value {
+    let n = (n.round() as i64).clamp(1, 12);
+    Value::Number(n.to_string())
+}
+
+fn to_value(value: &str) -> Value {
+    match value {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(value.to_string()),
+    }
+}
+
+fn get<'a>(items: &'a [(String, Json)], key: &str) -> Option<&'a Json> {
+    items.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(text: &str) -> Vec<(String, String)> {
+        parse(text).unwrap().options.iter().map(|(k, v)| (k.clone(), v.to_arg())).collect()
+    }
+
+    fn option(text: &str, key: &str) -> Option<String> {
+        options(text).into_iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn parse_err_1() {
+        assert_eq!(parse("[]").unwrap_err(), "expected an object");
+    }
+
+    #[test]
+    fn preset_default_1() {
+        let text = r#"{
+            "multipass": true,
+            "plugins": [
+                {
+                    "name": "preset-default",
+                    "params": { "overrides": { "removeTitle": false, "removeEmptyText": false } }
+                },
+                "removeXlink"
+            ]
+        }"#;
+
+        let opt = options(text);
+        assert!(!opt.iter().any(|(k, _)| k == "no-defaults"));
+        assert_eq!(option(text, "multipass"), Some("true".to_string()));
+        assert_eq!(option(text, "remove-title"), Some("false".to_string()));
+        assert_eq!(option(text, "remove-desc"), Some("true".to_string()));
+        assert_eq!(option(text, "href-style"), Some("svg2".to_string()));
+        // Still enabled by 'removeEmptyContainers'.
+        assert_eq!(option(text, "remove-empty-elements"), Some("true".to_string()));
+        assert!(parse(text).unwrap().warnings.is_empty());
+    }
+
+    #[test]
+    fn plugins_1() {
+        let text = r#"{
+            "floatPrecision": 2,
+            "js2svg": { "pretty": true, "indent": 2 },
+            "plugins": [
+                "removeComments",
+                { "name": "cleanupIds", "params": { "minify": false, "preserve": ["logo"], "preservePrefixes": ["icon-"] } },
+                { "name": "convertPathData", "params": { "floatPrecision": 3 } }
+            ]
+        }"#;
+
+        assert_eq!(options(text), vec![
+            ("no-defaults".to_string(), "true".to_string()),
+            ("remove-comments".to_string(), "true".to_string()),
+            ("remove-unreferenced-ids".to_string(), "true".to_string()),
+            ("paths-to-relative".to_string(), "true".to_string()),
+            ("remove-unused-segments".to_string(), "true".to_string()),
+            ("convert-segments".to_string(), "true".to_string()),
+            ("trim-paths".to_string(), "true".to_string()),
+            ("remove-dupl-cmd-in-paths".to_string(), "true".to_string()),
+            ("use-implicit-cmds".to_string(), "true".to_string()),
+            ("coordinates-precision".to_string(), "2".to_string()),
+            ("indent".to_string(), "2".to_string()),
+            ("trim-ids".to_string(), "false".to_string()),
+            ("paths-coordinates-precision".to_string(), "3".to_string()),
+            ("keep-ids".to_string(), "logo,icon\\-.*".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn warnings_1() {
+        let text = r#"{
+            "datauri": "base64",
+            "plugins": [
+                "removeDimensions",
+                { "name": "removeComments", "params": { "preservePatterns": false } },
+                { "name": "custom" },
+                { "name": "preset-default", "params": { "overrides": { "removeFoo": false } } }
+            ]
+        }"#;
+
+        assert_eq!(parse(text).unwrap().warnings, vec![
+            "option 'datauri' is not supported".to_string(),
+            "plugin 'removeDimensions' is not supported".to_string(),
+            "parameter 'preservePatterns' of 'removeComments' is not supported".to_string(),
+            "plugin 'custom' is not supported".to_string(),
+            "plugin 'removeFoo' is not a part of 'preset-default'".to_string(),
+        ]);
+    }
+}
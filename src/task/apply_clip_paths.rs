@@ -0,0 +1,238 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    FuzzyEq,
+    Length,
+    Node,
+    ValueId,
+};
+
+use task::short::{EId, AId, Unit};
+use task::utils;
+use task::utils::bbox::Rect;
+
+// Applies clip paths with a single rect to the rects, which are using them.
+//
+// When both rects are axis-aligned in the same user space, clipping is just
+// an intersection of them, so we can resize the clipped rect and remove
+// the 'clip-path' reference and the 'clipPath' element itself.
+//
+// We should run it before 'convert_shapes_to_paths'.
+pub fn apply_clip_paths(doc: &mut Document, keep_ids: &[String]) {
+    let nodes: Vec<Node> = doc.descendants().filter(|n| n.is_tag_name(EId::Rect)).collect();
+
+    for mut node in nodes {
+        let mut clip_path = match node.attributes().get_value(AId::ClipPath) {
+            Some(AttributeValue::FuncLink(link)) => link.clone(),
+            _ => continue,
+        };
+
+        // Clip path is used by other elements too.
+        if clip_path.uses_count() != 1 || utils::is_kept_id(&clip_path, keep_ids) {
+            continue;
+        }
+
+        if !is_simple_paint(&node) {
+            continue;
+        }
+
+        let (rect, clip_rect) = match (get_rect(&node), get_clip_rect(&clip_path)) {
+            (Some(rect), Some(clip_rect)) => (rect, clip_rect),
+            _ => continue,
+        };
+
+        match rect.intersect(&clip_rect) {
+            Some(r) => {
+                node.set_attribute((AId::X, Length::new_number(r.x1)));
+                node.set_attribute((AId::Y, Length::new_number(r.y1)));
+                node.set_attribute((AId::Width, Length::new_number(r.x2 - r.x1)));
+                node.set_attribute((AId::Height, Length::new_number(r.y2 - r.y1)));
+                node.remove_attribute(AId::ClipPath);
+            }
+            None => {
+                // The rect is completely clipped, so it's invisible.
+                if node.is_used() || utils::is_kept_id(&node, keep_ids) {
+                    continue;
+                }
+
+                node.remove();
+            }
+        }
+
+        clip_path.remove();
+    }
+}
+
+// Checks that the element rendering doesn't depend on its size.
+fn is_simple_paint(node: &Node) -> bool {
+    // Stroke is clipped too, so it can't be preserved.
+    if !matches!(utils::find_attribute(node, AId::Stroke),
+                 Some(AttributeValue::PredefValue(ValueId::None)) | None) {
+        return false;
+    }
+
+    // Paint servers, masks and filters are using the bounding box by default.
+    if let Some(AttributeValue::FuncLink(_)) = utils::find_attribute(node, AId::Fill) {
+        return false;
+    }
+
+    if !matches!(utils::find_attribute(node, AId::Filter),
+                 Some(AttributeValue::PredefValue(ValueId::None)) | None) {
+        return false;
+    }
+
+    !node.has_attribute(AId::Mask)
+}
+
+// Returns a clipping rect, when a clip path contains only a single rect.
+fn get_clip_rect(clip_path: &Node) -> Option<Rect> {
+    // 'clipPathUnits=objectBoundingBox' and transforms are not supported.
+    if clip_path.has_attributes(&[AId::ClipPathUnits, AId::Transform, AId::ClipPath]) {
+        return None;
+    }
+
+    let mut children = clip_path.children();
+    let child = children.next()?;
+    if children.next().is_some() || !child.is_tag_name(EId::Rect) {
+        return None;
+    }
+
+    if child.has_attributes(&[AId::Transform, AId::ClipPath, AId::Display, AId::Visibility]) {
+        return None;
+    }
+
+    get_rect(&child)
+}
+
+fn get_rect(node: &Node) -> Option<Rect> {
+    let attrs = node.attributes();
+
+    // Rounded rects are not supported.
+    if attrs.contains(AId::Rx) || attrs.contains(AId::Ry) {
+        return None;
+    }
+
+    let x = get_value!(attrs, Length, AId::X, Length::zero());
+    let y = get_value!(attrs, Length, AId::Y, Length::zero());
+    let w = get_value!(attrs, Length, AId::Width, Length::zero());
+    let h = get_value!(attrs, Length, AId::Height, Length::zero());
+
+    if !(x.unit == Unit::None && y.unit == Unit::None &&
+         w.unit == Unit::None && h.unit == Unit::None) {
+        return None;
+    }
+
+    if w.num.is_fuzzy_zero() || h.num.is_fuzzy_zero() {
+        return None;
+    }
+
+    Some(Rect { x1: x.num, y1: y.num, x2: x.num + w.num, y2: y.num + h.num })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &mut Document| apply_clip_paths(doc, &[]), $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    test!(apply_1,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='10' width='10' x='5' y='5'/>
+    </clipPath>
+    <rect clip-path='url(#clip1)' height='10' width='10'/>
+</svg>",
+"<svg>
+    <rect height='5' width='5' x='5' y='5'/>
+</svg>
+");
+
+    test!(apply_2,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='20' width='20' x='-5' y='-5'/>
+    </clipPath>
+    <rect clip-path='url(#clip1)' height='10' transform='translate(10 10)' width='10'/>
+</svg>",
+"<svg>
+    <rect height='10' transform='translate(10 10)' width='10' x='0' y='0'/>
+</svg>
+");
+
+    test!(rm_1,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='10' width='10' x='20' y='20'/>
+    </clipPath>
+    <rect clip-path='url(#clip1)' height='10' width='10'/>
+</svg>",
+"<svg/>
+");
+
+    test_eq!(keep_1,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='10' width='10' x='5' y='5'/>
+    </clipPath>
+    <rect clip-path='url(#clip1)' height='10' width='10'/>
+    <rect clip-path='url(#clip1)' height='10' width='10'/>
+</svg>
+");
+
+    test_eq!(keep_2,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='10' width='10' x='5' y='5'/>
+    </clipPath>
+    <rect clip-path='url(#clip1)' height='10' stroke='#000000' width='10'/>
+</svg>
+");
+
+    test_eq!(keep_3,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='10' transform='rotate(90)' width='10' x='5' y='5'/>
+    </clipPath>
+    <rect clip-path='url(#clip1)' height='10' width='10'/>
+</svg>
+");
+
+    test_eq!(keep_4,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='10' width='10' x='5' y='5'/>
+        <rect height='10' width='10' x='15' y='15'/>
+    </clipPath>
+    <rect clip-path='url(#clip1)' height='10' width='10'/>
+</svg>
+");
+}
@@ -0,0 +1,108 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+    Transform,
+};
+
+use options::CleaningOptions;
+use task::short::{EId, AId};
+use super::{
+    apply_transform_to_paths,
+    apply_transform_to_shapes,
+    prepare_transforms,
+};
+
+// Prepends the transform to all the top-level elements.
+//
+// Returns 'false' and doesn't change anything if the content can't be transformed.
+pub fn prepend_transform_to_content(doc: &Document, ts: &Transform) -> bool {
+    let svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => return false,
+    };
+
+    let nodes = match collect_content(&svg) {
+        Some(nodes) => nodes,
+        None => return false,
+    };
+
+    // Animated transforms will override the new one.
+    let has_animation = doc.descendants().svg().any(|(id, _)| {
+        id == EId::AnimateTransform || id == EId::AnimateMotion
+    });
+    if has_animation {
+        return false;
+    }
+
+    for mut node in nodes {
+        let mut new_ts = *ts;
+        if let Some(AttributeValue::Transform(ts2)) = node.attributes().get_value(AId::Transform) {
+            new_ts.append(ts2);
+        }
+
+        node.set_attribute((AId::Transform, new_ts));
+    }
+
+    true
+}
+
+// Applies transforms to the elements coordinates, when it's allowed by the options.
+pub fn apply_transform_to_content(doc: &Document, opt: &CleaningOptions) {
+    if opt.apply_transform_to_shapes {
+        // 'doc' must contain 'svg' node, so we can safely unwrap.
+        prepare_transforms(&doc.svg_element().unwrap(), true, opt);
+        apply_transform_to_shapes(doc);
+    }
+
+    if opt.paths_to_relative && opt.apply_transform_to_paths {
+        apply_transform_to_paths(doc, opt.jobs);
+    }
+}
+
+// Returns the top-level elements that should be transformed.
+//
+// Returns 'None' if the content can't be transformed.
+fn collect_content(svg: &Node) -> Option<Vec<Node>> {
+    let mut nodes = Vec::new();
+    for (id, node) in svg.children().svg() {
+        match id {
+              EId::Defs | EId::Style | EId::Script
+            | EId::Title | EId::Desc | EId::Metadata => {}
+              EId::G | EId::A | EId::Switch | EId::ForeignObject
+            | EId::Rect | EId::Circle | EId::Ellipse | EId::Line
+            | EId::Polyline | EId::Polygon | EId::Path
+            | EId::Text | EId::Image | EId::Use => {
+                // A referenced element will be rendered without the parent transform,
+                // so we can't change it.
+                if node.is_used() {
+                    return None;
+                }
+
+                nodes.push(node);
+            }
+            // Nested 'svg' or an animation element can't have a 'transform'.
+            _ => return None,
+        }
+    }
+
+    Some(nodes)
+}
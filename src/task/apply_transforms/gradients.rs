@@ -23,6 +23,7 @@ use svgdom::{
 };
 
 use task::short::{EId, AId};
+use task::utils::AnimatedAttributes;
 use super::utils;
 
 pub fn apply_transform_to_gradients(doc: &Document) {
@@ -30,7 +31,13 @@ pub fn apply_transform_to_gradients(doc: &Document) {
                   .filter(|n| n.is_gradient())
                   .filter(|n| n.has_attribute(AId::GradientTransform));
 
+    let animated = AnimatedAttributes::new(doc);
+
     for mut node in iter {
+        if animated.is_animated(&node) {
+            continue;
+        }
+
         {
             let flag = node.linked_nodes().any(|n| n.is_gradient());
 
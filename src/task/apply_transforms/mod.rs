@@ -16,12 +16,23 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
+pub use self::content::{
+    apply_transform_to_content,
+    prepend_transform_to_content,
+};
 pub use self::preproc::prepare_transforms;
 pub use self::gradients::apply_transform_to_gradients;
-pub use self::shapes::apply_transform_to_shapes;
+pub use self::paths::apply_transform_to_paths;
+pub use self::shapes::{
+    apply_transform_to_shapes,
+    apply_any_transform_to_shapes,
+};
 
+mod content;
 mod preproc;
 mod gradients;
+mod paint;
+mod paths;
 mod shapes;
 
 pub mod utils {
@@ -64,8 +75,9 @@ pub mod utils {
     }
 
     // Element shouldn't have any linked elements, because they also must be transformed.
-    // TODO: process 'fill', 'stroke' and 'filter' linked elements only if they
-    //       used only by this element.
+    //
+    // Elements that can transform 'fill', 'stroke' and 'filter' linked elements
+    // should use 'paint::is_valid_attrs' instead.
     pub fn is_valid_attrs(node: &Node) -> bool {
         for aid in &[AId::Fill, AId::Stroke, AId::Filter, AId::Mask, AId::ClipPath] {
             if !is_valid_attr(node, *aid) {
@@ -77,7 +89,7 @@ pub mod utils {
     }
 
     // Checks that first occurred attribute value is not a FuncLink.
-    fn is_valid_attr(node: &Node, aid: AId) -> bool {
+    pub fn is_valid_attr(node: &Node, aid: AId) -> bool {
         for parent in node.parents_with_self() {
             let attrs = parent.attributes();
 
@@ -100,6 +112,9 @@ pub mod utils {
             EId::Line |
             EId::LinearGradient => _is_valid_coords(node, &[AId::X1, AId::Y1, AId::X2, AId::Y2]),
             EId::RadialGradient => _is_valid_coords(node, &[AId::Cx, AId::Cy, AId::Fx, AId::Fy, AId::R]),
+            // 'points' can't have units.
+            EId::Polyline |
+            EId::Polygon => true,
             _ => false,
         }
     }
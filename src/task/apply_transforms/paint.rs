@@ -0,0 +1,302 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    FuzzyEq,
+    Node,
+    Transform,
+    ValueId,
+};
+
+use task::short::{EId, AId};
+use super::utils;
+
+// Like 'utils::is_valid_attrs', but also allows 'fill', 'stroke' and 'filter' links
+// to elements that are used only by this element, because we can transform them too
+// using 'apply_transform'.
+pub fn is_valid_attrs(node: &Node, ts: &Transform) -> bool {
+    for aid in &[AId::Mask, AId::ClipPath] {
+        if !utils::is_valid_attr(node, *aid) {
+            return false;
+        }
+    }
+
+    for aid in &[AId::Fill, AId::Stroke, AId::Filter] {
+        if !utils::is_valid_attr(node, *aid) && !is_valid_link(node, *aid, ts) {
+            return false;
+        }
+    }
+
+    true
+}
+
+// Must be called only for elements checked by 'is_valid_attrs'.
+pub fn apply_transform(node: &Node, ts: &Transform) {
+    for aid in &[AId::Fill, AId::Stroke, AId::Filter] {
+        let mut link = match get_link(node, *aid) {
+            Some(link) => link,
+            None => continue,
+        };
+
+        match link.tag_id().unwrap() {
+            EId::LinearGradient | EId::RadialGradient
+                if is_user_space(&link, AId::GradientUnits, ValueId::ObjectBoundingBox) => {
+                prepend_transform(&mut link, AId::GradientTransform, ts);
+            }
+            EId::Pattern
+                if is_user_space(&link, AId::PatternUnits, ValueId::ObjectBoundingBox) => {
+                prepend_transform(&mut link, AId::PatternTransform, ts);
+            }
+            EId::Filter
+                if is_user_space(&link, AId::PrimitiveUnits, ValueId::UserSpaceOnUse) => {
+                // 'is_bbox_preserved' guarantees that there is no rotate or skew,
+                // so the blur can be scaled along each axis separately.
+                let (sx, sy) = ts.get_scale();
+                for mut child in link.children() {
+                    scale_std_dev(&mut child, sx, sy);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn get_link(node: &Node, aid: AId) -> Option<Node> {
+    // Links defined in the parent element are used by other elements too,
+    // so we are checking only the current one.
+    match node.attributes().get_value(aid) {
+        Some(AttributeValue::FuncLink(link)) => Some(link.clone()),
+        _ => None,
+    }
+}
+
+fn is_valid_link(node: &Node, aid: AId, ts: &Transform) -> bool {
+    let link = match get_link(node, aid) {
+        Some(link) => link,
+        None => return false,
+    };
+
+    // The linked element should be used only by this element,
+    // including other elements via 'xlink:href'.
+    if link.uses_count() != 1 {
+        return false;
+    }
+
+    match link.tag_id().unwrap() {
+        EId::LinearGradient | EId::RadialGradient => {
+            // Gradient attributes are already resolved, so 'xlink:href' doesn't matter.
+               is_user_space(&link, AId::GradientUnits, ValueId::ObjectBoundingBox)
+            || is_bbox_preserved(ts)
+        }
+        EId::Pattern => {
+            // Pattern attributes are not resolved.
+            if link.has_attribute(AId::XlinkHref) {
+                return false;
+            }
+
+            let is_user_units
+                = is_user_space(&link, AId::PatternUnits, ValueId::ObjectBoundingBox);
+            let is_user_content
+                =    is_user_space(&link, AId::PatternContentUnits, ValueId::UserSpaceOnUse)
+                  && !link.has_attribute(AId::ViewBox);
+
+            if is_user_units && is_user_content {
+                true
+            } else if !is_user_units && !is_user_content {
+                is_bbox_preserved(ts)
+            } else {
+                // Mixed units can't be transformed using 'patternTransform'.
+                false
+            }
+        }
+        EId::Filter => is_valid_filter(&link, ts),
+        _ => false,
+    }
+}
+
+// Filter region is based on the element bounding box and the only primitive
+// that can be easily transformed is the 'feGaussianBlur'.
+fn is_valid_filter(link: &Node, ts: &Transform) -> bool {
+    if    link.has_attributes(&[AId::XlinkHref, AId::FilterRes])
+       || is_user_space(link, AId::FilterUnits, ValueId::ObjectBoundingBox) {
+        return false;
+    }
+
+    if !is_bbox_preserved(ts) {
+        return false;
+    }
+
+    link.children().all(|n| {
+           n.is_tag_name(EId::FeGaussianBlur)
+        && !n.has_attributes(&[AId::X, AId::Y, AId::Width, AId::Height])
+    })
+}
+
+// An element bounding box will be transformed exactly like the element itself
+// only when transform doesn't have a rotate, skew or mirror parts.
+fn is_bbox_preserved(ts: &Transform) -> bool {
+    ts.b.is_fuzzy_zero() && ts.c.is_fuzzy_zero() && ts.a > 0.0 && ts.d > 0.0
+}
+
+fn is_user_space(node: &Node, aid: AId, def: ValueId) -> bool {
+    let units = match node.attributes().get_value(aid) {
+        Some(&AttributeValue::PredefValue(id)) => id,
+        _ => def,
+    };
+
+    units == ValueId::UserSpaceOnUse
+}
+
+fn prepend_transform(node: &mut Node, aid: AId, ts: &Transform) {
+    let mut new_ts = *ts;
+    if let Some(AttributeValue::Transform(ts2)) = node.attributes().get_value(aid) {
+        new_ts.append(ts2);
+    }
+
+    node.set_attribute((aid, new_ts));
+}
+
+fn scale_std_dev(node: &mut Node, sx: f64, sy: f64) {
+    let (x, y) = match node.attributes().get_value(AId::StdDeviation) {
+        Some(&AttributeValue::Number(n)) => (n, n),
+        Some(AttributeValue::NumberList(list)) if list.len() == 1 => (list[0], list[0]),
+        Some(AttributeValue::NumberList(list)) if list.len() == 2 => (list[0], list[1]),
+        _ => return,
+    };
+
+    let (x, y) = (x * sx, y * sy);
+    if x.fuzzy_eq(&y) {
+        node.set_attribute((AId::StdDeviation, x));
+    } else {
+        node.set_attribute((AId::StdDeviation, AttributeValue::NumberList(vec![x, y])));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+    use task;
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                task::resolve_linear_gradient_attributes(&doc);
+                task::resolve_radial_gradient_attributes(&doc);
+                for node in doc.descendants().filter(|n| n.has_attribute(AId::Transform)) {
+                    let ts = utils::get_ts(&node);
+                    if is_valid_attrs(&node, &ts) {
+                        apply_transform(&node, &ts);
+                    }
+                }
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(pattern_1,
+"<svg>
+    <pattern id='p1' patternUnits='userSpaceOnUse'/>
+    <rect fill='url(#p1)' transform='translate(10 20)'/>
+</svg>",
+"<svg>
+    <pattern id='p1' patternTransform='translate(10 20)' patternUnits='userSpaceOnUse'/>
+    <rect fill='url(#p1)' transform='translate(10 20)'/>
+</svg>
+");
+
+    test!(filter_1,
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2'/>
+    </filter>
+    <rect filter='url(#f1)' transform='scale(2)'/>
+</svg>",
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='4'/>
+    </filter>
+    <rect filter='url(#f1)' transform='scale(2)'/>
+</svg>
+");
+
+    test!(filter_2,
+"<svg>
+    <filter id='f1' primitiveUnits='objectBoundingBox'>
+        <feGaussianBlur stdDeviation='0.1'/>
+    </filter>
+    <rect filter='url(#f1)' transform='scale(2)'/>
+</svg>",
+"<svg>
+    <filter id='f1' primitiveUnits='objectBoundingBox'>
+        <feGaussianBlur stdDeviation='0.1'/>
+    </filter>
+    <rect filter='url(#f1)' transform='scale(2)'/>
+</svg>
+");
+
+    test!(filter_3,
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2'/>
+    </filter>
+    <filter id='f2'>
+        <feGaussianBlur stdDeviation='2 3'/>
+    </filter>
+    <rect filter='url(#f1)' transform='scale(2 1)'/>
+    <rect filter='url(#f2)' transform='scale(2 1)'/>
+</svg>",
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='4 2'/>
+    </filter>
+    <filter id='f2'>
+        <feGaussianBlur stdDeviation='4 3'/>
+    </filter>
+    <rect filter='url(#f1)' transform='scale(2 1)'/>
+    <rect filter='url(#f2)' transform='scale(2 1)'/>
+</svg>
+");
+
+    // Mixed pattern units, unsupported filter primitives and rotation with a bounding box.
+    test!(keep_1,
+"<svg>
+    <pattern id='p1'/>
+    <filter id='f1'>
+        <feOffset dx='2'/>
+    </filter>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#p1)' transform='translate(10 20)'/>
+    <rect filter='url(#f1)' transform='scale(2)'/>
+    <rect fill='url(#lg1)' transform='rotate(90)'/>
+</svg>",
+"<svg>
+    <pattern id='p1'/>
+    <filter id='f1'>
+        <feOffset dx='2'/>
+    </filter>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#p1)' transform='translate(10 20)'/>
+    <rect filter='url(#f1)' transform='scale(2)'/>
+    <rect fill='url(#lg1)' transform='rotate(90)'/>
+</svg>
+");
+}
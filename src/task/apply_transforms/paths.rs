@@ -0,0 +1,308 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::mem;
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    FuzzyEq,
+    Transform,
+};
+use svgdom::path::{
+    Command,
+    Path,
+    Segment,
+    SegmentData,
+};
+
+use task::short::{EId, AId};
+use task::utils::{
+    parallel,
+    AnimatedAttributes,
+};
+use super::utils;
+use super::paint;
+
+pub fn apply_transform_to_paths(doc: &Document, jobs: usize) {
+    // Animations will not be transformed.
+    let animated = AnimatedAttributes::new(doc);
+
+    let mut nodes = Vec::new();
+    let mut items = Vec::new();
+    let iter = doc.descendants().filter(|n|    n.is_tag_name(EId::Path)
+                                            && n.has_attribute(AId::Transform));
+    for mut node in iter {
+        if animated.is_animated(&node) {
+            continue;
+        }
+
+        let ts = utils::get_ts(&node);
+
+        if !utils::is_valid_transform(&ts) || !paint::is_valid_attrs(&node, &ts) {
+            continue;
+        }
+
+        paint::apply_transform(&node, &ts);
+
+        // Paths are detached from the DOM, so they can be processed in parallel.
+        let path = match node.attributes_mut().get_value_mut(AId::D) {
+            Some(&mut AttributeValue::Path(ref mut path)) => Some(mem::replace(path, Path::new())),
+            _ => None,
+        };
+
+        items.push((path, ts));
+        nodes.push(node);
+    }
+
+    parallel::for_each(&mut items, jobs, |&mut (ref mut path, ts)| {
+        if let Some(ref mut path) = *path {
+            apply_transform(path, &ts);
+        }
+    });
+
+    for (mut node, (path, ts)) in nodes.into_iter().zip(items) {
+        if let Some(path) = path {
+            node.set_attribute((AId::D, path));
+        }
+        node.remove_attribute(AId::Transform);
+
+        if ts.has_scale() {
+            // TODO: maybe ignore paths without 'stroke-width'
+
+            // We must update 'stroke-width' if transform had scale part in it.
+            let (sx, _) = ts.get_scale();
+            ::task::utils::recalc_stroke(&mut node, sx);
+        }
+    }
+}
+
+// Segments can be absolute and relative, so we can apply transform without
+// converting the whole path into absolute one.
+pub fn apply_transform(path: &mut Path, ts: &Transform) {
+    let (sx, sy) = ts.get_scale();
+
+    // Relative segments contain only offsets, so translate part should be ignored.
+    let rel_ts = Transform::new(ts.a, ts.b, ts.c, ts.d, 0.0, 0.0);
+
+    // HorizontalLineTo and VerticalLineTo can be preserved only when the transform
+    // doesn't have a rotate part.
+    let keep_hv = ts.b.is_fuzzy_zero() && ts.c.is_fuzzy_zero();
+
+    // Mirroring changes the arc direction.
+    let is_flipped = ts.a * ts.d - ts.b * ts.c < 0.0;
+
+    // Current position in the original coordinates.
+    // We need it to convert HorizontalLineTo and VerticalLineTo into LineTo.
+    let mut prev_x = 0.0;
+    let mut prev_y = 0.0;
+    let mut prev_mx = 0.0;
+    let mut prev_my = 0.0;
+
+    for (idx, seg) in path.d.iter_mut().enumerate() {
+        // The first MoveTo is always absolute, even when it's set as relative.
+        let is_abs = seg.is_absolute() || idx == 0;
+
+        let (x, y) = match *seg.data() {
+            SegmentData::HorizontalLineTo { x } => {
+                if is_abs { (x, prev_y) } else { (x, 0.0) }
+            }
+            SegmentData::VerticalLineTo { y } => {
+                if is_abs { (prev_x, y) } else { (0.0, y) }
+            }
+            SegmentData::ClosePath => (prev_mx, prev_my),
+            _ => (seg.x().unwrap(), seg.y().unwrap()),
+        };
+
+        if seg.cmd() == Command::ClosePath {
+            prev_x = prev_mx;
+            prev_y = prev_my;
+        } else if is_abs {
+            prev_x = x;
+            prev_y = y;
+        } else {
+            prev_x += x;
+            prev_y += y;
+        }
+
+        if seg.cmd() == Command::MoveTo {
+            prev_mx = prev_x;
+            prev_my = prev_y;
+        }
+
+        let seg_ts = if is_abs { ts } else { &rel_ts };
+
+        match seg.cmd() {
+            Command::HorizontalLineTo | Command::VerticalLineTo if !keep_hv => {
+                let (nx, ny) = seg_ts.apply(x, y);
+                let absolute = seg.absolute;
+                *seg = Segment::new_line_to(nx, ny);
+                seg.absolute = absolute;
+                continue;
+            }
+            _ => {}
+        }
+
+        match *seg.data_mut() {
+              SegmentData::MoveTo { ref mut x, ref mut y }
+            | SegmentData::LineTo { ref mut x, ref mut y }
+            | SegmentData::SmoothQuadratic { ref mut x, ref mut y } => {
+                seg_ts.apply_ref(x, y);
+            }
+
+            SegmentData::HorizontalLineTo { ref mut x } => {
+                seg_ts.apply_ref(x, &mut 0.0);
+            }
+
+            SegmentData::VerticalLineTo { ref mut y } => {
+                seg_ts.apply_ref(&mut 0.0, y);
+            }
+
+            SegmentData::CurveTo { ref mut x1, ref mut y1, ref mut x2, ref mut y2,
+                                   ref mut x, ref mut y } => {
+                seg_ts.apply_ref(x1, y1);
+                seg_ts.apply_ref(x2, y2);
+                seg_ts.apply_ref(x,  y);
+            }
+
+            SegmentData::SmoothCurveTo { ref mut x2, ref mut y2, ref mut x, ref mut y } => {
+                seg_ts.apply_ref(x2, y2);
+                seg_ts.apply_ref(x,  y);
+            }
+
+            SegmentData::Quadratic { ref mut x1, ref mut y1, ref mut x, ref mut y } => {
+                seg_ts.apply_ref(x1, y1);
+                seg_ts.apply_ref(x,  y);
+            }
+
+            SegmentData::EllipticalArc { ref mut rx, ref mut ry, ref mut x_axis_rotation,
+                                         ref mut sweep, ref mut x, ref mut y, .. } => {
+                // Only proportional scale is supported for arcs.
+                debug_assert!(sx.fuzzy_eq(&sy));
+
+                *rx *= sx;
+                *ry *= sx;
+
+                // Rotate the ellipse axis the same way as the path itself.
+                let angle = x_axis_rotation.to_radians();
+                let (ax, ay) = rel_ts.apply(angle.cos(), angle.sin());
+                *x_axis_rotation = normalize_angle(ay.atan2(ax).to_degrees());
+
+                if is_flipped {
+                    *sweep = !*sweep;
+                }
+
+                seg_ts.apply_ref(x,  y);
+            }
+
+            SegmentData::ClosePath => {}
+        }
+    }
+}
+
+// Ellipse is symmetric, so the axis rotation can be kept in the 0..180 range.
+fn normalize_angle(angle: f64) -> f64 {
+    let mut angle = angle % 180.0;
+    if angle < 0.0 {
+        angle += 180.0;
+    }
+
+    if angle.fuzzy_eq(&180.0) {
+        angle = 0.0;
+    }
+
+    angle
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+    use svgdom::path::Path;
+    use svgdom::Transform;
+
+    macro_rules! test_path {
+        ($name:ident, $in_path:expr, $in_ts:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut path = Path::from_str($in_path).unwrap();
+                let ts = Transform::from_str($in_ts).unwrap();
+
+                apply_transform(&mut path, &ts);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    test_path!(apply_1, "M 10 20 L 30 40", "translate(10 20)",
+                        "M 20 40 L 40 60");
+
+    test_path!(apply_rel_1, "m 10 20 l 30 40 z m 10 10 l 5 5", "translate(10 20)",
+                            "m 20 40 l 30 40 z m 10 10 l 5 5");
+
+    test_path!(apply_rel_2, "M 10 20 l 30 40 c 1 2 3 4 5 6", "translate(10 20) scale(2)",
+                            "M 30 60 l 60 80 c 2 4 6 8 10 12");
+
+    test_path!(apply_hv_1, "M 10 20 H 30 v 40 h -5 V 10", "translate(10 20) scale(2)",
+                           "M 30 60 H 70 v 80 h -10 V 40");
+
+    test_path!(apply_hv_2, "M 10 20 H 30 v 40", "matrix(0 1 -1 0 0 0)",
+                           "M -20 10 L -20 30 l -40 0");
+
+    test_path!(apply_skew_1, "M 10 20 L 30 40 h 10 V 10", "matrix(1 0 0.5 2 0 0)",
+                             "M 20 40 L 50 80 l 10 0 L 45 20");
+
+    test_path!(apply_arc_1, "M 10 20 A 5 10 30 0 1 20 30", "translate(10 20) scale(2)",
+                            "M 30 60 A 10 20 30 0 1 50 80");
+
+    test_path!(apply_arc_2, "M 10 20 a 5 10 30 0 1 20 30", "matrix(0 1 -1 0 0 0)",
+                            "M -20 10 a 5 10 120 0 1 -30 20");
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                apply_transform_to_paths(&doc, 1);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(apply_doc_1,
+"<svg>
+    <path d='m 10 20 l 30 40' transform='translate(10 20)'/>
+    <path d='M 10 20 L 30 40' stroke-width='2' transform='scale(2)'/>
+</svg>",
+"<svg>
+    <path d='m 20 40 l 30 40'/>
+    <path d='M 20 40 L 60 80' stroke-width='4'/>
+</svg>
+");
+
+    // Non-proportional scale and linked elements are not supported.
+    test_eq!(keep_1,
+"<svg>
+    <mask id='m1'/>
+    <path d='M 10 20 L 30 40' transform='scale(2 3)'/>
+    <path d='M 10 20 L 30 40' mask='url(#m1)' transform='translate(10 20)'/>
+</svg>
+");
+}
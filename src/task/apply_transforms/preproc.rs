@@ -28,10 +28,14 @@ use options::CleaningOptions;
 // If group has transform and contains only valid elements
 // we can apply the group's transform to children before applying transform to
 // actual elements.
+//
+// Nested groups are processed recursively, so the transform will be folded down
+// to the leaf elements.
 pub fn prepare_transforms(parent: &Node, recursive: bool, opt: &CleaningOptions) {
-    let mut valid_elems: Vec<EId> = Vec::with_capacity(6);
+    let mut valid_elems: Vec<EId> = Vec::with_capacity(8);
     if opt.apply_transform_to_shapes {
-        valid_elems.extend_from_slice(&[EId::Rect, EId::Circle, EId::Ellipse, EId::Line]);
+        valid_elems.extend_from_slice(&[EId::Rect, EId::Circle, EId::Ellipse, EId::Line,
+                                        EId::Polyline, EId::Polygon]);
     }
 
     if opt.paths_to_relative && opt.apply_transform_to_paths {
@@ -53,21 +57,7 @@ pub fn prepare_transforms(parent: &Node, recursive: bool, opt: &CleaningOptions)
             continue;
         }
 
-        // Check that all children is valid.
-        let is_valid = node.children().svg().all(|(id, n)| {
-            let is_valid_coords = if id == EId::Path || id == EId::G {
-                true
-            } else {
-                utils::is_valid_coords(&n)
-            };
-
-               valid_elems.contains(&id)
-            && utils::has_valid_transform(&n)
-            && utils::is_valid_attrs(&n)
-            && is_valid_coords
-        });
-
-        if is_valid {
+        if is_valid_children(&node, &valid_elems) {
             let ts = utils::get_ts(&node);
             apply_ts_to_children(&node, ts);
             node.remove_attribute(AId::Transform);
@@ -79,16 +69,47 @@ pub fn prepare_transforms(parent: &Node, recursive: bool, opt: &CleaningOptions)
     }
 }
 
+// Check that all children is valid, including children of the nested groups.
+fn is_valid_children(node: &Node, valid_elems: &[EId]) -> bool {
+    node.children().svg().all(|(id, n)| {
+        let is_valid_coords = if id == EId::Path || id == EId::G {
+            true
+        } else {
+            utils::is_valid_coords(&n)
+        };
+
+        // A referenced element will be rendered without the parent transform,
+        // so we can't change it.
+        if n.is_used() {
+            return false;
+        }
+
+        if id == EId::G && !is_valid_children(&n, valid_elems) {
+            return false;
+        }
+
+           valid_elems.contains(&id)
+        && utils::has_valid_transform(&n)
+        && utils::is_valid_attrs(&n)
+        && is_valid_coords
+    })
+}
+
 fn apply_ts_to_children(node: &Node, ts: Transform) {
-    for (_, mut child) in node.children().svg() {
+    for (id, mut child) in node.children().svg() {
+        let mut ts1 = ts;
         if child.has_attribute(AId::Transform) {
             // We should multiply transform matrices.
-            let mut ts1 = ts;
             let ts2 = utils::get_ts(&child);
             ts1.append(&ts2);
-            child.set_attribute((AId::Transform, ts1));
+        }
+
+        if id == EId::G {
+            // Fold the transform down to the leaf elements.
+            apply_ts_to_children(&child, ts1);
+            child.remove_attribute(AId::Transform);
         } else {
-            child.set_attribute((AId::Transform, ts));
+            child.set_attribute((AId::Transform, ts1));
         }
     }
 }
@@ -133,6 +154,53 @@ mod tests {
         <path transform='translate(10 20)'/>
     </g>
 </svg>
+");
+
+    test!(apply_nested_1,
+"<svg>
+    <g transform='translate(10 20)'>
+        <g transform='scale(2)'>
+            <g>
+                <rect/>
+            </g>
+            <rect transform='translate(1 2)'/>
+        </g>
+        <rect/>
+    </g>
+</svg>",
+"<svg>
+    <g>
+        <g>
+            <g>
+                <rect transform='matrix(2 0 0 2 10 20)'/>
+            </g>
+            <rect transform='matrix(2 0 0 2 12 24)'/>
+        </g>
+        <rect transform='translate(10 20)'/>
+    </g>
+</svg>
+");
+
+    // Nested group should contain only supported children too.
+    test_eq!(keep_nested_1,
+"<svg>
+    <g transform='scale(10)'>
+        <g>
+            <text/>
+        </g>
+        <rect/>
+    </g>
+</svg>
+");
+
+    // Referenced elements are rendered without parent transform.
+    test_eq!(keep_used_1,
+"<svg>
+    <g transform='scale(10)'>
+        <rect id='r1'/>
+    </g>
+    <use xlink:href='#r1'/>
+</svg>
 ");
 
     test_eq!(keep_1,
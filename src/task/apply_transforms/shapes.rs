@@ -17,24 +17,43 @@
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
 use svgdom::{
+    path,
+    AttributeValue,
     Attributes,
     Document,
+    FuzzyEq,
+    Length,
     Node,
     Transform,
+    ValueId,
 };
 
-use task::short::{EId, AId};
+use options::CleaningOptions;
+use task::short::{EId, AId, Unit};
+use task::utils::AnimatedAttributes;
+use task::round_numbers::round_path;
 use super::utils;
+use super::paint;
+use super::paths;
 
 pub fn apply_transform_to_shapes(doc: &Document) {
+    // Animations will not be transformed.
+    let animated = AnimatedAttributes::new(doc);
+
     // Apply transform to shapes.
     let iter = doc.descendants().filter(|n| n.has_attribute(AId::Transform));
     for mut node in iter {
+        if animated.is_animated(&node) {
+            continue;
+        }
+
         match node.tag_id().unwrap() {
             EId::Rect => process_rect(&mut node),
             EId::Circle => process_circle(&mut node),
             EId::Ellipse => process_ellipse(&mut node),
             EId::Line => process_line(&mut node),
+            EId::Polyline |
+            EId::Polygon => process_poly(&mut node),
             _ => {}
         }
     }
@@ -43,14 +62,18 @@ pub fn apply_transform_to_shapes(doc: &Document) {
 fn process<F>(node: &mut Node, func: F)
     where F : Fn(&mut Attributes, &Transform)
 {
-    if    !utils::has_valid_transform(node)
-       || !utils::is_valid_attrs(node)
-       || !utils::is_valid_coords(node) {
+    if !utils::has_valid_transform(node) || !utils::is_valid_coords(node) {
         return;
     }
 
     let ts = utils::get_ts(node);
 
+    if !paint::is_valid_attrs(node, &ts) {
+        return;
+    }
+
+    paint::apply_transform(node, &ts);
+
     {
         let mut attrs = node.attributes_mut();
         func(&mut attrs, &ts);
@@ -110,6 +133,228 @@ fn process_line(node: &mut Node) {
     });
 }
 
+fn process_poly(node: &mut Node) {
+    process(node, |attrs, ts| {
+        if let Some(&mut AttributeValue::NumberList(ref mut points)) = attrs.get_value_mut(AId::Points) {
+            // Points with an odd count of coordinates must be fixed in fix_attrs::fix_poly.
+            for p in points.chunks_mut(2) {
+                if p.len() == 2 {
+                    let (x, y) = ts.apply(p[0], p[1]);
+                    p[0] = x;
+                    p[1] = y;
+                }
+            }
+        }
+    });
+}
+
+// Transforms with skew or non-proportional scale can't be applied to shapes directly,
+// but they can be applied to an equivalent path.
+//
+// Run after 'apply_transform_to_shapes'.
+pub fn apply_any_transform_to_shapes(doc: &Document, opt: &CleaningOptions) {
+    let animated = AnimatedAttributes::new(doc);
+
+    let iter = doc.descendants().filter(|n| n.has_attribute(AId::Transform));
+    for mut node in iter {
+        let id = node.tag_id().unwrap();
+        match id {
+            EId::Rect | EId::Circle | EId::Ellipse | EId::Line |
+            EId::Polyline | EId::Polygon => {}
+            _ => continue,
+        }
+
+        if animated.is_animated(&node) {
+            continue;
+        }
+
+        // Simple transforms are already processed by 'apply_transform_to_shapes'.
+        if utils::has_valid_transform(&node) || !utils::is_valid_coords(&node) {
+            continue;
+        }
+
+        let ts = utils::get_ts(&node);
+
+        if !paint::is_valid_attrs(&node, &ts) {
+            continue;
+        }
+
+        // Stroke can't be transformed in a non-proportional way.
+        if has_stroke(&node) {
+            continue;
+        }
+
+        // Markers are rendered using the element transform.
+        if node.has_attributes(&[AId::Marker, AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd]) {
+            continue;
+        }
+
+        let path = match shape_to_path(&node, id, &ts) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        // Arcs require more numbers than a circle or an ellipse,
+        // so the path is often longer than the original element with its transform.
+        if (id == EId::Circle || id == EId::Ellipse)
+            && !is_path_shorter(&node, &path, opt.paths_coordinates_precision) {
+            continue;
+        }
+
+        paint::apply_transform(&node, &ts);
+
+        node.set_tag_name(EId::Path);
+        node.set_attribute((AId::D, path));
+        node.remove_attributes(&[AId::X, AId::Y, AId::Width, AId::Height, AId::Rx, AId::Ry,
+                                 AId::Cx, AId::Cy, AId::R, AId::X1, AId::Y1, AId::X2, AId::Y2,
+                                 AId::Points, AId::Transform]);
+    }
+}
+
+fn has_stroke(node: &Node) -> bool {
+    for n in node.parents_with_self() {
+        if let Some(v) = n.attributes().get_value(AId::Stroke) {
+            return *v != AttributeValue::PredefValue(ValueId::None);
+        }
+    }
+
+    false
+}
+
+// Checks that the path is shorter than the shape attributes and transform it replaces.
+fn is_path_shorter(node: &Node, path: &path::Path, precision: u8) -> bool {
+    let mut path = path.clone();
+    round_path(&mut path, precision as usize);
+
+    // 'Attribute' is printed as 'name="value"' and we need a leading space too.
+    let attrs_len: usize = node.attributes().iter()
+        .filter(|a| a.has_id(AId::Cx) || a.has_id(AId::Cy) || a.has_id(AId::R)
+                    || a.has_id(AId::Rx) || a.has_id(AId::Ry) || a.has_id(AId::Transform))
+        .map(|a| 1 + format!("{}", a).len())
+        .sum();
+
+    let path_len = " d=''".len() + format!("{}", path).len();
+
+    let tag_len = if node.is_tag_name(EId::Circle) { "circle".len() } else { "ellipse".len() };
+
+    "path".len() + path_len < tag_len + attrs_len
+}
+
+// Returns a path equivalent to the shape with the transform applied.
+fn shape_to_path(node: &Node, id: EId, ts: &Transform) -> Option<path::Path> {
+    let attrs = node.attributes();
+
+    // Only unitless coordinates are supported.
+    let get_num = |aid: AId| -> Option<f64> {
+        let len = get_value!(attrs, Length, aid, Length::zero());
+        if len.unit == Unit::None { Some(len.num) } else { None }
+    };
+
+    let mut path = match id {
+        EId::Rect => {
+            // Rounded rects are not supported.
+            if !get_num(AId::Rx)?.is_fuzzy_zero() || !get_num(AId::Ry)?.is_fuzzy_zero() {
+                return None;
+            }
+
+            let x = get_num(AId::X)?;
+            let y = get_num(AId::Y)?;
+            let w = get_num(AId::Width)?;
+            let h = get_num(AId::Height)?;
+
+            path::Builder::new()
+                .move_to(x, y)
+                .hline_to(x + w)
+                .vline_to(y + h)
+                .hline_to(x)
+                .close_path()
+                .finalize()
+        }
+        EId::Circle => {
+            let r = get_num(AId::R)?;
+            return ellipse_to_path(get_num(AId::Cx)?, get_num(AId::Cy)?, r, r, ts);
+        }
+        EId::Ellipse => {
+            return ellipse_to_path(get_num(AId::Cx)?, get_num(AId::Cy)?,
+                                   get_num(AId::Rx)?, get_num(AId::Ry)?, ts);
+        }
+        EId::Line => {
+            path::Builder::new()
+                .move_to(get_num(AId::X1)?, get_num(AId::Y1)?)
+                .line_to(get_num(AId::X2)?, get_num(AId::Y2)?)
+                .finalize()
+        }
+        EId::Polyline | EId::Polygon => {
+            let points = match attrs.get_value(AId::Points) {
+                Some(AttributeValue::NumberList(points)) => points,
+                _ => return None,
+            };
+
+            let mut path = path::Path::with_capacity(points.len() / 2 + 1);
+            for p in points.chunks(2).filter(|p| p.len() == 2) {
+                if path.d.is_empty() {
+                    path.d.push(path::Segment::new_move_to(p[0], p[1]));
+                } else {
+                    path.d.push(path::Segment::new_line_to(p[0], p[1]));
+                }
+            }
+
+            if path.d.is_empty() {
+                return None;
+            }
+
+            if id == EId::Polygon {
+                path.d.push(path::Segment::new_close_path());
+            }
+
+            path
+        }
+        _ => return None,
+    };
+
+    paths::apply_transform(&mut path, ts);
+
+    Some(path)
+}
+
+// Any affine transform maps an ellipse onto another ellipse, so instead of transforming
+// the arcs we are calculating the axes of the resulting ellipse.
+fn ellipse_to_path(cx: f64, cy: f64, rx: f64, ry: f64, ts: &Transform) -> Option<path::Path> {
+    // Singular value decomposition of the transform matrix scaled by the radii.
+    // https://scicomp.stackexchange.com/a/8899
+    let (a, b, c, d) = (ts.a * rx, ts.b * rx, ts.c * ry, ts.d * ry);
+    let e = (a + d) / 2.0;
+    let f = (a - d) / 2.0;
+    let g = (b + c) / 2.0;
+    let h = (b - c) / 2.0;
+    let q = (e * e + h * h).sqrt();
+    let r = (f * f + g * g).sqrt();
+
+    let new_rx = q + r;
+    let new_ry = (q - r).abs();
+    if new_rx.is_fuzzy_zero() || new_ry.is_fuzzy_zero() {
+        return None;
+    }
+
+    let angle = (g.atan2(f) + h.atan2(e)) / 2.0;
+    let angle = angle.to_degrees() % 180.0;
+    let angle = if angle < 0.0 { angle + 180.0 } else { angle };
+
+    // Two opposite points of the ellipse stay opposite after the transform,
+    // so each arc is exactly a half of the ellipse.
+    let (x1, y1) = ts.apply(cx + rx, cy);
+    let (x2, y2) = ts.apply(cx - rx, cy);
+
+    let path = path::Builder::new()
+        .move_to(x1, y1)
+        .arc_to(new_rx, new_ry, angle, false, true, x2, y2)
+        .arc_to(new_rx, new_ry, angle, false, true, x1, y1)
+        .close_path()
+        .finalize();
+
+    Some(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,6 +436,143 @@ mod tests {
 "<svg>
     <line stroke-width='2' x1='30' x2='30' y1='50' y2='50'/>
 </svg>
+");
+
+    test!(apply_polyline_1,
+"<svg>
+    <polyline points='10 20 30 40 50 60' transform='translate(10 20) scale(2)'/>
+</svg>",
+"<svg>
+    <polyline points='30 60 70 100 110 140' stroke-width='2'/>
+</svg>
+");
+
+    test!(apply_polygon_1,
+"<svg>
+    <polygon points='10 20 30 40 50 60' transform='translate(10 20)'/>
+</svg>",
+"<svg>
+    <polygon points='20 40 40 60 60 80'/>
+</svg>
+");
+
+    // Animation values are not transformed.
+    test!(keep_animated_1,
+"<svg>
+    <rect height='10' width='10' transform='translate(10 20)'>
+        <animate attributeName='width' to='20'/>
+    </rect>
+</svg>",
+"<svg>
+    <rect height='10' transform='translate(10 20)' width='10'>
+        <animate attributeName='width' to='20'/>
+    </rect>
+</svg>
+");
+
+    macro_rules! test_any {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                apply_any_transform_to_shapes(&doc, &CleaningOptions::default());
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test_any!(apply_any_1,
+"<svg>
+    <rect height='10' width='10' x='10' y='10' transform='scale(2 3)'/>
+    <line x1='10' x2='20' y1='10' y2='20' transform='matrix(1 0 0.5 1 0 0)'/>
+    <polygon points='10 20 30 40' transform='scale(1 2)'/>
+</svg>",
+"<svg>
+    <path d='M 20 30 H 40 V 60 H 20 Z'/>
+    <path d='M 15 10 L 30 20'/>
+    <path d='M 10 40 L 30 80 Z'/>
+</svg>
+");
+
+    test_any!(apply_any_2,
+"<svg>
+    <circle cx='10' cy='10' r='10' transform='scale(1 2)'/>
+    <ellipse cx='10' cy='10' rx='10' ry='20' transform='scale(-1 2)'/>
+</svg>",
+"<svg>
+    <path d='M 20 20 A 20 10 90 0 1 0 20 A 20 10 90 0 1 20 20 Z'/>
+    <path d='M -20 20 A 40 10 90 0 1 0 20 A 40 10 90 0 1 -20 20 Z'/>
+</svg>
+");
+
+    // Keep circles and ellipses when the path is not shorter.
+    test_any!(keep_any_ellipse_1,
+"<svg>
+    <circle r='1' transform='matrix(1.2345678 0.1234567 0.5123456 1.3456789 10.1234567 20.1234567)'/>
+    <ellipse cx='10' cy='10' rx='10' ry='5' transform='matrix(1 0 0.57735026919 1 0 0)'/>
+</svg>",
+"<svg>
+    <circle r='1' transform='matrix(1.2345678 0.1234567 0.5123456 1.3456789 10.1234567 20.1234567)'/>
+    <ellipse cx='10' cy='10' rx='10' ry='5' transform='matrix(1 0 0.57735026919 1 0 0)'/>
+</svg>
+");
+
+    // Ignore shapes with stroke or with valid transforms.
+    test_any!(keep_any_1,
+"<svg>
+    <rect height='10' stroke='#ff0000' transform='scale(2 3)' width='10'/>
+    <g stroke='#ff0000'>
+        <rect height='10' transform='scale(2 3)' width='10'/>
+    </g>
+    <rect height='10' rx='2' transform='scale(2 3)' width='10'/>
+    <rect height='10' transform='scale(2)' width='10'/>
+</svg>",
+"<svg>
+    <rect height='10' stroke='#ff0000' transform='scale(2 3)' width='10'/>
+    <g stroke='#ff0000'>
+        <rect height='10' transform='scale(2 3)' width='10'/>
+    </g>
+    <rect height='10' rx='2' transform='scale(2 3)' width='10'/>
+    <rect height='10' transform='scale(2)' width='10'/>
+</svg>
+");
+
+    test!(apply_paint_1,
+"<svg>
+    <linearGradient id='lg1' gradientUnits='userSpaceOnUse' x1='0' x2='10' y1='0' y2='0'/>
+    <rect fill='url(#lg1)' height='10' width='10' transform='translate(10 20)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1' gradientTransform='translate(10 20)' gradientUnits='userSpaceOnUse' x1='0' x2='10' y1='0' y2='0'/>
+    <rect fill='url(#lg1)' height='10' width='10' x='10' y='20'/>
+</svg>
+");
+
+    test!(apply_paint_2,
+"<svg>
+    <linearGradient id='lg1' gradientTransform='scale(2)' gradientUnits='userSpaceOnUse'/>
+    <linearGradient id='lg2'/>
+    <rect fill='url(#lg1)' height='10' width='10' transform='translate(10 20)'/>
+    <rect stroke='url(#lg2)' height='10' width='10' transform='translate(10 20)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1' gradientTransform='matrix(2 0 0 2 10 20)' gradientUnits='userSpaceOnUse'/>
+    <linearGradient id='lg2'/>
+    <rect fill='url(#lg1)' height='10' width='10' x='10' y='20'/>
+    <rect height='10' stroke='url(#lg2)' width='10' x='10' y='20'/>
+</svg>
+");
+
+    // Ignore gradients that are used by other elements.
+    test_eq!(keep_paint_1,
+"<svg>
+    <linearGradient id='lg1' gradientUnits='userSpaceOnUse'/>
+    <linearGradient id='lg2' gradientUnits='userSpaceOnUse'/>
+    <linearGradient id='lg3' xlink:href='#lg2'/>
+    <rect fill='url(#lg1)' height='10' transform='translate(10 20)' width='10'/>
+    <rect fill='url(#lg1)' height='10' width='10'/>
+    <rect fill='url(#lg2)' height='10' transform='translate(10 20)' width='10'/>
+</svg>
 ");
 
     // Ignore shapes with invalid coordinates units.
@@ -0,0 +1,323 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Attribute,
+    AttributeType,
+    Document,
+    Indent,
+    Node,
+    WriteOptions,
+};
+
+use options::CleaningOptions;
+use task::short::{EId, AId};
+use task::utils;
+
+// Moves inheritable attributes between groups and their children,
+// choosing the representation with the fewest bytes.
+//
+// Groups, which are more expensive than their attributes written on each child,
+// are removed. And attributes with the same value on all children of a group
+// are moved to this group.
+pub fn collapse_attributes(doc: &Document, options: &CleaningOptions, opt: &WriteOptions) {
+    // 'doc' must contain 'svg' node, so we can safely unwrap.
+    let svg = doc.svg_element().unwrap();
+
+    while push_down(&svg, options, opt) {}
+
+    hoist(&svg);
+}
+
+// Returns `true` when a group was removed.
+//
+// Like in 'ungroup_groups', we start again from the root element after each change.
+fn push_down(parent: &Node, options: &CleaningOptions, opt: &WriteOptions) -> bool {
+    for mut node in parent.children() {
+        if node.is_tag_name(EId::G) && can_push_down(parent, &node, options, opt) {
+            let attrs: Vec<Attribute> = node.attributes().iter()
+                                            .filter(|a| a.visible).cloned().collect();
+
+            for (_, mut child) in node.children().svg() {
+                for attr in &attrs {
+                    if !has_visible_attr(&child, attr.id().unwrap()) {
+                        child.set_attribute(attr.clone());
+                    }
+                }
+            }
+
+            while node.has_children() {
+                let mut c = node.last_child().unwrap();
+                c.detach();
+                node.insert_after(&c);
+            }
+            node.remove();
+
+            return true;
+        }
+
+        if node.has_children() && push_down(&node, options, opt) {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn can_push_down(parent: &Node, g: &Node, options: &CleaningOptions, opt: &WriteOptions) -> bool {
+    if    g.is_used()
+       || utils::is_kept_id(g, &options.keep_ids)
+       || utils::has_accessibility_attrs(g) {
+        return false;
+    }
+
+    if parent.is_tag_name(EId::Switch) || parent.is_tag_name(EId::ClipPath) {
+        return false;
+    }
+
+    // Only inheritable attributes can be moved to children.
+    if !g.attributes().iter().filter(|a| a.visible).all(|a| a.is_inheritable()) {
+        return false;
+    }
+
+    if !g.children().svg().all(|(id, n)| is_rendered(id) && !n.is_used()) {
+        return false;
+    }
+
+    // The cost of attributes written on each child without them.
+    let mut push_down_len = 0;
+    for attr in g.attributes().iter().filter(|a| a.visible) {
+        let aid = attr.id().unwrap();
+        let count = g.children().svg().filter(|(_, n)| !has_visible_attr(n, aid)).count();
+        push_down_len += count * attr_len(attr);
+    }
+
+    // The cost of '<g ...>' and '</g>'.
+    let mut g_len = "<g></g>".len();
+    for attr in g.attributes().iter().filter(|a| a.visible) {
+        g_len += attr_len(attr);
+    }
+    if g.has_id() {
+        // 'id' will be lost, but it's unreferenced anyway.
+        g_len += " id=''".len() + g.id().len();
+    }
+    if opt.indent != Indent::None {
+        // Two additional lines.
+        g_len += 2;
+    }
+
+    push_down_len < g_len
+}
+
+// Moves attributes shared by all children to the parent container.
+//
+// Processed from the bottom to the top, so attributes can be moved
+// to the nearest common ancestor.
+fn hoist(parent: &Node) {
+    for (_, node) in parent.children().svg() {
+        if node.has_children() {
+            hoist(&node);
+        }
+    }
+
+    if !parent.is_tag_name(EId::Svg) && !parent.is_tag_name(EId::G) {
+        return;
+    }
+
+    let children: Vec<Node> = parent.children().svg()
+        .filter(|&(id, _)| !is_non_rendered(id))
+        .map(|(_, n)| n)
+        .collect();
+
+    // Moving an attribute from a single child is not smaller.
+    if children.len() < 2 {
+        return;
+    }
+
+    // A referenced element inherits attributes from the 'use' element
+    // and not from the parent one.
+    let is_valid = parent.children().svg().all(|(id, n)| {
+        (is_rendered(id) || is_non_rendered(id)) && !n.is_used()
+    });
+    if !is_valid {
+        return;
+    }
+
+    let mut shared: Vec<Attribute> = children[0].attributes().iter()
+        .filter(|a| a.visible && a.is_inheritable())
+        .cloned()
+        .collect();
+
+    shared.retain(|attr| {
+        children.iter().skip(1).all(|n| {
+            match n.attributes().get(attr.id().unwrap()) {
+                Some(a) => a.visible && a.value == attr.value,
+                None => false,
+            }
+        })
+    });
+
+    let mut parent = parent.clone();
+    for attr in shared {
+        let aid = attr.id().unwrap();
+        for child in &children {
+            let mut child = child.clone();
+            child.remove_attribute(aid);
+        }
+
+        parent.set_attribute(attr);
+    }
+}
+
+fn has_visible_attr(node: &Node, aid: AId) -> bool {
+    node.attributes().get(aid).map(|a| a.visible) == Some(true)
+}
+
+// Returns a length of the attribute with a leading space.
+fn attr_len(attr: &Attribute) -> usize {
+    // 'Attribute' is printed as 'name="value"'.
+    1 + format!("{}", attr).len()
+}
+
+// Elements, which inherit attributes from the parent.
+fn is_rendered(id: EId) -> bool {
+    matches!(id, EId::G | EId::A | EId::Switch | EId::Svg | EId::ForeignObject |
+                 EId::Rect | EId::Circle | EId::Ellipse | EId::Line |
+                 EId::Polyline | EId::Polygon | EId::Path |
+                 EId::Text | EId::Image | EId::Use)
+}
+
+// Elements, which are not affected by attributes.
+fn is_non_rendered(id: EId) -> bool {
+    matches!(id, EId::Title | EId::Desc | EId::Metadata | EId::Style | EId::Script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                collapse_attributes(doc, &CleaningOptions::default(), &WriteOptions::default())
+            }, $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    test!(hoist_1,
+"<svg>
+    <g opacity='0.5'>
+        <rect fill='#ff0000' stroke='#000000'/>
+        <rect fill='#ff0000' stroke='#0000ff'/>
+    </g>
+    <rect/>
+</svg>",
+"<svg>
+    <g fill='#ff0000' opacity='0.5'>
+        <rect stroke='#000000'/>
+        <rect stroke='#0000ff'/>
+    </g>
+    <rect/>
+</svg>
+");
+
+    // Attributes should be moved to the nearest common ancestor.
+    test!(hoist_2,
+"<svg>
+    <g opacity='0.5'>
+        <g stroke='#000000'>
+            <rect fill='#ff0000'/>
+            <rect fill='#ff0000'/>
+        </g>
+        <g stroke='#0000ff'>
+            <rect fill='#ff0000'/>
+            <rect fill='#ff0000'/>
+        </g>
+    </g>
+    <rect/>
+</svg>",
+"<svg>
+    <g fill='#ff0000' opacity='0.5'>
+        <g stroke='#000000'>
+            <rect/>
+            <rect/>
+        </g>
+        <g stroke='#0000ff'>
+            <rect/>
+            <rect/>
+        </g>
+    </g>
+    <rect/>
+</svg>
+");
+
+    test!(push_down_1,
+"<svg>
+    <g fill='#ff0000'>
+        <rect/>
+        <rect fill='#0000ff'/>
+    </g>
+    <rect/>
+</svg>",
+"<svg>
+    <rect fill='#ff0000'/>
+    <rect fill='#0000ff'/>
+    <rect/>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg>
+    <g fill='#ff0000'>
+        <rect/>
+        <rect/>
+        <rect/>
+    </g>
+    <rect/>
+</svg>
+");
+
+    // Non-inheritable attributes can't be moved.
+    test_eq!(keep_2,
+"<svg>
+    <g opacity='0.5'>
+        <rect opacity='0.5'/>
+        <rect opacity='0.5'/>
+    </g>
+    <rect/>
+</svg>
+");
+
+    // A referenced element inherits attributes from the 'use' element.
+    test_eq!(keep_3,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <g>
+        <rect id='rect1' fill='#ff0000'/>
+        <rect fill='#ff0000'/>
+    </g>
+    <use xlink:href='#rect1'/>
+</svg>
+");
+}
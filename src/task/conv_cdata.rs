@@ -0,0 +1,109 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Document,
+    Node,
+    NodeType,
+};
+
+// Converts CDATA sections into an escaped text, when it's shorter.
+//
+// CDATA inside the 'style' element is already parsed by the parser,
+// so only the text content is processed.
+pub fn convert_cdata(doc: &mut Document) {
+    let nodes: Vec<Node> = doc.descendants().filter(|n| n.node_type() == NodeType::Cdata).collect();
+
+    for mut node in nodes {
+        let text = node.text().clone();
+
+        // '<![CDATA[' and ']]>'.
+        let cdata_len = text.len() + 12;
+        let text_len = text.len() + text.matches('&').count() * 4
+                       + text.matches(&['<', '>'][..]).count() * 3;
+
+        if text_len > cdata_len {
+            continue;
+        }
+
+        // Join with a previous text node to produce a single one.
+        match node.previous_sibling() {
+            Some(ref mut prev) if prev.node_type() == NodeType::Text => {
+                let new_text = format!("{}{}", *prev.text(), text);
+                prev.set_text(&new_text);
+            }
+            _ => {
+                let new_node = doc.create_node(NodeType::Text, &text);
+                node.insert_before(&new_node);
+            }
+        }
+
+        // Join with a next text node.
+        if let Some(mut next) = node.next_sibling() {
+            if next.node_type() == NodeType::Text {
+                let mut prev = node.previous_sibling().unwrap();
+                let new_text = format!("{}{}", *prev.text(), *next.text());
+                prev.set_text(&new_text);
+                next.remove();
+            }
+        }
+
+        node.remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, convert_cdata, $in_text, $out_text);
+        )
+    }
+
+    test!(convert_1,
+"<svg>
+    <text><![CDATA[a < b]]></text>
+</svg>",
+"<svg>
+    <text>a &lt; b</text>
+</svg>
+");
+
+    test!(convert_2,
+"<svg>
+    <text>Text:<![CDATA[a < b]]></text>
+</svg>",
+"<svg>
+    <text>Text:a &lt; b</text>
+</svg>
+");
+
+    test!(keep_1,
+"<svg>
+    <text><![CDATA[<<<<<>>>>>]]></text>
+</svg>",
+"<svg>
+    <text>
+    <![CDATA[<<<<<>>>>>]]>
+    </text>
+</svg>
+");
+}
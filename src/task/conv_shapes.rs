@@ -23,9 +23,16 @@ use svgdom::{
     FuzzyEq,
     Length,
     Node,
+    WriteBuffer,
+    WriteOptions,
 };
 
+use options::CleaningOptions;
 use task::short::{EId, AId, Unit};
+use task::apply_transforms::utils::is_valid_attr;
+use task::merge_paths;
+use task::round_numbers::{round_number, round_path};
+use task::utils::{self, AnimatedAttributes};
 
 // TODO: convert thin rect to line-to path
 // view-calendar-list.svg
@@ -34,7 +41,14 @@ use task::short::{EId, AId, Unit};
 //
 // We should run it before path processing.
 pub fn convert_shapes_to_paths(doc: &Document) {
+    // Animated shape attributes, like 'width', will be lost after conversion.
+    let animated = AnimatedAttributes::new(doc);
+
     for (id, mut node) in doc.descendants().svg() {
+        if animated.is_animated(&node) {
+            continue;
+        }
+
         match id {
             EId::Line => convert_line(&mut node),
             EId::Rect => convert_rect(&mut node),
@@ -176,6 +190,207 @@ fn points_to_path(node: &Node) -> Option<path::Path> {
     Some(path)
 }
 
+// Converts any basic shape, including circles, ellipses and rounded rects,
+// when its path is shorter after rounding or when it can be merged with a sibling path.
+//
+// Run after 'convert_shapes_to_paths' and before 'merge_paths'.
+pub fn convert_shapes_to_shorter_paths(doc: &Document, options: &CleaningOptions, opt: &WriteOptions) {
+    let animated = AnimatedAttributes::new(doc);
+    let keep_ids = utils::kept_ids(doc, &options.keep_ids);
+
+    let nodes: Vec<Node> = doc.descendants().filter(|n| shape_to_path(n).is_some()).collect();
+    for mut node in nodes {
+        if animated.is_animated(&node) {
+            continue;
+        }
+
+        let path = match shape_to_path(&node) {
+            Some(path) => path,
+            None => continue,
+        };
+
+        let aids = shape_attributes(&node);
+
+        let can_merge = options.merge_paths && [node.previous_sibling(), node.next_sibling()]
+            .iter()
+            .filter_map(|n| n.as_ref())
+            .any(|sibling| {
+                let sibling_path = match sibling.attributes().get_value(AId::D) {
+                    Some(AttributeValue::Path(path)) if sibling.is_tag_name(EId::Path) => path.clone(),
+                    _ => match shape_to_path(sibling) {
+                        Some(path) => path,
+                        None => return false,
+                    },
+                };
+
+                merge_paths::can_merge(&node, &path, sibling, &sibling_path, &keep_ids, &animated)
+            });
+
+        if can_merge || is_shorter(&node, &path, aids, options, opt) {
+            node.set_tag_name(EId::Path);
+            node.set_attribute((AId::D, path));
+            node.remove_attributes(aids);
+        }
+    }
+}
+
+fn shape_attributes(node: &Node) -> &'static [AId] {
+    match node.tag_id() {
+        Some(EId::Line) => &[AId::X1, AId::Y1, AId::X2, AId::Y2],
+        Some(EId::Rect) => &[AId::X, AId::Y, AId::Rx, AId::Ry, AId::Width, AId::Height],
+        Some(EId::Circle) => &[AId::Cx, AId::Cy, AId::R],
+        Some(EId::Ellipse) => &[AId::Cx, AId::Cy, AId::Rx, AId::Ry],
+        Some(EId::Polyline) | Some(EId::Polygon) => &[AId::Points],
+        _ => &[],
+    }
+}
+
+// Returns an equivalent path of the shape.
+fn shape_to_path(node: &Node) -> Option<path::Path> {
+    let id = node.tag_id()?;
+
+    // Markers are rendered only on paths, lines, polylines and polygons.
+    if matches!(id, EId::Rect | EId::Circle | EId::Ellipse)
+        && [AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd].iter().any(|aid| !is_valid_attr(node, *aid)) {
+        return None;
+    }
+
+    let attrs = node.attributes();
+
+    // Only unitless coordinates are supported.
+    let get_num = |aid: AId| -> Option<f64> {
+        let len = get_value!(attrs, Length, aid, Length::zero());
+        if len.unit == Unit::None { Some(len.num) } else { None }
+    };
+
+    let path = match id {
+        EId::Line => {
+            path::Builder::new()
+                .move_to(get_num(AId::X1)?, get_num(AId::Y1)?)
+                .line_to(get_num(AId::X2)?, get_num(AId::Y2)?)
+                .finalize()
+        }
+        EId::Rect => {
+            let (x, y) = (get_num(AId::X)?, get_num(AId::Y)?);
+            let (w, h) = (get_num(AId::Width)?, get_num(AId::Height)?);
+
+            // Invisible rects are removed by 'remove_invisible_elements'.
+            if !(w > 0.0 && h > 0.0) {
+                return None;
+            }
+
+            // A missing radius is equal to the other one.
+            let (rx, ry) = match (attrs.contains(AId::Rx), attrs.contains(AId::Ry)) {
+                (true, false) => (get_num(AId::Rx)?, get_num(AId::Rx)?),
+                (false, true) => (get_num(AId::Ry)?, get_num(AId::Ry)?),
+                _ => (get_num(AId::Rx)?, get_num(AId::Ry)?),
+            };
+            let rx = rx.max(0.0).min(w / 2.0);
+            let ry = ry.max(0.0).min(h / 2.0);
+
+            if rx.is_fuzzy_zero() || ry.is_fuzzy_zero() {
+                path::Builder::new()
+                    .move_to(x, y)
+                    .hline_to(x + w)
+                    .vline_to(y + h)
+                    .hline_to(x)
+                    .close_path()
+                    .finalize()
+            } else {
+                path::Builder::new()
+                    .move_to(x + rx, y)
+                    .hline_to(x + w - rx)
+                    .arc_to(rx, ry, 0.0, false, true, x + w, y + ry)
+                    .vline_to(y + h - ry)
+                    .arc_to(rx, ry, 0.0, false, true, x + w - rx, y + h)
+                    .hline_to(x + rx)
+                    .arc_to(rx, ry, 0.0, false, true, x, y + h - ry)
+                    .vline_to(y + ry)
+                    .arc_to(rx, ry, 0.0, false, true, x + rx, y)
+                    .close_path()
+                    .finalize()
+            }
+        }
+        EId::Circle | EId::Ellipse => {
+            let (cx, cy) = (get_num(AId::Cx)?, get_num(AId::Cy)?);
+            let (rx, ry) = if id == EId::Circle {
+                (get_num(AId::R)?, get_num(AId::R)?)
+            } else {
+                (get_num(AId::Rx)?, get_num(AId::Ry)?)
+            };
+
+            if !(rx > 0.0 && ry > 0.0) {
+                return None;
+            }
+
+            path::Builder::new()
+                .move_to(cx + rx, cy)
+                .arc_to(rx, ry, 0.0, false, true, cx - rx, cy)
+                .arc_to(rx, ry, 0.0, false, true, cx + rx, cy)
+                .close_path()
+                .finalize()
+        }
+        EId::Polyline | EId::Polygon => {
+            let mut path = points_to_path(node)?;
+            if path.d.is_empty() {
+                return None;
+            }
+
+            if id == EId::Polygon {
+                path.d.push(path::Segment::new_close_path());
+            }
+
+            path
+        }
+        _ => return None,
+    };
+
+    Some(path)
+}
+
+// Checks that the path is shorter than the shape attributes it replaces.
+//
+// Paths are converted to relative coordinates later, which usually makes them even shorter,
+// so the estimate is conservative.
+fn is_shorter(
+    node: &Node,
+    path: &path::Path,
+    aids: &[AId],
+    options: &CleaningOptions,
+    opt: &WriteOptions,
+) -> bool {
+    let mut path = path.clone();
+    round_path(&mut path, options.paths_coordinates_precision as usize);
+
+    let mut buf = Vec::new();
+    path.write_buf_opt(opt, &mut buf);
+    let path_len = "path".len() + " d=''".len() + buf.len();
+
+    // Shapes are always SVG elements, so we can safely unwrap.
+    let mut shape_len = node.tag_id().unwrap().name().len();
+    for attr in node.attributes().iter().filter(|a| aids.iter().any(|aid| a.has_id(*aid))) {
+        let mut attr = attr.clone();
+        match attr.value {
+            AttributeValue::Length(ref mut len) => {
+                round_number(&mut len.num, options.coordinates_precision as usize);
+            }
+            AttributeValue::NumberList(ref mut list) => {
+                for n in list.iter_mut() {
+                    round_number(n, options.paths_coordinates_precision as usize);
+                }
+            }
+            _ => {}
+        }
+
+        // 'Attribute' is printed as 'name="value"' and we need a leading space too.
+        buf.clear();
+        attr.write_buf_opt(opt, &mut buf);
+        shape_len += 1 + buf.len();
+    }
+
+    path_len < shape_len
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -273,5 +488,104 @@ mod tests {
 "<svg>
     <path d='M 30 40 L 50 60 L 70 80 Z'/>
 </svg>
+");
+
+    test_eq!(keep_animated_1,
+"<svg>
+    <rect height='10' width='10'>
+        <animate attributeName='width' to='20'/>
+    </rect>
+</svg>
+");
+
+    macro_rules! test_shorter {
+        ($name:ident, $merge:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+
+                let mut options = CleaningOptions::default();
+                options.merge_paths = $merge;
+                options.coordinates_precision = 6;
+                options.paths_coordinates_precision = 8;
+
+                // The size is estimated using the final write options.
+                let mut opt = WriteOptions::default();
+                opt.remove_leading_zero = true;
+                opt.use_compact_path_notation = true;
+                opt.remove_duplicated_path_commands = true;
+                opt.use_implicit_lineto_commands = true;
+
+                convert_shapes_to_shorter_paths(&doc, &options, &opt);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    // Shapes, which can be merged with a sibling path or with each other.
+    test_shorter!(conv_mergeable_1, true,
+"<svg>
+    <path d='M 0 0 L 10 10' fill='#ff0000'/>
+    <circle cx='50' cy='50' fill='#ff0000' r='10'/>
+    <ellipse cx='100' cy='100' fill='#ff0000' rx='10' ry='5'/>
+</svg>",
+"<svg>
+    <path d='M 0 0 L 10 10' fill='#ff0000'/>
+    <path d='M 60 50 A 10 10 0 0 1 40 50 A 10 10 0 0 1 60 50 Z' fill='#ff0000'/>
+    <path d='M 110 100 A 10 5 0 0 1 90 100 A 10 5 0 0 1 110 100 Z' fill='#ff0000'/>
+</svg>
+");
+
+    test_shorter!(conv_mergeable_2, true,
+"<svg>
+    <rect fill='#ff0000' height='10' rx='2' width='10'/>
+    <rect fill='#ff0000' height='10' rx='2' width='10' x='20'/>
+</svg>",
+"<svg>
+    <path d='M 2 0 H 8 A 2 2 0 0 1 10 2 V 8 A 2 2 0 0 1 8 10 H 2 A 2 2 0 0 1 0 8 V 2 A 2 2 0 0 1 2 0 Z' fill='#ff0000'/>
+    <path d='M 22 0 H 28 A 2 2 0 0 1 30 2 V 8 A 2 2 0 0 1 28 10 H 22 A 2 2 0 0 1 20 8 V 2 A 2 2 0 0 1 22 0 Z' fill='#ff0000'/>
+</svg>
+");
+
+    // Overlapped shapes, shapes with different attributes, with markers
+    // or without 'merge_paths' can't be merged and their paths are longer.
+    test_shorter!(keep_not_mergeable_1, true,
+"<svg>
+    <marker id='m1'/>
+    <path d='M 0 0 L 60 60' fill='#ff0000'/>
+    <circle cx='50' cy='50' fill='#ff0000' r='10'/>
+    <circle cx='100' cy='100' fill='#00ff00' r='10'/>
+    <circle cx='150' cy='150' fill='#00ff00' marker-start='url(#m1)' r='10'/>
+</svg>",
+"<svg>
+    <marker id='m1'/>
+    <path d='M 0 0 L 60 60' fill='#ff0000'/>
+    <circle cx='50' cy='50' fill='#ff0000' r='10'/>
+    <circle cx='100' cy='100' fill='#00ff00' r='10'/>
+    <circle cx='150' cy='150' fill='#00ff00' marker-start='url(#m1)' r='10'/>
+</svg>
+");
+
+    test_shorter!(keep_not_mergeable_2, false,
+"<svg>
+    <path d='M 0 0 L 10 10' fill='#ff0000'/>
+    <circle cx='50' cy='50' fill='#ff0000' r='10'/>
+</svg>",
+"<svg>
+    <path d='M 0 0 L 10 10' fill='#ff0000'/>
+    <circle cx='50' cy='50' fill='#ff0000' r='10'/>
+</svg>
+");
+
+    // Shapes, which are shorter as paths after rounding.
+    test_shorter!(conv_shorter_1, false,
+"<svg>
+    <polyline points='0.000000001 0 10 10'/>
+    <line x1='0' x2='10.000000001' y1='0' y2='10'/>
+</svg>",
+"<svg>
+    <path d='M 0.000000001 0 L 10 10'/>
+    <path d='M 0 0 L 10.000000001 10'/>
+</svg>
 ");
 }
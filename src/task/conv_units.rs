@@ -0,0 +1,123 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Length,
+};
+
+use task::short::{AId, Unit};
+
+// Converts absolute units into user units, so other tasks, like transforms applying,
+// can process them.
+//
+// Relative units, like 'em', 'ex' and '%', are not converted, because they depend
+// on a font and a viewport.
+pub fn convert_units(doc: &Document, dpi: u32) {
+    let dpi = dpi as f64;
+
+    // 'doc' must contain 'svg' node, so we can safely unwrap.
+    let svg = doc.svg_element().unwrap();
+
+    for (_, mut node) in doc.descendants().svg() {
+        let is_root = node == svg;
+
+        let mut attrs = node.attributes_mut();
+        for (aid, attr) in attrs.iter_svg_mut() {
+            // The root element size defines the image size and can't be in user units.
+            if is_root && (aid == AId::Width || aid == AId::Height) {
+                continue;
+            }
+
+            match attr.value {
+                AttributeValue::Length(ref mut len) => {
+                    convert_length(len, dpi);
+                }
+                AttributeValue::LengthList(ref mut list) => {
+                    for len in list.iter_mut() {
+                        convert_length(len, dpi);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn convert_length(len: &mut Length, dpi: f64) {
+    let factor = match len.unit {
+        Unit::Px => 1.0,
+        Unit::In => dpi,
+        Unit::Cm => dpi / 2.54,
+        Unit::Mm => dpi / 25.4,
+        Unit::Pt => dpi / 72.0,
+        Unit::Pc => dpi / 6.0,
+        _ => return,
+    };
+
+    len.num *= factor;
+    len.unit = Unit::None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $dpi:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                convert_units(&doc, $dpi);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(convert_1, 96,
+"<svg width='10mm' height='1in'>
+    <rect x='1in' y='72pt' width='2.54cm' height='1pc' stroke-width='2px'/>
+    <svg width='1in' height='1in'/>
+</svg>",
+"<svg height='1in' width='10mm'>
+    <rect height='16' stroke-width='2' width='96' x='96' y='96'/>
+    <svg height='96' width='96'/>
+</svg>
+");
+
+    test!(convert_2, 90,
+"<svg>
+    <rect x='1in' stroke-dasharray='1in 2'/>
+</svg>",
+"<svg>
+    <rect stroke-dasharray='90 2' x='90'/>
+</svg>
+");
+
+    // Relative units should be kept.
+    test!(keep_1, 96,
+"<svg>
+    <rect x='10%' y='1em' width='2ex'/>
+</svg>",
+"<svg>
+    <rect width='2ex' x='10%' y='1em'/>
+</svg>
+");
+}
@@ -0,0 +1,343 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::cmp;
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    FuzzyEq,
+    Transform,
+    WriteBuffer,
+    WriteOptions,
+};
+
+use options::CleaningOptions;
+use task::short::AId;
+use task::exponent_notation::shorten_numbers;
+use task::minify_separators::remove_separators;
+use task::round_numbers::round_number;
+
+#[derive(Clone,Copy)]
+enum Func {
+    Translate(f64, f64),
+    Scale(f64, f64),
+    // Angle and center.
+    Rotate(f64, f64, f64),
+    SkewX(f64),
+    SkewY(f64),
+}
+
+// Rewrites transforms into the shortest equivalent list of transform functions.
+// Like 'matrix(0 1 -1 0 20 0)' -> 'rotate(90 10 10)'.
+//
+// The writer is able to simplify only a pure translate, scale or rotate,
+// so a combination of them is still written as a matrix.
+pub fn decompose_transforms(doc: &Document, options: &CleaningOptions, opt: &WriteOptions) {
+    // NOTE: Must be run at last, since values are stored as strings after it.
+
+    if !opt.simplify_transform_matrices {
+        return;
+    }
+
+    for (_, mut node) in doc.descendants().svg() {
+        let mut attrs = node.attributes_mut();
+        for (aid, attr) in attrs.iter_svg_mut() {
+            match aid {
+                  AId::Transform
+                | AId::GradientTransform
+                | AId::PatternTransform => {}
+                _ => continue,
+            }
+
+            let ts = match attr.value {
+                AttributeValue::Transform(ts) => ts,
+                _ => continue,
+            };
+
+            let mut text = Vec::new();
+            attr.value.write_buf_opt(opt, &mut text);
+            let mut best = finalize(&text, options);
+            let orig_len = best.len();
+
+            // Computed angles and scales are rarely exact, so we are trying to round them
+            // as much as possible while the result is still equal to the original transform.
+            for precision in 0..(options.transforms_precision + 1) {
+                for funcs in candidates(&ts, precision as usize, options) {
+                    if !is_equal(&ts, &to_transform(&funcs), options) {
+                        continue;
+                    }
+
+                    let text = finalize(&write_funcs(&funcs, options, opt), options);
+                    if text.len() < best.len() {
+                        best = text;
+                    }
+                }
+            }
+
+            if best.len() < orig_len {
+                // The writer always produces a valid UTF-8.
+                attr.value = AttributeValue::String(String::from_utf8(best).unwrap());
+            }
+        }
+    }
+}
+
+fn candidates(ts: &Transform, precision: usize, options: &CleaningOptions) -> Vec<Vec<Func>> {
+    let coord_precision = cmp::min(precision, options.coordinates_precision as usize);
+    let round = |mut n: f64, precision: usize| { round_number(&mut n, precision); n };
+
+    let (a, b, c, d, e, f) = (ts.a, ts.b, ts.c, ts.d, ts.e, ts.f);
+
+    let mut prefix = Vec::new();
+    if !(e.fuzzy_eq(&0.0) && f.fuzzy_eq(&0.0)) {
+        prefix.push(Func::Translate(e, f));
+    }
+
+    let mut list = Vec::new();
+
+    // Rotation and scale. A reflection is represented by a negative scale.
+    // Non-orthogonal matrices cannot be represented this way,
+    // but the result is verified anyway, so a rough check is enough.
+    if (a * c + b * d).abs() < 0.001 {
+        let sx = a.hypot(b);
+        if sx.fuzzy_ne(&0.0) {
+            let sy = round((a * d - b * c) / sx, precision);
+            let sx = round(sx, precision);
+            let angle = round(b.atan2(a).to_degrees(), precision);
+
+            let mut scale = Vec::new();
+            if !(sx.fuzzy_eq(&1.0) && sy.fuzzy_eq(&1.0)) {
+                scale.push(Func::Scale(sx, sy));
+            }
+
+            if angle.fuzzy_eq(&0.0) {
+                list.push(concat(&[&prefix, &scale]));
+            } else {
+                let rotate = [Func::Rotate(angle, 0.0, 0.0)];
+                list.push(concat(&[&prefix, &rotate, &scale]));
+
+                // 'rotate(a cx cy)' is 'translate(cx cy) rotate(a) translate(-cx -cy)',
+                // so the center is a solution of '(I - R) * c = t'.
+                if !prefix.is_empty() {
+                    let (sin, cos) = angle.to_radians().sin_cos();
+                    let det = (1.0 - cos) * (1.0 - cos) + sin * sin;
+                    let cx = round(((1.0 - cos) * e - sin * f) / det, coord_precision);
+                    let cy = round((sin * e + (1.0 - cos) * f) / det, coord_precision);
+                    list.push(concat(&[&[Func::Rotate(angle, cx, cy)], &scale]));
+                }
+            }
+        }
+    }
+
+    if a.fuzzy_eq(&1.0) && d.fuzzy_eq(&1.0) {
+        if b.fuzzy_eq(&0.0) {
+            let angle = round(c.atan().to_degrees(), precision);
+            list.push(concat(&[&prefix, &[Func::SkewX(angle)]]));
+        }
+
+        if c.fuzzy_eq(&0.0) {
+            let angle = round(b.atan().to_degrees(), precision);
+            list.push(concat(&[&prefix, &[Func::SkewY(angle)]]));
+        }
+    }
+
+    list.retain(|funcs| !funcs.is_empty());
+    list
+}
+
+fn concat(parts: &[&[Func]]) -> Vec<Func> {
+    parts.iter().flat_map(|p| p.iter().cloned()).collect()
+}
+
+fn to_transform(funcs: &[Func]) -> Transform {
+    let mut ts = Transform::default();
+    for func in funcs {
+        match *func {
+            Func::Translate(x, y) => ts.translate(x, y),
+            Func::Scale(x, y) => ts.scale(x, y),
+            Func::Rotate(a, cx, cy) => {
+                ts.translate(cx, cy);
+                ts.rotate(a);
+                ts.translate(-cx, -cy);
+            }
+            Func::SkewX(a) => ts.skew_x(a),
+            Func::SkewY(a) => ts.skew_y(a),
+        }
+    }
+
+    ts
+}
+
+// Checks that the decomposed transform is equal to the original one
+// with the precision that is used by the 'round_numbers' task.
+fn is_equal(ts1: &Transform, ts2: &Transform, options: &CleaningOptions) -> bool {
+    let ts_eps = 10f64.powi(-(options.transforms_precision as i32));
+    let coord_eps = 10f64.powi(-(options.coordinates_precision as i32));
+
+       (ts1.a - ts2.a).abs() <= ts_eps
+    && (ts1.b - ts2.b).abs() <= ts_eps
+    && (ts1.c - ts2.c).abs() <= ts_eps
+    && (ts1.d - ts2.d).abs() <= ts_eps
+    && (ts1.e - ts2.e).abs() <= coord_eps
+    && (ts1.f - ts2.f).abs() <= coord_eps
+}
+
+fn write_funcs(funcs: &[Func], options: &CleaningOptions, opt: &WriteOptions) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for (i, func) in funcs.iter().enumerate() {
+        if i != 0 && !options.minimal_list_separators {
+            buf.push(b' ');
+        }
+
+        let (name, nums) = match *func {
+            Func::Translate(x, y) if y.fuzzy_eq(&0.0) => ("translate", vec![x]),
+            Func::Translate(x, y) => ("translate", vec![x, y]),
+            Func::Scale(x, y) if x.fuzzy_eq(&y) => ("scale", vec![x]),
+            Func::Scale(x, y) => ("scale", vec![x, y]),
+            Func::Rotate(a, cx, cy) if cx.fuzzy_eq(&0.0) && cy.fuzzy_eq(&0.0) => ("rotate", vec![a]),
+            Func::Rotate(a, cx, cy) => ("rotate", vec![a, cx, cy]),
+            Func::SkewX(a) => ("skewX", vec![a]),
+            Func::SkewY(a) => ("skewY", vec![a]),
+        };
+
+        buf.extend_from_slice(name.as_bytes());
+        buf.push(b'(');
+        for (j, n) in nums.iter().enumerate() {
+            if j != 0 {
+                buf.push(b' ');
+            }
+            n.write_buf_opt(opt, &mut buf);
+        }
+        buf.push(b')');
+    }
+
+    buf
+}
+
+// Applies 'use-exponent-notation' and 'list-separator' options,
+// because they skip values stored as strings.
+fn finalize(text: &[u8], options: &CleaningOptions) -> Vec<u8> {
+    let mut text = text.to_vec();
+
+    if options.use_exponent_notation {
+        let mut buf = Vec::new();
+        shorten_numbers(&text, &mut buf);
+        text = buf;
+    }
+
+    if options.minimal_list_separators {
+        let mut buf = Vec::new();
+        remove_separators(&text, &mut buf);
+        text = buf;
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                decompose_transforms(doc, &CleaningOptions::default(), &write_opt_for_tests!())
+            }, $in_text, $out_text);
+        )
+    }
+
+    test!(rotate_1,
+"<svg>
+    <rect transform='matrix(0 1 -1 0 20 0)'/>
+</svg>",
+"<svg>
+    <rect transform='rotate(90 10 10)'/>
+</svg>
+");
+
+    test!(rotate_2,
+"<svg>
+    <rect transform='matrix(-2 0 0 -2 0 0)'/>
+</svg>",
+"<svg>
+    <rect transform='scale(-2)'/>
+</svg>
+");
+
+    test!(rotate_scale_1,
+"<svg>
+    <rect transform='rotate(45 50 50) scale(2)'/>
+</svg>",
+"<svg>
+    <rect transform='rotate(45 50 50) scale(2)'/>
+</svg>
+");
+
+    test!(skew_1,
+"<svg>
+    <rect transform='translate(10) skewX(30)'/>
+</svg>",
+"<svg>
+    <rect transform='translate(10) skewX(30)'/>
+</svg>
+");
+
+    test!(rotate_scale_2,
+"<svg>
+    <rect transform='rotate(30) scale(2)'/>
+</svg>",
+"<svg>
+    <rect transform='rotate(30) scale(2)'/>
+</svg>
+");
+
+    // A decomposed transform is longer.
+    test!(skip_1,
+"<svg>
+    <rect transform='translate(10 20) scale(2 3)'/>
+</svg>",
+"<svg>
+    <rect transform='matrix(2 0 0 3 10 20)'/>
+</svg>
+");
+
+    // A matrix with a skew and a scale can't be decomposed.
+    test!(skip_2,
+"<svg>
+    <rect transform='matrix(1 2 3 4 5 6)'/>
+</svg>",
+"<svg>
+    <rect transform='matrix(1 2 3 4 5 6)'/>
+</svg>
+");
+
+    #[test]
+    fn minimal_1() {
+        let doc = Document::from_str("<svg><rect transform='matrix(1 0 0.57735027 1 10 -20)'/></svg>").unwrap();
+        let options = CleaningOptions {
+            minimal_list_separators: true,
+            .. CleaningOptions::default()
+        };
+        let opt = write_opt_for_tests!();
+        decompose_transforms(&doc, &options, &opt);
+        assert_eq_text!(doc.to_string_with_opt(&opt),
+                        "<svg>\n    <rect transform='translate(10-20)skewX(30)'/>\n</svg>\n");
+    }
+}
@@ -0,0 +1,368 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeType,
+    AttributeValue,
+    Document,
+    WriteBuffer,
+    WriteOptions,
+};
+use svgdom::path::{
+    Command,
+    Path,
+    SegmentData,
+};
+
+// Replaces numbers with the exponent notation when it's shorter.
+// Like '1000000' -> '1e6' and '.00001' -> '1e-5'.
+//
+// Presentation attributes are CSS properties and CSS 2 doesn't support the exponent notation,
+// so they are skipped.
+pub fn use_exponent_notation(doc: &Document, opt: &WriteOptions) {
+    // NOTE: Must be run at last, since numbers are stored as strings after it.
+
+    for (_, mut node) in doc.descendants().svg() {
+        let mut attrs = node.attributes_mut();
+        for (_, attr) in attrs.iter_svg_mut() {
+            if attr.is_presentation() {
+                continue;
+            }
+
+            let mut buf = Vec::new();
+            match attr.value {
+                AttributeValue::Path(ref path) => write_path(path, opt, &mut buf),
+                  AttributeValue::Number(_)
+                | AttributeValue::NumberList(_)
+                | AttributeValue::Length(_)
+                | AttributeValue::LengthList(_)
+                | AttributeValue::Transform(_) => {
+                    let mut text = Vec::new();
+                    attr.value.write_buf_opt(opt, &mut text);
+                    shorten_numbers(&text, &mut buf);
+                }
+                _ => continue,
+            }
+
+            // The writer always produces a valid UTF-8.
+            attr.value = AttributeValue::String(String::from_utf8(buf).unwrap());
+        }
+    }
+}
+
+// Rewrites all numbers in a string, which has separators between numbers.
+pub fn shorten_numbers(text: &[u8], buf: &mut Vec<u8>) {
+    let is_num_char = |c: u8| c.is_ascii_digit() || c == b'.' || c == b'-';
+
+    let mut i = 0;
+    while i < text.len() {
+        if !is_num_char(text[i]) {
+            buf.push(text[i]);
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        i += 1;
+        while i < text.len() && (text[i].is_ascii_digit() || text[i] == b'.') {
+            i += 1;
+        }
+
+        let num = &text[start..i];
+        // Numbers followed by the 'em' or 'ex' units are kept as is,
+        // because '1e3em' is hard to read by some parsers.
+        if text.get(i) == Some(&b'e') {
+            buf.extend_from_slice(num);
+        } else {
+            write_shortest(num, buf);
+        }
+    }
+}
+
+// Writes a number in the exponent notation when it's shorter than the decimal one.
+//
+// 'num' should be in the decimal notation, as produced by the svgdom.
+fn write_shortest(num: &[u8], buf: &mut Vec<u8>) {
+    let (sign, digits) = match num.first() {
+        Some(&b'-') => (&num[..1], &num[1..]),
+        _ => (&num[..0], num),
+    };
+
+    let (int, frac) = match digits.iter().position(|c| *c == b'.') {
+        Some(pos) => (&digits[..pos], &digits[pos + 1..]),
+        None => (digits, &digits[..0]),
+    };
+
+    // Represent the number as 'mantissa * 10^exp', where 'mantissa' is an integer.
+    let mut mantissa: Vec<u8> = int.iter().chain(frac.iter()).cloned()
+                                   .skip_while(|c| *c == b'0').collect();
+    let mut exp = -(frac.len() as i32);
+    while mantissa.last() == Some(&b'0') {
+        mantissa.pop();
+        exp += 1;
+    }
+
+    // Zero or not a number at all.
+    if mantissa.is_empty() || exp == 0 {
+        buf.extend_from_slice(num);
+        return;
+    }
+
+    let exp = exp.to_string();
+    if mantissa.len() + 1 + exp.len() < digits.len() {
+        buf.extend_from_slice(sign);
+        buf.extend_from_slice(&mantissa);
+        buf.push(b'e');
+        buf.extend_from_slice(exp.as_bytes());
+    } else {
+        buf.extend_from_slice(num);
+    }
+}
+
+// Writes path data in the same way as the svgdom, but using 'write_shortest' for numbers.
+fn write_path(path: &Path, opt: &WriteOptions, buf: &mut Vec<u8>) {
+    let mut prev: Option<(Command, bool, bool)> = None;
+    let mut prev_has_dot = false;
+
+    for seg in &path.d {
+        // Check that the command can be skipped.
+        let mut is_implicit = false;
+        let mut print_cmd = true;
+        if let Some((prev_cmd, prev_absolute, prev_implicit)) = prev {
+            if seg.absolute == prev_absolute {
+                if opt.remove_duplicated_path_commands
+                   && prev_cmd != Command::MoveTo && seg.cmd() == prev_cmd {
+                    print_cmd = false;
+                }
+
+                if opt.use_implicit_lineto_commands && seg.cmd() == Command::LineTo
+                   && (prev_implicit || prev_cmd == Command::MoveTo) {
+                    is_implicit = true;
+                    print_cmd = false;
+                }
+            }
+        }
+        prev = Some((seg.cmd(), seg.absolute, is_implicit));
+
+        if print_cmd {
+            buf.push(cmd_char(seg.cmd(), seg.absolute));
+            if !(seg.cmd() == Command::ClosePath || opt.use_compact_path_notation) {
+                buf.push(b' ');
+            }
+        }
+
+        let mut coords = |nums: &[f64], is_cmd_written: bool, buf: &mut Vec<u8>| {
+            for (i, n) in nums.iter().enumerate() {
+                let mut text = Vec::new();
+                n.write_buf_opt(opt, &mut text);
+                let start = buf.len();
+                write_shortest(&text, buf);
+
+                if opt.use_compact_path_notation {
+                    let c = buf[start];
+                    let is_first = i == 0 && is_cmd_written;
+                    let write_space = if c == b'.' {
+                        !prev_has_dot && !is_first
+                    } else {
+                        c.is_ascii_digit() && !is_first
+                    };
+
+                    if write_space {
+                        buf.insert(start, b' ');
+                    }
+
+                    prev_has_dot = buf[start..].contains(&b'.');
+                } else {
+                    buf.push(b' ');
+                }
+            }
+        };
+
+        match *seg.data() {
+              SegmentData::MoveTo { x, y }
+            | SegmentData::LineTo { x, y }
+            | SegmentData::SmoothQuadratic { x, y } => coords(&[x, y], print_cmd, buf),
+            SegmentData::HorizontalLineTo { x } => coords(&[x], print_cmd, buf),
+            SegmentData::VerticalLineTo { y } => coords(&[y], print_cmd, buf),
+            SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
+                coords(&[x1, y1, x2, y2, x, y], print_cmd, buf)
+            }
+            SegmentData::SmoothCurveTo { x2, y2, x, y } => coords(&[x2, y2, x, y], print_cmd, buf),
+            SegmentData::Quadratic { x1, y1, x, y } => coords(&[x1, y1, x, y], print_cmd, buf),
+            SegmentData::EllipticalArc { rx, ry, x_axis_rotation, large_arc, sweep, x, y } => {
+                coords(&[rx, ry, x_axis_rotation], print_cmd, buf);
+
+                if opt.use_compact_path_notation {
+                    // Flags must always have a space before them.
+                    buf.push(b' ');
+                }
+
+                buf.push(if large_arc { b'1' } else { b'0' });
+                if !opt.join_arc_to_flags {
+                    buf.push(b' ');
+                }
+                buf.push(if sweep { b'1' } else { b'0' });
+                if !opt.join_arc_to_flags {
+                    buf.push(b' ');
+                }
+
+                // The first coordinate after flags doesn't need a separator, like after a command.
+                coords(&[x, y], true, buf);
+            }
+            SegmentData::ClosePath => {
+                if !opt.use_compact_path_notation {
+                    buf.push(b' ');
+                }
+            }
+        }
+    }
+
+    if !opt.use_compact_path_notation {
+        buf.pop();
+    }
+}
+
+fn cmd_char(cmd: Command, absolute: bool) -> u8 {
+    let c = match cmd {
+        Command::MoveTo => b'm',
+        Command::LineTo => b'l',
+        Command::HorizontalLineTo => b'h',
+        Command::VerticalLineTo => b'v',
+        Command::CurveTo => b'c',
+        Command::SmoothCurveTo => b's',
+        Command::Quadratic => b'q',
+        Command::SmoothQuadratic => b't',
+        Command::EllipticalArc => b'a',
+        Command::ClosePath => b'z',
+    };
+
+    if absolute { c.to_ascii_uppercase() } else { c }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                use_exponent_notation(doc, &write_opt_for_tests!())
+            }, $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_num {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut buf = Vec::new();
+                write_shortest($in_text.as_bytes(), &mut buf);
+                assert_eq!(String::from_utf8(buf).unwrap(), $out_text);
+            }
+        )
+    }
+
+    test_num!(num_1, "1000000", "1e6");
+    test_num!(num_2, ".00001", "1e-5");
+    test_num!(num_3, "-.00012", "-12e-5");
+    test_num!(num_4, "1000", "1e3");
+    test_num!(num_5, "100", "100");
+    test_num!(num_6, ".001", ".001");
+    test_num!(num_7, "0.001", "1e-3");
+    test_num!(num_8, ".01", ".01");
+    test_num!(num_9, "1.5", "1.5");
+    test_num!(num_10, "0", "0");
+    test_num!(num_11, "-1200000", "-12e5");
+    test_num!(num_12, "0.00001", "1e-5");
+
+    test!(attrs_1,
+"<svg viewBox='0 0 1000000 .00001'>
+    <rect x='1000' y='.001' width='1.5' height='10000%'/>
+</svg>",
+"<svg viewBox='0 0 1e6 1e-5'>
+    <rect height='1e4%' width='1.5' x='1e3' y='1e-3'/>
+</svg>
+");
+
+    // Presentation attributes and numbers with the 'em' unit are kept as is.
+    test!(skip_1,
+"<svg>
+    <rect stroke-width='.00001' x='1000em' transform='translate(1000 -.00001)'/>
+</svg>",
+"<svg>
+    <rect stroke-width='0.00001' transform='translate(1e3 -1e-5)' x='1000em'/>
+</svg>
+");
+
+    test!(path_1,
+"<svg>
+    <path d='M 1000000 .00001 L .5 .00002 L 1000 -.001 Z'/>
+</svg>",
+"<svg>
+    <path d='M 1e6 1e-5 L 0.5 2e-5 L 1e3 -1e-3 Z'/>
+</svg>
+");
+
+    // Without the exponent notation, the output must be the same as the svgdom one.
+    #[test]
+    fn path_2() {
+        use svgdom::path::Path;
+        use std::str::FromStr;
+
+        let paths = [
+            "M 10 20 L 30 40 L 50 60 Z M 1 2 L 3 4",
+            "m .5 .5 l -.5 .5 .5 .5 h 10 v -10 z",
+            "M 1.5 .5 C .5 .5 1 -2 .5 .5 S 1 1 2 2 Q 3 3 4 4 T 5 5",
+            "M 10 10 A 5 5 0 0 1 .5 .5 a 5 .5 30 1 0 -1 -1",
+        ];
+
+        for compact in &[false, true] {
+            for join_flags in &[false, true] {
+                let mut opt = write_opt_for_tests!();
+                opt.use_compact_path_notation = *compact;
+                opt.remove_duplicated_path_commands = *compact;
+                opt.use_implicit_lineto_commands = *compact;
+                opt.join_arc_to_flags = *join_flags;
+
+                for text in &paths {
+                    let path = Path::from_str(text).unwrap();
+                    let mut buf = Vec::new();
+                    write_path(&path, &opt, &mut buf);
+                    assert_eq!(String::from_utf8(buf).unwrap(), path.to_string_with_opt(&opt));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn path_compact_1() {
+        use svgdom::path::Path;
+        use std::str::FromStr;
+
+        let mut opt = write_opt_for_tests!();
+        opt.remove_leading_zero = true;
+        opt.use_compact_path_notation = true;
+        opt.use_implicit_lineto_commands = true;
+
+        let path = Path::from_str("M 1000 .5 L .00001 .5 L .5 1000 L 1.5 .00001 L .00001 .5").unwrap();
+        let mut buf = Vec::new();
+        write_path(&path, &opt, &mut buf);
+        assert_eq!(String::from_utf8(buf).unwrap(), "M1e3 .5 1e-5 .5.5 1e3 1.5 1e-5 1e-5 .5");
+    }
+}
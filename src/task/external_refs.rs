@@ -0,0 +1,245 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+#[cfg(feature = "fs")]
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::{EId, AId};
+#[cfg(feature = "fs")]
+use task::utils::base64;
+use options::{
+    CleaningOptions,
+    ExternalRefsMode,
+};
+use warning::{
+    self,
+    Warning,
+    WarningCode,
+};
+
+// Base64 increases the size by a third and big images are better cached separately,
+// so only small images are embedded.
+#[cfg(feature = "fs")]
+const EMBED_SIZE_LIMIT: u64 = 32 * 1024;
+
+// Processes references to external files, like images, fonts and links from 'use' elements.
+//
+// Local references are resolved relative to 'resources_dir', if set,
+// and broken ones are reported. Remote references can't be validated.
+pub fn process_external_refs(doc: &mut Document, options: &CleaningOptions) {
+    let dir = options.resources_dir.as_ref().map(Path::new);
+
+    for mut node in doc.descendants().filter(is_external_ref) {
+        let href = node.attributes().get_value(AId::XlinkHref).unwrap().to_string();
+
+        let path = dir.and_then(|dir| local_path(dir, &href));
+        let path = match path {
+            Some(ref path) if !is_file(path) => {
+                warning::add(Warning::new(WarningCode::BrokenReference,
+                    format!("the external reference '{}' is broken", href)));
+                None
+            }
+            path => path,
+        };
+
+        match options.external_refs {
+            ExternalRefsMode::Keep => {}
+            ExternalRefsMode::Remove => node.remove_attribute(AId::XlinkHref),
+            ExternalRefsMode::Embed => {
+                let is_image = node.is_tag_name(EId::Image) || node.is_tag_name(EId::FeImage);
+                if let (true, Some(path)) = (is_image, path) {
+                    if let Some(uri) = load_data_uri(&path) {
+                        node.set_attribute((AId::XlinkHref, uri));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Local links are parsed as 'Link' values, so any string is an external one.
+//
+// Data URIs and 'a' elements links are not external resources.
+fn is_external_ref(node: &Node) -> bool {
+    if node.is_tag_name(EId::A) {
+        return false;
+    }
+
+    let attrs = node.attributes();
+    match attrs.get_value(AId::XlinkHref) {
+        Some(AttributeValue::String(s)) => !s.trim_start().to_lowercase().starts_with("data:"),
+        _ => false,
+    }
+}
+
+// Converts a reference to a local file path.
+//
+// Returns 'None' for remote references.
+fn local_path(dir: &Path, href: &str) -> Option<PathBuf> {
+    let href = href.split(['#', '?']).next().unwrap();
+    if href.is_empty() {
+        return None;
+    }
+
+    if let Some(path) = href.strip_prefix("file://") {
+        return Some(PathBuf::from(path));
+    }
+
+    // Like 'http://' or '//example.com'.
+    let has_scheme = match href.find(':') {
+        Some(idx) => !href[..idx].contains('/'),
+        None => false,
+    };
+
+    if has_scheme || href.starts_with("//") {
+        return None;
+    }
+
+    Some(dir.join(href))
+}
+
+#[cfg(feature = "fs")]
+fn is_file(path: &Path) -> bool {
+    path.is_file()
+}
+
+// Local files can't be accessed without the file system, so all of them are broken.
+#[cfg(not(feature = "fs"))]
+fn is_file(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(feature = "fs")]
+fn load_data_uri(path: &Path) -> Option<String> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let mime = match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    };
+
+    if fs::metadata(path).ok()?.len() > EMBED_SIZE_LIMIT {
+        return None;
+    }
+
+    let data = fs::read(path).ok()?;
+    Some(format!("data:{};base64,{}", mime, base64::encode(&data)))
+}
+
+#[cfg(not(feature = "fs"))]
+fn load_data_uri(_path: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $mode:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut doc = Document::from_str($in_text).unwrap();
+                let options = CleaningOptions {
+                    external_refs: $mode,
+                    resources_dir: Some(env::temp_dir().to_str().unwrap().to_string()),
+                    .. CleaningOptions::default()
+                };
+                process_external_refs(&mut doc, &options);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(keep_1, ExternalRefsMode::Keep,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='svgcleaner-missing.png'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='svgcleaner-missing.png'/>
+</svg>
+");
+
+    test!(rm_1, ExternalRefsMode::Remove,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <a xlink:href='http://example.com'/>
+    <image xlink:href='http://example.com/image.png'/>
+    <image xlink:href='data:image/png;base64,AAAA'/>
+    <use xlink:href='image.svg#rect1'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <a xlink:href='http://example.com'/>
+    <image/>
+    <image xlink:href='data:image/png;base64,AAAA'/>
+    <use/>
+</svg>
+");
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn embed_1() {
+        let dir = env::temp_dir();
+        fs::write(dir.join("svgcleaner-embed-1.png"), b"foo").unwrap();
+
+        let mut doc = Document::from_str(
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='svgcleaner-embed-1.png'/>
+    <image xlink:href='http://example.com/image.png'/>
+    <use xlink:href='svgcleaner-embed-1.png'/>
+</svg>").unwrap();
+
+        let options = CleaningOptions {
+            external_refs: ExternalRefsMode::Embed,
+            resources_dir: Some(dir.to_str().unwrap().to_string()),
+            .. CleaningOptions::default()
+        };
+        process_external_refs(&mut doc, &options);
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/png;base64,Zm9v'/>
+    <image xlink:href='http://example.com/image.png'/>
+    <use xlink:href='svgcleaner-embed-1.png'/>
+</svg>
+");
+    }
+
+    #[test]
+    fn local_path_1() {
+        let dir = Path::new("/tmp");
+        assert_eq!(local_path(dir, "image.png#a"), Some(PathBuf::from("/tmp/image.png")));
+        assert_eq!(local_path(dir, "file:///a/image.png"), Some(PathBuf::from("/a/image.png")));
+        assert_eq!(local_path(dir, "https://example.com/image.png"), None);
+        assert_eq!(local_path(dir, "//example.com/image.png"), None);
+    }
+}
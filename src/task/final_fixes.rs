@@ -36,10 +36,30 @@ pub fn remove_empty_defs(doc: &Document) {
     }
 }
 
-pub fn fix_xmlns_attribute(doc: &Document, rm_unused: bool) {
+// In the SVG 2 mode links are written as plain 'href', so the namespace is required
+// only by other 'xlink:*' attributes.
+pub fn fix_xmlns_attribute(doc: &Document, rm_unused: bool, svg2: bool) {
     // doc must contain 'svg' node, so we can safely unwrap.
     let mut svg = doc.svg_element().unwrap();
 
+    if svg2 {
+        let has_xlink_attrs = doc.descendants().svg().any(|(_, node)| {
+            node.attributes().iter().any(|attr| matches!(attr.id(),
+                Some(AId::XlinkActuate) | Some(AId::XlinkArcrole) | Some(AId::XlinkRole) |
+                Some(AId::XlinkShow) | Some(AId::XlinkTitle) | Some(AId::XlinkType)))
+        });
+
+        if has_xlink_attrs {
+            if !svg.has_attribute(AId::XmlnsXlink) {
+                svg.set_attribute((AId::XmlnsXlink, "http://www.w3.org/1999/xlink"));
+            }
+        } else {
+            svg.remove_attribute(AId::XmlnsXlink);
+        }
+
+        return;
+    }
+
     let mut has_links = false;
     for (_, node) in doc.descendants().svg() {
         if node.is_used() {
@@ -109,10 +129,13 @@ mod tests {
 
     macro_rules! test_xmlns {
         ($name:ident, $in_text:expr, $out_text:expr, $rm_unused:expr) => (
+            test_xmlns!($name, $in_text, $out_text, $rm_unused, false);
+        );
+        ($name:ident, $in_text:expr, $out_text:expr, $rm_unused:expr, $svg2:expr) => (
             #[test]
             fn $name() {
                 let doc = Document::from_str($in_text).unwrap();
-                fix_xmlns_attribute(&doc, $rm_unused);
+                fix_xmlns_attribute(&doc, $rm_unused, $svg2);
                 assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
             }
         )
@@ -158,4 +181,24 @@ mod tests {
     <use xlink:href='#rect1'/>
 </svg>
 ", true);
+
+    test_xmlns!(xmlns_svg2_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='rect1'/>
+    <use xlink:href='#rect1'/>
+</svg>",
+"<svg>
+    <rect id='rect1'/>
+    <use xlink:href='#rect1'/>
+</svg>
+", false, true);
+
+    test_xmlns!(xmlns_svg2_2,
+"<svg>
+    <a xlink:href='https://example.com' xlink:title='Title'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <a xlink:href='https://example.com' xlink:title='Title'/>
+</svg>
+", false, true);
 }
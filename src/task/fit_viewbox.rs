@@ -0,0 +1,223 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Length,
+};
+
+use task::short::{AId, Unit};
+use task::utils::AnimatedAttributes;
+use task::utils::bbox::{
+    calc_approx_bbox,
+    Rect,
+};
+use super::rm_outside_elems::get_visible_region;
+
+// Rewrites the root 'viewBox', 'width' and 'height' to fit the content bounding box.
+//
+// The size is changed proportionally, so the content scale is preserved.
+//
+// Text bounding box is only an estimation, so it can be cropped.
+// Documents with elements with an unknown bounding box, like images,
+// or with filters, markers and animations are not processed.
+pub fn fit_viewbox(doc: &Document, padding: f64, precision: u8) {
+    let mut svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => return,
+    };
+
+    // An animation can move the content outside the new 'viewBox'.
+    if !AnimatedAttributes::new(doc).is_empty() {
+        return;
+    }
+
+    let mut bbox = match calc_approx_bbox(&svg) {
+        Some(bbox) => bbox,
+        None => return,
+    };
+
+    // Invisible content should stay invisible.
+    if let Some(region) = get_visible_region(&svg) {
+        bbox = match bbox.intersect(&region) {
+            Some(bbox) => bbox,
+            None => return,
+        };
+    }
+
+    let bbox = round_rect_out(&bbox.expand(padding), precision);
+    if !(bbox.width() > 0.0 && bbox.height() > 0.0) {
+        return;
+    }
+
+    let new_size = {
+        let attrs = svg.attributes();
+
+        let view_box = match attrs.get_value(AId::ViewBox) {
+            Some(AttributeValue::NumberList(list)) if list.len() == 4 => Some(list.clone()),
+            _ => None,
+        };
+
+        let size = match (attrs.get_value(AId::Width), attrs.get_value(AId::Height)) {
+            (Some(&AttributeValue::Length(w)), Some(&AttributeValue::Length(h)))
+                if w.unit == h.unit && w.unit != Unit::Percent => Some((w, h)),
+            _ => None,
+        };
+
+        match size {
+            Some((w, h)) => {
+                let (sx, sy) = match view_box {
+                    Some(vb) => {
+                        let sx = w.num / vb[2];
+                        let sy = h.num / vb[3];
+                        match attrs.get_value(AId::PreserveAspectRatio) {
+                            Some(AttributeValue::String(s)) if s.starts_with("none") => (sx, sy),
+                            Some(AttributeValue::String(s)) if s.contains("slice") => {
+                                (sx.max(sy), sx.max(sy))
+                            }
+                            _ => (sx.min(sy), sx.min(sy)),
+                        }
+                    }
+                    None => {
+                        // Without a 'viewBox' the size must be in user units.
+                        if w.unit != Unit::None {
+                            return;
+                        }
+
+                        (1.0, 1.0)
+                    }
+                };
+
+                if !(sx.is_finite() && sy.is_finite()) {
+                    return;
+                }
+
+                let round = |n: f64| {
+                    let m = 10f64.powi(precision as i32);
+                    (n * m).round() / m
+                };
+
+                Some((Length::new(round(bbox.width() * sx), w.unit),
+                      Length::new(round(bbox.height() * sy), h.unit)))
+            }
+            None => None,
+        }
+    };
+
+    if let Some((w, h)) = new_size {
+        svg.set_attribute((AId::Width, w));
+        svg.set_attribute((AId::Height, h));
+    }
+
+    svg.set_attribute((AId::ViewBox, vec![bbox.x1, bbox.y1, bbox.width(), bbox.height()]));
+}
+
+// Rounds the rect outwards, so it will not become smaller.
+fn round_rect_out(rect: &Rect, precision: u8) -> Rect {
+    let m = 10f64.powi(precision as i32);
+    let x1 = (rect.x1 * m).floor() / m;
+    let y1 = (rect.y1 * m).floor() / m;
+
+    // Round a size instead of coordinates to prevent float errors.
+    let w = ((rect.x2 - x1) * m).ceil() / m;
+    let h = ((rect.y2 - y1) * m).ceil() / m;
+
+    Rect { x1, y1, x2: x1 + w, y2: y1 + h }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| fit_viewbox(doc, 0.0, 6), $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    test!(fit_1,
+"<svg height='100' viewBox='0 0 100 100' width='100'>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>",
+"<svg height='20' viewBox='10 20 40 20' width='40'>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>
+");
+
+    // Scale should be preserved.
+    test!(fit_2,
+"<svg height='200' viewBox='0 0 100 100' width='200'>
+    <g transform='translate(10 10)'>
+        <circle cx='10' cy='10' r='10' stroke='#000000' stroke-width='0.5'/>
+    </g>
+</svg>",
+"<svg height='41' viewBox='9.75 9.75 20.5 20.5' width='41'>
+    <g transform='translate(10 10)'>
+        <circle cx='10' cy='10' r='10' stroke='#000000' stroke-width='0.5'/>
+    </g>
+</svg>
+");
+
+    // Cropped content should stay cropped.
+    test!(fit_3,
+"<svg height='100' viewBox='0 0 100 100' width='100'>
+    <rect height='20' width='200' x='10' y='20'/>
+</svg>",
+"<svg height='20' viewBox='10 20 90 20' width='90'>
+    <rect height='20' width='200' x='10' y='20'/>
+</svg>
+");
+
+    test!(fit_4,
+"<svg>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>",
+"<svg viewBox='10 20 40 20'>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>
+");
+
+    base_test!(padding_1, |doc: &Document| fit_viewbox(doc, 5.0, 6),
+"<svg viewBox='0 0 100 100'>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>",
+"<svg viewBox='5 15 50 30'>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg viewBox='0 0 100 100'>
+    <image height='20' width='40'/>
+</svg>
+");
+
+    test_eq!(keep_2,
+"<svg height='10mm' width='10mm'>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>
+");
+}
@@ -22,6 +22,7 @@ use svgdom::{
     ElementType,
     Length,
     Node,
+    ValueId,
 };
 
 use task::short::{AId, EId, Unit};
@@ -37,6 +38,7 @@ pub fn fix_invalid_attributes(doc: &Document) {
     }
 
     fix_marker(doc);
+    fix_marker_links(doc);
 }
 
 /// Fix `rect` element attributes.
@@ -317,3 +319,72 @@ fn fix_marker(doc: &Document) {
         }
     }
 }
+
+// Marker properties that reference non-'marker' elements are not rendered.
+//
+// Such properties will be removed or, when a marker can be inherited from a parent element,
+// replaced with 'none'.
+//
+// Details: https://www.w3.org/TR/SVG/painting.html#MarkerProperties
+fn fix_marker_links(doc: &Document) {
+    for mut node in doc.descendants() {
+        for aid in &[AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd] {
+            let is_invalid = match node.attributes().get_value(*aid) {
+                Some(AttributeValue::FuncLink(link)) => !link.is_tag_name(EId::Marker),
+                _ => false,
+            };
+
+            if !is_invalid {
+                continue;
+            }
+
+            if node.parents().any(|p| p.has_attribute(*aid)) {
+                node.set_attribute((*aid, ValueId::None));
+            } else {
+                node.remove_attribute(*aid);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_marker {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| fix_marker_links(doc), $in_text, $out_text);
+        )
+    }
+
+    test!(fix_marker_1,
+"<svg>
+    <linearGradient id='lg1'/>
+    <marker id='m1'/>
+    <path marker-end='url(#lg1)' marker-start='url(#m1)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1'/>
+    <marker id='m1'/>
+    <path marker-start='url(#m1)'/>
+</svg>
+");
+
+    test!(fix_marker_2,
+"<svg>
+    <linearGradient id='lg1'/>
+    <marker id='m1'/>
+    <g marker-end='url(#m1)'>
+        <path marker-end='url(#lg1)'/>
+    </g>
+</svg>",
+"<svg>
+    <linearGradient id='lg1'/>
+    <marker id='m1'/>
+    <g marker-end='url(#m1)'>
+        <path marker-end='none'/>
+    </g>
+</svg>
+");
+}
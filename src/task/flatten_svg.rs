@@ -0,0 +1,469 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Length,
+    Node,
+    Transform,
+    ValueId,
+};
+
+use task::short::{EId, AId, Unit};
+use task::utils::{self, AnimatedAttributes};
+use task::utils::bbox::{self, Rect};
+
+// Attributes that define the viewport and are replaced by a transform and a clip.
+static VIEWPORT_ATTRIBUTES: &[AId] = &[
+    AId::X,
+    AId::Y,
+    AId::Width,
+    AId::Height,
+    AId::ViewBox,
+    AId::PreserveAspectRatio,
+    AId::Overflow,
+    AId::Version,
+    AId::BaseProfile,
+    AId::ZoomAndPan,
+    AId::ContentScriptType,
+    AId::ContentStyleType,
+    AId::Xmlns,
+    AId::XmlnsXlink,
+];
+
+// A nested 'svg' viewport.
+struct Viewport {
+    // A 'viewBox' and a position transform.
+    ts: Transform,
+    // A clip rectangle in the user space of the content.
+    // 'None' when the content is not clipped.
+    clip: Option<Rect>,
+}
+
+// Replaces nested 'svg' elements with groups.
+//
+// The viewport position and the 'viewBox' are converted into a transform.
+// A clip path is created only when the content can be clipped by the viewport.
+pub fn flatten_nested_svg(doc: &mut Document, keep_ids: &[String]) {
+    let animated = AnimatedAttributes::new(doc);
+
+    // 'doc' must contain 'svg' node, so we can safely unwrap.
+    let root = doc.svg_element().unwrap();
+
+    // Process nested elements first.
+    let nodes: Vec<Node> = root.descendants()
+                               .filter(|n| n.is_tag_name(EId::Svg) && *n != root)
+                               .collect();
+    for node in nodes.into_iter().rev() {
+        if !can_flatten(&node, keep_ids, &animated) {
+            continue;
+        }
+
+        if let Some(viewport) = get_viewport(&node) {
+            flatten_svg(doc, node, viewport);
+        }
+    }
+}
+
+fn can_flatten(node: &Node, keep_ids: &[String], animated: &AnimatedAttributes) -> bool {
+    // A referenced 'svg' element is rendered using the 'use' size.
+    if    node.is_used()
+       || utils::is_kept_id(node, keep_ids)
+       || utils::has_event_attrs(node)
+       || animated.is_animated(node) {
+        return false;
+    }
+
+    // The 'transform' attribute on 'svg' is an SVG 2 feature.
+    // Masking attributes will conflict with the viewport clip.
+    if node.has_attributes(&[AId::Transform, AId::ClipPath, AId::Mask, AId::Filter]) {
+        return false;
+    }
+
+    let is_valid_parent = node.parent().map(|p| {
+        matches!(p.tag_id(), Some(EId::Svg) | Some(EId::G) | Some(EId::A) | Some(EId::Switch))
+    });
+    if is_valid_parent != Some(true) {
+        return false;
+    }
+
+    // Percentage lengths are relative to the nearest viewport,
+    // which will be changed after flattening.
+    node.descendants().all(|n| !has_percent_units(&n) && !has_user_space_percent_link(&n))
+}
+
+fn has_percent_units(node: &Node) -> bool {
+    node.attributes().iter().any(|attr| {
+        match attr.value {
+            AttributeValue::Length(len) => len.unit == Unit::Percent,
+            AttributeValue::LengthList(ref list) => list.iter().any(|len| len.unit == Unit::Percent),
+            _ => false,
+        }
+    })
+}
+
+// Checks that the element references a gradient, a pattern, etc.
+// with 'userSpaceOnUse' units and percentage lengths, which are relative to the viewport.
+fn has_user_space_percent_link(node: &Node) -> bool {
+    let units_aids = [AId::GradientUnits, AId::PatternUnits, AId::ClipPathUnits,
+                      AId::MaskUnits, AId::MaskContentUnits, AId::FilterUnits,
+                      AId::PrimitiveUnits];
+
+    node.attributes().iter().any(|attr| {
+        let link = match attr.value {
+            AttributeValue::Link(ref link) | AttributeValue::FuncLink(ref link) => link,
+            _ => return false,
+        };
+
+        let is_user_space = units_aids.iter().any(|aid| {
+            match link.attributes().get_value(*aid) {
+                Some(&AttributeValue::PredefValue(v)) => v == ValueId::UserSpaceOnUse,
+                _ => false,
+            }
+        });
+
+        // Gradients and patterns inherit attributes via 'xlink:href',
+        // so we have to check the whole chain.
+        let has_percent = link.descendants().any(|n| has_percent_units(&n))
+            || has_user_space_percent_link(link);
+
+        (is_user_space || link.is_tag_name(EId::Pattern)) && has_percent
+    })
+}
+
+fn get_viewport(node: &Node) -> Option<Viewport> {
+    let attrs = node.attributes();
+
+    let get_len = |aid: AId| -> Option<Option<f64>> {
+        match attrs.get_value(aid) {
+            Some(&AttributeValue::Length(len)) if is_px(len) => Some(Some(len.num)),
+            // Only unitless coordinates are supported.
+            Some(_) => None,
+            None => Some(None),
+        }
+    };
+
+    let x = get_len(AId::X)?.unwrap_or(0.0);
+    let y = get_len(AId::Y)?.unwrap_or(0.0);
+    let width = get_len(AId::Width)?;
+    let height = get_len(AId::Height)?;
+
+    let view_box = match attrs.get_value(AId::ViewBox) {
+        Some(AttributeValue::NumberList(vb)) if vb.len() == 4 => {
+            if !(vb[2] > 0.0 && vb[3] > 0.0) {
+                return None;
+            }
+
+            Some((vb[0], vb[1], vb[2], vb[3]))
+        }
+        Some(_) => return None,
+        None => None,
+    };
+
+    let is_visible = match attrs.get_value(AId::Overflow) {
+        Some(&AttributeValue::PredefValue(v)) => v == ValueId::Visible || v == ValueId::Auto,
+        _ => false,
+    };
+
+    // The default size is '100%', which depends on the parent viewport.
+    let (w, h) = match (width, height) {
+        (Some(w), Some(h)) => (w, h),
+        _ if view_box.is_none() && is_visible => {
+            let mut ts = Transform::default();
+            ts.translate(x, y);
+            return Some(Viewport { ts, clip: None });
+        }
+        _ => return None,
+    };
+
+    // A zero-sized viewport disables rendering.
+    if !(w > 0.0 && h > 0.0) {
+        return None;
+    }
+
+    let (tx, ty, sx, sy) = match view_box {
+        Some((vx, vy, vw, vh)) => {
+            let aspect = match attrs.get_value(AId::PreserveAspectRatio) {
+                Some(AttributeValue::String(s)) => s.clone(),
+                _ => String::new(),
+            };
+            view_box_transform(x, y, w, h, (vx, vy, vw, vh), &aspect)
+        }
+        None => (x, y, 1.0, 1.0),
+    };
+
+    let mut ts = Transform::default();
+    ts.translate(tx, ty);
+    ts.scale(sx, sy);
+
+    // Convert the viewport into the content coordinates.
+    let clip = if is_visible {
+        None
+    } else {
+        let x1 = (x - tx) / sx;
+        let y1 = (y - ty) / sy;
+        Some(Rect { x1, y1, x2: x1 + w / sx, y2: y1 + h / sy })
+    };
+
+    Some(Viewport { ts, clip })
+}
+
+// Returns a translate and a scale of the 'viewBox' to the viewport transform.
+fn view_box_transform(x: f64, y: f64, w: f64, h: f64, view_box: (f64, f64, f64, f64),
+                      aspect: &str) -> (f64, f64, f64, f64) {
+    let (vx, vy, vw, vh) = view_box;
+
+    let mut iter = aspect.split_whitespace().skip_while(|s| *s == "defer");
+    let align = iter.next().unwrap_or("xMidYMid");
+    let is_slice = iter.next() == Some("slice");
+
+    let mut sx = w / vw;
+    let mut sy = h / vh;
+    if align != "none" {
+        let s = if is_slice { sx.max(sy) } else { sx.min(sy) };
+        sx = s;
+        sy = s;
+    }
+
+    let mut tx = x - vx * sx;
+    let mut ty = y - vy * sy;
+
+    let dx = w - vw * sx;
+    let dy = h - vh * sy;
+    if align.starts_with("xMid") {
+        tx += dx / 2.0;
+    } else if align.starts_with("xMax") {
+        tx += dx;
+    }
+
+    if align.ends_with("YMid") {
+        ty += dy / 2.0;
+    } else if align.ends_with("YMax") {
+        ty += dy;
+    }
+
+    (tx, ty, sx, sy)
+}
+
+fn is_px(len: Length) -> bool {
+    len.unit == Unit::None || len.unit == Unit::Px
+}
+
+fn flatten_svg(doc: &mut Document, mut node: Node, viewport: Viewport) {
+    let mut g = doc.create_element(EId::G);
+
+    if node.has_id() {
+        g.set_id(node.id().clone());
+    }
+
+    for attr in node.attributes().iter() {
+        if !VIEWPORT_ATTRIBUTES.iter().any(|aid| attr.has_id(*aid)) {
+            g.set_attribute(attr.clone());
+        }
+    }
+
+    if !viewport.ts.is_default() {
+        g.set_attribute((AId::Transform, viewport.ts));
+    }
+
+    node.insert_before(&g);
+
+    let children: Vec<Node> = node.children().collect();
+    for child in &children {
+        g.append(child);
+    }
+
+    node.remove();
+
+    let clip = match viewport.clip {
+        Some(clip) if g.has_children() => clip,
+        _ => return,
+    };
+
+    // The bounding box doesn't include the 'g' transform.
+    if let Some(rect) = bbox::calc_painted_bbox(&g) {
+        if clip.contains(&rect) {
+            return;
+        }
+    }
+
+    let mut clip_path = doc.create_element(EId::ClipPath);
+    clip_path.set_id(gen_id(doc));
+
+    let mut rect = doc.create_element(EId::Rect);
+    rect.set_attribute((AId::X, (clip.x1, Unit::None)));
+    rect.set_attribute((AId::Y, (clip.y1, Unit::None)));
+    rect.set_attribute((AId::Width, (clip.width(), Unit::None)));
+    rect.set_attribute((AId::Height, (clip.height(), Unit::None)));
+    clip_path.append(&rect);
+
+    let mut defs = match doc.descendants().find(|n| n.is_tag_name(EId::Defs)) {
+        Some(defs) => defs,
+        None => {
+            let defs = doc.create_element(EId::Defs);
+            doc.svg_element().unwrap().prepend(&defs);
+            defs
+        }
+    };
+    defs.append(&clip_path);
+
+    g.set_attribute((AId::ClipPath, clip_path));
+}
+
+fn gen_id(doc: &Document) -> String {
+    let mut n = 1;
+    loop {
+        let id = format!("clipPath{}", n);
+        if !doc.descendants().any(|n| *n.id() == id) {
+            return id;
+        }
+
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &mut Document| flatten_nested_svg(doc, &[]), $in_text, $out_text);
+        )
+    }
+
+    test!(position_1,
+"<svg>
+    <svg x='10' y='20' width='100' height='100'>
+        <rect width='50' height='50'/>
+    </svg>
+</svg>",
+"<svg>
+    <g transform='translate(10 20)'>
+        <rect height='50' width='50'/>
+    </g>
+</svg>
+");
+
+    test!(view_box_1,
+"<svg>
+    <svg width='100' height='50' viewBox='0 0 10 10' fill='red'>
+        <rect width='10' height='10'/>
+    </svg>
+</svg>",
+"<svg>
+    <g fill='#ff0000' transform='matrix(5 0 0 5 25 0)'>
+        <rect height='10' width='10'/>
+    </g>
+</svg>
+");
+
+    test!(view_box_2,
+"<svg>
+    <svg width='100' height='50' viewBox='0 0 10 10' preserveAspectRatio='none'>
+        <rect width='10' height='10'/>
+    </svg>
+</svg>",
+"<svg>
+    <g transform='scale(10 5)'>
+        <rect height='10' width='10'/>
+    </g>
+</svg>
+");
+
+    test!(clip_1,
+"<svg>
+    <svg x='10' width='20' height='20'>
+        <rect width='50' height='50'/>
+    </svg>
+</svg>",
+"<svg>
+    <defs>
+        <clipPath id='clipPath1'>
+            <rect height='20' width='20' x='0' y='0'/>
+        </clipPath>
+    </defs>
+    <g clip-path='url(#clipPath1)' transform='translate(10)'>
+        <rect height='50' width='50'/>
+    </g>
+</svg>
+");
+
+    test!(overflow_1,
+"<svg>
+    <svg x='10' width='20' height='20' overflow='visible'>
+        <rect width='50' height='50'/>
+    </svg>
+    <svg x='10' overflow='auto'>
+        <rect width='50' height='50'/>
+    </svg>
+</svg>",
+"<svg>
+    <g transform='translate(10)'>
+        <rect height='50' width='50'/>
+    </g>
+    <g transform='translate(10)'>
+        <rect height='50' width='50'/>
+    </g>
+</svg>
+");
+
+    test!(nested_1,
+"<svg>
+    <svg x='10' width='100' height='100'>
+        <svg x='10' width='100' height='100'>
+            <rect width='50' height='50'/>
+        </svg>
+    </svg>
+</svg>",
+"<svg>
+    <g transform='translate(10)'>
+        <g transform='translate(10)'>
+            <rect height='50' width='50'/>
+        </g>
+    </g>
+</svg>
+");
+
+    test!(skip_1,
+"<svg>
+    <svg width='50%' height='100'/>
+    <svg width='100' height='100'>
+        <rect width='50%' height='50'/>
+    </svg>
+    <svg>
+        <rect width='50' height='50'/>
+    </svg>
+    <svg id='svg1' width='100' height='100'/>
+    <use xlink:href='#svg1'/>
+</svg>",
+"<svg>
+    <svg height='100' width='50%'/>
+    <svg height='100' width='100'>
+        <rect height='50' width='50%'/>
+    </svg>
+    <svg>
+        <rect height='50' width='50'/>
+    </svg>
+    <svg id='svg1' height='100' width='100'/>
+    <use xlink:href='#svg1'/>
+</svg>
+");
+}
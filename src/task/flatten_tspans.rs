@@ -0,0 +1,193 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Document,
+    Node,
+    NodeType,
+};
+
+use options::CleaningOptions;
+use task::short::{EId, AId};
+use task::utils;
+
+// Removes 'tspan' elements without attributes and merges subsequent 'tspan' elements
+// with the same attributes.
+pub fn flatten_tspans(doc: &Document, options: &CleaningOptions) {
+    // Process nested elements first.
+    let nodes: Vec<Node> = doc.descendants()
+        .filter(|n| n.is_tag_name(EId::Tspan))
+        .collect();
+
+    for mut node in nodes.into_iter().rev() {
+        if !can_flatten(&node, options) {
+            continue;
+        }
+
+        if node.attributes().iter().all(|a| !a.visible) {
+            ungroup(&mut node);
+            continue;
+        }
+
+        if is_positioned(&node) {
+            continue;
+        }
+
+        if let Some(mut prev) = node.previous_sibling() {
+            if     prev.is_tag_name(EId::Tspan)
+                && can_flatten(&prev, options)
+                && is_same_attributes(&prev, &node) {
+                while let Some(mut child) = node.first_child() {
+                    child.detach();
+                    prev.append(&child);
+                }
+                node.remove();
+            }
+        }
+    }
+
+    for node in doc.descendants().filter(|n| n.is_tag_name(EId::Text)) {
+        join_text_nodes(&node);
+    }
+}
+
+fn can_flatten(node: &Node, options: &CleaningOptions) -> bool {
+    !node.is_used() && !utils::is_kept_id(node, &options.keep_ids) && !node.has_id()
+}
+
+// The second 'tspan' with such attributes starts a new text chunk.
+fn is_positioned(node: &Node) -> bool {
+    node.has_attributes(&[AId::X, AId::Y, AId::Dx, AId::Dy, AId::Rotate,
+                          AId::TextLength, AId::LengthAdjust])
+}
+
+fn is_same_attributes(node1: &Node, node2: &Node) -> bool {
+    let attrs1 = node1.attributes();
+    let attrs2 = node2.attributes();
+
+    let count = |attrs: &::svgdom::Attributes| attrs.iter().filter(|a| a.visible).count();
+
+    if count(&attrs1) != count(&attrs2) {
+        return false;
+    }
+
+    for attr in attrs1.iter().filter(|a| a.visible) {
+        match attrs2.get(attr.name.into_ref()) {
+            Some(attr2) if attr2.visible && attr2.value == attr.value => {}
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+fn ungroup(node: &mut Node) {
+    while let Some(mut child) = node.first_child() {
+        child.detach();
+        node.insert_before(&child);
+    }
+    node.remove();
+}
+
+fn join_text_nodes(parent: &Node) {
+    let nodes: Vec<Node> = parent.descendants()
+        .filter(|n| n.node_type() == NodeType::Text)
+        .collect();
+
+    for mut node in nodes {
+        let prev = node.previous_sibling();
+        if let Some(mut prev) = prev {
+            if prev.node_type() == NodeType::Text {
+                prev.text_mut().push_str(&node.text());
+                node.remove();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                flatten_tspans(doc, &CleaningOptions::default())
+            }, $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    test!(flatten_1,
+"<svg>
+    <text>Some <tspan>long <tspan>text</tspan></tspan> end</text>
+</svg>",
+"<svg>
+    <text>Some long text end</text>
+</svg>
+");
+
+    test!(flatten_2,
+"<svg>
+    <text>Some <tspan fill='#ff0000'><tspan>long</tspan> text</tspan></text>
+</svg>",
+"<svg>
+    <text>Some <tspan fill='#ff0000'>long text</tspan></text>
+</svg>
+");
+
+    test!(merge_1,
+"<svg>
+    <text>Some <tspan fill='#ff0000'>long</tspan><tspan fill='#ff0000'> red</tspan><tspan fill='#ff0000'> text</tspan></text>
+</svg>",
+"<svg>
+    <text>Some <tspan fill='#ff0000'>long red text</tspan></text>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg>
+    <text>Some <tspan fill='#ff0000'>red</tspan><tspan fill='#0000ff'> blue</tspan></text>
+</svg>
+");
+
+    test_eq!(keep_2,
+"<svg>
+    <text><tspan x='10' y='10'>Some</tspan><tspan x='10' y='20'>text</tspan></text>
+</svg>
+");
+
+    test_eq!(keep_3,
+"<svg>
+    <text><tspan fill='#ff0000'>Some</tspan> long <tspan fill='#ff0000'>text</tspan></text>
+</svg>
+");
+
+    test_eq!(keep_4,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <text>Some <tspan id='tspan1'>text</tspan></text>
+    <text><tref xlink:href='#tspan1'/></text>
+</svg>
+");
+}
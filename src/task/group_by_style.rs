@@ -162,12 +162,12 @@ impl TableRow {
         max_count
     }
 
-    // Returns the longest continuous range of set flags.
+    // Returns continuous ranges of set flags, sorted by length.
     //
     // # Example
     //
-    // `|-***-*****--|` -> (5, 10)
-    fn longest_range(&self) -> Range<usize> {
+    // `|-***-*****--|` -> [(5, 9), (1, 3)]
+    fn ranges(&self) -> Vec<Range<usize>> {
         let mut list = Vec::new();
 
         let mut start = None;
@@ -191,7 +191,7 @@ impl TableRow {
 
         list.sort_by(|a, b| b.len().cmp(&a.len()));
 
-        list[0].clone()
+        list
     }
 }
 
@@ -471,19 +471,23 @@ fn _group_by_style(parent: &Node, opt: &WriteOptions) {
         // If parent node is 'g' - use it,
         // it not - create new one.
         let is_valid_parent = parent.is_tag_name(EId::Svg) || parent.is_tag_name(EId::G);
+        let len = node_list.len();
         let mut g_node = if is_valid_parent && is_all_children {
-            parent.clone()
-        } else {
+            Some(parent.clone())
+        } else if is_smaller(&table.d[0].attributes, len, opt) {
             let g_node = parent.document().create_element(EId::G);
             node_list[0].insert_before(&g_node);
-            g_node
+            Some(g_node)
+        } else {
+            None
         };
 
-        let len = node_list.len();
-        move_nodes(&table.d[0].attributes, &mut g_node, &mut node_list, 0..len);
+        if let Some(ref mut g_node) = g_node {
+            move_nodes(&table.d[0].attributes, g_node, &mut node_list, 0..len);
 
-        // Remove first row.
-        table.d.remove(0);
+            // Remove first row.
+            table.d.remove(0);
+        }
     }
 
     if table.d.is_empty() {
@@ -493,25 +497,28 @@ fn _group_by_style(parent: &Node, opt: &WriteOptions) {
     // Process row that is partially filled, aka:
     // fill='red' |-*****---|
     // so we can group selected elements to the subgroup.
-    // We can do this only once, because we can't group nodes that
+    // We can do this only for one row, because we can't group nodes that
     // already been had been moved to subgroup.
     {
         let d = &table.d[0];
 
-        // Get the longest range on nodes.
-        let range = d.longest_range();
+        // Process all ranges of nodes, aka:
+        // |*****--*****|
+        for range in d.ranges() {
+            // Range end is inclusive.
+            let count = range.end - range.start + 1;
+            if count < min_nodes_count || !is_smaller(&d.attributes, count, opt) {
+                continue;
+            }
 
-        // Do the same as in previous block.
-        let mut g_node = parent.document().create_element(EId::G);
-        node_list[range.start].insert_before(&g_node);
+            // Do the same as in previous block.
+            let mut g_node = parent.document().create_element(EId::G);
+            node_list[range.start].insert_before(&g_node);
 
-        move_nodes(&d.attributes, &mut g_node, &mut node_list, range);
+            move_nodes(&d.attributes, &mut g_node, &mut node_list, range);
+        }
     }
 
-    // TODO: process rows with multiple ranges, aka
-    // |*****--*****|
-    // Currently only first range will be processed
-
     // TODO: invert attributes values if possible
     // Example:
     // <rect fill='#ff0000'/>
@@ -531,6 +538,23 @@ fn _group_by_style(parent: &Node, opt: &WriteOptions) {
     // </g>
 }
 
+// Checks that a new group with the specified attributes is smaller
+// than these attributes on each of the grouped elements.
+fn is_smaller(attributes: &[Attribute], count: usize, opt: &WriteOptions) -> bool {
+    // 'Attribute' is printed as 'name="value"' and we need a leading space too.
+    let attrs_len: usize = attributes.iter().map(|a| 1 + format!("{}", a).len()).sum();
+
+    let mut g_len = "<g></g>".len() + attrs_len;
+    g_len += match opt.indent {
+        Indent::None => 0,
+        // Two new lines and an additional indent on each grouped element.
+        Indent::Spaces(n) => 2 + count * n as usize,
+        Indent::Tabs => 2 + count,
+    };
+
+    count * attrs_len > g_len
+}
+
 fn move_nodes(attributes: &[Attribute], g_node: &mut Node, node_list: &mut [Node], range: Range<usize>) {
     let attr_ids: Vec<AId> = attributes.iter().map(|a| a.id().unwrap()).collect();
 
@@ -799,7 +823,7 @@ mod tests {
 </svg>
 ");
 
-    // Choose longest range.
+    // Process all ranges.
     test!(group_11,
 "<svg>
     <rect id='r1' fill='#ff0000'/>
@@ -812,9 +836,11 @@ mod tests {
     <rect id='r8' fill='#ff0000'/>
 </svg>",
 "<svg>
-    <rect id='r1' fill='#ff0000'/>
-    <rect id='r2' fill='#ff0000'/>
-    <rect id='r3' fill='#ff0000'/>
+    <g fill='#ff0000'>
+        <rect id='r1'/>
+        <rect id='r2'/>
+        <rect id='r3'/>
+    </g>
     <rect id='r4'/>
     <g fill='#ff0000'>
         <rect id='r5'/>
@@ -917,4 +943,20 @@ mod tests {
 </svg>
 ");
 
+
+    #[test]
+    fn is_smaller_1() {
+        use svgdom::{Attribute, Indent};
+
+        let attrs = [Attribute::new(AId::Fill, "red")];
+
+        let opt = WriteOptions { indent: Indent::None, .. WriteOptions::default() };
+        assert!(!is_smaller(&attrs, 1, &opt));
+        assert!(is_smaller(&attrs, 2, &opt));
+
+        // Grouped elements will have a bigger indent.
+        let opt = WriteOptions { indent: Indent::Spaces(4), .. WriteOptions::default() };
+        assert!(!is_smaller(&attrs, 2, &opt));
+        assert!(is_smaller(&attrs, 3, &opt));
+    }
 }
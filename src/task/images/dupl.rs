@@ -0,0 +1,191 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::{EId, AId};
+use task::utils::{self, AnimatedAttributes};
+
+// Attributes that are moved to the shared 'image' element.
+static SHARED_ATTRIBUTES: &[AId] = &[
+    AId::XlinkHref,
+    AId::Width,
+    AId::Height,
+    AId::PreserveAspectRatio,
+];
+
+// Moves identical embedded images into a single 'image' element inside 'defs'
+// and replaces them with 'use' elements.
+//
+// 'x' and 'y' attributes of the 'use' element have the same meaning,
+// but 'width' and 'height' are ignored, so only images with the same size can be merged.
+pub fn remove_dupl_images(doc: &mut Document, keep_ids: &[String]) {
+    let animated = AnimatedAttributes::new(doc);
+
+    let mut groups: Vec<Vec<Node>> = Vec::new();
+    for node in doc.descendants().filter(|n| n.is_tag_name(EId::Image)) {
+        if    node.is_used()
+           || utils::is_kept_id(&node, keep_ids)
+           || animated.is_animated(&node) {
+            continue;
+        }
+
+        if href_len(&node).is_none() {
+            continue;
+        }
+
+        match groups.iter().position(|g| is_same_image(&g[0], &node)) {
+            Some(idx) => groups[idx].push(node.clone()),
+            None => groups.push(vec![node.clone()]),
+        }
+    }
+
+    for group in groups.into_iter().filter(|g| g.len() > 1) {
+        let id = gen_id(doc);
+        if !is_smaller(&group, &id) {
+            continue;
+        }
+
+        let mut image = doc.create_element(EId::Image);
+        image.set_id(id);
+        for aid in SHARED_ATTRIBUTES {
+            if let Some(attr) = group[0].attributes().get(*aid) {
+                image.set_attribute(attr.clone());
+            }
+        }
+
+        let mut defs = match doc.descendants().find(|n| n.is_tag_name(EId::Defs)) {
+            Some(defs) => defs,
+            None => {
+                let defs = doc.create_element(EId::Defs);
+                doc.svg_element().unwrap().prepend(&defs);
+                defs
+            }
+        };
+        defs.append(&image);
+
+        for mut node in group {
+            let mut use_node = doc.create_element(EId::Use);
+            for attr in node.attributes().iter() {
+                if !SHARED_ATTRIBUTES.iter().any(|aid| attr.has_id(*aid)) {
+                    use_node.set_attribute(attr.clone());
+                }
+            }
+            use_node.set_attribute((AId::XlinkHref, image.clone()));
+
+            node.insert_before(&use_node);
+            node.remove();
+        }
+    }
+}
+
+// Only long references are worth sharing, so we are skipping links.
+fn href_len(node: &Node) -> Option<usize> {
+    match node.attributes().get_value(AId::XlinkHref) {
+        Some(AttributeValue::String(s)) => Some(s.len()),
+        _ => None,
+    }
+}
+
+fn is_same_image(node1: &Node, node2: &Node) -> bool {
+    let attrs1 = node1.attributes();
+    let attrs2 = node2.attributes();
+    SHARED_ATTRIBUTES.iter().all(|aid| attrs1.get_value(*aid) == attrs2.get_value(*aid))
+}
+
+// Compares ' xlink:href="..."' on each image with the shared image
+// and ' xlink:href="#id"' on each 'use'.
+fn is_smaller(group: &[Node], id: &str) -> bool {
+    let href_len = href_len(&group[0]).unwrap() + 14;
+    let old_len = href_len * group.len();
+    // '<use/>' is shorter than '<image/>', so we are ignoring it.
+    let new_len = href_len + "<image id=\"\"/>".len() + id.len() + (id.len() + 15) * group.len();
+    new_len < old_len
+}
+
+fn gen_id(doc: &Document) -> String {
+    let mut n = 1;
+    loop {
+        let id = format!("img{}", n);
+        if !doc.descendants().any(|n| *n.id() == id) {
+            return id;
+        }
+
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut doc = Document::from_str($in_text).unwrap();
+                remove_dupl_images(&mut doc, &["keep".to_string()]);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(rm_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image height='10' width='10' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+    <image height='10' transform='scale(2)' width='10' x='20' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <defs>
+        <image id='img1' height='10' width='10' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+    </defs>
+    <use xlink:href='#img1'/>
+    <use transform='scale(2)' x='20' xlink:href='#img1'/>
+</svg>
+");
+
+    test!(keep_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image height='10' width='10' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+    <image height='20' width='10' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+    <image id='keep' height='20' width='10' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image height='10' width='10' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+    <image height='20' width='10' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+    <image id='keep' height='20' width='10' xlink:href='data:image/png;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA'/>
+</svg>
+");
+
+    // Short references are not worth sharing.
+    test!(keep_2,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='a.png'/>
+    <image xlink:href='a.png'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='a.png'/>
+    <image xlink:href='a.png'/>
+</svg>
+");
+}
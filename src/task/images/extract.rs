@@ -0,0 +1,130 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::fs;
+use std::path::Path;
+
+use svgdom::{
+    AttributeValue,
+    Document,
+};
+
+use task::short::AId;
+use error::Result;
+use super::{
+    is_image,
+    parse_data_uri,
+};
+
+// Writes embedded images to files inside the 'dir' and replaces data URIs with relative links.
+//
+// The 'dir' is relative to the 'output_dir'. Files are named by the content hash,
+// so identical images are written only once and can be cached.
+pub fn extract_images(doc: &Document, dir: &str, output_dir: Option<&str>) -> Result<()> {
+    let out_dir = match output_dir {
+        Some(output_dir) => Path::new(output_dir).join(dir),
+        None => Path::new(dir).to_path_buf(),
+    };
+
+    for mut node in doc.descendants().filter(is_image) {
+        let (mime, data) = {
+            let attrs = node.attributes();
+            match attrs.get_value(AId::XlinkHref) {
+                Some(AttributeValue::String(s)) => match parse_data_uri(s) {
+                    Some(v) => v,
+                    None => continue,
+                },
+                _ => continue,
+            }
+        };
+
+        let ext = match mime.as_str() {
+            "image/png" => "png",
+            "image/jpeg" | "image/jpg" => "jpg",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            "image/svg+xml" => "svg",
+            _ => continue,
+        };
+
+        let name = format!("{:016x}.{}", fnv_hash(&data), ext);
+        let path = out_dir.join(&name);
+        if !path.exists() {
+            fs::create_dir_all(&out_dir)?;
+            fs::write(&path, &data)?;
+        }
+
+        let href = format!("{}/{}", dir.trim_end_matches(['/', '\\']), name);
+        node.set_attribute((AId::XlinkHref, href));
+    }
+
+    Ok(())
+}
+
+// FNV-1a.
+fn fnv_hash(data: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &b in data {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use svgdom::{Document, ToStringWithOptions};
+
+    #[test]
+    fn extract_1() {
+        let out_dir = env::temp_dir().join("svgcleaner-extract-1");
+        let _ = fs::remove_dir_all(&out_dir);
+
+        let doc = Document::from_str(
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/png;base64,Zm9v'/>
+    <image xlink:href='data:image/png;base64,Zm9v'/>
+    <image xlink:href='data:text/plain;base64,Zm9v'/>
+    <image xlink:href='image.png'/>
+</svg>").unwrap();
+
+        extract_images(&doc, "images/", Some(out_dir.to_str().unwrap())).unwrap();
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='images/dcb27518fed9d577.png'/>
+    <image xlink:href='images/dcb27518fed9d577.png'/>
+    <image xlink:href='data:text/plain;base64,Zm9v'/>
+    <image xlink:href='image.png'/>
+</svg>
+");
+
+        let data = fs::read(out_dir.join("images").join("dcb27518fed9d577.png")).unwrap();
+        assert_eq!(data, b"foo");
+        assert_eq!(fs::read_dir(out_dir.join("images")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn fnv_hash_1() {
+        assert_eq!(fnv_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,161 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use bytes::{
+    read_u16,
+    read_u32,
+};
+
+const SOI: u8 = 0xD8;
+const SOS: u8 = 0xDA;
+const APP0: u8 = 0xE0;
+const APP1: u8 = 0xE1;
+const APP2: u8 = 0xE2;
+const APP14: u8 = 0xEE;
+const APP15: u8 = 0xEF;
+const COM: u8 = 0xFE;
+
+const EXIF_ORIENTATION: u16 = 0x0112;
+
+// Removes comments and application segments, which don't affect rendering.
+//
+// ICC profiles, Adobe color transforms and EXIF data with a non-default orientation
+// are preserved. Image data after the first scan is copied as is.
+//
+// Returns 'None' for an invalid data.
+//
+// TODO: lossless recompression of the image data, like optimized Huffman tables,
+//       and downscaling to the displayed size are not implemented yet.
+pub fn optimize(data: &[u8]) -> Option<Vec<u8>> {
+    if data.get(0..2)? != [0xFF, SOI] {
+        return None;
+    }
+
+    let mut out = vec![0xFF, SOI];
+    let mut pos = 2;
+    loop {
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+
+        let marker = *data.get(pos + 1)?;
+        match marker {
+            // Fill bytes.
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            SOS => {
+                out.extend_from_slice(&data[pos..]);
+                return Some(out);
+            }
+            _ => {}
+        }
+
+        let len = read_u16(data, pos + 2, false)? as usize;
+        let end = pos + 2 + len;
+        let segment = data.get(pos..end)?;
+
+        if is_kept_segment(marker, segment.get(4..)?) {
+            out.extend_from_slice(segment);
+        }
+
+        pos = end;
+    }
+}
+
+fn is_kept_segment(marker: u8, data: &[u8]) -> bool {
+    match marker {
+        APP1 if data.starts_with(b"Exif\0\0") => {
+            // Browsers are rotating images according to the orientation.
+            exif_orientation(&data[6..]).is_some_and(|o| o != 1)
+        }
+        APP2 => data.starts_with(b"ICC_PROFILE\0"),
+        APP14 => data.starts_with(b"Adobe"),
+        APP0..=APP15 | COM => false,
+        _ => true,
+    }
+}
+
+// Reads the orientation from the first IFD of the EXIF TIFF structure.
+fn exif_orientation(tiff: &[u8]) -> Option<u16> {
+    let le = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+
+    let ifd = read_u32(tiff, 4, le)? as usize;
+    let count = read_u16(tiff, ifd, le)? as usize;
+    for i in 0..count {
+        let entry = ifd + 2 + i * 12;
+        if read_u16(tiff, entry, le)? == EXIF_ORIENTATION {
+            return read_u16(tiff, entry + 8, le);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(marker: u8, data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0xFF, marker];
+        out.extend_from_slice(&(data.len() as u16 + 2).to_be_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    // A big-endian EXIF with a single orientation entry.
+    fn exif(orientation: u16) -> Vec<u8> {
+        let mut data = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01".to_vec();
+        data.extend_from_slice(&orientation.to_be_bytes());
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn optimize_1() {
+        let mut data = vec![0xFF, SOI];
+        data.extend(segment(APP0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0"));
+        data.extend(segment(APP1, &exif(1)));
+        data.extend(segment(APP2, b"ICC_PROFILE\0\x01\x01"));
+        data.extend(segment(COM, b"comment"));
+        data.extend(segment(0xDB, b"table"));
+        data.extend_from_slice(&[0xFF, SOS, 0, 2, 1, 2, 3, 0xFF, 0xD9]);
+
+        let mut expected = vec![0xFF, SOI];
+        expected.extend(segment(APP2, b"ICC_PROFILE\0\x01\x01"));
+        expected.extend(segment(0xDB, b"table"));
+        expected.extend_from_slice(&[0xFF, SOS, 0, 2, 1, 2, 3, 0xFF, 0xD9]);
+
+        assert_eq!(optimize(&data), Some(expected));
+    }
+
+    #[test]
+    fn optimize_2() {
+        let mut data = vec![0xFF, SOI];
+        data.extend(segment(APP1, &exif(6)));
+        data.extend_from_slice(&[0xFF, SOS, 0, 2]);
+
+        assert_eq!(optimize(&data), Some(data.clone()));
+        assert_eq!(optimize(&data[..5]), None);
+    }
+}
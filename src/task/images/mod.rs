@@ -0,0 +1,144 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::{EId, AId};
+use task::utils::base64;
+
+pub use self::dupl::remove_dupl_images;
+#[cfg(feature = "fs")]
+pub use self::extract::extract_images;
+
+mod dupl;
+#[cfg(feature = "fs")]
+mod extract;
+mod jpeg;
+pub mod png;
+
+// Losslessly optimizes embedded PNG and JPEG images.
+//
+// Non-rendering data is removed: text, time and physical size chunks from PNG
+// and comments, thumbnails and editors data from JPEG.
+// PNG image data is recompressed, while JPEG image data is kept as is.
+// Images are never downscaled.
+pub fn optimize_images(doc: &Document) {
+    for mut node in doc.descendants().filter(is_image) {
+        let new_href = {
+            let attrs = node.attributes();
+            let href = match attrs.get_value(AId::XlinkHref) {
+                Some(AttributeValue::String(s)) => s,
+                _ => continue,
+            };
+
+            match optimize_data_uri(href) {
+                Some(new_href) => new_href,
+                None => continue,
+            }
+        };
+
+        node.set_attribute((AId::XlinkHref, new_href));
+    }
+}
+
+fn is_image(node: &Node) -> bool {
+    node.is_tag_name(EId::Image) || node.is_tag_name(EId::FeImage)
+}
+
+// Returns an optimized data URI when it's shorter.
+fn optimize_data_uri(href: &str) -> Option<String> {
+    let (mime, data) = parse_data_uri(href)?;
+
+    let new_data = match mime.as_str() {
+        "image/png" => png::optimize(&data)?,
+        "image/jpeg" | "image/jpg" => jpeg::optimize(&data)?,
+        _ => return None,
+    };
+
+    let new_href = format!("data:{};base64,{}", mime, base64::encode(&new_data));
+    if new_href.len() < href.len() {
+        Some(new_href)
+    } else {
+        None
+    }
+}
+
+// Parses a base64 data URI, like 'data:image/png;base64,...'.
+fn parse_data_uri(href: &str) -> Option<(String, Vec<u8>)> {
+    let href = href.trim();
+    if !href.get(..5)?.eq_ignore_ascii_case("data:") {
+        return None;
+    }
+
+    let idx = href.find(',')?;
+    let header = href[5..idx].to_lowercase();
+    let mime = header.strip_suffix(";base64")?;
+    let data = base64::decode(&href[idx + 1..])?;
+
+    Some((mime.trim().to_string(), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, optimize_images, $in_text, $out_text);
+        )
+    }
+
+    // A PNG with a 'tEXt' chunk and two 'IDAT' chunks.
+    test!(optimize_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/png;base64,
+        iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAAMHRFWHRDb21tZW50AHh4eHh4eHh4eHh4eHh4
+        eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHh4eHhdiqdiAAAAAklEQVRhYqOh5ygAAAACSURBVGNkePQgnwAAAABJ
+        RU5ErkJggg=='/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAABElEQVRhYmNkJuX1CgAAAABJRU5ErkJggg=='/>
+</svg>
+");
+
+    test!(keep_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/png;base64,AAAA'/>
+    <image xlink:href='data:image/gif;base64,AAAA'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/png;base64,AAAA'/>
+    <image xlink:href='data:image/gif;base64,AAAA'/>
+</svg>
+");
+
+    #[test]
+    fn parse_data_uri_1() {
+        assert_eq!(parse_data_uri("data:image/png;base64,Zm9v"),
+                   Some(("image/png".to_string(), b"foo".to_vec())));
+        assert_eq!(parse_data_uri("DATA:image/PNG;BASE64,Zm9v"),
+                   Some(("image/png".to_string(), b"foo".to_vec())));
+        assert_eq!(parse_data_uri("data:image/svg+xml,<svg/>"), None);
+        assert_eq!(parse_data_uri("image.png"), None);
+    }
+}
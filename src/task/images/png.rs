@@ -0,0 +1,434 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use bytes::{
+    crc32,
+    read_u32,
+};
+use gzip;
+
+pub const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// Larger images are not recompressed, since it requires too much memory and time.
+const MAX_DATA_SIZE: usize = 32 * 1024 * 1024;
+
+// Ancillary chunks, which affect rendering.
+//
+// Text, time, physical size, background and histogram chunks are removed.
+static KEEP_CHUNKS: &[&[u8]] = &[
+    b"tRNS", b"gAMA", b"cHRM", b"sRGB", b"iCCP", b"cICP", b"eXIf",
+    // APNG animation.
+    b"acTL", b"fcTL", b"fdAT",
+];
+
+/// A PNG chunk.
+pub struct Chunk<'a> {
+    /// A chunk type, like `IHDR`.
+    pub name: &'a [u8],
+    /// Chunk data.
+    pub data: &'a [u8],
+    /// The whole chunk, including the length, the type and the CRC.
+    pub raw: &'a [u8],
+}
+
+/// A PNG image header.
+pub struct Header {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub color_type: u8,
+    pub interlace: u8,
+}
+
+impl Header {
+    /// Parses the `IHDR` chunk data.
+    pub fn parse(data: &[u8]) -> Option<Header> {
+        if data.len() != 13 {
+            return None;
+        }
+
+        let header = Header {
+            width: read_u32(data, 0, false)?,
+            height: read_u32(data, 4, false)?,
+            bit_depth: data[8],
+            color_type: data[9],
+            interlace: data[12],
+        };
+
+        let is_valid_depth = match header.color_type {
+            0 => [1, 2, 4, 8, 16].contains(&header.bit_depth),
+            3 => [1, 2, 4, 8].contains(&header.bit_depth),
+            2 | 4 | 6 => [8, 16].contains(&header.bit_depth),
+            _ => false,
+        };
+
+        if is_valid_depth { Some(header) } else { None }
+    }
+
+    /// Returns a number of channels.
+    pub fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3, // RGB
+            4 => 2, // grayscale with alpha
+            6 => 4, // RGBA
+            _ => 1, // grayscale or palette
+        }
+    }
+
+    /// Returns a number of bytes in a row, excluding the filter type byte.
+    pub fn stride(&self) -> Option<usize> {
+        let bits = (self.width as usize).checked_mul(self.channels() * self.bit_depth as usize)?;
+        Some(bits.checked_add(7)? / 8)
+    }
+
+    /// Returns a number of bytes per pixel, rounded up to one, which is used by filters.
+    pub fn bpp(&self) -> usize {
+        ((self.channels() * self.bit_depth as usize) / 8).max(1)
+    }
+}
+
+/// Splits PNG data into chunks.
+///
+/// Returns `None` for an invalid or truncated data.
+pub fn parse_chunks<'a>(data: &'a [u8]) -> Option<Vec<Chunk<'a>>> {
+    if !data.starts_with(SIGNATURE) {
+        return None;
+    }
+
+    let mut chunks = Vec::new();
+    let mut pos = SIGNATURE.len();
+    loop {
+        let len = read_u32(data, pos, false)? as usize;
+        let end = pos.checked_add(len)?.checked_add(12)?;
+        let raw = data.get(pos..end)?;
+        let name = &raw[4..8];
+
+        chunks.push(Chunk { name, data: &raw[8..8 + len], raw });
+        pos = end;
+
+        if name == b"IEND" {
+            return Some(chunks);
+        }
+    }
+}
+
+// Removes non-rendering chunks, joins 'IDAT' chunks and recompresses image data,
+// when it becomes smaller.
+//
+// Returns 'None' for an invalid data.
+pub fn optimize(data: &[u8]) -> Option<Vec<u8>> {
+    let chunks = parse_chunks(data)?;
+    let header = chunks.first().filter(|c| c.name == b"IHDR").and_then(|c| Header::parse(c.data));
+
+    let mut out = SIGNATURE.to_vec();
+    let mut idat = Vec::new();
+    for chunk in &chunks {
+        if chunk.name == b"IDAT" {
+            idat.extend_from_slice(chunk.data);
+            continue;
+        }
+
+        // 'IDAT' chunks are always consecutive.
+        if !idat.is_empty() {
+            let new_idat = header.as_ref().and_then(|h| recompress(h, &idat));
+            write_chunk(b"IDAT", new_idat.as_ref().unwrap_or(&idat), &mut out);
+            idat.clear();
+        }
+
+        if is_critical(chunk.name) || KEEP_CHUNKS.contains(&chunk.name) {
+            out.extend_from_slice(chunk.raw);
+        }
+    }
+
+    Some(out)
+}
+
+// Critical chunks have an uppercase first letter.
+fn is_critical(name: &[u8]) -> bool {
+    name[0] & 0x20 == 0
+}
+
+fn write_chunk(name: &[u8], data: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(name);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Losslessly recompresses image data using the best filter strategy.
+//
+// Pixels are never changed, only row filters and the deflate stream are.
+// Returns 'None' when the result is not smaller or the image is not supported.
+//
+// TODO: downscaling to the displayed size is not implemented yet.
+fn recompress(header: &Header, idat: &[u8]) -> Option<Vec<u8>> {
+    // Interlaced images have rows of different sizes.
+    if header.interlace != 0 {
+        return None;
+    }
+
+    let stride = header.stride()?;
+    let height = header.height as usize;
+    let size = stride.checked_add(1)?.checked_mul(height)?;
+    if size > MAX_DATA_SIZE {
+        return None;
+    }
+
+    let mut rows = gzip::zlib_decompress(idat, size).ok()?;
+    if rows.len() != size {
+        return None;
+    }
+    unfilter(&mut rows, stride, height, header.bpp())?;
+
+    let mut best: Option<Vec<u8>> = None;
+    // No filters are usually better for palette and grayscale images
+    // and adaptive ones for true color images.
+    for &is_adaptive in &[false, true] {
+        let data = gzip::zlib_compress(&filter(&rows, stride, header.bpp(), is_adaptive));
+        if data.len() < best.as_ref().map_or(idat.len(), |d| d.len()) {
+            best = Some(data);
+        }
+    }
+
+    best
+}
+
+/// Reverts per-row filters in place. Each row starts with a filter type byte,
+/// which is preserved.
+///
+/// Returns `None` for an invalid data.
+///
+/// Details: https://www.w3.org/TR/PNG/#9Filters
+pub fn unfilter(data: &mut [u8], stride: usize, height: usize, bpp: usize) -> Option<()> {
+    if data.len() < (stride + 1) * height {
+        return None;
+    }
+
+    for y in 0..height {
+        let row = y * (stride + 1);
+        let filter = data[row];
+        if filter > 4 {
+            return None;
+        }
+
+        for x in 0..stride {
+            let a = if x >= bpp { data[row + 1 + x - bpp] } else { 0 };
+            let (b, c) = if y == 0 {
+                (0, 0)
+            } else {
+                let prev = row - stride - 1;
+                (data[prev + 1 + x], if x >= bpp { data[prev + 1 + x - bpp] } else { 0 })
+            };
+
+            data[row + 1 + x] = data[row + 1 + x].wrapping_add(predict(filter, a, b, c));
+        }
+    }
+
+    Some(())
+}
+
+// Applies filters to unfiltered rows.
+//
+// The adaptive mode selects a filter with the minimum sum of absolute differences for each row,
+// as recommended by the PNG specification. Otherwise, rows are not filtered.
+fn filter(rows: &[u8], stride: usize, bpp: usize, is_adaptive: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(rows.len());
+    let mut candidate = Vec::with_capacity(stride + 1);
+    let mut prev: Option<&[u8]> = None;
+    for row in rows.chunks(stride + 1) {
+        let row = &row[1..];
+        if !is_adaptive {
+            out.push(0);
+            out.extend_from_slice(row);
+            continue;
+        }
+
+        let mut best: Option<(u64, Vec<u8>)> = None;
+        for filter in 0..5 {
+            candidate.clear();
+            filter_row(filter, row, prev, bpp, &mut candidate);
+
+            let sum = candidate[1..].iter().map(|&v| (v as i8).unsigned_abs() as u64).sum();
+            if best.as_ref().is_none_or(|&(best_sum, _)| sum < best_sum) {
+                best = Some((sum, candidate.clone()));
+            }
+        }
+
+        out.extend_from_slice(&best.unwrap().1);
+        prev = Some(row);
+    }
+
+    out
+}
+
+fn filter_row(filter: u8, row: &[u8], prev: Option<&[u8]>, bpp: usize, out: &mut Vec<u8>) {
+    out.push(filter);
+    for x in 0..row.len() {
+        let a = if x >= bpp { row[x - bpp] } else { 0 };
+        let b = prev.map_or(0, |p| p[x]);
+        let c = match prev {
+            Some(p) if x >= bpp => p[x - bpp],
+            _ => 0,
+        };
+
+        out.push(row[x].wrapping_sub(predict(filter, a, b, c)));
+    }
+}
+
+// 'a' is the left byte, 'b' is the upper one and 'c' is the upper left one.
+fn predict(filter: u8, a: u8, b: u8, c: u8) -> u8 {
+    match filter {
+        1 => a,
+        2 => b,
+        3 => ((a as u16 + b as u16) / 2) as u8,
+        4 => paeth(a, b, c),
+        _ => 0,
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(name: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_chunk(name, data, &mut out);
+        out
+    }
+
+    // Creates zlib data with a single stored deflate block.
+    fn stored_zlib(data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0x78, 0x01, 0x01];
+        out.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(!(data.len() as u16)).to_le_bytes());
+        out.extend_from_slice(data);
+        let (mut a, mut b) = (1u32, 0u32);
+        for &v in data {
+            a = (a + v as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        out.extend_from_slice(&(b << 16 | a).to_be_bytes());
+        out
+    }
+
+    fn header(width: u32, height: u32, bit_depth: u8, color_type: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn optimize_1() {
+        let mut data = SIGNATURE.to_vec();
+        data.extend(chunk(b"IHDR", &[0; 13]));
+        data.extend(chunk(b"tEXt", b"key\0value"));
+        data.extend(chunk(b"gAMA", &[0; 4]));
+        data.extend(chunk(b"IDAT", b"abc"));
+        data.extend(chunk(b"IDAT", b"def"));
+        data.extend(chunk(b"tIME", &[0; 7]));
+        data.extend(chunk(b"IEND", b""));
+
+        let mut expected = SIGNATURE.to_vec();
+        expected.extend(chunk(b"IHDR", &[0; 13]));
+        expected.extend(chunk(b"gAMA", &[0; 4]));
+        expected.extend(chunk(b"IDAT", b"abcdef"));
+        expected.extend(chunk(b"IEND", b""));
+
+        assert_eq!(optimize(&data), Some(expected));
+    }
+
+    #[test]
+    fn optimize_2() {
+        // Truncated data.
+        let mut data = SIGNATURE.to_vec();
+        data.extend(chunk(b"IHDR", &[0; 13]));
+        assert_eq!(optimize(&data), None);
+        assert_eq!(optimize(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn recompress_1() {
+        // A 16x16 RGBA gradient, which is stored without compression.
+        let mut rows = Vec::new();
+        for y in 0..16u8 {
+            rows.push(0);
+            for x in 0..16u8 {
+                rows.extend_from_slice(&[x * 16, y * 16, 128, 255]);
+            }
+        }
+
+        let mut data = SIGNATURE.to_vec();
+        data.extend(chunk(b"IHDR", &header(16, 16, 8, 6)));
+        data.extend(chunk(b"IDAT", &stored_zlib(&rows)));
+        data.extend(chunk(b"IEND", b""));
+
+        let new_data = optimize(&data).unwrap();
+        assert!(new_data.len() < data.len() / 2);
+
+        // Pixels must be the same.
+        let chunks = parse_chunks(&new_data).unwrap();
+        let mut new_rows = gzip::zlib_decompress(chunks[1].data, 65 * 16).unwrap();
+        unfilter(&mut new_rows, 64, 16, 4).unwrap();
+        for (row, new_row) in rows.chunks(65).zip(new_rows.chunks(65)) {
+            assert_eq!(row[1..], new_row[1..]);
+        }
+    }
+
+    #[test]
+    fn unfilter_1() {
+        // RGB with the 'Sub' filter on the first row and the 'Up' filter on the second one.
+        let mut data = vec![1, 10, 20, 30, 1, 1, 1,
+                            2, 5, 5, 5, 0, 0, 0];
+        unfilter(&mut data, 6, 2, 3).unwrap();
+        assert_eq!(data, vec![1, 10, 20, 30, 11, 21, 31,
+                              2, 15, 25, 35, 11, 21, 31]);
+
+        assert_eq!(unfilter(&mut [5, 0], 1, 1, 1), None);
+    }
+
+    #[test]
+    fn filter_1() {
+        let rows = vec![0, 1, 2, 3, 4, 5, 6,
+                        0, 1, 2, 3, 4, 5, 6];
+        for &is_adaptive in &[false, true] {
+            let mut data = filter(&rows, 6, 3, is_adaptive);
+            unfilter(&mut data, 6, 2, 3).unwrap();
+            assert_eq!(data[1..7], rows[1..7]);
+            assert_eq!(data[8..], rows[8..]);
+        }
+    }
+}
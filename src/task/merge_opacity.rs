@@ -0,0 +1,233 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    FuzzyEq,
+    Node,
+    ValueId,
+};
+
+use task::short::{EId, AId};
+use task::utils::{find_attribute, AnimatedAttributes};
+use task::round_numbers::round_number;
+use options::CleaningOptions;
+
+pub fn merge_opacity(doc: &Document, opt: &CleaningOptions) {
+    let precision = opt.properties_precision as usize;
+    let animated = AnimatedAttributes::new(doc);
+
+    for (id, mut node) in doc.descendants().svg() {
+        if is_shape(id) && !animated.is_animated(&node) {
+            merge_into_paint(&mut node, precision);
+        }
+    }
+
+    rm_inherited_opacity(doc, &animated);
+}
+
+fn is_shape(id: EId) -> bool {
+    matches!(id, EId::Path | EId::Rect | EId::Circle | EId::Ellipse |
+                 EId::Line | EId::Polyline | EId::Polygon)
+}
+
+// Moves the element 'opacity' into 'fill-opacity' or 'stroke-opacity'.
+//
+// We can do this only when the element has only a fill or only a stroke,
+// because 'opacity' is applied to the whole element, so the stroke and the fill
+// overlapping will be different.
+//
+// 'fill-opacity' is longer than 'opacity', so we merge only into an existing attribute.
+fn merge_into_paint(node: &mut Node, precision: usize) {
+    let opacity = match node.attributes().get(AId::Opacity) {
+        Some(attr) if attr.visible => {
+            match attr.value {
+                AttributeValue::Number(n) => n,
+                _ => return,
+            }
+        }
+        _ => return,
+    };
+
+    // Markers are rendered as a part of the element
+    // and filters are applied before the opacity.
+    if node.has_attributes(&[AId::Marker, AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd]) {
+        return;
+    }
+
+    if !matches!(find_attribute(node, AId::Filter),
+                 Some(AttributeValue::PredefValue(ValueId::None)) | None) {
+        return;
+    }
+
+    let has_fill = !matches!(find_attribute(node, AId::Fill),
+                             Some(AttributeValue::PredefValue(ValueId::None)));
+    let has_stroke = !matches!(find_attribute(node, AId::Stroke),
+                               Some(AttributeValue::PredefValue(ValueId::None)) | None);
+
+    let aid = match (has_fill, has_stroke) {
+        (true, false) => AId::FillOpacity,
+        (false, true) => AId::StrokeOpacity,
+        _ => return,
+    };
+
+    let paint_opacity = match node.attributes().get(aid) {
+        Some(attr) if attr.visible => {
+            match attr.value {
+                AttributeValue::Number(n) => n,
+                _ => return,
+            }
+        }
+        _ => return,
+    };
+
+    let mut new_opacity = paint_opacity * opacity;
+    round_number(&mut new_opacity, precision);
+
+    let mut attrs = node.attributes_mut();
+    attrs.insert_from(aid, new_opacity);
+    attrs.remove(AId::Opacity);
+}
+
+// Removes 'fill-opacity' and 'stroke-opacity' that are equal to the inherited value.
+fn rm_inherited_opacity(doc: &Document, animated: &AnimatedAttributes) {
+    for (_, mut node) in doc.descendants().svg() {
+        for aid in &[AId::FillOpacity, AId::StrokeOpacity] {
+            if animated.contains_inherited(&node, *aid) {
+                continue;
+            }
+
+            let value = match node.attributes().get(*aid) {
+                Some(attr) if attr.visible => {
+                    match attr.value {
+                        AttributeValue::Number(n) => n,
+                        _ => continue,
+                    }
+                }
+                _ => continue,
+            };
+
+            let parent = match node.parent() {
+                Some(p) => p,
+                None => continue,
+            };
+
+            let inherited = match find_attribute(&parent, *aid) {
+                Some(AttributeValue::Number(n)) => n,
+                // The default value is processed by 'remove_default_attributes'.
+                _ => continue,
+            };
+
+            if value.fuzzy_eq(&inherited) {
+                node.remove_attribute(*aid);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| merge_opacity(doc, &CleaningOptions::default()),
+                       $in_text, $out_text);
+        )
+    }
+
+    test!(fill_1,
+"<svg>
+    <rect fill-opacity='0.5' opacity='0.5'/>
+</svg>",
+"<svg>
+    <rect fill-opacity='0.25'/>
+</svg>
+");
+
+    test!(stroke_1,
+"<svg>
+    <rect fill='none' opacity='0.5' stroke='#ff0000' stroke-opacity='0.5'/>
+</svg>",
+"<svg>
+    <rect fill='none' stroke='#ff0000' stroke-opacity='0.25'/>
+</svg>
+");
+
+    // Adding a new attribute will make the element bigger.
+    test!(skip_inherited_1,
+"<svg>
+    <g fill-opacity='0.5'>
+        <rect opacity='0.5'/>
+    </g>
+</svg>",
+"<svg>
+    <g fill-opacity='0.5'>
+        <rect opacity='0.5'/>
+    </g>
+</svg>
+");
+
+    test!(skip_fill_and_stroke_1,
+"<svg>
+    <rect fill-opacity='0.5' opacity='0.5' stroke='#ff0000'/>
+</svg>",
+"<svg>
+    <rect fill-opacity='0.5' opacity='0.5' stroke='#ff0000'/>
+</svg>
+");
+
+    test!(skip_group_1,
+"<svg>
+    <g opacity='0.5'>
+        <rect/>
+    </g>
+</svg>",
+"<svg>
+    <g opacity='0.5'>
+        <rect/>
+    </g>
+</svg>
+");
+
+    test!(skip_filter_1,
+"<svg>
+    <filter id='f'/>
+    <rect fill-opacity='0.5' filter='url(#f)' opacity='0.5'/>
+</svg>",
+"<svg>
+    <filter id='f'/>
+    <rect fill-opacity='0.5' filter='url(#f)' opacity='0.5'/>
+</svg>
+");
+
+    test!(inherited_1,
+"<svg>
+    <g fill-opacity='0.5' stroke-opacity='0.3'>
+        <rect fill-opacity='0.5' stroke-opacity='0.4'/>
+    </g>
+</svg>",
+"<svg>
+    <g fill-opacity='0.5' stroke-opacity='0.3'>
+        <rect stroke-opacity='0.4'/>
+    </g>
+</svg>
+");
+}
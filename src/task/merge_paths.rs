@@ -0,0 +1,267 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    path,
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::{EId, AId};
+use task::apply_transforms::utils::is_valid_attr;
+use task::utils::{self, AnimatedAttributes};
+use task::utils::bbox::{
+    calc_path_bbox,
+    calc_stroke_offset,
+    Rect,
+};
+
+// Merges subsequent paths with the same attributes into one.
+//
+// Paths are merged only when they do not overlap, so the fill rule,
+// opacity and painting order are not affected.
+//
+// We should run it before path processing, because paths are joined
+// in absolute coordinates.
+pub fn merge_paths(doc: &Document, keep_ids: &[String]) {
+    let parents: Vec<Node> = doc.descendants()
+        .filter(|n| n.children().any(|c| c.is_tag_name(EId::Path)))
+        .collect();
+
+    let animated = AnimatedAttributes::new(doc);
+
+    for parent in parents {
+        let mut prev: Option<(Node, Rect)> = None;
+        let children: Vec<Node> = parent.children().collect();
+        for mut node in children {
+            let bbox = match get_bbox(&node, keep_ids, &animated) {
+                Some(bbox) => bbox,
+                None => {
+                    prev = None;
+                    continue;
+                }
+            };
+
+            if let Some((ref mut prev_node, ref mut prev_bbox)) = prev {
+                if !prev_bbox.intersects(&bbox) && is_same_attributes(prev_node, &node) {
+                    append_path(prev_node, &node);
+                    *prev_bbox = prev_bbox.union(&bbox);
+                    node.remove();
+                    continue;
+                }
+            }
+
+            prev = Some((node, bbox));
+        }
+    }
+}
+
+// Checks that two elements, represented by the specified paths, can be merged.
+//
+// Used to convert shapes into paths only when they can be merged with their siblings.
+pub fn can_merge(
+    node1: &Node,
+    path1: &path::Path,
+    node2: &Node,
+    path2: &path::Path,
+    keep_ids: &[String],
+    animated: &AnimatedAttributes,
+) -> bool {
+    let bbox1 = calc_bbox(node1, path1, keep_ids, animated);
+    let bbox2 = calc_bbox(node2, path2, keep_ids, animated);
+    match (bbox1, bbox2) {
+        (Some(bbox1), Some(bbox2)) => !bbox1.intersects(&bbox2) && is_same_attributes(node1, node2),
+        _ => false,
+    }
+}
+
+// Returns a bounding box with a stroke, if the path can be merged.
+fn get_bbox(node: &Node, keep_ids: &[String], animated: &AnimatedAttributes) -> Option<Rect> {
+    if !node.is_tag_name(EId::Path) {
+        return None;
+    }
+
+    match node.attributes().get_value(AId::D) {
+        Some(AttributeValue::Path(path)) => calc_bbox(node, path, keep_ids, animated),
+        _ => None,
+    }
+}
+
+// Returns a bounding box with a stroke of the element represented by the path,
+// if it can be merged.
+fn calc_bbox(
+    node: &Node,
+    path: &path::Path,
+    keep_ids: &[String],
+    animated: &AnimatedAttributes,
+) -> Option<Rect> {
+    if node.is_used() {
+        return None;
+    }
+
+    // Kept paths can be targeted from the outside, so they should stay separate.
+    if utils::is_kept_id(node, keep_ids) {
+        return None;
+    }
+
+    // Accessibility data describes a single element.
+    if utils::has_accessibility_attrs(node) {
+        return None;
+    }
+
+    // Event handlers will be triggered by all merged paths.
+    if utils::has_event_attrs(node) {
+        return None;
+    }
+
+    // An animation will be applied to all merged paths.
+    if animated.is_animated(node) {
+        return None;
+    }
+
+    // Links can depend on the element bounding box, so we skip them all.
+    if node.attributes().iter().any(|a| matches!(a.value, AttributeValue::FuncLink(_))) {
+        return None;
+    }
+
+    // Also check inherited ones. Markers are rendered at each subpath.
+    for aid in &[AId::Fill, AId::Stroke, AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd] {
+        if !is_valid_attr(node, *aid) {
+            return None;
+        }
+    }
+
+    Some(calc_path_bbox(path)?.expand(calc_stroke_offset(node)?))
+}
+
+fn is_same_attributes(node1: &Node, node2: &Node) -> bool {
+    let attrs1 = node1.attributes();
+    let attrs2 = node2.attributes();
+
+    // Shapes geometry is skipped too, since they are compared before the conversion.
+    let is_skipped = |aid: Option<AId>| {
+        match aid {
+            Some(aid) => matches!(aid, AId::D | AId::Id | AId::X | AId::Y | AId::Width | AId::Height
+                                       | AId::Rx | AId::Ry | AId::Cx | AId::Cy | AId::R
+                                       | AId::X1 | AId::Y1 | AId::X2 | AId::Y2 | AId::Points),
+            None => false,
+        }
+    };
+
+    let count = |attrs: &::svgdom::Attributes| {
+        attrs.iter().filter(|a| !is_skipped(a.id())).count()
+    };
+
+    if count(&attrs1) != count(&attrs2) {
+        return false;
+    }
+
+    for attr in attrs1.iter().filter(|a| !is_skipped(a.id())) {
+        match attrs2.get(attr.name.into_ref()) {
+            Some(attr2) if attr2.value == attr.value => {}
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+fn append_path(node1: &mut Node, node2: &Node) {
+    let path2 = match node2.attributes().get_value(AId::D) {
+        Some(AttributeValue::Path(path)) => path.clone(),
+        _ => return,
+    };
+
+    let mut attrs = node1.attributes_mut();
+    if let Some(&mut AttributeValue::Path(ref mut path1)) = attrs.get_value_mut(AId::D) {
+        // Relative MoveTo will be relative to the previous path end,
+        // so we should join absolute paths.
+        path1.conv_to_absolute();
+        let mut path2 = path2;
+        path2.conv_to_absolute();
+        path1.d.extend_from_slice(&path2.d);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| merge_paths(doc, &[]), $in_text, $out_text);
+        )
+    }
+
+    test!(merge_1,
+"<svg>
+    <path d='M 10 10 L 20 20' fill='#ff0000'/>
+    <path d='m 30 30 l 10 10' fill='#ff0000'/>
+    <path d='M 50 50 L 60 60' fill='#ff0000'/>
+</svg>",
+"<svg>
+    <path d='M 10 10 L 20 20 M 30 30 L 40 40 M 50 50 L 60 60' fill='#ff0000'/>
+</svg>
+");
+
+    // Overlapped paths, including a stroke.
+    test_eq!(keep_overlapped_1,
+"<svg>
+    <path d='M 10 10 L 20 20'/>
+    <path d='M 15 15 L 30 30'/>
+    <path d='M 40 40 L 50 50' stroke='#000000' stroke-width='10'/>
+    <path d='M 60 60 L 70 70' stroke='#000000' stroke-width='10'/>
+</svg>
+");
+
+    test_eq!(keep_different_attrs_1,
+"<svg>
+    <path d='M 10 10 L 20 20' fill='#ff0000'/>
+    <path d='M 30 30 L 40 40' fill='#00ff00'/>
+</svg>
+");
+
+    test_eq!(keep_links_1,
+"<svg>
+    <linearGradient id='lg1'/>
+    <path d='M 10 10 L 20 20' fill='url(#lg1)'/>
+    <path d='M 30 30 L 40 40' fill='url(#lg1)'/>
+    <path id='p1' d='M 50 50 L 60 60'/>
+    <path d='M 70 70 L 80 80'/>
+    <use xlink:href='#p1'/>
+</svg>
+");
+
+    test_eq!(keep_accessibility_1,
+"<svg>
+    <path d='M 10 10 L 20 20' aria-label='line'/>
+    <path d='M 30 30 L 40 40' aria-label='line'/>
+</svg>
+");
+
+    test_eq!(keep_animated_1,
+"<svg>
+    <path d='M 10 10 L 20 20'/>
+    <path d='M 30 30 L 40 40'>
+        <animate attributeName='opacity' to='0.5'/>
+    </path>
+</svg>
+");
+}
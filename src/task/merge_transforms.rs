@@ -0,0 +1,192 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+    Transform,
+};
+
+use task::short::{EId, AId};
+use task::utils::AnimatedAttributes;
+
+// Moves a group transform to the only child with a transform and removes the group.
+// Like '<g transform="A"><path transform="B"/></g>' -> '<path transform="A B"/>'.
+//
+// Unlike 'ungroup_groups', only groups without any other attributes are processed,
+// so it can be used when groups ungrouping is disabled.
+pub fn merge_transforms(doc: &Document) {
+    let animated = AnimatedAttributes::new(doc);
+
+    // Process nested groups first, so a chain of groups can be merged in one pass.
+    let groups: Vec<Node> = doc.descendants().filter(|n| n.is_tag_name(EId::G)).collect();
+    for mut g in groups.into_iter().rev() {
+        let ts = match can_merge(&g, &animated) {
+            Some(ts) => ts,
+            None => continue,
+        };
+
+        let mut child = g.first_child().unwrap();
+
+        let mut new_ts = ts;
+        if let Some(AttributeValue::Transform(child_ts)) = child.attributes().get_value(AId::Transform) {
+            new_ts.append(child_ts);
+        }
+        child.set_attribute((AId::Transform, new_ts));
+
+        child.detach();
+        g.insert_after(&child);
+        g.remove();
+    }
+}
+
+fn can_merge(g: &Node, animated: &AnimatedAttributes) -> Option<Transform> {
+    // The 'clipPath' doesn't support 'g' elements, so such groups must not be unwrapped.
+    if g.parent().map(|p| p.is_tag_name(EId::ClipPath)) == Some(true) {
+        return None;
+    }
+
+    let ts = {
+        let attrs = g.attributes();
+        if attrs.len() != 1 {
+            return None;
+        }
+
+        match attrs.get_value(AId::Transform) {
+            Some(&AttributeValue::Transform(ts)) => ts,
+            _ => return None,
+        }
+    };
+
+    if g.children().count() != 1 || animated.is_animated(g) {
+        return None;
+    }
+
+    let child = g.first_child().unwrap();
+
+    // Nested 'svg' elements don't support the 'transform' attribute.
+    if !child.is_svg_element() || child.is_tag_name(EId::Svg) {
+        return None;
+    }
+
+    match child.attributes().get_value(AId::Transform) {
+        Some(&AttributeValue::Transform(_)) => {}
+        _ => return None,
+    }
+
+    // A referenced element will be rendered with a new transform by the 'use' element.
+    if child.is_used() || animated.contains(&child, AId::Transform) {
+        return None;
+    }
+
+    Some(ts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, merge_transforms, $in_text, $out_text);
+        )
+    }
+
+    test!(skew_1,
+"<svg>
+    <g transform='skewX(45)'>
+        <rect transform='scale(2 3)'/>
+    </g>
+</svg>",
+"<svg>
+    <rect transform='matrix(2 0 3 3 0 0)'/>
+</svg>
+");
+
+    test!(nested_1,
+"<svg>
+    <g transform='scale(2 1)'>
+        <g transform='translate(10)'>
+            <rect transform='scale(2)'/>
+        </g>
+    </g>
+</svg>",
+"<svg>
+    <rect transform='matrix(4 0 0 2 20 0)'/>
+</svg>
+");
+
+    test!(skip_attributes_1,
+"<svg>
+    <g transform='scale(2)' opacity='0.5'>
+        <rect transform='scale(2)'/>
+    </g>
+    <g transform='scale(2)'>
+        <rect/>
+    </g>
+    <g transform='scale(2)'>
+        <rect transform='scale(2)'/>
+        <rect transform='scale(2)'/>
+    </g>
+</svg>",
+"<svg>
+    <g opacity='0.5' transform='scale(2)'>
+        <rect transform='scale(2)'/>
+    </g>
+    <g transform='scale(2)'>
+        <rect/>
+    </g>
+    <g transform='scale(2)'>
+        <rect transform='scale(2)'/>
+        <rect transform='scale(2)'/>
+    </g>
+</svg>
+");
+
+    test!(skip_used_1,
+"<svg>
+    <g transform='scale(2)'>
+        <rect id='rect1' transform='scale(2)'/>
+    </g>
+    <use xlink:href='#rect1'/>
+</svg>",
+"<svg>
+    <g transform='scale(2)'>
+        <rect id='rect1' transform='scale(2)'/>
+    </g>
+    <use xlink:href='#rect1'/>
+</svg>
+");
+
+    test!(skip_animated_1,
+"<svg>
+    <g transform='scale(2)'>
+        <rect id='rect1' transform='scale(2)'/>
+    </g>
+    <animateTransform xlink:href='#rect1' attributeName='transform' type='rotate' to='90'/>
+</svg>",
+"<svg>
+    <g transform='scale(2)'>
+        <rect id='rect1' transform='scale(2)'/>
+    </g>
+    <animateTransform attributeName='transform' to='90' type='rotate' xlink:href='#rect1'/>
+</svg>
+");
+}
@@ -0,0 +1,152 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Color,
+    Document,
+    WriteOptions,
+};
+
+// Color keywords that can be shorter than the '#RRGGBB' notation.
+static COLOR_NAMES: &[(u8, u8, u8, &str)] = &[
+    (0, 0, 0, "black"),
+    (0, 0, 128, "navy"),
+    (0, 0, 255, "blue"),
+    (0, 128, 0, "green"),
+    (0, 128, 128, "teal"),
+    (0, 255, 0, "lime"),
+    (0, 255, 255, "aqua"),
+    (75, 0, 130, "indigo"),
+    (128, 0, 0, "maroon"),
+    (128, 0, 128, "purple"),
+    (128, 128, 0, "olive"),
+    (128, 128, 128, "gray"),
+    (160, 82, 45, "sienna"),
+    (165, 42, 42, "brown"),
+    (192, 192, 192, "silver"),
+    (205, 133, 63, "peru"),
+    (210, 180, 140, "tan"),
+    (218, 112, 214, "orchid"),
+    (221, 160, 221, "plum"),
+    (238, 130, 238, "violet"),
+    (240, 230, 140, "khaki"),
+    (240, 255, 255, "azure"),
+    (245, 222, 179, "wheat"),
+    (245, 245, 220, "beige"),
+    (250, 128, 114, "salmon"),
+    (250, 240, 230, "linen"),
+    (255, 0, 0, "red"),
+    (255, 99, 71, "tomato"),
+    (255, 127, 80, "coral"),
+    (255, 165, 0, "orange"),
+    (255, 192, 203, "pink"),
+    (255, 215, 0, "gold"),
+    (255, 228, 196, "bisque"),
+    (255, 250, 250, "snow"),
+    (255, 255, 0, "yellow"),
+    (255, 255, 240, "ivory"),
+    (255, 255, 255, "white"),
+];
+
+// Replaces colors with keywords when they are shorter than the hex notation.
+//
+// Colors are already parsed, so 'rgb()' and keyword colors will be written
+// in the hex notation by default.
+pub fn minify_colors(doc: &Document, opt: &WriteOptions) {
+    // NOTE: Must be run at last, since colors are stored as strings after it.
+
+    for (_, mut node) in doc.descendants().svg() {
+        let mut attrs = node.attributes_mut();
+        for (_, attr) in attrs.iter_svg_mut() {
+            let name = match attr.value {
+                AttributeValue::Color(c) => find_name(c, opt.trim_hex_colors),
+                _ => None,
+            };
+
+            if let Some(name) = name {
+                attr.value = AttributeValue::String(name.to_string());
+            }
+        }
+    }
+}
+
+fn find_name(c: Color, trim_hex_colors: bool) -> Option<&'static str> {
+    let name = COLOR_NAMES.iter()
+        .find(|&&(r, g, b, _)| c.red == r && c.green == g && c.blue == b)
+        .map(|&(_, _, _, name)| name)?;
+
+    if name.len() < hex_len(c, trim_hex_colors) {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+fn hex_len(c: Color, trim_hex_colors: bool) -> usize {
+    let is_short = |n: u8| n >> 4 == n & 0x0f;
+
+    if trim_hex_colors && is_short(c.red) && is_short(c.green) && is_short(c.blue) {
+        4
+    } else {
+        7
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $trim:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                let mut opt = write_opt_for_tests!();
+                opt.trim_hex_colors = $trim;
+                minify_colors(&doc, &opt);
+                assert_eq_text!(doc.to_string_with_opt(&opt), $out_text);
+            }
+        )
+    }
+
+    test!(names_1, true,
+"<svg>
+    <rect fill='rgb(255, 0, 0)' stroke='#D2B48C'/>
+    <rect fill='#000000' stroke='#0000FF'/>
+    <stop stop-color='rgb(0,128,0)'/>
+</svg>",
+"<svg>
+    <rect fill='red' stroke='tan'/>
+    <rect fill='#000' stroke='#00f'/>
+    <stop stop-color='green'/>
+</svg>
+");
+
+    test!(names_2, false,
+"<svg>
+    <rect fill='#000000' stroke='#0000ff'/>
+    <rect fill='#ffff00' stroke='#123456'/>
+</svg>",
+"<svg>
+    <rect fill='black' stroke='blue'/>
+    <rect fill='yellow' stroke='#123456'/>
+</svg>
+");
+}
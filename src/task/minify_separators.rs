@@ -0,0 +1,168 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    WriteBuffer,
+    WriteOptions,
+};
+
+use task::short::AId;
+use task::exponent_notation::shorten_numbers;
+
+// Removes list separators where the next number can be parsed without them.
+// Like '10 -5' -> '10-5' and '.5 .5' -> '.5.5'.
+//
+// Only 'points' and transforms are processed, because the grammar of other lists,
+// like 'viewBox' and 'stroke-dasharray', requires separators.
+// Path data is already handled by the writer.
+pub fn minify_list_separators(doc: &Document, use_exponent: bool, opt: &WriteOptions) {
+    // NOTE: Must be run at last, since values are stored as strings after it.
+
+    for (_, mut node) in doc.descendants().svg() {
+        let mut attrs = node.attributes_mut();
+        for (aid, attr) in attrs.iter_svg_mut() {
+            match aid {
+                  AId::Points
+                | AId::Transform
+                | AId::GradientTransform
+                | AId::PatternTransform => {}
+                _ => continue,
+            }
+
+            match attr.value {
+                AttributeValue::NumberList(_) | AttributeValue::Transform(_) => {}
+                _ => continue,
+            }
+
+            let mut text = Vec::new();
+            attr.value.write_buf_opt(opt, &mut text);
+
+            // Values are stored as strings, so 'use-exponent-notation' will skip them.
+            if use_exponent {
+                let mut buf = Vec::new();
+                shorten_numbers(&text, &mut buf);
+                text = buf;
+            }
+
+            let mut buf = Vec::new();
+            remove_separators(&text, &mut buf);
+
+            // The writer always produces a valid UTF-8.
+            attr.value = AttributeValue::String(String::from_utf8(buf).unwrap());
+        }
+    }
+}
+
+pub fn remove_separators(text: &[u8], buf: &mut Vec<u8>) {
+    let is_sep = |c: u8| c == b' ' || c == b',';
+
+    // Whether the current number contains a dot, so the next one can start with a dot.
+    let mut has_dot = false;
+    let mut i = 0;
+    while i < text.len() {
+        let c = text[i];
+        if !is_sep(c) {
+            if c == b'.' {
+                has_dot = true;
+            } else if !c.is_ascii_digit() {
+                has_dot = false;
+            }
+
+            buf.push(c);
+            i += 1;
+            continue;
+        }
+
+        let prev = buf.last().cloned();
+        while i < text.len() && is_sep(text[i]) {
+            i += 1;
+        }
+        let next = text.get(i).cloned();
+
+        let is_after_num = prev.map(|c| c.is_ascii_digit() || c == b'.') == Some(true);
+        let can_skip = match next {
+            Some(b'-') => is_after_num,
+            Some(b'.') => is_after_num && has_dot,
+            _ => false,
+        };
+
+        if !can_skip {
+            buf.push(b' ');
+        }
+
+        // A separator always ends a number.
+        has_dot = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                let mut opt = write_opt_for_tests!();
+                opt.remove_leading_zero = true;
+                minify_list_separators(doc, false, &opt)
+            }, $in_text, $out_text);
+        )
+    }
+
+    test!(points_1,
+"<svg>
+    <polygon points='10 -5 .5 .5 1.5 .5 1.5 20 -.5 -30'/>
+</svg>",
+"<svg>
+    <polygon points='10-5 .5.5 1.5.5 1.5 20-.5-30'/>
+</svg>
+");
+
+    test!(transform_1,
+"<svg>
+    <rect transform='matrix(.5 -.5 .5 .5 10 -20)'/>
+    <linearGradient gradientTransform='translate(10 -20)'/>
+</svg>",
+"<svg>
+    <rect transform='matrix(.5-.5.5.5 10-20)'/>
+    <linearGradient gradientTransform='translate(10-20)'/>
+</svg>
+");
+
+    // Other lists are not affected.
+    test!(skip_1,
+"<svg viewBox='0 -10 100 100'>
+    <rect stroke-dasharray='5 -1'/>
+</svg>",
+"<svg viewBox='0 -10 100 100'>
+    <rect stroke-dasharray='5 -1'/>
+</svg>
+");
+
+    #[test]
+    fn exponent_1() {
+        let doc = Document::from_str("<svg><polygon points='1000 -.00001 .5 .5'/></svg>").unwrap();
+        let mut opt = write_opt_for_tests!();
+        opt.remove_leading_zero = true;
+        minify_list_separators(&doc, true, &opt);
+        assert_eq_text!(doc.to_string_with_opt(&opt), "<svg>\n    <polygon points='1e3-1e-5 .5.5'/>\n</svg>\n");
+    }
+}
@@ -16,32 +16,83 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
-pub use self::conv_shapes::convert_shapes_to_paths;
+pub use self::apply_clip_paths::apply_clip_paths;
+pub use self::collapse_attrs::collapse_attributes;
+pub use self::conv_cdata::convert_cdata;
+pub use self::conv_shapes::{
+    convert_shapes_to_paths,
+    convert_shapes_to_shorter_paths,
+};
+pub use self::conv_units::convert_units;
+pub use self::decompose_transforms::decompose_transforms;
+pub use self::exponent_notation::use_exponent_notation;
+pub use self::external_refs::process_external_refs;
 pub use self::final_fixes::*;
+pub use self::fit_viewbox::fit_viewbox;
 pub use self::fix_attrs::fix_invalid_attributes;
+pub use self::flatten_svg::flatten_nested_svg;
+pub use self::flatten_tspans::flatten_tspans;
 pub use self::group_by_style::group_by_style;
 pub use self::group_defs::group_defs;
 pub use self::join_style_attrs::join_style_attributes;
 pub use self::merge_gradients::merge_gradients;
+pub use self::merge_opacity::merge_opacity;
+pub use self::merge_paths::merge_paths;
+pub use self::merge_transforms::merge_transforms;
+pub use self::minify_colors::minify_colors;
+pub use self::minify_separators::minify_list_separators;
+pub use self::normalize_text::normalize_text;
+pub use self::optimize_symbols::optimize_symbols;
 pub use self::preclean_checks::preclean_checks;
 pub use self::regroup_gradient_stops::regroup_gradient_stops;
 pub use self::resolve_gradients::*;
+pub use self::resolve_css::{
+    find_styles,
+    resolve_css,
+};
+pub use self::resolve_css_vars::resolve_css_variables;
 pub use self::resolve_inherit::resolve_inherit;
+pub use self::resolve_patterns::resolve_patterns;
+pub use self::resolve_switch::resolve_switch;
 pub use self::resolve_use::resolve_use;
+pub use self::rm_conditional_attrs::remove_conditional_attributes;
 pub use self::rm_default_attrs::remove_default_attributes;
 pub use self::rm_default_transform::remove_default_transform;
 pub use self::rm_dupl_defs::*;
-pub use self::rm_elems::remove_element;
+pub use self::rm_editor_data::{
+    is_editor_prefix,
+    remove_editor_data,
+};
+pub use self::rm_elems::{
+    remove_animations,
+    remove_element,
+    remove_scripts,
+};
+pub use self::rm_empty_elems::remove_empty_elements;
 pub use self::rm_gradient_attrs::remove_gradient_attributes;
 pub use self::rm_invalid_stops::remove_invalid_stops;
 pub use self::rm_invisible_elems::remove_invisible_elements;
+pub use self::rm_metadata::remove_metadata;
 pub use self::rm_needless_attrs::remove_needless_attributes;
+pub use self::rm_nonsvg_attrs::remove_nonsvg_attributes;
+pub use self::rm_noop_masking::remove_noop_masking;
+pub use self::rm_outside_elems::remove_outside_elements;
 pub use self::rm_text_attrs::remove_text_attributes;
 pub use self::rm_unref_ids::remove_unreferenced_ids;
+pub use self::rm_unused_classes::remove_unused_classes;
 pub use self::rm_unused_coords::remove_unused_coordinates;
 pub use self::rm_unused_defs::remove_unused_defs;
+pub use self::rm_unused_ns::remove_unused_namespaces;
 pub use self::rm_version::remove_version;
 pub use self::round_numbers::round_numbers;
+pub use self::sanitize::sanitize;
+pub use self::scale_coords::scale_coordinates;
+pub use self::simplify_filters::simplify_filters;
+pub use self::sort_attrs::sort_attributes;
+pub use self::sort_defs::sort_defs;
+pub use self::styles_to_css::convert_styles_to_css;
+pub use self::translate_to_origin::translate_to_origin;
+pub use self::trim_classes::trim_classes;
 pub use self::trim_ids::trim_ids;
 pub use self::ungroup_defs::ungroup_defs;
 pub use self::ungroup_groups::ungroup_groups;
@@ -49,37 +100,76 @@ pub use self::ungroup_groups::ungroup_groups;
 #[macro_use]
 mod macros;
 
+mod apply_clip_paths;
+mod collapse_attrs;
+mod conv_cdata;
 mod conv_shapes;
+mod conv_units;
+mod decompose_transforms;
+mod exponent_notation;
+mod external_refs;
 mod final_fixes;
+mod fit_viewbox;
 mod fix_attrs;
+mod flatten_svg;
+mod flatten_tspans;
 mod group_by_style;
 mod group_defs;
 mod join_style_attrs;
 mod merge_gradients;
+mod merge_opacity;
+mod merge_paths;
+mod merge_transforms;
+mod minify_colors;
+mod minify_separators;
+mod normalize_text;
+mod optimize_symbols;
 mod preclean_checks;
 mod regroup_gradient_stops;
+mod resolve_css;
+mod resolve_css_vars;
 mod resolve_gradients;
 mod resolve_inherit;
+mod resolve_patterns;
+mod resolve_switch;
 mod resolve_use;
+mod rm_conditional_attrs;
 mod rm_default_attrs;
 mod rm_default_transform;
 mod rm_dupl_defs;
+mod rm_editor_data;
 mod rm_elems;
+mod rm_empty_elems;
 mod rm_gradient_attrs;
 mod rm_invalid_stops;
 mod rm_invisible_elems;
+mod rm_metadata;
 mod rm_needless_attrs;
+mod rm_nonsvg_attrs;
+mod rm_noop_masking;
+mod rm_outside_elems;
 mod rm_text_attrs;
 mod rm_unref_ids;
+mod rm_unused_classes;
 mod rm_unused_coords;
 mod rm_unused_defs;
+mod rm_unused_ns;
 mod rm_version;
 mod round_numbers;
+mod sanitize;
+mod scale_coords;
+mod simplify_filters;
+mod sort_attrs;
+mod sort_defs;
+mod styles_to_css;
+mod translate_to_origin;
+mod trim_classes;
 mod trim_ids;
 mod ungroup_defs;
 mod ungroup_groups;
 
 pub mod apply_transforms;
+pub mod images;
 pub mod paths;
 pub mod utils;
 
@@ -0,0 +1,232 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+    NodeType,
+};
+
+use task::short::{EId, AId};
+
+// The parser already processes whitespaces, but other tasks can remove elements
+// between text nodes, so we have to do it again.
+pub fn normalize_text(doc: &Document) {
+    remove_redundant_xmlspace(&doc.root(), false);
+
+    for node in doc.descendants().filter(|n| n.is_tag_name(EId::Text)) {
+        // Nested 'text' elements are not allowed.
+        if node.parents().any(|n| n.is_tag_name(EId::Text)) {
+            continue;
+        }
+
+        // CDATA is converted into text by an earlier task, when possible,
+        // so we simply skip such elements.
+        if node.descendants().any(|n| n.node_type() == NodeType::Cdata) {
+            continue;
+        }
+
+        normalize_whitespaces(&node);
+    }
+}
+
+// Removes 'xml:space' attributes with the same value as the inherited one.
+fn remove_redundant_xmlspace(parent: &Node, parent_preserve: bool) {
+    for mut node in parent.children().filter(|n| n.node_type() == NodeType::Element) {
+        let mut preserve = parent_preserve;
+
+        if let Some(v) = xmlspace(&node) {
+            if v == parent_preserve {
+                node.remove_attribute(AId::XmlSpace);
+            }
+            preserve = v;
+        }
+
+        remove_redundant_xmlspace(&node, preserve);
+    }
+}
+
+fn xmlspace(node: &Node) -> Option<bool> {
+    let attrs = node.attributes();
+    match attrs.get_value(AId::XmlSpace) {
+        Some(AttributeValue::String(s)) => Some(s == "preserve"),
+        _ => None,
+    }
+}
+
+fn is_preserved(text_node: &Node) -> bool {
+    text_node.parents().filter_map(|n| xmlspace(&n)).next().unwrap_or(false)
+}
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+fn normalize_whitespaces(text_elem: &Node) {
+    let nodes: Vec<Node> = text_elem.descendants()
+        .filter(|n| n.node_type() == NodeType::Text)
+        .collect();
+
+    // Whether the previous rendered character is a space.
+    // Leading spaces are not rendered, so we start with 'true'.
+    let mut prev_space = true;
+    let mut last_default: Option<Node> = None;
+    for mut node in nodes {
+        if is_preserved(&node) {
+            if let Some(c) = node.text().chars().last() {
+                prev_space = c == ' ';
+                last_default = None;
+            }
+            continue;
+        }
+
+        let new_text = {
+            let text = node.text();
+            let mut s = String::with_capacity(text.len());
+            for c in text.chars() {
+                if is_space(c) {
+                    if !prev_space {
+                        s.push(' ');
+                        prev_space = true;
+                    }
+                } else {
+                    s.push(c);
+                    prev_space = false;
+                }
+            }
+            s
+        };
+
+        if !new_text.is_empty() {
+            last_default = Some(node.clone());
+        }
+
+        node.set_text(&new_text);
+    }
+
+    // Trailing spaces are not rendered either.
+    if let Some(mut node) = last_default {
+        if node.text().ends_with(' ') {
+            node.text_mut().pop();
+        }
+    }
+
+    let mut empty: Vec<Node> = text_elem.descendants()
+        .filter(|n| n.node_type() == NodeType::Text && n.text().is_empty())
+        .collect();
+    for node in &mut empty {
+        node.remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| normalize_text(doc), $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    // Text nodes are already trimmed by the parser, so we modify them manually.
+    macro_rules! test_text {
+        ($name:ident, $in_text:expr, $texts:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                let nodes: Vec<Node> = doc.descendants()
+                    .filter(|n| n.node_type() == NodeType::Text)
+                    .collect();
+                for (mut node, text) in nodes.into_iter().zip($texts.iter()) {
+                    node.set_text(text);
+                }
+                normalize_text(doc)
+            }, $in_text, $out_text);
+        )
+    }
+
+    test!(rm_xmlspace_1,
+"<svg xml:space='default'>
+    <text xml:space='default'>Text</text>
+    <g xml:space='preserve'>
+        <text xml:space='preserve'>Text</text>
+        <text xml:space='default'>Text</text>
+    </g>
+</svg>",
+"<svg>
+    <text>Text</text>
+    <g xml:space='preserve'>
+        <text>Text</text>
+        <text xml:space='default'>Text</text>
+    </g>
+</svg>
+");
+
+    test_text!(collapse_1,
+"<svg>
+    <text>Text</text>
+</svg>",
+["  Some \n\t text  "],
+"<svg>
+    <text>Some text</text>
+</svg>
+");
+
+    test_text!(collapse_2,
+"<svg>
+    <text>Text<tspan>Text</tspan>Text</text>
+</svg>",
+["Some ", "  text ", "  end "],
+"<svg>
+    <text>Some <tspan>text </tspan>end</text>
+</svg>
+");
+
+    test_text!(rm_empty_1,
+"<svg>
+    <text>Text<tspan>Text</tspan></text>
+</svg>",
+["Text ", " "],
+"<svg>
+    <text>Text<tspan/></text>
+</svg>
+");
+
+    test_text!(keep_preserve_1,
+"<svg>
+    <text>Text<tspan xml:space='preserve'>Text</tspan>Text</text>
+</svg>",
+["Some ", "  text  ", "  end"],
+"<svg>
+    <text>Some <tspan xml:space='preserve'>  text  </tspan>end</text>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg>
+    <text>Some <tspan>text</tspan> end</text>
+</svg>
+");
+}
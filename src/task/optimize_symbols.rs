@@ -0,0 +1,556 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeType,
+    AttributeValue,
+    Document,
+    ElementType,
+    Length,
+    Name,
+    Node,
+    NodeType,
+    Transform,
+    ValueId,
+    WriteBuffer,
+    WriteOptions,
+};
+
+use task::short::{EId, AId, Unit};
+use task::utils::{self, AnimatedAttributes};
+use task::utils::bbox::{self, Rect};
+
+// 'use' attributes that are already processed or doesn't affect the linked element.
+static USE_ATTRIBUTES: &[AId] = &[
+    AId::X,
+    AId::Y,
+    AId::Width,
+    AId::Height,
+    AId::Transform,
+    AId::XlinkHref,
+];
+
+// Does both conversions between 'use' elements and the content:
+//
+// - a 'symbol' element that is used only once is replaced with a group
+// - identical elements are moved into 'defs' and replaced with 'use' elements,
+//   when the result is smaller
+//
+// Elements are compared ignoring their 'transform', because it can be set on the 'use'.
+// The shared element is referenced directly and not via 'symbol',
+// because 'symbol' will clip the content by its viewport.
+pub fn optimize_symbols(doc: &mut Document, keep_ids: &[String], opt: &WriteOptions) {
+    let animated = AnimatedAttributes::new(doc);
+    inline_symbols(doc, keep_ids, &animated);
+    factor_elements(doc, keep_ids, &animated, opt);
+}
+
+fn inline_symbols(doc: &mut Document, keep_ids: &[String], animated: &AnimatedAttributes) {
+    let nodes: Vec<Node> = doc.descendants().filter(|n| n.is_tag_name(EId::Use)).collect();
+    for node in nodes {
+        let symbol = match node.attributes().get_value(AId::XlinkHref) {
+            Some(AttributeValue::Link(link)) if link.is_tag_name(EId::Symbol) => link.clone(),
+            _ => continue,
+        };
+
+        if can_inline(&node, &symbol, keep_ids, animated) {
+            inline_symbol(doc, node, symbol);
+        }
+    }
+}
+
+fn can_inline(node: &Node, symbol: &Node, keep_ids: &[String], animated: &AnimatedAttributes)
+              -> bool {
+    if    node.is_used()
+       || utils::is_kept_id(node, keep_ids)
+       || utils::has_accessibility_attrs(node)
+       || utils::has_event_attrs(node)
+       || animated.is_animated(node) {
+        return false;
+    }
+
+    if    symbol.uses_count() != 1
+       || utils::is_kept_id(symbol, keep_ids)
+       || animated.is_animated(symbol) {
+        return false;
+    }
+
+    // 'viewBox' requires an additional transform and SVG 2 attributes are not supported.
+    if symbol.has_attributes(&[AId::ViewBox, AId::X, AId::Y, AId::Width, AId::Height,
+                               AId::RefX, AId::RefY]) {
+        return false;
+    }
+
+    // Only unitless coordinates are supported.
+    let attrs = node.attributes();
+    for aid in &[AId::X, AId::Y, AId::Width, AId::Height] {
+        match attrs.get_value(*aid) {
+            Some(&AttributeValue::Length(len)) if len.unit == Unit::None => {}
+            None => {}
+            _ => return false,
+        }
+    }
+
+    // Non-inheritable attributes, like 'opacity', will be applied twice after merging.
+    for (aid, attr) in symbol.attributes().iter_svg() {
+        if !attr.is_inheritable() && node.has_attribute(aid) && !USE_ATTRIBUTES.contains(&aid) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn inline_symbol(doc: &mut Document, mut node: Node, mut symbol: Node) {
+    let mut g = doc.create_element(EId::G);
+
+    let ts = {
+        let attrs = node.attributes();
+        let x = get_value!(attrs, Length, AId::X, Length::zero());
+        let y = get_value!(attrs, Length, AId::Y, Length::zero());
+
+        let mut ts = get_value!(attrs, Transform, AId::Transform, Transform::default());
+        ts.translate(x.num, y.num);
+        ts
+    };
+
+    if !ts.is_default() {
+        g.set_attribute((AId::Transform, ts));
+    }
+
+    for attr in node.attributes().iter() {
+        if !USE_ATTRIBUTES.iter().any(|aid| attr.has_id(*aid)) {
+            g.set_attribute(attr.clone());
+        }
+    }
+
+    // 'symbol' attributes have a higher priority.
+    for attr in symbol.attributes().iter() {
+        if !attr.has_id(AId::PreserveAspectRatio) && !attr.has_id(AId::Overflow) {
+            g.set_attribute(attr.clone());
+        }
+    }
+
+    node.insert_before(&g);
+
+    let children: Vec<Node> = symbol.children().collect();
+    for child in &children {
+        g.append(child);
+    }
+
+    // The bounding box is checked after moving, because the content
+    // can inherit a stroke from the 'use' element.
+    if !is_overflow_visible(&symbol) && !is_inside_viewport(&g, &node) {
+        for child in &children {
+            symbol.append(child);
+        }
+        g.remove();
+        return;
+    }
+
+    node.remove();
+    symbol.remove();
+}
+
+fn is_overflow_visible(node: &Node) -> bool {
+    match node.attributes().get_value(AId::Overflow) {
+        Some(&AttributeValue::PredefValue(v)) => v == ValueId::Visible || v == ValueId::Auto,
+        _ => false,
+    }
+}
+
+// Checks that the content will not be clipped by the 'symbol' viewport.
+fn is_inside_viewport(g: &Node, use_node: &Node) -> bool {
+    let (w, h) = match viewport_size(use_node) {
+        Some(v) => v,
+        None => return false,
+    };
+
+    // The bounding box doesn't include the 'g' transform, which contains the 'use' offset.
+    match bbox::calc_painted_bbox(g) {
+        Some(rect) => Rect { x1: 0.0, y1: 0.0, x2: w, y2: h }.contains(&rect),
+        None => false,
+    }
+}
+
+// The 'symbol' viewport size is defined by the 'use' element
+// or by the nearest 'svg' element by default.
+fn viewport_size(node: &Node) -> Option<(f64, f64)> {
+    let attrs = node.attributes();
+    let get_len = |aid: AId| {
+        match attrs.get_value(aid) {
+            Some(&AttributeValue::Length(len)) => Some(len.num),
+            _ => None,
+        }
+    };
+
+    let w = get_len(AId::Width);
+    let h = get_len(AId::Height);
+
+    if let (Some(w), Some(h)) = (w, h) {
+        return Some((w, h));
+    }
+
+    let svg = node.parents().find(|n| n.is_tag_name(EId::Svg))?;
+    let (vw, vh) = svg_size(&svg)?;
+    Some((w.unwrap_or(vw), h.unwrap_or(vh)))
+}
+
+fn svg_size(svg: &Node) -> Option<(f64, f64)> {
+    let attrs = svg.attributes();
+    if let Some(AttributeValue::NumberList(vb)) = attrs.get_value(AId::ViewBox) {
+        if vb.len() == 4 {
+            return Some((vb[2], vb[3]));
+        }
+    }
+
+    match (attrs.get_value(AId::Width), attrs.get_value(AId::Height)) {
+        (Some(&AttributeValue::Length(w)), Some(&AttributeValue::Length(h)))
+            if is_px(w) && is_px(h) => Some((w.num, h.num)),
+        _ => None,
+    }
+}
+
+fn is_px(len: Length) -> bool {
+    len.unit == Unit::None || len.unit == Unit::Px
+}
+
+fn factor_elements(doc: &mut Document, keep_ids: &[String], animated: &AnimatedAttributes,
+                   opt: &WriteOptions) {
+    // Elements are grouped by the size first, so we don't have to compare all of them.
+    let mut groups: Vec<(usize, Vec<Node>)> = Vec::new();
+    for node in doc.descendants().filter(|n| can_factor(n, keep_ids, animated)) {
+        let size = element_size(&node, opt, true);
+        let idx = groups.iter().position(|&(s, ref g)| s == size && is_same_element(&g[0], &node));
+        match idx {
+            Some(idx) => groups[idx].1.push(node.clone()),
+            None => groups.push((size, vec![node.clone()])),
+        }
+    }
+
+    let mut groups: Vec<Vec<Node>> = groups.into_iter()
+                                           .map(|(_, g)| g)
+                                           .filter(|g| g.len() > 1)
+                                           .collect();
+
+    // Process bigger elements first, because they can contain smaller ones.
+    groups.sort_by_key(|g| ::std::cmp::Reverse(element_size(&g[0], opt, true) * g.len()));
+
+    for mut group in groups {
+        // Elements can be already moved or removed with a parent element.
+        let svg = doc.svg_element().unwrap();
+        group.retain(|n| n.parents().any(|p| p == svg) && n.parents().all(is_valid_parent));
+        if group.len() < 2 {
+            continue;
+        }
+
+        let id = gen_id(doc);
+        let has_defs = doc.descendants().any(|n| n.is_tag_name(EId::Defs));
+        if !is_smaller(&group, &id, has_defs, opt) {
+            continue;
+        }
+
+        factor_group(doc, group, id);
+    }
+}
+
+fn can_factor(node: &Node, keep_ids: &[String], animated: &AnimatedAttributes) -> bool {
+    if !(node.is_shape() || node.is_tag_name(EId::G) || node.is_tag_name(EId::Text)) {
+        return false;
+    }
+
+    if !node.parents().all(is_valid_parent) {
+        return false;
+    }
+
+    node.descendants().all(|n| {
+           !n.is_used()
+        && !utils::is_kept_id(&n, keep_ids)
+        && !utils::has_accessibility_attrs(&n)
+        && !utils::has_event_attrs(&n)
+        && !animated.is_animated(&n)
+        && !n.tag_id().is_some_and(utils::animation::is_animation)
+    })
+}
+
+// Elements inside 'defs', 'clipPath', 'switch', etc. are not supported.
+fn is_valid_parent(node: Node) -> bool {
+    matches!(node.tag_id(), Some(EId::G) | Some(EId::A) | Some(EId::Svg))
+}
+
+fn is_same_element(node1: &Node, node2: &Node) -> bool {
+    if node1.tag_id() != node2.tag_id() {
+        return false;
+    }
+
+    let attrs1 = node1.attributes();
+    let attrs2 = node2.attributes();
+    let count = |attrs: &::svgdom::Attributes| {
+        attrs.iter().filter(|a| !a.has_id(AId::Transform)).count()
+    };
+
+       count(&attrs1) == count(&attrs2)
+    && attrs1.iter()
+             .filter(|a| !a.has_id(AId::Transform))
+             .all(|a1| attrs2.iter().any(|a2| a1 == a2))
+    && utils::is_equal_content(node1, node2)
+}
+
+// Compares the size of elements with the size of the shared element and 'use' elements.
+fn is_smaller(group: &[Node], id: &str, has_defs: bool, opt: &WriteOptions) -> bool {
+    let old_len: usize = group.iter().map(|n| element_size(n, opt, false)).sum();
+
+    let mut new_len = element_size(&group[0], opt, true) + " id=\"\"".len() + id.len();
+    if group[0].has_id() {
+        new_len -= " id=\"\"".len() + group[0].id().len();
+    }
+
+    if !has_defs {
+        new_len += "<defs></defs>".len();
+    }
+
+    for node in group {
+        new_len += "<use xlink:href=\"#\"/>".len() + id.len();
+        if let Some(attr) = node.attributes().get(AId::Transform) {
+            new_len += attr_size(attr, opt);
+        }
+    }
+
+    new_len < old_len
+}
+
+fn factor_group(doc: &mut Document, group: Vec<Node>, id: String) {
+    let mut shared = group[0].clone();
+    shared.set_id(id);
+
+    for node in &group {
+        let mut use_node = doc.create_element(EId::Use);
+        if let Some(attr) = node.attributes().get(AId::Transform) {
+            use_node.set_attribute(attr.clone());
+        }
+        use_node.set_attribute((AId::XlinkHref, shared.clone()));
+        node.clone().insert_before(&use_node);
+    }
+
+    for mut node in group.into_iter().skip(1) {
+        node.remove();
+    }
+
+    let mut defs = match doc.descendants().find(|n| n.is_tag_name(EId::Defs)) {
+        Some(defs) => defs,
+        None => {
+            let defs = doc.create_element(EId::Defs);
+            doc.svg_element().unwrap().prepend(&defs);
+            defs
+        }
+    };
+
+    shared.remove_attribute(AId::Transform);
+    shared.detach();
+    defs.append(&shared);
+}
+
+// Calculates the size of the element as it will be written.
+fn element_size(node: &Node, opt: &WriteOptions, skip_transform: bool) -> usize {
+    let mut len = 0;
+
+    for child in node.children() {
+        len += match child.node_type() {
+            NodeType::Element => element_size(&child, opt, false),
+            _ => child.text().len(),
+        };
+    }
+
+    let tag_len = match *node.tag_name().unwrap() {
+        Name::Id(id) => id.name().len(),
+        Name::Name(ref name) => name.len(),
+    };
+
+    // '<tag/>' or '<tag></tag>'.
+    len += if node.has_children() { tag_len * 2 + 5 } else { tag_len + 3 };
+
+    if node.has_id() {
+        len += " id=\"\"".len() + node.id().len();
+    }
+
+    for attr in node.attributes().iter().filter(|a| a.visible) {
+        if skip_transform && attr.has_id(AId::Transform) {
+            continue;
+        }
+
+        len += attr_size(attr, opt);
+    }
+
+    len
+}
+
+// ' name="value"'
+fn attr_size(attr: &::svgdom::Attribute, opt: &WriteOptions) -> usize {
+    let mut buf = Vec::new();
+    attr.write_buf_opt(opt, &mut buf);
+    buf.len() + 1
+}
+
+fn gen_id(doc: &Document) -> String {
+    let mut n = 1;
+    loop {
+        let id = format!("use{}", n);
+        if !doc.descendants().any(|n| *n.id() == id) {
+            return id;
+        }
+
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |mut doc: &mut Document| {
+                optimize_symbols(&mut doc, &["keep".to_string()], &write_opt_for_tests!())
+            }, $in_text, $out_text);
+        )
+    }
+
+    test!(inline_1,
+"<svg viewBox='0 0 100 100'>
+    <defs>
+        <symbol id='s1' fill='#ff0000'>
+            <rect height='10' width='10'/>
+        </symbol>
+    </defs>
+    <use x='10' xlink:href='#s1'/>
+</svg>",
+"<svg viewBox='0 0 100 100'>
+    <defs/>
+    <g fill='#ff0000' transform='translate(10)'>
+        <rect height='10' width='10'/>
+    </g>
+</svg>
+");
+
+    // Content outside the viewport is clipped by the 'symbol'.
+    test_eq!(keep_inline_1,
+"<svg viewBox='0 0 100 100'>
+    <symbol id='s1'>
+        <rect height='10' width='10' x='-5'/>
+    </symbol>
+    <use xlink:href='#s1'/>
+</svg>
+");
+
+    test!(inline_2,
+"<svg viewBox='0 0 100 100'>
+    <symbol id='s1' overflow='visible'>
+        <rect height='10' width='10' x='-5'/>
+    </symbol>
+    <use xlink:href='#s1'/>
+</svg>",
+"<svg viewBox='0 0 100 100'>
+    <g>
+        <rect height='10' width='10' x='-5'/>
+    </g>
+</svg>
+");
+
+    test_eq!(keep_inline_2,
+"<svg viewBox='0 0 100 100'>
+    <symbol id='s1' viewBox='0 0 10 10'>
+        <rect height='10' width='10'/>
+    </symbol>
+    <use xlink:href='#s1'/>
+</svg>
+");
+
+    test_eq!(keep_inline_3,
+"<svg viewBox='0 0 100 100'>
+    <symbol id='s1'>
+        <rect height='10' width='10'/>
+    </symbol>
+    <use xlink:href='#s1'/>
+    <use x='20' xlink:href='#s1'/>
+</svg>
+");
+
+    test!(factor_1,
+"<svg>
+    <path d='M 10 20 L 30 40 L 50 60 L 70 80 L 90 100 Z' fill='#ff0000' stroke='#000000'/>
+    <path d='M 10 20 L 30 40 L 50 60 L 70 80 L 90 100 Z' fill='#ff0000' stroke='#000000' transform='translate(10 20)'/>
+    <path d='M 10 20 L 30 40 L 50 60 L 70 80 L 90 100 Z' fill='#ff0000' stroke='#000000' transform='translate(30 40)'/>
+</svg>",
+"<svg>
+    <defs>
+        <path id='use1' d='M 10 20 L 30 40 L 50 60 L 70 80 L 90 100 Z' fill='#ff0000' stroke='#000000'/>
+    </defs>
+    <use xlink:href='#use1'/>
+    <use transform='translate(10 20)' xlink:href='#use1'/>
+    <use transform='translate(30 40)' xlink:href='#use1'/>
+</svg>
+");
+
+    test!(factor_2,
+"<svg>
+    <g fill='#ff0000'>
+        <rect height='10' width='20' x='5' y='5'/>
+        <circle cx='30' cy='30' r='10'/>
+    </g>
+    <g fill='#ff0000' transform='scale(2)'>
+        <rect height='10' width='20' x='5' y='5'/>
+        <circle cx='30' cy='30' r='10'/>
+    </g>
+</svg>",
+"<svg>
+    <defs>
+        <g id='use1' fill='#ff0000'>
+            <rect height='10' width='20' x='5' y='5'/>
+            <circle cx='30' cy='30' r='10'/>
+        </g>
+    </defs>
+    <use xlink:href='#use1'/>
+    <use transform='scale(2)' xlink:href='#use1'/>
+</svg>
+");
+
+    // 'use' elements are bigger.
+    test_eq!(keep_factor_1,
+"<svg>
+    <rect height='10' width='10'/>
+    <rect height='10' width='10'/>
+</svg>
+");
+
+    test_eq!(keep_factor_2,
+"<svg>
+    <path id='keep' d='M 10 20 L 30 40 L 50 60 L 70 80 L 90 100 Z' fill='#ff0000' stroke='#000000'/>
+    <path d='M 10 20 L 30 40 L 50 60 L 70 80 L 90 100 Z' fill='#ff0000' stroke='#000000'/>
+</svg>
+");
+
+    test_eq!(keep_factor_3,
+"<svg>
+    <clipPath id='clip1'>
+        <path d='M 10 20 L 30 40 L 50 60 L 70 80 L 90 100 Z' fill='#ff0000' stroke='#000000'/>
+        <path d='M 10 20 L 30 40 L 50 60 L 70 80 L 90 100 Z' fill='#ff0000' stroke='#000000'/>
+    </clipPath>
+</svg>
+");
+}
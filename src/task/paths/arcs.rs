@@ -0,0 +1,349 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::f64::consts::PI;
+
+use svgdom::{
+    FuzzyEq,
+    WriteOptions,
+};
+use svgdom::path::{
+    Path,
+    Segment,
+    SegmentData,
+};
+
+use super::conv_segments;
+use super::utils;
+
+// Cubic curves can approximate only arcs up to 90 degrees without a visible error.
+const MAX_CURVE_ANGLE: f64 = PI / 2.0 + 1e-6;
+
+// Allowed difference between an actual curve and an ideal arc approximation,
+// relative to the arc radius.
+const TOLERANCE: f64 = 1e-3;
+
+struct CircleArc {
+    cx: f64,
+    cy: f64,
+    r: f64,
+    // Signed angle.
+    angle: f64,
+}
+
+// Replaces sequences of CurveTo segments that approximate a circular arc
+// with a single EllipticalArc, when it's shorter.
+//
+// All segments must be absolute.
+pub fn convert_curves_to_arcs(path: &mut Path, precision: u8) {
+    if path.d.is_empty() {
+        return;
+    }
+
+    // Segments are copied into a new list instead of being replaced in place,
+    // because replacing is quadratic on paths with many segments.
+    // An arc ends at the same point as replaced curves, so we can still resolve
+    // the previous point using the original segments.
+    let mut d = Vec::with_capacity(path.d.len());
+    d.push(path.d[0]);
+
+    let mut i = 1;
+    while i < path.d.len() {
+        let (px, py) = utils::resolve_xy(&path.d, i - 1);
+        let first = match curve_to_arc(px, py, &path.d[i]) {
+            Some(arc) => arc,
+            None => {
+                d.push(path.d[i]);
+                i += 1;
+                continue;
+            }
+        };
+
+        // Collect following curves that are parts of the same circle.
+        let mut total_angle = first.angle;
+        let mut end = i + 1;
+        while end < path.d.len() {
+            let (px, py) = utils::resolve_xy(&path.d, end - 1);
+            let arc = match curve_to_arc(px, py, &path.d[end]) {
+                Some(arc) => arc,
+                None => break,
+            };
+
+            let tol = first.r * TOLERANCE;
+            let is_same_circle =    (arc.cx - first.cx).abs() <= tol
+                                 && (arc.cy - first.cy).abs() <= tol
+                                 && (arc.r - first.r).abs() <= tol
+                                 && arc.angle.signum() == first.angle.signum();
+
+            // A single arc can't be a full circle.
+            if !is_same_circle || (total_angle + arc.angle).abs() >= 2.0 * PI - 1e-6 {
+                break;
+            }
+
+            total_angle += arc.angle;
+            end += 1;
+        }
+
+        let (x, y) = utils::resolve_xy(&path.d, end - 1);
+        let r = round(first.r, precision);
+        let arc_seg = Segment::new_arc_to(r, r, 0.0, total_angle.abs() > PI, total_angle > 0.0,
+                                          x, y);
+
+        if is_shorter(&[arc_seg], &path.d[i..end], px, py) {
+            d.push(arc_seg);
+            i = end;
+        } else {
+            d.push(path.d[i]);
+            i += 1;
+        }
+    }
+
+    path.d = d;
+}
+
+// Replaces EllipticalArc segments with CurveTo, when it's shorter.
+//
+// All segments must be absolute.
+pub fn convert_arcs_to_curves(path: &mut Path, precision: u8) {
+    for i in 1..path.d.len() {
+        let (px, py) = utils::resolve_xy(&path.d, i - 1);
+        let curve = match arc_to_curve(px, py, &path.d[i], precision) {
+            Some(curve) => curve,
+            None => continue,
+        };
+
+        if is_shorter(&[curve], &path.d[i..i + 1], px, py) {
+            path.d[i] = curve;
+        }
+    }
+}
+
+fn curve_to_arc(px: f64, py: f64, seg: &Segment) -> Option<CircleArc> {
+    let (x1, y1, x2, y2, x, y) = match *seg.data() {
+        SegmentData::CurveTo { x1, y1, x2, y2, x, y } => (x1, y1, x2, y2, x, y),
+        _ => return None,
+    };
+
+    // Tangents at the start and at the end.
+    let (tx0, ty0) = (x1 - px, y1 - py);
+    let (tx1, ty1) = (x - x2, y - y2);
+
+    // The center is an intersection of the normals.
+    let cross = ty0 * tx1 - tx0 * ty1;
+    if cross.is_fuzzy_zero() {
+        return None;
+    }
+
+    let t = ((x - px) * tx1 + (y - py) * ty1) / cross;
+    let cx = px + ty0 * t;
+    let cy = py - tx0 * t;
+
+    let r = (px - cx).hypot(py - cy);
+    let r_end = (x - cx).hypot(y - cy);
+    if r.is_fuzzy_zero() || (r - r_end).abs() > r * TOLERANCE {
+        return None;
+    }
+
+    // The direction is defined by the start tangent.
+    let is_positive = (px - cx) * ty0 - (py - cy) * tx0 > 0.0;
+    let mut angle = (y - cy).atan2(x - cx) - (py - cy).atan2(px - cx);
+    if is_positive && angle < 0.0 {
+        angle += 2.0 * PI;
+    } else if !is_positive && angle > 0.0 {
+        angle -= 2.0 * PI;
+    }
+
+    if angle.abs() > MAX_CURVE_ANGLE {
+        return None;
+    }
+
+    // Control points should be at the ideal distance.
+    let k = 4.0 / 3.0 * (angle.abs() / 4.0).tan() * r;
+    let tol = r * TOLERANCE;
+    if    (tx0.hypot(ty0) - k).abs() > tol
+       || (tx1.hypot(ty1) - k).abs() > tol {
+        return None;
+    }
+
+    Some(CircleArc { cx, cy, r, angle })
+}
+
+fn arc_to_curve(px: f64, py: f64, seg: &Segment, precision: u8) -> Option<Segment> {
+    let (rx, ry, x_axis_rotation, large_arc, sweep, x, y) = match *seg.data() {
+        SegmentData::EllipticalArc { rx, ry, x_axis_rotation, large_arc, sweep, x, y } => {
+            (rx.abs(), ry.abs(), x_axis_rotation, large_arc, sweep, x, y)
+        }
+        _ => return None,
+    };
+
+    if rx.is_fuzzy_zero() || ry.is_fuzzy_zero() || (px.fuzzy_eq(&x) && py.fuzzy_eq(&y)) {
+        return None;
+    }
+
+    // Conversion from endpoint to center parameterization.
+    // See: https://www.w3.org/TR/SVG11/implnote.html#ArcConversionEndpointToCenter
+    let (sin_phi, cos_phi) = x_axis_rotation.to_radians().sin_cos();
+    let dx2 = (px - x) / 2.0;
+    let dy2 = (py - y) / 2.0;
+    let x1p =  cos_phi * dx2 + sin_phi * dy2;
+    let y1p = -sin_phi * dx2 + cos_phi * dy2;
+
+    let mut rx = rx;
+    let mut ry = ry;
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+    let den = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let mut coef = (num / den).max(0.0).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+
+    let cxp =  coef * rx * y1p / ry;
+    let cyp = -coef * ry * x1p / rx;
+
+    let cx = cos_phi * cxp - sin_phi * cyp + (px + x) / 2.0;
+    let cy = sin_phi * cxp + cos_phi * cyp + (py + y) / 2.0;
+
+    let theta1 = ((y1p - cyp) / ry).atan2((x1p - cxp) / rx);
+    let theta2 = ((-y1p - cyp) / ry).atan2((-x1p - cxp) / rx);
+    let mut delta = theta2 - theta1;
+    if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    } else if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    }
+
+    if delta.abs() > MAX_CURVE_ANGLE {
+        return None;
+    }
+
+    let k = 4.0 / 3.0 * (delta / 4.0).tan();
+
+    // Derivative of the ellipse point at the specified angle.
+    let derivative = |theta: f64| {
+        let (sin_t, cos_t) = theta.sin_cos();
+        let dx = -rx * sin_t;
+        let dy =  ry * cos_t;
+        (cos_phi * dx - sin_phi * dy, sin_phi * dx + cos_phi * dy)
+    };
+
+    let (d1x, d1y) = derivative(theta1);
+    let (d2x, d2y) = derivative(theta2);
+
+    // Make sure that the center was calculated correctly.
+    debug_assert!(cx.is_finite() && cy.is_finite());
+
+    Some(Segment::new_curve_to(
+        round(px + k * d1x, precision), round(py + k * d1y, precision),
+        round(x  - k * d2x, precision), round(y  - k * d2y, precision),
+        x, y,
+    ))
+}
+
+// Checks that new segments are shorter than old ones in both,
+// relative and absolute, notations.
+fn is_shorter(new: &[Segment], old: &[Segment], px: f64, py: f64) -> bool {
+    let opt = WriteOptions {
+        remove_leading_zero: true,
+        .. WriteOptions::default()
+    };
+
+    let mut buf = Vec::new();
+    let mut calc_len = |segments: &[Segment]| {
+        let mut abs_len = 0;
+        let mut rel_len = 0;
+        let mut prev_abs = None;
+        let mut prev_rel = None;
+        let mut prev_x = px;
+        let mut prev_y = py;
+        for seg in segments {
+            let mut rel_seg = *seg;
+            conv_segments::to_relative(&mut rel_seg, prev_x, prev_y);
+
+            abs_len += conv_segments::estimate_len(seg, prev_abs, &opt, &mut buf);
+            rel_len += conv_segments::estimate_len(&rel_seg, prev_rel, &opt, &mut buf);
+
+            prev_abs = Some(*seg);
+            prev_rel = Some(rel_seg);
+            prev_x = seg.x().unwrap();
+            prev_y = seg.y().unwrap();
+        }
+
+        (abs_len, rel_len)
+    };
+
+    let (new_abs, new_rel) = calc_len(new);
+    let (old_abs, old_rel) = calc_len(old);
+
+    new_abs < old_abs && new_rel < old_rel
+}
+
+fn round(n: f64, precision: u8) -> f64 {
+    let k = 10f64.powi(precision as i32);
+    (n * k).round() / k
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use svgdom::path::Path;
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut path = Path::from_str($in_text).unwrap();
+                path.conv_to_absolute();
+                convert_curves_to_arcs(&mut path, 6);
+                convert_arcs_to_curves(&mut path, 6);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    test!(curves_to_arc_1,
+        "M 110 100 C 110 105.522847 105.522847 110 100 110 C 94.477153 110 90 105.522847 90 100",
+        "M 110 100 A 10 10 0 0 1 90 100");
+
+    // Full circle should be split.
+    test!(curves_to_arc_2,
+        "M 110 100 C 110 105.522847 105.522847 110 100 110 C 94.477153 110 90 105.522847 90 100 \
+         C 90 94.477153 94.477153 90 100 90 C 105.522847 90 110 94.477153 110 100 Z",
+        "M 110 100 A 10 10 0 1 1 100 90 A 10 10 0 0 1 110 100 Z");
+
+    // Curve is not a circle arc.
+    test!(keep_curve_1,
+        "M 110 100 C 110 110 105 110 100 110",
+        "M 110 100 C 110 110 105 110 100 110");
+
+    test!(keep_arc_1,
+        "M 10 20 A 10 10 0 0 1 30 20",
+        "M 10 20 A 10 10 0 0 1 30 20");
+
+    // Curve is shorter than an arc with long numbers.
+    test!(arc_to_curve_1,
+        "M 0 0 A 7.0710678119 7.0710678119 12.3456789012 0 1 10 0",
+        "M 0 0 C 2.761424 -2.761424 7.238576 -2.761424 10 0");
+}
@@ -21,6 +21,8 @@ use std::cmp::Ordering;
 use svgdom::{
     FuzzyEq,
     FuzzyOrd,
+    WriteBuffer,
+    WriteOptions,
 };
 use svgdom::path::{
     Command,
@@ -49,24 +51,22 @@ pub fn convert_segments(path: &mut Path) {
 pub fn fix_m(path: &mut Path) {
     let mut mx = 0.0;
     let mut my = 0.0;
-    let mut i = 1;
-    while i < path.d.len() {
-        let prev_cmd = path.d[i - 1].cmd();
-        let curr_cmd = path.d[i].cmd();
+    let mut d = Vec::with_capacity(path.d.len());
+    for seg in &path.d {
+        let is_after_z = d.last().map(|s: &Segment| s.cmd()) == Some(Command::ClosePath);
+        if is_after_z && seg.cmd() != Command::MoveTo {
+            d.push(Segment::new_move_to(mx, my));
+        }
 
-        if let &SegmentData::MoveTo { x, y } = path.d[i - 1].data() {
+        if let SegmentData::MoveTo { x, y } = *seg.data() {
             mx = x;
             my = y;
         }
 
-        if prev_cmd == Command::ClosePath {
-            if curr_cmd != Command::MoveTo {
-                path.d.insert(i, Segment::new_move_to(mx, my));
-            }
-        }
-
-        i += 1;
+        d.push(*seg);
     }
+
+    path.d = d;
 }
 
 // Convert HorizontalLineTo and VerticalLineTo segments into LineTo
@@ -133,11 +133,314 @@ pub fn convert_l_to_hv(path: &mut Path) {
     }
 }
 
+// Merges subsequent HorizontalLineTo or VerticalLineTo segments with the same direction.
+//
+// Segments with an opposite direction are preserved, because they are visible with a stroke.
+//
+// All segments must be absolute.
+pub fn merge_hv(path: &mut Path) {
+    let mut prev_x = 0.0;
+    let mut prev_y = 0.0;
+    let mut prev_mx = 0.0;
+    let mut prev_my = 0.0;
+
+    // Start point of the previous segment.
+    let mut start_x = 0.0;
+    let mut start_y = 0.0;
+
+    // Merged segments are removed by moving the rest to the front of the list.
+    let mut len = 0;
+    for i in 0..path.d.len() {
+        let seg = path.d[i];
+        debug_assert!(seg.absolute);
+
+        if len != 0 {
+            let prev_seg = path.d[len - 1];
+            let is_same_dir = |a: f64, b: f64, c: f64| (b - a) * (c - b) > 0.0;
+
+            let can_merge = match (*prev_seg.data(), *seg.data()) {
+                (SegmentData::HorizontalLineTo { x: x1 }, SegmentData::HorizontalLineTo { x }) => {
+                    is_same_dir(start_x, x1, x)
+                }
+                (SegmentData::VerticalLineTo { y: y1 }, SegmentData::VerticalLineTo { y }) => {
+                    is_same_dir(start_y, y1, y)
+                }
+                _ => false,
+            };
+
+            if can_merge {
+                // The previous segment now ends at the current segment end,
+                // so the start point is unchanged.
+                path.d[len - 1] = seg;
+                match *seg.data() {
+                    SegmentData::HorizontalLineTo { x } => prev_x = x,
+                    SegmentData::VerticalLineTo { y } => prev_y = y,
+                    _ => unreachable!(),
+                }
+                continue;
+            }
+        }
+
+        start_x = prev_x;
+        start_y = prev_y;
+
+        match *seg.data() {
+            SegmentData::HorizontalLineTo { x } => prev_x = x,
+            SegmentData::VerticalLineTo { y } => prev_y = y,
+            SegmentData::ClosePath => {
+                prev_x = prev_mx;
+                prev_y = prev_my;
+            }
+            _ => {
+                prev_x = seg.x().unwrap();
+                prev_y = seg.y().unwrap();
+            }
+        }
+
+        if seg.cmd() == Command::MoveTo {
+            prev_mx = prev_x;
+            prev_my = prev_y;
+        }
+
+        path.d[len] = seg;
+        len += 1;
+    }
+    path.d.truncate(len);
+}
+
+// Converts each segment into a relative or an absolute one, depending on which one is shorter.
+//
+// All segments must be absolute.
+pub fn convert_to_shortest(path: &mut Path) {
+    let opt = WriteOptions {
+        remove_leading_zero: true,
+        .. WriteOptions::default()
+    };
+
+    // Current point and the start of the current subpath.
+    let mut prev_x = 0.0;
+    let mut prev_y = 0.0;
+    let mut prev_mx = 0.0;
+    let mut prev_my = 0.0;
+
+    let mut prev_seg: Option<Segment> = None;
+    let mut buf = Vec::new();
+
+    for seg in &mut path.d {
+        debug_assert!(seg.absolute);
+
+        let (x, y) = match *seg.data() {
+            SegmentData::HorizontalLineTo { x } => (x, prev_y),
+            SegmentData::VerticalLineTo { y } => (prev_x, y),
+            SegmentData::ClosePath => (prev_mx, prev_my),
+            _ => (seg.x().unwrap(), seg.y().unwrap()),
+        };
+
+        if seg.cmd() == Command::ClosePath {
+            // 'z' and 'Z' are equal.
+            seg.absolute = false;
+        } else {
+            let mut rel_seg = *seg;
+            to_relative(&mut rel_seg, prev_x, prev_y);
+
+            let abs_len = estimate_len(seg, prev_seg, &opt, &mut buf);
+            let rel_len = estimate_len(&rel_seg, prev_seg, &opt, &mut buf);
+
+            // Prefer relative segments when lengths are equal.
+            if rel_len <= abs_len {
+                *seg = rel_seg;
+            }
+        }
+
+        if seg.cmd() == Command::MoveTo {
+            prev_mx = x;
+            prev_my = y;
+        }
+
+        prev_x = x;
+        prev_y = y;
+        prev_seg = Some(*seg);
+    }
+}
+
+pub fn to_relative(seg: &mut Segment, dx: f64, dy: f64) {
+    seg.absolute = false;
+
+    match *seg.data_mut() {
+          SegmentData::MoveTo { ref mut x, ref mut y }
+        | SegmentData::LineTo { ref mut x, ref mut y }
+        | SegmentData::SmoothQuadratic { ref mut x, ref mut y }
+        | SegmentData::EllipticalArc { ref mut x, ref mut y, .. } => {
+            *x -= dx;
+            *y -= dy;
+        }
+        SegmentData::HorizontalLineTo { ref mut x } => {
+            *x -= dx;
+        }
+        SegmentData::VerticalLineTo { ref mut y } => {
+            *y -= dy;
+        }
+        SegmentData::CurveTo { ref mut x1, ref mut y1, ref mut x2, ref mut y2,
+                               ref mut x, ref mut y } => {
+            *x1 -= dx;
+            *y1 -= dy;
+            *x2 -= dx;
+            *y2 -= dy;
+            *x -= dx;
+            *y -= dy;
+        }
+        SegmentData::SmoothCurveTo { ref mut x2, ref mut y2, ref mut x, ref mut y } => {
+            *x2 -= dx;
+            *y2 -= dy;
+            *x -= dx;
+            *y -= dy;
+        }
+        SegmentData::Quadratic { ref mut x1, ref mut y1, ref mut x, ref mut y } => {
+            *x1 -= dx;
+            *y1 -= dy;
+            *x -= dx;
+            *y -= dy;
+        }
+        SegmentData::ClosePath => {}
+    }
+}
+
+// Estimates the segment length in the compact notation.
+//
+// It's not exact, since we don't know the actual write options,
+// but good enough to select the shortest one.
+pub fn estimate_len(seg: &Segment, prev_seg: Option<Segment>, opt: &WriteOptions,
+                    buf: &mut Vec<u8>) -> usize {
+    // A command can be skipped when it's the same as the previous one
+    // or when LineTo is followed by MoveTo.
+    let is_cmd_skipped = match prev_seg {
+        Some(prev) if prev.absolute == seg.absolute => {
+               (prev.cmd() == seg.cmd() && prev.cmd() != Command::MoveTo)
+            || (prev.cmd() == Command::MoveTo && seg.cmd() == Command::LineTo)
+        }
+        _ => false,
+    };
+
+    let mut len = if is_cmd_skipped { 0 } else { 1 };
+
+    let mut coords = [0.0; 6];
+    let count = match *seg.data() {
+          SegmentData::MoveTo { x, y }
+        | SegmentData::LineTo { x, y }
+        | SegmentData::SmoothQuadratic { x, y } => {
+            coords[0] = x;
+            coords[1] = y;
+            2
+        }
+        SegmentData::EllipticalArc { rx, ry, x_axis_rotation, x, y, .. } => {
+            // Flags are always separated by spaces.
+            len += 4;
+            coords[..5].copy_from_slice(&[rx, ry, x_axis_rotation, x, y]);
+            5
+        }
+        SegmentData::HorizontalLineTo { x } => {
+            coords[0] = x;
+            1
+        }
+        SegmentData::VerticalLineTo { y } => {
+            coords[0] = y;
+            1
+        }
+        SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
+            coords = [x1, y1, x2, y2, x, y];
+            6
+        }
+        SegmentData::SmoothCurveTo { x2, y2, x, y } => {
+            coords[..4].copy_from_slice(&[x2, y2, x, y]);
+            4
+        }
+        SegmentData::Quadratic { x1, y1, x, y } => {
+            coords[..4].copy_from_slice(&[x1, y1, x, y]);
+            4
+        }
+        SegmentData::ClosePath => 0,
+    };
+
+    for (i, n) in coords[..count].iter().enumerate() {
+        let (n_len, is_negative) = num_len(*n, opt, buf);
+        len += n_len;
+
+        // Negative numbers doesn't require a separator.
+        if (i != 0 || is_cmd_skipped) && !is_negative {
+            len += 1;
+        }
+    }
+
+    len
+}
+
+// Returns the length of a number written by svgdom and whether it starts with '-'.
+//
+// Numbers are written with up to 11 decimal places using the shortest representation,
+// so instead of formatting a number we can look for the smallest number of decimal places
+// that represents it exactly. Formatting is used only for numbers,
+// which are too big to be checked this way.
+//
+// TODO: an arena-based path representation and a faster float formatter with precision
+//       clamping are not implemented, since `Path` and its writer belong to svgdom.
+fn num_len(n: f64, opt: &WriteOptions, buf: &mut Vec<u8>) -> (usize, bool) {
+    // The same rounding as in svgdom.
+    let v = (n * 100_000_000_000.0).round() / 100_000_000_000.0;
+
+    if v == 0.0 {
+        // '0' or '-0'.
+        let is_negative = v.is_sign_negative();
+        return (1 + is_negative as usize, is_negative);
+    }
+
+    let is_negative = v < 0.0;
+    let a = v.abs();
+    let mut p = 1.0;
+    for k in 0..12 {
+        let m = (a * p).round();
+
+        // Larger numbers can be represented by more than one integer.
+        if m >= 4_503_599_627_370_496.0 {
+            break;
+        }
+
+        if m / p == a {
+            let mut digits = 1;
+            let mut int = m as u64;
+            while int >= 10 {
+                int /= 10;
+                digits += 1;
+            }
+
+            let mut len = is_negative as usize;
+            if digits > k {
+                len += digits - k;
+            } else if !opt.remove_leading_zero {
+                // The leading zero.
+                len += 1;
+            }
+
+            if k != 0 {
+                // The dot and the fractional part.
+                len += 1 + k;
+            }
+
+            return (len, is_negative);
+        }
+
+        p *= 10.0;
+    }
+
+    buf.clear();
+    n.write_buf_opt(opt, buf);
+    (buf.len(), buf[0] == b'-')
+}
+
 fn _convert_segments(path: &mut Path, is_changed: &mut bool) {
     let mut i = 1;
     while i < path.d.len() {
         let prev_seg = path.d[i - 1];
-        let (prev_x, prev_y) = utils::resolve_xy(path, i - 1);
+        let (prev_x, prev_y) = utils::resolve_xy(&path.d, i - 1);
         let curr_seg = &mut path.d[i];
         match *curr_seg.data() {
             SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
@@ -298,6 +601,42 @@ mod tests {
     test!(conv_cl_2, "M 10 15 C 10 15 72.5 10 72.5 55 C 72.5 100 135 100 135 55 L 10 55",
                      "M 10 15 S 72.5 10 72.5 55 S 135 100 135 55 L 10 55");
 
+    macro_rules! test_shortest {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut path = Path::from_str($in_text).unwrap();
+                path.conv_to_absolute();
+                convert_to_shortest(&mut path);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    test_shortest!(shortest_1, "M 10.5 20.5 L 30.5 40.5 L 100 200 L 110 210",
+                               "m 10.5 20.5 l 20 20 L 100 200 l 10 10");
+
+    test_shortest!(shortest_2, "M 100.5 100 H 1000 V 110 Z M 10 20 C 10 10 25 10 25 20",
+                               "m 100.5 100 H 1000 v 10 z M 10 20 c 0 -10 15 -10 15 0");
+
+    macro_rules! test_merge {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut path = Path::from_str($in_text).unwrap();
+                merge_hv(&mut path);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    test_merge!(merge_hv_1, "M 10 10 H 20 H 30 H 40 V 20 V 30 H 30",
+                            "M 10 10 H 40 V 30 H 30");
+
+    // Segments with an opposite direction should be preserved.
+    test_merge!(merge_hv_2, "M 10 10 H 30 H 20 V 20 V 5 Z M 10 10 V 20 V 30",
+                            "M 10 10 H 30 H 20 V 20 V 5 Z M 10 10 V 30");
+
     #[test]
     fn hv_to_l_1() {
         // Test segments with less then two coordinates: H, V, Z.
@@ -308,4 +647,25 @@ mod tests {
         convert_l_to_hv(&mut path);
         assert_eq_text!(path.to_string(), text);
     }
+
+    #[test]
+    fn num_len_1() {
+        let numbers = [
+            0.0, -0.0, 1.0, -1.0, 0.5, -0.5, 0.05, 10.0, 100.25, -0.00001, 123456.789,
+            0.1 + 0.2, 29.999999999999996, 1e-12, -1e-12, 0.000000000015, 1e15, 1e20,
+            1.0 / 3.0, -2.0 / 3.0, 12345678.87654321, 956.03427, -499.16974,
+            f64::MAX, f64::MIN_POSITIVE,
+        ];
+
+        let mut buf = Vec::new();
+        for &remove_leading_zero in &[false, true] {
+            let opt = WriteOptions { remove_leading_zero, .. WriteOptions::default() };
+            for &n in &numbers {
+                let mut text = Vec::new();
+                n.write_buf_opt(&opt, &mut text);
+                assert_eq!(num_len(n, &opt, &mut buf), (text.len(), text[0] == b'-'),
+                           "{}", String::from_utf8(text).unwrap());
+            }
+        }
+    }
 }
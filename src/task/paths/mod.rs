@@ -16,22 +16,32 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
+use std::mem;
+
 use svgdom::{
     AttributeValue,
     Document,
-    Transform,
+    Node,
+    ValueId,
 };
 use svgdom::path::Path;
 
 use task::short::{EId, AId};
-use task::apply_transforms::utils as ts_utils;
+use task::utils::{
+    find_attribute,
+    parallel,
+};
 use options::CleaningOptions;
 
-mod apply_transform;
+mod arcs;
 mod conv_segments;
+mod reorder;
 mod rm_unused;
+mod simplify;
 
 pub fn process_paths(doc: &Document, opt: &CleaningOptions) {
+    let mut nodes = Vec::new();
+    let mut items = Vec::new();
     for mut node in doc.descendants().filter(|n| n.is_tag_name(EId::Path)) {
         // We can't process paths with marker, because if we remove all segments
         // it will break rendering.
@@ -39,37 +49,47 @@ pub fn process_paths(doc: &Document, opt: &CleaningOptions) {
         // TODO: do not remove first segment if node has marker
         let has_marker = node.has_attributes(&[AId::Marker, AId::MarkerStart,
                                                AId::MarkerMid, AId::MarkerEnd]);
+        let has_caps = has_visible_caps(&node);
+        let has_dashes = !matches!(find_attribute(&node, AId::StrokeDasharray),
+                                   Some(AttributeValue::PredefValue(ValueId::None)) | None);
+        let has_fill = !matches!(find_attribute(&node, AId::Fill),
+                                 Some(AttributeValue::PredefValue(ValueId::None)));
+
+        let subpaths = SubpathsMode {
+            // Dashes are restarted at each subpath and markers are rendered in order,
+            // so we can't touch subpaths in those cases.
+            reorder: opt.reorder_subpaths && !has_marker && !has_dashes,
+            // Reversing changes the winding, which affects filling.
+            reverse: !has_fill,
+        };
+
+        // Paths are detached from the DOM, so they can be processed in parallel.
+        let path = match node.attributes_mut().get_value_mut(AId::D) {
+            Some(&mut AttributeValue::Path(ref mut path)) => mem::replace(path, Path::new()),
+            _ => continue,
+        };
+
+        items.push((path, has_marker, has_caps, subpaths));
+        nodes.push(node);
+    }
 
-        let mut ts = None;
-        if opt.apply_transform_to_paths {
-            if node.has_attribute(AId::Transform) {
-                let tsl = ts_utils::get_ts(&node);
-
-                if    ts_utils::is_valid_transform(&tsl)
-                   && ts_utils::is_valid_attrs(&node) {
-                    ts = Some(tsl);
-
-                    node.remove_attribute(AId::Transform);
-
-                    if tsl.has_scale() {
-                        // TODO: maybe ignore paths without 'stroke-width'
-
-                        // We must update 'stroke-width' if transform had scale part in it.
-                        let (sx, _) = tsl.get_scale();
-                        ::task::utils::recalc_stroke(&mut node, sx);
-                    }
-                }
-            }
-        }
+    parallel::for_each(&mut items, opt.jobs, |&mut (ref mut path, has_marker, has_caps, subpaths)| {
+        process_path(path, has_marker, has_caps, subpaths, opt);
+    });
 
-        let mut attrs = node.attributes_mut();
-        if let Some(&mut AttributeValue::Path(ref mut path)) = attrs.get_value_mut(AId::D) {
-            process_path(path, has_marker, ts, opt);
-        }
+    for (mut node, (path, ..)) in nodes.into_iter().zip(items) {
+        node.set_attribute((AId::D, path));
     }
 }
 
-fn process_path(path: &mut Path, has_marker: bool, ts: Option<Transform>, opt: &CleaningOptions) {
+#[derive(Clone, Copy)]
+struct SubpathsMode {
+    reorder: bool,
+    reverse: bool,
+}
+
+fn process_path(path: &mut Path, has_marker: bool, has_caps: bool, subpaths: SubpathsMode,
+                opt: &CleaningOptions) {
     if path.d.is_empty() {
         return;
     }
@@ -80,44 +100,62 @@ fn process_path(path: &mut Path, has_marker: bool, ts: Option<Transform>, opt: &
     conv_segments::convert_hv_to_l(path);
 
     if opt.convert_segments {
+        // Run before 'convert_segments', because it works only with CurveTo.
+        arcs::convert_curves_to_arcs(path, opt.paths_coordinates_precision);
+        arcs::convert_arcs_to_curves(path, opt.paths_coordinates_precision);
+
         conv_segments::convert_segments(path);
     }
 
+    // Markers are rendered at each vertex, so we can't remove any of them.
+    if opt.simplify_paths > 0.0 && !has_marker {
+        simplify::simplify_lines(path, opt.simplify_paths);
+    }
+
     if opt.remove_unused_segments && !has_marker {
-        rm_unused::remove_unused_segments(path);
+        rm_unused::remove_unused_segments(path, has_caps);
 
         if path.d.is_empty() {
             return;
         }
     }
 
-    if let Some(ref ts) = ts {
-        apply_transform::apply_transform(path, ts);
+    if subpaths.reorder {
+        reorder::reorder_subpaths(path, subpaths.reverse);
     }
 
     conv_segments::convert_l_to_hv(path);
 
-    path.conv_to_relative();
+    // Markers are rendered at each vertex, so we can't remove any of them.
+    if opt.convert_segments && !has_marker {
+        conv_segments::merge_hv(path);
+    }
+
+    // Mix relative and absolute segments, depending on which one is shorter.
+    conv_segments::convert_to_shortest(path);
+}
+
+// Checks that zero-length subpaths will be rendered.
+fn has_visible_caps(node: &Node) -> bool {
+    let has_stroke = !matches!(find_attribute(node, AId::Stroke),
+                               Some(AttributeValue::PredefValue(ValueId::None)) | None);
+
+    let has_caps = matches!(find_attribute(node, AId::StrokeLinecap),
+                            Some(AttributeValue::PredefValue(ValueId::Round)) |
+                            Some(AttributeValue::PredefValue(ValueId::Square)));
 
-    // TODO: estimate path length
-    // NOTE: A relative path can be bigger, but usually only on few chars.
-    //       But to check it - we need to convert an original and a new path to strings
-    //       and compare theme. And in case that new path is bigger - fallback to original,
-    //       so we need a copy of original path too. It will be extremely slow. Straightforward
-    //       implementation gives us 2x slowdown of the whole application time.
-    //       Which is unacceptable.
-    //       So we sacrifice a very small amount of cleaning ratio for performance benefits.
+    has_stroke && has_caps
 }
 
 mod utils {
-    use svgdom::path::{Path, Command};
+    use svgdom::path::{Segment, Command};
 
     // HorizontalLineTo, VerticalLineTo and ClosePath does not have 'x'/'y' coordinates,
     // so we have to find them in previous segments.
-    pub fn resolve_xy(path: &Path, start: usize) -> (f64, f64) {
+    pub fn resolve_xy(segments: &[Segment], start: usize) -> (f64, f64) {
         let mut i = start;
         loop {
-            let seg = &path.d[i];
+            let seg = &segments[i];
 
             // H and V should be already converted into L,
             // so we check only for Z.
@@ -141,6 +179,7 @@ mod tests {
     use super::*;
     use options::CleaningOptions;
     use svgdom::{Document, ToStringWithOptions};
+    use task::apply_transforms;
 
     macro_rules! test {
         ($name:ident, $in_text:expr, $out_text:expr) => (
@@ -152,8 +191,10 @@ mod tests {
                 opt.paths_to_relative = true;
                 opt.remove_unused_segments = true;
                 opt.convert_segments = true;
+                opt.reorder_subpaths = true;
                 opt.apply_transform_to_paths = true;
 
+                apply_transforms::apply_transform_to_paths(&doc, 1);
                 process_paths(&doc, &opt);
                 assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
             }
@@ -216,6 +257,33 @@ mod tests {
         <path id='valid' d='m 10 20 l 20 20' transform='translate(10 20)'/>
     </g>
 </svg>
+");
+
+    // Zero-length subpaths are rendered as dots with round caps.
+    test!(caps,
+"<svg>
+    <path d='M 10 20 L 10 20' stroke='#000000' stroke-linecap='round'/>
+    <path d='M 10 20 L 10 20' stroke-linecap='round'/>
+</svg>",
+"<svg>
+    <path d='m 10 20 z' stroke='#000000' stroke-linecap='round'/>
+    <path d='' stroke-linecap='round'/>
+</svg>
+");
+
+    test!(subpaths,
+"<svg>
+    <path d='M 10 10 L 20 20 M 50 50 L 21 21' fill='none'/>
+    <path d='M 10 10 L 20 20 M 50 50 L 21 21'/>
+    <path d='M 10 10 L 20 20 M 50 50 L 21 21 M 20 20 L 30 30'/>
+    <path d='M 10 10 L 20 20 M 50 50 L 21 21 M 20 20 L 30 30' stroke-dasharray='5'/>
+</svg>",
+"<svg>
+    <path d='m 10 10 l 10 10 m 1 1 l 29 29' fill='none'/>
+    <path d='m 10 10 l 10 10 m 30 30 l -29 -29'/>
+    <path d='m 10 10 l 10 10 m 0 0 l 10 10 m 20 20 l -29 -29'/>
+    <path d='m 10 10 l 10 10 m 30 30 l -29 -29 m -1 -1 l 10 10' stroke-dasharray='5'/>
+</svg>
 ");
 
     test!(marker,
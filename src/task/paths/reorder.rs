@@ -0,0 +1,267 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::ops::Range;
+
+use svgdom::path::{
+    Command,
+    Path,
+    Segment,
+    SegmentData,
+};
+
+// Subpaths are referencing the path segments instead of copying them,
+// because a path can have thousands of them.
+struct Subpath {
+    range: Range<usize>,
+    start: (f64, f64),
+    end: (f64, f64),
+    is_reversible: bool,
+    is_reversed: bool,
+}
+
+// Reorders subpaths, so the distance between the end of the previous subpath
+// and the start of the next one will be as small as possible.
+// Smaller distance leads to a shorter relative MoveTo.
+//
+// When 'allow_reverse' is set, open subpaths can also be reversed.
+// This changes the winding, so it can be used only for paths without a fill.
+//
+// All segments must be absolute.
+pub fn reorder_subpaths(path: &mut Path, allow_reverse: bool) {
+    let mut subpaths = match split_subpaths(path, allow_reverse) {
+        Some(v) => v,
+        None => return,
+    };
+
+    if subpaths.len() < 2 {
+        return;
+    }
+
+    let old_dist = calc_distance(&subpaths);
+
+    // The first subpath stays in place, because the first MoveTo is always absolute.
+    // Only indices are moved around, because a path can have thousands of subpaths.
+    let mut order = vec![0];
+    let mut remaining: Vec<usize> = (1..subpaths.len()).collect();
+    let mut new_dist = 0.0;
+    while !remaining.is_empty() {
+        let prev = subpaths[*order.last().unwrap()].end;
+
+        let mut best_pos = 0;
+        let mut best_rev = false;
+        let mut best_dist = f64::MAX;
+        for (pos, &idx) in remaining.iter().enumerate() {
+            let sp = &subpaths[idx];
+            let d = dist(prev, sp.start);
+            if d < best_dist {
+                best_pos = pos;
+                best_rev = false;
+                best_dist = d;
+            }
+
+            if sp.is_reversible {
+                let d = dist(prev, sp.end);
+                if d < best_dist {
+                    best_pos = pos;
+                    best_rev = true;
+                    best_dist = d;
+                }
+            }
+        }
+
+        let idx = remaining.remove(best_pos);
+        if best_rev {
+            let sp = &mut subpaths[idx];
+            sp.is_reversed = true;
+            ::std::mem::swap(&mut sp.start, &mut sp.end);
+        }
+        order.push(idx);
+        new_dist += best_dist;
+    }
+
+    // Keep the original order when we can't improve it.
+    if new_dist >= old_dist {
+        return;
+    }
+
+    let mut d = Vec::with_capacity(path.d.len());
+    for idx in order {
+        let sp = &subpaths[idx];
+        let segments = &path.d[sp.range.clone()];
+        if sp.is_reversed {
+            push_reversed(segments, sp.start, &mut d);
+        } else {
+            d.extend_from_slice(segments);
+        }
+    }
+    path.d = d;
+}
+
+fn split_subpaths(path: &Path, allow_reverse: bool) -> Option<Vec<Subpath>> {
+    let mut subpaths: Vec<Subpath> = Vec::new();
+
+    for (i, seg) in path.d.iter().enumerate() {
+        if let SegmentData::MoveTo { x, y } = *seg.data() {
+            subpaths.push(Subpath {
+                range: i..i + 1,
+                start: (x, y),
+                end: (x, y),
+                is_reversible: allow_reverse,
+                is_reversed: false,
+            });
+            continue;
+        }
+
+        let sp = subpaths.last_mut()?;
+
+        // Every subpath must start with MoveTo.
+        // 'fix_m' should be called before.
+        if path.d[i - 1].cmd() == Command::ClosePath {
+            return None;
+        }
+
+        match *seg.data() {
+            SegmentData::LineTo { x, y } |
+            SegmentData::CurveTo { x, y, .. } |
+            SegmentData::Quadratic { x, y, .. } |
+            SegmentData::EllipticalArc { x, y, .. } => {
+                sp.end = (x, y);
+            }
+            SegmentData::ClosePath => {
+                // Closed subpaths are ended at the start point,
+                // so there is no point in reversing them.
+                sp.end = sp.start;
+                sp.is_reversible = false;
+            }
+            _ => {
+                // Smooth segments depend on the previous segment
+                // and HorizontalLineTo/VerticalLineTo should be already converted.
+                sp.end = resolve_end(seg, sp.end);
+                sp.is_reversible = false;
+            }
+        }
+
+        sp.range.end = i + 1;
+    }
+
+    // Subpaths without drawing segments should be already removed,
+    // but we still shouldn't move them.
+    if subpaths.iter().any(|sp| sp.range.len() == 1) {
+        return None;
+    }
+
+    Some(subpaths)
+}
+
+fn resolve_end(seg: &Segment, prev: (f64, f64)) -> (f64, f64) {
+    match *seg.data() {
+        SegmentData::HorizontalLineTo { x } => (x, prev.1),
+        SegmentData::VerticalLineTo { y } => (prev.0, y),
+        SegmentData::SmoothCurveTo { x, y, .. } |
+        SegmentData::SmoothQuadratic { x, y } => (x, y),
+        _ => prev,
+    }
+}
+
+// Appends segments of an open subpath in reverse order, starting from its end point.
+fn push_reversed(segments: &[Segment], start: (f64, f64), d: &mut Vec<Segment>) {
+    d.push(Segment::new_move_to(start.0, start.1));
+
+    for i in (1..segments.len()).rev() {
+        let prev = &segments[i - 1];
+        let (x, y) = (prev.x().unwrap(), prev.y().unwrap());
+        let new_seg = match *segments[i].data() {
+            SegmentData::LineTo { .. } => {
+                Segment::new_line_to(x, y)
+            }
+            SegmentData::CurveTo { x1, y1, x2, y2, .. } => {
+                Segment::new_curve_to(x2, y2, x1, y1, x, y)
+            }
+            SegmentData::Quadratic { x1, y1, .. } => {
+                Segment::new_quad_to(x1, y1, x, y)
+            }
+            SegmentData::EllipticalArc { rx, ry, x_axis_rotation, large_arc, sweep, .. } => {
+                Segment::new_arc_to(rx, ry, x_axis_rotation, large_arc, !sweep, x, y)
+            }
+            _ => unreachable!("only reversible subpaths can be reversed"),
+        };
+
+        d.push(new_seg);
+    }
+}
+
+fn calc_distance(subpaths: &[Subpath]) -> f64 {
+    subpaths.windows(2).map(|w| dist(w[0].end, w[1].start)).sum()
+}
+
+// We are using the Manhattan distance, because it's closer
+// to the length of the relative MoveTo notation.
+fn dist(p1: (f64, f64), p2: (f64, f64)) -> f64 {
+    (p2.0 - p1.0).abs() + (p2.1 - p1.1).abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use svgdom::path::Path;
+
+    macro_rules! test {
+        ($name:ident, $allow_reverse:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut path = Path::from_str($in_text).unwrap();
+                reorder_subpaths(&mut path, $allow_reverse);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    test!(reorder_1, false,
+          "M 10 10 L 20 20 M 100 100 L 110 110 M 20 20 L 30 30",
+          "M 10 10 L 20 20 M 20 20 L 30 30 M 100 100 L 110 110");
+
+    test!(reorder_closed_1, false,
+          "M 10 10 L 20 20 L 10 20 Z M 100 100 L 110 110 Z M 15 15 L 30 30 Z",
+          "M 10 10 L 20 20 L 10 20 Z M 15 15 L 30 30 Z M 100 100 L 110 110 Z");
+
+    test!(reverse_1, true,
+          "M 10 10 L 20 20 M 50 50 L 21 21",
+          "M 10 10 L 20 20 M 21 21 L 50 50");
+
+    test!(reverse_2, true,
+          "M 10 10 L 20 20 M 50 50 C 40 40 30 30 20 21 A 5 5 0 0 1 25 25",
+          "M 10 10 L 20 20 M 25 25 A 5 5 0 0 0 20 21 C 30 30 40 40 50 50");
+
+    // Without reversing we can't do any better.
+    test!(keep_1, false,
+          "M 10 10 L 20 20 M 50 50 L 21 21",
+          "M 10 10 L 20 20 M 50 50 L 21 21");
+
+    // Closed subpaths should not be reversed.
+    test!(keep_2, true,
+          "M 10 10 L 20 20 M 50 50 L 21 21 Z",
+          "M 10 10 L 20 20 M 50 50 L 21 21 Z");
+
+    // Smooth segments depend on the previous one.
+    test!(keep_3, true,
+          "M 10 10 L 20 20 M 50 50 S 30 30 21 21",
+          "M 10 10 L 20 20 M 50 50 S 30 30 21 21");
+}
@@ -23,11 +23,14 @@ use svgdom::path::{
     Command,
     Path,
     Segment,
+    SegmentData,
 };
 
 use super::utils;
 
-pub fn remove_unused_segments(path: &mut Path) {
+// 'keep_dots' should be set when the path has a stroke with a round or square cap,
+// because zero-length subpaths are rendered as dots in this case.
+pub fn remove_unused_segments(path: &mut Path, keep_dots: bool) {
     // Repeat until we have any changes.
     let mut is_changed = true;
     while is_changed {
@@ -42,10 +45,11 @@ pub fn remove_unused_segments(path: &mut Path) {
         // Order is important.
         if remove_mm(path) { is_changed = true; }
         if remove_zz(path) { is_changed = true; }
-        if remove_mz(path) { is_changed = true; }
+        if !keep_dots && remove_mz(path) { is_changed = true; }
+        if remove_trailing_m(path) { is_changed = true; }
         process_lz(path, &mut is_changed);
         if remove_equal(path) { is_changed = true; }
-        remove_zero_lenght(path, &mut is_changed);
+        remove_zero_lenght(path, keep_dots, &mut is_changed);
 
         if path.d.is_empty() {
             break;
@@ -60,36 +64,42 @@ enum DrainMode {
 }
 
 // Removes segments by predicate and returns 'true' if any was removed.
+//
+// Kept segments are moved to the front of the list and the rest is truncated,
+// because removing them one by one is quadratic.
 fn drain_by_pair<P>(path: &mut Path, mode: DrainMode, p: P) -> bool
     where P: Fn(&Segment, &Segment) -> bool
 {
-    let old_len = path.d.len();
-
-    let mut i = 1;
-    while i < path.d.len() {
-        let prev = path.d[i - 1];
-        let curr = path.d[i];
-        if p(&prev, &curr) {
-            let step_back = match mode {
-                DrainMode::Single => {
-                    path.d.remove(i - 1);
-                    1
+    let d = &mut path.d;
+    let old_len = d.len();
+
+    let mut len = 0;
+    for i in 0..old_len {
+        let seg = d[i];
+        match mode {
+            DrainMode::Single => {
+                // Only the previous segment is removed,
+                // so the current one is compared with the next one as is.
+                if i + 1 == old_len || !p(&seg, &d[i + 1]) {
+                    d[len] = seg;
+                    len += 1;
                 }
-                DrainMode::Both => {
-                    path.d.remove(i - 1);
-                    path.d.remove(i - 1);
-
-                    if i > 1 { 2 } else { 0 }
+            }
+            DrainMode::Both => {
+                // The pair is removed, so the next segment is compared with the one
+                // before the pair.
+                if len > 0 && p(&d[len - 1], &seg) {
+                    len -= 1;
+                } else {
+                    d[len] = seg;
+                    len += 1;
                 }
-            };
-
-            i -= step_back;
+            }
         }
-
-        i += 1;
     }
+    d.truncate(len);
 
-    old_len != path.d.len()
+    old_len != len
 }
 
 // Remove continuous MoveTo segments since they are pointless.
@@ -117,6 +127,16 @@ fn remove_mz(path: &mut Path) -> bool {
     })
 }
 
+// Remove MoveTo at the end of the path since it's an empty subpath.
+fn remove_trailing_m(path: &mut Path) -> bool {
+    if path.d.len() > 1 && path.d.last().unwrap().cmd() == Command::MoveTo {
+        path.d.pop();
+        true
+    } else {
+        false
+    }
+}
+
 // If current segment is the same as previous - remove it.
 fn remove_equal(path: &mut Path) -> bool {
     drain_by_pair(path, DrainMode::Single, |prev, curr| {
@@ -125,28 +145,57 @@ fn remove_equal(path: &mut Path) -> bool {
 }
 
 // If segment moved to the same position as current - remove it.
-fn remove_zero_lenght(path: &mut Path, is_changed: &mut bool) {
-    let mut i = 1;
-    while i < path.d.len() {
+fn remove_zero_lenght(path: &mut Path, keep_dots: bool, is_changed: &mut bool) {
+    // The number of kept segments.
+    let mut len = 1;
+    for i in 1..path.d.len() {
         let curr = path.d[i];
-        let (px, py) = utils::resolve_xy(path, i - 1);
+        let (px, py) = utils::resolve_xy(&path.d, len - 1);
+        let is_same_point = |x: f64, y: f64| x.fuzzy_eq(&px) && y.fuzzy_eq(&py);
 
-        let is_equal = match curr.cmd() {
-            Command::LineTo => {
-                curr.x().unwrap().fuzzy_eq(&px) && curr.y().unwrap().fuzzy_eq(&py)
+        let is_zero_length = match *curr.data() {
+            SegmentData::LineTo { x, y } => {
+                is_same_point(x, y)
             }
-            // Curves can't be removed.
+            SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
+                is_same_point(x1, y1) && is_same_point(x2, y2) && is_same_point(x, y)
+            }
+            SegmentData::Quadratic { x1, y1, x, y } => {
+                is_same_point(x1, y1) && is_same_point(x, y)
+            }
+            // Smooth curves depend on the previous segment,
+            // so they can't be zero-length even with the same points.
             _ => false,
         };
 
-        if is_equal {
-            path.d.remove(i);
-            i -= 1;
+        // An arc with the same end points is not rendered at all.
+        let is_empty_arc = if let SegmentData::EllipticalArc { x, y, .. } = *curr.data() {
+            is_same_point(x, y)
+        } else {
+            false
+        };
+
+        // Smooth curves use the previous segment control points.
+        let next_cmd = path.d.get(i + 1).map(|s| s.cmd());
+        let is_next_smooth =    next_cmd == Some(Command::SmoothCurveTo)
+                             || next_cmd == Some(Command::SmoothQuadratic);
+
+        // A single zero-length segment in the subpath will be rendered as a dot.
+        let is_dot = {
+            let is_prev_m = path.d[len - 1].cmd() == Command::MoveTo;
+            let is_next_m = next_cmd.is_none() || next_cmd == Some(Command::MoveTo);
+
+            keep_dots && is_prev_m && is_next_m
+        };
+
+        if (is_zero_length && !is_dot || is_empty_arc) && !is_next_smooth {
             *is_changed = true;
+        } else {
+            path.d[len] = curr;
+            len += 1;
         }
-
-        i += 1;
     }
+    path.d.truncate(len);
 }
 
 fn process_lz(path: &mut Path, is_changed: &mut bool) {
@@ -154,55 +203,68 @@ fn process_lz(path: &mut Path, is_changed: &mut bool) {
         return;
     }
 
-    let mut i = 1;
     let mut mx = path.d[0].x().unwrap();
     let mut my = path.d[0].y().unwrap();
-    while i < path.d.len() {
-        let curr_cmd = path.d[i].cmd();
-
-        // If current segment is ClosePath and previous segment is line-based segment
-        // which points to previous MoveTo - then this line-based segment is pointless,
-        // because ClosePath will render the same line by itself.
-        if curr_cmd == Command::ClosePath {
-            let prev_i = i - 1;
-            let prev = path.d[prev_i];
-            if is_line_based(prev.cmd()) {
-                let (x, y) = utils::resolve_xy(path, prev_i);
+    // The number of kept segments. The previous segment is always 'path.d[len - 1]'.
+    let mut len = 1;
+    let old_len = path.d.len();
+    for i in 1..old_len {
+        let mut curr = path.d[i];
+
+        loop {
+            let curr_cmd = curr.cmd();
+
+            // If current segment is ClosePath and previous segment is line-based segment
+            // which points to previous MoveTo - then this line-based segment is pointless,
+            // because ClosePath will render the same line by itself.
+            if curr_cmd == Command::ClosePath && is_line_based(path.d[len - 1].cmd()) {
+                let (x, y) = utils::resolve_xy(&path.d, len - 1);
                 if mx.fuzzy_eq(&x) && my.fuzzy_eq(&y) {
                     // Remove this line-based segment.
-                    path.d.remove(prev_i);
-                    i -= 1;
+                    len -= 1;
                     *is_changed = true;
                     continue;
                 }
             }
-        }
 
-        // If line-based segment is followed by MoveTo or located at the end of the path
-        // and points to previous MoveTo - than we can replace it with ClosePath.
-        let is_last = i == path.d.len() - 1 && curr_cmd != Command::ClosePath;
-        if curr_cmd == Command::MoveTo || is_last {
-            let prev_i = if is_last { i } else { i - 1 };
-            let prev = path.d[prev_i];
-            if is_line_based(prev.cmd()) {
-                let (x, y) = utils::resolve_xy(path, prev_i);
-                if mx.fuzzy_eq(&x) && my.fuzzy_eq(&y) {
-                    // Replace line-based segment with ClosePath.
-                    path.d[prev_i] = Segment::new_close_path();
-                    *is_changed = true;
-                    continue;
+            // If line-based segment is followed by MoveTo or located at the end of the path
+            // and points to previous MoveTo - than we can replace it with ClosePath.
+            let is_last = i == old_len - 1 && curr_cmd != Command::ClosePath;
+            if curr_cmd == Command::MoveTo || is_last {
+                let prev = if is_last { curr } else { path.d[len - 1] };
+                if is_line_based(prev.cmd()) {
+                    let (x, y) = if is_last {
+                        (curr.x().unwrap(), curr.y().unwrap())
+                    } else {
+                        utils::resolve_xy(&path.d, len - 1)
+                    };
+
+                    if mx.fuzzy_eq(&x) && my.fuzzy_eq(&y) {
+                        // Replace line-based segment with ClosePath.
+                        if is_last {
+                            curr = Segment::new_close_path();
+                        } else {
+                            path.d[len - 1] = Segment::new_close_path();
+                        }
+                        *is_changed = true;
+                        continue;
+                    }
                 }
             }
+
+            break;
         }
 
         // Remember last MoveTo.
-        if curr_cmd == Command::MoveTo {
-            mx = path.d[i].x().unwrap();
-            my = path.d[i].y().unwrap();
+        if curr.cmd() == Command::MoveTo {
+            mx = curr.x().unwrap();
+            my = curr.y().unwrap();
         }
 
-        i += 1;
+        path.d[len] = curr;
+        len += 1;
     }
+    path.d.truncate(len);
 }
 
 fn is_line_based(seg: Command) -> bool
@@ -230,7 +292,20 @@ mod tests {
                 let mut path = Path::from_str($in_text).unwrap();
                 path.conv_to_absolute();
                 conv_segments::convert_hv_to_l(&mut path);
-                remove_unused_segments(&mut path);
+                remove_unused_segments(&mut path, false);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    macro_rules! test_dots {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut path = Path::from_str($in_text).unwrap();
+                path.conv_to_absolute();
+                conv_segments::convert_hv_to_l(&mut path);
+                remove_unused_segments(&mut path, true);
                 assert_eq_text!(path.to_string(), $out_text);
             }
         )
@@ -283,11 +358,29 @@ mod tests {
         "M 10 10 L 10 10",
         "");
 
-    // Only H, V, L segments should be removed.
+    test!(rm_zero_lenght_3,
+        "M 10 10 L 20 20 C 20 20 20 20 20 20 Q 20 20 20 20 A 5 5 0 0 1 20 20 L 30 30",
+        "M 10 10 L 20 20 L 30 30");
+
+    // Curve with the same end points is not zero-length.
     test!(keep_zero_lenght_1,
         "M 10 10 C 20 20 30 30 10 10",
         "M 10 10 C 20 20 30 30 10 10");
 
+    // Next segment depends on the previous control points.
+    test!(keep_zero_lenght_2,
+        "M 10 10 C 10 10 10 10 10 10 S 20 20 30 30",
+        "M 10 10 C 10 10 10 10 10 10 S 20 20 30 30");
+
+    test!(rm_trailing_m_1,
+        "M 10 10 L 20 20 M 30 30",
+        "M 10 10 L 20 20");
+
+    // Zero-length subpaths are rendered as dots with round and square caps.
+    test_dots!(keep_dots_1,
+        "M 10 10 L 10 10 M 20 20 Z M 30 30 L 40 40 L 40 40",
+        "M 10 10 Z M 20 20 Z M 30 30 L 40 40");
+
 
     test!(rm_equal_1,
         "M 10 10 C 20 20 30 30 10 10 C 20 20 30 30 10 10",
@@ -0,0 +1,137 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::path::{
+    Command,
+    Path,
+};
+
+use super::utils;
+
+// Removes LineTo segments that are closer than 'tolerance' to a simplified polyline,
+// using the Ramer–Douglas–Peucker algorithm.
+//
+// Curves are preserved as is.
+//
+// All segments must be absolute and H/V segments must be converted into L.
+pub fn simplify_lines(path: &mut Path, tolerance: f64) {
+    let mut keep = vec![true; path.d.len()];
+
+    let mut i = 1;
+    while i < path.d.len() {
+        if path.d[i].cmd() != Command::LineTo {
+            i += 1;
+            continue;
+        }
+
+        // Collect a polyline, which starts at the end of the previous segment.
+        let start = i - 1;
+        let mut points = vec![utils::resolve_xy(&path.d, start)];
+        while i < path.d.len() && path.d[i].cmd() == Command::LineTo {
+            points.push((path.d[i].x().unwrap(), path.d[i].y().unwrap()));
+            i += 1;
+        }
+
+        let mut keep_points = vec![false; points.len()];
+        keep_points[0] = true;
+        *keep_points.last_mut().unwrap() = true;
+        rdp(&points, 0, points.len() - 1, tolerance, &mut keep_points);
+
+        // The first point belongs to the previous segment.
+        for (idx, k) in keep_points.iter().enumerate().skip(1) {
+            keep[start + idx] = *k;
+        }
+    }
+
+    let mut idx = 0;
+    path.d.retain(|_| {
+        idx += 1;
+        keep[idx - 1]
+    });
+}
+
+fn rdp(points: &[(f64, f64)], first: usize, last: usize, tolerance: f64, keep: &mut [bool]) {
+    if last <= first + 1 {
+        return;
+    }
+
+    let mut max_dist = 0.0;
+    let mut max_idx = first;
+    for i in (first + 1)..last {
+        let d = dist_to_segment(points[i], points[first], points[last]);
+        if d > max_dist {
+            max_dist = d;
+            max_idx = i;
+        }
+    }
+
+    if max_dist > tolerance {
+        keep[max_idx] = true;
+        rdp(points, first, max_idx, tolerance, keep);
+        rdp(points, max_idx, last, tolerance, keep);
+    }
+}
+
+fn dist_to_segment(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let dx = b.0 - a.0;
+    let dy = b.1 - a.1;
+    let len_sq = dx * dx + dy * dy;
+
+    // Project the point onto the segment.
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+
+    let x = a.0 + t * dx;
+    let y = a.1 + t * dy;
+    ((p.0 - x).powi(2) + (p.1 - y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use svgdom::path::Path;
+
+    macro_rules! test {
+        ($name:ident, $tolerance:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut path = Path::from_str($in_text).unwrap();
+                simplify_lines(&mut path, $tolerance);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    test!(simplify_1, 0.5,
+        "M 10 10 L 20 10.2 L 30 9.9 L 40 10 L 40 20",
+        "M 10 10 L 40 10 L 40 20");
+
+    test!(simplify_2, 0.1,
+        "M 10 10 L 20 10.2 L 30 9.9 L 40 10 L 40 20",
+        "M 10 10 L 20 10.2 L 30 9.9 L 40 10 L 40 20");
+
+    // Curves and subpaths ends should be preserved.
+    test!(simplify_3, 1.0,
+        "M 10 10 L 20 10 L 30 10 C 40 10 40 20 30 20 L 20 20 L 10 20 Z M 50 50 L 60 50 L 70 50",
+        "M 10 10 L 30 10 C 40 10 40 20 30 20 L 10 20 Z M 50 50 L 70 50");
+}
@@ -17,62 +17,34 @@
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
 use svgdom::{
-    AttributeType,
     AttributeValue,
     Document,
+    Node,
 };
 
 use task::short::{EId, AId};
+use options::{
+    CleaningOptions,
+    ExternalRefsMode,
+};
 use error::{
     ErrorKind,
+    Feature,
     Result,
 };
 
-pub fn preclean_checks(doc: &Document) -> Result<()> {
-    check_for_unsupported_elements(doc)?;
-    check_for_script_attributes(doc)?;
-    check_for_conditional_attributes(doc)?;
-    check_for_external_xlink(doc)?;
-
-    Ok(())
-}
-
-fn check_for_unsupported_elements(doc: &Document) -> Result<()> {
-    for (_, node) in doc.descendants().svg() {
-        match node.tag_id().unwrap() {
-            EId::Script => {
-                return Err(ErrorKind::ScriptingIsNotSupported.into());
-            }
-              EId::Animate
-            | EId::AnimateColor
-            | EId::AnimateMotion
-            | EId::AnimateTransform
-            | EId::Set => {
-                return Err(ErrorKind::AnimationIsNotSupported.into());
-            }
-            _ => {}
-        }
-    }
-
-    Ok(())
-}
+pub fn preclean_checks(doc: &Document, options: &CleaningOptions) -> Result<()> {
+    check_for_conditional_attributes(doc, options)?;
 
-fn check_for_script_attributes(doc: &Document) -> Result<()> {
-    for (_, node) in doc.descendants().svg() {
-        for attr in node.attributes().iter() {
-            if     attr.is_graphical_event()
-                || attr.is_document_event()
-                || attr.is_animation_event()
-            {
-                return Err(ErrorKind::ScriptingIsNotSupported.into());
-            }
-        }
+    // External links will be removed by the 'sanitize' or 'external-references' tasks.
+    if !options.sanitize && options.external_refs != ExternalRefsMode::Remove {
+        check_for_external_xlink(doc)?;
     }
 
     Ok(())
 }
 
-fn check_for_conditional_attributes(doc: &Document) -> Result<()> {
+fn check_for_conditional_attributes(doc: &Document, options: &CleaningOptions) -> Result<()> {
     // TODO: what to do with 'requiredExtensions'?
 
     macro_rules! check_attr {
@@ -81,20 +53,34 @@ fn check_for_conditional_attributes(doc: &Document) -> Result<()> {
             if let Some(&AttributeValue::String(ref s)) = attrs.get_value($aid) {
                 if !s.is_empty() {
                     // NOTE: We are only care about non-empty attributes.
-                    return Err(ErrorKind::ConditionalProcessingIsNotSupported.into());
+                    return Err(ErrorKind::UnsupportedFeature(Feature::ConditionalProcessing).into());
                 }
             }
         )
     }
 
     for (_, node) in doc.descendants().svg() {
-        check_attr!(AId::RequiredFeatures, node);
+        // Will be evaluated by the 'resolve-switch' task.
+        if options.resolve_switch && is_in_switch(&node) {
+            continue;
+        }
+
+        // Will be evaluated by the 'remove-conditional-attributes' task.
+        let is_switch_child = node.parent().is_some_and(|p| p.is_tag_name(EId::Switch));
+        if !options.remove_conditional_attributes || is_switch_child {
+            check_attr!(AId::RequiredFeatures, node);
+        }
+
         check_attr!(AId::SystemLanguage, node);
     }
 
     Ok(())
 }
 
+fn is_in_switch(node: &Node) -> bool {
+    node.is_tag_name(EId::Switch) || node.parent().is_some_and(|p| p.is_tag_name(EId::Switch))
+}
+
 fn check_for_external_xlink(doc: &Document) -> Result<()> {
     for (_, node) in doc.descendants().svg() {
         if !node.has_attribute(AId::XlinkHref) {
@@ -111,7 +97,7 @@ fn check_for_external_xlink(doc: &Document) -> Result<()> {
 
         let attrs = node.attributes();
         if let AttributeValue::String(ref s) = *attrs.get_value(AId::XlinkHref).unwrap() {
-            return Err(ErrorKind::ExternalHrefIsNotSupported(s.clone()).into());
+            return Err(ErrorKind::UnsupportedFeature(Feature::ExternalHref(s.clone())).into());
         }
     }
 
@@ -128,7 +114,8 @@ mod tests {
             #[test]
             fn $name() {
                 let doc = Document::from_str($in_text).unwrap();
-                assert_eq!(preclean_checks(&doc).unwrap_err().full_chain(), $err);
+                let e = preclean_checks(&doc, &CleaningOptions::default()).unwrap_err();
+                assert_eq!(e.full_chain(), $err);
             }
         )
     }
@@ -138,19 +125,15 @@ mod tests {
             #[test]
             fn $name() {
                 let doc = Document::from_str($in_text).unwrap();
-                assert_eq!(preclean_checks(&doc).is_ok(), true);
+                assert_eq!(preclean_checks(&doc, &CleaningOptions::default()).is_ok(), true);
             }
         )
     }
 
-    test!(test_scripting_1, "<svg><script/></svg>",
-          "Error: scripting is not supported");
+    test_ok!(test_scripting_1, "<svg><script/></svg>");
+    test_ok!(test_scripting_2, "<svg onload=''/>");
 
-    test!(test_scripting_2, "<svg onload=''/>",
-          "Error: scripting is not supported");
-
-    test!(test_animation_1, "<svg><set/></svg>",
-          "Error: animation is not supported");
+    test_ok!(test_animation_1, "<svg><set/></svg>");
 
     test!(test_conditions_1, "<svg><switch requiredFeatures='text'/></svg>",
           "Error: conditional processing attributes is not supported");
@@ -160,4 +143,47 @@ mod tests {
 
     test_ok!(test_conditions_3, "<svg><switch requiredFeatures=''/></svg>");
     test_ok!(test_conditions_4, "<svg><switch systemLanguage=''/></svg>");
+
+    #[test]
+    fn test_conditions_5() {
+        let doc = Document::from_str(
+            "<svg><switch systemLanguage='en'><rect systemLanguage='de'/></switch></svg>"
+        ).unwrap();
+
+        let mut opt = CleaningOptions::default();
+        assert!(preclean_checks(&doc, &opt).is_err());
+
+        opt.resolve_switch = true;
+        assert!(preclean_checks(&doc, &opt).is_ok());
+    }
+
+    #[test]
+    fn test_conditions_6() {
+        let doc = Document::from_str(
+            "<svg><g requiredFeatures='http://www.w3.org/TR/SVG11/feature#Shape'/></svg>"
+        ).unwrap();
+
+        let mut opt = CleaningOptions::default();
+        assert!(preclean_checks(&doc, &opt).is_err());
+
+        opt.remove_conditional_attributes = true;
+        assert!(preclean_checks(&doc, &opt).is_ok());
+    }
+
+    #[test]
+    fn test_external_1() {
+        let doc = Document::from_str(
+            "<svg xmlns:xlink='http://www.w3.org/1999/xlink'><use xlink:href='a.svg#b'/></svg>"
+        ).unwrap();
+
+        let mut opt = CleaningOptions::default();
+        assert!(preclean_checks(&doc, &opt).is_err());
+
+        opt.external_refs = ExternalRefsMode::Remove;
+        assert!(preclean_checks(&doc, &opt).is_ok());
+
+        opt.external_refs = ExternalRefsMode::Keep;
+        opt.sanitize = true;
+        assert!(preclean_checks(&doc, &opt).is_ok());
+    }
 }
@@ -17,6 +17,7 @@
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
 use svgdom::{
+    AttributeValue,
     Document,
     ElementType,
     Node,
@@ -25,6 +26,9 @@ use svgdom::{
 use task::short::{EId, AId};
 
 pub fn regroup_gradient_stops(doc: &mut Document) {
+    flatten_links(doc);
+    inline_single_stops(doc);
+
     let mut nodes: Vec<Node> = doc.descendants()
         .filter(|n| n.is_gradient())
         .filter(|n| n.has_children())
@@ -88,6 +92,92 @@ pub fn regroup_gradient_stops(doc: &mut Document) {
     }
 }
 
+// Links gradients directly to the gradient with stops, skipping
+// gradients that only pass stops through. Such gradients will be removed
+// if they are no longer used.
+fn flatten_links(doc: &Document) {
+    let nodes: Vec<Node> = doc.descendants()
+        .filter(|n| n.is_gradient())
+        .filter(|n| n.has_attribute(AId::XlinkHref))
+        .collect();
+
+    let mut rm_nodes = Vec::new();
+    for mut node in nodes {
+        let link = match get_link(&node) {
+            Some(link) => link,
+            None => continue,
+        };
+
+        let mut target = link.clone();
+        while is_pass_through(&target) {
+            target = get_link(&target).unwrap();
+        }
+
+        if target != link {
+            node.set_attribute((AId::XlinkHref, target));
+            if !rm_nodes.contains(&link) {
+                rm_nodes.push(link);
+            }
+        }
+    }
+
+    // Removing a gradient can make a gradient that was linked by it unused.
+    while let Some(idx) = rm_nodes.iter().position(|n| !n.is_used()) {
+        rm_nodes.remove(idx).remove();
+    }
+}
+
+// Moves stops into the gradient, when it's the only one that uses them.
+fn inline_single_stops(doc: &Document) {
+    let nodes: Vec<Node> = doc.descendants()
+        .filter(|n| n.is_gradient())
+        .filter(|n| n.has_children() && !n.has_attribute(AId::XlinkHref))
+        .filter(|n| has_only_attributes(n, &[AId::Id]))
+        .filter(|n| n.uses_count() == 1)
+        .collect();
+
+    for mut node in nodes {
+        let mut link = node.linked_nodes().next().unwrap();
+        if !link.is_gradient() || link.has_children() || get_link(&link) != Some(node.clone()) {
+            continue;
+        }
+
+        while node.has_children() {
+            let mut c = node.first_child().unwrap();
+            c.detach();
+            link.append(&c);
+        }
+
+        link.remove_attribute(AId::XlinkHref);
+        node.remove();
+    }
+}
+
+// Checks that the gradient doesn't change anything except the link.
+//
+// Gradient attributes are resolved, so only visible ones are actually
+// inherited by other gradients.
+fn is_pass_through(node: &Node) -> bool {
+    !node.has_children() && get_link(node).is_some()
+        && has_only_attributes(node, &[AId::Id, AId::XlinkHref])
+}
+
+fn has_only_attributes(node: &Node, ids: &[AId]) -> bool {
+    node.attributes().iter().filter(|a| a.visible).all(|a| {
+        match a.id() {
+            Some(aid) => ids.contains(&aid),
+            None => false,
+        }
+    })
+}
+
+fn get_link(node: &Node) -> Option<Node> {
+    match node.attributes().get_value(AId::XlinkHref) {
+        Some(AttributeValue::Link(link)) => Some(link.clone()),
+        _ => None,
+    }
+}
+
 fn gen_id(doc: &Document, prefix: &str) -> String {
     let mut n = 1;
 
@@ -184,4 +274,93 @@ mod tests {
 </svg>
 ");
 
+    test!(flatten_1,
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0'/>
+        <stop offset='1'/>
+    </linearGradient>
+    <linearGradient id='lg2' xlink:href='#lg1'/>
+    <linearGradient id='lg3' xlink:href='#lg2'/>
+    <linearGradient id='lg4' x1='50' xlink:href='#lg3'/>
+    <linearGradient id='lg5' x1='100' xlink:href='#lg3'/>
+    <rect fill='url(#lg4)'/>
+    <rect fill='url(#lg5)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0'/>
+        <stop offset='1'/>
+    </linearGradient>
+    <linearGradient id='lg4' x1='50' xlink:href='#lg1'/>
+    <linearGradient id='lg5' x1='100' xlink:href='#lg1'/>
+    <rect fill='url(#lg4)'/>
+    <rect fill='url(#lg5)'/>
+</svg>
+");
+
+    // Gradient with own attributes can't be skipped.
+    test!(flatten_2,
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0'/>
+        <stop offset='1'/>
+    </linearGradient>
+    <linearGradient id='lg2' x1='50' xlink:href='#lg1'/>
+    <linearGradient id='lg3' xlink:href='#lg2'/>
+    <linearGradient id='lg4' xlink:href='#lg2'/>
+    <rect fill='url(#lg3)'/>
+    <rect fill='url(#lg4)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg2' x1='50'>
+        <stop offset='0'/>
+        <stop offset='1'/>
+    </linearGradient>
+    <linearGradient id='lg3' xlink:href='#lg2'/>
+    <linearGradient id='lg4' xlink:href='#lg2'/>
+    <rect fill='url(#lg3)'/>
+    <rect fill='url(#lg4)'/>
+</svg>
+");
+
+    test!(inline_1,
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0'/>
+        <stop offset='1'/>
+    </linearGradient>
+    <linearGradient id='lg2' x1='50' xlink:href='#lg1'/>
+    <rect fill='url(#lg2)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg2' x1='50'>
+        <stop offset='0'/>
+        <stop offset='1'/>
+    </linearGradient>
+    <rect fill='url(#lg2)'/>
+</svg>
+");
+
+    // Used directly.
+    test!(inline_2,
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0'/>
+        <stop offset='1'/>
+    </linearGradient>
+    <linearGradient id='lg2' x1='50' xlink:href='#lg1'/>
+    <rect fill='url(#lg1)'/>
+    <rect fill='url(#lg2)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0'/>
+        <stop offset='1'/>
+    </linearGradient>
+    <linearGradient id='lg2' x1='50' xlink:href='#lg1'/>
+    <rect fill='url(#lg1)'/>
+    <rect fill='url(#lg2)'/>
+</svg>
+");
 }
@@ -0,0 +1,468 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::borrow::Cow;
+
+use simplecss::Tokenizer;
+use simplecss::Token as CssToken;
+use xmlparser::{
+    self,
+    ElementEnd,
+    FromSpan,
+    StrSpan,
+    Token,
+};
+
+struct Rule<'a> {
+    selector: CssToken<'a>,
+    specificity: u32,
+    declarations: Vec<(&'a str, &'a str)>,
+}
+
+// A start tag with attributes, which are used by selectors.
+#[derive(Default)]
+struct Tag<'a> {
+    name: &'a str,
+    id: Option<&'a str>,
+    class: Option<&'a str>,
+    style: Option<StrSpan<'a>>,
+}
+
+// Prepares embedded stylesheets for the parser.
+//
+// 'svgdom' resolves '<style>' elements into presentation attributes and removes them,
+// but it applies rules in the order they are defined and doesn't support '!important'.
+// So we are sorting rules by the selector specificity and moving important
+// declarations to the end, stripping the '!important' keyword.
+//
+// The parser applies 'style' attributes after all the rules, which is correct
+// for normal declarations, but important ones must win. So declarations,
+// which are overridden by important rules, are removed from 'style' attributes.
+// This way the parser will produce a correct cascade:
+// normal rules < 'style' attribute < important rules.
+//
+// Stylesheets with unsupported selectors are left untouched.
+pub fn resolve_css<'a>(text: &'a str) -> Cow<'a, str> {
+    if !text.contains("<style") {
+        return Cow::Borrowed(text);
+    }
+
+    let styles = find_styles(text);
+    if styles.is_empty() {
+        return Cow::Borrowed(text);
+    }
+
+    let stylesheets: Vec<_> = styles.into_iter()
+        .filter_map(|(start, end)| parse_rules(&text[start..end]).map(|rules| (start, end, rules)))
+        .collect();
+
+    // A list of replaced ranges.
+    let mut edits = Vec::new();
+    let mut important = Vec::new();
+    for &(start, end, ref rules) in &stylesheets {
+        edits.push((start, end, prepare_css(rules, &mut important)));
+    }
+
+    if !important.is_empty() {
+        for_each_tag(text, |tag| {
+            let span = match tag.style {
+                Some(span) => span,
+                None => return,
+            };
+
+            let names: Vec<&str> = important.iter()
+                .filter(|&&(selector, _)| is_match(selector, tag))
+                .map(|&(_, name)| name)
+                .collect();
+
+            if let Some(style) = remove_declarations(span.to_str(), &names) {
+                edits.push((span.start(), span.end(), style));
+            }
+        });
+    }
+
+    edits.sort_by_key(|&(start, _, _)| start);
+
+    let mut new_text = String::with_capacity(text.len());
+    let mut pos = 0;
+    for (start, end, s) in edits {
+        new_text.push_str(&text[pos..start]);
+        new_text.push_str(&s);
+        pos = end;
+    }
+    new_text.push_str(&text[pos..]);
+
+    Cow::Owned(new_text)
+}
+
+// Returns ranges of '<style>' elements content, excluding CDATA markers.
+//
+// Elements are located by the XML tokenizer, so comments, CDATA and attribute values,
+// which contain '<style', are ignored. Elements with mixed content are skipped.
+pub fn find_styles(text: &str) -> Vec<(usize, usize)> {
+    let mut styles = Vec::new();
+    let mut is_style = false;
+    // Text and CDATA spans of the current '<style>' element.
+    let mut content: Option<Vec<StrSpan>> = None;
+
+    for token in xmlparser::Tokenizer::from_str(text) {
+        // Malformed files are handled by the parser.
+        let token = match token {
+            Ok(token) => token,
+            Err(_) => break,
+        };
+
+        match token {
+            Token::ElementStart(name) => {
+                is_style = name.to_str() == "style";
+                content = None;
+            }
+            Token::ElementEnd(ElementEnd::Open) => {
+                content = if is_style { Some(Vec::new()) } else { None };
+            }
+            Token::Text(span) | Token::Cdata(span) => {
+                if let Some(ref mut spans) = content {
+                    spans.push(span);
+                }
+            }
+            Token::ElementEnd(ElementEnd::Close(_)) => {
+                if let Some(spans) = content.take() {
+                    if spans.len() == 1 {
+                        styles.push((spans[0].start(), spans[0].end()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    styles
+}
+
+// Calls the closure for each start tag.
+fn for_each_tag<'a, F: FnMut(&Tag<'a>)>(text: &'a str, mut f: F) {
+    let mut tag: Option<Tag> = None;
+    for token in xmlparser::Tokenizer::from_str(text) {
+        let token = match token {
+            Ok(token) => token,
+            Err(_) => break,
+        };
+
+        match token {
+            Token::ElementStart(name) => {
+                tag = Some(Tag { name: name.to_str(), .. Tag::default() });
+            }
+            Token::Attribute(name, value) => {
+                if let Some(ref mut tag) = tag {
+                    match name.to_str() {
+                        "id" => tag.id = Some(value.to_str()),
+                        "class" => tag.class = Some(value.to_str()),
+                        "style" => tag.style = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            Token::ElementEnd(_) => {
+                if let Some(tag) = tag.take() {
+                    f(&tag);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_match(selector: &CssToken, tag: &Tag) -> bool {
+    match *selector {
+        CssToken::UniversalSelector => true,
+        CssToken::TypeSelector(name) => tag.name == name,
+        CssToken::ClassSelector(name) => {
+            tag.class.is_some_and(|class| class.split_whitespace().any(|c| c == name))
+        }
+        CssToken::IdSelector(name) => tag.id == Some(name),
+        _ => false,
+    }
+}
+
+// Removes declarations with the specified names from the 'style' attribute value.
+//
+// Returns `None` when nothing was removed.
+fn remove_declarations(style: &str, names: &[&str]) -> Option<String> {
+    let is_removed = |decl: &&str| {
+        let name = decl.split(':').next().unwrap_or("").trim();
+        names.contains(&name)
+    };
+
+    if !style.split(';').any(|decl| is_removed(&decl)) {
+        return None;
+    }
+
+    let decls: Vec<&str> = style.split(';').filter(|decl| !is_removed(decl)).collect();
+    Some(decls.join(";"))
+}
+
+// Returns the new stylesheet and collects important declarations names with their selectors.
+fn prepare_css<'r, 'a>(
+    rules: &'r [Rule<'a>],
+    important_names: &mut Vec<(&'r CssToken<'a>, &'a str)>
+) -> String {
+    let mut normal = Vec::new();
+    let mut important = Vec::new();
+    for rule in rules {
+        let mut normal_decls = Vec::new();
+        let mut important_decls = Vec::new();
+        for &(name, value) in &rule.declarations {
+            match strip_important(value) {
+                Some(value) => {
+                    important_decls.push((name, value));
+                    important_names.push((&rule.selector, name));
+                }
+                None => normal_decls.push((name, value)),
+            }
+        }
+
+        if !normal_decls.is_empty() {
+            normal.push((rule, normal_decls));
+        }
+
+        if !important_decls.is_empty() {
+            important.push((rule, important_decls));
+        }
+    }
+
+    // Sort is stable, so rules with the same specificity will preserve their order.
+    normal.sort_by_key(|&(rule, _)| rule.specificity);
+    important.sort_by_key(|&(rule, _)| rule.specificity);
+
+    let mut s = String::new();
+    for (rule, decls) in normal.into_iter().chain(important) {
+        write_selector(&rule.selector, &mut s);
+        s.push('{');
+        for (name, value) in decls {
+            s.push_str(name);
+            s.push(':');
+            s.push_str(value);
+            s.push(';');
+        }
+        s.push('}');
+    }
+
+    s
+}
+
+// Splits grouped selectors, so each rule will have only one selector.
+fn parse_rules<'a>(css: &'a str) -> Option<Vec<Rule<'a>>> {
+    let mut rules = Vec::new();
+    let mut tokenizer = Tokenizer::new(css);
+
+    'root: loop {
+        let mut selectors = Vec::new();
+
+        // Each selector must be followed by a comma or a block start,
+        // otherwise it's a compound one, which we do not support.
+        let mut prev_is_selector = false;
+        loop {
+            let token = tokenizer.parse_next().ok()?;
+            match token {
+                CssToken::EndOfStream => break 'root,
+                CssToken::BlockStart => break,
+                CssToken::Comma => {
+                    prev_is_selector = false;
+                    continue;
+                }
+                _ => {}
+            }
+
+            if prev_is_selector {
+                return None;
+            }
+
+            let specificity = match token {
+                CssToken::UniversalSelector => 0,
+                CssToken::TypeSelector(_) => 1,
+                CssToken::ClassSelector(_) => 10,
+                CssToken::IdSelector(_) => 100,
+                _ => return None,
+            };
+
+            selectors.push((token, specificity));
+            prev_is_selector = true;
+        }
+
+        let mut declarations = Vec::new();
+        loop {
+            match tokenizer.parse_next().ok()? {
+                CssToken::Declaration(name, value) => declarations.push((name, value)),
+                CssToken::BlockEnd | CssToken::EndOfStream => break,
+                _ => return None,
+            }
+        }
+
+        for (selector, specificity) in selectors {
+            rules.push(Rule {
+                selector,
+                specificity,
+                declarations: declarations.clone(),
+            });
+        }
+    }
+
+    Some(rules)
+}
+
+pub fn strip_important(value: &str) -> Option<&str> {
+    let idx = value.rfind('!')?;
+    if value[idx + 1..].trim().eq_ignore_ascii_case("important") {
+        Some(value[..idx].trim())
+    } else {
+        None
+    }
+}
+
+fn write_selector(selector: &CssToken, s: &mut String) {
+    match *selector {
+        CssToken::UniversalSelector => s.push('*'),
+        CssToken::TypeSelector(name) => s.push_str(name),
+        CssToken::ClassSelector(name) => {
+            s.push('.');
+            s.push_str(name);
+        }
+        CssToken::IdSelector(name) => {
+            s.push('#');
+            s.push_str(name);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let text = resolve_css($in_text);
+                let doc = Document::from_str(&text).unwrap();
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    macro_rules! test_css {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                assert_eq!(resolve_css($in_text), $out_text);
+            }
+        )
+    }
+
+    test!(specificity_1,
+"<svg>
+    <style>
+        #rect1 { fill: red; }
+        .cls1 { fill: green; stroke: blue; }
+        rect { fill: blue; stroke: green; }
+    </style>
+    <rect id='rect1' class='cls1'/>
+    <rect class='cls1'/>
+    <rect/>
+</svg>",
+"<svg>
+    <rect id='rect1' fill='#ff0000' stroke='#0000ff'/>
+    <rect fill='#008000' stroke='#0000ff'/>
+    <rect fill='#0000ff' stroke='#008000'/>
+</svg>
+");
+
+    test!(important_1,
+"<svg>
+    <style>
+        .cls1 { fill: green !important; }
+        #rect1 { fill: red; }
+    </style>
+    <rect id='rect1' class='cls1'/>
+</svg>",
+"<svg>
+    <rect id='rect1' fill='#008000'/>
+</svg>
+");
+
+    test!(important_2,
+"<svg>
+    <style>
+        .cls1 { fill: blue !important; }
+    </style>
+    <rect class='cls1' style='fill:black;stroke:red'/>
+</svg>",
+"<svg>
+    <rect fill='#0000ff' stroke='#ff0000'/>
+</svg>
+");
+
+    // The 'style' attribute has a higher priority than normal rules.
+    test!(important_3,
+"<svg>
+    <style>
+        #rect1 { fill: blue; stroke: green !important; }
+    </style>
+    <rect id='rect1' style='fill:black;stroke:red'/>
+</svg>",
+"<svg>
+    <rect id='rect1' fill='#000000' stroke='#008000'/>
+</svg>
+");
+
+    test_css!(important_4,
+"<svg><style>rect{fill:red !important}</style><rect style='fill:blue'/><g style='fill:blue'/></svg>",
+"<svg><style>rect{fill:red;}</style><rect style=''/><g style='fill:blue'/></svg>");
+
+    test!(cdata_1,
+"<svg>
+    <style><![CDATA[
+        .cls1, .cls2 { fill: red; }
+    ]]></style>
+    <rect class='cls1'/>
+    <rect class='cls2'/>
+</svg>",
+"<svg>
+    <rect fill='#ff0000'/>
+    <rect fill='#ff0000'/>
+</svg>
+");
+
+    test_css!(order_1,
+"<style>rect{fill:red} *{fill:none} .a, #b{stroke:red !important;fill:blue}</style>",
+"<style>*{fill:none;}rect{fill:red;}.a{fill:blue;}#b{fill:blue;}.a{stroke:red;}#b{stroke:red;}</style>");
+
+    // Compound selectors are not supported.
+    test_css!(skip_1,
+"<style>rect.a{fill:red}</style>",
+"<style>rect.a{fill:red}</style>");
+
+    test_css!(skip_2,
+"<styles>rect{fill:red}</styles>",
+"<styles>rect{fill:red}</styles>");
+
+    test_css!(skip_3,
+"<svg><!-- <style>rect{fill:red}</style> --><text title='<style>'>rect{fill:red}</text></svg>",
+"<svg><!-- <style>rect{fill:red}</style> --><text title='<style>'>rect{fill:red}</text></svg>");
+}
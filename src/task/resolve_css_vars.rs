@@ -0,0 +1,411 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::borrow::Cow;
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
+use foreign::markup_len;
+use task::resolve_css::{
+    find_styles,
+    strip_important,
+};
+
+// A nested variables limit, which also prevents circular references.
+const MAX_DEPTH: usize = 16;
+
+// Resolves CSS variables, aka 'var(--name)', in attributes and embedded stylesheets,
+// since they are not supported by the parser.
+//
+// Variables are taken from the ':root' rules, which are removed afterwards.
+// Variables from `defines` take precedence over the embedded ones.
+//
+// Variables, which are also set by other rules or 'style' attributes, depend on the cascade,
+// so they are left untouched, unless set via `defines`.
+// Undefined variables are replaced with a fallback value, when it's set.
+pub fn resolve_css_variables<'a>(text: &'a str, defines: &[(String, String)]) -> Cow<'a, str> {
+    if !text.contains("var(") && !text.contains(":root") {
+        return Cow::Borrowed(text);
+    }
+
+    let mut vars = HashMap::new();
+    let mut skipped = HashSet::new();
+
+    let mut styles = Vec::new();
+    for (start, end) in find_styles(text) {
+        styles.push((start, end, remove_root_rules(&text[start..end], &mut vars, &mut skipped)));
+    }
+
+    for_each_start_tag(text, |tag| {
+        collect_custom_properties(tag, &mut skipped);
+    });
+
+    for name in &skipped {
+        vars.remove(name);
+    }
+
+    for (name, value) in defines {
+        let name = if name.starts_with("--") { name.clone() } else { format!("--{}", name) };
+        skipped.remove(&name);
+        vars.insert(name, value.trim().to_string());
+    }
+
+    resolve_nested(&mut vars, &mut skipped);
+
+    let mut new_text = String::with_capacity(text.len());
+    let mut pos = 0;
+    for (start, end, css) in styles {
+        new_text.push_str(&text[pos..start]);
+        match substitute(&css, &vars, &skipped) {
+            Some(new_css) => new_text.push_str(&new_css),
+            None => new_text.push_str(&css),
+        }
+        pos = end;
+    }
+    new_text.push_str(&text[pos..]);
+
+    let mut result = String::with_capacity(new_text.len());
+    let mut s = new_text.as_str();
+    while let Some(idx) = s.find('<') {
+        result.push_str(&s[..idx]);
+        s = &s[idx..];
+
+        let len = match markup_len(s) {
+            Some(len) => len,
+            None => break,
+        };
+
+        let tag = &s[..len];
+        match if is_start_tag(tag) { substitute(tag, &vars, &skipped) } else { None } {
+            Some(new_tag) => result.push_str(&new_tag),
+            None => result.push_str(tag),
+        }
+
+        s = &s[len..];
+    }
+    result.push_str(s);
+
+    Cow::Owned(result)
+}
+
+// Removes custom properties from the ':root' rules and collects them.
+//
+// Custom properties from other rules are marked as skipped.
+fn remove_root_rules(css: &str, vars: &mut HashMap<String, String>,
+                     skipped: &mut HashSet<String>) -> String {
+    let mut new_css = String::with_capacity(css.len());
+    let mut s = css;
+    while let Some((start, block_start, block_end)) = find_root_rule(s) {
+        new_css.push_str(&s[..start]);
+        collect_custom_properties(&s[..start], skipped);
+
+        let mut decls = Vec::new();
+        for decl in s[block_start..block_end].split(';') {
+            let (name, value) = match decl.find(':') {
+                Some(idx) => (decl[..idx].trim(), decl[idx + 1..].trim()),
+                None => continue,
+            };
+
+            if name.starts_with("--") {
+                let value = strip_important(value).unwrap_or(value);
+                vars.insert(name.to_string(), value.to_string());
+            } else {
+                decls.push(decl.trim());
+            }
+        }
+
+        // Keep regular declarations.
+        if !decls.is_empty() {
+            new_css.push_str(":root{");
+            new_css.push_str(&decls.join(";"));
+            new_css.push('}');
+        }
+
+        s = &s[block_end + 1..];
+    }
+
+    collect_custom_properties(s, skipped);
+    new_css.push_str(s);
+
+    new_css
+}
+
+// Returns the ':root' selector position and its block content range.
+fn find_root_rule(css: &str) -> Option<(usize, usize, usize)> {
+    let mut pos = 0;
+    loop {
+        let start = pos + css[pos..].find(":root")?;
+        pos = start + 5;
+
+        // Only a standalone selector is supported, so it must be a start of a rule.
+        let prev = css[..start].trim_end();
+        if !(prev.is_empty() || prev.ends_with('}') || prev.ends_with("*/")) {
+            continue;
+        }
+
+        let rest = css[pos..].trim_start();
+        if !rest.starts_with('{') {
+            continue;
+        }
+
+        let block_start = css.len() - rest.len() + 1;
+        let block_end = block_start + css[block_start..].find('}')?;
+
+        return Some((start, block_start, block_end));
+    }
+}
+
+// Collects custom properties names, like '--name:', from declarations.
+fn collect_custom_properties(s: &str, names: &mut HashSet<String>) {
+    let mut pos = 0;
+    while let Some(idx) = s[pos..].find("--") {
+        let start = pos + idx;
+        pos = start + 2;
+
+        let prev = s[..start].chars().next_back();
+        if !prev.is_none_or(|c| c == '{' || c == ';' || c == '"' || c == '\'' || c.is_whitespace()) {
+            continue;
+        }
+
+        let len = s[pos..].find(|c: char| !is_ident_char(c)).unwrap_or(s.len() - pos);
+        if s[pos + len..].trim_start().starts_with(':') {
+            names.insert(s[start..pos + len].to_string());
+        }
+        pos += len;
+    }
+}
+
+// Resolves variables, which are referencing other variables.
+//
+// Variables, which cannot be resolved, are marked as skipped.
+fn resolve_nested(vars: &mut HashMap<String, String>, skipped: &mut HashSet<String>) {
+    for _ in 0..MAX_DEPTH {
+        let mut is_changed = false;
+        let names: Vec<String> = vars.keys().cloned().collect();
+        for name in names {
+            if let Some(value) = substitute(&vars[&name], vars, skipped) {
+                vars.insert(name, value);
+                is_changed = true;
+            }
+        }
+
+        if !is_changed {
+            break;
+        }
+    }
+
+    let unresolved: Vec<String> = vars.iter()
+        .filter(|&(_, value)| find_var(value).is_some())
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    for name in unresolved {
+        vars.remove(&name);
+        skipped.insert(name);
+    }
+}
+
+// Replaces 'var()' functions with variables values or fallback values.
+//
+// Returns `None` when nothing was replaced.
+fn substitute(s: &str, vars: &HashMap<String, String>, skipped: &HashSet<String>) -> Option<String> {
+    let mut new_s = String::new();
+    let mut is_changed = false;
+
+    let mut rest = s;
+    while let Some(idx) = find_var(rest) {
+        let args_start = idx + 4;
+        let args_end = match closing_paren(&rest[args_start..]) {
+            Some(len) => args_start + len,
+            None => break,
+        };
+
+        let args = &rest[args_start..args_end];
+        let (name, fallback) = match args.find(',') {
+            Some(i) => (args[..i].trim(), Some(args[i + 1..].trim())),
+            None => (args.trim(), None),
+        };
+
+        let value = match vars.get(name) {
+            Some(value) => Some(Cow::Borrowed(value.as_str())),
+            None if skipped.contains(name) => None,
+            None => fallback.map(|f| {
+                substitute(f, vars, skipped).map(Cow::Owned).unwrap_or(Cow::Borrowed(f))
+            }),
+        };
+
+        match value {
+            Some(value) => {
+                new_s.push_str(&rest[..idx]);
+                new_s.push_str(&value);
+                is_changed = true;
+            }
+            None => new_s.push_str(&rest[..args_end + 1]),
+        }
+
+        rest = &rest[args_end + 1..];
+    }
+
+    new_s.push_str(rest);
+
+    if is_changed { Some(new_s) } else { None }
+}
+
+// Returns the position of the next 'var(' function.
+fn find_var(s: &str) -> Option<usize> {
+    let mut pos = 0;
+    loop {
+        let idx = pos + s[pos..].find("var(")?;
+        if !s[..idx].chars().next_back().is_some_and(is_ident_char) {
+            return Some(idx);
+        }
+        pos = idx + 4;
+    }
+}
+
+// Returns the position of the closing parenthesis, taking nested ones into account.
+fn closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+fn is_start_tag(tag: &str) -> bool {
+    !(tag.starts_with("<!") || tag.starts_with("<?") || tag.starts_with("</"))
+}
+
+fn for_each_start_tag<F: FnMut(&str)>(text: &str, mut f: F) {
+    let mut s = text;
+    while let Some(idx) = s.find('<') {
+        s = &s[idx..];
+        let len = match markup_len(s) {
+            Some(len) => len,
+            None => break,
+        };
+
+        if is_start_tag(&s[..len]) {
+            f(&s[..len]);
+        }
+
+        s = &s[len..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+    use task::resolve_css;
+
+    macro_rules! test {
+        ($name:ident, $defines:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let defines: Vec<(String, String)> = $defines.iter()
+                    .map(|&(n, v): &(&str, &str)| (n.to_string(), v.to_string())).collect();
+                let text = resolve_css_variables($in_text, &defines);
+                let text = resolve_css(&text);
+                let doc = Document::from_str(&text).unwrap();
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    macro_rules! test_text {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                assert_eq!(resolve_css_variables($in_text, &[]), $out_text);
+            }
+        )
+    }
+
+    test!(root_1, [],
+"<svg>
+    <style>
+        :root { --main: red; --width: 2 }
+        .cls1 { stroke: var(--main); }
+    </style>
+    <rect class='cls1' fill='var(--main)' style='stroke-width:var(--width)'/>
+</svg>",
+"<svg>
+    <rect fill='#ff0000' stroke='#ff0000' stroke-width='2'/>
+</svg>
+");
+
+    test!(define_1, [("main", "green"), ("--width", "3")],
+"<svg>
+    <style>:root { --main: red; }</style>
+    <rect fill='var(--main)' stroke-width='var(--width)'/>
+</svg>",
+"<svg>
+    <rect fill='#008000' stroke-width='3'/>
+</svg>
+");
+
+    test!(fallback_1, [],
+"<svg>
+    <rect fill='var(--main, var(--other, blue))'/>
+</svg>",
+"<svg>
+    <rect fill='#0000ff'/>
+</svg>
+");
+
+    test!(nested_1, [],
+"<svg>
+    <style>:root { --base: green; --main: var(--base); }</style>
+    <rect fill='var(--main)'/>
+</svg>",
+"<svg>
+    <rect fill='#008000'/>
+</svg>
+");
+
+    // Variables redefined by other rules depend on the cascade.
+    test_text!(skip_1,
+"<svg><style>:root{--main:red} .dark{--main:blue}</style><rect fill='var(--main)'/></svg>",
+"<svg><style> .dark{--main:blue}</style><rect fill='var(--main)'/></svg>");
+
+    test_text!(skip_2,
+"<svg><style>:root{--main:red}</style><g style='--main:blue'><rect fill='var(--main)'/></g></svg>",
+"<svg><style></style><g style='--main:blue'><rect fill='var(--main)'/></g></svg>");
+
+    // Circular references.
+    test_text!(skip_3,
+"<svg><style>:root{--a:var(--b);--b:var(--a)}</style><rect fill='var(--a, red)'/></svg>",
+"<svg><style></style><rect fill='var(--a, red)'/></svg>");
+
+    test_text!(keep_1,
+"<svg><style>:root{fill:red;--a:blue}</style><text>var(--a)</text></svg>",
+"<svg><style>:root{fill:red}</style><text>var(--a)</text></svg>");
+}
@@ -34,6 +34,11 @@ use error::{
     ErrorKind,
     Result,
 };
+use warning::{
+    self,
+    Warning,
+    WarningCode,
+};
 
 /// Resolve attributes of `linearGradient` elements.
 ///
@@ -125,8 +130,8 @@ pub fn resolve_stop_attributes(doc: &Document) -> Result<()> {
             } else {
                 if idx == 0 {
                     // Allow first stop to not have an offset.
-                    warn!("The 'stop' element must have an 'offset' attribute. \
-                           Fallback to 'offset=0'.");
+                    warning::add(Warning::new(WarningCode::MissingOffset,
+                        "the 'stop' element must have an 'offset' attribute, fallback to 'offset=0'"));
                     node.set_attribute((AId::Offset, Length::zero()));
                 } else {
                     return Err(ErrorKind::MissingAttribute("stop".to_string(),
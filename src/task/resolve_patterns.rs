@@ -0,0 +1,191 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::{EId, AId};
+use task::utils::{self, AnimatedAttributes};
+
+// Attributes that can be inherited via 'xlink:href'.
+//
+// Details: https://www.w3.org/TR/SVG/pservers.html#PatternElementHrefAttribute
+static INHERITED_ATTRS: &[AId] = &[
+    AId::X,
+    AId::Y,
+    AId::Width,
+    AId::Height,
+    AId::PatternUnits,
+    AId::PatternContentUnits,
+    AId::PatternTransform,
+    AId::ViewBox,
+    AId::PreserveAspectRatio,
+];
+
+// Merges patterns that are referenced only by other patterns via 'xlink:href'
+// into the referencing ones.
+//
+// Inherited attributes and content are copied from the referenced pattern,
+// which is removed after that, so the output is never bigger.
+pub fn resolve_patterns(doc: &Document, keep_ids: &[String]) {
+    let animated = AnimatedAttributes::new(doc);
+
+    // Repeat until all chains are resolved.
+    loop {
+        let node = doc.descendants()
+                      .filter(|n| n.is_tag_name(EId::Pattern))
+                      .find(|n| can_resolve(n, &animated, keep_ids));
+
+        match node {
+            Some(mut node) => resolve(&mut node),
+            None => break,
+        }
+    }
+}
+
+fn can_resolve(node: &Node, animated: &AnimatedAttributes, keep_ids: &[String]) -> bool {
+    let link = match get_link(node) {
+        Some(link) => link,
+        None => return false,
+    };
+
+    // The linked pattern should be used only by this pattern.
+    if !link.is_tag_name(EId::Pattern) || link.uses_count() != 1 || link == *node {
+        return false;
+    }
+
+    if utils::is_kept_id(&link, keep_ids) {
+        return false;
+    }
+
+    !animated.is_animated(node) && !animated.is_animated(&link)
+}
+
+fn resolve(node: &mut Node) {
+    let mut link = get_link(node).unwrap();
+
+    for aid in INHERITED_ATTRS {
+        if !node.has_attribute(*aid) {
+            if let Some(attr) = link.attributes().get(*aid).cloned() {
+                node.set_attribute(attr);
+            }
+        }
+    }
+
+    // Content is inherited only when the pattern doesn't have its own.
+    if !node.children().any(|n| n.is_svg_element()) {
+        let children: Vec<Node> = link.children().collect();
+        for child in &children {
+            node.append(child);
+        }
+    }
+
+    // Continue the chain.
+    match get_link(&link) {
+        Some(next) => node.set_attribute((AId::XlinkHref, next)),
+        None => node.remove_attribute(AId::XlinkHref),
+    }
+
+    link.remove();
+}
+
+fn get_link(node: &Node) -> Option<Node> {
+    match node.attributes().get_value(AId::XlinkHref) {
+        Some(AttributeValue::Link(link)) => Some(link.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| resolve_patterns(doc, &[]), $in_text, $out_text);
+        )
+    }
+
+    test!(resolve_1,
+"<svg>
+    <pattern id='p1' height='10' patternUnits='userSpaceOnUse' width='10'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <pattern id='p2' patternTransform='scale(2)' xlink:href='#p1'/>
+    <rect fill='url(#p2)'/>
+</svg>",
+"<svg>
+    <pattern id='p2' height='10' patternTransform='scale(2)' patternUnits='userSpaceOnUse' width='10'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <rect fill='url(#p2)'/>
+</svg>
+");
+
+    // Own attributes and content have a priority.
+    test!(resolve_2,
+"<svg>
+    <pattern id='p1' height='10' width='10'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <pattern id='p2' width='20' xlink:href='#p1'>
+        <circle r='5'/>
+    </pattern>
+    <rect fill='url(#p2)'/>
+</svg>",
+"<svg>
+    <pattern id='p2' height='10' width='20'>
+        <circle r='5'/>
+    </pattern>
+    <rect fill='url(#p2)'/>
+</svg>
+");
+
+    test!(resolve_chain_1,
+"<svg>
+    <pattern id='p1' height='10' width='10'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <pattern id='p2' x='5' xlink:href='#p1'/>
+    <pattern id='p3' y='5' xlink:href='#p2'/>
+    <rect fill='url(#p3)'/>
+</svg>",
+"<svg>
+    <pattern id='p3' height='10' width='10' x='5' y='5'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <rect fill='url(#p3)'/>
+</svg>
+");
+
+    // The linked pattern is used by an another element.
+    test_eq!(keep_1,
+"<svg>
+    <pattern id='p1' height='10' width='10'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <pattern id='p2' xlink:href='#p1'/>
+    <rect fill='url(#p1)'/>
+    <rect fill='url(#p2)'/>
+</svg>
+");
+}
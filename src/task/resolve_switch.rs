@@ -0,0 +1,213 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Document,
+    Node,
+    NodeType,
+};
+
+use task::short::EId;
+use task::utils::animation::is_animation;
+use task::utils::conditional::{
+    self,
+    CONDITIONAL_ATTRS,
+};
+
+// Replaces 'switch' elements with their first child, for which conditional processing
+// attributes evaluate to 'true'.
+//
+// The 'switch' element is removed completely when none of the children match
+// and is converted into a group when it has attributes or non-rendering children,
+// like 'title' or animations.
+pub fn resolve_switch(doc: &mut Document, languages: &[String]) {
+    let switches: Vec<Node> = doc.descendants().filter(|n| n.is_tag_name(EId::Switch)).collect();
+
+    // Process nested elements first, so an outer 'switch' will see the resolved content.
+    for mut node in switches.into_iter().rev() {
+        if !conditional::eval_conditions(&node, languages) {
+            node.remove();
+            continue;
+        }
+
+        resolve(&mut node, languages);
+    }
+}
+
+fn resolve(node: &mut Node, languages: &[String]) {
+    let mut chosen = None;
+    for mut child in node.children().filter(is_candidate).collect::<Vec<_>>() {
+        if chosen.is_none() && conditional::eval_conditions(&child, languages) {
+            child.remove_attributes(CONDITIONAL_ATTRS);
+            chosen = Some(child);
+        } else {
+            child.remove();
+        }
+    }
+
+    node.remove_attributes(CONDITIONAL_ATTRS);
+
+    let has_other = node.children()
+                        .any(|n| n.node_type() == NodeType::Element && !is_candidate(&n));
+    if node.attributes().is_empty() && !has_other {
+        if let Some(mut child) = chosen {
+            child.detach();
+            node.insert_before(&child);
+        }
+
+        node.remove();
+    } else {
+        node.set_tag_name(EId::G);
+    }
+}
+
+// Checks that the element is evaluated by the 'switch'.
+//
+// Descriptive and animation elements are not rendered,
+// so they are never chosen.
+fn is_candidate(node: &Node) -> bool {
+    if node.node_type() != NodeType::Element {
+        return false;
+    }
+
+    match node.tag_id() {
+        Some(EId::Title) | Some(EId::Desc) | Some(EId::Metadata) => false,
+        Some(id) => !is_animation(id),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &mut Document| resolve_switch(doc, &["en".to_string()]),
+                       $in_text, $out_text);
+        )
+    }
+
+    test!(resolve_1,
+"<svg>
+    <switch>
+        <rect systemLanguage='ru' width='10'/>
+        <rect systemLanguage='en-US, de' width='20'/>
+        <rect width='30'/>
+    </switch>
+</svg>",
+"<svg>
+    <rect width='20'/>
+</svg>
+");
+
+    test!(resolve_2,
+"<svg>
+    <switch>
+        <foreignObject requiredExtensions='http://example.com/ext'/>
+        <text requiredFeatures='http://www.w3.org/TR/SVG11/feature#Font'/>
+        <rect requiredFeatures='http://www.w3.org/TR/SVG11/feature#Shape'/>
+    </switch>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    // An empty attribute evaluates to 'false'.
+    test!(resolve_3,
+"<svg>
+    <switch>
+        <rect requiredFeatures='' width='10'/>
+        <rect width='20'/>
+    </switch>
+</svg>",
+"<svg>
+    <rect width='20'/>
+</svg>
+");
+
+    test!(resolve_4,
+"<svg>
+    <switch>
+        <foreignObject requiredExtensions='http://www.w3.org/1999/xhtml'/>
+        <text>Text</text>
+    </switch>
+</svg>",
+"<svg>
+    <foreignObject/>
+</svg>
+");
+
+    test!(rm_switch_1,
+"<svg>
+    <switch>
+        <rect systemLanguage='ru'/>
+    </switch>
+    <switch systemLanguage='de'>
+        <rect/>
+    </switch>
+</svg>",
+"<svg/>
+");
+
+    test!(to_group_1,
+"<svg>
+    <switch fill='#ff0000'>
+        <rect systemLanguage='ru'/>
+        <rect/>
+    </switch>
+</svg>",
+"<svg>
+    <g fill='#ff0000'>
+        <rect/>
+    </g>
+</svg>
+");
+
+    test!(to_group_2,
+"<svg>
+    <switch>
+        <title>Title</title>
+        <rect/>
+    </switch>
+</svg>",
+"<svg>
+    <g>
+        <title>Title</title>
+        <rect/>
+    </g>
+</svg>
+");
+
+    test!(nested_1,
+"<svg>
+    <switch>
+        <switch systemLanguage='en'>
+            <rect systemLanguage='ru'/>
+            <circle/>
+        </switch>
+        <rect/>
+    </switch>
+</svg>",
+"<svg>
+    <circle/>
+</svg>
+");
+}
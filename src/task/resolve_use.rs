@@ -17,15 +17,22 @@
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
 use svgdom::{
+    AttributeType,
     AttributeValue,
     Document,
+    ElementType,
     Length,
+    Node,
     Transform,
 };
 
 use task::short::{EId, AId, Unit};
+use task::utils::{self, AnimatedAttributes};
 
-pub fn resolve_use(doc: &Document) {
+// We should run it before groups ungrouping and transforms applying,
+// so they can process resolved elements too.
+pub fn resolve_use(doc: &Document, keep_ids: &[String]) {
+    let animated = AnimatedAttributes::new(doc);
     let mut nodes = Vec::new();
 
     for node in doc.descendants().filter(|n| n.is_tag_name(EId::Use)) {
@@ -34,6 +41,26 @@ pub fn resolve_use(doc: &Document) {
             continue;
         }
 
+        // The 'use' node id will be lost after resolving.
+        if utils::is_kept_id(&node, keep_ids) {
+            continue;
+        }
+
+        // Non-SVG attributes will be lost after resolving too.
+        if utils::has_accessibility_attrs(&node) {
+            continue;
+        }
+
+        // Event handlers can rely on the 'use' element as an event target.
+        if utils::has_event_attrs(&node) {
+            continue;
+        }
+
+        // Animations of the 'use' element will be lost after resolving too.
+        if animated.is_animated(&node) {
+            continue;
+        }
+
         if let Some(value) = node.attributes().get_value(AId::XlinkHref) {
             if let AttributeValue::Link(ref link) = *value {
 
@@ -43,18 +70,20 @@ pub fn resolve_use(doc: &Document) {
                     continue;
                 }
 
-                // 'symbol' elements can't be simply replaced.
-                if link.is_tag_name(EId::Symbol) {
+                // Only shapes and groups can be simply replaced.
+                // 'symbol' and 'svg' elements are using 'width' and 'height'
+                // from the 'use' element, and 'use' elements linked to other 'use' elements
+                // will be resolved on the next pass.
+                if !is_valid_link(link) {
                     continue;
                 }
 
-                // Ignore 'use' elements linked to other 'use' elements.
-                if link.is_tag_name(EId::Use) {
+                // Element should be used only once.
+                if link.uses_count() != 1 {
                     continue;
                 }
 
-                // Element should be used only once.
-                if link.uses_count() != 1 {
+                if !can_merge_attributes(&node, link) {
                     continue;
                 }
 
@@ -67,34 +96,41 @@ pub fn resolve_use(doc: &Document) {
         // Unlink 'use'.
         node.remove_attribute(AId::XlinkHref);
 
-        {
-            // 'use' element support 'x', 'y' and 'transform' attributes and we should process them
-            // so we apply translate transform to the linked element transform.
+        // 'use' element support 'x', 'y' and 'transform' attributes and we should process them
+        // so we apply translate transform to the linked element transform.
+        let ts = {
+            let attrs = node.attributes();
 
-            let mut attrs = node.attributes_mut();
+            let x = get_value!(attrs, Length, AId::X, Length::zero());
+            let y = get_value!(attrs, Length, AId::Y, Length::zero());
 
-            // 'x' or 'y' should be set.
-            if attrs.contains(AId::X) || attrs.contains(AId::Y) {
-                let x = get_value!(attrs, Length, AId::X, Length::zero());
-                let y = get_value!(attrs, Length, AId::Y, Length::zero());
+            let mut ts = get_value!(attrs, Transform, AId::Transform, Transform::default());
+            ts.translate(x.num, y.num);
 
-                // We can apply 'x' and 'y' to transform only when they have 'none' units.
-                if !(x.unit == Unit::None && y.unit == Unit::None) {
-                    continue;
-                }
+            // The 'use' transform must be applied before the linked element one.
+            let link_attrs = link.attributes();
+            let link_ts = get_value!(link_attrs, Transform, AId::Transform, Transform::default());
+            ts.append(&link_ts);
 
-                let mut ts = get_value!(attrs, Transform, AId::Transform, Transform::default());
-                ts.translate(x.num, y.num);
+            ts
+        };
 
-                attrs.insert_from(AId::Transform, ts);
-                attrs.remove(AId::X);
-                attrs.remove(AId::Y);
-            }
+        if !ts.is_default() {
+            link.set_attribute((AId::Transform, ts));
         }
 
         // Copy attributes.
         for attr in node.attributes().iter() {
-            link.set_attribute(attr.clone());
+            if let Some(aid) = attr.id() {
+                if SKIP_ATTRIBUTES.contains(&aid) {
+                    continue;
+                }
+            }
+
+            // Linked element attributes have a higher priority.
+            if !link.has_attribute(attr.name.into_ref()) {
+                link.set_attribute(attr.clone());
+            }
         }
 
         // TODO: maybe just change the tag name
@@ -105,6 +141,48 @@ pub fn resolve_use(doc: &Document) {
     }
 }
 
+// This attributes are already processed or doesn't affect the linked element.
+static SKIP_ATTRIBUTES: &[AId] = &[
+    AId::Id,
+    AId::X,
+    AId::Y,
+    AId::Width,
+    AId::Height,
+    AId::Transform,
+];
+
+fn is_valid_link(link: &Node) -> bool {
+    link.is_shape() || link.is_tag_name(EId::G)
+}
+
+fn can_merge_attributes(node: &Node, link: &Node) -> bool {
+    let attrs = node.attributes();
+
+    // We can apply 'x' and 'y' to transform only when they have 'none' units.
+    for aid in &[AId::X, AId::Y] {
+        if let Some(&AttributeValue::Length(len)) = attrs.get_value(*aid) {
+            if len.unit != Unit::None {
+                return false;
+            }
+        }
+    }
+
+    // Inheritable attributes of the 'use' element will be overridden
+    // by the linked element ones. But non-inheritable, like 'opacity',
+    // will be applied twice, so we can't merge them.
+    for (aid, attr) in attrs.iter_svg() {
+        if SKIP_ATTRIBUTES.contains(&aid) || aid == AId::XlinkHref {
+            continue;
+        }
+
+        if !attr.is_inheritable() && link.has_attribute(aid) {
+            return false;
+        }
+    }
+
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,7 +190,7 @@ mod tests {
 
     macro_rules! test {
         ($name:ident, $in_text:expr, $out_text:expr) => (
-            base_test!($name, resolve_use, $in_text, $out_text);
+            base_test!($name, |doc: &Document| resolve_use(doc, &[]), $in_text, $out_text);
         )
     }
 
@@ -199,6 +277,36 @@ mod tests {
 "
 );
 
+    test!(resolve_7,
+"<svg>
+    <defs>
+        <rect id='r1' fill='#00ff00' transform='scale(2)' width='10'/>
+    </defs>
+    <use id='u1' fill='#ff0000' stroke='#ff0000' width='20' x='10' xlink:href='#r1'/>
+</svg>",
+"<svg>
+    <defs/>
+    <rect id='r1' fill='#00ff00' stroke='#ff0000' transform='matrix(2 0 0 2 10 0)' width='10'/>
+</svg>
+");
+
+    test!(resolve_8,
+"<svg>
+    <defs>
+        <g id='g1'>
+            <rect/>
+        </g>
+    </defs>
+    <use xlink:href='#g1' y='10'/>
+</svg>",
+"<svg>
+    <defs/>
+    <g id='g1' transform='translate(0 10)'>
+        <rect/>
+    </g>
+</svg>
+");
+
     test_eq!(keep_1,
 "<svg>
     <rect id='r1'/>
@@ -215,6 +323,22 @@ mod tests {
     </defs>
     <use xlink:href='#r1'/>
 </svg>
+");
+
+    // Non-inheritable attributes can't be merged.
+    // Units should be resolved.
+    // Only shapes and groups are supported.
+    test_eq!(keep_4,
+"<svg>
+    <defs>
+        <rect id='r1' opacity='0.5'/>
+        <rect id='r2'/>
+        <image id='i1'/>
+    </defs>
+    <use opacity='0.5' xlink:href='#r1'/>
+    <use x='10mm' xlink:href='#r2'/>
+    <use xlink:href='#i1'/>
+</svg>
 ");
 
     test_eq!(keep_3,
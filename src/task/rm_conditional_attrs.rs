@@ -0,0 +1,179 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::{EId, AId};
+use task::utils;
+use task::utils::conditional;
+
+static ATTRS: &[AId] = &[
+    AId::RequiredFeatures,
+    AId::RequiredExtensions,
+];
+
+// Removes 'requiredFeatures' and 'requiredExtensions' attributes, which always evaluate to 'true',
+// and elements with such attributes, which always evaluate to 'false'.
+//
+// Unlike 'systemLanguage', these attributes do not depend on the user preferences,
+// so they can be evaluated beforehand.
+//
+// Children of the 'switch' element are processed by the 'resolve-switch' task,
+// because only the first matching child is rendered.
+pub fn remove_conditional_attributes(doc: &mut Document, keep_ids: &[String]) {
+    let svg = doc.svg_element().unwrap();
+    let nodes: Vec<Node> = doc.descendants().filter(|n| n.has_attributes(ATTRS)).collect();
+
+    for mut node in nodes {
+        // Skip elements that were removed with their parents.
+        if node != svg && !node.parents().any(|p| p == svg) {
+            continue;
+        }
+
+        if node.parent().is_some_and(|p| p.is_tag_name(EId::Switch)) {
+            continue;
+        }
+
+        if eval(&node) {
+            node.remove_attributes(ATTRS);
+        } else if can_remove(&node, &svg, keep_ids) {
+            node.remove();
+        }
+    }
+}
+
+fn eval(node: &Node) -> bool {
+    let attrs = node.attributes();
+
+    if let Some(AttributeValue::String(s)) = attrs.get_value(AId::RequiredFeatures) {
+        if !conditional::eval_required_features(s) {
+            return false;
+        }
+    }
+
+    if let Some(AttributeValue::String(s)) = attrs.get_value(AId::RequiredExtensions) {
+        if !conditional::eval_required_extensions(s) {
+            return false;
+        }
+    }
+
+    true
+}
+
+// Conditional processing attributes only affect the direct rendering of the element,
+// so elements that are referenced, directly or via their parents, should be preserved.
+//
+// Details: https://www.w3.org/TR/SVG11/struct.html#ConditionalProcessingOverview
+fn can_remove(node: &Node, svg: &Node, keep_ids: &[String]) -> bool {
+    if node == svg {
+        return false;
+    }
+
+    let is_referenced = |n: &Node| n.is_used() || utils::is_kept_id(n, keep_ids);
+    if is_referenced(node) {
+        return false;
+    }
+
+    node.parents().all(|p| {
+        !is_referenced(&p) && !matches!(p.tag_id(), Some(EId::Defs) | Some(EId::ClipPath)
+            | Some(EId::Mask) | Some(EId::Pattern) | Some(EId::Marker) | Some(EId::Symbol))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &mut Document| remove_conditional_attributes(doc, &[]),
+                       $in_text, $out_text);
+        )
+    }
+
+    test!(rm_attr_1,
+"<svg requiredFeatures='http://www.w3.org/TR/SVG11/feature#SVG'>
+    <rect requiredFeatures='http://www.w3.org/TR/SVG11/feature#Shape
+                            http://www.w3.org/TR/SVG11/feature#PaintAttribute'/>
+    <foreignObject requiredExtensions='http://www.w3.org/1999/xhtml'/>
+</svg>",
+"<svg>
+    <rect/>
+    <foreignObject/>
+</svg>
+");
+
+    test!(rm_elem_1,
+"<svg>
+    <text requiredFeatures='http://www.w3.org/TR/SVG11/feature#Font'/>
+    <foreignObject requiredExtensions='http://example.com/ext'/>
+    <rect requiredFeatures=''/>
+    <rect requiredFeatures='http://www.w3.org/TR/SVG11/feature#Shape' requiredExtensions=''/>
+    <g>
+        <rect requiredFeatures='org.w3c.svg.static'/>
+    </g>
+</svg>",
+"<svg>
+    <g/>
+</svg>
+");
+
+    test!(keep_switch_1,
+"<svg>
+    <switch>
+        <text requiredFeatures='http://www.w3.org/TR/SVG11/feature#Font'/>
+        <rect requiredFeatures='http://www.w3.org/TR/SVG11/feature#Shape'/>
+    </switch>
+</svg>",
+"<svg>
+    <switch>
+        <text requiredFeatures='http://www.w3.org/TR/SVG11/feature#Font'/>
+        <rect requiredFeatures='http://www.w3.org/TR/SVG11/feature#Shape'/>
+    </switch>
+</svg>
+");
+
+    // Referenced elements are rendered regardless of the attributes.
+    test!(keep_used_1,
+"<svg>
+    <defs>
+        <rect id='r1' requiredFeatures=''/>
+    </defs>
+    <g id='g1'>
+        <rect requiredFeatures=''/>
+    </g>
+    <use xlink:href='#r1'/>
+    <use xlink:href='#g1'/>
+</svg>",
+"<svg>
+    <defs>
+        <rect id='r1' requiredFeatures=''/>
+    </defs>
+    <g id='g1'>
+        <rect requiredFeatures=''/>
+    </g>
+    <use xlink:href='#r1'/>
+    <use xlink:href='#g1'/>
+</svg>
+");
+}
@@ -21,54 +21,76 @@ use svgdom::{
     AttributeType,
     AttributeValue,
     Document,
+    FuzzyEq,
     Length,
+    Node,
     ValueId,
 };
 
 use task::short::{EId, AId, Unit};
+use task::utils::AnimatedAttributes;
 
 // TODO: xml:space
 
 pub fn remove_default_attributes(doc: &Document) {
+    let animated = AnimatedAttributes::new(doc);
     let mut rm_list = Vec::with_capacity(16);
 
     for (_, mut node) in doc.descendants().svg() {
         let tag_name = node.tag_id().unwrap();
 
         for (aid, attr) in node.attributes().iter_svg() {
-            if attr.is_presentation() {
-                if attr.check_is_default() {
-                    if let Some(n) = node.parents().find(|n| n.has_attribute(aid)) {
-                        if let Some(a) = n.attributes().get(aid) {
-                            if !a.visible {
-                                rm_list.push(aid);
-                            }
-                        }
-                    } else {
+            // An animation of the parent attribute will override an inherited value,
+            // but not the one set explicitly.
+            let is_animated = if attr.is_inheritable() {
+                animated.contains_inherited(&node, aid)
+            } else {
+                animated.contains(&node, aid)
+            };
+
+            if is_animated {
+                continue;
+            }
+
+            if aid == AId::Overflow {
+                // The initial value for 'overflow' as defined in CSS2 is 'visible',
+                // and this applies also to the root 'svg' element;
+                // however, for child elements of an SVG document,
+                // SVG's user agent style sheet overrides this initial value and sets the
+                // 'overflow' property on elements that establish new viewports
+                // (e.g., 'svg' elements), 'pattern' elements and 'marker' elements
+                // to the value 'hidden'.
+                //
+                // https://www.w3.org/TR/SVG/masking.html#OverflowProperty
+
+                let def = if node == doc.svg_element().unwrap() {
+                    ValueId::Visible
+                } else {
+                    ValueId::Hidden
+                };
+
+                if attr.value == AttributeValue::PredefValue(def) {
+                    rm_list.push(aid);
+                }
+            } else if attr.is_inheritable() {
+                if let Some(n) = node.parents().find(|n| n.has_attribute(aid)) {
+                    let attrs = n.attributes();
+                    let parent_attr = attrs.get(aid).unwrap();
+
+                    if !parent_attr.visible && is_default_presentation(aid, &attr.value) {
+                        rm_list.push(aid);
+                    } else if    is_same_value(&attr.value, &parent_attr.value)
+                              && !is_used_by_use(&node) {
+                        // The value is the same as the inherited one.
                         rm_list.push(aid);
                     }
-                } else if aid == AId::Overflow {
-                    // The initial value for 'overflow' as defined in CSS2 is 'visible',
-                    // and this applies also to the root 'svg' element;
-                    // however, for child elements of an SVG document,
-                    // SVG's user agent style sheet overrides this initial value and sets the
-                    // 'overflow' property on elements that establish new viewports
-                    // (e.g., 'svg' elements), 'pattern' elements and 'marker' elements
-                    // to the value 'hidden'.
-                    //
-                    // https://www.w3.org/TR/SVG/masking.html#OverflowProperty
-
-                    if node == doc.svg_element().unwrap() {
-                        if let AttributeValue::PredefValue(id) = attr.value {
-                            if id == ValueId::Visible {
-                                rm_list.push(aid);
-                            }
-                        }
-                    } else if let AttributeValue::PredefValue(id) = attr.value {
-                        if id == ValueId::Hidden {
-                            rm_list.push(aid);
-                        }
-                    }
+                } else if is_default_presentation(aid, &attr.value) {
+                    rm_list.push(aid);
+                }
+            } else if attr.is_presentation() {
+                // Non-inheritable attributes are not affected by the parent ones.
+                if is_default_presentation(aid, &attr.value) {
+                    rm_list.push(aid);
                 }
             } else if is_default(attr, tag_name) {
                 // Check default values of an non-presentation attributes.
@@ -88,6 +110,63 @@ pub fn remove_default_attributes(doc: &Document) {
     }
 }
 
+// Elements referenced by 'use' inherit attributes from the 'use' element
+// and not from the parent one.
+fn is_used_by_use(node: &Node) -> bool {
+    node.parents_with_self().any(|n| n.linked_nodes().any(|l| l.is_tag_name(EId::Use)))
+}
+
+// Checks that the value is the initial value of the presentation attribute.
+//
+// https://www.w3.org/TR/SVG11/propidx.html
+fn is_default_presentation(aid: AId, value: &AttributeValue) -> bool {
+    match (aid, value) {
+        // 'normal' is the same as zero.
+          (AId::LetterSpacing, &AttributeValue::Length(len))
+        | (AId::WordSpacing, &AttributeValue::Length(len))
+        // 'baseline' is the same as zero.
+        | (AId::BaselineShift, &AttributeValue::Length(len)) => {
+            return len.num.is_fuzzy_zero();
+        }
+        (AId::GlyphOrientationHorizontal, AttributeValue::String(s)) => {
+            return s == "0" || s == "0deg";
+        }
+        _ => {}
+    }
+
+    let def = match aid {
+        // Not defined by svgdom.
+        AId::PointerEvents => AttributeValue::PredefValue(ValueId::VisiblePainted),
+        _ => {
+            match AttributeValue::default_value(aid) {
+                Some(v) => v,
+                None => return false,
+            }
+        }
+    };
+
+    is_same_value(value, &def)
+}
+
+// Compares values taking into account that numbers can be stored
+// as 'Number' or as 'Length' and that 'px' is the same as user units.
+fn is_same_value(value1: &AttributeValue, value2: &AttributeValue) -> bool {
+    fn to_num(value: &AttributeValue) -> Option<f64> {
+        match *value {
+            AttributeValue::Number(n) => Some(n),
+            AttributeValue::Length(len) if len.unit == Unit::None || len.unit == Unit::Px => {
+                Some(len.num)
+            }
+            _ => None,
+        }
+    }
+
+    match (to_num(value1), to_num(value2)) {
+        (Some(n1), Some(n2)) => n1.fuzzy_eq(&n2),
+        _ => value1 == value2,
+    }
+}
+
 fn is_default(attr: &Attribute, tag_name: EId) -> bool {
     // Process only popular and simple attributes.
 
@@ -301,5 +380,79 @@ mod tests {
     <rect/>
     <svg overflow='visible'/>
 </svg>
+");
+
+    test!(rm_2,
+"<svg>
+    <rect letter-spacing='0' pointer-events='visiblePainted' stroke-miterlimit='4' stroke-width='1px'/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    // The same value as the inherited one.
+    test!(rm_inherited_1,
+"<svg>
+    <g fill='#ff0000' fill-rule='evenodd'>
+        <rect fill='#ff0000' fill-rule='evenodd'/>
+    </g>
+</svg>",
+"<svg>
+    <g fill='#ff0000' fill-rule='evenodd'>
+        <rect/>
+    </g>
+</svg>
+");
+
+    // Non-inheritable attributes are not affected by the parent ones.
+    test!(rm_non_inheritable_1,
+"<svg>
+    <g opacity='0.5'>
+        <rect opacity='1'/>
+    </g>
+</svg>",
+"<svg>
+    <g opacity='0.5'>
+        <rect/>
+    </g>
+</svg>
+");
+
+    // The referenced element inherits attributes from the 'use' element.
+    test!(keep_used_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <g fill='#ff0000'>
+        <rect id='rect1' fill='#ff0000'/>
+    </g>
+    <use fill='#00ff00' xlink:href='#rect1'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <g fill='#ff0000'>
+        <rect id='rect1' fill='#ff0000'/>
+    </g>
+    <use fill='#00ff00' xlink:href='#rect1'/>
+</svg>
+");
+
+    test!(keep_animated_1,
+"<svg>
+    <g fill='#000000'>
+        <rect fill='#000000'/>
+        <animate attributeName='fill' to='#00ff00'/>
+    </g>
+    <rect opacity='1'>
+        <set attributeName='opacity' to='0.5'/>
+    </rect>
+</svg>",
+"<svg>
+    <g fill='#000000'>
+        <rect fill='#000000'/>
+        <animate attributeName='fill' to='#00ff00'/>
+    </g>
+    <rect opacity='1'>
+        <set attributeName='opacity' to='0.5'/>
+    </rect>
+</svg>
 ");
 }
@@ -16,17 +16,111 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
-use svgdom::{Document, AttributeValue};
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
 
 use task::short::AId;
+use task::round_numbers::round_number;
+use options::CleaningOptions;
+
+// Removes transforms equal to the identity matrix after rounding,
+// like 'translate(0 0)', 'scale(1)' and 'rotate(0)'.
+pub fn remove_default_transform(doc: &Document, opt: &CleaningOptions) {
+    let ts_precision = opt.transforms_precision as usize;
+    let coord_precision = opt.coordinates_precision as usize;
 
-pub fn remove_default_transform(doc: &Document) {
     for mut node in doc.descendants() {
-        let av = node.attributes().get_value(AId::Transform).cloned();
-        if let Some(AttributeValue::Transform(ts)) = av {
-            if ts.is_default() {
-                node.remove_attribute(AId::Transform);
+        for aid in &[AId::Transform, AId::GradientTransform, AId::PatternTransform] {
+            let aid = *aid;
+
+            let av = node.attributes().get_value(aid).cloned();
+            if let Some(AttributeValue::Transform(mut ts)) = av {
+                round_number(&mut ts.a, ts_precision);
+                round_number(&mut ts.b, ts_precision);
+                round_number(&mut ts.c, ts_precision);
+                round_number(&mut ts.d, ts_precision);
+                round_number(&mut ts.e, coord_precision);
+                round_number(&mut ts.f, coord_precision);
+
+                if ts.is_default() && !is_inherited(&node, aid) {
+                    node.remove_attribute(aid);
+                }
             }
         }
     }
 }
+
+// Checks that the attribute is set on one of the elements linked via 'xlink:href'.
+//
+// Gradients and patterns inherit attributes from the referenced element,
+// so an identity transform overrides the inherited one and must be kept.
+fn is_inherited(node: &Node, aid: AId) -> bool {
+    let mut node = node.clone();
+    // Limit a depth in case of a recursive link.
+    for _ in 0..32 {
+        let link = match node.attributes().get_value(AId::XlinkHref) {
+            Some(AttributeValue::Link(link)) => link.clone(),
+            _ => return false,
+        };
+
+        if link.has_attribute(aid) {
+            return true;
+        }
+
+        node = link;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                remove_default_transform(doc, &CleaningOptions::default())
+            }, $in_text, $out_text);
+        )
+    }
+
+    test!(rm_1,
+"<svg>
+    <rect transform='translate(0,0)'/>
+    <rect transform='scale(1)'/>
+    <rect transform='rotate(0)'/>
+    <rect transform='matrix(1,0,0,1,0,0)'/>
+    <rect transform='translate(0.0000000001)'/>
+    <linearGradient gradientTransform='scale(1.0000000001)'/>
+    <pattern patternTransform='translate(0.0000001 0)'/>
+</svg>",
+"<svg>
+    <rect/>
+    <rect/>
+    <rect/>
+    <rect/>
+    <rect/>
+    <linearGradient/>
+    <pattern/>
+</svg>
+");
+
+    // A rounded identity transform overrides the inherited one.
+    test!(keep_inherited_1,
+"<svg>
+    <linearGradient id='lg1' gradientTransform='translate(15)'/>
+    <linearGradient id='lg2' xlink:href='#lg1' gradientTransform='translate(0.0000001)'/>
+    <linearGradient id='lg3' gradientTransform='translate(0.0000001)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1' gradientTransform='translate(15)'/>
+    <linearGradient id='lg2' gradientTransform='translate(0.0000001)' xlink:href='#lg1'/>
+    <linearGradient id='lg3'/>
+</svg>
+");
+}
@@ -23,7 +23,7 @@ use svgdom::{
 
 use task::short::{EId, AId};
 
-pub fn remove_dupl_fe_gaussian_blur(doc: &Document) {
+pub fn remove_dupl_fe_gaussian_blur(doc: &Document, keep_ids: &[String]) {
     let filter_attrs = [
         AId::X,
         AId::Y,
@@ -72,7 +72,7 @@ pub fn remove_dupl_fe_gaussian_blur(doc: &Document) {
         nodes.push(node.clone());
     }
 
-    super::rm_loop(&mut nodes, |node1, node2| {
+    super::rm_loop(&mut nodes, keep_ids, |node1, node2| {
         if !is_attrs_equal(node1, node2, &filter_attrs) {
             return false;
         }
@@ -116,7 +116,7 @@ mod tests {
 
     macro_rules! test {
         ($name:ident, $in_text:expr, $out_text:expr) => (
-            base_test!($name, remove_dupl_fe_gaussian_blur, $in_text, $out_text);
+            base_test!($name, |doc: &Document| remove_dupl_fe_gaussian_blur(doc, &[]), $in_text, $out_text);
         )
     }
 
@@ -23,7 +23,7 @@ use svgdom::{
 
 use task::short::{EId, AId};
 
-pub fn remove_dupl_linear_gradients(doc: &Document) {
+pub fn remove_dupl_linear_gradients(doc: &Document, keep_ids: &[String]) {
     let attrs = [
         AId::X1,
         AId::Y1,
@@ -37,12 +37,12 @@ pub fn remove_dupl_linear_gradients(doc: &Document) {
                        .filter(|n| n.is_tag_name(EId::LinearGradient))
                        .collect::<Vec<Node>>();
 
-    super::rm_loop(&mut nodes, |node1, node2| {
+    super::rm_loop(&mut nodes, keep_ids, |node1, node2| {
         if !super::is_gradient_attrs_equal(node1, node2, &attrs) {
             return false;
         }
 
-        if !super::is_equal_stops(node1, node2) {
+        if !super::is_equal_inherited_stops(node1, node2) {
             return false;
         }
 
@@ -62,7 +62,7 @@ mod tests {
             fn $name() {
                 let doc = Document::from_str($in_text).unwrap();
                 task::resolve_linear_gradient_attributes(&doc);
-                remove_dupl_linear_gradients(&doc);
+                remove_dupl_linear_gradients(&doc, &[]);
                 assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
             }
         )
@@ -186,5 +186,55 @@ mod tests {
 "<svg>
     <linearGradient id='lg1'/>
 </svg>
+");
+
+    // Inherited stops.
+    test!(rm_7,
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0' stop-color='#ff0000'/>
+        <stop offset='1' stop-color='#0000ff'/>
+    </linearGradient>
+    <linearGradient id='lg2' x1='5' xlink:href='#lg1'/>
+    <linearGradient id='lg3' x1='5'>
+        <stop offset='0' stop-color='#ff0000'/>
+        <stop offset='1' stop-color='#0000ff'/>
+    </linearGradient>
+    <rect fill='url(#lg1)'/>
+    <rect fill='url(#lg2)'/>
+    <rect fill='url(#lg3)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0' stop-color='#ff0000'/>
+        <stop offset='1' stop-color='#0000ff'/>
+    </linearGradient>
+    <linearGradient id='lg2' x1='5' xlink:href='#lg1'/>
+    <rect fill='url(#lg1)'/>
+    <rect fill='url(#lg2)'/>
+    <rect fill='url(#lg2)'/>
+</svg>
+");
+
+    // Stops source can't be removed.
+    test!(keep_1,
+"<svg>
+    <linearGradient id='lg1' xlink:href='#lg2'/>
+    <linearGradient id='lg2'>
+        <stop offset='0' stop-color='#ff0000'/>
+        <stop offset='1' stop-color='#0000ff'/>
+    </linearGradient>
+    <rect fill='url(#lg1)'/>
+    <rect fill='url(#lg2)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1' xlink:href='#lg2'/>
+    <linearGradient id='lg2'>
+        <stop offset='0' stop-color='#ff0000'/>
+        <stop offset='1' stop-color='#0000ff'/>
+    </linearGradient>
+    <rect fill='url(#lg1)'/>
+    <rect fill='url(#lg2)'/>
+</svg>
 ");
 }
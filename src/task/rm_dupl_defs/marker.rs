@@ -0,0 +1,108 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Document,
+    Node,
+};
+
+use task::short::EId;
+use task::utils;
+
+pub fn remove_dupl_markers(doc: &Document, keep_ids: &[String]) {
+    let mut nodes = doc.descendants()
+                       .filter(|n| n.is_tag_name(EId::Marker))
+                       // Links to the marker content will be broken after removing.
+                       .filter(|n| !n.descendants().skip(1).any(|c| c.is_used()))
+                       .collect::<Vec<Node>>();
+
+    super::rm_loop(&mut nodes, keep_ids, |node1, node2| {
+        utils::is_equal_attrs(node1, node2) && utils::is_equal_content(node1, node2)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| remove_dupl_markers(doc, &[]), $in_text, $out_text);
+        )
+    }
+
+    test!(rm_1,
+"<svg>
+    <defs>
+        <marker id='m1' markerHeight='4' markerWidth='4' orient='auto'>
+            <path d='M 0 0 L 4 2 L 0 4 Z'/>
+        </marker>
+        <marker id='m2' markerHeight='4' markerWidth='4' orient='auto'>
+            <path d='M 0 0 L 4 2 L 0 4 Z'/>
+        </marker>
+    </defs>
+    <path d='M 10 20 L 30 40' marker-end='url(#m1)'/>
+    <path d='M 10 20 L 30 40' marker-end='url(#m2)' marker-start='url(#m2)'/>
+</svg>",
+"<svg>
+    <defs>
+        <marker id='m1' markerHeight='4' markerWidth='4' orient='auto'>
+            <path d='M 0 0 L 4 2 L 0 4 Z'/>
+        </marker>
+    </defs>
+    <path d='M 10 20 L 30 40' marker-end='url(#m1)'/>
+    <path d='M 10 20 L 30 40' marker-end='url(#m1)' marker-start='url(#m1)'/>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg>
+    <marker id='m1' orient='auto'>
+        <path d='M 0 0 L 4 2 L 0 4 Z'/>
+    </marker>
+    <marker id='m2'>
+        <path d='M 0 0 L 4 2 L 0 4 Z'/>
+    </marker>
+</svg>
+");
+
+    test_eq!(keep_2,
+"<svg>
+    <marker id='m1'>
+        <path d='M 0 0 L 4 2 L 0 4 Z'/>
+    </marker>
+    <marker id='m2'>
+        <path d='M 0 0 L 4 2 L 0 4 Z' fill='#ff0000'/>
+    </marker>
+</svg>
+");
+
+    // Marker content is used by an another element.
+    test_eq!(keep_3,
+"<svg>
+    <marker id='m1'>
+        <path id='p1' d='M 0 0 L 4 2 L 0 4 Z'/>
+    </marker>
+    <marker id='m2'>
+        <path id='p2' d='M 0 0 L 4 2 L 0 4 Z'/>
+    </marker>
+    <use xlink:href='#p2'/>
+</svg>
+");
+}
@@ -23,14 +23,19 @@ use svgdom::{
 };
 
 use task::short::AId;
+use task::utils;
 
 pub use self::linear_gradient::remove_dupl_linear_gradients;
 pub use self::radial_gradient::remove_dupl_radial_gradients;
 pub use self::fe_gaussian_blur::remove_dupl_fe_gaussian_blur;
+pub use self::marker::remove_dupl_markers;
+pub use self::pattern::remove_dupl_patterns;
 
 mod linear_gradient;
 mod radial_gradient;
 mod fe_gaussian_blur;
+mod marker;
+mod pattern;
 
 macro_rules! check_attr {
     ($attrs1:expr, $attrs2:expr, $id:expr, $def:expr) => ({
@@ -42,7 +47,7 @@ macro_rules! check_attr {
     })
 }
 
-fn rm_loop<F>(nodes: &mut Vec<Node>, cmp: F)
+fn rm_loop<F>(nodes: &mut Vec<Node>, keep_ids: &[String], cmp: F)
     where F : Fn(&Node, &Node) -> bool
 {
     let mut link_attrs: Vec<(Node, AId, Node)> = Vec::new();
@@ -57,6 +62,11 @@ fn rm_loop<F>(nodes: &mut Vec<Node>, cmp: F)
             let mut node2 = nodes[i2].clone();
             i2 += 1;
 
+            // Only the first node is preserved, so kept nodes can't be removed as duplicates.
+            if utils::is_kept_id(&node2, keep_ids) {
+                continue;
+            }
+
             if !cmp(&node1, &node2) {
                 continue;
             }
@@ -119,6 +129,39 @@ fn is_gradient_attrs_equal(node1: &Node, node2: &Node, attrs: &[AId]) -> bool {
     true
 }
 
+// Compares stops that will be actually used by gradients,
+// which can be inherited via 'xlink:href'.
+fn is_equal_inherited_stops(node1: &Node, node2: &Node) -> bool {
+    let stops1 = stops_source(node1);
+    let stops2 = stops_source(node2);
+
+    // Gradient that provides stops to an another one can't be removed.
+    if (stops1 == *node2 || stops2 == *node1) && stops1.has_children() {
+        return false;
+    }
+
+    if stops1 == stops2 {
+        return true;
+    }
+
+    is_equal_stops(&stops1, &stops2)
+}
+
+// Returns a gradient that contains stops for the current gradient.
+fn stops_source(node: &Node) -> Node {
+    let mut node = node.clone();
+    while !node.has_children() {
+        let link = match node.attributes().get_value(AId::XlinkHref) {
+            Some(AttributeValue::Link(link)) => link.clone(),
+            _ => break,
+        };
+
+        node = link;
+    }
+
+    node
+}
+
 pub fn is_equal_stops(node1: &Node, node2: &Node) -> bool {
     let children_count1 = node1.children().count();
 
@@ -133,9 +176,9 @@ pub fn is_equal_stops(node1: &Node, node2: &Node) -> bool {
     let iter1 = node1.children();
     let iter2 = node2.children();
 
-    for (mut c1, mut c2) in iter1.zip(iter2) {
-        let attrs1 = c1.attributes_mut();
-        let attrs2 = c2.attributes_mut();
+    for (c1, c2) in iter1.zip(iter2) {
+        let attrs1 = c1.attributes();
+        let attrs2 = c2.attributes();
 
         if !(   attrs1.get_value(AId::Offset) == attrs2.get_value(AId::Offset)
              && attrs1.get_value(AId::StopColor) == attrs2.get_value(AId::StopColor)
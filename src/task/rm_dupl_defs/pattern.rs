@@ -0,0 +1,99 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Document,
+    Node,
+};
+
+use task::short::EId;
+use task::utils;
+
+pub fn remove_dupl_patterns(doc: &Document, keep_ids: &[String]) {
+    let mut nodes = doc.descendants()
+                       .filter(|n| n.is_tag_name(EId::Pattern))
+                       // Pattern that provides attributes to an another one can't be removed.
+                       .filter(|n| !n.linked_nodes().any(|l| l.is_tag_name(EId::Pattern)))
+                       // Links to the pattern content will be broken after removing.
+                       .filter(|n| !n.descendants().skip(1).any(|c| c.is_used()))
+                       .collect::<Vec<Node>>();
+
+    super::rm_loop(&mut nodes, keep_ids, |node1, node2| {
+        utils::is_equal_attrs(node1, node2) && utils::is_equal_content(node1, node2)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| remove_dupl_patterns(doc, &[]), $in_text, $out_text);
+        )
+    }
+
+    test!(rm_1,
+"<svg>
+    <defs>
+        <pattern id='p1' height='10' patternUnits='userSpaceOnUse' width='10'>
+            <rect height='5' width='5'/>
+        </pattern>
+        <pattern id='p2' height='10' patternUnits='userSpaceOnUse' width='10'>
+            <rect height='5' width='5'/>
+        </pattern>
+    </defs>
+    <rect fill='url(#p1)'/>
+    <rect fill='url(#p2)' stroke='url(#p2)'/>
+</svg>",
+"<svg>
+    <defs>
+        <pattern id='p1' height='10' patternUnits='userSpaceOnUse' width='10'>
+            <rect height='5' width='5'/>
+        </pattern>
+    </defs>
+    <rect fill='url(#p1)'/>
+    <rect fill='url(#p1)' stroke='url(#p1)'/>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg>
+    <pattern id='p1' height='10' width='10'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <pattern id='p2' height='10' width='20'>
+        <rect height='5' width='5'/>
+    </pattern>
+</svg>
+");
+
+    // Pattern is used via 'xlink:href'.
+    test_eq!(keep_2,
+"<svg>
+    <pattern id='p1' height='10' width='10'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <pattern id='p2' height='10' width='10'>
+        <rect height='5' width='5'/>
+    </pattern>
+    <pattern id='p3' x='5' xlink:href='#p2'/>
+</svg>
+");
+}
@@ -23,7 +23,7 @@ use svgdom::{
 
 use task::short::{EId, AId};
 
-pub fn remove_dupl_radial_gradients(doc: &Document) {
+pub fn remove_dupl_radial_gradients(doc: &Document, keep_ids: &[String]) {
     let attrs = [
         AId::Cx,
         AId::Cy,
@@ -38,12 +38,12 @@ pub fn remove_dupl_radial_gradients(doc: &Document) {
                        .filter(|n| n.is_tag_name(EId::RadialGradient))
                        .collect::<Vec<Node>>();
 
-    super::rm_loop(&mut nodes, |node1, node2| {
+    super::rm_loop(&mut nodes, keep_ids, |node1, node2| {
         if !super::is_gradient_attrs_equal(node1, node2, &attrs) {
             return false;
         }
 
-        if !super::is_equal_stops(node1, node2) {
+        if !super::is_equal_inherited_stops(node1, node2) {
             return false;
         }
 
@@ -63,7 +63,7 @@ mod tests {
             fn $name() {
                 let doc = Document::from_str($in_text).unwrap();
                 task::resolve_radial_gradient_attributes(&doc);
-                remove_dupl_radial_gradients(&doc);
+                remove_dupl_radial_gradients(&doc, &[]);
                 assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
             }
         )
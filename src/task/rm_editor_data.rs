@@ -0,0 +1,195 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Document,
+    Name,
+    Node,
+    NodeType,
+};
+
+use options::CleaningOptions;
+use task::utils;
+
+#[derive(Clone,Copy,PartialEq)]
+enum Editor {
+    Inkscape,
+    Sodipodi,
+    Illustrator,
+    Sketch,
+    Figma,
+}
+
+// Editors namespaces with their commonly used prefixes.
+static NAMESPACES: &[(Editor, &str, &str)] = &[
+    (Editor::Inkscape,      "inkscape", "http://www.inkscape.org/namespaces/inkscape"),
+    (Editor::Sodipodi,      "sodipodi", "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd"),
+    (Editor::Sodipodi,      "sodipodi", "http://inkscape.sourceforge.net/DTD/sodipodi-0.dtd"),
+    (Editor::Illustrator,   "i",        "http://ns.adobe.com/AdobeIllustrator/10.0/"),
+    (Editor::Illustrator,   "x",        "http://ns.adobe.com/Extensibility/1.0/"),
+    (Editor::Illustrator,   "graph",    "http://ns.adobe.com/Graphs/1.0/"),
+    (Editor::Illustrator,   "a",        "http://ns.adobe.com/AdobeSVGViewerExtensions/3.0/"),
+    (Editor::Illustrator,   "sfw",      "http://ns.adobe.com/SaveForWeb/1.0/"),
+    (Editor::Illustrator,   "ir",       "http://ns.adobe.com/ImageReplacement/1.0/"),
+    (Editor::Illustrator,   "custom",   "http://ns.adobe.com/GenericCustomNamespace/1.0/"),
+    (Editor::Illustrator,   "xflows",   "http://ns.adobe.com/Flows/1.0/"),
+    (Editor::Illustrator,   "v",        "http://ns.adobe.com/Variables/1.0/"),
+    (Editor::Sketch,        "sketch",   "http://www.bohemiancoding.com/sketch/ns"),
+    (Editor::Figma,         "figma",    "http://www.figma.com/figma/ns"),
+];
+
+// Removes elements and attributes from editors namespaces.
+//
+// Prefixes are resolved using the 'xmlns:*' attributes. Since they can be already removed
+// by the parser, undeclared prefixes are resolved using the commonly used ones.
+pub fn remove_editor_data(doc: &mut Document, options: &CleaningOptions) {
+    let declared = utils::declared_namespaces(doc);
+
+    let is_removable = |prefix: &str| {
+        is_editor_prefix(prefix, declared.get(prefix).map(|uri| uri.as_str()), options)
+    };
+
+    doc.drain(|n| {
+        match n.tag_name() {
+            Some(ref tag_name) => match **tag_name {
+                Name::Name(ref name) => prefix(name).is_some_and(&is_removable),
+                Name::Id(_) => false,
+            },
+            None => false,
+        }
+    });
+
+    let nodes: Vec<Node> = doc.descendants().filter(|n| n.node_type() == NodeType::Element).collect();
+    for mut node in nodes {
+        node.attributes_mut().retain(|attr| {
+            match attr.name {
+                Name::Name(ref name) => {
+                    let p = name.strip_prefix("xmlns:").or_else(|| prefix(name));
+                    !p.is_some_and(&is_removable)
+                }
+                Name::Id(_) => true,
+            }
+        });
+    }
+}
+
+// Checks that elements and attributes with the prefix are removed by the options.
+//
+// 'uri' is the namespace the prefix is bound to, when declared.
+pub fn is_editor_prefix(prefix: &str, uri: Option<&str>, options: &CleaningOptions) -> bool {
+    let editor = match uri {
+        Some(uri) => NAMESPACES.iter().find(|&&(_, _, u)| u == uri),
+        None => NAMESPACES.iter().find(|&&(_, p, _)| p == prefix),
+    };
+
+    match editor {
+        Some(&(Editor::Inkscape, _, _)) => options.remove_inkscape_data,
+        Some(&(Editor::Sodipodi, _, _)) => options.remove_sodipodi_data,
+        Some(&(Editor::Illustrator, _, _)) => options.remove_ai_data,
+        Some(&(Editor::Sketch, _, _)) => options.remove_sketch_data,
+        Some(&(Editor::Figma, _, _)) => options.remove_figma_data,
+        None => false,
+    }
+}
+
+fn prefix(name: &str) -> Option<&str> {
+    utils::split_prefix(name).map(|(prefix, _)| prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    fn all_editors() -> CleaningOptions {
+        CleaningOptions {
+            remove_inkscape_data: true,
+            remove_sodipodi_data: true,
+            remove_ai_data: true,
+            remove_sketch_data: true,
+            remove_figma_data: true,
+            ..CleaningOptions::default()
+        }
+    }
+
+    macro_rules! test {
+        ($name:ident, $options:expr, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &mut Document| remove_editor_data(doc, &$options),
+                       $in_text, $out_text);
+        )
+    }
+
+    test!(rm_1, all_editors(),
+"<svg xmlns:inkscape='http://www.inkscape.org/namespaces/inkscape' \
+      xmlns:sodipodi='http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd' \
+      inkscape:version='1.0' sodipodi:docname='test.svg'>
+    <sodipodi:namedview inkscape:zoom='1'/>
+    <g inkscape:label='Layer 1' inkscape:groupmode='layer'>
+        <rect/>
+    </g>
+</svg>",
+"<svg>
+    <g>
+        <rect/>
+    </g>
+</svg>
+");
+
+    test!(rm_2, all_editors(),
+"<svg xmlns:ai='http://ns.adobe.com/AdobeIllustrator/10.0/' \
+      xmlns:sketch='http://www.bohemiancoding.com/sketch/ns'>
+    <ai:pgf>data</ai:pgf>
+    <rect ai:knockout='Off' sketch:type='MSShapeGroup'/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    // Undeclared prefixes are resolved by the common names.
+    test!(rm_3, all_editors(),
+"<svg>
+    <sodipodi:namedview/>
+    <rect inkscape:label='rect' figma:id='1' other:attr='1'/>
+</svg>",
+"<svg>
+    <rect other:attr='1'/>
+</svg>
+");
+
+    test!(keep_1, CleaningOptions { remove_sodipodi_data: true, ..CleaningOptions::default() },
+"<svg xmlns:inkscape='http://www.inkscape.org/namespaces/inkscape' \
+      xmlns:sodipodi='http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd'>
+    <sodipodi:namedview/>
+    <g inkscape:label='Layer 1'/>
+</svg>",
+"<svg xmlns:inkscape='http://www.inkscape.org/namespaces/inkscape'>
+    <g inkscape:label='Layer 1'/>
+</svg>
+");
+
+    // Prefixes are resolved by the namespace and not by the name.
+    test!(keep_2, all_editors(),
+"<svg xmlns:i='http://example.com/ns'>
+    <rect i:attr='1'/>
+</svg>",
+"<svg xmlns:i='http://example.com/ns'>
+    <rect i:attr='1'/>
+</svg>
+");
+}
@@ -16,15 +16,44 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
-use svgdom::Document;
+use svgdom::{
+    AttributeValue,
+    Document,
+};
 
-use task::short::EId;
+use task::short::{EId, AId};
+use task::utils::animation::is_animation;
+use task::utils::script::is_event_attr;
 
 // TODO: to mod::utils
 pub fn remove_element(doc: &mut Document, id: EId) {
     doc.drain(|n| n.is_tag_name(id));
 }
 
+pub fn remove_animations(doc: &mut Document) {
+    doc.drain(|n| n.tag_id().is_some_and(is_animation));
+}
+
+// Removes 'script' elements, event attributes and 'javascript:' links.
+pub fn remove_scripts(doc: &mut Document) {
+    doc.drain(|n| n.is_tag_name(EId::Script));
+
+    for mut node in doc.descendants() {
+        node.attributes_mut().retain(|a| {
+            if is_event_attr(a) {
+                return false;
+            }
+
+            match a.value {
+                AttributeValue::String(ref s) if a.has_id(AId::XlinkHref) => {
+                    !s.trim_start().to_lowercase().starts_with("javascript:")
+                }
+                _ => true,
+            }
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,4 +103,49 @@ mod tests {
     <rect/>
 </svg>
 ");
+
+    #[test]
+    fn rm_animations_1() {
+        let mut doc = Document::from_str(
+"<svg>
+    <rect>
+        <animate attributeName='width' to='20'/>
+        <set attributeName='fill' to='#ff0000'/>
+    </rect>
+    <animateMotion>
+        <mpath/>
+    </animateMotion>
+</svg>").unwrap();
+
+        remove_animations(&mut doc);
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg>
+    <rect/>
+</svg>
+");
+    }
+
+    #[test]
+    fn rm_scripts_1() {
+        let mut doc = Document::from_str(
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink' onload='init()'>
+    <script>init = function() {}</script>
+    <a xlink:href='javascript:init()'>
+        <rect onclick='init()'/>
+    </a>
+    <a xlink:href='https://example.com'/>
+</svg>").unwrap();
+
+        remove_scripts(&mut doc);
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <a>
+        <rect/>
+    </a>
+    <a xlink:href='https://example.com'/>
+</svg>
+");
+    }
 }
@@ -0,0 +1,170 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Document,
+    Node,
+    NodeType,
+};
+
+use options::CleaningOptions;
+use task::short::{EId, AId};
+use task::utils;
+
+// Removes containers without renderable children and text elements without characters.
+//
+// Removing one element can make its parent empty, so we repeat until nothing is removed.
+pub fn remove_empty_elements(doc: &Document, options: &CleaningOptions) {
+    loop {
+        let nodes: Vec<Node> = doc.descendants().svg()
+            .filter(|&(id, ref node)| is_empty(id, node, options))
+            .map(|(_, node)| node)
+            .collect();
+
+        if nodes.is_empty() {
+            break;
+        }
+
+        for mut node in nodes {
+            // A parent can be already removed.
+            if node.parent().is_some() {
+                node.remove();
+            }
+        }
+    }
+}
+
+fn is_empty(id: EId, node: &Node, options: &CleaningOptions) -> bool {
+    // Referenced and kept elements should be preserved even when empty.
+    if node.is_used() || utils::is_kept_id(node, &options.keep_ids) {
+        return false;
+    }
+
+    match id {
+        EId::G | EId::Defs | EId::Switch | EId::A => {
+            // An empty group with a filter can still be rendered.
+            // Checkout 'filters-tile-01-b.svg' in 'W3C_SVG_11_TestSuite' for details.
+            if node.has_attribute(AId::Filter) {
+                return false;
+            }
+
+            // 'title' and 'desc' elements describe their parent element.
+            node.children().svg().all(|(id, _)| {
+                match id {
+                    EId::Title | EId::Desc => !options.keep_accessibility,
+                    EId::Metadata => true,
+                    _ => false,
+                }
+            })
+        }
+        // Text without non-whitespace characters is not rendered.
+        EId::Text => !has_text(node, |s| !s.trim().is_empty()),
+        // Whitespaces inside 'tspan' can still affect the text layout.
+        EId::Tspan | EId::TextPath => !has_text(node, |s| !s.is_empty()),
+        _ => false,
+    }
+}
+
+fn has_text<F>(node: &Node, f: F) -> bool
+    where F: Fn(&str) -> bool
+{
+    node.descendants().any(|n| {
+        match n.node_type() {
+            NodeType::Text | NodeType::Cdata => f(&n.text()),
+            // 'tref' references characters of another element.
+            NodeType::Element => n.is_tag_name(EId::Tref),
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                remove_empty_elements(doc, &CleaningOptions::default())
+            }, $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    test!(rm_containers_1,
+"<svg>
+    <defs/>
+    <g/>
+    <switch/>
+    <a/>
+    <rect/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    // Parent container should be removed too.
+    test!(rm_containers_2,
+"<svg>
+    <g fill='#ff0000'>
+        <g>
+            <title>Title</title>
+        </g>
+        <a>
+            <g/>
+        </a>
+    </g>
+    <rect/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    test!(rm_text_1,
+"<svg>
+    <text>  </text>
+    <text>Text<tspan/></text>
+    <text><tspan x='10'/></text>
+</svg>",
+"<svg>
+    <text>Text</text>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg>
+    <g filter='url(#filter1)'/>
+    <filter id='filter1'/>
+</svg>
+");
+
+    test_eq!(keep_2,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <g id='g1'/>
+    <use xlink:href='#g1'/>
+</svg>
+");
+}
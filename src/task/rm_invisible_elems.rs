@@ -18,6 +18,8 @@
 
 use svgdom::{
     AttributeValue,
+    Attributes,
+    Color,
     Document,
     ElementType,
     FuzzyEq,
@@ -26,28 +28,32 @@ use svgdom::{
 };
 
 use task::short::{EId, AId};
-use task::utils;
+use task::utils::{self, AnimatedAttributes};
 
 // TODO: process mask element
-// TODO: process visibility
 // TODO: process feGaussianBlur with stdDeviation=0
 // TODO: split to suboptions
 // TODO: polyline/polygon without points
 // TODO: remove elements with transform="matrix(0 0 0 0 0 0)"
 
-pub fn remove_invisible_elements(doc: &mut Document) {
+pub fn remove_invisible_elements(doc: &mut Document, keep_ids: &[String]) {
+    let animated = AnimatedAttributes::new(doc);
+    let kept = utils::kept_ids(doc, keep_ids);
+
     let mut is_any_removed = false;
-    process_display_attribute(doc, &mut is_any_removed);
-    process_paths(doc, &mut is_any_removed);
+    process_display_attribute(doc, &animated, &mut is_any_removed);
+    process_opacity(doc, &animated, &kept, &mut is_any_removed);
+    process_visibility(doc, &animated, &kept, &mut is_any_removed);
+    process_paths(doc, &animated, &mut is_any_removed);
     process_clip_paths(doc, &mut is_any_removed);
     process_empty_filter(doc, &mut is_any_removed);
     process_fe_color_matrix(doc);
     process_use(doc, &mut is_any_removed);
     process_gradients(doc, &mut is_any_removed);
-    process_rect(doc, &mut is_any_removed);
+    process_shapes(doc, &animated, &mut is_any_removed);
 
     if is_any_removed {
-        super::remove_unused_defs(doc);
+        super::remove_unused_defs(doc, keep_ids);
     }
 }
 
@@ -108,7 +114,7 @@ fn is_valid_clip_path_elem(node: &Node) -> bool {
 }
 
 // Paths with empty 'd' attribute are invisible and we can remove them.
-fn process_paths(doc: &mut Document, is_any_removed: &mut bool) {
+fn process_paths(doc: &mut Document, animated: &AnimatedAttributes, is_any_removed: &mut bool) {
     fn is_invisible(node: &Node) -> bool {
         if let Some(&AttributeValue::Path(ref d)) = node.attributes().get_value(AId::D) {
             d.d.is_empty()
@@ -118,17 +124,23 @@ fn process_paths(doc: &mut Document, is_any_removed: &mut bool) {
         }
     }
 
-    let c = doc.drain(|n| n.is_tag_name(EId::Path) && is_invisible(n));
+    let c = doc.drain(|n| {
+        n.is_tag_name(EId::Path) && is_invisible(n) && !animated.contains(n, AId::D)
+    });
     if c != 0 {
         *is_any_removed = true;
     }
 }
 
 // Remove elements with 'display:none'.
-fn process_display_attribute(doc: &Document, is_any_removed: &mut bool) {
+fn process_display_attribute(
+    doc: &Document,
+    animated: &AnimatedAttributes,
+    is_any_removed: &mut bool,
+) {
     let mut nodes = Vec::with_capacity(16);
 
-    _process_display_attribute(&doc.root(), &mut nodes, is_any_removed);
+    _process_display_attribute(&doc.root(), animated, &mut nodes);
 
     if !nodes.is_empty() {
         *is_any_removed = true;
@@ -137,20 +149,121 @@ fn process_display_attribute(doc: &Document, is_any_removed: &mut bool) {
     utils::remove_nodes(&mut nodes);
 }
 
-fn _process_display_attribute(parent: &Node, nodes: &mut Vec<Node>, is_any_removed: &mut bool) {
+fn _process_display_attribute(parent: &Node, animated: &AnimatedAttributes, nodes: &mut Vec<Node>) {
     for (_, node) in parent.children().svg() {
         // If elements has attribute 'display:none' and this element is not used - we can remove it.
         let val = AttributeValue::PredefValue(ValueId::None);
-        if node.attributes().get_value(AId::Display) == Some(&val) && !node.is_used() {
+        if    node.attributes().get_value(AId::Display) == Some(&val)
+           && !node.is_used()
+           && !animated.contains(&node, AId::Display) {
             // All children must be unused to.
             if !node.descendants().any(|n| n.is_used()) {
                 // TODO: ungroup used elements and remove unused
                 nodes.push(node.clone());
             }
         } else if node.has_children() {
-            _process_display_attribute(&node, nodes, is_any_removed);
+            _process_display_attribute(&node, animated, nodes);
+        }
+    }
+}
+
+// Remove elements with 'opacity=0'.
+fn process_opacity(
+    doc: &Document,
+    animated: &AnimatedAttributes,
+    kept: &[String],
+    is_any_removed: &mut bool,
+) {
+    let mut nodes: Vec<Node> = doc.descendants()
+        .filter(|n| n.is_graphic() || n.is_tag_name(EId::G) || n.is_tag_name(EId::A))
+        .filter(|n| {
+            match n.attributes().get_value(AId::Opacity) {
+                Some(&AttributeValue::Number(n)) => n.is_fuzzy_zero(),
+                _ => false,
+            }
+        })
+        .filter(|n| is_rendered(n) && !is_used(n) && !animated.contains(n, AId::Opacity))
+        .filter(|n| !is_scripted(n, kept))
+        .collect();
+
+    remove_top_nodes(&mut nodes, is_any_removed);
+}
+
+// Remove elements with 'visibility:hidden', which children are hidden too.
+fn process_visibility(
+    doc: &Document,
+    animated: &AnimatedAttributes,
+    kept: &[String],
+    is_any_removed: &mut bool,
+) {
+    fn is_hidden(node: &Node) -> bool {
+        match node.attributes().get_value(AId::Visibility) {
+            Some(&AttributeValue::PredefValue(id)) => id == ValueId::Hidden || id == ValueId::Collapse,
+            _ => false,
+        }
+    }
+
+    // Markers, filters and referenced elements can have own visibility.
+    fn can_be_visible(node: &Node) -> bool {
+        let aids = [AId::Filter, AId::Marker, AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd];
+
+           node.parents_with_self().any(|n| n.has_attributes(&aids))
+        || node.descendants().any(|n| {
+               n.has_attributes(&aids) || n.is_tag_name(EId::Use)
+            || n.attributes().get_value(AId::Visibility)
+                   == Some(&AttributeValue::PredefValue(ValueId::Visible))
+        })
+    }
+
+    let mut nodes: Vec<Node> = doc.descendants()
+        .filter(|n| n.is_graphic() || n.is_tag_name(EId::G) || n.is_tag_name(EId::A))
+        .filter(is_hidden)
+        .filter(|n| is_rendered(n) && !is_used(n) && !can_be_visible(n))
+        // An animated visibility of any child can make it visible.
+        .filter(|n| !n.descendants().any(|c| animated.contains(&c, AId::Visibility)))
+        .filter(|n| !is_scripted(n, kept))
+        .collect();
+
+    remove_top_nodes(&mut nodes, is_any_removed);
+}
+
+// Checks that the node is a part of the rendering tree
+// and not a part of 'defs', 'clipPath', 'mask', etc.
+fn is_rendered(node: &Node) -> bool {
+    node.parents().all(|p| {
+        match p.tag_id() {
+            Some(id) => matches!(id, EId::Svg | EId::G | EId::A | EId::Switch),
+            None => true,
         }
+    })
+}
+
+// Checks that the node or its children are referenced by other elements.
+fn is_used(node: &Node) -> bool {
+    node.descendants().any(|n| n.is_used())
+}
+
+// Checks that the node or its children can be made visible by a script:
+// they have event attributes or ids, which are referenced by scripts or kept by '--keep-ids'.
+fn is_scripted(node: &Node, kept: &[String]) -> bool {
+    node.descendants().any(|n| utils::has_event_attrs(&n) || utils::is_kept_id(&n, kept))
+}
+
+// Removes nodes, skipping ones which are already removed with their parents.
+fn remove_top_nodes(nodes: &mut Vec<Node>, is_any_removed: &mut bool) {
+    if nodes.is_empty() {
+        return;
     }
+
+    *is_any_removed = true;
+
+    let top: Vec<Node> = nodes.iter()
+        .filter(|n| !n.parents().any(|p| nodes.contains(&p)))
+        .cloned()
+        .collect();
+
+    *nodes = top;
+    utils::remove_nodes(nodes);
 }
 
 // Remove 'filter' elements without children.
@@ -244,21 +357,16 @@ fn process_gradients(doc: &Document, is_any_removed: &mut bool) {
     {
         // 'If one stop is defined, then paint with the solid color fill using the color
         // defined for that gradient stop.'
+        //
+        // The same applies to gradients with stops of the same color and opacity.
         let iter = doc.descendants()
                       .filter(|n| n.is_gradient())
-                      .filter(|n| n.children().count() == 1 && !n.has_attribute(AId::XlinkHref));
+                      .filter(|n| n.has_children() && !n.has_attribute(AId::XlinkHref));
 
         for n in iter {
-            let stop = n.first_child().unwrap();
-
-            let color = match stop.attributes().get_value(AId::StopColor) {
-                Some(&AttributeValue::Color(c)) => c,
-                _ => unreachable!("attribute must be resolved"),
-            };
-
-            let opacity = match stop.attributes().get_value(AId::StopOpacity) {
-                Some(&AttributeValue::Number(n)) => n,
-                _ => unreachable!("attribute must be resolved"),
+            let (color, opacity) = match solid_color(&n) {
+                Some(v) => v,
+                None => continue,
             };
 
             // Replace links with colors, but not in gradients,
@@ -268,12 +376,24 @@ fn process_gradients(doc: &Document, is_any_removed: &mut bool) {
                              .collect::<Vec<Node>>() {
                 while let Some(aid) = find_link_attribute(&link, &n) {
                     link.set_attribute((aid, color));
+
+                    let opacity_aid = match aid {
+                        AId::Fill => AId::FillOpacity,
+                        AId::Stroke => AId::StrokeOpacity,
+                        _ => continue,
+                    };
+
+                    // Stop opacity is multiplied by the paint opacity.
+                    let paint_opacity = link.parents_with_self()
+                        .filter_map(|p| match p.attributes().get_value(opacity_aid) {
+                            Some(&AttributeValue::Number(n)) => Some(n),
+                            _ => None,
+                        })
+                        .next()
+                        .unwrap_or(1.0);
+
                     if opacity.fuzzy_ne(&1.0) {
-                        match aid {
-                            AId::Fill => link.set_attribute((AId::FillOpacity, opacity)),
-                            AId::Stroke => link.set_attribute((AId::StrokeOpacity, opacity)),
-                            _ => {}
-                        }
+                        link.set_attribute((opacity_aid, paint_opacity * opacity));
                     }
                 }
             }
@@ -288,6 +408,35 @@ fn process_gradients(doc: &Document, is_any_removed: &mut bool) {
     utils::remove_nodes(&mut nodes);
 }
 
+// Returns the color and opacity of the gradient, when all stops are the same.
+fn solid_color(node: &Node) -> Option<(Color, f64)> {
+    let mut value = None;
+    for stop in node.children() {
+        let attrs = stop.attributes();
+
+        let color = match attrs.get_value(AId::StopColor) {
+            Some(&AttributeValue::Color(c)) => c,
+            _ => unreachable!("attribute must be resolved"),
+        };
+
+        let opacity = match attrs.get_value(AId::StopOpacity) {
+            Some(&AttributeValue::Number(n)) => n,
+            _ => unreachable!("attribute must be resolved"),
+        };
+
+        match value {
+            Some((c, o)) => {
+                if c != color || opacity.fuzzy_ne(&o) {
+                    return None;
+                }
+            }
+            None => value = Some((color, opacity)),
+        }
+    }
+
+    value
+}
+
 fn find_link_attribute(node: &Node, link: &Node) -> Option<AId> {
     let attrs = node.attributes();
 
@@ -306,31 +455,31 @@ fn find_link_attribute(node: &Node, link: &Node) -> Option<AId> {
     None
 }
 
-// Remove rects with a zero size.
-fn process_rect(doc: &mut Document, is_any_removed: &mut bool) {
-    fn is_invisible(node: &Node) -> bool {
-        if !node.is_tag_name(EId::Rect) {
-            return false;
+// Remove shapes with a zero size.
+fn process_shapes(doc: &mut Document, animated: &AnimatedAttributes, is_any_removed: &mut bool) {
+    fn is_zero(attrs: &Attributes, aid: AId) -> bool {
+        match attrs.get_value(aid) {
+            Some(&AttributeValue::Length(len)) => len.num.is_fuzzy_zero(),
+            _ => false,
         }
+    }
 
+    fn is_invisible(node: &Node) -> bool {
         let attrs = node.attributes();
 
-        if let Some(&AttributeValue::Length(len)) = attrs.get_value(AId::Width) {
-            if len.num.is_fuzzy_zero() {
-                return true;
-            }
-        }
-
-        if let Some(&AttributeValue::Length(len)) = attrs.get_value(AId::Height) {
-            if len.num.is_fuzzy_zero() {
-                return true;
-            }
-        }
+        let is_zero_size = match node.tag_id() {
+            Some(EId::Rect) => is_zero(&attrs, AId::Width) || is_zero(&attrs, AId::Height),
+            Some(EId::Circle) => is_zero(&attrs, AId::R),
+            Some(EId::Ellipse) => is_zero(&attrs, AId::Rx) || is_zero(&attrs, AId::Ry),
+            _ => false,
+        };
 
-        false
+        // Referenced shapes are kept, so we do not break links.
+        is_zero_size && !node.is_used()
     }
 
-    let c = doc.drain(is_invisible);
+    // An animation can change the shape size.
+    let c = doc.drain(|n| is_invisible(n) && !animated.is_animated(n));
     if c != 0 {
         *is_any_removed = true;
     }
@@ -350,7 +499,7 @@ mod tests {
                 // We must prepare defs, because 'remove_invisible_elements'
                 // invokes 'remove_unused_defs'.
                 group_defs(&mut doc);
-                remove_invisible_elements(&mut doc);
+                remove_invisible_elements(&mut doc, &[]);
                 // Removes 'defs' element.
                 remove_empty_defs(&mut doc);
                 assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
@@ -530,6 +679,88 @@ mod tests {
 "<svg>
     <rect fill='#ff0000' fill-opacity='0.5' height='5' stroke='#ff0000' stroke-opacity='0.5' width='5'/>
 </svg>
+");
+
+    test!(rm_gradient_4,
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0' stop-color='#ff0000' stop-opacity='0.5'/>
+        <stop offset='1' stop-color='#ff0000' stop-opacity='0.5'/>
+    </linearGradient>
+    <g fill-opacity='0.5'>
+        <rect fill='url(#lg1)' height='5' width='5'/>
+    </g>
+</svg>",
+"<svg>
+    <g fill-opacity='0.5'>
+        <rect fill='#ff0000' fill-opacity='0.25' height='5' width='5'/>
+    </g>
+</svg>
+");
+
+    test!(keep_gradient_1,
+"<svg>
+    <linearGradient id='lg1'>
+        <stop offset='0' stop-color='#ff0000' stop-opacity='1'/>
+        <stop offset='1' stop-color='#ff0000' stop-opacity='0.5'/>
+    </linearGradient>
+    <rect fill='url(#lg1)' height='5' width='5'/>
+</svg>",
+"<svg>
+    <defs>
+        <linearGradient id='lg1'>
+            <stop offset='0' stop-color='#ff0000' stop-opacity='1'/>
+            <stop offset='1' stop-color='#ff0000' stop-opacity='0.5'/>
+        </linearGradient>
+    </defs>
+    <rect fill='url(#lg1)' height='5' width='5'/>
+</svg>
+");
+
+    test!(rm_opacity_1,
+"<svg>
+    <g opacity='0'>
+        <rect height='5' opacity='0' width='5'/>
+    </g>
+    <rect height='5' opacity='0' width='5'/>
+    <rect id='rect1' height='5' opacity='0' width='5'/>
+    <use xlink:href='#rect1'/>
+</svg>",
+"<svg>
+    <rect id='rect1' height='5' opacity='0' width='5'/>
+    <use xlink:href='#rect1'/>
+</svg>
+");
+
+    test!(rm_visibility_1,
+"<svg>
+    <g visibility='hidden'>
+        <rect height='5' width='5'/>
+    </g>
+    <rect height='5' visibility='collapse' width='5'/>
+    <g visibility='hidden'>
+        <rect height='5' visibility='visible' width='5'/>
+    </g>
+</svg>",
+"<svg>
+    <g visibility='hidden'>
+        <rect height='5' visibility='visible' width='5'/>
+    </g>
+</svg>
+");
+
+    // Markers are rendered independently.
+    test!(keep_visibility_1,
+"<svg>
+    <marker id='m1'/>
+    <path d='M 10 20 L 30 40' marker-start='url(#m1)' visibility='hidden'/>
+</svg>",
+"<svg>
+    <defs>
+        <marker id='m1'/>
+    </defs>
+    <path d='M 10 20 L 30 40' marker-start='url(#m1)' visibility='hidden'/>
+</svg>
 ");
 
     test!(rm_rect_1,
@@ -539,6 +770,20 @@ mod tests {
     <rect width='0' height='10'/>
 </svg>",
 "<svg/>
+");
+
+    test!(rm_circle_1,
+"<svg>
+    <circle r='0'/>
+    <ellipse rx='0' ry='5'/>
+    <ellipse rx='5' ry='0'/>
+    <circle id='circle1' r='0'/>
+    <use xlink:href='#circle1'/>
+</svg>",
+"<svg>
+    <circle id='circle1' r='0'/>
+    <use xlink:href='#circle1'/>
+</svg>
 ");
 
     test!(rm_fe_color_matrix_1,
@@ -553,4 +798,41 @@ mod tests {
 </svg>
 ");
 
+
+    test!(keep_animated_1,
+"<svg>
+    <rect display='none' height='10' width='10'>
+        <set attributeName='display' to='inline'/>
+    </rect>
+    <rect height='0' width='10'>
+        <animate attributeName='height' to='10'/>
+    </rect>
+</svg>",
+"<svg>
+    <rect display='none' height='10' width='10'>
+        <set attributeName='display' to='inline'/>
+    </rect>
+    <rect height='0' width='10'>
+        <animate attributeName='height' to='10'/>
+    </rect>
+</svg>
+");
+
+    test_eq!(keep_scripted_1,
+"<svg>
+    <script>document.getElementById('hit').setAttribute('opacity', '1')</script>
+    <rect id='hit' height='10' opacity='0' width='10'/>
+    <rect height='10' onclick='evt.target.setAttribute(\"opacity\", \"1\")' opacity='0' width='10'/>
+</svg>
+");
+
+    test_eq!(keep_scripted_2,
+"<svg>
+    <script>document.getElementById('hit').setAttribute('visibility', 'visible')</script>
+    <rect id='hit' height='10' visibility='hidden' width='10'/>
+    <g visibility='hidden'>
+        <rect height='10' onclick='show()' width='10'/>
+    </g>
+</svg>
+");
 }
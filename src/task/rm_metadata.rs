@@ -0,0 +1,216 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::collections::HashMap;
+
+use svgdom::{
+    Document,
+    Name,
+    Node,
+    NodeType,
+};
+
+use task::short::EId;
+use task::utils;
+
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+
+// Namespaces with their commonly used prefixes.
+static NAMESPACES: &[(&str, &str)] = &[
+    ("rdf", RDF_NS),
+    ("dc", DC_NS),
+    ("cc", "http://creativecommons.org/ns#"),
+    ("cc", "http://web.resource.org/cc/"),
+];
+
+// Removes 'metadata' elements and RDF blocks outside of them.
+//
+// With `keep_license`, only license-related RDF data is preserved inside 'metadata':
+// Creative Commons licenses and Dublin Core rights and creators.
+pub fn remove_metadata(doc: &mut Document, keep_license: bool) {
+    let declared = utils::declared_namespaces(doc);
+
+    // Non-SVG elements are preserved only when the parser is configured to,
+    // so RDF blocks can be found outside the 'metadata' element too.
+    doc.drain(|n| {
+        is_element(n, &declared, RDF_NS, "RDF") && !n.parents().any(|p| p.is_tag_name(EId::Metadata))
+    });
+
+    let nodes: Vec<Node> = doc.descendants().filter(|n| n.is_tag_name(EId::Metadata)).collect();
+    for mut node in nodes {
+        if !keep_license || !strip_non_license(&node, &declared) {
+            node.remove();
+        }
+    }
+}
+
+// Removes all the children that are not a license data.
+//
+// Returns `true` if any license data is found.
+fn strip_non_license(node: &Node, declared: &HashMap<String, String>) -> bool {
+    let mut is_found = false;
+    for mut child in node.children().collect::<Vec<Node>>() {
+        if child.node_type() == NodeType::Element {
+            if is_license(&child, declared) {
+                is_found = true;
+                continue;
+            }
+
+            if strip_non_license(&child, declared) {
+                is_found = true;
+                continue;
+            }
+        }
+
+        child.remove();
+    }
+
+    is_found
+}
+
+fn is_license(node: &Node, declared: &HashMap<String, String>) -> bool {
+    let (uri, name) = match resolve_name(node, declared) {
+        Some(v) => v,
+        None => return false,
+    };
+
+    if uri == DC_NS {
+        name == "rights" || name == "creator"
+    } else if NAMESPACES.iter().any(|&(p, u)| p == "cc" && u == uri) {
+        // 'License' contains a license description and 'license' contains a link to it.
+        name == "License" || name == "license"
+    } else {
+        false
+    }
+}
+
+fn is_element(node: &Node, declared: &HashMap<String, String>, ns: &str, local_name: &str) -> bool {
+    match resolve_name(node, declared) {
+        Some((uri, name)) => uri == ns && name == local_name,
+        None => false,
+    }
+}
+
+// Returns a namespace URI and a local name of the element.
+//
+// Undeclared prefixes are resolved using the commonly used ones.
+fn resolve_name(node: &Node, declared: &HashMap<String, String>) -> Option<(String, String)> {
+    let tag_name = node.tag_name()?;
+    let name = match *tag_name {
+        Name::Name(ref name) => name,
+        Name::Id(_) => return None,
+    };
+
+    let (prefix, local_name) = utils::split_prefix(name)?;
+    let uri = match declared.get(prefix) {
+        Some(uri) => uri.clone(),
+        None => NAMESPACES.iter().find(|&&(p, _)| p == prefix)?.1.to_string(),
+    };
+
+    Some((uri, local_name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $keep_license:expr, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &mut Document| remove_metadata(doc, $keep_license),
+                       $in_text, $out_text);
+        )
+    }
+
+    test!(rm_1, false,
+"<svg>
+    <metadata>
+        <rdf:RDF>
+            <cc:Work>
+                <cc:license/>
+            </cc:Work>
+        </rdf:RDF>
+    </metadata>
+    <rdf:RDF/>
+    <title/>
+</svg>",
+"<svg>
+    <title/>
+</svg>
+");
+
+    test!(keep_license_1, true,
+"<svg>
+    <metadata>
+        <rdf:RDF>
+            <cc:Work>
+                <dc:format>image/svg+xml</dc:format>
+                <dc:title/>
+                <dc:creator>Author</dc:creator>
+                <cc:license/>
+            </cc:Work>
+            <cc:License>
+                <cc:permits/>
+            </cc:License>
+        </rdf:RDF>
+    </metadata>
+</svg>",
+"<svg>
+    <metadata>
+        <rdf:RDF>
+            <cc:Work>
+                <dc:creator>Author</dc:creator>
+                <cc:license/>
+            </cc:Work>
+            <cc:License>
+                <cc:permits/>
+            </cc:License>
+        </rdf:RDF>
+    </metadata>
+</svg>
+");
+
+    test!(keep_license_2, true,
+"<svg>
+    <metadata>
+        <rdf:RDF>
+            <cc:Work>
+                <dc:format>image/svg+xml</dc:format>
+            </cc:Work>
+        </rdf:RDF>
+    </metadata>
+</svg>",
+"<svg/>
+");
+
+    // Prefixes are resolved by the namespace and not by the name.
+    test!(keep_license_3, true,
+"<svg xmlns:cc='http://example.com/ns' xmlns:l='http://creativecommons.org/ns#'>
+    <metadata>
+        <cc:license/>
+        <l:license/>
+    </metadata>
+</svg>",
+"<svg xmlns:cc='http://example.com/ns' xmlns:l='http://creativecommons.org/ns#'>
+    <metadata>
+        <l:license/>
+    </metadata>
+</svg>
+");
+}
@@ -27,11 +27,20 @@ use svgdom::{
 };
 
 use task::short::{EId, AId};
+use task::utils::{self, AnimatedAttributes};
 
 // TODO: split to suboptions
 
 pub fn remove_needless_attributes(doc: &Document) {
+    let animated = AnimatedAttributes::new(doc);
+
     for (id, mut node) in doc.descendants().svg() {
+        // An animation can make a needless attribute useful,
+        // like 'fill-rule' after animating 'fill' from 'none'.
+        if node.parents_with_self().any(|n| animated.is_animated(&n)) {
+            continue;
+        }
+
         match id {
             EId::ClipPath => process_clip_path(&node),
             EId::Rect => process_rect(&mut node),
@@ -135,6 +144,10 @@ fn process_poly(node: &mut Node) {
 }
 
 fn is_basic_shapes_attr(a: &Attribute) -> bool {
+    if utils::is_accessibility_attr(a) {
+        return true;
+    }
+
     // List of common basic shapes attributes.
     // https://www.w3.org/TR/SVG/shapes.html#RectElement
 
@@ -379,6 +392,15 @@ mod tests {
 "<svg overflow='scroll'>
     <rect/>
 </svg>
+");
+
+    test!(keep_accessibility_1,
+"<svg>
+    <rect cx='5' role='img' tabindex='0' aria-label='square' data-name='square'/>
+</svg>",
+"<svg>
+    <rect role='img' tabindex='0' aria-label='square'/>
+</svg>
 ");
 
 }
@@ -0,0 +1,59 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Document,
+    Name,
+    Node,
+};
+
+use task::utils;
+
+// Removes non-SVG attributes, except the accessibility ones.
+pub fn remove_nonsvg_attributes(doc: &Document) {
+    let nodes: Vec<Node> = doc.descendants().collect();
+    for mut node in nodes {
+        node.attributes_mut().retain(|a| {
+            match a.name {
+                Name::Id(_) => true,
+                Name::Name(_) => utils::is_accessibility_attr(a),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, remove_nonsvg_attributes, $in_text, $out_text);
+        )
+    }
+
+    test!(rm_1,
+"<svg role='img' aria-labelledby='title' data-name='image'>
+    <rect tabindex='0' aria-label='rect' inkscape:label='rect' width='10'/>
+</svg>",
+"<svg role='img' aria-labelledby='title'>
+    <rect width='10' tabindex='0' aria-label='rect'/>
+</svg>
+");
+}
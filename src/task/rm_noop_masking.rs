@@ -0,0 +1,325 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Color,
+    Document,
+    Length,
+    Node,
+    ValueId,
+};
+
+use task::short::{EId, AId, Unit};
+use task::utils;
+use task::utils::bbox::{
+    calc_bbox,
+    calc_painted_bbox,
+    Rect,
+};
+
+// Removes 'clip-path' and 'mask' attributes that do not affect the rendering.
+//
+// A clip path is a no-op when one of its rects contains the whole element,
+// including a stroke. A mask is a no-op when it contains a single white
+// and opaque rect, which covers the whole element and the mask region does too.
+//
+// Unused 'clipPath' and 'mask' elements are removed too.
+pub fn remove_noop_masking(doc: &mut Document, keep_ids: &[String]) {
+    let nodes: Vec<Node> = doc.descendants()
+        .filter(|n| n.has_attribute(AId::ClipPath) || n.has_attribute(AId::Mask))
+        .collect();
+
+    for mut node in nodes {
+        // A bounding box with a stroke.
+        let bbox = match calc_painted_bbox(&node) {
+            Some(bbox) => bbox,
+            None => continue,
+        };
+
+        for aid in &[AId::ClipPath, AId::Mask] {
+            let mut link = match node.attributes().get_value(*aid) {
+                Some(AttributeValue::FuncLink(link)) => link.clone(),
+                _ => continue,
+            };
+
+            let is_noop = match *aid {
+                AId::ClipPath => is_noop_clip_path(&link, &bbox),
+                _ => is_noop_mask(&link, &node, &bbox),
+            };
+
+            if !is_noop {
+                continue;
+            }
+
+            node.remove_attribute(*aid);
+
+            if !link.is_used() && !utils::is_kept_id(&link, keep_ids) {
+                link.remove();
+            }
+        }
+    }
+}
+
+fn is_noop_clip_path(clip_path: &Node, bbox: &Rect) -> bool {
+    if !is_user_space(clip_path, AId::ClipPathUnits) {
+        return false;
+    }
+
+    if clip_path.has_attributes(&[AId::Transform, AId::ClipPath]) {
+        return false;
+    }
+
+    // A clipping region is a union of all children,
+    // so a single containing rect is enough.
+    clip_path.children().any(|child| {
+        match get_content_rect(&child) {
+            Some(rect) => rect.contains(bbox),
+            None => false,
+        }
+    })
+}
+
+fn is_noop_mask(mask: &Node, node: &Node, bbox: &Rect) -> bool {
+    if !is_user_space(mask, AId::MaskContentUnits) {
+        return false;
+    }
+
+    if mask.has_attributes(&[AId::ClipPath, AId::Mask, AId::Filter, AId::Opacity]) {
+        return false;
+    }
+
+    let region = match calc_mask_region(mask, node) {
+        Some(region) => region,
+        None => return false,
+    };
+
+    if !region.contains(bbox) {
+        return false;
+    }
+
+    let mut children = mask.children();
+    let child = match (children.next(), children.next()) {
+        (Some(child), None) => child,
+        _ => return false,
+    };
+
+    if child.has_attributes(&[AId::Opacity, AId::Filter, AId::Mask]) {
+        return false;
+    }
+
+    // Mask content inherits attributes from the 'mask' element ancestors.
+    // The default fill is black, so it must be set.
+    match utils::find_attribute(&child, AId::Fill) {
+        Some(AttributeValue::Color(c)) if c == Color::new(255, 255, 255) => {}
+        _ => return false,
+    }
+
+    match utils::find_attribute(&child, AId::FillOpacity) {
+        Some(AttributeValue::Number(n)) if n >= 1.0 => {}
+        None => {}
+        _ => return false,
+    }
+
+    // A semi-transparent stroke can make the edges darker.
+    if !matches!(utils::find_attribute(&child, AId::Stroke),
+                 Some(AttributeValue::PredefValue(ValueId::None)) | None) {
+        return false;
+    }
+
+    match get_content_rect(&child) {
+        Some(rect) => rect.contains(bbox),
+        None => false,
+    }
+}
+
+// Calculates a mask region in the user space of the masked element.
+fn calc_mask_region(mask: &Node, node: &Node) -> Option<Rect> {
+    let attrs = mask.attributes();
+
+    let x = get_value!(attrs, Length, AId::X, Length::new(-10.0, Unit::Percent));
+    let y = get_value!(attrs, Length, AId::Y, Length::new(-10.0, Unit::Percent));
+    let w = get_value!(attrs, Length, AId::Width, Length::new(120.0, Unit::Percent));
+    let h = get_value!(attrs, Length, AId::Height, Length::new(120.0, Unit::Percent));
+
+    if is_user_space(mask, AId::MaskUnits) {
+        // Percentage values are relative to the viewport, which we don't know.
+        if !(x.unit == Unit::None && y.unit == Unit::None &&
+             w.unit == Unit::None && h.unit == Unit::None) {
+            return None;
+        }
+
+        return Some(Rect { x1: x.num, y1: y.num, x2: x.num + w.num, y2: y.num + h.num });
+    }
+
+    let bbox = calc_bbox(node)?;
+    let bw = bbox.width();
+    let bh = bbox.height();
+
+    // Elements with a zero-sized bounding box are not rendered at all.
+    if !(bw > 0.0 && bh > 0.0) {
+        return None;
+    }
+
+    let fract = |len: Length| -> Option<f64> {
+        match len.unit {
+            Unit::None => Some(len.num),
+            Unit::Percent => Some(len.num / 100.0),
+            _ => None,
+        }
+    };
+
+    let x = bbox.x1 + fract(x)? * bw;
+    let y = bbox.y1 + fract(y)? * bh;
+    Some(Rect { x1: x, y1: y, x2: x + fract(w)? * bw, y2: y + fract(h)? * bh })
+}
+
+fn is_user_space(node: &Node, aid: AId) -> bool {
+    match node.attributes().get_value(aid) {
+        Some(&AttributeValue::PredefValue(v)) => v == ValueId::UserSpaceOnUse,
+        // Only 'maskUnits' is 'objectBoundingBox' by default.
+        None => aid != AId::MaskUnits,
+        _ => false,
+    }
+}
+
+// Returns a rect of a simple 'rect' element inside 'clipPath' or 'mask'.
+fn get_content_rect(node: &Node) -> Option<Rect> {
+    if !node.is_tag_name(EId::Rect) {
+        return None;
+    }
+
+    if node.has_attributes(&[AId::Transform, AId::ClipPath, AId::Display, AId::Visibility]) {
+        return None;
+    }
+
+    // Rounded corners can't contain anything.
+    if node.has_attributes(&[AId::Rx, AId::Ry]) {
+        return None;
+    }
+
+    calc_bbox(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &mut Document| remove_noop_masking(doc, &[]),
+                       $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    test!(clip_path_1,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='20' width='20'/>
+    </clipPath>
+    <circle clip-path='url(#clip1)' cx='10' cy='10' r='5'/>
+</svg>",
+"<svg>
+    <circle cx='10' cy='10' r='5'/>
+</svg>
+");
+
+    test!(clip_path_2,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='20' width='20'/>
+    </clipPath>
+    <circle clip-path='url(#clip1)' cx='10' cy='10' r='5'/>
+    <circle clip-path='url(#clip1)' cx='10' cy='10' r='15'/>
+</svg>",
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='20' width='20'/>
+    </clipPath>
+    <circle cx='10' cy='10' r='5'/>
+    <circle clip-path='url(#clip1)' cx='10' cy='10' r='15'/>
+</svg>
+");
+
+    // The stroke is clipped.
+    test_eq!(keep_clip_path_1,
+"<svg>
+    <clipPath id='clip1'>
+        <rect height='20' width='20'/>
+    </clipPath>
+    <circle clip-path='url(#clip1)' cx='10' cy='10' r='9' stroke='#000000' stroke-width='4'/>
+</svg>
+");
+
+    test_eq!(keep_clip_path_2,
+"<svg>
+    <clipPath id='clip1' clipPathUnits='objectBoundingBox'>
+        <rect height='20' width='20'/>
+    </clipPath>
+    <circle clip-path='url(#clip1)' cx='10' cy='10' r='5'/>
+</svg>
+");
+
+    test!(mask_1,
+"<svg>
+    <mask id='mask1'>
+        <rect fill='#ffffff' height='20' width='20'/>
+    </mask>
+    <circle cx='10' cy='10' mask='url(#mask1)' r='5'/>
+</svg>",
+"<svg>
+    <circle cx='10' cy='10' r='5'/>
+</svg>
+");
+
+    // The rect is not white.
+    test_eq!(keep_mask_1,
+"<svg>
+    <mask id='mask1'>
+        <rect fill='#fefefe' height='20' width='20'/>
+    </mask>
+    <circle cx='10' cy='10' mask='url(#mask1)' r='5'/>
+</svg>
+");
+
+    // The stroke is outside the default mask region.
+    test_eq!(keep_mask_2,
+"<svg>
+    <mask id='mask1'>
+        <rect fill='#ffffff' height='20' width='20'/>
+    </mask>
+    <circle cx='10' cy='10' mask='url(#mask1)' r='5' stroke='#000000' stroke-width='4'/>
+</svg>
+");
+
+    test_eq!(keep_mask_3,
+"<svg>
+    <mask id='mask1'>
+        <rect fill='#ffffff' fill-opacity='0.5' height='20' width='20'/>
+    </mask>
+    <circle cx='10' cy='10' mask='url(#mask1)' r='5'/>
+</svg>
+");
+}
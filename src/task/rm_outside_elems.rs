@@ -0,0 +1,279 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    ElementType,
+    Node,
+    Transform,
+    ValueId,
+};
+
+use task::short::{EId, AId, Unit};
+use task::utils::{self, AnimatedAttributes};
+use task::utils::bbox::{
+    calc_painted_bbox,
+    transform_rect,
+    Rect,
+};
+
+// Removes shapes and paths which are completely outside the root 'viewBox'.
+//
+// We are using a bounding box with a maximum possible stroke size,
+// so an element will be removed only when it is definitely invisible.
+pub fn remove_outside_elements(doc: &mut Document, keep_ids: &[String]) {
+    let svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => return,
+    };
+
+    let region = match get_visible_region(&svg) {
+        Some(r) => r,
+        None => return,
+    };
+
+    // An animation can move an element inside the 'viewBox'.
+    let animated = AnimatedAttributes::new(doc);
+
+    let mut is_any_removed = false;
+    let nodes: Vec<Node> = svg.descendants()
+        .filter(|n| !utils::is_kept_id(n, keep_ids) && is_outside(n, &svg, &region))
+        .filter(|n| !n.parents_with_self().any(|p| animated.is_animated(&p)))
+        .collect();
+
+    for mut node in nodes {
+        node.remove();
+        is_any_removed = true;
+    }
+
+    if is_any_removed {
+        super::remove_unused_defs(doc, keep_ids);
+    }
+}
+
+// Returns a region in the root user space, which can be visible.
+pub fn get_visible_region(svg: &Node) -> Option<Rect> {
+    let attrs = svg.attributes();
+
+    // Content outside the viewport is visible with 'overflow:visible'.
+    match attrs.get_value(AId::Overflow) {
+        Some(&AttributeValue::PredefValue(ValueId::Hidden)) |
+        Some(&AttributeValue::PredefValue(ValueId::Scroll)) | None => {}
+        _ => return None,
+    }
+
+    let vb = match attrs.get_value(AId::ViewBox) {
+        Some(AttributeValue::NumberList(list)) if list.len() == 4 => list.clone(),
+        _ => return None,
+    };
+
+    let (x, y, w, h) = (vb[0], vb[1], vb[2], vb[3]);
+    if !(w > 0.0 && h > 0.0) {
+        return None;
+    }
+
+    let mut rect = Rect { x1: x, y1: y, x2: x + w, y2: y + h };
+
+    // With 'meet' and a viewport aspect ratio different from the 'viewBox' one,
+    // an additional area along one of the axes will be visible.
+    let is_none = match attrs.get_value(AId::PreserveAspectRatio) {
+        Some(AttributeValue::String(s)) => {
+            if s.contains("slice") {
+                return Some(rect);
+            }
+
+            s.starts_with("none")
+        }
+        _ => false,
+    };
+
+    if is_none {
+        return Some(rect);
+    }
+
+    let (vw, vh) = match (attrs.get_value(AId::Width), attrs.get_value(AId::Height)) {
+        (Some(&AttributeValue::Length(vw)), Some(&AttributeValue::Length(vh))) => (vw, vh),
+        _ => return None,
+    };
+
+    if vw.unit != vh.unit || vw.unit == Unit::Percent || !(vw.num > 0.0 && vh.num > 0.0) {
+        return None;
+    }
+
+    let scale = (vw.num / w).min(vh.num / h);
+    // We don't know an alignment, so we extend the region in both directions.
+    let dx = vw.num / scale - w;
+    let dy = vh.num / scale - h;
+    rect.x1 -= dx;
+    rect.x2 += dx;
+    rect.y1 -= dy;
+    rect.y2 += dy;
+
+    Some(rect)
+}
+
+fn is_outside(node: &Node, svg: &Node, region: &Rect) -> bool {
+    if !node.is_graphic() || node.is_used() {
+        return false;
+    }
+
+    // Only the elements from the rendering tree of the root 'svg' are processed.
+    let is_rendered = node.parents().all(|p| {
+        p == *svg || matches!(p.tag_id(), Some(EId::G) | Some(EId::A) | Some(EId::Switch))
+    });
+
+    if !is_rendered {
+        return false;
+    }
+
+    // Filters and markers can be rendered outside the element bounding box.
+    let aids = [AId::Filter, AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd];
+    if node.parents_with_self().any(|n| n.has_attributes(&aids)) {
+        return false;
+    }
+
+    let bbox = match calc_painted_bbox(node) {
+        Some(bbox) => bbox,
+        None => return false,
+    };
+
+    let ts = match calc_abs_transform(node, svg) {
+        Some(ts) => ts,
+        None => return false,
+    };
+
+    !transform_rect(&bbox, &ts).intersects(region)
+}
+
+// Calculates a transform from the element user space to the root one.
+fn calc_abs_transform(node: &Node, svg: &Node) -> Option<Transform> {
+    let mut ts = Transform::default();
+    for n in node.parents_with_self().take_while(|n| n != svg) {
+        match n.attributes().get_value(AId::Transform) {
+            Some(AttributeValue::Transform(t)) => {
+                let mut t = *t;
+                t.append(&ts);
+                ts = t;
+            }
+            None => {}
+            _ => return None,
+        }
+    }
+
+    Some(ts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+    use task::{group_defs, remove_empty_defs};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut doc = Document::from_str($in_text).unwrap();
+                // We must prepare defs, because 'remove_outside_elements'
+                // invokes 'remove_unused_defs'.
+                group_defs(&mut doc);
+                remove_outside_elements(&mut doc, &[]);
+                remove_empty_defs(&mut doc);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(rm_rect_1,
+"<svg viewBox='0 0 100 100' width='100' height='100'>
+    <rect x='10' y='10' width='20' height='20'/>
+    <rect x='110' y='10' width='20' height='20'/>
+    <circle cx='-50' cy='50' r='20'/>
+</svg>",
+"<svg height='100' viewBox='0 0 100 100' width='100'>
+    <rect height='20' width='20' x='10' y='10'/>
+</svg>
+");
+
+    // Stroke is part of the bounding box.
+    test_eq!(keep_stroke_1,
+"<svg height='100' viewBox='0 0 100 100' width='100'>
+    <rect height='20' stroke='#000000' stroke-width='30' width='20' x='110' y='10'/>
+</svg>
+");
+
+    test!(rm_transform_1,
+"<svg viewBox='0 0 100 100' width='100' height='100'>
+    <g transform='translate(200 0)'>
+        <path d='M 10 10 L 20 20'/>
+    </g>
+    <path d='M 10 10 L 20 20' transform='translate(-15 0)'/>
+</svg>",
+"<svg height='100' viewBox='0 0 100 100' width='100'>
+    <g transform='translate(200)'/>
+    <path d='M 10 10 L 20 20' transform='translate(-15)'/>
+</svg>
+");
+
+    // The viewport is wider than the 'viewBox'.
+    test_eq!(keep_aspect_1,
+"<svg height='100' viewBox='0 0 100 100' width='200'>
+    <rect height='20' width='20' x='120' y='10'/>
+</svg>
+");
+
+    test!(rm_aspect_1,
+"<svg viewBox='0 0 100 100' width='200' height='100' preserveAspectRatio='none'>
+    <rect x='120' y='10' width='20' height='20'/>
+</svg>",
+"<svg height='100' preserveAspectRatio='none' viewBox='0 0 100 100' width='200'/>
+");
+
+    // Unknown viewport size.
+    test_eq!(keep_no_size_1,
+"<svg viewBox='0 0 100 100'>
+    <rect height='20' width='20' x='120' y='10'/>
+</svg>
+");
+
+    test_eq!(keep_overflow_1,
+"<svg height='100' overflow='visible' viewBox='0 0 100 100' width='100'>
+    <rect height='20' width='20' x='120' y='10'/>
+</svg>
+");
+
+    test!(keep_used_1,
+"<svg viewBox='0 0 100 100' width='100' height='100' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='r1' x='120' y='10' width='20' height='20'/>
+    <use xlink:href='#r1' x='-110'/>
+</svg>",
+"<svg height='100' viewBox='0 0 100 100' width='100' xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='r1' height='20' width='20' x='120' y='10'/>
+    <use x='-110' xlink:href='#r1'/>
+</svg>
+");
+
+    test!(rm_defs_1,
+"<svg viewBox='0 0 100 100' width='100' height='100'>
+    <linearGradient id='lg1'/>
+    <rect fill='url(#lg1)' x='120' y='10' width='20' height='20'/>
+</svg>",
+"<svg height='100' viewBox='0 0 100 100' width='100'/>
+");
+}
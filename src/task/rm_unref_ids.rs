@@ -18,9 +18,11 @@
 
 use svgdom::Document;
 
-pub fn remove_unreferenced_ids(doc: &Document) {
+use task::utils;
+
+pub fn remove_unreferenced_ids(doc: &Document, keep_ids: &[String]) {
     for mut node in doc.descendants() {
-        if node.has_id() && !node.is_used() {
+        if node.has_id() && !node.is_used() && !utils::is_kept_id(&node, keep_ids) {
             node.set_id(String::new());
         }
     }
@@ -34,7 +36,7 @@ mod tests {
 
     macro_rules! test {
         ($name:ident, $in_text:expr, $out_text:expr) => (
-            base_test!($name, remove_unreferenced_ids, $in_text, $out_text);
+            base_test!($name, |doc: &Document| remove_unreferenced_ids(doc, &[]), $in_text, $out_text);
         )
     }
 
@@ -49,5 +51,19 @@ mod tests {
     <radialGradient/>
     <rect fill='url(#lg1)'/>
 </svg>
+");
+    base_test!(keep_1, |doc: &Document| {
+        remove_unreferenced_ids(doc, &["icon-.*".to_string(), "logo".to_string()])
+    },
+"<svg>
+    <g id='icon-home'/>
+    <g id='icon'/>
+    <g id='logo'/>
+</svg>",
+"<svg>
+    <g id='icon-home'/>
+    <g/>
+    <g id='logo'/>
+</svg>
 ");
 }
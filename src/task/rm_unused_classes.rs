@@ -0,0 +1,108 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::AId;
+use task::utils;
+use task::utils::css;
+
+// Removes classes, which are not used by stylesheets and scripts.
+//
+// Classes resolved by the parser are already removed, so only classes from
+// the preserved rules, like ':hover', and classes referenced by scripts are left.
+pub fn remove_unused_classes(doc: &Document) {
+    let mut used = utils::ScriptRefs::new(doc).classes;
+    for node in css::style_text_nodes(doc) {
+        used.extend(css::class_names(&node.text()));
+    }
+
+    let nodes: Vec<Node> = doc.descendants().filter(|n| n.has_attribute(AId::Class)).collect();
+    for mut node in nodes {
+        let names = match node.attributes().get_value(AId::Class) {
+            Some(AttributeValue::String(s)) => {
+                s.split_whitespace().filter(|name| used.iter().any(|u| u == name))
+                 .collect::<Vec<_>>().join(" ")
+            }
+            _ => continue,
+        };
+
+        if names.is_empty() {
+            node.remove_attribute(AId::Class);
+        } else {
+            node.set_attribute((AId::Class, names));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, NodeType, ParseOptions, ToStringWithOptions};
+    use task::short::EId;
+
+    macro_rules! test {
+        ($name:ident, $css:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut opt = ParseOptions::default();
+                opt.skip_unresolved_classes = false;
+
+                let mut doc = Document::from_str_with_opt($in_text, &opt).unwrap();
+                if !$css.is_empty() {
+                    let mut svg = doc.svg_element().unwrap();
+                    let mut style = doc.create_element(EId::Style);
+                    let text = doc.create_node(NodeType::Text, $css);
+                    style.append(&text);
+                    svg.prepend(&style);
+                }
+                remove_unused_classes(&doc);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(rm_1, ".a:hover{fill:red} @media print{.b{fill:none}}",
+"<svg>
+    <rect class='a c'/>
+    <rect class='b'/>
+    <rect class='d'/>
+</svg>",
+"<svg>
+    <style>.a:hover{fill:red} @media print{.b{fill:none}}</style>
+    <rect class='a'/>
+    <rect class='b'/>
+    <rect/>
+</svg>
+");
+
+    test!(keep_1, "",
+"<svg>
+    <script>document.querySelector('.btn')</script>
+    <rect class='btn'/>
+</svg>",
+"<svg>
+    <script>document.querySelector('.btn')</script>
+    <rect class='btn'/>
+</svg>
+");
+}
@@ -22,11 +22,18 @@ use svgdom::{
 };
 
 use task::short::{EId, AId};
+use task::utils::AnimatedAttributes;
 
 pub fn remove_unused_coordinates(doc: &Document) {
+    let animated = AnimatedAttributes::new(doc);
     let mut rm_list = Vec::with_capacity(16);
 
     for (id, mut node) in doc.descendants().svg() {
+        // Removed coordinates depend on other ones, which can be animated.
+        if animated.is_animated(&node) {
+            continue;
+        }
+
         {
             let attrs = node.attributes();
 
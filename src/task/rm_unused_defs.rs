@@ -22,24 +22,29 @@ use svgdom::{
 };
 
 use task::short::EId;
+use task::utils;
 
-pub fn remove_unused_defs(doc: &Document) {
+pub fn remove_unused_defs(doc: &Document, keep_ids: &[String]) {
     // Unwrap is safe, because 'defs' already had been created in 'group_defs'.
     let mut defs = doc.descendants().filter(|n| n.is_tag_name(EId::Defs)).nth(0).unwrap();
 
     // Repeat until no unused nodes left.
-    while remove_unused_defs_impl(&mut defs) { }
+    while remove_unused_defs_impl(&mut defs, keep_ids) { }
 }
 
 // Returns true if tree structure has been changed.
-fn remove_unused_defs_impl(defs: &mut Node) -> bool {
+fn remove_unused_defs_impl(defs: &mut Node, keep_ids: &[String]) -> bool {
     // TODO: understand how styles are propagates inside defs
 
+    let alive = collect_alive(defs, keep_ids);
+
     let mut mv_nodes = Vec::new();
     let mut rm_nodes = Vec::new();
 
     for node in defs.children() {
-        if !node.is_used() && !is_font_node(&node) {
+        if    !is_font_node(&node)
+           && !utils::is_kept_id(&node, keep_ids)
+           && !is_used_by_alive(&node, &node, defs, &alive) {
             // Nodes outside defs we have to move to main 'defs' node, not to parent,
             // because otherwise they became renderable.
             ungroup_children(&node, &mut mv_nodes, &mut rm_nodes);
@@ -64,6 +69,55 @@ fn remove_unused_defs_impl(defs: &mut Node) -> bool {
     is_any_changed
 }
 
+// Collects 'defs' children that are referenced, directly or via other 'defs' children,
+// by the rendered content.
+//
+// Unlike 'Node::is_used', this method ignores elements that are referenced only
+// by themselves or by other unused elements, like a pattern with a shape that uses
+// this pattern.
+//
+// Kept nodes and their children are always alive.
+fn collect_alive(defs: &Node, keep_ids: &[String]) -> Vec<Node> {
+    let mut alive: Vec<Node> = defs.children()
+        .filter(|n| is_font_node(n) || n.descendants().any(|c| utils::is_kept_id(&c, keep_ids)))
+        .collect();
+
+    loop {
+        let mut is_changed = false;
+        for node in defs.children() {
+            if alive.contains(&node) {
+                continue;
+            }
+
+            if node.descendants().any(|n| is_used_by_alive(&n, &node, defs, &alive)) {
+                alive.push(node.clone());
+                is_changed = true;
+            }
+        }
+
+        if !is_changed {
+            break;
+        }
+    }
+
+    alive
+}
+
+// Checks that the node is referenced by an alive element outside the 'root' subtree.
+fn is_used_by_alive(node: &Node, root: &Node, defs: &Node, alive: &[Node]) -> bool {
+    node.linked_nodes().any(|link| {
+        if link.parents_with_self().any(|n| n == *root) {
+            return false;
+        }
+
+        // Find a 'defs' child that contains this link.
+        match link.parents_with_self().find(|n| n.parent().as_ref() == Some(defs)) {
+            Some(ref n) => alive.contains(n),
+            None => true,
+        }
+    })
+}
+
 fn is_font_node(node: &Node) -> bool {
     node.is_tag_name(EId::FontFace) || node.is_tag_name(EId::Font)
 }
@@ -87,7 +141,7 @@ mod tests {
 
     macro_rules! test {
         ($name:ident, $in_text:expr, $out_text:expr) => (
-            base_test!($name, remove_unused_defs, $in_text, $out_text);
+            base_test!($name, |doc: &Document| remove_unused_defs(doc, &[]), $in_text, $out_text);
         )
     }
 
@@ -250,5 +304,63 @@ mod tests {
         </font-face>
     </defs>
 </svg>
+");
+
+    test!(recursive_2,
+"<svg>
+    <defs>
+        <pattern id='patt1'>
+            <rect fill='url(#patt1)'/>
+        </pattern>
+    </defs>
+</svg>",
+"<svg>
+    <defs/>
+</svg>
+");
+
+    test!(recursive_3,
+"<svg>
+    <defs>
+        <linearGradient id='lg1'>
+            <stop/>
+        </linearGradient>
+        <pattern id='patt1'>
+            <rect fill='url(#lg1)'/>
+        </pattern>
+        <marker id='marker1'>
+            <rect fill='url(#patt1)'/>
+        </marker>
+    </defs>
+    <path marker-start='url(#marker1)'/>
+</svg>",
+"<svg>
+    <defs>
+        <linearGradient id='lg1'>
+            <stop/>
+        </linearGradient>
+        <pattern id='patt1'>
+            <rect fill='url(#lg1)'/>
+        </pattern>
+        <marker id='marker1'>
+            <rect fill='url(#patt1)'/>
+        </marker>
+    </defs>
+    <path marker-start='url(#marker1)'/>
+</svg>
+");
+
+    base_test!(keep_1, |doc: &Document| remove_unused_defs(doc, &["lg1".to_string()]),
+"<svg>
+    <defs>
+        <linearGradient id='lg1'/>
+        <linearGradient id='lg2'/>
+    </defs>
+</svg>",
+"<svg>
+    <defs>
+        <linearGradient id='lg1'/>
+    </defs>
+</svg>
 ");
 }
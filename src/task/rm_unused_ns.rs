@@ -0,0 +1,229 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::collections::HashSet;
+
+use svgdom::{
+    Document,
+    Name,
+    Node,
+    NodeType,
+};
+
+use task::utils;
+
+// Removes unused 'xmlns:*' declarations and moves the used ones,
+// which are declared only by nested elements, to the root element.
+//
+// 'xmlns:xlink' is handled by 'fix_xmlns_attribute'.
+pub fn remove_unused_namespaces(doc: &Document) {
+    // Remove declarations, which prefixes are not used in their scope.
+    for (mut node, prefix) in declarations(doc) {
+        if !is_used(&node, &prefix, true) {
+            node.remove_attribute(xmlns_name(&prefix).as_str());
+        }
+    }
+
+    hoist_declarations(doc);
+}
+
+fn hoist_declarations(doc: &Document) {
+    // doc must contain 'svg' node, so we can safely unwrap.
+    let mut svg = doc.svg_element().unwrap();
+
+    let decls = declarations(doc);
+
+    let mut prefixes: Vec<&String> = Vec::new();
+    for (_, prefix) in &decls {
+        if !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+    }
+
+    for prefix in prefixes {
+        let name = xmlns_name(prefix);
+        let nodes: Vec<&Node> = decls.iter().filter(|&(_, p)| p == prefix).map(|d| &d.0).collect();
+
+        // We can't move a declaration when the same prefix is bound to different namespaces.
+        let uris: HashSet<String> = nodes.iter().map(|n| {
+            n.attributes().get_value(name.as_str()).map(|v| v.to_string()).unwrap_or_default()
+        }).collect();
+        if uris.len() != 1 || (nodes.len() == 1 && *nodes[0] == svg) {
+            continue;
+        }
+
+        for node in nodes {
+            if *node != svg {
+                node.clone().remove_attribute(name.as_str());
+            }
+        }
+
+        let uri = uris.into_iter().next().unwrap();
+        svg.set_attribute((name.as_str(), uri));
+    }
+}
+
+// Returns all 'xmlns:*' declarations in the document order.
+fn declarations(doc: &Document) -> Vec<(Node, String)> {
+    let mut list = Vec::new();
+    for node in doc.descendants().filter(|n| n.node_type() == NodeType::Element) {
+        for attr in node.attributes().iter() {
+            if let Name::Name(ref name) = attr.name {
+                if let Some(prefix) = name.strip_prefix("xmlns:") {
+                    list.push((node.clone(), prefix.to_string()));
+                }
+            }
+        }
+    }
+
+    list
+}
+
+// Checks that the prefix is used by the node or its descendants,
+// skipping elements that redeclare this prefix.
+fn is_used(node: &Node, prefix: &str, is_root: bool) -> bool {
+    if node.node_type() != NodeType::Element {
+        return false;
+    }
+
+    if !is_root && node.has_attribute(xmlns_name(prefix).as_str()) {
+        return false;
+    }
+
+    if let Name::Name(ref name) = *node.tag_name().unwrap() {
+        if has_prefix(name, prefix) {
+            return true;
+        }
+    }
+
+    let is_attr_used = node.attributes().iter().any(|attr| {
+        match attr.name {
+            Name::Name(ref name) => !name.starts_with("xmlns:") && has_prefix(name, prefix),
+            Name::Id(_) => false,
+        }
+    });
+
+    is_attr_used || node.children().any(|child| is_used(&child, prefix, false))
+}
+
+fn has_prefix(name: &str, prefix: &str) -> bool {
+    utils::split_prefix(name).is_some_and(|(p, _)| p == prefix)
+}
+
+fn xmlns_name(prefix: &str) -> String {
+    format!("xmlns:{}", prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, remove_unused_namespaces, $in_text, $out_text);
+        )
+    }
+
+    test!(rm_1,
+"<svg xmlns:a='http://a' xmlns:b='http://b' xmlns:c='http://c'>
+    <rect a:foo='1'/>
+    <c:item/>
+</svg>",
+"<svg xmlns:a='http://a' xmlns:c='http://c'>
+    <rect a:foo='1'/>
+    <c:item/>
+</svg>
+");
+
+    test!(rm_2,
+"<svg>
+    <g xmlns:a='http://a'>
+        <rect/>
+    </g>
+</svg>",
+"<svg>
+    <g>
+        <rect/>
+    </g>
+</svg>
+");
+
+    test!(move_1,
+"<svg>
+    <g xmlns:a='http://a'>
+        <rect a:foo='1'/>
+    </g>
+    <a:item xmlns:a='http://a'/>
+</svg>",
+"<svg xmlns:a='http://a'>
+    <g>
+        <rect a:foo='1'/>
+    </g>
+    <a:item/>
+</svg>
+");
+
+    // Duplicated declaration.
+    test!(move_2,
+"<svg xmlns:a='http://a'>
+    <g xmlns:a='http://a'>
+        <rect a:foo='1'/>
+    </g>
+</svg>",
+"<svg xmlns:a='http://a'>
+    <g>
+        <rect a:foo='1'/>
+    </g>
+</svg>
+");
+
+    // The prefix is bound to different namespaces.
+    test!(keep_1,
+"<svg>
+    <g xmlns:a='http://a'>
+        <rect a:foo='1'/>
+    </g>
+    <g xmlns:a='http://b'>
+        <rect a:foo='1'/>
+    </g>
+</svg>",
+"<svg>
+    <g xmlns:a='http://a'>
+        <rect a:foo='1'/>
+    </g>
+    <g xmlns:a='http://b'>
+        <rect a:foo='1'/>
+    </g>
+</svg>
+");
+
+    // The root declaration is shadowed.
+    test!(move_3,
+"<svg xmlns:a='http://a'>
+    <g xmlns:a='http://b'>
+        <rect a:foo='1'/>
+    </g>
+</svg>",
+"<svg xmlns:a='http://b'>
+    <g>
+        <rect a:foo='1'/>
+    </g>
+</svg>
+");
+}
@@ -98,7 +98,14 @@ pub fn round_numbers(doc: &Document, opt: &CleaningOptions) {
                     }
                 }
 
-                AId::ViewBox |
+                AId::ViewBox => {
+                    if let AttributeValue::NumberList(ref mut list) = attr.value {
+                        for n in list.iter_mut() {
+                            round_number(n, coord_precision);
+                        }
+                    }
+                }
+
                 AId::Points => {
                     if let AttributeValue::NumberList(ref mut list) = attr.value {
                         for n in list.iter_mut() {
@@ -129,11 +136,11 @@ static POW_VEC: &'static [f64] = &[
     1_000_000_000_000.0,
 ];
 
-fn round_number(n: &mut f64, precision: usize) {
+pub fn round_number(n: &mut f64, precision: usize) {
     *n = (*n * POW_VEC[precision]).round() / POW_VEC[precision];
 }
 
-fn round_path(path: &mut path::Path, precision: usize) {
+pub fn round_path(path: &mut path::Path, precision: usize) {
     use svgdom::path::SegmentData;
 
     for seg in &mut path.d {
@@ -0,0 +1,163 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Attribute,
+    AttributeValue,
+    Document,
+    Name,
+    NodeType,
+};
+
+use task::short::{EId, AId};
+use task::{remove_element, remove_scripts};
+
+// Removes everything that can execute code or load external resources:
+// scripts, event attributes, 'foreignObject' elements, external links
+// and data URIs, except embedded images.
+//
+// Non-SVG elements and 'on*' attributes are removed too, because they are preserved
+// only when the parser is configured to and can't be checked.
+pub fn sanitize(doc: &mut Document) {
+    remove_scripts(doc);
+    remove_element(doc, EId::ForeignObject);
+    doc.drain(|n| n.node_type() == NodeType::Element && n.tag_id().is_none());
+
+    for mut node in doc.descendants() {
+        let is_image = node.is_tag_name(EId::Image) || node.is_tag_name(EId::FeImage);
+        node.attributes_mut().retain(|a| !is_unsafe_link(a, is_image) && !is_unknown_event(a));
+    }
+}
+
+fn is_unknown_event(attr: &Attribute) -> bool {
+    match attr.name {
+        Name::Name(ref name) => name.to_lowercase().starts_with("on"),
+        Name::Id(_) => false,
+    }
+}
+
+// Local links are parsed as 'Link' values, so any string is an external one.
+fn is_unsafe_link(attr: &Attribute, is_image: bool) -> bool {
+    let is_href = match attr.name {
+        Name::Id(aid) => aid == AId::XlinkHref,
+        Name::Name(ref name) => name == "href",
+    };
+
+    match attr.value {
+        AttributeValue::String(ref s) if is_href => !(is_image && is_image_data_uri(s)),
+        _ => false,
+    }
+}
+
+fn is_image_data_uri(text: &str) -> bool {
+    text.trim_start().to_lowercase().starts_with("data:image/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ParseOptions, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, sanitize, $in_text, $out_text);
+        )
+    }
+
+    test!(rm_scripts_1,
+"<svg onload='init()'>
+    <script>init = function() {}</script>
+    <rect onclick='init()'/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    test!(rm_foreign_object_1,
+"<svg>
+    <foreignObject>
+        <rect/>
+    </foreignObject>
+    <rect/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    test!(rm_external_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <a xlink:href='http://example.com'/>
+    <image xlink:href='file:///etc/passwd'/>
+    <use xlink:href='http://example.com/image.svg#rect'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <a/>
+    <image/>
+    <use/>
+</svg>
+");
+
+    test!(rm_data_uri_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/png;base64,AAAA'/>
+    <image xlink:href='data:text/html;base64,AAAA'/>
+    <a xlink:href='data:image/svg+xml;base64,AAAA'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <image xlink:href='data:image/png;base64,AAAA'/>
+    <image/>
+    <a/>
+</svg>
+");
+
+    #[test]
+    fn rm_nonsvg_1() {
+        let opt = ParseOptions {
+            parse_unknown_elements: true,
+            parse_unknown_attributes: true,
+            .. ParseOptions::default()
+        };
+
+        let mut doc = Document::from_str_with_opt(
+"<svg xmlns:html='http://www.w3.org/1999/xhtml'>
+    <html:iframe src='http://example.com'/>
+    <rect onfoo='f()' href='http://example.com' aria-label='Label'/>
+</svg>", &opt).unwrap();
+
+        sanitize(&mut doc);
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg xmlns:html='http://www.w3.org/1999/xhtml'>
+    <rect aria-label='Label'/>
+</svg>
+");
+    }
+
+    test!(keep_local_1,
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='rect1'/>
+    <use xlink:href='#rect1'/>
+</svg>",
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <rect id='rect1'/>
+    <use xlink:href='#rect1'/>
+</svg>
+");
+}
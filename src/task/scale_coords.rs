@@ -0,0 +1,138 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Transform,
+};
+
+use options::CleaningOptions;
+use task::short::{AId, Unit};
+use task::apply_transforms;
+use warning::{
+    self,
+    Warning,
+    WarningCode,
+};
+
+// Multiplies the whole coordinate system by the specified factor.
+//
+// The 'viewBox' is scaled too, so the image will look exactly the same.
+// It's useful to get rid of decimal points, when all coordinates
+// have the same number of decimal digits.
+pub fn scale_coordinates(doc: &Document, factor: f64, opt: &CleaningOptions) {
+    let mut svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => return,
+    };
+
+    let view_box = {
+        let attrs = svg.attributes();
+        match attrs.get_value(AId::ViewBox) {
+            Some(AttributeValue::NumberList(list)) if list.len() == 4 => list.clone(),
+            _ => {
+                // Without a 'viewBox' the user space is defined by the size,
+                // so we have to create it.
+                match (attrs.get_value(AId::Width), attrs.get_value(AId::Height)) {
+                    (Some(&AttributeValue::Length(w)), Some(&AttributeValue::Length(h)))
+                        if is_user_unit(w.unit) && is_user_unit(h.unit) => {
+                        vec![0.0, 0.0, w.num, h.num]
+                    }
+                    _ => return,
+                }
+            }
+        }
+    };
+
+    let ts = Transform::new(factor, 0.0, 0.0, factor, 0.0, 0.0);
+    if !apply_transforms::prepend_transform_to_content(doc, &ts) {
+        warning::add(Warning::new(WarningCode::SkippedTask,
+            "the 'scale-coordinates' task is skipped, because the content can't be transformed"));
+        return;
+    }
+
+    let view_box: Vec<f64> = view_box.iter().map(|n| n * factor).collect();
+    svg.set_attribute((AId::ViewBox, view_box));
+
+    apply_transforms::apply_transform_to_content(doc, opt);
+}
+
+fn is_user_unit(unit: Unit) -> bool {
+    unit == Unit::None || unit == Unit::Px
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                let opt = CleaningOptions {
+                    apply_transform_to_shapes: true,
+                    apply_transform_to_paths: true,
+                    paths_to_relative: true,
+                    ..CleaningOptions::default()
+                };
+                scale_coordinates(doc, 10.0, &opt)
+            }, $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    test!(scale_1,
+"<svg viewBox='0.5 0 10.5 10'>
+    <rect height='2.5' width='4.5' x='1.5' y='2.5'/>
+    <path d='M 1.5 2.5 l 0.5 0.5' stroke='#000000' stroke-width='0.1'/>
+</svg>",
+"<svg viewBox='5 0 105 100'>
+    <rect height='25' stroke-width='10' width='45' x='15' y='25'/>
+    <path d='M 15 25 l 5 5' stroke='#000000' stroke-width='1'/>
+</svg>
+");
+
+    test!(scale_2,
+"<svg height='10' width='10'>
+    <circle cx='1.5' cy='2.5' r='0.5'/>
+</svg>",
+"<svg height='10' viewBox='0 0 100 100' width='10'>
+    <circle cx='15' cy='25' r='5' stroke-width='10'/>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg height='10mm' width='10mm'>
+    <circle cx='1.5' cy='2.5' r='0.5'/>
+</svg>
+");
+
+    test_eq!(keep_2,
+"<svg viewBox='0 0 10 10'>
+    <rect height='2.5' width='4.5' x='1.5' y='2.5'>
+        <animateMotion path='M 0 0 L 1 1'/>
+    </rect>
+</svg>
+");
+}
@@ -0,0 +1,688 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+    ValueId,
+};
+
+use task::short::{EId, AId, Unit};
+use task::utils::{self, AnimatedAttributes};
+
+#[derive(Clone, PartialEq, Debug)]
+enum Input {
+    // One of the standard inputs, like 'SourceGraphic'.
+    Keyword(String),
+    // An index of the filter primitive.
+    Primitive(usize),
+}
+
+struct Primitive {
+    node: Node,
+    // 'in', 'in2' or 'feMergeNode' inputs.
+    inputs: Vec<Input>,
+    // An input that is passed through as is, when a primitive is a no-op.
+    identity: Option<Input>,
+}
+
+pub fn simplify_filters(doc: &mut Document, keep_ids: &[String]) {
+    let animated = AnimatedAttributes::new(doc);
+
+    let filters: Vec<Node> = doc.descendants().filter(|n| n.is_tag_name(EId::Filter)).collect();
+    for mut filter in filters {
+        if !can_simplify(&filter, &animated, keep_ids) {
+            continue;
+        }
+
+        rm_default_region(&mut filter);
+
+        // Each step can make other steps possible, so repeat until nothing is changed.
+        loop {
+            let list = match parse_primitives(&filter) {
+                Some(list) => list,
+                None => break,
+            };
+
+            let output = resolve(&list, Input::Primitive(list.len() - 1));
+            if output == Input::Keyword("SourceGraphic".to_string()) {
+                // The whole filter is a no-op.
+                // Referencing 'filter' attributes will be removed automatically.
+                if !utils::is_kept_id(&filter, keep_ids) {
+                    filter.remove();
+                }
+                break;
+            }
+
+            if !rm_trailing_identity(&list)
+                && !rm_unreachable(&list)
+                && !rm_default_inputs(&list)
+                && !rm_unused_results(&list) {
+                break;
+            }
+        }
+    }
+}
+
+fn can_simplify(filter: &Node, animated: &AnimatedAttributes, keep_ids: &[String]) -> bool {
+    // Filter should not be linked.
+    if filter.has_attribute(AId::XlinkHref) {
+        return false;
+    }
+
+    // And should not be used as a template by other filters.
+    if filter.linked_nodes().any(|n| n.is_tag_name(EId::Filter)) {
+        return false;
+    }
+
+    if animated.is_animated(filter) {
+        return false;
+    }
+
+    for child in filter.children() {
+        match child.tag_id() {
+            Some(id) if is_primitive(id) => {}
+            _ => return false,
+        }
+
+        for node in child.descendants() {
+            if node.is_used() || utils::is_kept_id(&node, keep_ids) || animated.is_animated(&node) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+fn is_primitive(id: EId) -> bool {
+    matches!(id, EId::FeBlend | EId::FeColorMatrix | EId::FeComponentTransfer
+               | EId::FeComposite | EId::FeConvolveMatrix | EId::FeDiffuseLighting
+               | EId::FeDisplacementMap | EId::FeFlood | EId::FeGaussianBlur
+               | EId::FeImage | EId::FeMerge | EId::FeMorphology | EId::FeOffset
+               | EId::FeSpecularLighting | EId::FeTile | EId::FeTurbulence)
+}
+
+fn is_keyword(name: &str) -> bool {
+    matches!(name, "SourceGraphic" | "SourceAlpha" | "BackgroundImage" | "BackgroundAlpha"
+                 | "FillPaint" | "StrokePaint")
+}
+
+// The filter region attributes are equal to defaults only in the 'objectBoundingBox' units.
+fn rm_default_region(filter: &mut Node) {
+    let is_bbox = match filter.attributes().get_value(AId::FilterUnits) {
+        Some(&AttributeValue::PredefValue(v)) => v == ValueId::ObjectBoundingBox,
+        None => true,
+        _ => false,
+    };
+
+    if !is_bbox {
+        return;
+    }
+
+    filter.attributes_mut().retain(|attr| {
+        match attr.id() {
+            Some(AId::X) | Some(AId::Y) => {
+                attr.value != AttributeValue::from((-10.0, Unit::Percent))
+            }
+            Some(AId::Width) | Some(AId::Height) => {
+                attr.value != AttributeValue::from((120.0, Unit::Percent))
+            }
+            _ => true,
+        }
+    });
+
+    let is_default_units = match filter.attributes().get_value(AId::FilterUnits) {
+        Some(&AttributeValue::PredefValue(v)) => v == ValueId::ObjectBoundingBox,
+        _ => false,
+    };
+
+    if is_default_units {
+        filter.remove_attribute(AId::FilterUnits);
+    }
+}
+
+// Returns 'None' when the filter contains something that we do not support.
+fn parse_primitives(filter: &Node) -> Option<Vec<Primitive>> {
+    let mut list: Vec<Primitive> = Vec::new();
+    let mut results: Vec<Option<String>> = Vec::new();
+
+    for node in filter.children() {
+        let idx = list.len();
+        let mut inputs = Vec::new();
+
+        match node.tag_id().unwrap() {
+            EId::FeFlood | EId::FeImage | EId::FeTurbulence => {}
+            EId::FeMerge => {
+                for child in node.children() {
+                    if !child.is_tag_name(EId::FeMergeNode) {
+                        return None;
+                    }
+
+                    inputs.push(parse_input(&child, AId::In, idx, &results)?);
+                }
+            }
+            EId::FeBlend | EId::FeComposite | EId::FeDisplacementMap => {
+                inputs.push(parse_input(&node, AId::In, idx, &results)?);
+                inputs.push(parse_input(&node, AId::In2, idx, &results)?);
+            }
+            _ => {
+                inputs.push(parse_input(&node, AId::In, idx, &results)?);
+            }
+        }
+
+        let result = match node.attributes().get_value(AId::Result) {
+            Some(AttributeValue::String(name)) => Some(name.clone()),
+            Some(_) => return None,
+            None => None,
+        };
+
+        let mut prim = Primitive {
+            node: node.clone(),
+            inputs,
+            identity: None,
+        };
+        prim.identity = identity_input(&prim, &list);
+
+        list.push(prim);
+        results.push(result);
+    }
+
+    if list.is_empty() {
+        None
+    } else {
+        Some(list)
+    }
+}
+
+fn parse_input(node: &Node, aid: AId, idx: usize, results: &[Option<String>]) -> Option<Input> {
+    match node.attributes().get_value(aid) {
+        Some(AttributeValue::String(name)) => {
+            if is_keyword(name) {
+                return Some(Input::Keyword(name.clone()));
+            }
+
+            // A reference to the most recent primitive with the same 'result'.
+            match results.iter().rposition(|r| r.as_ref() == Some(name)) {
+                Some(i) => Some(Input::Primitive(i)),
+                // An unknown reference is the same as a missing one.
+                None => Some(implicit_input(idx)),
+            }
+        }
+        Some(_) => None,
+        None => Some(implicit_input(idx)),
+    }
+}
+
+// The first primitive has 'SourceGraphic' as a default input
+// and all others use the result of a previous one.
+fn implicit_input(idx: usize) -> Input {
+    if idx == 0 {
+        Input::Keyword("SourceGraphic".to_string())
+    } else {
+        Input::Primitive(idx - 1)
+    }
+}
+
+// Skips all no-op primitives.
+fn resolve(list: &[Primitive], mut input: Input) -> Input {
+    while let Input::Primitive(idx) = input {
+        match list[idx].identity {
+            Some(ref next) => input = next.clone(),
+            None => break,
+        }
+    }
+
+    input
+}
+
+fn identity_input(prim: &Primitive, list: &[Primitive]) -> Option<Input> {
+    let node = &prim.node;
+    let attrs = node.attributes();
+
+    // A primitive subregion can clip the input.
+    if node.has_attributes(&[AId::X, AId::Y, AId::Width, AId::Height]) {
+        return None;
+    }
+
+    let is_identity = match node.tag_id().unwrap() {
+        EId::FeOffset => {
+            is_zero(attrs.get_value(AId::Dx)) && is_zero(attrs.get_value(AId::Dy))
+        }
+        EId::FeColorMatrix => is_identity_matrix(node),
+        // A zero deviation disables the blur.
+        EId::FeGaussianBlur => {
+            match attrs.get_value(AId::StdDeviation) {
+                Some(AttributeValue::NumberList(list)) => list.iter().all(|n| *n == 0.0),
+                value => is_zero(value),
+            }
+        }
+        EId::FeBlend => {
+            let is_normal = match attrs.get_value(AId::Mode) {
+                Some(AttributeValue::String(mode)) => mode == "normal",
+                None => true,
+                _ => false,
+            };
+
+            // Blending with a transparent image does nothing.
+            is_normal && match resolve(list, prim.inputs[1].clone()) {
+                Input::Primitive(idx) => is_transparent_flood(&list[idx].node),
+                Input::Keyword(_) => false,
+            }
+        }
+        _ => false,
+    };
+
+    if is_identity {
+        Some(prim.inputs[0].clone())
+    } else {
+        None
+    }
+}
+
+fn is_zero(value: Option<&AttributeValue>) -> bool {
+    match value {
+        Some(&AttributeValue::Number(n)) => n == 0.0,
+        Some(&AttributeValue::Length(l)) => l.num == 0.0,
+        None => true,
+        _ => false,
+    }
+}
+
+fn is_identity_matrix(node: &Node) -> bool {
+    let attrs = node.attributes();
+
+    let kind = match attrs.get_value(AId::Type) {
+        Some(AttributeValue::String(kind)) => kind.clone(),
+        None => "matrix".to_string(),
+        _ => return false,
+    };
+
+    let values = match attrs.get_value(AId::Values) {
+        Some(AttributeValue::String(values)) => {
+            let mut list = Vec::new();
+            for v in values.split(|c: char| c.is_whitespace() || c == ',') {
+                if v.is_empty() {
+                    continue;
+                }
+
+                match v.parse::<f64>() {
+                    Ok(n) => list.push(n),
+                    Err(_) => return false,
+                }
+            }
+
+            list
+        }
+        Some(&AttributeValue::Number(n)) => vec![n],
+        Some(AttributeValue::NumberList(list)) => list.clone(),
+        None => Vec::new(),
+        _ => return false,
+    };
+
+    match kind.as_str() {
+        "matrix" => {
+            values.is_empty()
+                || values.len() == 20
+                   && values.iter().enumerate().all(|(i, v)| {
+                       let expected = if i % 6 == 0 { 1.0 } else { 0.0 };
+                       *v == expected
+                   })
+        }
+        "saturate" => values.is_empty() || values == [1.0],
+        "hueRotate" => values.is_empty() || values == [0.0],
+        _ => false,
+    }
+}
+
+fn is_transparent_flood(node: &Node) -> bool {
+    if !node.is_tag_name(EId::FeFlood) {
+        return false;
+    }
+
+    match node.attributes().get_value(AId::FloodOpacity) {
+        Some(&AttributeValue::Number(n)) => n == 0.0,
+        _ => false,
+    }
+}
+
+// Removes the last primitive when it passes the previous one as is.
+fn rm_trailing_identity(list: &[Primitive]) -> bool {
+    if list.len() < 2 {
+        return false;
+    }
+
+    let last = list.last().unwrap();
+    if last.identity == Some(Input::Primitive(list.len() - 2)) {
+        last.node.clone().remove();
+        return true;
+    }
+
+    false
+}
+
+// Removes primitives that do not contribute to the filter output.
+fn rm_unreachable(list: &[Primitive]) -> bool {
+    let mut reachable = vec![false; list.len()];
+    reachable[list.len() - 1] = true;
+
+    for (idx, prim) in list.iter().enumerate().rev() {
+        if !reachable[idx] {
+            continue;
+        }
+
+        for input in &prim.inputs {
+            if let Input::Primitive(i) = *input {
+                reachable[i] = true;
+            }
+        }
+    }
+
+    let mut is_changed = false;
+    for (prim, _) in list.iter().zip(reachable).filter(|&(_, r)| !r) {
+        prim.node.clone().remove();
+        is_changed = true;
+    }
+
+    is_changed
+}
+
+// Removes 'in' attributes that are equal to the implicit input.
+fn rm_default_inputs(list: &[Primitive]) -> bool {
+    let mut is_changed = false;
+    for (idx, prim) in list.iter().enumerate() {
+        if prim.node.is_tag_name(EId::FeMerge) || prim.inputs.is_empty() {
+            continue;
+        }
+
+        if prim.node.has_attribute(AId::In) && prim.inputs[0] == implicit_input(idx) {
+            prim.node.clone().remove_attribute(AId::In);
+            is_changed = true;
+        }
+    }
+
+    is_changed
+}
+
+// Removes 'result' attributes that are not referenced by any other primitive.
+fn rm_unused_results(list: &[Primitive]) -> bool {
+    let mut is_changed = false;
+    for (idx, prim) in list.iter().enumerate() {
+        let is_used = {
+            let attrs = prim.node.attributes();
+            let name = match attrs.get_value(AId::Result) {
+                Some(AttributeValue::String(name)) => name,
+                _ => continue,
+            };
+
+            list.iter().skip(idx + 1).any(|p| is_referenced_by_name(p, idx, name))
+        };
+
+        if !is_used {
+            prim.node.clone().remove_attribute(AId::Result);
+            is_changed = true;
+        }
+    }
+
+    is_changed
+}
+
+// Checks that the primitive references a primitive with the 'target' index by its name.
+fn is_referenced_by_name(prim: &Primitive, target: usize, name: &str) -> bool {
+    let has_name = |node: &Node, aid: AId| {
+        match node.attributes().get_value(aid) {
+            Some(AttributeValue::String(s)) => s == name,
+            _ => false,
+        }
+    };
+
+    let input = Input::Primitive(target);
+
+    if prim.node.is_tag_name(EId::FeMerge) {
+        return prim.node.children().zip(prim.inputs.iter()).any(|(child, i)| {
+            *i == input && has_name(&child, AId::In)
+        });
+    }
+
+    let aids = [AId::In, AId::In2];
+    prim.inputs.iter().zip(aids.iter()).any(|(i, aid)| *i == input && has_name(&prim.node, *aid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |mut doc: &mut Document| simplify_filters(&mut doc, &[]),
+                       $in_text, $out_text);
+        )
+    }
+
+    test!(rm_noop_1,
+"<svg>
+    <filter id='f1'>
+        <feOffset dx='0' dy='0'/>
+    </filter>
+    <rect filter='url(#f1)'/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    test!(rm_noop_2,
+"<svg>
+    <filter id='f1'>
+        <feColorMatrix values='1 0 0 0 0 0 1 0 0 0 0 0 1 0 0 0 0 0 1 0' result='r1'/>
+        <feColorMatrix in='r1' type='saturate' values='1'/>
+        <feColorMatrix type='hueRotate'/>
+    </filter>
+    <rect filter='url(#f1)'/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    test!(rm_noop_3,
+"<svg>
+    <filter id='f1'>
+        <feFlood flood-opacity='0' result='empty'/>
+        <feBlend in='SourceGraphic' in2='empty' mode='normal'/>
+    </filter>
+    <rect filter='url(#f1)'/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    test!(rm_noop_4,
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='0'/>
+    </filter>
+    <filter id='f2'>
+        <feGaussianBlur stdDeviation='0 0'/>
+    </filter>
+    <rect filter='url(#f1)'/>
+    <rect filter='url(#f2)'/>
+</svg>",
+"<svg>
+    <rect/>
+    <rect/>
+</svg>
+");
+
+    test!(rm_noop_5,
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur/>
+        <feOffset dx='0'/>
+    </filter>
+    <rect filter='url(#f1)'/>
+</svg>",
+"<svg>
+    <rect/>
+</svg>
+");
+
+    // Blur only along the X axis.
+    test_eq!(keep_blur_1,
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2 0'/>
+    </filter>
+</svg>
+");
+
+    test!(rm_trailing_1,
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2'/>
+        <feOffset/>
+    </filter>
+</svg>",
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2'/>
+    </filter>
+</svg>
+");
+
+    test!(rm_unreachable_1,
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='5' result='unused'/>
+        <feGaussianBlur in='SourceGraphic' stdDeviation='2'/>
+    </filter>
+</svg>",
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2'/>
+    </filter>
+</svg>
+");
+
+    test!(rm_results_1,
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2' result='blur'/>
+        <feOffset in='blur' dx='2' result='offset'/>
+        <feMerge>
+            <feMergeNode in='offset'/>
+            <feMergeNode in='SourceGraphic'/>
+        </feMerge>
+    </filter>
+</svg>",
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2'/>
+        <feOffset dx='2' result='offset'/>
+        <feMerge>
+            <feMergeNode in='offset'/>
+            <feMergeNode in='SourceGraphic'/>
+        </feMerge>
+    </filter>
+</svg>
+");
+
+    test!(rm_region_1,
+"<svg>
+    <filter id='f1' x='-10%' y='-10%' width='120%' height='120%' filterUnits='objectBoundingBox'>
+        <feGaussianBlur stdDeviation='2'/>
+    </filter>
+</svg>",
+"<svg>
+    <filter id='f1'>
+        <feGaussianBlur stdDeviation='2'/>
+    </filter>
+</svg>
+");
+
+    // Region in the user space is not a default one.
+    test_eq!(keep_region_1,
+"<svg>
+    <filter id='f1' filterUnits='userSpaceOnUse' height='120%' width='120%' x='-10%' y='-10%'>
+        <feGaussianBlur stdDeviation='2'/>
+    </filter>
+</svg>
+");
+
+    // A primitive subregion can clip an image.
+    test_eq!(keep_1,
+"<svg>
+    <filter id='f1'>
+        <feOffset height='10' width='10'/>
+    </filter>
+</svg>
+");
+
+    test_eq!(keep_2,
+"<svg>
+    <filter id='f1'>
+        <feColorMatrix type='saturate' values='0.5'/>
+    </filter>
+</svg>
+");
+
+    // Filter is used as a template.
+    test_eq!(keep_3,
+"<svg>
+    <filter id='f1'>
+        <feOffset/>
+    </filter>
+    <filter id='f2' xlink:href='#f1'/>
+</svg>
+");
+
+    test_eq!(keep_animated_1,
+"<svg>
+    <filter id='f1'>
+        <feOffset dx='0'>
+            <animate attributeName='dx' to='10'/>
+        </feOffset>
+    </filter>
+</svg>
+");
+
+    #[test]
+    fn keep_ids_1() {
+        let mut doc = Document::from_str(
+"<svg>
+    <filter id='f1'>
+        <feOffset/>
+    </filter>
+    <rect filter='url(#f1)'/>
+</svg>").unwrap();
+
+        simplify_filters(&mut doc, &["f1".to_string()]);
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg>
+    <filter id='f1'>
+        <feOffset/>
+    </filter>
+    <rect filter='url(#f1)'/>
+</svg>
+");
+    }
+}
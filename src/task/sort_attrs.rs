@@ -0,0 +1,117 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::collections::HashMap;
+
+use svgdom::{
+    Attribute,
+    Document,
+};
+
+use task::short::AId;
+
+// Sorts attributes of each element by the number of their occurrences in the document.
+//
+// Frequent attributes go first, so elements tend to start with the same byte sequences,
+// which improves compression ratio. Ties are broken by the attribute ID, so the result is
+// stable across elements. Non-SVG attributes are kept at the end in the original order.
+//
+// NOTE: Has an effect only with 'AttributesOrder::AsIs'.
+pub fn sort_attributes(doc: &Document) {
+    let mut counts: HashMap<AId, usize> = HashMap::new();
+    for (_, node) in doc.descendants().svg() {
+        for (aid, _) in node.attributes().iter_svg() {
+            *counts.entry(aid).or_insert(0) += 1;
+        }
+    }
+
+    for (_, mut node) in doc.descendants().svg() {
+        let mut attrs = node.attributes_mut();
+
+        let mut list: Vec<Attribute> = attrs.iter().cloned().collect();
+        list.sort_by_key(|attr| {
+            match attr.id() {
+                Some(aid) => (0, usize::MAX - counts[&aid], aid as usize),
+                None => (1, 0, 0),
+            }
+        });
+
+        // Attributes are only permuted, so links stay valid.
+        for (attr, new_attr) in attrs.iter_mut().zip(list) {
+            *attr = new_attr;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions, AttributesOrder};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                sort_attributes(&doc);
+                let mut opt = write_opt_for_tests!();
+                opt.attributes_order = AttributesOrder::AsIs;
+                assert_eq_text!(doc.to_string_with_opt(&opt), $out_text);
+            }
+        )
+    }
+
+    test!(sort_1,
+"<svg>
+    <rect x='1' fill='red' width='10'/>
+    <rect width='10' fill='blue'/>
+    <circle r='5' fill='green'/>
+</svg>",
+"<svg>
+    <rect fill='#ff0000' width='10' x='1'/>
+    <rect fill='#0000ff' width='10'/>
+    <circle fill='#008000' r='5'/>
+</svg>
+");
+
+    test!(sort_2,
+"<svg>
+    <linearGradient id='lg1'/>
+    <rect stroke='red' fill='url(#lg1)'/>
+    <rect fill='url(#lg1)' stroke='blue' opacity='0.5'/>
+    <rect stroke='green'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1'/>
+    <rect stroke='#ff0000' fill='url(#lg1)'/>
+    <rect stroke='#0000ff' fill='url(#lg1)' opacity='0.5'/>
+    <rect stroke='#008000'/>
+</svg>
+");
+
+    test!(keep_non_svg_attrs_1,
+"<svg>
+    <rect data-b='2' x='1' data-a='1' fill='red'/>
+    <rect fill='red'/>
+</svg>",
+"<svg>
+    <rect fill='#ff0000' x='1' data-b='2' data-a='1'/>
+    <rect fill='#ff0000'/>
+</svg>
+");
+}
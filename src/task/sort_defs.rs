@@ -0,0 +1,197 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use options::SortDefsMode;
+use task::short::EId;
+
+// Sorts 'defs' children, so the output doesn't depend on the order
+// in which an editor has written them.
+//
+// Only SVG elements are sorted. Other nodes are kept before them.
+pub fn sort_defs(doc: &Document, mode: SortDefsMode) {
+    let order = match mode {
+        SortDefsMode::None => return,
+        SortDefsMode::FirstUse => first_use_order(doc),
+        SortDefsMode::Type => Vec::new(),
+    };
+
+    let defs_list: Vec<Node> = doc.descendants().filter(|n| n.is_tag_name(EId::Defs)).collect();
+    for mut defs in defs_list {
+        let mut nodes: Vec<Node> = defs.children().filter(|n| n.is_svg_element()).collect();
+
+        match mode {
+            SortDefsMode::FirstUse => {
+                // Unused elements are moved to the end, preserving their order.
+                nodes.sort_by_key(|n| order.iter().position(|o| o == n).unwrap_or(order.len()));
+            }
+            SortDefsMode::Type => {
+                nodes.sort_by(|n1, n2| {
+                    let (eid1, eid2) = (n1.tag_id().unwrap(), n2.tag_id().unwrap());
+                    eid1.name().cmp(eid2.name()).then_with(|| n1.id().cmp(&n2.id()))
+                });
+            }
+            SortDefsMode::None => unreachable!(),
+        }
+
+        for n in &mut nodes {
+            n.detach();
+            defs.append(n);
+        }
+    }
+}
+
+// Collects 'defs' children in the order they are referenced for the first time.
+//
+// Elements referenced only by other 'defs' children are placed right after them.
+fn first_use_order(doc: &Document) -> Vec<Node> {
+    let mut order = Vec::new();
+
+    // doc must contain 'svg' node, so we can safely unwrap.
+    let svg = doc.svg_element().unwrap();
+    collect_links(&svg, &mut order);
+
+    order
+}
+
+fn collect_links(root: &Node, order: &mut Vec<Node>) {
+    for node in root.descendants() {
+        // Elements inside 'defs' are processed only after they were referenced.
+        if node != *root && is_in_defs(&node) && !is_in_defs(root) {
+            continue;
+        }
+
+        let links: Vec<Node> = node.attributes().iter().filter_map(|attr| {
+            match attr.value {
+                  AttributeValue::Link(ref link)
+                | AttributeValue::FuncLink(ref link) => Some(link.clone()),
+                _ => None,
+            }
+        }).collect();
+
+        for link in links {
+            if let Some(top) = defs_child(&link) {
+                if !order.contains(&top) {
+                    order.push(top.clone());
+                    collect_links(&top, order);
+                }
+            }
+        }
+    }
+}
+
+// Returns a 'defs' child that contains the current node.
+fn defs_child(node: &Node) -> Option<Node> {
+    let mut node = node.clone();
+    while let Some(parent) = node.parent() {
+        if parent.is_tag_name(EId::Defs) {
+            return Some(node);
+        }
+
+        node = parent;
+    }
+
+    None
+}
+
+fn is_in_defs(node: &Node) -> bool {
+    node.parents().any(|p| p.is_tag_name(EId::Defs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $mode:expr, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| sort_defs(doc, $mode), $in_text, $out_text);
+        )
+    }
+
+    test!(first_use_1, SortDefsMode::FirstUse,
+"<svg>
+    <defs>
+        <linearGradient id='lg1'/>
+        <clipPath id='clip1'/>
+        <radialGradient id='rg1'/>
+    </defs>
+    <rect fill='url(#rg1)'/>
+    <rect fill='url(#lg1)' clip-path='url(#clip1)'/>
+</svg>",
+"<svg>
+    <defs>
+        <radialGradient id='rg1'/>
+        <linearGradient id='lg1'/>
+        <clipPath id='clip1'/>
+    </defs>
+    <rect fill='url(#rg1)'/>
+    <rect clip-path='url(#clip1)' fill='url(#lg1)'/>
+</svg>
+");
+
+    // Nested references and unused elements.
+    test!(first_use_2, SortDefsMode::FirstUse,
+"<svg>
+    <defs>
+        <filter id='f1'/>
+        <linearGradient id='lg1'/>
+        <clipPath id='clip1'>
+            <rect fill='url(#lg2)'/>
+        </clipPath>
+        <linearGradient id='lg2' xlink:href='#lg1'/>
+    </defs>
+    <rect clip-path='url(#clip1)'/>
+</svg>",
+"<svg>
+    <defs>
+        <clipPath id='clip1'>
+            <rect fill='url(#lg2)'/>
+        </clipPath>
+        <linearGradient id='lg2' xlink:href='#lg1'/>
+        <linearGradient id='lg1'/>
+        <filter id='f1'/>
+    </defs>
+    <rect clip-path='url(#clip1)'/>
+</svg>
+");
+
+    test!(type_1, SortDefsMode::Type,
+"<svg>
+    <defs>
+        <radialGradient id='a'/>
+        <linearGradient id='b'/>
+        <clipPath id='c'/>
+        <linearGradient id='a'/>
+    </defs>
+</svg>",
+"<svg>
+    <defs>
+        <clipPath id='c'/>
+        <linearGradient id='a'/>
+        <linearGradient id='b'/>
+        <radialGradient id='a'/>
+    </defs>
+</svg>
+");
+}
@@ -0,0 +1,275 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use svgdom::{
+    AttributeType,
+    AttributeValue,
+    Attributes,
+    Document,
+    Node,
+    NodeType,
+    WriteBuffer,
+    WriteOptions,
+};
+
+use task::short::{EId, AId};
+use task::trim_ids::Num;
+use task::utils;
+use options::StylesMode;
+
+// Length of the '<style></style>'.
+const STYLE_ELEMENT_LEN: usize = 15;
+
+struct Group {
+    style: String,
+    attrs_len: usize,
+    nodes: Vec<Node>,
+}
+
+pub fn convert_styles_to_css(doc: &mut Document, mode: StylesMode, opt: &WriteOptions) {
+    // NOTE: Must be run at last, since it breaks linking.
+
+    if mode == StylesMode::Presentation {
+        return;
+    }
+
+    let (mut groups, used_classes) = collect_groups(doc, opt);
+
+    // Most used styles should get shortest names.
+    groups.sort_by_key(|g| Reverse(g.nodes.len()));
+
+    let mut num = Num::new();
+    let mut css = String::new();
+    let mut saved_len = 0;
+    let mut selected = Vec::new();
+    for group in groups {
+        let mut class = num.to_string();
+        while used_classes.contains(&class) {
+            num.plus_one();
+            class = num.to_string();
+        }
+
+        // Compare ' fill="#000000"' with ' class="a"' + '.a{fill:#000000}'.
+        let count = group.nodes.len();
+        let old_len = group.attrs_len * count;
+        let new_len = (class.len() + 9) * count + class.len() + group.style.len() + 3;
+
+        if mode == StylesMode::Auto && new_len >= old_len {
+            continue;
+        }
+
+        saved_len += old_len as isize - new_len as isize;
+        num.plus_one();
+
+        css.push('.');
+        css.push_str(&class);
+        css.push('{');
+        css.push_str(&group.style);
+        css.push('}');
+
+        selected.push((class, group.nodes));
+    }
+
+    if selected.is_empty() {
+        return;
+    }
+
+    if mode == StylesMode::Auto && saved_len <= STYLE_ELEMENT_LEN as isize {
+        return;
+    }
+
+    for (class, nodes) in selected {
+        for mut node in nodes {
+            let mut attrs = node.attributes_mut();
+            let ids: Vec<AId> = attrs.iter_svg()
+                                     .filter(|&(_, a)| a.is_presentation() && a.visible)
+                                     .map(|(aid, _)| aid)
+                                     .collect();
+
+            for id in ids {
+                // Use "private" method, because we breaking linking on purpose.
+                // Default 'remove()' will panic here.
+                attrs.remove_impl(id);
+            }
+
+            attrs.insert_from(AId::Class, class.clone());
+        }
+    }
+
+    let mut svg = doc.svg_element().unwrap();
+    let mut style_node = doc.create_element(EId::Style);
+    let text_node = doc.create_node(NodeType::Text, &css);
+    style_node.append(&text_node);
+    svg.prepend(&style_node);
+}
+
+fn collect_groups(doc: &Document, opt: &WriteOptions) -> (Vec<Group>, Vec<String>) {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut groups_map: HashMap<String, usize> = HashMap::new();
+    // Scripts can select elements by a class name, so we should not reuse it.
+    let mut used_classes = utils::ScriptRefs::new(doc).classes;
+
+    for (_, node) in doc.descendants().svg() {
+        let attrs = node.attributes();
+
+        // Elements with unresolved classes are ignored,
+        // but we still have to preserve those names.
+        if let Some(AttributeValue::String(s)) = attrs.get_value(AId::Class) {
+            used_classes.extend(s.split_whitespace().map(String::from));
+            continue;
+        }
+
+        let (style, attrs_len) = gen_style(&attrs, opt);
+        if style.is_empty() {
+            continue;
+        }
+
+        if let Some(idx) = groups_map.get(&style) {
+            groups[*idx].nodes.push(node.clone());
+            continue;
+        }
+
+        groups_map.insert(style.clone(), groups.len());
+        groups.push(Group {
+            style,
+            attrs_len,
+            nodes: vec![node.clone()],
+        });
+    }
+
+    (groups, used_classes)
+}
+
+// Returns a CSS declarations block and the length of the same attributes
+// in the presentational notation.
+fn gen_style(attrs: &Attributes, opt: &WriteOptions) -> (String, usize) {
+    let mut style = Vec::new();
+    let mut attrs_len = 0;
+    for (aid, attr) in attrs.iter_svg().filter(|&(_, a)| a.is_presentation() && a.visible) {
+        let value_start = style.len() + aid.name().len() + 1;
+
+        style.extend_from_slice(aid.name().as_bytes());
+        style.push(b':');
+        attr.value.write_buf_opt(opt, &mut style);
+
+        // ' name="value"'
+        attrs_len += aid.name().len() + (style.len() - value_start) + 4;
+
+        style.push(b';');
+    }
+    style.pop();
+
+    // Unwrap can't fail.
+    (String::from_utf8(style).unwrap(), attrs_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ParseOptions, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $mode:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut doc = Document::from_str($in_text).unwrap();
+                convert_styles_to_css(&mut doc, $mode, &write_opt_for_tests!());
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(css_1, StylesMode::Css,
+"<svg>
+    <rect fill='red' stroke='blue'/>
+    <rect fill='red' stroke='blue'/>
+    <rect fill='red'/>
+</svg>",
+"<svg>
+    <style>.a{fill:#ff0000;stroke:#0000ff}.b{fill:#ff0000}</style>
+    <rect class='a'/>
+    <rect class='a'/>
+    <rect class='b'/>
+</svg>
+");
+
+    test!(auto_1, StylesMode::Auto,
+"<svg>
+    <rect fill='red' stroke='blue'/>
+    <rect fill='red' stroke='blue'/>
+    <rect fill='red' stroke='blue'/>
+    <rect fill='red' stroke='blue'/>
+    <rect fill='red'/>
+</svg>",
+"<svg>
+    <style>.a{fill:#ff0000;stroke:#0000ff}</style>
+    <rect class='a'/>
+    <rect class='a'/>
+    <rect class='a'/>
+    <rect class='a'/>
+    <rect fill='#ff0000'/>
+</svg>
+");
+
+    // The 'style' element is bigger than the saved space.
+    test!(auto_2, StylesMode::Auto,
+"<svg>
+    <rect fill='red' stroke='blue'/>
+    <rect fill='red' stroke='blue'/>
+</svg>",
+"<svg>
+    <rect fill='#ff0000' stroke='#0000ff'/>
+    <rect fill='#ff0000' stroke='#0000ff'/>
+</svg>
+");
+
+    #[test]
+    fn used_class_1() {
+        let mut opt = ParseOptions::default();
+        opt.skip_unresolved_classes = false;
+
+        let mut doc = Document::from_str_with_opt(
+"<svg>
+    <rect fill='red'/>
+    <rect class='a'/>
+</svg>", &opt).unwrap();
+
+        convert_styles_to_css(&mut doc, StylesMode::Css, &write_opt_for_tests!());
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg>
+    <style>.b{fill:#ff0000}</style>
+    <rect class='b'/>
+    <rect class='a'/>
+</svg>
+");
+    }
+
+    test!(presentation_1, StylesMode::Presentation,
+"<svg>
+    <rect fill='red'/>
+    <rect fill='red'/>
+</svg>",
+"<svg>
+    <rect fill='#ff0000'/>
+    <rect fill='#ff0000'/>
+</svg>
+");
+}
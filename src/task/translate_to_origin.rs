@@ -0,0 +1,145 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Transform,
+};
+
+use options::CleaningOptions;
+use task::short::AId;
+use task::apply_transforms;
+use warning::{
+    self,
+    Warning,
+    WarningCode,
+};
+
+// Moves the 'viewBox' origin to '0 0' by translating the top-level content.
+//
+// The translation is stored in the 'transform' attribute and then applied to
+// the elements coordinates, when the transforms applying is enabled.
+pub fn translate_to_origin(doc: &Document, opt: &CleaningOptions) {
+    let mut svg = match doc.svg_element() {
+        Some(svg) => svg,
+        None => return,
+    };
+
+    let (x, y, w, h) = match svg.attributes().get_value(AId::ViewBox) {
+        Some(AttributeValue::NumberList(list)) if list.len() == 4 => {
+            (list[0], list[1], list[2], list[3])
+        }
+        _ => return,
+    };
+
+    if x == 0.0 && y == 0.0 {
+        return;
+    }
+
+    let ts = Transform::new(1.0, 0.0, 0.0, 1.0, -x, -y);
+    if !apply_transforms::prepend_transform_to_content(doc, &ts) {
+        warning::add(Warning::new(WarningCode::SkippedTask,
+            "the 'translate-to-origin' task is skipped, because the content can't be transformed"));
+        return;
+    }
+
+    svg.set_attribute((AId::ViewBox, vec![0.0, 0.0, w, h]));
+
+    apply_transforms::apply_transform_to_content(doc, opt);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, ToStringWithOptions};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, |doc: &Document| {
+                let opt = CleaningOptions {
+                    apply_transform_to_shapes: true,
+                    apply_transform_to_paths: true,
+                    paths_to_relative: true,
+                    ..CleaningOptions::default()
+                };
+                translate_to_origin(doc, &opt)
+            }, $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, $in_text);
+        )
+    }
+
+    test!(translate_1,
+"<svg viewBox='10 20 100 100'>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>",
+"<svg viewBox='0 0 100 100'>
+    <rect height='20' width='40' x='0' y='0'/>
+</svg>
+");
+
+    test!(translate_2,
+"<svg viewBox='10 20 100 100'>
+    <g transform='scale(2)'>
+        <path d='M 10 20 L 30 40'/>
+    </g>
+    <circle cx='10' cy='20' r='5' stroke='#000000'/>
+</svg>",
+"<svg viewBox='0 0 100 100'>
+    <g>
+        <path d='M 10 20 L 50 60' stroke-width='2'/>
+    </g>
+    <circle cx='0' cy='0' r='5' stroke='#000000'/>
+</svg>
+");
+
+    // Content that can't be processed should preserve the transform.
+    test!(translate_3,
+"<svg viewBox='10 20 100 100'>
+    <text x='10' y='20'>Text</text>
+</svg>",
+"<svg viewBox='0 0 100 100'>
+    <text transform='translate(-10 -20)' x='10' y='20'>Text</text>
+</svg>
+");
+
+    test_eq!(keep_1,
+"<svg viewBox='0 0 100 100'>
+    <rect height='20' width='40' x='10' y='20'/>
+</svg>
+");
+
+    test_eq!(keep_2,
+"<svg viewBox='10 20 100 100'>
+    <rect height='20' width='40' x='10' y='20'>
+        <animateTransform attributeName='transform' from='0' to='90' type='rotate'/>
+    </rect>
+</svg>
+");
+
+    test_eq!(keep_3,
+"<svg viewBox='10 20 100 100'>
+    <svg height='20' width='40' x='10' y='20'/>
+</svg>
+");
+}
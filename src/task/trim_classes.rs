@@ -0,0 +1,148 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::collections::HashMap;
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::AId;
+use task::trim_ids::Num;
+use task::utils;
+use task::utils::css;
+
+// Renames classes to shorter ones and updates stylesheets accordingly.
+//
+// Documents with scripts or event attributes are skipped, since class names
+// can be built dynamically, like 'btn-' + state, which cannot be detected.
+pub fn trim_classes(doc: &Document) {
+    if utils::script::has_scripts(doc) {
+        return;
+    }
+
+    let nodes: Vec<Node> = doc.descendants().filter(|n| n.has_attribute(AId::Class)).collect();
+
+    // Count classes usage in the document order.
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for node in &nodes {
+        if let Some(AttributeValue::String(s)) = node.attributes().get_value(AId::Class) {
+            for name in s.split_whitespace() {
+                match counts.iter_mut().find(|(n, _)| n == name) {
+                    Some(item) => item.1 += 1,
+                    None => counts.push((name.to_string(), 1)),
+                }
+            }
+        }
+    }
+
+    if counts.is_empty() {
+        return;
+    }
+
+    // Most used classes should get shortest names.
+    // Sort is stable, so classes with the same count will preserve their order.
+    counts.sort_by_key(|&(_, count)| ::std::cmp::Reverse(count));
+
+    let mut num = Num::new();
+    let mut names = HashMap::new();
+    for (name, _) in counts {
+        names.insert(name, num.to_string());
+        num.plus_one();
+    }
+
+    for mut node in nodes {
+        let new_value = match node.attributes().get_value(AId::Class) {
+            Some(AttributeValue::String(s)) => {
+                s.split_whitespace().map(|name| names.get(name).map(|n| n.as_str()).unwrap_or(name))
+                 .collect::<Vec<_>>().join(" ")
+            }
+            _ => continue,
+        };
+
+        node.set_attribute((AId::Class, new_value));
+    }
+
+    for mut node in css::style_text_nodes(doc) {
+        let new_css = css::rename_classes(&node.text(), &names);
+        node.set_text(&new_css);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, NodeType, ParseOptions, ToStringWithOptions};
+    use task::short::EId;
+
+    macro_rules! test {
+        ($name:ident, $css:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut opt = ParseOptions::default();
+                opt.skip_unresolved_classes = false;
+
+                let mut doc = Document::from_str_with_opt($in_text, &opt).unwrap();
+                let mut svg = doc.svg_element().unwrap();
+                let mut style = doc.create_element(EId::Style);
+                let text = doc.create_node(NodeType::Text, $css);
+                style.append(&text);
+                svg.prepend(&style);
+                trim_classes(&doc);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(trim_1, ".icon:hover{fill:red} .icon-big:hover{fill:blue}",
+"<svg>
+    <rect class='icon-big'/>
+    <rect class='icon icon-big'/>
+</svg>",
+"<svg>
+    <style>.b:hover{fill:red} .a:hover{fill:blue}</style>
+    <rect class='a'/>
+    <rect class='b a'/>
+</svg>
+");
+
+    test!(keep_1, ".btn:hover{fill:red}",
+"<svg>
+    <script>document.querySelector('.btn')</script>
+    <rect class='btn'/>
+</svg>",
+"<svg>
+    <style>.btn:hover{fill:red}</style>
+    <script>document.querySelector('.btn')</script>
+    <rect class='btn'/>
+</svg>
+");
+
+    // Class names can be built by scripts.
+    test!(keep_2, ".btn-active{fill:red}",
+"<svg>
+    <rect class='btn' onclick='this.classList.toggle(\"btn-\"+\"active\")'/>
+</svg>",
+"<svg>
+    <style>.btn-active{fill:red}</style>
+    <rect class='btn' onclick='this.classList.toggle(\"btn-\"+\"active\")'/>
+</svg>
+");
+}
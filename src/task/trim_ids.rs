@@ -18,17 +18,19 @@
 
 use svgdom::Document;
 
+use task::utils;
+
 const NUM_SIZE: usize = 5;
 const CHARS_SIZE: usize = 62;
 const MAX_NUM_CHAR: u8 = (CHARS_SIZE - 1) as u8;
 
-struct Num {
+pub struct Num {
     d: [u8; NUM_SIZE],
     chars: [u8; CHARS_SIZE],
 }
 
 impl Num {
-    fn new() -> Num {
+    pub fn new() -> Num {
         let mut chars = [0u8; CHARS_SIZE];
         {
             let mut pos = 0;
@@ -73,7 +75,7 @@ impl Num {
     }
 
     // Can panic if number is bigger than around 100000000.
-    fn plus_one(&mut self) {
+    pub fn plus_one(&mut self) {
         let mut head = NUM_SIZE-1;
         for i in (0..5).rev() {
             if self.d[i] == 255 {
@@ -85,7 +87,7 @@ impl Num {
         self.shift(NUM_SIZE-1, &head);
     }
 
-    fn to_string(&self) -> String {
+    pub fn to_string(&self) -> String {
         let mut new = [0u8; NUM_SIZE];
         let mut len = NUM_SIZE;
         for i in (0..NUM_SIZE).rev() {
@@ -102,10 +104,20 @@ impl Num {
     }
 }
 
-pub fn trim_ids(doc: &Document) {
+pub fn trim_ids(doc: &Document, keep_ids: &[String]) {
+    let kept: Vec<String> = doc.descendants()
+        .filter(|n| utils::is_kept_id(n, keep_ids))
+        .map(|n| n.id().clone())
+        .collect();
+
     let mut num = Num::new();
     for (_, mut node) in doc.descendants().svg() {
-        if node.has_id() {
+        if node.has_id() && !utils::is_kept_id(&node, keep_ids) {
+            // New ids should not collide with the kept ones.
+            while kept.contains(&num.to_string()) {
+                num.plus_one();
+            }
+
             node.set_id(num.to_string());
             num.plus_one();
         }
@@ -114,7 +126,23 @@ pub fn trim_ids(doc: &Document) {
 
 #[cfg(test)]
 mod tests {
-    use super::Num;
+    use super::*;
+    use svgdom::ToStringWithOptions;
+
+    base_test!(keep_1, |doc: &Document| trim_ids(doc, &["a".to_string(), "keep-.*".to_string()]),
+"<svg>
+    <rect id='rect1'/>
+    <rect id='a'/>
+    <rect id='keep-me'/>
+    <rect id='rect2'/>
+</svg>",
+"<svg>
+    <rect id='b'/>
+    <rect id='a'/>
+    <rect id='keep-me'/>
+    <rect id='c'/>
+</svg>
+");
 
     #[test]
     fn test_1() {
@@ -24,6 +24,7 @@ use svgdom::{
 
 use task::short::{EId, AId};
 use task::apply_transforms;
+use task::utils::{self, AnimatedAttributes};
 use options::CleaningOptions;
 
 pub fn ungroup_groups(doc: &Document, opt: &CleaningOptions) {
@@ -31,16 +32,17 @@ pub fn ungroup_groups(doc: &Document, opt: &CleaningOptions) {
     let svg = doc.svg_element().unwrap();
     apply_transforms::prepare_transforms(&svg, true, opt);
 
-    while _ungroup_groups(&svg, opt) {}
+    let animated = AnimatedAttributes::new(doc);
+    while _ungroup_groups(&svg, opt, &animated) {}
 }
 
 // Returns `true` when valid `g` occurred.
 //
 // If such `g` found - ungroup it and start again from the root `svg` element.
-fn _ungroup_groups(parent: &Node, opt: &CleaningOptions) -> bool {
+fn _ungroup_groups(parent: &Node, opt: &CleaningOptions, animated: &AnimatedAttributes) -> bool {
     for mut node in parent.children() {
         if node.is_tag_name(EId::G) {
-            if can_ungroup(parent, &node) {
+            if can_ungroup(parent, &node, opt, animated) {
                 ungroup_group(&mut node);
                 node.remove();
 
@@ -51,7 +53,7 @@ fn _ungroup_groups(parent: &Node, opt: &CleaningOptions) -> bool {
         }
 
         if node.has_children() {
-            if _ungroup_groups(&node, opt) {
+            if _ungroup_groups(&node, opt, animated) {
                 return true;
             }
         }
@@ -60,11 +62,38 @@ fn _ungroup_groups(parent: &Node, opt: &CleaningOptions) -> bool {
     false
 }
 
-fn can_ungroup(parent: &Node, g: &Node) -> bool {
+fn can_ungroup(
+    parent: &Node,
+    g: &Node,
+    opt: &CleaningOptions,
+    animated: &AnimatedAttributes,
+) -> bool {
     // We can't ungroup groups if they have one of the listed attribute.
     // Checkout 'painting-marker-02-f.svg' in 'W3C_SVG_11_TestSuite' for details.
     let invalid_attrs = [AId::Mask, AId::ClipPath, AId::Filter];
 
+    // Kept groups should be preserved even when empty.
+    if utils::is_kept_id(g, &opt.keep_ids) {
+        return false;
+    }
+
+    // Only presentational attributes are moved to children,
+    // so accessibility attributes will be lost.
+    if utils::has_accessibility_attrs(g) {
+        return false;
+    }
+
+    // Event handlers are not inherited.
+    if utils::has_event_attrs(g) {
+        return false;
+    }
+
+    // 'title' and 'desc' elements describe their parent element.
+    if opt.keep_accessibility && g.children().any(|c| c.is_tag_name(EId::Title)
+                                                    || c.is_tag_name(EId::Desc)) {
+        return false;
+    }
+
     // TODO: we should not ungroup groups with non-inheritable attributes.
 
     if !g.has_children() && !g.has_attribute(AId::Filter) {
@@ -78,6 +107,11 @@ fn can_ungroup(parent: &Node, g: &Node) -> bool {
         return false;
     }
 
+    // Group shouldn't be a target of an animation.
+    if animated.is_animated(g) {
+        return false;
+    }
+
     // Group shouldn't contain some attributes.
     if g.has_attributes(&invalid_attrs) {
         return false;
@@ -178,7 +212,10 @@ fn ungroup_group(g: &mut Node) {
                 continue;
             }
 
-            if !child.has_attribute(aid) {
+            // A hidden attribute has the same value as the inherited one,
+            // but it will not be written, so we should replace it.
+            let is_hidden = child.attributes().get(aid).map(|a| !a.visible) == Some(true);
+            if !child.has_attribute(aid) || is_hidden {
                 child.set_attribute(attr.clone());
             }
         }
@@ -217,7 +254,7 @@ mod tests {
 
                 // We must check that we moved linked elements correctly.
                 // If we not, than referenced elements will be removed. Which is wrong.
-                rm_unused_defs::remove_unused_defs(&doc);
+                rm_unused_defs::remove_unused_defs(&doc, &[]);
 
                 // Removes 'defs' element.
                 remove_empty_defs(&doc);
@@ -610,4 +647,77 @@ mod tests {
 </svg>
 ");
 
+    test!(keep_accessibility_1,
+"<svg>
+    <g role='img' aria-label='image'>
+        <rect/>
+    </g>
+</svg>",
+"<svg>
+    <g role='img' aria-label='image'>
+        <rect/>
+    </g>
+</svg>
+");
+
+    #[test]
+    fn keep_accessibility_2() {
+        let doc = Document::from_str(
+"<svg>
+    <g>
+        <title>Image</title>
+        <rect/>
+    </g>
+</svg>").unwrap();
+
+        let options = CleaningOptions { keep_accessibility: true, ..CleaningOptions::default() };
+        ungroup_groups(&doc, &options);
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg>
+    <g>
+        <title>Image</title>
+        <rect/>
+    </g>
+</svg>
+");
+    }
+
+    test_eq!(keep_animated_1,
+"<svg>
+    <g transform='translate(10 20)'>
+        <rect height='10' width='10'/>
+        <animateTransform attributeName='transform' to='90' type='rotate'/>
+    </g>
+</svg>
+");
+
+    test_eq!(keep_events_1,
+"<svg>
+    <g onclick='f()'>
+        <rect/>
+        <rect/>
+    </g>
+</svg>
+");
+
+    #[test]
+    fn hidden_attrs_1() {
+        let doc = Document::from_str(
+"<svg>
+    <g fill='#ff0000'>
+        <rect fill='#ff0000'/>
+    </g>
+</svg>").unwrap();
+
+        // Hides the 'fill' attribute on the 'rect' element.
+        ::task::remove_default_attributes(&doc);
+        ungroup_groups(&doc, &CleaningOptions::default());
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg>
+    <rect fill='#ff0000'/>
+</svg>
+");
+    }
 }
@@ -0,0 +1,207 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::{EId, AId};
+
+pub fn is_animation(id: EId) -> bool {
+    matches!(id, EId::Animate | EId::AnimateColor | EId::AnimateMotion
+               | EId::AnimateTransform | EId::Set)
+}
+
+// An index of elements and attributes modified by SMIL animations.
+//
+// Tasks should not remove, move or recalculate animated attributes and should not
+// restructure animated elements, because the animation will be applied to a wrong value
+// or will lose its target.
+pub struct AnimatedAttributes {
+    // A target element and an animated attribute.
+    //
+    // The attribute is 'None' when it's not an SVG one.
+    list: Vec<(Node, Option<AId>)>,
+}
+
+impl AnimatedAttributes {
+    pub fn new(doc: &Document) -> AnimatedAttributes {
+        let mut list = Vec::new();
+
+        for (id, node) in doc.descendants().svg().filter(|&(id, _)| is_animation(id)) {
+            let target = {
+                let attrs = node.attributes();
+                match attrs.get_value(AId::XlinkHref) {
+                    Some(AttributeValue::Link(link)) => Some(link.clone()),
+                    _ => node.parent(),
+                }
+            };
+
+            let target = match target {
+                Some(target) => target,
+                None => continue,
+            };
+
+            let aid = match id {
+                // 'animateMotion' adds a supplemental transformation.
+                EId::AnimateMotion => Some(AId::Transform),
+                _ => {
+                    let attrs = node.attributes();
+                    match attrs.get_value(AId::AttributeName) {
+                        Some(AttributeValue::String(name)) => AId::from_name(name),
+                        _ if id == EId::AnimateTransform => Some(AId::Transform),
+                        _ => None,
+                    }
+                }
+            };
+
+            list.push((target, aid));
+        }
+
+        AnimatedAttributes { list }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    // Checks that any attribute of the element is animated.
+    pub fn is_animated(&self, node: &Node) -> bool {
+        self.list.iter().any(|(n, _)| n == node)
+    }
+
+    pub fn contains(&self, node: &Node, aid: AId) -> bool {
+        self.list.iter().any(|(n, a)| n == node && *a == Some(aid))
+    }
+
+    // Checks that the attribute is animated on the element or on one of its parents.
+    //
+    // Useful for inheritable attributes.
+    pub fn contains_inherited(&self, node: &Node, aid: AId) -> bool {
+        node.parents_with_self().any(|n| self.contains(&n, aid))
+    }
+}
+
+// Collects ids referenced by animation timing attributes, like 'begin="button.click"',
+// and by animation values, like 'to="url(#gradient)"'.
+//
+// Such references are not tracked as links.
+pub fn referenced_ids(doc: &Document) -> Vec<String> {
+    let mut ids = Vec::new();
+
+    for (_, node) in doc.descendants().svg().filter(|&(id, _)| is_animation(id)) {
+        let attrs = node.attributes();
+
+        for aid in &[AId::Begin, AId::End] {
+            if let Some(AttributeValue::String(s)) = attrs.get_value(*aid) {
+                for value in s.split(';') {
+                    if let Some(id) = syncbase_id(value.trim()) {
+                        ids.push(id.to_string());
+                    }
+                }
+            }
+        }
+
+        // '#ff0000' is a color and not a link, unless we are animating a link.
+        let is_link = match attrs.get_value(AId::AttributeName) {
+            Some(AttributeValue::String(name)) => name == "xlink:href",
+            _ => false,
+        };
+
+        for aid in &[AId::From, AId::To, AId::By, AId::Values] {
+            if let Some(AttributeValue::String(s)) = attrs.get_value(*aid) {
+                for value in s.split(';') {
+                    if let Some(id) = iri_id(value.trim(), is_link) {
+                        ids.push(id.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    ids
+}
+
+// Extracts an element id from values like 'button.click' or 'anim1.end+1s'.
+fn syncbase_id(value: &str) -> Option<&str> {
+    let idx = value.find('.')?;
+    let id = &value[..idx];
+
+    // Skip clock values, like '0.5s'.
+    match id.chars().next() {
+        Some(c) if c.is_ascii_digit() || c == '+' || c == '-' => None,
+        Some(_) => Some(id),
+        None => None,
+    }
+}
+
+// Extracts an element id from values like 'url(#id)' or '#id' for links.
+fn iri_id(value: &str, is_link: bool) -> Option<&str> {
+    if is_link {
+        return value.strip_prefix('#');
+    }
+
+    let id = value.strip_prefix("url(#")?;
+    id.find(')').map(|idx| id[..idx].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::Document;
+
+    #[test]
+    fn animated_attributes_1() {
+        let doc = Document::from_str(
+"<svg xmlns:xlink='http://www.w3.org/1999/xlink'>
+    <g id='g1'>
+        <rect id='rect1'>
+            <animate attributeName='fill' to='#ff0000'/>
+        </rect>
+    </g>
+    <animateTransform xlink:href='#g1' attributeName='transform' type='rotate' to='90'/>
+</svg>").unwrap();
+
+        let g = doc.descendants().find(|n| n.id().as_str() == "g1").unwrap();
+        let rect = doc.descendants().find(|n| n.id().as_str() == "rect1").unwrap();
+
+        let animated = AnimatedAttributes::new(&doc);
+        assert!(animated.contains(&rect, AId::Fill));
+        assert!(!animated.contains(&rect, AId::Transform));
+        assert!(animated.contains(&g, AId::Transform));
+        assert!(animated.contains_inherited(&rect, AId::Transform));
+        assert!(!animated.is_animated(&doc.root()));
+    }
+
+    #[test]
+    fn referenced_ids_1() {
+        let doc = Document::from_str(
+"<svg>
+    <rect id='button'/>
+    <rect>
+        <animate attributeName='fill' begin='button.click; 0.5s' end='anim1.end+1s'
+                 values='#ff0000;url(#lg1)'/>
+    </rect>
+</svg>").unwrap();
+
+        assert_eq!(referenced_ids(&doc),
+                   vec!["button".to_string(), "anim1".to_string(), "lg1".to_string()]);
+    }
+}
@@ -0,0 +1,686 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::f64;
+
+use svgdom::{
+    AttributeValue,
+    ElementType,
+    Length,
+    Node,
+    NodeType,
+    Transform,
+    ValueId,
+};
+use svgdom::path::{
+    Path,
+    SegmentData,
+};
+
+use task::short::{EId, AId, Unit};
+
+// Bounding boxes are calculated in the user space of the element,
+// i.e. without its own 'transform'.
+
+#[derive(Clone,Copy,PartialEq,Debug)]
+pub struct Rect {
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+}
+
+impl Rect {
+    fn new_empty() -> Rect {
+        Rect { x1: f64::MAX, y1: f64::MAX, x2: f64::MIN, y2: f64::MIN }
+    }
+
+    fn add_point(&mut self, x: f64, y: f64) {
+        self.x1 = self.x1.min(x);
+        self.y1 = self.y1.min(y);
+        self.x2 = self.x2.max(x);
+        self.y2 = self.y2.max(y);
+    }
+
+    pub fn width(&self) -> f64 {
+        self.x2 - self.x1
+    }
+
+    pub fn height(&self) -> f64 {
+        self.y2 - self.y1
+    }
+
+    pub fn expand(&self, offset: f64) -> Rect {
+        Rect {
+            x1: self.x1 - offset,
+            y1: self.y1 - offset,
+            x2: self.x2 + offset,
+            y2: self.y2 + offset,
+        }
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        !(   self.x2 < other.x1 || other.x2 < self.x1
+          || self.y2 < other.y1 || other.y2 < self.y1)
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            x1: self.x1.min(other.x1),
+            y1: self.y1.min(other.y1),
+            x2: self.x2.max(other.x2),
+            y2: self.y2.max(other.y2),
+        }
+    }
+
+    pub fn contains(&self, other: &Rect) -> bool {
+           self.x1 <= other.x1 && self.y1 <= other.y1
+        && self.x2 >= other.x2 && self.y2 >= other.y2
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect {
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+            x2: self.x2.min(other.x2),
+            y2: self.y2.min(other.y2),
+        };
+
+        if rect.x1 < rect.x2 && rect.y1 < rect.y2 {
+            Some(rect)
+        } else {
+            None
+        }
+    }
+}
+
+// Calculates a precise bounding box of the element without a stroke.
+//
+// Supports basic shapes, paths and groups of them. Returns `None` for elements
+// with an unknown geometry, like text, images and 'use', or with units.
+pub fn calc_bbox(node: &Node) -> Option<Rect> {
+    match node.tag_id()? {
+        EId::G | EId::A => calc_group_bbox(node, false, &calc_bbox),
+        EId::Svg if !is_nested_svg(node) => calc_group_bbox(node, false, &calc_bbox),
+        _ => calc_shape_bbox(node),
+    }
+}
+
+// Calculates a bounding box, which contains everything rendered by the element.
+//
+// Unlike 'calc_bbox', it includes a stroke and can be bigger than needed.
+// Returns `None` for elements with filters and markers.
+pub fn calc_painted_bbox(node: &Node) -> Option<Rect> {
+    _calc_painted_bbox(node, false)
+}
+
+// Like 'calc_painted_bbox', but also supports text using an average glyph size.
+// The result is not precise and can be smaller than the rendered text.
+pub fn calc_approx_bbox(node: &Node) -> Option<Rect> {
+    _calc_painted_bbox(node, true)
+}
+
+fn _calc_painted_bbox(node: &Node, approx: bool) -> Option<Rect> {
+    // Filters are applied to the element itself, so the inherited ones are not checked.
+    if node.has_attribute(AId::Filter) {
+        return None;
+    }
+
+    let calc = |n: &Node| _calc_painted_bbox(n, approx);
+    match node.tag_id()? {
+        EId::G | EId::A => calc_group_bbox(node, true, &calc),
+        EId::Svg if !is_nested_svg(node) => calc_group_bbox(node, true, &calc),
+        EId::Text if approx => Some(calc_text_bbox(node)?.expand(calc_stroke_offset(node)?)),
+        _ => {
+            // Markers are rendered outside the element bounding box.
+            for aid in &[AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd] {
+                if super::find_attribute(node, *aid).is_some() {
+                    return None;
+                }
+            }
+
+            let bbox = calc_shape_bbox(node)?;
+            Some(bbox.expand(calc_stroke_offset(node)?))
+        }
+    }
+}
+
+fn is_nested_svg(node: &Node) -> bool {
+    node.parents().any(|p| p.is_tag_name(EId::Svg))
+}
+
+fn calc_group_bbox(node: &Node, approx: bool, calc: &dyn Fn(&Node) -> Option<Rect>)
+                   -> Option<Rect> {
+    let mut rect = Rect::new_empty();
+    let mut is_empty = true;
+
+    for child in node.children() {
+        if child.node_type() != NodeType::Element {
+            continue;
+        }
+
+        // Skip elements that are not rendered directly, like 'defs' or 'clipPath'.
+        let is_rendered = child.is_graphic()
+            || matches!(child.tag_id(), Some(EId::G) | Some(EId::A)
+                                        | Some(EId::Svg) | Some(EId::Switch));
+        if !is_rendered {
+            continue;
+        }
+
+        if child.attributes().get_value(AId::Display)
+            == Some(&AttributeValue::PredefValue(ValueId::None)) {
+            continue;
+        }
+
+        let mut bbox = calc(&child)?;
+
+        match child.attributes().get_value(AId::Transform) {
+            Some(&AttributeValue::Transform(ts)) => {
+                // A bounding box of a rotated bounding box is bigger than needed.
+                if !approx && (ts.has_rotate() || ts.has_skew()) {
+                    return None;
+                }
+
+                bbox = transform_rect(&bbox, &ts);
+            }
+            None => {}
+            _ => return None,
+        }
+
+        rect = rect.union(&bbox);
+        is_empty = false;
+    }
+
+    if is_empty { None } else { Some(rect) }
+}
+
+fn calc_shape_bbox(node: &Node) -> Option<Rect> {
+    let attrs = node.attributes();
+
+    // Only unitless coordinates are supported.
+    let get_num = |aid: AId| -> Option<f64> {
+        let len = get_value!(attrs, Length, aid, Length::zero());
+        if len.unit == Unit::None && len.num.is_finite() { Some(len.num) } else { None }
+    };
+
+    let rect = match node.tag_id()? {
+        EId::Rect => {
+            let x = get_num(AId::X)?;
+            let y = get_num(AId::Y)?;
+            Rect { x1: x, y1: y, x2: x + get_num(AId::Width)?, y2: y + get_num(AId::Height)? }
+        }
+        EId::Circle => {
+            let cx = get_num(AId::Cx)?;
+            let cy = get_num(AId::Cy)?;
+            let r = get_num(AId::R)?;
+            Rect { x1: cx - r, y1: cy - r, x2: cx + r, y2: cy + r }
+        }
+        EId::Ellipse => {
+            let cx = get_num(AId::Cx)?;
+            let cy = get_num(AId::Cy)?;
+            let rx = get_num(AId::Rx)?;
+            let ry = get_num(AId::Ry)?;
+            Rect { x1: cx - rx, y1: cy - ry, x2: cx + rx, y2: cy + ry }
+        }
+        EId::Line => {
+            let x1 = get_num(AId::X1)?;
+            let y1 = get_num(AId::Y1)?;
+            let x2 = get_num(AId::X2)?;
+            let y2 = get_num(AId::Y2)?;
+            Rect { x1: x1.min(x2), y1: y1.min(y2), x2: x1.max(x2), y2: y1.max(y2) }
+        }
+        EId::Polyline | EId::Polygon => {
+            let points = match attrs.get_value(AId::Points) {
+                Some(AttributeValue::NumberList(points)) if points.len() >= 2 => points,
+                _ => return None,
+            };
+
+            let mut rect = Rect::new_empty();
+            for p in points.chunks(2).filter(|p| p.len() == 2) {
+                rect.add_point(p[0], p[1]);
+            }
+            rect
+        }
+        EId::Path => {
+            match attrs.get_value(AId::D) {
+                Some(AttributeValue::Path(path)) => calc_path_bbox(path)?,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(rect)
+}
+
+// Estimates a text bounding box using an average glyph width.
+//
+// Only the position of the 'text' element itself is used.
+fn calc_text_bbox(node: &Node) -> Option<Rect> {
+    let attrs = node.attributes();
+
+    let get_first = |aid: AId| -> Option<f64> {
+        let len = match attrs.get_value(aid) {
+            Some(AttributeValue::LengthList(list)) => list.first().cloned()?,
+            Some(&AttributeValue::Length(len)) => len,
+            None => return Some(0.0),
+            _ => return None,
+        };
+
+        if len.unit == Unit::None { Some(len.num) } else { None }
+    };
+
+    let x = get_first(AId::X)?;
+    let y = get_first(AId::Y)?;
+
+    let font_size = match super::find_attribute(node, AId::FontSize) {
+        Some(AttributeValue::Length(len)) if len.unit == Unit::None => len.num,
+        None => 16.0,
+        _ => return None,
+    };
+
+    let chars: usize = node.descendants()
+        .filter(|n| n.node_type() == NodeType::Text)
+        .map(|n| n.text().trim().chars().count())
+        .sum();
+
+    if chars == 0 {
+        return None;
+    }
+
+    let width = chars as f64 * font_size * 0.6;
+    let x = match super::find_attribute(node, AId::TextAnchor) {
+        Some(AttributeValue::PredefValue(ValueId::Middle)) => x - width / 2.0,
+        Some(AttributeValue::PredefValue(ValueId::End)) => x - width,
+        _ => x,
+    };
+
+    // The baseline is near the bottom of the glyphs.
+    Some(Rect { x1: x, y1: y - font_size, x2: x + width, y2: y + font_size * 0.25 })
+}
+
+// Calculates a precise bounding box of the path, including curves extrema.
+pub fn calc_path_bbox(path: &Path) -> Option<Rect> {
+    if path.d.is_empty() {
+        return None;
+    }
+
+    let mut path = path.clone();
+    path.conv_to_absolute();
+
+    let mut rect = Rect::new_empty();
+
+    // Current point, subpath start and the last control point
+    // of the previous curve, which is used by smooth curves.
+    let mut prev_x = 0.0;
+    let mut prev_y = 0.0;
+    let mut prev_mx = 0.0;
+    let mut prev_my = 0.0;
+    let mut prev_cubic = None;
+    let mut prev_quad = None;
+
+    for seg in &path.d {
+        let reflect = |ctrl: Option<(f64, f64)>| {
+            match ctrl {
+                Some((cx, cy)) => (prev_x * 2.0 - cx, prev_y * 2.0 - cy),
+                None => (prev_x, prev_y),
+            }
+        };
+
+        let mut cubic = None;
+        let mut quad = None;
+
+        let (x, y) = match *seg.data() {
+              SegmentData::MoveTo { x, y }
+            | SegmentData::LineTo { x, y } => (x, y),
+            SegmentData::HorizontalLineTo { x } => (x, prev_y),
+            SegmentData::VerticalLineTo { y } => (prev_x, y),
+            SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
+                add_cubic_extrema(&mut rect, (prev_x, prev_y), (x1, y1), (x2, y2), (x, y));
+                cubic = Some((x2, y2));
+                (x, y)
+            }
+            SegmentData::SmoothCurveTo { x2, y2, x, y } => {
+                let p1 = reflect(prev_cubic);
+                add_cubic_extrema(&mut rect, (prev_x, prev_y), p1, (x2, y2), (x, y));
+                cubic = Some((x2, y2));
+                (x, y)
+            }
+            SegmentData::Quadratic { x1, y1, x, y } => {
+                add_quad_extrema(&mut rect, (prev_x, prev_y), (x1, y1), (x, y));
+                quad = Some((x1, y1));
+                (x, y)
+            }
+            SegmentData::SmoothQuadratic { x, y } => {
+                let p1 = reflect(prev_quad);
+                add_quad_extrema(&mut rect, (prev_x, prev_y), p1, (x, y));
+                quad = Some(p1);
+                (x, y)
+            }
+            SegmentData::EllipticalArc { rx, ry, x_axis_rotation, large_arc, sweep, x, y } => {
+                let arc = Arc { rx, ry, x_axis_rotation, large_arc, sweep };
+                add_arc_extrema(&mut rect, (prev_x, prev_y), &arc, (x, y));
+                (x, y)
+            }
+            SegmentData::ClosePath => (prev_mx, prev_my),
+        };
+
+        rect.add_point(x, y);
+
+        if let SegmentData::MoveTo { .. } = *seg.data() {
+            prev_mx = x;
+            prev_my = y;
+        }
+
+        prev_x = x;
+        prev_y = y;
+        prev_cubic = cubic;
+        prev_quad = quad;
+    }
+
+    Some(rect)
+}
+
+type Point = (f64, f64);
+
+fn add_cubic_extrema(rect: &mut Rect, p0: Point, p1: Point, p2: Point, p3: Point) {
+    let calc = |t: f64, a: f64, b: f64, c: f64, d: f64| {
+        let mt = 1.0 - t;
+        mt * mt * mt * a + 3.0 * mt * mt * t * b + 3.0 * mt * t * t * c + t * t * t * d
+    };
+
+    // Roots of the derivative: a*t^2 + b*t + c = 0.
+    let roots = |p0: f64, p1: f64, p2: f64, p3: f64| -> Vec<f64> {
+        let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+        let b = 2.0 * (p0 - 2.0 * p1 + p2);
+        let c = p1 - p0;
+
+        let mut list = Vec::new();
+        if a.abs() < 1e-12 {
+            if b.abs() > 1e-12 {
+                list.push(-c / b);
+            }
+        } else {
+            let d = b * b - 4.0 * a * c;
+            if d >= 0.0 {
+                let d = d.sqrt();
+                list.push((-b + d) / (2.0 * a));
+                list.push((-b - d) / (2.0 * a));
+            }
+        }
+
+        list.retain(|t| *t > 0.0 && *t < 1.0);
+        list
+    };
+
+    let mut ts = roots(p0.0, p1.0, p2.0, p3.0);
+    ts.extend(roots(p0.1, p1.1, p2.1, p3.1));
+    for t in ts {
+        rect.add_point(calc(t, p0.0, p1.0, p2.0, p3.0), calc(t, p0.1, p1.1, p2.1, p3.1));
+    }
+}
+
+fn add_quad_extrema(rect: &mut Rect, p0: Point, p1: Point, p2: Point) {
+    let calc = |t: f64, a: f64, b: f64, c: f64| {
+        let mt = 1.0 - t;
+        mt * mt * a + 2.0 * mt * t * b + t * t * c
+    };
+
+    let root = |p0: f64, p1: f64, p2: f64| {
+        let d = p0 - 2.0 * p1 + p2;
+        if d.abs() < 1e-12 { None } else { Some((p0 - p1) / d) }
+    };
+
+    for t in root(p0.0, p1.0, p2.0).into_iter().chain(root(p0.1, p1.1, p2.1)) {
+        if t > 0.0 && t < 1.0 {
+            rect.add_point(calc(t, p0.0, p1.0, p2.0), calc(t, p0.1, p1.1, p2.1));
+        }
+    }
+}
+
+struct Arc {
+    rx: f64,
+    ry: f64,
+    x_axis_rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+}
+
+// Adds ellipse extrema, which are the part of the arc.
+//
+// https://www.w3.org/TR/SVG/implnote.html#ArcConversionEndpointToCenter
+fn add_arc_extrema(rect: &mut Rect, p1: Point, arc: &Arc, p2: Point) {
+    use std::f64::consts::PI;
+
+    let mut rx = arc.rx.abs();
+    let mut ry = arc.ry.abs();
+
+    // A straight line or an omitted arc.
+    if rx < 1e-12 || ry < 1e-12 || (p1.0 == p2.0 && p1.1 == p2.1) {
+        return;
+    }
+
+    let (sin, cos) = arc.x_axis_rotation.to_radians().sin_cos();
+
+    let dx2 = (p1.0 - p2.0) / 2.0;
+    let dy2 = (p1.1 - p2.1) / 2.0;
+    let x1p = cos * dx2 + sin * dy2;
+    let y1p = -sin * dx2 + cos * dy2;
+
+    // Scale up too small radii.
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+    let den = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let mut coef = (num / den).max(0.0).sqrt();
+    if arc.large_arc == arc.sweep {
+        coef = -coef;
+    }
+
+    let cxp = coef * rx * y1p / ry;
+    let cyp = -coef * ry * x1p / rx;
+    let cx = cos * cxp - sin * cyp + (p1.0 + p2.0) / 2.0;
+    let cy = sin * cxp + cos * cyp + (p1.1 + p2.1) / 2.0;
+
+    let theta1 = ((y1p - cyp) / ry).atan2((x1p - cxp) / rx);
+    let theta2 = ((-y1p - cyp) / ry).atan2((-x1p - cxp) / rx);
+    let mut dtheta = theta2 - theta1;
+    if arc.sweep && dtheta < 0.0 {
+        dtheta += 2.0 * PI;
+    } else if !arc.sweep && dtheta > 0.0 {
+        dtheta -= 2.0 * PI;
+    }
+
+    // Angles, where the derivative of x or y is zero.
+    let tx = (-ry * sin).atan2(rx * cos);
+    let ty = (ry * cos).atan2(rx * sin);
+    for t in &[tx, tx + PI, ty, ty + PI] {
+        let d = if dtheta >= 0.0 {
+            (t - theta1).rem_euclid(2.0 * PI)
+        } else {
+            (theta1 - t).rem_euclid(2.0 * PI)
+        };
+
+        if d <= dtheta.abs() {
+            let (t_sin, t_cos) = t.sin_cos();
+            rect.add_point(cx + rx * cos * t_cos - ry * sin * t_sin,
+                           cy + rx * sin * t_cos + ry * cos * t_sin);
+        }
+    }
+}
+
+pub fn calc_stroke_offset(node: &Node) -> Option<f64> {
+    let find_value = |aid: AId| {
+        node.parents_with_self()
+            .find(|n| n.has_attribute(aid))
+            .and_then(|n| n.attributes().get_value(aid).cloned())
+    };
+
+    match find_value(AId::Stroke) {
+        Some(AttributeValue::PredefValue(ValueId::None)) | None => return Some(0.0),
+        _ => {}
+    }
+
+    let width = match find_value(AId::StrokeWidth) {
+        Some(AttributeValue::Length(len)) => len,
+        None => Length::new_number(1.0),
+        _ => return None,
+    };
+
+    // We can't calculate a size with units.
+    if width.unit != Unit::None {
+        return None;
+    }
+
+    // Shapes without sharp corners or with right angles only.
+    if matches!(node.tag_id(), Some(EId::Rect) | Some(EId::Circle) | Some(EId::Ellipse)) {
+        return Some(width.num / 2.0);
+    }
+
+    // Square caps and miter joins can be bigger than a half of the stroke width.
+    let mut scale = 1.0;
+
+    match find_value(AId::StrokeLinejoin) {
+        Some(AttributeValue::PredefValue(ValueId::Round)) |
+        Some(AttributeValue::PredefValue(ValueId::Bevel)) => {}
+        _ => {
+            scale = match find_value(AId::StrokeMiterlimit) {
+                Some(AttributeValue::Number(n)) => n,
+                _ => 4.0,
+            };
+        }
+    }
+
+    if let Some(AttributeValue::PredefValue(ValueId::Square)) = find_value(AId::StrokeLinecap) {
+        scale = f64::max(scale, f64::consts::SQRT_2);
+    }
+
+    Some(width.num / 2.0 * scale)
+}
+
+// Returns a bounding box of the transformed rect.
+pub fn transform_rect(rect: &Rect, ts: &Transform) -> Rect {
+    let points = [
+        ts.apply(rect.x1, rect.y1),
+        ts.apply(rect.x2, rect.y1),
+        ts.apply(rect.x2, rect.y2),
+        ts.apply(rect.x1, rect.y2),
+    ];
+
+    let mut new_rect = Rect::new_empty();
+    for &(x, y) in &points {
+        new_rect.add_point(x, y);
+    }
+
+    new_rect
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::Document;
+
+    macro_rules! test {
+        ($name:ident, $func:ident, $in_text:expr, $rect:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                let node = doc.svg_element().unwrap().first_child().unwrap();
+                let rect = $func(&node).unwrap();
+                let (x1, y1, x2, y2) = $rect;
+                let is_eq = |a: f64, b: f64| (a - b).abs() < 1e-9;
+                assert!(is_eq(rect.x1, x1) && is_eq(rect.y1, y1)
+                        && is_eq(rect.x2, x2) && is_eq(rect.y2, y2),
+                        "{:?}", rect);
+            }
+        )
+    }
+
+    macro_rules! test_none {
+        ($name:ident, $func:ident, $in_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                let node = doc.svg_element().unwrap().first_child().unwrap();
+                assert!($func(&node).is_none());
+            }
+        )
+    }
+
+    test!(path_1, calc_bbox,
+        "<svg><path d='M 10 20 L 30 40'/></svg>",
+        (10.0, 20.0, 30.0, 40.0));
+
+    // Control points are outside the curve.
+    test!(path_curve_1, calc_bbox,
+        "<svg><path d='M 0 0 C 0 40 40 40 40 0'/></svg>",
+        (0.0, 0.0, 40.0, 30.0));
+
+    test!(path_quad_1, calc_bbox,
+        "<svg><path d='M 0 0 Q 20 40 40 0 T 80 0'/></svg>",
+        (0.0, -20.0, 80.0, 20.0));
+
+    test!(path_arc_1, calc_bbox,
+        "<svg><path d='M 0 0 A 10 10 0 0 1 20 0'/></svg>",
+        (0.0, -10.0, 20.0, 0.0));
+
+    test!(path_arc_2, calc_bbox,
+        "<svg><path d='M 0 0 A 10 10 0 1 0 10 10'/></svg>",
+        (-10.0, 0.0, 10.0, 20.0));
+
+    // Too small radii are scaled up.
+    test!(path_arc_3, calc_bbox,
+        "<svg><path d='M 0 0 A 1 1 0 0 0 20 0'/></svg>",
+        (0.0, 0.0, 20.0, 10.0));
+
+    test!(group_1, calc_bbox,
+        "<svg><g><rect width='10' height='10'/>\
+         <circle cx='30' cy='30' r='5' transform='translate(10)'/></g></svg>",
+        (0.0, 0.0, 45.0, 35.0));
+
+    test!(group_2, calc_bbox,
+        "<svg><g><defs><rect width='100' height='100'/></defs>\
+         <rect width='10' height='10'/><rect width='100' height='100' display='none'/></g></svg>",
+        (0.0, 0.0, 10.0, 10.0));
+
+    test_none!(group_rotate_1, calc_bbox,
+        "<svg><g><rect width='10' height='10' transform='rotate(45)'/></g></svg>");
+
+    test_none!(text_1, calc_bbox,
+        "<svg><text>Text</text></svg>");
+
+    test!(text_2, calc_approx_bbox,
+        "<svg><text x='10' y='20' font-size='10'>Text</text></svg>",
+        (10.0, 10.0, 34.0, 22.5));
+
+    test!(painted_1, calc_painted_bbox,
+        "<svg><g><rect width='10' height='10' stroke='#000000' stroke-width='2'/></g></svg>",
+        (-1.0, -1.0, 11.0, 11.0));
+
+    // Miter joins can be up to 'stroke-miterlimit' times bigger than a half of the stroke.
+    test!(painted_2, calc_painted_bbox,
+        "<svg><path d='M 0 0 L 10 0 L 0 1' stroke='#000000' stroke-width='2'/></svg>",
+        (-4.0, -4.0, 14.0, 5.0));
+
+    test_none!(painted_3, calc_painted_bbox,
+        "<svg><path d='M 10 20 L 30 40' marker-end='url(#m)'/><marker id='m'/></svg>");
+}
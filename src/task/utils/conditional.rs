@@ -0,0 +1,167 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    AttributeValue,
+    Node,
+};
+
+use task::short::AId;
+
+pub static CONDITIONAL_ATTRS: &[AId] = &[
+    AId::RequiredFeatures,
+    AId::RequiredExtensions,
+    AId::SystemLanguage,
+];
+
+// SVG 1.1 feature strings, which are supported by the modern browsers.
+//
+// SVG fonts are not supported by most of them, so '#Font' and '#BasicFont'
+// are not listed.
+//
+// Details: https://www.w3.org/TR/SVG11/feature.html
+static SUPPORTED_FEATURES: &[&str] = &[
+    "SVG",
+    "SVGDOM",
+    "SVG-static",
+    "SVGDOM-static",
+    "SVG-animation",
+    "SVGDOM-animation",
+    "SVG-dynamic",
+    "SVGDOM-dynamic",
+    "CoreAttribute",
+    "Structure",
+    "BasicStructure",
+    "ContainerAttribute",
+    "ConditionalProcessing",
+    "Image",
+    "Style",
+    "ViewportAttribute",
+    "Shape",
+    "Text",
+    "BasicText",
+    "PaintAttribute",
+    "BasicPaintAttribute",
+    "OpacityAttribute",
+    "GraphicsAttribute",
+    "BasicGraphicsAttribute",
+    "Marker",
+    "ColorProfile",
+    "Gradient",
+    "Pattern",
+    "Clip",
+    "BasicClip",
+    "Mask",
+    "Filter",
+    "BasicFilter",
+    "DocumentEventsAttribute",
+    "GraphicalEventsAttribute",
+    "AnimationEventsAttribute",
+    "Cursor",
+    "Hyperlinking",
+    "XlinkAttribute",
+    "ExternalResourcesRequired",
+    "View",
+    "Script",
+    "Animation",
+    "Extensibility",
+];
+
+static FEATURE_PREFIX: &str = "http://www.w3.org/TR/SVG11/feature#";
+
+// Namespaces, which can be used inside the 'foreignObject' element.
+static SUPPORTED_EXTENSIONS: &[&str] = &[
+    "http://www.w3.org/1999/xhtml",
+    "http://www.w3.org/1998/Math/MathML",
+];
+
+// Evaluates conditional processing attributes of the element
+// according to the SVG 1.1 rules.
+//
+// All the attributes should evaluate to 'true' and a missing attribute is 'true',
+// but an empty one is 'false'.
+//
+// Details: https://www.w3.org/TR/SVG11/struct.html#ConditionalProcessing
+pub fn eval_conditions(node: &Node, languages: &[String]) -> bool {
+    let attrs = node.attributes();
+    let get = |aid| match attrs.get_value(aid) {
+        Some(AttributeValue::String(s)) => Some(s.clone()),
+        _ => None,
+    };
+
+    if let Some(features) = get(AId::RequiredFeatures) {
+        if !eval_required_features(&features) {
+            return false;
+        }
+    }
+
+    if let Some(extensions) = get(AId::RequiredExtensions) {
+        if !eval_required_extensions(&extensions) {
+            return false;
+        }
+    }
+
+    if let Some(langs) = get(AId::SystemLanguage) {
+        // Unlike other attributes, only one of the languages should match.
+        let is_match = langs.split(',')
+                            .map(|s| s.trim())
+                            .any(|lang| is_language_match(lang, languages));
+        if !is_match {
+            return false;
+        }
+    }
+
+    true
+}
+
+pub fn eval_required_features(text: &str) -> bool {
+    eval_list(text, is_supported_feature)
+}
+
+pub fn eval_required_extensions(text: &str) -> bool {
+    eval_list(text, |s| SUPPORTED_EXTENSIONS.contains(&s))
+}
+
+// Checks that the list is not empty and all the items are supported.
+fn eval_list<F>(text: &str, is_supported: F) -> bool
+    where F: Fn(&str) -> bool
+{
+    let mut list = text.split_whitespace().peekable();
+    list.peek().is_some() && list.all(is_supported)
+}
+
+fn is_supported_feature(feature: &str) -> bool {
+    if !feature.starts_with(FEATURE_PREFIX) {
+        return false;
+    }
+
+    SUPPORTED_FEATURES.contains(&&feature[FEATURE_PREFIX.len()..])
+}
+
+// The language matches when one of the user languages is equal to it
+// or is a prefix of it followed by '-'. So 'en' matches 'en-US', but not vice versa.
+fn is_language_match(lang: &str, languages: &[String]) -> bool {
+    if lang.is_empty() {
+        return false;
+    }
+
+    let lang = lang.to_lowercase();
+    languages.iter().map(|l| l.to_lowercase()).any(|l| {
+        lang == l || (lang.starts_with(&l) && lang[l.len()..].starts_with('-'))
+    })
+}
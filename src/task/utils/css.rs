@@ -0,0 +1,234 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Minimal CSS helpers for rules, which are not resolved by the parser.
+
+use std::collections::HashMap;
+
+use svgdom::{
+    Document,
+    Node,
+    NodeType,
+};
+
+use task::short::EId;
+
+// Returns text nodes of 'style' elements.
+pub fn style_text_nodes(doc: &Document) -> Vec<Node> {
+    doc.descendants()
+       .filter(|n| n.is_tag_name(EId::Style))
+       .flat_map(|n| n.children())
+       .filter(|n| n.node_type() == NodeType::Text || n.node_type() == NodeType::Cdata)
+       .collect()
+}
+
+// Splits a stylesheet into top-level rules, including comments and whitespaces before them.
+//
+// Returns `None` on unbalanced blocks.
+pub fn split_rules(css: &str) -> Option<Vec<(usize, usize)>> {
+    let bytes = css.as_bytes();
+    let mut rules = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += css[i + 2..].find("*/")? + 4;
+                continue;
+            }
+            b'"' | b'\'' => {
+                let quote = bytes[i] as char;
+                i += css[i + 1..].find(quote)? + 2;
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                if depth == 0 {
+                    return None;
+                }
+
+                depth -= 1;
+                if depth == 0 {
+                    rules.push((start, i + 1));
+                    start = i + 1;
+                }
+            }
+            // At-rules without a block, like '@import'.
+            b';' if depth == 0 => {
+                rules.push((start, i + 1));
+                start = i + 1;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    if depth != 0 {
+        return None;
+    }
+
+    if start < css.len() {
+        rules.push((start, css.len()));
+    }
+
+    Some(rules)
+}
+
+// Returns selectors of a rule, including the nested ones in at-rules like '@media'.
+pub fn selectors(rule: &str) -> Vec<&str> {
+    let rule = rule.trim_start();
+    let block_start = match rule.find('{') {
+        Some(idx) => idx,
+        None => return Vec::new(),
+    };
+
+    if !rule.starts_with('@') {
+        return vec![&rule[..block_start]];
+    }
+
+    match nested_block(rule) {
+        Some(block) => match split_rules(block) {
+            Some(rules) => rules.iter().flat_map(|&(start, end)| selectors(&block[start..end])).collect(),
+            None => Vec::new(),
+        },
+        None => Vec::new(),
+    }
+}
+
+// Returns names, like '#id' or '.class', from a selector.
+pub fn selector_names(selector: &str, prefix: char) -> Vec<String> {
+    let mut names = Vec::new();
+    for (idx, _) in selector.match_indices(prefix) {
+        let name: String = selector[idx + 1..].chars().take_while(|c| is_ident_char(*c)).collect();
+        if !name.is_empty() {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+// Returns class names used by a stylesheet.
+pub fn class_names(css: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for (start, end) in split_rules(css).unwrap_or_default() {
+        for selector in selectors(&css[start..end]) {
+            names.extend(selector_names(selector, '.'));
+        }
+    }
+
+    names
+}
+
+// Renames classes in selectors of a stylesheet.
+pub fn rename_classes(css: &str, names: &HashMap<String, String>) -> String {
+    let rules = match split_rules(css) {
+        Some(rules) => rules,
+        None => return css.to_string(),
+    };
+
+    let mut new_css = String::with_capacity(css.len());
+    for (start, end) in rules {
+        let rule = &css[start..end];
+        let block_start = match rule.find('{') {
+            Some(idx) => idx,
+            None => {
+                new_css.push_str(rule);
+                continue;
+            }
+        };
+
+        if rule.trim_start().starts_with('@') {
+            match nested_block(rule) {
+                Some(block) => {
+                    new_css.push_str(&rule[..block_start + 1]);
+                    new_css.push_str(&rename_classes(block, names));
+                    new_css.push('}');
+                }
+                None => new_css.push_str(rule),
+            }
+        } else {
+            rename_selector(&rule[..block_start], names, &mut new_css);
+            new_css.push_str(&rule[block_start..]);
+        }
+    }
+
+    new_css
+}
+
+fn rename_selector(selector: &str, names: &HashMap<String, String>, out: &mut String) {
+    let mut s = selector;
+    while let Some(idx) = s.find('.') {
+        out.push_str(&s[..idx + 1]);
+        s = &s[idx + 1..];
+
+        let len = s.find(|c: char| !is_ident_char(c)).unwrap_or(s.len());
+        match names.get(&s[..len]) {
+            Some(new_name) => out.push_str(new_name),
+            None => out.push_str(&s[..len]),
+        }
+        s = &s[len..];
+    }
+    out.push_str(s);
+}
+
+// Returns the block content of conditional at-rules, which contain other rules.
+fn nested_block(rule: &str) -> Option<&str> {
+    let trimmed = rule.trim_start();
+    if !(trimmed.starts_with("@media") || trimmed.starts_with("@supports")) {
+        return None;
+    }
+
+    let start = rule.find('{')?;
+    let end = rule.rfind('}')?;
+    Some(&rule[start + 1..end])
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_rules_1() {
+        let css = "a{b:c} /* } */ @import 'x;y'; @media x { a{b:c} }";
+        let rules: Vec<&str> = split_rules(css).unwrap().iter().map(|&(s, e)| &css[s..e]).collect();
+        assert_eq!(rules, vec!["a{b:c}", " /* } */ @import 'x;y';", " @media x { a{b:c} }"]);
+    }
+
+    #[test]
+    fn class_names_1() {
+        assert_eq!(class_names(".a:hover, g > .b{fill:url(x.png)} @media print { .c.a{} } @font-face{src:url(x.woff)}"),
+                   vec!["a".to_string(), "b".to_string(), "c".to_string(), "a".to_string()]);
+    }
+
+    #[test]
+    fn rename_classes_1() {
+        let mut names = HashMap::new();
+        names.insert("icon".to_string(), "a".to_string());
+        names.insert("icon-big".to_string(), "b".to_string());
+
+        assert_eq!(rename_classes(".icon:hover, .icon-big{fill:url(x.icon)} @media print { .icon{} }", &names),
+                   ".a:hover, .b{fill:url(x.icon)} @media print { .a{} }");
+    }
+}
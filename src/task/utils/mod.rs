@@ -0,0 +1,372 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use regex::{
+    self,
+    Regex,
+};
+
+use svgdom::{
+    Attribute,
+    AttributeValue,
+    Document,
+    FuzzyEq,
+    Name,
+    Node,
+    NodeType,
+};
+
+use task::short::AId;
+
+pub use self::animation::AnimatedAttributes;
+pub use self::script::{
+    has_event_attrs,
+    ScriptRefs,
+};
+
+pub mod animation;
+pub mod base64;
+pub mod bbox;
+pub mod conditional;
+pub mod css;
+pub mod parallel;
+pub mod script;
+
+pub fn recalc_stroke(node: &mut Node, scale_factor: f64) {
+    recalc_stroke_num(node, AId::StrokeWidth, scale_factor);
+    recalc_stroke_dasharray(node, scale_factor);
+    recalc_stroke_num(node, AId::StrokeDashoffset, scale_factor);
+}
+
+fn recalc_stroke_num(node: &mut Node, aid: AId, scale_factor: f64) {
+    // Resolve current value.
+    let value = if let Some(attr) = node.attributes().get(aid).cloned() {
+        // Defined in the current node.
+        attr.value
+    } else {
+        if let Some(n) = node.parents().find(|n| n.has_attribute(aid)) {
+            // Defined in the parent node.
+            n.attributes().get_value(aid).cloned().unwrap()
+        } else {
+            // Default value.
+            AttributeValue::default_value(aid).unwrap()
+        }
+    };
+
+    if let AttributeValue::Length(mut len) = value {
+        if !len.num.is_fuzzy_zero() {
+            len.num *= scale_factor;
+            node.set_attribute((aid, len));
+        }
+    }
+}
+
+fn recalc_stroke_dasharray(node: &mut Node, scale_factor: f64) {
+    let aid = AId::StrokeDasharray;
+
+    // Resolve current 'stroke-dasharray'.
+    let value = if let Some(attr) = node.attributes().get(aid).cloned() {
+        // Defined in the current node.
+        Some(attr.value)
+    } else {
+        if let Some(n) = node.parents().find(|n| n.has_attribute(aid)) {
+            // Defined in the parent node.
+            Some(n.attributes().get_value(aid).cloned().unwrap())
+        } else {
+            None
+        }
+    };
+
+    if let Some(value) = value {
+        if let AttributeValue::LengthList(mut list) = value {
+            for n in &mut list {
+                n.num *= scale_factor
+            }
+            node.set_attribute((aid, list));
+        }
+    }
+}
+
+// Returns the first occurred attribute value, including inherited ones.
+pub fn find_attribute(node: &Node, aid: AId) -> Option<AttributeValue> {
+    node.parents_with_self()
+        .find(|n| n.has_attribute(aid))
+        .and_then(|n| n.attributes().get_value(aid).cloned())
+}
+
+pub fn remove_nodes(nodes: &mut Vec<Node>) {
+    for n in nodes.iter_mut() {
+        n.remove();
+    }
+    nodes.clear();
+}
+
+// Checks that the name matches a simple glob pattern with '*' and '?' wildcards.
+pub fn matches_pattern(name: &str, pattern: &str) -> bool {
+    fn matches(name: &[char], pattern: &[char]) -> bool {
+        match pattern.first() {
+            None => name.is_empty(),
+            Some(&'*') => (0..name.len() + 1).any(|i| matches(&name[i..], &pattern[1..])),
+            Some(&'?') => !name.is_empty() && matches(&name[1..], &pattern[1..]),
+            Some(c) => name.first() == Some(c) && matches(&name[1..], &pattern[1..]),
+        }
+    }
+
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    matches(&name, &pattern)
+}
+
+// Checks that the node id matches one of the '--keep-ids' regular expressions.
+//
+// Such nodes should not be removed and their ids should not be changed.
+pub fn is_kept_id(node: &Node, keep_ids: &[String]) -> bool {
+    !keep_ids.is_empty() && node.has_id() && {
+        let id = node.id();
+        keep_ids.iter().any(|p| matches_regex(&id, p))
+    }
+}
+
+// Checks that the whole name matches the regular expression.
+//
+// Invalid expressions never match. They are reported by the CLI,
+// so here we are checking only the ones set via the library API.
+pub fn matches_regex(name: &str, pattern: &str) -> bool {
+    thread_local! {
+        // Tasks are checking the same patterns over and over.
+        static CACHE: RefCell<HashMap<String, Option<Regex>>> = RefCell::new(HashMap::new());
+    }
+
+    // Most of the patterns are plain ids.
+    if !pattern.chars().any(is_regex_meta) {
+        return name == pattern;
+    }
+
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if !cache.contains_key(pattern) {
+            cache.insert(pattern.to_string(), new_id_regex(pattern).ok());
+        }
+
+        cache[pattern].as_ref().map_or(false, |re| re.is_match(name))
+    })
+}
+
+/// Compiles an id pattern, which must match the whole id.
+pub fn new_id_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{})$", pattern))
+}
+
+fn is_regex_meta(c: char) -> bool {
+    matches!(c, '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$')
+}
+
+// Checks that the attribute is an accessibility one: 'role', 'tabindex' or 'aria-*'.
+//
+// Such attributes are non-SVG ones, so they are present only when the parser preserves them,
+// but when they are, cleaning tasks should never remove them.
+pub fn is_accessibility_attr(attr: &Attribute) -> bool {
+    match attr.name {
+        Name::Name(ref name) => name == "role" || name == "tabindex" || name.starts_with("aria-"),
+        Name::Id(_) => false,
+    }
+}
+
+pub fn has_accessibility_attrs(node: &Node) -> bool {
+    node.attributes().iter().any(is_accessibility_attr)
+}
+
+// Returns '--keep-ids' patterns extended with ids referenced by accessibility attributes,
+// like 'aria-labelledby', by animations and by scripts, which are not tracked as links.
+//
+// Referenced ids are escaped, so they can be matched as patterns.
+pub fn kept_ids(doc: &Document, keep_ids: &[String]) -> Vec<String> {
+    let mut ids = keep_ids.to_vec();
+    ids.extend(animation::referenced_ids(doc).iter().map(|id| regex::escape(id)));
+    ids.extend(ScriptRefs::new(doc).ids.iter().map(|id| regex::escape(id)));
+
+    for node in doc.descendants() {
+        for attr in node.attributes().iter().filter(|a| is_accessibility_attr(a)) {
+            if let Name::Name(ref name) = attr.name {
+                if is_id_list_attr(name) {
+                    for id in attr.value.to_string().split_whitespace() {
+                        ids.push(regex::escape(id));
+                    }
+                }
+            }
+        }
+    }
+
+    ids
+}
+
+fn is_id_list_attr(name: &str) -> bool {
+    matches!(name, "aria-activedescendant" | "aria-controls" | "aria-describedby" | "aria-details"
+                 | "aria-errormessage" | "aria-flowto" | "aria-labelledby" | "aria-owns")
+}
+
+// Compares all attributes of the elements.
+pub fn is_equal_attrs(node1: &Node, node2: &Node) -> bool {
+    let attrs1 = node1.attributes();
+    let attrs2 = node2.attributes();
+
+    attrs1.len() == attrs2.len() && attrs1.iter().all(|a1| attrs2.iter().any(|a2| a1 == a2))
+}
+
+// Compares children of the elements recursively.
+pub fn is_equal_content(node1: &Node, node2: &Node) -> bool {
+    if node1.children().count() != node2.children().count() {
+        return false;
+    }
+
+    node1.children().zip(node2.children()).all(|(c1, c2)| {
+           c1.node_type() == c2.node_type()
+        && c1.tag_name().map(|n| n.clone()) == c2.tag_name().map(|n| n.clone())
+        && *c1.text() == *c2.text()
+        && is_equal_attrs(&c1, &c2)
+        && is_equal_content(&c1, &c2)
+    })
+}
+
+// Collects namespaces declared using 'xmlns:*' attributes.
+//
+// Returns a prefix to URI map.
+pub fn declared_namespaces(doc: &Document) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    for node in doc.descendants().filter(|n| n.node_type() == NodeType::Element) {
+        for attr in node.attributes().iter() {
+            if let Name::Name(ref name) = attr.name {
+                if let Some(prefix) = name.strip_prefix("xmlns:") {
+                    map.insert(prefix.to_string(), attr.value.to_string());
+                }
+            }
+        }
+    }
+
+    map
+}
+
+// Splits a prefixed name like 'inkscape:label' into a prefix and a local name.
+pub fn split_prefix(name: &str) -> Option<(&str, &str)> {
+    name.find(':').map(|idx| (&name[..idx], &name[idx + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use task::short::EId;
+    use svgdom::{Document, ToStringWithOptions};
+    use task::utils;
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_str($in_text).unwrap();
+                let mut node = doc.descendants().find(|n| n.is_tag_name(EId::Path)).unwrap();
+                utils::recalc_stroke(&mut node, 2.0);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    // From default.
+    test!(recalc_stroke_1,
+"<svg>
+    <path/>
+</svg>",
+"<svg>
+    <path stroke-width='2'/>
+</svg>
+");
+
+    // From current.
+    test!(recalc_stroke_2,
+"<svg>
+    <path stroke-width='2'/>
+</svg>",
+"<svg>
+    <path stroke-width='4'/>
+</svg>
+");
+
+    // From parent.
+    test!(recalc_stroke_3,
+"<svg stroke-width='2'>
+    <path />
+</svg>",
+"<svg stroke-width='2'>
+    <path stroke-width='4'/>
+</svg>
+");
+
+    test!(recalc_stroke_4,
+"<svg>
+    <path stroke-dasharray='5 2 5 5 2 5'/>
+</svg>",
+"<svg>
+    <path stroke-dasharray='10 4 10 10 4 10' stroke-width='2'/>
+</svg>
+");
+
+    test!(recalc_stroke_5,
+"<svg>
+    <path stroke-dashoffset='2'/>
+</svg>",
+"<svg>
+    <path stroke-dashoffset='4' stroke-width='2'/>
+</svg>
+");
+
+    test!(recalc_stroke_6,
+"<svg>
+    <path stroke-dasharray='5 2 5 5 2 5' stroke-dashoffset='2'/>
+</svg>",
+"<svg>
+    <path stroke-dasharray='10 4 10 10 4 10' stroke-dashoffset='4' stroke-width='2'/>
+</svg>
+");
+
+    #[test]
+    fn kept_ids_1() {
+        let doc = Document::from_str(
+"<svg role='img' aria-labelledby='title1 desc1'>
+    <title id='title1'/>
+    <desc id='desc1'/>
+    <rect id='rect1' aria-label='rect2'/>
+</svg>").unwrap();
+
+        assert_eq!(utils::kept_ids(&doc, &["g.*".to_string()]),
+                   vec!["g.*".to_string(), "title1".to_string(), "desc1".to_string()]);
+    }
+
+    #[test]
+    fn matches_regex_1() {
+        assert!(utils::matches_regex("icon-1", "icon-.*"));
+        assert!(utils::matches_regex("icon-1", "icon-[0-9]+|logo"));
+        assert!(utils::matches_regex("logo", "icon-[0-9]+|logo"));
+        assert!(utils::matches_regex("a.b", "a.b"));
+        assert!(utils::matches_regex("a.b", "a\\.b"));
+        assert!(!utils::matches_regex("axb", "a\\.b"));
+        assert!(!utils::matches_regex("my-icon-1", "icon-.*"));
+        assert!(!utils::matches_regex("icon-1", "icon-"));
+        assert!(!utils::matches_regex("icon-1", "icon-("));
+    }
+}
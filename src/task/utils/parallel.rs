@@ -0,0 +1,65 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use std::thread;
+
+// Spawning threads is not free, so small documents are processed sequentially.
+const MIN_ITEMS_PER_JOB: usize = 32;
+
+// Processes items using the specified number of threads.
+//
+// The DOM is not thread-safe, so items must be detached from it first.
+// Items are split into contiguous chunks, which usually correspond to subtrees
+// of the document, and each chunk is processed by a separate thread.
+pub fn for_each<T, F>(items: &mut [T], jobs: usize, f: F)
+    where T: Send, F: Fn(&mut T) + Sync
+{
+    let jobs = jobs.min(items.len() / MIN_ITEMS_PER_JOB);
+    if jobs <= 1 {
+        items.iter_mut().for_each(f);
+        return;
+    }
+
+    let chunk_size = items.len().div_ceil(jobs);
+    let f = &f;
+    thread::scope(|s| {
+        for chunk in items.chunks_mut(chunk_size) {
+            s.spawn(move || chunk.iter_mut().for_each(f));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn for_each_1() {
+        let mut items: Vec<usize> = (0..1000).collect();
+        for_each(&mut items, 4, |n| *n *= 2);
+        assert_eq!(items, (0..1000).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn for_each_2() {
+        // Not enough items for a second thread.
+        let mut items = vec![1, 2, 3];
+        for_each(&mut items, 4, |n| *n += 1);
+        assert_eq!(items, vec![2, 3, 4]);
+    }
+}
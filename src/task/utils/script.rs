@@ -0,0 +1,167 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use svgdom::{
+    Attribute,
+    AttributeType,
+    AttributeValue,
+    Document,
+    Node,
+};
+
+use task::short::EId;
+
+pub fn is_event_attr(attr: &Attribute) -> bool {
+    attr.is_graphical_event() || attr.is_document_event() || attr.is_animation_event()
+}
+
+pub fn has_event_attrs(node: &Node) -> bool {
+    node.attributes().iter().any(is_event_attr)
+}
+
+pub fn has_scripts(doc: &Document) -> bool {
+    doc.descendants().any(|n| n.is_tag_name(EId::Script) || has_event_attrs(&n))
+}
+
+// Names referenced by scripts and event attributes.
+//
+// Scripts are not parsed, so this is a best-effort guess based on string literals:
+// a literal like 'rect1' can be an id or a class name, '#rect1' is an id selector
+// and '.cls' is a class selector. False positives only prevent some optimizations.
+pub struct ScriptRefs {
+    pub ids: Vec<String>,
+    pub classes: Vec<String>,
+}
+
+impl ScriptRefs {
+    pub fn new(doc: &Document) -> ScriptRefs {
+        let mut refs = ScriptRefs {
+            ids: Vec::new(),
+            classes: Vec::new(),
+        };
+
+        for node in doc.descendants() {
+            if node.is_tag_name(EId::Script) {
+                for child in node.children() {
+                    refs.process_code(&child.text());
+                }
+            }
+
+            for attr in node.attributes().iter().filter(|a| is_event_attr(a)) {
+                if let AttributeValue::String(ref code) = attr.value {
+                    refs.process_code(code);
+                }
+            }
+        }
+
+        refs
+    }
+
+    fn process_code(&mut self, code: &str) {
+        for literal in string_literals(code) {
+            if is_name(literal) {
+                self.ids.push(literal.to_string());
+                self.classes.push(literal.to_string());
+                continue;
+            }
+
+            // Selectors, like '#rect1 > .cls'.
+            for (idx, c) in literal.char_indices() {
+                if c != '#' && c != '.' {
+                    continue;
+                }
+
+                let start = idx + 1;
+                let len = literal[start..].find(|c| !is_name_char(c))
+                                          .unwrap_or(literal.len() - start);
+                if len == 0 {
+                    continue;
+                }
+
+                let name = literal[start..start + len].to_string();
+                if c == '#' {
+                    self.ids.push(name);
+                } else {
+                    self.classes.push(name);
+                }
+            }
+        }
+    }
+}
+
+// Returns the content of all the string literals in a JavaScript code.
+fn string_literals(code: &str) -> Vec<&str> {
+    let mut list = Vec::new();
+
+    let mut iter = code.char_indices();
+    while let Some((start, quote)) = iter.next() {
+        if quote != '\'' && quote != '"' && quote != '`' {
+            continue;
+        }
+
+        let mut is_escaped = false;
+        for (idx, c) in iter.by_ref() {
+            if is_escaped {
+                is_escaped = false;
+            } else if c == '\\' {
+                is_escaped = true;
+            } else if c == quote {
+                list.push(&code[start + 1..idx]);
+                break;
+            }
+        }
+    }
+
+    list
+}
+
+fn is_name(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(is_name_char)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::Document;
+
+    #[test]
+    fn script_refs_1() {
+        let doc = Document::from_str(
+"<svg>
+    <script><![CDATA[
+        document.getElementById('rect1').classList.add(\"active\");
+        document.querySelector(`#g1 > .item`);
+        var s = 'a b';
+    ]]></script>
+    <rect id='rect1' onclick='toggle(\"rect2\")'/>
+</svg>").unwrap();
+
+        let refs = ScriptRefs::new(&doc);
+        assert_eq!(refs.ids, vec!["rect1", "active", "g1", "rect2"]);
+        assert_eq!(refs.classes, vec!["rect1", "active", "item", "rect2"]);
+    }
+
+    #[test]
+    fn string_literals_1() {
+        assert_eq!(string_literals(r#"f('a\'b', "c") + `d`"#), vec![r"a\'b", "c", "d"]);
+    }
+}
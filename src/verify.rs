@@ -0,0 +1,179 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+// Rendering verification of cleaned files.
+//
+// Files are rendered using 'resvg', which is linked only with the 'verify' feature,
+// so svgcleaner doesn't depend on a rendering library by default.
+
+use std::io;
+#[cfg(feature = "verify")]
+use std::path::Path;
+#[cfg(feature = "verify")]
+use std::sync::{
+    Arc,
+    OnceLock,
+};
+
+#[cfg(feature = "verify")]
+use resvg::{
+    tiny_skia,
+    usvg,
+};
+
+// The maximum difference of a color channel, which is still treated as the same color.
+// Cleaned files usually have rounded coordinates, which affects anti-aliasing a bit.
+const CHANNEL_TOLERANCE: u8 = 4;
+
+/// A raster image with non-premultiplied RGBA8 pixels.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Image {
+    /// Image width in pixels.
+    pub width: u32,
+    /// Image height in pixels.
+    pub height: u32,
+    /// Pixels data. Contains `width * height * 4` bytes.
+    pub data: Vec<u8>,
+}
+
+/// Checks that svgcleaner was built with the `verify` feature.
+pub fn is_renderer_available() -> bool {
+    cfg!(feature = "verify")
+}
+
+/// Renders SVG data using `resvg`.
+///
+/// Relative references are resolved using `resources_dir`, when set.
+#[cfg(feature = "verify")]
+pub fn render(data: &[u8], resources_dir: Option<&str>) -> Result<Image, io::Error> {
+    // Loading system fonts is slow, so it's done only once.
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+
+    let fontdb = FONTS.get_or_init(|| {
+        let mut db = usvg::fontdb::Database::new();
+        db.load_system_fonts();
+        Arc::new(db)
+    });
+
+    let opt = usvg::Options {
+        resources_dir: resources_dir.map(|dir| Path::new(dir).to_path_buf()),
+        fontdb: fontdb.clone(),
+        ..usvg::Options::default()
+    };
+
+    let tree = usvg::Tree::from_data(data, &opt)
+        .map_err(|e| invalid_data(&format!("failed to render: {}", e)))?;
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| invalid_data("failed to render: invalid image size"))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    let mut data = Vec::with_capacity(pixmap.pixels().len() * 4);
+    for p in pixmap.pixels() {
+        let c = p.demultiply();
+        data.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+    }
+
+    Ok(Image { width: size.width(), height: size.height(), data })
+}
+
+/// Always fails, since svgcleaner was built without the `verify` feature.
+#[cfg(not(feature = "verify"))]
+pub fn render(_data: &[u8], _resources_dir: Option<&str>) -> Result<Image, io::Error> {
+    Err(io::Error::other("svgcleaner was built without the 'verify' feature"))
+}
+
+/// Returns a percentage of pixels, which are different in two images.
+///
+/// Images with different sizes are treated as completely different.
+pub fn compare(img1: &Image, img2: &Image) -> f64 {
+    if img1.width != img2.width || img1.height != img2.height {
+        return 100.0;
+    }
+
+    let count = (img1.width * img1.height) as usize;
+    if count == 0 {
+        return 0.0;
+    }
+
+    let diff = img1.data.chunks(4).zip(img2.data.chunks(4))
+        .filter(|&(p1, p2)| !is_same_pixel(p1, p2))
+        .count();
+
+    diff as f64 / count as f64 * 100.0
+}
+
+fn is_same_pixel(p1: &[u8], p2: &[u8]) -> bool {
+    // Colors of fully transparent pixels are not important.
+    if p1[3] == 0 && p2[3] == 0 {
+        return true;
+    }
+
+    p1.iter().zip(p2).all(|(&c1, &c2)| {
+        (c1 as i16 - c2 as i16).abs() <= CHANNEL_TOLERANCE as i16
+    })
+}
+
+#[cfg(feature = "verify")]
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "verify")]
+    fn render_1() {
+        let svg = b"<svg xmlns='http://www.w3.org/2000/svg' width='2' height='1'>\
+                    <rect width='1' height='1' fill='#ff0000'/></svg>";
+        let img = render(svg, None).unwrap();
+        assert_eq!(img, Image { width: 2, height: 1, data: vec![255, 0, 0, 255, 0, 0, 0, 0] });
+    }
+
+    #[test]
+    #[cfg(feature = "verify")]
+    fn render_2() {
+        assert!(render(b"<svg", None).is_err());
+    }
+
+    #[test]
+    fn compare_1() {
+        let img1 = Image { width: 2, height: 1, data: vec![255, 0, 0, 255, 0, 0, 0, 0] };
+        let mut img2 = img1.clone();
+        assert_eq!(compare(&img1, &img2), 0.0);
+
+        // Anti-aliasing differences are ignored.
+        img2.data[0] = 253;
+        // Colors of transparent pixels are ignored.
+        img2.data[4] = 100;
+        assert_eq!(compare(&img1, &img2), 0.0);
+
+        img2.data[3] = 0;
+        assert_eq!(compare(&img1, &img2), 50.0);
+    }
+
+    #[test]
+    fn compare_2() {
+        let img1 = Image { width: 1, height: 1, data: vec![0, 0, 0, 255] };
+        let img2 = Image { width: 1, height: 2, data: vec![0, 0, 0, 255, 0, 0, 0, 255] };
+        assert_eq!(compare(&img1, &img2), 100.0);
+    }
+}
@@ -0,0 +1,201 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! Warnings, which are reported during cleaning.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::mem;
+
+use recover::{
+    Recovery,
+    RecoveryKind,
+};
+
+/// A machine-readable warning code.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum WarningCode {
+    /// An unescaped `&` or `<` was escaped.
+    UnescapedCharacter,
+    /// An HTML entity was replaced with a character reference.
+    HtmlEntity,
+    /// An attribute value without quotes was quoted.
+    UnquotedAttribute,
+    /// A duplicated attribute was removed.
+    DuplicatedAttribute,
+    /// An element without a closing tag was closed.
+    UnclosedElement,
+    /// A closing tag without an opening one was removed.
+    UnexpectedClosingTag,
+    /// An attribute with an invalid value was removed.
+    InvalidAttribute,
+    /// A task was skipped, because it can't process the document safely.
+    SkippedTask,
+    /// A reference to an external file, which doesn't exist.
+    BrokenReference,
+    /// A gradient `stop` element without an `offset` attribute.
+    MissingOffset,
+    /// A warning from the parser, like an unresolved reference or an unsupported construct.
+    Parser,
+}
+
+impl WarningCode {
+    /// Returns the code name, like `unclosed-element`.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            WarningCode::UnescapedCharacter => "unescaped-character",
+            WarningCode::HtmlEntity => "html-entity",
+            WarningCode::UnquotedAttribute => "unquoted-attribute",
+            WarningCode::DuplicatedAttribute => "duplicated-attribute",
+            WarningCode::UnclosedElement => "unclosed-element",
+            WarningCode::UnexpectedClosingTag => "unexpected-closing-tag",
+            WarningCode::InvalidAttribute => "invalid-attribute",
+            WarningCode::SkippedTask => "skipped-task",
+            WarningCode::BrokenReference => "broken-reference",
+            WarningCode::MissingOffset => "missing-offset",
+            WarningCode::Parser => "parser",
+        }
+    }
+}
+
+impl fmt::Display for WarningCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A warning.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Warning {
+    /// A warning code.
+    pub code: WarningCode,
+    /// A human-readable message.
+    pub message: String,
+    /// A position in the input text. The line and the column are counted from 1.
+    pub pos: Option<(usize, usize)>,
+}
+
+impl Warning {
+    /// Creates a new warning without a position.
+    pub fn new<T: Into<String>>(code: WarningCode, message: T) -> Warning {
+        Warning {
+            code,
+            message: message.into(),
+            pos: None,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.pos {
+            Some((line, column)) => write!(f, "{} at {}:{}", self.message, line, column),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl From<Recovery> for Warning {
+    fn from(value: Recovery) -> Warning {
+        let code = match value.kind {
+            RecoveryKind::StrayAmpersand | RecoveryKind::StrayLessThan => {
+                WarningCode::UnescapedCharacter
+            }
+            RecoveryKind::HtmlEntity(_) => WarningCode::HtmlEntity,
+            RecoveryKind::UnquotedAttribute(_) => WarningCode::UnquotedAttribute,
+            RecoveryKind::DuplicatedAttribute(_) => WarningCode::DuplicatedAttribute,
+            RecoveryKind::UnclosedElement(_) => WarningCode::UnclosedElement,
+            RecoveryKind::UnexpectedClosingTag(_) => WarningCode::UnexpectedClosingTag,
+            RecoveryKind::InvalidAttribute(_) => WarningCode::InvalidAttribute,
+        };
+
+        Warning {
+            code,
+            message: value.kind.to_string(),
+            pos: value.pos,
+        }
+    }
+}
+
+thread_local! {
+    static WARNINGS: RefCell<Option<Vec<Warning>>> = const { RefCell::new(None) };
+}
+
+/// Collects warnings, which are added on the current thread while the function is running.
+pub fn collect<T, F: FnOnce() -> T>(f: F) -> (T, Vec<Warning>) {
+    let prev = WARNINGS.with(|w| w.borrow_mut().replace(Vec::new()));
+    let res = f();
+    let warnings = WARNINGS.with(|w| mem::replace(&mut *w.borrow_mut(), prev));
+    (res, warnings.unwrap_or_default())
+}
+
+/// Adds a warning to the collected ones.
+///
+/// Returns the warning back, when warnings are not collected on the current thread.
+pub fn try_add(warning: Warning) -> Result<(), Warning> {
+    WARNINGS.with(|w| match *w.borrow_mut() {
+        Some(ref mut list) => {
+            list.push(warning);
+            Ok(())
+        }
+        None => Err(warning),
+    })
+}
+
+/// Adds a warning to the collected ones or logs it, when warnings are not collected.
+pub fn add(warning: Warning) {
+    if let Err(warning) = try_add(warning) {
+        warn!("{}.", warning);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_1() {
+        let (n, warnings) = collect(|| {
+            add(Warning::new(WarningCode::SkippedTask, "first"));
+
+            // Nested warnings are not visible to the outer collector.
+            let (_, nested) = collect(|| add(Warning::new(WarningCode::Parser, "nested")));
+            assert_eq!(nested.len(), 1);
+
+            add(Warning::new(WarningCode::SkippedTask, "second"));
+            42
+        });
+
+        assert_eq!(n, 42);
+        assert_eq!(warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+                   vec!["first", "second"]);
+
+        assert!(try_add(Warning::new(WarningCode::Parser, "lost")).is_err());
+    }
+
+    #[test]
+    fn from_recovery_1() {
+        let w = Warning::from(Recovery {
+            pos: Some((2, 5)),
+            kind: RecoveryKind::UnclosedElement("g".to_string()),
+        });
+
+        assert_eq!(w.code.as_str(), "unclosed-element");
+        assert_eq!(w.to_string(), "unclosed element 'g' at 2:5");
+    }
+}
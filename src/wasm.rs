@@ -0,0 +1,38 @@
+// svgcleaner could help you to clean up your SVG files
+// from unnecessary data.
+// Copyright (C) 2012-2018 Evgeniy Reizner
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! JavaScript bindings.
+
+use wasm_bindgen::prelude::*;
+
+use cleaner::Cleaner;
+
+/// Cleans an SVG string.
+///
+/// `options_json` contains options with the same names and values as in the CLI,
+/// like `{"remove-title": false, "indent": 2}`. An empty string means the recommended options.
+///
+/// Throws an error message on invalid options or input.
+#[wasm_bindgen]
+pub fn clean(input: &str, options_json: &str) -> Result<String, JsValue> {
+    let options_json = if options_json.trim().is_empty() { "{}" } else { options_json };
+
+    Cleaner::from_json(options_json)
+        .and_then(|cleaner| cleaner.clean_str(input))
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}